@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use async_trait::async_trait;
+use fc_db::BadBlock;
 use fp_consensus::{ensure_log, FindLogError};
 use fp_rpc::EthereumRuntimeRPCApi;
 use sc_client_api::{self, backend::AuxStore, BlockOf};
@@ -94,6 +95,29 @@ where
 			_marker: PhantomData,
 		}
 	}
+
+	/// Keeps the rejected block and the reason in the bad blocks database.
+	fn record_bad_block(
+		&self,
+		header: B::Header,
+		body: Option<Vec<B::Extrinsic>>,
+		error: &ConsensusError,
+	) {
+		let hash = header.hash();
+		let bad_block = BadBlock {
+			header,
+			body,
+			reason: error.to_string(),
+		};
+		if let Err(err) = self.backend.bad_blocks().write(bad_block) {
+			log::warn!(
+				target: "frontier-consensus",
+				"Failed to record the bad block {:?}: {}",
+				hash,
+				err,
+			);
+		}
+	}
 }
 
 #[async_trait]
@@ -124,11 +148,34 @@ where
 		// We validate that there are only one frontier log. No other
 		// actions are needed and mapping syncing is delegated to a separate
 		// worker.
-		ensure_log(&block.header.digest()).map_err(|e| Error::from(e))?;
+		if let Err(e) = ensure_log(&block.header.digest()) {
+			let error = Error::from(e).into();
+			self.record_bad_block(block.header, block.body, &error);
+			return Err(error);
+		}
 
-		self.inner
+		// The inner import consumes the body, only the header of a block failing it is kept.
+		let header = block.header.clone();
+		let result: Result<ImportResult, Self::Error> = self
+			.inner
 			.import_block(block, new_cache)
 			.await
-			.map_err(Into::into)
+			.map_err(Into::into);
+
+		if let Err(error) = &result {
+			if is_bad_block_error(error) {
+				self.record_bad_block(header, None, error);
+			}
+		}
+		result
 	}
 }
+
+/// Whether an import error rejects the block itself, failing its verification or execution, and
+/// not a node side failure such as an unavailable state or a lookup error.
+fn is_bad_block_error(error: &ConsensusError) -> bool {
+	matches!(
+		error,
+		ConsensusError::ClientImport(_) | ConsensusError::InvalidJustification
+	)
+}
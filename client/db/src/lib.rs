@@ -16,16 +16,26 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+mod sql;
+mod token_transfers;
 mod utils;
 
 pub use sp_database::Database;
+pub use sql::{
+	ContractCreation, ContractCreationEntry, InternalTransfer, InternalTransferEntry,
+	LogIndexCommitment, LogIndexDb, LogIndexEntry, LogIndexQuery, LogIndexStatus, TokenTransfer,
+	TokenTransferQuery, INDEXED_TOPICS,
+};
+pub use token_transfers::{
+	decode_token_transfers, TokenStandard, TokenTransferEntry, TransferLogPosition,
+};
 
 use codec::{Decode, Encode};
 use fp_storage::PALLET_ETHEREUM_SCHEMA_CACHE;
 use pallet_ethereum::EthereumStorageSchema;
 use parking_lot::Mutex;
 use sp_core::H256;
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 use std::{
 	marker::PhantomData,
 	path::{Path, PathBuf},
@@ -40,6 +50,8 @@ pub type DbHash = [u8; DB_HASH_LEN];
 pub struct DatabaseSettings {
 	/// Where to find the database.
 	pub source: DatabaseSettingsSrc,
+	/// Path of the SQL log index, None to run without it.
+	pub log_index: Option<PathBuf>,
 }
 
 /// Where to find the database.
@@ -64,21 +76,36 @@ impl DatabaseSettingsSrc {
 }
 
 pub(crate) mod columns {
-	pub const NUM_COLUMNS: u32 = 4;
+	pub const NUM_COLUMNS: u32 = 5;
 
 	pub const META: u32 = 0;
 	pub const BLOCK_MAPPING: u32 = 1;
 	pub const TRANSACTION_MAPPING: u32 = 2;
 	pub const SYNCED_MAPPING: u32 = 3;
+	pub const LOCAL_TRANSACTIONS: u32 = 4;
 }
 
 pub(crate) mod static_keys {
 	pub const CURRENT_SYNCING_TIPS: &[u8] = b"CURRENT_SYNCING_TIPS";
+	pub const LOCAL_TRANSACTIONS_HEAD: &[u8] = b"LOCAL_TRANSACTIONS_HEAD";
+	pub const BAD_BLOCKS: &[u8] = b"BAD_BLOCKS";
+	pub const TRANSACTION_INDEX_TAIL: &[u8] = b"TRANSACTION_INDEX_TAIL";
 }
 
+/// Maximum number of bad blocks kept, the oldest are dropped first.
+pub const BAD_BLOCKS_LIMIT: usize = 10;
+
+/// Maximum number of transactions in the local transactions journal, the oldest are dropped
+/// first.
+pub const LOCAL_TRANSACTIONS_LIMIT: u64 = 4096;
+
 pub struct Backend<Block: BlockT> {
+	source: DatabaseSettingsSrc,
 	meta: Arc<MetaDb<Block>>,
 	mapping: Arc<MappingDb<Block>>,
+	local_transactions: Arc<LocalTransactionsDb>,
+	bad_blocks: Arc<BadBlocksDb<Block>>,
+	log_index: Option<Arc<LogIndexDb<Block>>>,
 }
 
 impl<Block: BlockT> Backend<Block> {
@@ -86,6 +113,7 @@ impl<Block: BlockT> Backend<Block> {
 		let db = utils::open_database(config)?;
 
 		Ok(Self {
+			source: config.source.clone(),
 			mapping: Arc::new(MappingDb {
 				db: db.clone(),
 				write_lock: Arc::new(Mutex::new(())),
@@ -95,9 +123,26 @@ impl<Block: BlockT> Backend<Block> {
 				db: db.clone(),
 				_marker: PhantomData,
 			}),
+			local_transactions: Arc::new(LocalTransactionsDb {
+				db: db.clone(),
+				write_lock: Arc::new(Mutex::new(())),
+			}),
+			bad_blocks: Arc::new(BadBlocksDb {
+				db: db.clone(),
+				write_lock: Arc::new(Mutex::new(())),
+				_marker: PhantomData,
+			}),
+			log_index: match &config.log_index {
+				Some(path) => Some(Arc::new(LogIndexDb::open(path)?)),
+				None => None,
+			},
 		})
 	}
 
+	pub fn source(&self) -> &DatabaseSettingsSrc {
+		&self.source
+	}
+
 	pub fn mapping(&self) -> &Arc<MappingDb<Block>> {
 		&self.mapping
 	}
@@ -105,6 +150,19 @@ impl<Block: BlockT> Backend<Block> {
 	pub fn meta(&self) -> &Arc<MetaDb<Block>> {
 		&self.meta
 	}
+
+	pub fn local_transactions(&self) -> &Arc<LocalTransactionsDb> {
+		&self.local_transactions
+	}
+
+	pub fn bad_blocks(&self) -> &Arc<BadBlocksDb<Block>> {
+		&self.bad_blocks
+	}
+
+	/// SQL log index, None when disabled.
+	pub fn log_index(&self) -> Option<&Arc<LogIndexDb<Block>>> {
+		self.log_index.as_ref()
+	}
 }
 
 pub struct MetaDb<Block: BlockT> {
@@ -141,6 +199,36 @@ impl<Block: BlockT> MetaDb<Block> {
 		Ok(())
 	}
 
+	/// Number of the oldest block whose transactions are indexed, None until the mapping sync
+	/// has indexed the transaction history it retains.
+	pub fn transaction_index_tail(&self) -> Result<Option<u64>, String> {
+		match self.db.get(
+			crate::columns::META,
+			&crate::static_keys::TRANSACTION_INDEX_TAIL,
+		) {
+			Some(raw) => Ok(Some(
+				u64::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?,
+			)),
+			None => Ok(None),
+		}
+	}
+
+	pub fn write_transaction_index_tail(&self, tail: u64) -> Result<(), String> {
+		let mut transaction = sp_database::Transaction::new();
+
+		transaction.set(
+			crate::columns::META,
+			crate::static_keys::TRANSACTION_INDEX_TAIL,
+			&tail.encode(),
+		);
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
 	pub fn ethereum_schema(&self) -> Result<Option<Vec<(EthereumStorageSchema, H256)>>, String> {
 		match self
 			.db
@@ -288,4 +376,325 @@ impl<Block: BlockT> MappingDb<Block> {
 
 		Ok(())
 	}
+
+	/// Removes from the transaction mapping the transactions of a block, leaving the block
+	/// mapping in place.
+	pub fn remove_transaction_hashes(
+		&self,
+		block_hash: Block::Hash,
+		ethereum_transaction_hashes: Vec<H256>,
+	) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut transaction = sp_database::Transaction::new();
+		self.remove_transaction_metadata(
+			&mut transaction,
+			block_hash,
+			ethereum_transaction_hashes,
+		)?;
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	/// Removes the whole mapping of a block no longer in the chain, which is then unsynced.
+	pub fn remove_block(&self, commitment: MappingCommitment<Block>) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut transaction = sp_database::Transaction::new();
+
+		if self.block_hash(&commitment.ethereum_block_hash)? == Some(commitment.block_hash) {
+			transaction.remove(
+				crate::columns::BLOCK_MAPPING,
+				&commitment.ethereum_block_hash.encode(),
+			);
+		}
+		self.remove_transaction_metadata(
+			&mut transaction,
+			commitment.block_hash,
+			commitment.ethereum_transaction_hashes,
+		)?;
+		transaction.remove(
+			crate::columns::SYNCED_MAPPING,
+			&commitment.block_hash.encode(),
+		);
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	fn remove_transaction_metadata(
+		&self,
+		transaction: &mut sp_database::Transaction<DbHash>,
+		block_hash: Block::Hash,
+		ethereum_transaction_hashes: Vec<H256>,
+	) -> Result<(), String> {
+		for ethereum_transaction_hash in ethereum_transaction_hashes {
+			let mut metadata = self.transaction_metadata(&ethereum_transaction_hash)?;
+			metadata.retain(|metadata| metadata.block_hash != block_hash);
+			if metadata.is_empty() {
+				transaction.remove(
+					crate::columns::TRANSACTION_MAPPING,
+					&ethereum_transaction_hash.encode(),
+				);
+			} else {
+				transaction.set(
+					crate::columns::TRANSACTION_MAPPING,
+					&ethereum_transaction_hash.encode(),
+					&metadata.encode(),
+				);
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Journal of the locally submitted Ethereum transactions, by transaction hash.
+///
+/// Each transaction is stored under its hash and takes a slot of a ring of
+/// `LOCAL_TRANSACTIONS_LIMIT` slots, the hashes being listed from the ring. The slot of the
+/// oldest transaction is reused once the ring is full.
+pub struct LocalTransactionsDb {
+	db: Arc<dyn Database<DbHash>>,
+	write_lock: Arc<Mutex<()>>,
+}
+
+/// Journaled transaction, with the slot of its hash.
+#[derive(Encode, Decode)]
+struct LocalTransaction {
+	slot: u64,
+	transaction: Vec<u8>,
+}
+
+impl LocalTransactionsDb {
+	fn slot_key(slot: u64) -> Vec<u8> {
+		(b"slot", slot).encode()
+	}
+
+	/// Number of transactions ever written, the next one taking the slot `head % limit`.
+	fn head(&self) -> Result<u64, String> {
+		match self.db.get(
+			crate::columns::META,
+			&crate::static_keys::LOCAL_TRANSACTIONS_HEAD,
+		) {
+			Some(raw) => Ok(u64::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?),
+			None => Ok(0),
+		}
+	}
+
+	fn entry(&self, transaction_hash: &H256) -> Result<Option<LocalTransaction>, String> {
+		match self.db.get(
+			crate::columns::LOCAL_TRANSACTIONS,
+			&transaction_hash.encode(),
+		) {
+			Some(raw) => Ok(Some(
+				LocalTransaction::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?,
+			)),
+			None => Ok(None),
+		}
+	}
+
+	/// Hashes of the journaled transactions, the oldest first.
+	pub fn hashes(&self) -> Result<Vec<H256>, String> {
+		let head = self.head()?;
+		let mut hashes = Vec::new();
+		for index in head.saturating_sub(LOCAL_TRANSACTIONS_LIMIT)..head {
+			let slot = index % LOCAL_TRANSACTIONS_LIMIT;
+			if let Some(raw) = self
+				.db
+				.get(crate::columns::LOCAL_TRANSACTIONS, &Self::slot_key(slot))
+			{
+				hashes.push(H256::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?);
+			}
+		}
+		Ok(hashes)
+	}
+
+	/// The rlp encoded transaction.
+	pub fn transaction(&self, transaction_hash: &H256) -> Result<Option<Vec<u8>>, String> {
+		Ok(self.entry(transaction_hash)?.map(|entry| entry.transaction))
+	}
+
+	pub fn write(&self, transaction_hash: H256, transaction: Vec<u8>) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		if self.entry(&transaction_hash)?.is_some() {
+			return Ok(());
+		}
+		let head = self.head()?;
+		let slot = head % LOCAL_TRANSACTIONS_LIMIT;
+		let slot_key = Self::slot_key(slot);
+
+		let mut transaction_db = sp_database::Transaction::new();
+
+		// The ring is full, the oldest transaction is dropped.
+		if let Some(raw) = self.db.get(crate::columns::LOCAL_TRANSACTIONS, &slot_key) {
+			transaction_db.remove(crate::columns::LOCAL_TRANSACTIONS, &raw);
+		}
+		transaction_db.set(
+			crate::columns::LOCAL_TRANSACTIONS,
+			&transaction_hash.encode(),
+			&LocalTransaction { slot, transaction }.encode(),
+		);
+		transaction_db.set(
+			crate::columns::LOCAL_TRANSACTIONS,
+			&slot_key,
+			&transaction_hash.encode(),
+		);
+		transaction_db.set(
+			crate::columns::META,
+			crate::static_keys::LOCAL_TRANSACTIONS_HEAD,
+			&(head + 1).encode(),
+		);
+
+		self.db
+			.commit(transaction_db)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	pub fn remove(&self, transaction_hash: &H256) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let entry = match self.entry(transaction_hash)? {
+			Some(entry) => entry,
+			None => return Ok(()),
+		};
+
+		let mut transaction_db = sp_database::Transaction::new();
+
+		transaction_db.remove(
+			crate::columns::LOCAL_TRANSACTIONS,
+			&transaction_hash.encode(),
+		);
+		transaction_db.remove(
+			crate::columns::LOCAL_TRANSACTIONS,
+			&Self::slot_key(entry.slot),
+		);
+
+		self.db
+			.commit(transaction_db)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+}
+
+/// Block rejected on import.
+#[derive(Clone, Encode, Decode)]
+pub struct BadBlock<Block: BlockT> {
+	/// Header of the block.
+	pub header: Block::Header,
+	/// Extrinsics of the block, None if the import was not given the body or the block was
+	/// rejected on execution.
+	pub body: Option<Vec<Block::Extrinsic>>,
+	/// Reason of the rejection.
+	pub reason: String,
+}
+
+/// The latest blocks rejected on import, at most `BAD_BLOCKS_LIMIT`.
+pub struct BadBlocksDb<Block: BlockT> {
+	db: Arc<dyn Database<DbHash>>,
+	write_lock: Arc<Mutex<()>>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block: BlockT> BadBlocksDb<Block> {
+	pub fn bad_blocks(&self) -> Result<Vec<BadBlock<Block>>, String> {
+		match self
+			.db
+			.get(crate::columns::META, &crate::static_keys::BAD_BLOCKS)
+		{
+			Some(raw) => Ok(
+				Vec::<BadBlock<Block>>::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?
+			),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	pub fn write(&self, bad_block: BadBlock<Block>) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut bad_blocks = self.bad_blocks()?;
+		bad_blocks.retain(|known| known.header.hash() != bad_block.header.hash());
+		bad_blocks.push(bad_block);
+		if bad_blocks.len() > BAD_BLOCKS_LIMIT {
+			bad_blocks.drain(..bad_blocks.len() - BAD_BLOCKS_LIMIT);
+		}
+
+		let mut transaction = sp_database::Transaction::new();
+
+		transaction.set(
+			crate::columns::META,
+			crate::static_keys::BAD_BLOCKS,
+			&bad_blocks.encode(),
+		);
+
+		self.db
+			.commit(transaction)
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn local_transactions_db() -> LocalTransactionsDb {
+		LocalTransactionsDb {
+			db: Arc::new(sp_database::MemDb::default()),
+			write_lock: Arc::new(Mutex::new(())),
+		}
+	}
+
+	#[test]
+	fn local_transactions_are_kept_by_hash() {
+		let journal = local_transactions_db();
+		let first = H256::repeat_byte(1);
+		let second = H256::repeat_byte(2);
+
+		journal.write(first, vec![1]).unwrap();
+		journal.write(second, vec![2]).unwrap();
+		journal.write(first, vec![3]).unwrap();
+		assert_eq!(journal.hashes().unwrap(), vec![first, second]);
+		assert_eq!(journal.transaction(&first).unwrap(), Some(vec![1]));
+
+		journal.remove(&first).unwrap();
+		assert_eq!(journal.hashes().unwrap(), vec![second]);
+		assert_eq!(journal.transaction(&first).unwrap(), None);
+	}
+
+	#[test]
+	fn local_transactions_drop_the_oldest_when_full() {
+		let journal = local_transactions_db();
+		for index in 0..=LOCAL_TRANSACTIONS_LIMIT {
+			journal
+				.write(H256::from_low_u64_be(index), index.encode())
+				.unwrap();
+		}
+
+		let hashes = journal.hashes().unwrap();
+		assert_eq!(hashes.len() as u64, LOCAL_TRANSACTIONS_LIMIT);
+		assert_eq!(hashes[0], H256::from_low_u64_be(1));
+		assert_eq!(
+			journal.transaction(&H256::from_low_u64_be(0)).unwrap(),
+			None
+		);
+		assert_eq!(
+			journal
+				.transaction(&H256::from_low_u64_be(LOCAL_TRANSACTIONS_LIMIT))
+				.unwrap(),
+			Some(LOCAL_TRANSACTIONS_LIMIT.encode())
+		);
+	}
 }
@@ -0,0 +1,1231 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! SQL backend: an SQLite index of the Ethereum logs by address and topics.
+//!
+//! Each indexed block has a row in `blocks`, and each of its logs a row in `logs` with the
+//! address and the first four topics, so the blocks holding the logs matching a filter are
+//! found without loading the blocks. The logs themselves are still read from the blocks.
+//! The `meta` table holds the checkpoint of the log indexer worker, and `backfilled_batches` the
+//! ranges of blocks indexed by a backfill. The token transfers decoded from the logs have a row
+//! in `token_transfers`, by sender, recipient and token, and the contracts created by the
+//! transactions one in `contract_creations`, by address. The value transfers below the top level
+//! calls of the transactions, recorded by the runtime, have a row in `internal_transfers`, by
+//! sender and recipient.
+//!
+//! The blocks retracted from the best chain and their logs are kept with their `canonical` flag
+//! cleared rather than deleted, and flagged again if enacted back. Writing a block clears the
+//! flag of the other blocks of its number, so at most one block per number is canonical. The
+//! queries only consider the canonical rows.
+
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+use rusqlite::{
+	params, params_from_iter, types::Value, Connection, OptionalExtension, Transaction,
+};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::Block as BlockT;
+use std::{marker::PhantomData, path::Path};
+
+use crate::token_transfers::{TokenStandard, TokenTransferEntry};
+
+/// Number of topics of a log indexed.
+pub const INDEXED_TOPICS: usize = 4;
+
+/// Key of the checkpoint of the log indexer worker in the `meta` table.
+const LOG_INDEXER_CHECKPOINT: &str = "log_indexer_checkpoint";
+
+/// Version of the schema, in the `user_version` of the database. Versions 2 to 4 add the
+/// `token_transfers`, `contract_creations` and `internal_transfers` tables, empty for the blocks
+/// indexed before.
+const SCHEMA_VERSION: i64 = 4;
+
+/// Tables of the rows of the indexed blocks, replaced when a block is indexed again.
+const BLOCK_ROW_TABLES: &[&str] = &[
+	"logs",
+	"token_transfers",
+	"contract_creations",
+	"internal_transfers",
+];
+
+/// Adds the `canonical` flags to the indexes of version 0, all their blocks being canonical.
+const MIGRATION_1: &str = "
+	ALTER TABLE blocks ADD COLUMN canonical INTEGER NOT NULL DEFAULT 1;
+	ALTER TABLE logs ADD COLUMN canonical INTEGER NOT NULL DEFAULT 1;
+";
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS blocks (
+		substrate_hash BLOB NOT NULL PRIMARY KEY,
+		ethereum_hash BLOB NOT NULL,
+		block_number INTEGER NOT NULL,
+		canonical INTEGER NOT NULL DEFAULT 1
+	);
+	CREATE INDEX IF NOT EXISTS blocks_block_number ON blocks (block_number);
+	CREATE TABLE IF NOT EXISTS logs (
+		substrate_hash BLOB NOT NULL,
+		block_number INTEGER NOT NULL,
+		transaction_index INTEGER NOT NULL,
+		log_index INTEGER NOT NULL,
+		transaction_log_index INTEGER NOT NULL,
+		address BLOB NOT NULL,
+		topic0 BLOB,
+		topic1 BLOB,
+		topic2 BLOB,
+		topic3 BLOB,
+		canonical INTEGER NOT NULL DEFAULT 1,
+		PRIMARY KEY (substrate_hash, log_index)
+	);
+	CREATE INDEX IF NOT EXISTS logs_address_topic0 ON logs (address, topic0, block_number);
+	CREATE INDEX IF NOT EXISTS logs_topic0 ON logs (topic0, block_number);
+	CREATE INDEX IF NOT EXISTS logs_block_number ON logs (block_number);
+	CREATE TABLE IF NOT EXISTS meta (
+		key TEXT NOT NULL PRIMARY KEY,
+		value INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS backfilled_batches (
+		from_block INTEGER NOT NULL,
+		to_block INTEGER NOT NULL,
+		PRIMARY KEY (from_block, to_block)
+	);
+	CREATE TABLE IF NOT EXISTS token_transfers (
+		substrate_hash BLOB NOT NULL,
+		block_number INTEGER NOT NULL,
+		log_index INTEGER NOT NULL,
+		batch_index INTEGER NOT NULL,
+		transaction_hash BLOB NOT NULL,
+		transaction_index INTEGER NOT NULL,
+		token BLOB NOT NULL,
+		standard INTEGER NOT NULL,
+		sender BLOB NOT NULL,
+		recipient BLOB NOT NULL,
+		token_id BLOB,
+		value BLOB NOT NULL,
+		canonical INTEGER NOT NULL DEFAULT 1,
+		PRIMARY KEY (substrate_hash, log_index, batch_index)
+	);
+	CREATE INDEX IF NOT EXISTS token_transfers_sender ON token_transfers (sender, block_number);
+	CREATE INDEX IF NOT EXISTS token_transfers_recipient
+		ON token_transfers (recipient, block_number);
+	CREATE INDEX IF NOT EXISTS token_transfers_token ON token_transfers (token, block_number);
+	CREATE INDEX IF NOT EXISTS token_transfers_block_number ON token_transfers (block_number);
+	CREATE TABLE IF NOT EXISTS contract_creations (
+		substrate_hash BLOB NOT NULL,
+		block_number INTEGER NOT NULL,
+		transaction_index INTEGER NOT NULL,
+		transaction_hash BLOB NOT NULL,
+		address BLOB NOT NULL,
+		creator BLOB NOT NULL,
+		init_code_hash BLOB NOT NULL,
+		canonical INTEGER NOT NULL DEFAULT 1,
+		PRIMARY KEY (substrate_hash, transaction_index)
+	);
+	CREATE INDEX IF NOT EXISTS contract_creations_address
+		ON contract_creations (address, block_number);
+	CREATE INDEX IF NOT EXISTS contract_creations_block_number
+		ON contract_creations (block_number);
+	CREATE TABLE IF NOT EXISTS internal_transfers (
+		substrate_hash BLOB NOT NULL,
+		block_number INTEGER NOT NULL,
+		transaction_index INTEGER NOT NULL,
+		transfer_index INTEGER NOT NULL,
+		transaction_hash BLOB NOT NULL,
+		sender BLOB NOT NULL,
+		recipient BLOB NOT NULL,
+		value BLOB NOT NULL,
+		canonical INTEGER NOT NULL DEFAULT 1,
+		PRIMARY KEY (substrate_hash, transaction_index, transfer_index)
+	);
+	CREATE INDEX IF NOT EXISTS internal_transfers_sender
+		ON internal_transfers (sender, block_number);
+	CREATE INDEX IF NOT EXISTS internal_transfers_recipient
+		ON internal_transfers (recipient, block_number);
+	CREATE INDEX IF NOT EXISTS internal_transfers_block_number
+		ON internal_transfers (block_number);
+";
+
+/// Log of an indexed block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogIndexEntry {
+	/// Index of the transaction emitting the log in the block.
+	pub transaction_index: u32,
+	/// Index of the log in the block.
+	pub log_index: u32,
+	/// Index of the log in the logs of its transaction, locating its data in the receipt.
+	pub transaction_log_index: u32,
+	/// Address of the emitting contract.
+	pub address: H160,
+	/// Topics of the log, only the first `INDEXED_TOPICS` are indexed.
+	pub topics: Vec<H256>,
+}
+
+/// Logs of a block to index.
+#[derive(Clone, Debug)]
+pub struct LogIndexCommitment<Block: BlockT> {
+	pub block_hash: Block::Hash,
+	pub ethereum_block_hash: H256,
+	pub block_number: u64,
+	pub logs: Vec<LogIndexEntry>,
+	/// Token transfers decoded from the logs.
+	pub token_transfers: Vec<TokenTransferEntry>,
+	/// Contracts created by the transactions.
+	pub contract_creations: Vec<ContractCreationEntry>,
+	/// Value transfers below the top level calls of the transactions.
+	pub internal_transfers: Vec<InternalTransferEntry>,
+}
+
+/// Contract created by a transaction of an indexed block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractCreationEntry {
+	/// Address of the contract.
+	pub address: H160,
+	/// Hash of the creating transaction.
+	pub transaction_hash: H256,
+	/// Index of the creating transaction in the block.
+	pub transaction_index: u32,
+	/// Sender of the creating transaction.
+	pub creator: H160,
+	/// Keccak hash of the init code.
+	pub init_code_hash: H256,
+}
+
+/// Indexed contract creation of a canonical block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractCreation {
+	pub block_number: u64,
+	pub ethereum_block_hash: H256,
+	pub creation: ContractCreationEntry,
+}
+
+/// Value transfer below the top level call of a transaction of an indexed block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InternalTransferEntry {
+	/// Hash of the transaction.
+	pub transaction_hash: H256,
+	/// Index of the transaction in the block.
+	pub transaction_index: u32,
+	/// Index of the transfer in the transfers of its transaction, in execution order.
+	pub transfer_index: u32,
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+}
+
+/// Indexed internal transfer of a canonical block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InternalTransfer {
+	pub block_number: u64,
+	pub ethereum_block_hash: H256,
+	pub transfer: InternalTransferEntry,
+}
+
+/// Condition on the indexed logs: the address is one of `addresses`, and each topic one of the
+/// hashes at its position in `topics`. An empty list matches any value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LogIndexQuery {
+	pub addresses: Vec<H160>,
+	pub topics: Vec<Vec<H256>>,
+}
+
+/// Condition on the indexed token transfers: `address` is the sender or the recipient, and the
+/// token one of `tokens`. None and an empty list match any value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TokenTransferQuery {
+	pub address: Option<H160>,
+	pub tokens: Vec<H160>,
+}
+
+/// Indexed token transfer of a canonical block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenTransfer {
+	pub block_number: u64,
+	pub ethereum_block_hash: H256,
+	pub transfer: TokenTransferEntry,
+}
+
+/// Blocks covered by the log index.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LogIndexStatus {
+	/// Number of indexed blocks, forks included.
+	pub indexed_blocks: u64,
+	/// Number of indexed blocks retracted from the best chain.
+	pub orphaned_blocks: u64,
+	/// Lowest indexed block number.
+	pub lowest_block: Option<u64>,
+	/// Highest indexed block number.
+	pub highest_block: Option<u64>,
+}
+
+pub struct LogIndexDb<Block: BlockT> {
+	connection: Mutex<Connection>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block: BlockT> LogIndexDb<Block> {
+	/// Opens the index at `path`, creating it if needed.
+	pub fn open(path: &Path) -> Result<Self, String> {
+		Self::with_connection(Connection::open(path).map_err(|e| format!("{:?}", e))?)
+	}
+
+	/// Migrates and creates the schema of the index opened by `connection`.
+	fn with_connection(connection: Connection) -> Result<Self, String> {
+		connection
+			.execute_batch("PRAGMA journal_mode = WAL;")
+			.map_err(|e| format!("{:?}", e))?;
+		let version = connection
+			.query_row("PRAGMA user_version", params![], |row| row.get::<_, i64>(0))
+			.map_err(|e| format!("{:?}", e))?;
+		if version > SCHEMA_VERSION {
+			return Err(format!(
+				"Log index schema version {} is newer than the supported {}",
+				version, SCHEMA_VERSION
+			));
+		}
+		let has_blocks = connection
+			.query_row(
+				"SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'blocks'",
+				params![],
+				|_| Ok(()),
+			)
+			.optional()
+			.map_err(|e| format!("{:?}", e))?
+			.is_some();
+		if version == 0 && has_blocks {
+			connection
+				.execute_batch(MIGRATION_1)
+				.map_err(|e| format!("{:?}", e))?;
+		}
+		connection
+			.execute_batch(SCHEMA)
+			.map_err(|e| format!("{:?}", e))?;
+		connection
+			.execute_batch(&format!("PRAGMA user_version = {};", SCHEMA_VERSION))
+			.map_err(|e| format!("{:?}", e))?;
+
+		Ok(Self {
+			connection: Mutex::new(connection),
+			_marker: PhantomData,
+		})
+	}
+
+	/// Whether the logs of the block `block_hash` are indexed.
+	pub fn is_indexed(&self, block_hash: &Block::Hash) -> Result<bool, String> {
+		self.connection
+			.lock()
+			.query_row(
+				"SELECT 1 FROM blocks WHERE substrate_hash = ?1",
+				params![block_hash.encode()],
+				|_| Ok(()),
+			)
+			.optional()
+			.map(|row| row.is_some())
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// Next block number the log indexer worker indexes, None before its first run.
+	pub fn checkpoint(&self) -> Result<Option<u64>, String> {
+		self.connection
+			.lock()
+			.query_row(
+				"SELECT value FROM meta WHERE key = ?1",
+				params![LOG_INDEXER_CHECKPOINT],
+				|row| row.get::<_, i64>(0),
+			)
+			.optional()
+			.map(|value| value.map(|value| value as u64))
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// In a single transaction, clears the canonical flag of the `retracted` blocks, sets the
+	/// one of the `enacted` blocks, indexes the logs of the canonical blocks of `commitments`,
+	/// replacing the ones indexed before, and moves the checkpoint of the log indexer worker to
+	/// `checkpoint` if any.
+	pub fn write_batch(
+		&self,
+		retracted: &[Block::Hash],
+		enacted: &[Block::Hash],
+		commitments: Vec<LogIndexCommitment<Block>>,
+		checkpoint: Option<u64>,
+	) -> Result<(), String> {
+		let mut connection = self.connection.lock();
+		let transaction = connection.transaction().map_err(|e| format!("{:?}", e))?;
+		for hash in retracted {
+			set_canonical::<Block>(&transaction, hash, false).map_err(|e| format!("{:?}", e))?;
+		}
+		for hash in enacted {
+			set_canonical::<Block>(&transaction, hash, true).map_err(|e| format!("{:?}", e))?;
+		}
+		for commitment in commitments {
+			insert_block(&transaction, commitment).map_err(|e| format!("{:?}", e))?;
+		}
+		if let Some(checkpoint) = checkpoint {
+			transaction
+				.execute(
+					"INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+					params![LOG_INDEXER_CHECKPOINT, checkpoint as i64],
+				)
+				.map_err(|e| format!("{:?}", e))?;
+		}
+		transaction.commit().map_err(|e| format!("{:?}", e))
+	}
+
+	/// Whether a backfill indexed the blocks `from` to `to` as a batch.
+	pub fn is_backfilled(&self, from: u64, to: u64) -> Result<bool, String> {
+		self.connection
+			.lock()
+			.query_row(
+				"SELECT 1 FROM backfilled_batches WHERE from_block = ?1 AND to_block = ?2",
+				params![from as i64, to as i64],
+				|_| Ok(()),
+			)
+			.optional()
+			.map(|row| row.is_some())
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// Indexes the logs of the blocks of a backfill batch `from` to `to` in a single
+	/// transaction, recording the batch as done.
+	pub fn write_backfill_batch(
+		&self,
+		commitments: Vec<LogIndexCommitment<Block>>,
+		from: u64,
+		to: u64,
+	) -> Result<(), String> {
+		let mut connection = self.connection.lock();
+		let transaction = connection.transaction().map_err(|e| format!("{:?}", e))?;
+		for commitment in commitments {
+			insert_block(&transaction, commitment).map_err(|e| format!("{:?}", e))?;
+		}
+		transaction
+			.execute(
+				"INSERT OR REPLACE INTO backfilled_batches (from_block, to_block) VALUES (?1, ?2)",
+				params![from as i64, to as i64],
+			)
+			.map_err(|e| format!("{:?}", e))?;
+		transaction.commit().map_err(|e| format!("{:?}", e))
+	}
+
+	/// Indexed canonical blocks numbered `from` to `to`.
+	pub fn indexed_blocks(&self, from: u64, to: u64) -> Result<Vec<Block::Hash>, String> {
+		self.block_hashes(
+			"SELECT substrate_hash FROM blocks WHERE block_number BETWEEN ?1 AND ?2
+				AND canonical = 1",
+			vec![Value::Integer(from as i64), Value::Integer(to as i64)],
+		)
+	}
+
+	/// Indexed canonical blocks numbered `from` to `to` holding logs matching `query`.
+	pub fn matching_blocks(
+		&self,
+		query: &LogIndexQuery,
+		from: u64,
+		to: u64,
+	) -> Result<Vec<Block::Hash>, String> {
+		let mut sql =
+			"SELECT DISTINCT substrate_hash FROM logs WHERE block_number BETWEEN ?1 AND ?2
+				AND canonical = 1"
+				.to_string();
+		let mut values = vec![Value::Integer(from as i64), Value::Integer(to as i64)];
+
+		let mut condition = |column: &str, hashes: Vec<Vec<u8>>| {
+			if hashes.is_empty() {
+				return;
+			}
+			let placeholders = (0..hashes.len())
+				.map(|i| format!("?{}", values.len() + i + 1))
+				.collect::<Vec<_>>()
+				.join(", ");
+			sql.push_str(&format!(" AND {} IN ({})", column, placeholders));
+			values.extend(hashes.into_iter().map(Value::Blob));
+		};
+		condition(
+			"address",
+			query
+				.addresses
+				.iter()
+				.map(|address| address.as_bytes().to_vec())
+				.collect(),
+		);
+		for (index, topics) in query.topics.iter().take(INDEXED_TOPICS).enumerate() {
+			condition(
+				&format!("topic{}", index),
+				topics
+					.iter()
+					.map(|topic| topic.as_bytes().to_vec())
+					.collect(),
+			);
+		}
+
+		self.block_hashes(&sql, values)
+	}
+
+	/// Canonical token transfers of the blocks numbered `from` to `to` matching `query`, in
+	/// chain order from the position `start` (block number, log index, batch index) included,
+	/// at most `limit` of them.
+	pub fn token_transfers(
+		&self,
+		query: &TokenTransferQuery,
+		from: u64,
+		to: u64,
+		start: Option<(u64, u32, u32)>,
+		limit: usize,
+	) -> Result<Vec<TokenTransfer>, String> {
+		let mut sql = "SELECT t.block_number, b.ethereum_hash, t.transaction_hash,
+				t.transaction_index, t.log_index, t.batch_index, t.token, t.standard, t.sender,
+				t.recipient, t.token_id, t.value
+			FROM token_transfers t JOIN blocks b ON b.substrate_hash = t.substrate_hash
+			WHERE t.block_number BETWEEN ?1 AND ?2 AND t.canonical = 1"
+			.to_string();
+		let mut values = vec![Value::Integer(from as i64), Value::Integer(to as i64)];
+		if let Some(address) = query.address {
+			sql.push_str(&format!(
+				" AND (t.sender = ?{0} OR t.recipient = ?{0})",
+				values.len() + 1
+			));
+			values.push(Value::Blob(address.as_bytes().to_vec()));
+		}
+		if !query.tokens.is_empty() {
+			let placeholders = (0..query.tokens.len())
+				.map(|i| format!("?{}", values.len() + i + 1))
+				.collect::<Vec<_>>()
+				.join(", ");
+			sql.push_str(&format!(" AND t.token IN ({})", placeholders));
+			values.extend(
+				query
+					.tokens
+					.iter()
+					.map(|token| Value::Blob(token.as_bytes().to_vec())),
+			);
+		}
+		if let Some((block_number, log_index, batch_index)) = start {
+			sql.push_str(&format!(
+				" AND (t.block_number, t.log_index, t.batch_index) >= (?{}, ?{}, ?{})",
+				values.len() + 1,
+				values.len() + 2,
+				values.len() + 3
+			));
+			values.extend(vec![
+				Value::Integer(block_number as i64),
+				Value::Integer(log_index as i64),
+				Value::Integer(batch_index as i64),
+			]);
+		}
+		sql.push_str(&format!(
+			" ORDER BY t.block_number, t.log_index, t.batch_index LIMIT ?{}",
+			values.len() + 1
+		));
+		values.push(Value::Integer(limit as i64));
+
+		let connection = self.connection.lock();
+		let mut statement = connection.prepare(&sql).map_err(|e| format!("{:?}", e))?;
+		let rows = statement
+			.query_map(params_from_iter(values.iter()), |row| {
+				Ok((
+					row.get::<_, i64>(0)?,
+					row.get::<_, Vec<u8>>(1)?,
+					row.get::<_, Vec<u8>>(2)?,
+					row.get::<_, u32>(3)?,
+					row.get::<_, u32>(4)?,
+					row.get::<_, u32>(5)?,
+					row.get::<_, Vec<u8>>(6)?,
+					row.get::<_, u32>(7)?,
+					row.get::<_, Vec<u8>>(8)?,
+					row.get::<_, Vec<u8>>(9)?,
+					row.get::<_, Option<Vec<u8>>>(10)?,
+					row.get::<_, Vec<u8>>(11)?,
+				))
+			})
+			.map_err(|e| format!("{:?}", e))?;
+
+		let mut transfers = Vec::new();
+		for row in rows {
+			let (
+				block_number,
+				ethereum_block_hash,
+				transaction_hash,
+				transaction_index,
+				log_index,
+				batch_index,
+				token,
+				standard,
+				sender,
+				recipient,
+				token_id,
+				value,
+			) = row.map_err(|e| format!("{:?}", e))?;
+			let standard = TokenStandard::from_number(standard)
+				.ok_or_else(|| format!("Unknown token standard {}", standard))?;
+			transfers.push(TokenTransfer {
+				block_number: block_number as u64,
+				ethereum_block_hash: H256::from_slice(&ethereum_block_hash),
+				transfer: TokenTransferEntry {
+					transaction_hash: H256::from_slice(&transaction_hash),
+					transaction_index,
+					log_index,
+					batch_index,
+					token: H160::from_slice(&token),
+					standard,
+					from: H160::from_slice(&sender),
+					to: H160::from_slice(&recipient),
+					token_id: token_id.map(|token_id| U256::from_big_endian(&token_id)),
+					value: U256::from_big_endian(&value),
+				},
+			});
+		}
+		Ok(transfers)
+	}
+
+	/// Latest canonical creation of the contract `address`.
+	pub fn contract_creation(&self, address: H160) -> Result<Option<ContractCreation>, String> {
+		self.connection
+			.lock()
+			.query_row(
+				"SELECT c.block_number, b.ethereum_hash, c.transaction_hash, c.transaction_index,
+					c.creator, c.init_code_hash
+				FROM contract_creations c JOIN blocks b ON b.substrate_hash = c.substrate_hash
+				WHERE c.address = ?1 AND c.canonical = 1
+				ORDER BY c.block_number DESC LIMIT 1",
+				params![address.as_bytes()],
+				|row| {
+					Ok(ContractCreation {
+						block_number: row.get::<_, i64>(0)? as u64,
+						ethereum_block_hash: H256::from_slice(&row.get::<_, Vec<u8>>(1)?),
+						creation: ContractCreationEntry {
+							address,
+							transaction_hash: H256::from_slice(&row.get::<_, Vec<u8>>(2)?),
+							transaction_index: row.get(3)?,
+							creator: H160::from_slice(&row.get::<_, Vec<u8>>(4)?),
+							init_code_hash: H256::from_slice(&row.get::<_, Vec<u8>>(5)?),
+						},
+					})
+				},
+			)
+			.optional()
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// Canonical internal transfers of the blocks numbered `from` to `to`, sent or received by
+	/// `address` if any, in chain order, at most `limit` of them.
+	pub fn internal_transfers(
+		&self,
+		address: Option<H160>,
+		from: u64,
+		to: u64,
+		limit: usize,
+	) -> Result<Vec<InternalTransfer>, String> {
+		let mut sql = "SELECT i.block_number, b.ethereum_hash, i.transaction_hash,
+				i.transaction_index, i.transfer_index, i.sender, i.recipient, i.value
+			FROM internal_transfers i JOIN blocks b ON b.substrate_hash = i.substrate_hash
+			WHERE i.block_number BETWEEN ?1 AND ?2 AND i.canonical = 1"
+			.to_string();
+		let mut values = vec![Value::Integer(from as i64), Value::Integer(to as i64)];
+		if let Some(address) = address {
+			sql.push_str(" AND (i.sender = ?3 OR i.recipient = ?3)");
+			values.push(Value::Blob(address.as_bytes().to_vec()));
+		}
+		sql.push_str(&format!(
+			" ORDER BY i.block_number, i.transaction_index, i.transfer_index LIMIT ?{}",
+			values.len() + 1
+		));
+		values.push(Value::Integer(limit as i64));
+
+		let connection = self.connection.lock();
+		let mut statement = connection.prepare(&sql).map_err(|e| format!("{:?}", e))?;
+		let rows = statement
+			.query_map(params_from_iter(values.iter()), |row| {
+				Ok(InternalTransfer {
+					block_number: row.get::<_, i64>(0)? as u64,
+					ethereum_block_hash: H256::from_slice(&row.get::<_, Vec<u8>>(1)?),
+					transfer: InternalTransferEntry {
+						transaction_hash: H256::from_slice(&row.get::<_, Vec<u8>>(2)?),
+						transaction_index: row.get(3)?,
+						transfer_index: row.get(4)?,
+						from: H160::from_slice(&row.get::<_, Vec<u8>>(5)?),
+						to: H160::from_slice(&row.get::<_, Vec<u8>>(6)?),
+						value: U256::from_big_endian(&row.get::<_, Vec<u8>>(7)?),
+					},
+				})
+			})
+			.map_err(|e| format!("{:?}", e))?;
+		rows.collect::<rusqlite::Result<_>>()
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// Removes the blocks numbered below `below`, outside the served history, with their rows,
+	/// forks included. At most the `max_numbers` lowest block numbers are removed per call,
+	/// returning whether blocks below `below` remain. The backfilled batches stay recorded, so
+	/// a backfill does not index the pruned blocks again.
+	pub fn prune(&self, below: u64, max_numbers: u64) -> Result<bool, String> {
+		let mut connection = self.connection.lock();
+		let transaction = connection.transaction().map_err(|e| format!("{:?}", e))?;
+		let lowest = transaction
+			.query_row("SELECT MIN(block_number) FROM blocks", params![], |row| {
+				row.get::<_, Option<i64>>(0)
+			})
+			.map_err(|e| format!("{:?}", e))?;
+		let lowest = match lowest {
+			Some(lowest) if (lowest as u64) < below => lowest as u64,
+			_ => return Ok(false),
+		};
+		let bound = below.min(lowest.saturating_add(max_numbers));
+		for table in std::iter::once(&"blocks").chain(BLOCK_ROW_TABLES) {
+			transaction
+				.execute(
+					&format!("DELETE FROM {} WHERE block_number < ?1", table),
+					params![bound as i64],
+				)
+				.map_err(|e| format!("{:?}", e))?;
+		}
+		transaction.commit().map_err(|e| format!("{:?}", e))?;
+		Ok(bound < below)
+	}
+
+	/// Blocks covered by the index.
+	pub fn status(&self) -> Result<LogIndexStatus, String> {
+		self.connection
+			.lock()
+			.query_row(
+				"SELECT COUNT(*), COUNT(*) - SUM(canonical), MIN(block_number), MAX(block_number)
+				FROM blocks",
+				params![],
+				|row| {
+					Ok(LogIndexStatus {
+						indexed_blocks: row.get::<_, i64>(0)? as u64,
+						orphaned_blocks: row.get::<_, Option<i64>>(1)?.unwrap_or_default() as u64,
+						lowest_block: row.get::<_, Option<i64>>(2)?.map(|number| number as u64),
+						highest_block: row.get::<_, Option<i64>>(3)?.map(|number| number as u64),
+					})
+				},
+			)
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	fn block_hashes(&self, sql: &str, values: Vec<Value>) -> Result<Vec<Block::Hash>, String> {
+		let connection = self.connection.lock();
+		let mut statement = connection.prepare(sql).map_err(|e| format!("{:?}", e))?;
+		let rows = statement
+			.query_map(params_from_iter(values.iter()), |row| {
+				row.get::<_, Vec<u8>>(0)
+			})
+			.map_err(|e| format!("{:?}", e))?;
+
+		let mut hashes = Vec::new();
+		for row in rows {
+			let bytes = row.map_err(|e| format!("{:?}", e))?;
+			hashes.push(Block::Hash::decode(&mut &bytes[..]).map_err(|e| format!("{:?}", e))?);
+		}
+		Ok(hashes)
+	}
+}
+
+fn insert_block<Block: BlockT>(
+	transaction: &Transaction,
+	commitment: LogIndexCommitment<Block>,
+) -> rusqlite::Result<()> {
+	let block_hash = commitment.block_hash.encode();
+	let block_number = commitment.block_number as i64;
+
+	for table in std::iter::once(&"blocks").chain(BLOCK_ROW_TABLES) {
+		transaction.execute(
+			&format!(
+				"UPDATE {} SET canonical = 0 WHERE block_number = ?1 AND substrate_hash != ?2",
+				table
+			),
+			params![block_number, block_hash],
+		)?;
+	}
+	for table in BLOCK_ROW_TABLES {
+		transaction.execute(
+			&format!("DELETE FROM {} WHERE substrate_hash = ?1", table),
+			params![block_hash],
+		)?;
+	}
+	let mut insert = transaction.prepare_cached(
+		"INSERT INTO logs (substrate_hash, block_number, transaction_index, log_index,
+			transaction_log_index, address, topic0, topic1, topic2, topic3)
+		VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+	)?;
+	for log in commitment.logs {
+		let topic = |index: usize| log.topics.get(index).map(|topic| topic.as_bytes());
+		insert.execute(params![
+			block_hash,
+			block_number,
+			log.transaction_index,
+			log.log_index,
+			log.transaction_log_index,
+			log.address.as_bytes(),
+			topic(0),
+			topic(1),
+			topic(2),
+			topic(3),
+		])?;
+	}
+	let mut insert = transaction.prepare_cached(
+		"INSERT INTO token_transfers (substrate_hash, block_number, log_index, batch_index,
+			transaction_hash, transaction_index, token, standard, sender, recipient, token_id,
+			value)
+		VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+	)?;
+	let word = |value: U256| {
+		let mut bytes = [0u8; 32];
+		value.to_big_endian(&mut bytes);
+		bytes
+	};
+	for transfer in commitment.token_transfers {
+		let token_id = transfer.token_id.map(word);
+		insert.execute(params![
+			block_hash,
+			block_number,
+			transfer.log_index,
+			transfer.batch_index,
+			transfer.transaction_hash.as_bytes(),
+			transfer.transaction_index,
+			transfer.token.as_bytes(),
+			transfer.standard.number(),
+			transfer.from.as_bytes(),
+			transfer.to.as_bytes(),
+			token_id.as_ref().map(|token_id| &token_id[..]),
+			&word(transfer.value)[..],
+		])?;
+	}
+	let mut insert = transaction.prepare_cached(
+		"INSERT INTO contract_creations (substrate_hash, block_number, transaction_index,
+			transaction_hash, address, creator, init_code_hash)
+		VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+	)?;
+	for creation in commitment.contract_creations {
+		insert.execute(params![
+			block_hash,
+			block_number,
+			creation.transaction_index,
+			creation.transaction_hash.as_bytes(),
+			creation.address.as_bytes(),
+			creation.creator.as_bytes(),
+			creation.init_code_hash.as_bytes(),
+		])?;
+	}
+	let mut insert = transaction.prepare_cached(
+		"INSERT INTO internal_transfers (substrate_hash, block_number, transaction_index,
+			transfer_index, transaction_hash, sender, recipient, value)
+		VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+	)?;
+	for transfer in commitment.internal_transfers {
+		insert.execute(params![
+			block_hash,
+			block_number,
+			transfer.transaction_index,
+			transfer.transfer_index,
+			transfer.transaction_hash.as_bytes(),
+			transfer.from.as_bytes(),
+			transfer.to.as_bytes(),
+			&word(transfer.value)[..],
+		])?;
+	}
+	transaction.execute(
+		"INSERT OR REPLACE INTO blocks (substrate_hash, ethereum_hash, block_number)
+		VALUES (?1, ?2, ?3)",
+		params![
+			block_hash,
+			commitment.ethereum_block_hash.as_bytes(),
+			block_number
+		],
+	)?;
+	Ok(())
+}
+
+/// Sets the canonical flag of an indexed block and of its logs, clearing the one of the other
+/// blocks of its number when set.
+fn set_canonical<Block: BlockT>(
+	transaction: &Transaction,
+	block_hash: &Block::Hash,
+	canonical: bool,
+) -> rusqlite::Result<()> {
+	let block_hash = block_hash.encode();
+	let block_number = match transaction
+		.query_row(
+			"SELECT block_number FROM blocks WHERE substrate_hash = ?1",
+			params![block_hash],
+			|row| row.get::<_, i64>(0),
+		)
+		.optional()?
+	{
+		Some(block_number) => block_number,
+		None => return Ok(()),
+	};
+	for table in std::iter::once(&"blocks").chain(BLOCK_ROW_TABLES) {
+		if canonical {
+			transaction.execute(
+				&format!(
+					"UPDATE {} SET canonical = (substrate_hash = ?2) WHERE block_number = ?1",
+					table
+				),
+				params![block_number, block_hash],
+			)?;
+		} else {
+			transaction.execute(
+				&format!(
+					"UPDATE {} SET canonical = 0 WHERE substrate_hash = ?1",
+					table
+				),
+				params![block_hash],
+			)?;
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{generic, traits::BlakeTwo256, OpaqueExtrinsic};
+
+	type Block = generic::Block<generic::Header<u64, BlakeTwo256>, OpaqueExtrinsic>;
+
+	fn open() -> LogIndexDb<Block> {
+		LogIndexDb::with_connection(Connection::open_in_memory().unwrap()).unwrap()
+	}
+
+	/// Block `number` of the fork `fork`, with a log of `address`.
+	fn commitment(number: u64, fork: u8, address: H160) -> LogIndexCommitment<Block> {
+		let mut hash = H256::from_low_u64_be(number);
+		hash.0[0] = fork;
+		LogIndexCommitment {
+			block_hash: hash,
+			ethereum_block_hash: H256::from_low_u64_be(number + 1_000),
+			block_number: number,
+			logs: vec![LogIndexEntry {
+				transaction_index: 0,
+				log_index: 0,
+				transaction_log_index: 0,
+				address,
+				topics: vec![H256::repeat_byte(0x11)],
+			}],
+			token_transfers: Vec::new(),
+			contract_creations: Vec::new(),
+			internal_transfers: Vec::new(),
+		}
+	}
+
+	fn write_chain(db: &LogIndexDb<Block>, numbers: std::ops::Range<u64>) {
+		let commitments = numbers
+			.map(|number| commitment(number, 0, H160::repeat_byte(1)))
+			.collect();
+		db.write_batch(&[], &[], commitments, None).unwrap();
+	}
+
+	#[test]
+	fn migrations_flag_the_blocks_of_a_version_0_index_canonical() {
+		let connection = Connection::open_in_memory().unwrap();
+		connection
+			.execute_batch(
+				"CREATE TABLE blocks (
+					substrate_hash BLOB NOT NULL PRIMARY KEY,
+					ethereum_hash BLOB NOT NULL,
+					block_number INTEGER NOT NULL
+				);
+				CREATE TABLE logs (
+					substrate_hash BLOB NOT NULL,
+					block_number INTEGER NOT NULL,
+					transaction_index INTEGER NOT NULL,
+					log_index INTEGER NOT NULL,
+					transaction_log_index INTEGER NOT NULL,
+					address BLOB NOT NULL,
+					topic0 BLOB,
+					topic1 BLOB,
+					topic2 BLOB,
+					topic3 BLOB,
+					PRIMARY KEY (substrate_hash, log_index)
+				);",
+			)
+			.unwrap();
+		let hash = H256::from_low_u64_be(1);
+		connection
+			.execute(
+				"INSERT INTO blocks VALUES (?1, ?2, 1)",
+				params![hash.encode(), H256::zero().as_bytes()],
+			)
+			.unwrap();
+		connection
+			.execute(
+				"INSERT INTO logs VALUES (?1, 1, 0, 0, 0, ?2, NULL, NULL, NULL, NULL)",
+				params![hash.encode(), H160::repeat_byte(1).as_bytes()],
+			)
+			.unwrap();
+
+		let db = LogIndexDb::<Block>::with_connection(connection).unwrap();
+		assert_eq!(db.indexed_blocks(0, 10).unwrap(), vec![hash]);
+		assert_eq!(
+			db.matching_blocks(&LogIndexQuery::default(), 0, 10)
+				.unwrap(),
+			vec![hash]
+		);
+		assert_eq!(db.status().unwrap().orphaned_blocks, 0);
+		assert_eq!(db.internal_transfers(None, 0, 10, 10), Ok(Vec::new()));
+
+		// Opening the migrated index again is a no-op.
+		let db = LogIndexDb::<Block>::with_connection(db.connection.into_inner()).unwrap();
+		assert_eq!(db.indexed_blocks(0, 10).unwrap(), vec![hash]);
+		let version = db
+			.connection
+			.lock()
+			.query_row("PRAGMA user_version", params![], |row| row.get::<_, i64>(0))
+			.unwrap();
+		assert_eq!(version, SCHEMA_VERSION);
+	}
+
+	#[test]
+	fn opening_an_index_of_a_newer_schema_fails() {
+		let connection = Connection::open_in_memory().unwrap();
+		connection
+			.execute_batch(&format!("PRAGMA user_version = {};", SCHEMA_VERSION + 1))
+			.unwrap();
+		assert!(LogIndexDb::<Block>::with_connection(connection).is_err());
+	}
+
+	#[test]
+	fn reorgs_flip_the_canonical_block_of_a_number() {
+		let db = open();
+		write_chain(&db, 0..3);
+		let fork = commitment(2, 1, H160::repeat_byte(2));
+		let fork_hash = fork.block_hash;
+		let main_hash = commitment(2, 0, H160::repeat_byte(1)).block_hash;
+		let query = LogIndexQuery {
+			addresses: vec![H160::repeat_byte(2)],
+			topics: Vec::new(),
+		};
+
+		// Writing the fork block retracts the block of its number.
+		db.write_batch(&[main_hash], &[], vec![fork], Some(3))
+			.unwrap();
+		assert_eq!(db.checkpoint(), Ok(Some(3)));
+		assert_eq!(db.indexed_blocks(2, 2).unwrap(), vec![fork_hash]);
+		assert_eq!(db.matching_blocks(&query, 0, 2).unwrap(), vec![fork_hash]);
+		let status = db.status().unwrap();
+		assert_eq!(status.indexed_blocks, 4);
+		assert_eq!(status.orphaned_blocks, 1);
+
+		// Enacting the retracted block back clears the flag of the fork.
+		db.write_batch(&[fork_hash], &[main_hash], Vec::new(), None)
+			.unwrap();
+		assert_eq!(db.indexed_blocks(2, 2).unwrap(), vec![main_hash]);
+		assert!(db.matching_blocks(&query, 0, 2).unwrap().is_empty());
+		assert_eq!(db.status().unwrap().orphaned_blocks, 1);
+
+		// Enacting a block is enough to retract the other blocks of its number.
+		db.write_batch(&[], &[fork_hash], Vec::new(), None).unwrap();
+		assert_eq!(db.indexed_blocks(2, 2).unwrap(), vec![fork_hash]);
+		assert_eq!(db.matching_blocks(&query, 0, 2).unwrap(), vec![fork_hash]);
+		assert!(db.is_indexed(&main_hash).unwrap());
+	}
+
+	#[test]
+	fn matching_blocks_filters_by_address_and_topics() {
+		let db = open();
+		let mut first = commitment(1, 0, H160::repeat_byte(1));
+		first.logs[0].topics = vec![H256::repeat_byte(0x11), H256::repeat_byte(0x22)];
+		let mut second = commitment(2, 0, H160::repeat_byte(2));
+		second.logs[0].topics = vec![H256::repeat_byte(0x11), H256::repeat_byte(0x33)];
+		let third = commitment(3, 0, H160::repeat_byte(1));
+		let (first_hash, second_hash, third_hash) =
+			(first.block_hash, second.block_hash, third.block_hash);
+		db.write_batch(&[], &[], vec![first, second, third], None)
+			.unwrap();
+
+		let matching = |addresses: Vec<H160>, topics: Vec<Vec<H256>>, from, to| {
+			let mut hashes = db
+				.matching_blocks(&LogIndexQuery { addresses, topics }, from, to)
+				.unwrap();
+			hashes.sort();
+			hashes
+		};
+		assert_eq!(
+			matching(Vec::new(), Vec::new(), 0, 10),
+			vec![first_hash, second_hash, third_hash]
+		);
+		assert_eq!(
+			matching(vec![H160::repeat_byte(1)], Vec::new(), 0, 10),
+			vec![first_hash, third_hash]
+		);
+		assert_eq!(
+			matching(vec![H160::repeat_byte(1)], Vec::new(), 2, 10),
+			vec![third_hash]
+		);
+		assert_eq!(
+			matching(
+				vec![H160::repeat_byte(1), H160::repeat_byte(2)],
+				vec![Vec::new(), vec![H256::repeat_byte(0x33)]],
+				0,
+				10
+			),
+			vec![second_hash]
+		);
+		assert_eq!(
+			matching(
+				Vec::new(),
+				vec![
+					vec![H256::repeat_byte(0x11)],
+					vec![H256::repeat_byte(0x22), H256::repeat_byte(0x33)]
+				],
+				0,
+				10
+			),
+			vec![first_hash, second_hash]
+		);
+		assert!(matching(vec![H160::repeat_byte(3)], Vec::new(), 0, 10).is_empty());
+	}
+
+	fn internal_transfer(
+		transaction_index: u32,
+		transfer_index: u32,
+		from: u8,
+		to: u8,
+	) -> InternalTransferEntry {
+		InternalTransferEntry {
+			transaction_hash: H256::from_low_u64_be(transaction_index as u64),
+			transaction_index,
+			transfer_index,
+			from: H160::repeat_byte(from),
+			to: H160::repeat_byte(to),
+			value: U256::from(1_000),
+		}
+	}
+
+	#[test]
+	fn internal_transfers_are_returned_in_chain_order() {
+		let db = open();
+		let mut first = commitment(1, 0, H160::repeat_byte(1));
+		first.internal_transfers = vec![
+			internal_transfer(0, 0, 1, 2),
+			internal_transfer(0, 1, 2, 3),
+			internal_transfer(1, 0, 3, 4),
+		];
+		let mut second = commitment(2, 0, H160::repeat_byte(1));
+		second.internal_transfers = vec![internal_transfer(0, 0, 4, 2)];
+		db.write_batch(&[], &[], vec![second, first], None).unwrap();
+
+		let transfers = |address: Option<u8>, from, to, limit| {
+			db.internal_transfers(address.map(H160::repeat_byte), from, to, limit)
+				.unwrap()
+				.into_iter()
+				.map(|transfer| {
+					(
+						transfer.block_number,
+						transfer.transfer.transaction_index,
+						transfer.transfer.transfer_index,
+					)
+				})
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(
+			transfers(None, 0, 10, 10),
+			vec![(1, 0, 0), (1, 0, 1), (1, 1, 0), (2, 0, 0)]
+		);
+		assert_eq!(transfers(None, 0, 10, 2), vec![(1, 0, 0), (1, 0, 1)]);
+		assert_eq!(transfers(None, 2, 10, 10), vec![(2, 0, 0)]);
+		assert_eq!(
+			transfers(Some(2), 0, 10, 10),
+			vec![(1, 0, 0), (1, 0, 1), (2, 0, 0)]
+		);
+		assert_eq!(transfers(Some(4), 0, 1, 10), vec![(1, 1, 0)]);
+
+		let transfer = db.internal_transfers(None, 2, 2, 1).unwrap().remove(0);
+		assert_eq!(
+			transfer,
+			InternalTransfer {
+				block_number: 2,
+				ethereum_block_hash: H256::from_low_u64_be(1_002),
+				transfer: internal_transfer(0, 0, 4, 2),
+			}
+		);
+	}
+
+	#[test]
+	fn internal_transfers_follow_the_canonical_blocks() {
+		let db = open();
+		let mut main = commitment(1, 0, H160::repeat_byte(1));
+		main.internal_transfers = vec![internal_transfer(0, 0, 1, 2)];
+		let mut fork = commitment(1, 1, H160::repeat_byte(1));
+		fork.internal_transfers = vec![internal_transfer(0, 0, 1, 3)];
+		let (main_hash, fork_hash) = (main.block_hash, fork.block_hash);
+		db.write_batch(&[], &[], vec![main], None).unwrap();
+		db.write_batch(&[main_hash], &[], vec![fork], None).unwrap();
+
+		let recipients = |db: &LogIndexDb<Block>| {
+			db.internal_transfers(None, 0, 10, 10)
+				.unwrap()
+				.into_iter()
+				.map(|transfer| transfer.transfer.to)
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(recipients(&db), vec![H160::repeat_byte(3)]);
+		db.write_batch(&[fork_hash], &[main_hash], Vec::new(), None)
+			.unwrap();
+		assert_eq!(recipients(&db), vec![H160::repeat_byte(2)]);
+
+		// Indexing a block again replaces its transfers.
+		let mut main = commitment(1, 0, H160::repeat_byte(1));
+		main.internal_transfers = vec![internal_transfer(0, 0, 1, 4)];
+		db.write_batch(&[], &[], vec![main], None).unwrap();
+		assert_eq!(recipients(&db), vec![H160::repeat_byte(4)]);
+	}
+
+	#[test]
+	fn prune_removes_the_blocks_below_the_history() {
+		let db = open();
+		write_chain(&db, 0..10);
+		db.write_batch(&[], &[], vec![commitment(2, 1, H160::repeat_byte(1))], None)
+			.unwrap();
+
+		assert_eq!(db.prune(5, 100), Ok(false));
+		let status = db.status().unwrap();
+		assert_eq!(status.lowest_block, Some(5));
+		assert_eq!(status.indexed_blocks, 5);
+		assert_eq!(status.orphaned_blocks, 0);
+		assert!(db
+			.matching_blocks(&LogIndexQuery::default(), 0, 4)
+			.unwrap()
+			.is_empty());
+		assert_eq!(
+			db.matching_blocks(&LogIndexQuery::default(), 0, 9)
+				.unwrap()
+				.len(),
+			5
+		);
+	}
+
+	#[test]
+	fn prune_proceeds_in_bounded_steps() {
+		let db = open();
+		write_chain(&db, 0..10);
+
+		assert_eq!(db.prune(8, 3), Ok(true));
+		assert_eq!(db.status().unwrap().lowest_block, Some(3));
+		assert_eq!(db.prune(8, 3), Ok(true));
+		assert_eq!(db.status().unwrap().lowest_block, Some(6));
+		assert_eq!(db.prune(8, 3), Ok(false));
+		assert_eq!(db.status().unwrap().lowest_block, Some(8));
+		assert_eq!(db.prune(8, 3), Ok(false));
+	}
+
+	#[test]
+	fn prune_of_an_empty_index_is_a_no_op() {
+		let db = open();
+		assert_eq!(db.prune(8, 3), Ok(false));
+		assert_eq!(db.status().unwrap(), LogIndexStatus::default());
+	}
+}
@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding of the token transfers from the logs, for the token transfer index.
+//!
+//! ERC-20 and ERC-721 share the `Transfer` event, told apart by the number of indexed topics:
+//! ERC-20 logs the value in the data, ERC-721 indexes the token id. ERC-1155 logs
+//! `TransferSingle` and `TransferBatch`, the latter decoded into one transfer per token id.
+//! Logs which do not follow these layouts are ignored.
+
+use sp_core::{hashing::keccak_256, H160, H256, U256};
+
+const TRANSFER: &[u8] = b"Transfer(address,address,uint256)";
+const TRANSFER_SINGLE: &[u8] = b"TransferSingle(address,address,address,uint256,uint256)";
+const TRANSFER_BATCH: &[u8] = b"TransferBatch(address,address,address,uint256[],uint256[])";
+
+/// Token standard of a transfer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TokenStandard {
+	Erc20,
+	Erc721,
+	Erc1155,
+}
+
+impl TokenStandard {
+	/// Number of the ERC defining the standard.
+	pub fn number(&self) -> u32 {
+		match self {
+			TokenStandard::Erc20 => 20,
+			TokenStandard::Erc721 => 721,
+			TokenStandard::Erc1155 => 1155,
+		}
+	}
+
+	/// Standard defined by the ERC `number`.
+	pub fn from_number(number: u32) -> Option<Self> {
+		match number {
+			20 => Some(TokenStandard::Erc20),
+			721 => Some(TokenStandard::Erc721),
+			1155 => Some(TokenStandard::Erc1155),
+			_ => None,
+		}
+	}
+}
+
+/// Token transfer decoded from a log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenTransferEntry {
+	/// Hash of the transaction emitting the log.
+	pub transaction_hash: H256,
+	/// Index of the transaction in the block.
+	pub transaction_index: u32,
+	/// Index of the log in the block.
+	pub log_index: u32,
+	/// Index of the transfer in an ERC-1155 batch, 0 otherwise.
+	pub batch_index: u32,
+	/// Address of the token contract.
+	pub token: H160,
+	pub standard: TokenStandard,
+	pub from: H160,
+	pub to: H160,
+	/// Token id, None for ERC-20.
+	pub token_id: Option<U256>,
+	/// Amount transferred, 1 for ERC-721.
+	pub value: U256,
+}
+
+/// Position of the log a transfer is decoded from.
+#[derive(Clone, Copy, Debug)]
+pub struct TransferLogPosition {
+	pub transaction_hash: H256,
+	pub transaction_index: u32,
+	pub log_index: u32,
+}
+
+fn address(topic: &H256) -> H160 {
+	H160::from_slice(&topic.as_bytes()[12..])
+}
+
+fn word(data: &[u8], offset: usize) -> Option<U256> {
+	data.get(offset..offset.checked_add(32)?)
+		.map(U256::from_big_endian)
+}
+
+/// Array of words of the ABI encoded `data` whose offset is at the word `index`.
+fn words(data: &[u8], index: usize) -> Option<Vec<U256>> {
+	let offset = word(data, index * 32)?;
+	if offset > U256::from(data.len()) {
+		return None;
+	}
+	let offset = offset.as_usize();
+	let len = word(data, offset)?;
+	if len > U256::from(data.len() / 32) {
+		return None;
+	}
+	(0..len.as_usize())
+		.map(|i| word(data, offset + 32 + i * 32))
+		.collect()
+}
+
+/// Token transfers logged by the contract `token`, with the given topics and data.
+pub fn decode_token_transfers(
+	position: TransferLogPosition,
+	token: H160,
+	topics: &[H256],
+	data: &[u8],
+) -> Vec<TokenTransferEntry> {
+	let transfer = |batch_index: u32,
+	                standard: TokenStandard,
+	                from: &H256,
+	                to: &H256,
+	                token_id: Option<U256>,
+	                value: U256| TokenTransferEntry {
+		transaction_hash: position.transaction_hash,
+		transaction_index: position.transaction_index,
+		log_index: position.log_index,
+		batch_index,
+		token,
+		standard,
+		from: address(from),
+		to: address(to),
+		token_id,
+		value,
+	};
+
+	let signature = match topics.first() {
+		Some(signature) => signature.as_bytes(),
+		None => return Vec::new(),
+	};
+	if signature == keccak_256(TRANSFER) {
+		match (topics, data.len()) {
+			([_, from, to], 32) => word(data, 0)
+				.map(|value| transfer(0, TokenStandard::Erc20, from, to, None, value))
+				.into_iter()
+				.collect(),
+			([_, from, to, token_id], 0) => vec![transfer(
+				0,
+				TokenStandard::Erc721,
+				from,
+				to,
+				Some(U256::from_big_endian(token_id.as_bytes())),
+				U256::one(),
+			)],
+			_ => Vec::new(),
+		}
+	} else if signature == keccak_256(TRANSFER_SINGLE) {
+		match (topics, word(data, 0), word(data, 32)) {
+			([_, _, from, to], Some(token_id), Some(value)) if data.len() == 64 => vec![transfer(
+				0,
+				TokenStandard::Erc1155,
+				from,
+				to,
+				Some(token_id),
+				value,
+			)],
+			_ => Vec::new(),
+		}
+	} else if signature == keccak_256(TRANSFER_BATCH) {
+		match (topics, words(data, 0), words(data, 1)) {
+			([_, _, from, to], Some(token_ids), Some(values))
+				if token_ids.len() == values.len() =>
+			{
+				token_ids
+					.into_iter()
+					.zip(values)
+					.enumerate()
+					.map(|(index, (token_id, value))| {
+						transfer(
+							index as u32,
+							TokenStandard::Erc1155,
+							from,
+							to,
+							Some(token_id),
+							value,
+						)
+					})
+					.collect()
+			}
+			_ => Vec::new(),
+		}
+	} else {
+		Vec::new()
+	}
+}
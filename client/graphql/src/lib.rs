@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Ethereum GraphQL endpoint, compatible with the `/graphql` endpoint of geth (EIP-1767).
+//!
+//! The resolvers read the chain data through the `eth_` JSON-RPC methods of an RPC handler,
+//! so that both interfaces are served by the same storage and runtime adapters.
+
+mod schema;
+
+pub use schema::{Account, Block, Log, Mutation, Query, Transaction};
+
+use async_graphql::{EmptySubscription, Schema};
+use futures::future::BoxFuture;
+use hyper::{
+	header,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response, Server, StatusCode,
+};
+use jsonrpc_core::{IoHandler, Metadata};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+/// Ethereum GraphQL schema.
+pub type EthSchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// JSON-RPC handler the resolvers read the chain data from.
+pub trait RpcHandler: Send + Sync + 'static {
+	/// Handles a JSON-RPC request, returning the response if any.
+	fn handle(&self, request: String) -> BoxFuture<'static, Option<String>>;
+}
+
+impl<M: Metadata + Default> RpcHandler for IoHandler<M> {
+	fn handle(&self, request: String) -> BoxFuture<'static, Option<String>> {
+		Box::pin(IoHandler::handle_request(self, &request))
+	}
+}
+
+/// Builds the schema resolving against the given JSON-RPC handler.
+pub fn schema(handler: Arc<dyn RpcHandler>) -> EthSchema {
+	Schema::build(Query, Mutation, EmptySubscription)
+		.data(handler)
+		.finish()
+}
+
+/// Serves the schema at `/graphql` on the given address.
+pub async fn run_server(addr: SocketAddr, schema: EthSchema) {
+	let make_service = make_service_fn(move |_| {
+		let schema = schema.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request| {
+				handle_request(schema.clone(), request)
+			}))
+		}
+	});
+
+	log::info!("GraphQL server listening on http://{}/graphql", addr);
+	if let Err(err) = Server::bind(&addr).serve(make_service).await {
+		log::error!("GraphQL server failed: {:?}", err);
+	}
+}
+
+async fn handle_request(
+	schema: EthSchema,
+	request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+	if request.uri().path() != "/graphql" {
+		return Ok(status_response(StatusCode::NOT_FOUND));
+	}
+	if request.method() != Method::POST {
+		return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+	}
+
+	let body = match hyper::body::to_bytes(request.into_body()).await {
+		Ok(body) => body,
+		Err(_) => return Ok(status_response(StatusCode::BAD_REQUEST)),
+	};
+	let request: async_graphql::Request = match serde_json::from_slice(&body) {
+		Ok(request) => request,
+		Err(_) => return Ok(status_response(StatusCode::BAD_REQUEST)),
+	};
+
+	let response = schema.execute(request).await;
+	let body = serde_json::to_vec(&response).unwrap_or_default();
+	Ok(Response::builder()
+		.header(header::CONTENT_TYPE, "application/json")
+		.body(Body::from(body))
+		.unwrap_or_else(|_| status_response(StatusCode::INTERNAL_SERVER_ERROR)))
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+	let mut response = Response::new(Body::empty());
+	*response.status_mut() = status;
+	response
+}
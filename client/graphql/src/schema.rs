@@ -0,0 +1,743 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::RpcHandler;
+use async_graphql::{scalar, Context, Error, InputObject, Object, Result};
+use ethereum_types::{H160, H256, U256};
+use fc_rpc_core::types::{
+	BlockNumber, BlockTransactions, Bytes as RpcBytes, Log as RpcLog, Receipt, RichBlock,
+	Transaction as RpcTransaction,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// 32 bytes, hex encoded.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bytes32(pub H256);
+scalar!(Bytes32, "Bytes32", "32 bytes, hex encoded.");
+
+/// 20 bytes Ethereum address, hex encoded.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Address(pub H160);
+scalar!(
+	Address,
+	"Address",
+	"20 bytes Ethereum address, hex encoded."
+);
+
+/// Arbitrary length binary data, hex encoded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bytes(pub RpcBytes);
+scalar!(Bytes, "Bytes", "Arbitrary length binary data, hex encoded.");
+
+/// Large integer, hex encoded.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BigInt(pub U256);
+scalar!(BigInt, "BigInt", "Large integer, hex encoded.");
+
+/// 64 bits integer.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Long(pub u64);
+scalar!(Long, "Long", "64 bits integer.");
+
+/// Maximum number of blocks returned by the `blocks` query.
+pub const MAX_BLOCK_RANGE: u64 = 1024;
+
+fn long(value: U256) -> Long {
+	Long(value.low_u64())
+}
+
+/// Calls a JSON-RPC method of the handler in the schema data.
+async fn rpc<T: DeserializeOwned>(ctx: &Context<'_>, method: &str, params: Value) -> Result<T> {
+	let handler = ctx.data::<Arc<dyn RpcHandler>>()?;
+	let request = json!({
+		"jsonrpc": "2.0",
+		"id": 1,
+		"method": method,
+		"params": params,
+	});
+
+	let response = handler
+		.handle(request.to_string())
+		.await
+		.ok_or_else(|| Error::new(format!("no response to {}", method)))?;
+	let mut response: Value = serde_json::from_str(&response)?;
+	if let Some(error) = response.get("error") {
+		return Err(Error::new(
+			error["message"]
+				.as_str()
+				.unwrap_or("JSON-RPC error")
+				.to_string(),
+		));
+	}
+	Ok(serde_json::from_value(response["result"].take())?)
+}
+
+async fn block_by_number(ctx: &Context<'_>, number: BlockNumber) -> Result<Option<Block>> {
+	Ok(
+		rpc::<Option<RichBlock>>(ctx, "eth_getBlockByNumber", json!([number, true]))
+			.await?
+			.map(Block),
+	)
+}
+
+async fn block_by_hash(ctx: &Context<'_>, hash: H256) -> Result<Option<Block>> {
+	Ok(
+		rpc::<Option<RichBlock>>(ctx, "eth_getBlockByHash", json!([hash, true]))
+			.await?
+			.map(Block),
+	)
+}
+
+async fn transaction_by_hash(ctx: &Context<'_>, hash: H256) -> Result<Option<Transaction>> {
+	Ok(
+		rpc::<Option<RpcTransaction>>(ctx, "eth_getTransactionByHash", json!([hash]))
+			.await?
+			.map(Transaction),
+	)
+}
+
+/// Topics filter, a list of alternatives per position, an empty list matching any topic.
+fn topics_param(topics: Option<Vec<Vec<Bytes32>>>) -> Value {
+	match topics {
+		Some(topics) => Value::Array(
+			topics
+				.into_iter()
+				.map(|alternatives| match alternatives.len() {
+					0 => Value::Null,
+					_ => json!(alternatives),
+				})
+				.collect(),
+		),
+		None => Value::Null,
+	}
+}
+
+/// Log filter over a block range.
+#[derive(InputObject)]
+pub struct FilterCriteria {
+	/// First block of the range, the latest block if not set.
+	from_block: Option<Long>,
+	/// Last block of the range, the latest block if not set.
+	to_block: Option<Long>,
+	/// Log emitters, any if not set.
+	addresses: Option<Vec<Address>>,
+	/// Log topics by position.
+	topics: Option<Vec<Vec<Bytes32>>>,
+}
+
+/// Log filter within a block.
+#[derive(InputObject)]
+pub struct BlockFilterCriteria {
+	/// Log emitters, any if not set.
+	addresses: Option<Vec<Address>>,
+	/// Log topics by position.
+	topics: Option<Vec<Vec<Bytes32>>>,
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+	/// Block by number or hash, the latest block if none is given.
+	async fn block(
+		&self,
+		ctx: &Context<'_>,
+		number: Option<Long>,
+		hash: Option<Bytes32>,
+	) -> Result<Option<Block>> {
+		match (number, hash) {
+			(Some(_), Some(_)) => Err(Error::new("only one of number or hash must be specified")),
+			(_, Some(hash)) => block_by_hash(ctx, hash.0).await,
+			(Some(number), None) => block_by_number(ctx, BlockNumber::Num(number.0)).await,
+			(None, None) => block_by_number(ctx, BlockNumber::Latest).await,
+		}
+	}
+
+	/// Blocks in the given range, up to the latest block. The range is limited to
+	/// `MAX_BLOCK_RANGE` blocks.
+	async fn blocks(&self, ctx: &Context<'_>, from: Long, to: Option<Long>) -> Result<Vec<Block>> {
+		let latest: U256 = rpc(ctx, "eth_blockNumber", json!([])).await?;
+		let to = to.map(|to| to.0).unwrap_or(u64::MAX).min(latest.low_u64());
+		if to >= from.0 && to - from.0 >= MAX_BLOCK_RANGE {
+			return Err(Error::new(format!(
+				"block range too large, at most {} blocks are returned",
+				MAX_BLOCK_RANGE
+			)));
+		}
+
+		let mut blocks = Vec::new();
+		for number in from.0..=to {
+			match block_by_number(ctx, BlockNumber::Num(number)).await? {
+				Some(block) => blocks.push(block),
+				None => break,
+			}
+		}
+		Ok(blocks)
+	}
+
+	/// Transaction by hash.
+	async fn transaction(&self, ctx: &Context<'_>, hash: Bytes32) -> Result<Option<Transaction>> {
+		transaction_by_hash(ctx, hash.0).await
+	}
+
+	/// Logs matching the filter.
+	async fn logs(&self, ctx: &Context<'_>, filter: FilterCriteria) -> Result<Vec<Log>> {
+		let filter = json!({
+			"fromBlock": filter.from_block.map(|number| BlockNumber::Num(number.0)),
+			"toBlock": filter.to_block.map(|number| BlockNumber::Num(number.0)),
+			"address": filter.addresses,
+			"topics": topics_param(filter.topics),
+		});
+		let logs: Vec<RpcLog> = rpc(ctx, "eth_getLogs", json!([filter])).await?;
+		Ok(logs.into_iter().map(Log).collect())
+	}
+
+	/// Suggested gas price.
+	async fn gas_price(&self, ctx: &Context<'_>) -> Result<BigInt> {
+		Ok(BigInt(rpc(ctx, "eth_gasPrice", json!([])).await?))
+	}
+
+	/// Chain id used for transaction signing.
+	#[graphql(name = "chainID")]
+	async fn chain_id(&self, ctx: &Context<'_>) -> Result<BigInt> {
+		let chain_id: Option<U256> = rpc(ctx, "eth_chainId", json!([])).await?;
+		Ok(BigInt(chain_id.unwrap_or_default()))
+	}
+}
+
+pub struct Mutation;
+
+#[Object]
+impl Mutation {
+	/// Sends a signed transaction, returning its hash.
+	async fn send_raw_transaction(&self, ctx: &Context<'_>, data: Bytes) -> Result<Bytes32> {
+		Ok(Bytes32(
+			rpc(ctx, "eth_sendRawTransaction", json!([data])).await?,
+		))
+	}
+}
+
+/// Ethereum account state at a block.
+pub struct Account {
+	address: H160,
+	block: BlockNumber,
+}
+
+#[Object]
+impl Account {
+	async fn address(&self) -> Address {
+		Address(self.address)
+	}
+
+	async fn balance(&self, ctx: &Context<'_>) -> Result<BigInt> {
+		Ok(BigInt(
+			rpc(ctx, "eth_getBalance", json!([self.address, self.block])).await?,
+		))
+	}
+
+	async fn transaction_count(&self, ctx: &Context<'_>) -> Result<Long> {
+		let count: U256 = rpc(
+			ctx,
+			"eth_getTransactionCount",
+			json!([self.address, self.block]),
+		)
+		.await?;
+		Ok(long(count))
+	}
+
+	async fn code(&self, ctx: &Context<'_>) -> Result<Bytes> {
+		Ok(Bytes(
+			rpc(ctx, "eth_getCode", json!([self.address, self.block])).await?,
+		))
+	}
+
+	async fn storage(&self, ctx: &Context<'_>, slot: Bytes32) -> Result<Bytes32> {
+		let slot = U256::from_big_endian(slot.0.as_bytes());
+		Ok(Bytes32(
+			rpc(
+				ctx,
+				"eth_getStorageAt",
+				json!([self.address, slot, self.block]),
+			)
+			.await?,
+		))
+	}
+}
+
+fn account_at(address: H160, block: Option<Long>, default: BlockNumber) -> Account {
+	Account {
+		address,
+		block: block
+			.map(|number| BlockNumber::Num(number.0))
+			.unwrap_or(default),
+	}
+}
+
+/// Ethereum block.
+pub struct Block(RichBlock);
+
+impl Block {
+	fn block_number(&self) -> BlockNumber {
+		self.0
+			.inner
+			.header
+			.number
+			.map(|number| BlockNumber::Num(number.low_u64()))
+			.unwrap_or(BlockNumber::Pending)
+	}
+
+	/// Seal field of the header: Frontier gives the mix hash and the nonce as `sealFields`,
+	/// geth-like nodes as the `mixHash` and `nonce` fields, kept as extra info.
+	fn seal_field(&self, index: usize, name: &str) -> Option<RpcBytes> {
+		match self.0.extra_info.get(name) {
+			Some(value) => serde_json::from_value(Value::String(value.clone())).ok(),
+			None => self.0.inner.header.seal_fields.get(index).cloned(),
+		}
+	}
+
+	fn full_transactions(&self) -> Vec<RpcTransaction> {
+		match &self.0.inner.transactions {
+			BlockTransactions::Full(transactions) => transactions.clone(),
+			BlockTransactions::Hashes(_) => Vec::new(),
+		}
+	}
+}
+
+#[Object]
+impl Block {
+	async fn number(&self) -> Long {
+		long(self.0.inner.header.number.unwrap_or_default())
+	}
+
+	async fn hash(&self) -> Bytes32 {
+		Bytes32(self.0.inner.header.hash.unwrap_or_default())
+	}
+
+	async fn parent(&self, ctx: &Context<'_>) -> Result<Option<Block>> {
+		if self.0.inner.header.number.unwrap_or_default().is_zero() {
+			return Ok(None);
+		}
+		block_by_hash(ctx, self.0.inner.header.parent_hash).await
+	}
+
+	async fn nonce(&self) -> Bytes {
+		Bytes(
+			self.seal_field(1, "nonce")
+				.unwrap_or_else(|| RpcBytes(vec![0u8; 8])),
+		)
+	}
+
+	async fn transactions_root(&self) -> Bytes32 {
+		Bytes32(self.0.inner.header.transactions_root)
+	}
+
+	async fn transaction_count(&self) -> Long {
+		Long(self.full_transactions().len() as u64)
+	}
+
+	async fn state_root(&self) -> Bytes32 {
+		Bytes32(self.0.inner.header.state_root)
+	}
+
+	async fn receipts_root(&self) -> Bytes32 {
+		Bytes32(self.0.inner.header.receipts_root)
+	}
+
+	async fn miner(&self, block: Option<Long>) -> Account {
+		account_at(self.0.inner.header.miner, block, self.block_number())
+	}
+
+	async fn extra_data(&self) -> Bytes {
+		Bytes(self.0.inner.header.extra_data.clone())
+	}
+
+	async fn gas_limit(&self) -> Long {
+		long(self.0.inner.header.gas_limit)
+	}
+
+	async fn gas_used(&self) -> Long {
+		long(self.0.inner.header.gas_used)
+	}
+
+	async fn timestamp(&self) -> Long {
+		long(self.0.inner.header.timestamp)
+	}
+
+	async fn logs_bloom(&self) -> Bytes {
+		Bytes(RpcBytes(self.0.inner.header.logs_bloom.as_bytes().to_vec()))
+	}
+
+	async fn mix_hash(&self) -> Bytes32 {
+		Bytes32(
+			self.seal_field(0, "mixHash")
+				.filter(|mix_hash| mix_hash.0.len() == 32)
+				.map(|mix_hash| H256::from_slice(&mix_hash.0))
+				.unwrap_or_default(),
+		)
+	}
+
+	async fn difficulty(&self) -> BigInt {
+		BigInt(self.0.inner.header.difficulty)
+	}
+
+	async fn total_difficulty(&self) -> BigInt {
+		BigInt(self.0.inner.total_difficulty)
+	}
+
+	async fn ommer_count(&self) -> Long {
+		Long(self.0.inner.uncles.len() as u64)
+	}
+
+	async fn ommer_hash(&self) -> Bytes32 {
+		Bytes32(self.0.inner.header.uncles_hash)
+	}
+
+	async fn transactions(&self) -> Vec<Transaction> {
+		self.full_transactions()
+			.into_iter()
+			.map(Transaction)
+			.collect()
+	}
+
+	async fn transaction_at(&self, index: Long) -> Option<Transaction> {
+		self.full_transactions()
+			.into_iter()
+			.nth(index.0 as usize)
+			.map(Transaction)
+	}
+
+	async fn logs(&self, ctx: &Context<'_>, filter: BlockFilterCriteria) -> Result<Vec<Log>> {
+		let filter = json!({
+			"blockHash": self.0.inner.header.hash,
+			"address": filter.addresses,
+			"topics": topics_param(filter.topics),
+		});
+		let logs: Vec<RpcLog> = rpc(ctx, "eth_getLogs", json!([filter])).await?;
+		Ok(logs.into_iter().map(Log).collect())
+	}
+
+	async fn account(&self, address: Address) -> Account {
+		account_at(address.0, None, self.block_number())
+	}
+}
+
+/// Ethereum transaction.
+pub struct Transaction(RpcTransaction);
+
+impl Transaction {
+	fn block_number(&self) -> BlockNumber {
+		self.0
+			.block_number
+			.map(|number| BlockNumber::Num(number.low_u64()))
+			.unwrap_or(BlockNumber::Latest)
+	}
+
+	async fn receipt(&self, ctx: &Context<'_>) -> Result<Option<Receipt>> {
+		rpc(ctx, "eth_getTransactionReceipt", json!([self.0.hash])).await
+	}
+}
+
+#[Object]
+impl Transaction {
+	async fn hash(&self) -> Bytes32 {
+		Bytes32(self.0.hash)
+	}
+
+	async fn nonce(&self) -> Long {
+		long(self.0.nonce)
+	}
+
+	async fn index(&self) -> Option<Long> {
+		self.0.transaction_index.map(long)
+	}
+
+	async fn from(&self, block: Option<Long>) -> Account {
+		account_at(self.0.from, block, self.block_number())
+	}
+
+	async fn to(&self, block: Option<Long>) -> Option<Account> {
+		self.0
+			.to
+			.map(|to| account_at(to, block, self.block_number()))
+	}
+
+	async fn value(&self) -> BigInt {
+		BigInt(self.0.value)
+	}
+
+	async fn gas_price(&self) -> BigInt {
+		BigInt(self.0.gas_price)
+	}
+
+	async fn gas(&self) -> Long {
+		long(self.0.gas)
+	}
+
+	async fn input_data(&self) -> Bytes {
+		Bytes(self.0.input.clone())
+	}
+
+	async fn block(&self, ctx: &Context<'_>) -> Result<Option<Block>> {
+		match self.0.block_hash {
+			Some(hash) => block_by_hash(ctx, hash).await,
+			None => Ok(None),
+		}
+	}
+
+	async fn status(&self, ctx: &Context<'_>) -> Result<Option<Long>> {
+		Ok(self
+			.receipt(ctx)
+			.await?
+			.and_then(|receipt| receipt.status_code)
+			.map(|status| Long(status.low_u64())))
+	}
+
+	async fn gas_used(&self, ctx: &Context<'_>) -> Result<Option<Long>> {
+		Ok(self
+			.receipt(ctx)
+			.await?
+			.and_then(|receipt| receipt.gas_used)
+			.map(long))
+	}
+
+	async fn cumulative_gas_used(&self, ctx: &Context<'_>) -> Result<Option<Long>> {
+		Ok(self
+			.receipt(ctx)
+			.await?
+			.map(|receipt| long(receipt.cumulative_gas_used)))
+	}
+
+	async fn created_contract(
+		&self,
+		ctx: &Context<'_>,
+		block: Option<Long>,
+	) -> Result<Option<Account>> {
+		Ok(self
+			.receipt(ctx)
+			.await?
+			.and_then(|receipt| receipt.contract_address)
+			.map(|address| account_at(address, block, self.block_number())))
+	}
+
+	async fn logs(&self, ctx: &Context<'_>) -> Result<Option<Vec<Log>>> {
+		Ok(self
+			.receipt(ctx)
+			.await?
+			.map(|receipt| receipt.logs.into_iter().map(Log).collect()))
+	}
+
+	async fn r(&self) -> BigInt {
+		BigInt(self.0.r)
+	}
+
+	async fn s(&self) -> BigInt {
+		BigInt(self.0.s)
+	}
+
+	async fn v(&self) -> BigInt {
+		BigInt(self.0.v)
+	}
+}
+
+/// Ethereum log.
+pub struct Log(RpcLog);
+
+#[Object]
+impl Log {
+	async fn index(&self) -> Long {
+		long(self.0.log_index.unwrap_or_default())
+	}
+
+	async fn account(&self, block: Option<Long>) -> Account {
+		let default = self
+			.0
+			.block_number
+			.map(|number| BlockNumber::Num(number.low_u64()))
+			.unwrap_or(BlockNumber::Latest);
+		account_at(self.0.address, block, default)
+	}
+
+	async fn topics(&self) -> Vec<Bytes32> {
+		self.0.topics.iter().cloned().map(Bytes32).collect()
+	}
+
+	async fn data(&self) -> Bytes {
+		Bytes(self.0.data.clone())
+	}
+
+	async fn transaction(&self, ctx: &Context<'_>) -> Result<Option<Transaction>> {
+		match self.0.transaction_hash {
+			Some(hash) => transaction_by_hash(ctx, hash).await,
+			None => Ok(None),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema;
+	use jsonrpc_core::{IoHandler, Params};
+
+	const LATEST: u64 = 2048;
+
+	fn rpc_block(number: u64, seal: Value) -> Value {
+		let mut block = json!({
+			"difficulty": "0x0",
+			"extraData": "0x",
+			"gasLimit": "0x1c9c380",
+			"gasUsed": "0x0",
+			"hash": H256::from_low_u64_be(number + 1),
+			"logsBloom": format!("0x{}", "00".repeat(256)),
+			"miner": H160::default(),
+			"number": U256::from(number),
+			"parentHash": H256::from_low_u64_be(number),
+			"receiptsRoot": H256::default(),
+			"sha3Uncles": H256::default(),
+			"size": "0x200",
+			"stateRoot": H256::default(),
+			"timestamp": "0x0",
+			"totalDifficulty": "0x0",
+			"transactions": [],
+			"transactionsRoot": H256::default(),
+			"uncles": [],
+		});
+		block
+			.as_object_mut()
+			.unwrap()
+			.extend(seal.as_object().unwrap().clone());
+		block
+	}
+
+	/// Frontier-like handler, serving the mix hash and the nonce as seal fields.
+	fn handler() -> IoHandler {
+		let mut io = IoHandler::new();
+		io.add_sync_method("eth_blockNumber", |_| Ok(json!(U256::from(LATEST))));
+		io.add_sync_method("eth_getBlockByNumber", |params: Params| {
+			let (number, _): (BlockNumber, bool) = params.parse()?;
+			Ok(match number {
+				BlockNumber::Num(number) if number <= LATEST => rpc_block(
+					number,
+					json!({ "sealFields": [H256::repeat_byte(0x11), "0x0102030405060708"] }),
+				),
+				_ => Value::Null,
+			})
+		});
+		io.add_sync_method("eth_getBlockByHash", |_| {
+			Ok(rpc_block(
+				1,
+				json!({
+					"mixHash": H256::repeat_byte(0x22),
+					"nonce": "0x0807060504030201",
+				}),
+			))
+		});
+		io.add_sync_method("eth_gasPrice", |_| {
+			Err(jsonrpc_core::Error::invalid_params("gas price unavailable"))
+		});
+		io
+	}
+
+	fn execute(query: &str) -> async_graphql::Response {
+		let schema = schema(Arc::new(handler()));
+		futures::executor::block_on(schema.execute(query))
+	}
+
+	fn data(query: &str) -> Value {
+		let response = execute(query);
+		assert!(response.errors.is_empty(), "{:?}", response.errors);
+		serde_json::to_value(&response.data).unwrap()
+	}
+
+	#[test]
+	fn block_seal_fields_are_served() {
+		assert_eq!(
+			data("{ block(number: 1) { mixHash nonce } }"),
+			json!({
+				"block": {
+					"mixHash": H256::repeat_byte(0x11),
+					"nonce": "0x0102030405060708",
+				}
+			}),
+		);
+	}
+
+	#[test]
+	fn geth_like_block_seal_fields_are_served() {
+		let hash = H256::from_low_u64_be(2);
+		assert_eq!(
+			data(&format!(
+				"{{ block(hash: \"{:?}\") {{ mixHash nonce }} }}",
+				hash
+			)),
+			json!({
+				"block": {
+					"mixHash": H256::repeat_byte(0x22),
+					"nonce": "0x0807060504030201",
+				}
+			}),
+		);
+	}
+
+	#[test]
+	fn blocks_are_returned_up_to_the_latest_block() {
+		assert_eq!(
+			data(&format!(
+				"{{ blocks(from: {}, to: {}) {{ number }} }}",
+				LATEST - 1,
+				LATEST + 10
+			)),
+			json!({ "blocks": [{ "number": LATEST - 1 }, { "number": LATEST }] }),
+		);
+		assert_eq!(
+			data("{ blocks(from: 10, to: 5) { number } }"),
+			json!({ "blocks": [] }),
+		);
+	}
+
+	#[test]
+	fn blocks_range_is_capped() {
+		let last = MAX_BLOCK_RANGE - 1;
+		let blocks = data(&format!("{{ blocks(from: 0, to: {}) {{ number }} }}", last));
+		assert_eq!(
+			blocks["blocks"].as_array().unwrap().len() as u64,
+			MAX_BLOCK_RANGE
+		);
+
+		let response = execute(&format!(
+			"{{ blocks(from: 0, to: {}) {{ number }} }}",
+			MAX_BLOCK_RANGE
+		));
+		assert_eq!(response.errors.len(), 1);
+		assert!(response.errors[0].message.contains("block range too large"));
+	}
+
+	#[test]
+	fn rpc_errors_are_returned() {
+		let response = execute("{ gasPrice }");
+		assert_eq!(response.errors.len(), 1);
+		assert_eq!(response.errors[0].message, "gas price unavailable");
+	}
+}
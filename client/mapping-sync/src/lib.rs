@@ -16,8 +16,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+mod log_indexer;
 mod worker;
 
+pub use log_indexer::{log_index_commitment, LogIndexerWorker};
 pub use worker::{MappingSyncWorker, SyncStrategy};
 
 use fp_consensus::FindLogError;
@@ -27,12 +29,18 @@ use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, Header as HeaderT, Zero},
+	traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto, Zero},
 };
 
+/// Maximum number of blocks whose transactions are removed from the index per synced block,
+/// spreading the pruning of a long history over several rounds.
+const PRUNE_LIMIT: u64 = 256;
+
+/// Syncs the mapping of a block, indexing its transactions by hash if `index_transactions`.
 pub fn sync_block<Block: BlockT>(
 	backend: &fc_db::Backend<Block>,
 	header: &Block::Header,
+	index_transactions: bool,
 ) -> Result<(), String> {
 	match fp_consensus::find_log(header.digest()) {
 		Ok(log) => {
@@ -41,7 +49,11 @@ pub fn sync_block<Block: BlockT>(
 			let mapping_commitment = fc_db::MappingCommitment {
 				block_hash: header.hash(),
 				ethereum_block_hash: post_hashes.block_hash,
-				ethereum_transaction_hashes: post_hashes.transaction_hashes,
+				ethereum_transaction_hashes: if index_transactions {
+					post_hashes.transaction_hashes
+				} else {
+					Vec::new()
+				},
 			};
 			backend.mapping().write_hashes(mapping_commitment)?;
 
@@ -94,11 +106,85 @@ where
 	Ok(())
 }
 
+/// Moves the tail of the transaction index after the sync of the block `number`.
+///
+/// Until the tail is known, the sync walks back from the tips and the index is complete once
+/// it reaches the genesis, the first block out of the `tx_index_history` latest blocks, or a
+/// block synced before the tail was recorded. Then the transactions of the blocks falling out
+/// of the history are removed from the index, at most `PRUNE_LIMIT` blocks at a time.
+fn update_transaction_index_tail<Block: BlockT, B>(
+	substrate_backend: &B,
+	frontier_backend: &fc_db::Backend<Block>,
+	header: &Block::Header,
+	best_number: u64,
+	tx_index_history: Option<u64>,
+) -> Result<(), String>
+where
+	B: sp_blockchain::HeaderBackend<Block>,
+{
+	let number: u64 = (*header.number()).unique_saturated_into();
+	let tail = match frontier_backend.meta().transaction_index_tail()? {
+		Some(tail) => tail,
+		None => {
+			let tail = if tx_index_history.map_or(false, |history| number + history <= best_number)
+			{
+				Some(number + 1)
+			} else if number == 0 || frontier_backend.mapping().is_synced(header.parent_hash())? {
+				Some(0)
+			} else {
+				None
+			};
+			if let Some(tail) = tail {
+				frontier_backend.meta().write_transaction_index_tail(tail)?;
+			}
+			return Ok(());
+		}
+	};
+
+	let history = match tx_index_history {
+		Some(history) => history,
+		None => return Ok(()),
+	};
+	let new_tail = (best_number + 1)
+		.saturating_sub(history)
+		.min(tail + PRUNE_LIMIT);
+	if new_tail <= tail {
+		return Ok(());
+	}
+	for number in tail..new_tail {
+		let hash = match substrate_backend
+			.hash(number.unique_saturated_into())
+			.map_err(|e| format!("{:?}", e))?
+		{
+			Some(hash) => hash,
+			None => continue,
+		};
+		let header = match substrate_backend
+			.header(BlockId::Hash(hash))
+			.map_err(|e| format!("{:?}", e))?
+		{
+			Some(header) => header,
+			None => continue,
+		};
+		if let Ok(log) = fp_consensus::find_log(header.digest()) {
+			frontier_backend
+				.mapping()
+				.remove_transaction_hashes(hash, log.into_hashes().transaction_hashes)?;
+		}
+	}
+	frontier_backend
+		.meta()
+		.write_transaction_index_tail(new_tail)?;
+
+	Ok(())
+}
+
 pub fn sync_one_block<Block: BlockT, C, B>(
 	client: &C,
 	substrate_backend: &B,
 	frontier_backend: &fc_db::Backend<Block>,
 	strategy: SyncStrategy,
+	tx_index_history: Option<u64>,
 ) -> Result<bool, String>
 where
 	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
@@ -144,8 +230,16 @@ where
 		.map_err(|e| format!("{:?}", e))?
 		.ok_or("Header not found".to_string())?;
 
+	let best_number: u64 = client.info().best_number.unique_saturated_into();
 	if operating_header.number() == &Zero::zero() {
 		sync_genesis_block(client, frontier_backend, &operating_header)?;
+		update_transaction_index_tail(
+			substrate_backend,
+			frontier_backend,
+			&operating_header,
+			best_number,
+			tx_index_history,
+		)?;
 
 		frontier_backend
 			.meta()
@@ -157,7 +251,17 @@ where
 		{
 			return Ok(false);
 		}
-		sync_block(frontier_backend, &operating_header)?;
+		let number: u64 = (*operating_header.number()).unique_saturated_into();
+		let index_transactions =
+			tx_index_history.map_or(true, |history| number + history > best_number);
+		sync_block(frontier_backend, &operating_header, index_transactions)?;
+		update_transaction_index_tail(
+			substrate_backend,
+			frontier_backend,
+			&operating_header,
+			best_number,
+			tx_index_history,
+		)?;
 
 		current_syncing_tips.push(*operating_header.parent_hash());
 		frontier_backend
@@ -173,6 +277,7 @@ pub fn sync_blocks<Block: BlockT, C, B>(
 	frontier_backend: &fc_db::Backend<Block>,
 	limit: usize,
 	strategy: SyncStrategy,
+	tx_index_history: Option<u64>,
 ) -> Result<bool, String>
 where
 	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
@@ -182,8 +287,14 @@ where
 	let mut synced_any = false;
 
 	for _ in 0..limit {
-		synced_any =
-			synced_any || sync_one_block(client, substrate_backend, frontier_backend, strategy)?;
+		synced_any = synced_any
+			|| sync_one_block(
+				client,
+				substrate_backend,
+				frontier_backend,
+				strategy,
+				tx_index_history,
+			)?;
 	}
 
 	Ok(synced_any)
@@ -0,0 +1,430 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Worker writing the logs of the best chain to the SQL log index.
+//!
+//! It runs apart from the mapping sync, with its own checkpoint, so the log index lagging
+//! behind never delays the hash mappings. On its first run it starts from the best block, the
+//! older blocks being left to a backfill. It then indexes the best chain forward from its
+//! checkpoint, `BATCH_SIZE` blocks per database transaction. On re-organisations it flips the
+//! canonical flags of the retracted and enacted blocks, indexing the enacted ones it missed.
+//! With a history window, it prunes the blocks older than the window from the index.
+
+use fp_rpc::{EthereumRuntimeRPCApi, InternalTransfer, TransactionStatus};
+use futures::{
+	prelude::*,
+	task::{Context, Poll},
+};
+use futures_timer::Delay;
+use log::debug;
+use sc_client_api::ImportNotifications;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Hash as HashT, Header as HeaderT, Keccak256, UniqueSaturatedInto},
+};
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+/// Maximum number of blocks of the best chain indexed per database transaction.
+const BATCH_SIZE: u64 = 64;
+
+/// Maximum number of block numbers pruned from the index per database transaction.
+const PRUNE_BATCH_SIZE: u64 = 1024;
+
+/// Logs, token transfers, contract creations and internal transfers of the block `hash` to
+/// index, None if it has no Ethereum block. Only the contracts created by the transactions
+/// themselves are indexed, the ones created by contracts being only known by tracing the
+/// transactions. The internal transfers are the ones the runtime records, if any.
+pub fn log_index_commitment<Block: BlockT, C>(
+	client: &C,
+	hash: Block::Hash,
+) -> Result<Option<fc_db::LogIndexCommitment<Block>>, String>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	let id = BlockId::Hash(hash);
+	let header = match client.header(id).map_err(|e| format!("{:?}", e))? {
+		Some(header) => header,
+		None => return Ok(None),
+	};
+	let ethereum_block_hash = match fp_consensus::find_log(header.digest()) {
+		Ok(log) => log.into_hashes().block_hash,
+		Err(_) => return Ok(None),
+	};
+	let statuses = client
+		.runtime_api()
+		.current_transaction_statuses(&id)
+		.map_err(|e| format!("{:?}", e))?
+		.unwrap_or_default();
+
+	// The init code is read from the block when it created contracts.
+	let block = if statuses
+		.iter()
+		.any(|status| status.contract_address.is_some())
+	{
+		client
+			.runtime_api()
+			.current_block(&id)
+			.map_err(|e| format!("{:?}", e))?
+	} else {
+		None
+	};
+	let internal_transfers = match client
+		.runtime_api()
+		.api_version::<dyn EthereumRuntimeRPCApi<Block>>(&id)
+		.map_err(|e| format!("{:?}", e))?
+	{
+		Some(version) if version >= 14 => client
+			.runtime_api()
+			.internal_transfers(&id)
+			.map_err(|e| format!("{:?}", e))?,
+		_ => Vec::new(),
+	};
+	let internal_transfers = internal_transfer_entries(&statuses, internal_transfers);
+
+	let mut contract_creations = Vec::new();
+	let mut logs = Vec::new();
+	let mut token_transfers = Vec::new();
+	for status in statuses {
+		let transaction = block
+			.as_ref()
+			.and_then(|block| block.transactions.get(status.transaction_index as usize));
+		if let (Some(address), Some(transaction)) = (status.contract_address, transaction) {
+			contract_creations.push(fc_db::ContractCreationEntry {
+				address,
+				transaction_hash: status.transaction_hash,
+				transaction_index: status.transaction_index,
+				creator: status.from,
+				init_code_hash: Keccak256::hash(&transaction.input),
+			});
+		}
+		for (transaction_log_index, log) in status.logs.into_iter().enumerate() {
+			let position = fc_db::TransferLogPosition {
+				transaction_hash: status.transaction_hash,
+				transaction_index: status.transaction_index,
+				log_index: logs.len() as u32,
+			};
+			token_transfers.extend(fc_db::decode_token_transfers(
+				position,
+				log.address,
+				&log.topics,
+				&log.data,
+			));
+			logs.push(fc_db::LogIndexEntry {
+				transaction_index: status.transaction_index,
+				log_index: position.log_index,
+				transaction_log_index: transaction_log_index as u32,
+				address: log.address,
+				topics: log.topics,
+			});
+		}
+	}
+	Ok(Some(fc_db::LogIndexCommitment {
+		block_hash: hash,
+		ethereum_block_hash,
+		block_number: (*header.number()).unique_saturated_into(),
+		logs,
+		token_transfers,
+		contract_creations,
+		internal_transfers,
+	}))
+}
+
+/// Internal transfers recorded by the runtime to index, numbered in the transfers of their
+/// transaction. The ones of unknown transactions are skipped.
+fn internal_transfer_entries(
+	statuses: &[TransactionStatus],
+	internal_transfers: Vec<InternalTransfer>,
+) -> Vec<fc_db::InternalTransferEntry> {
+	let mut entries: Vec<fc_db::InternalTransferEntry> = Vec::new();
+	for transfer in internal_transfers {
+		let transaction_hash = match statuses.get(transfer.transaction_index as usize) {
+			Some(status) => status.transaction_hash,
+			None => continue,
+		};
+		// The transfers of a transaction follow each other, in execution order.
+		let transfer_index = entries
+			.iter()
+			.rev()
+			.take_while(|entry| entry.transaction_index == transfer.transaction_index)
+			.count() as u32;
+		entries.push(fc_db::InternalTransferEntry {
+			transaction_hash,
+			transaction_index: transfer.transaction_index,
+			transfer_index,
+			from: transfer.from,
+			to: transfer.to,
+			value: transfer.value,
+		});
+	}
+	entries
+}
+
+/// Flags the blocks `retracted` and `enacted` by re-organisations, indexes the enacted
+/// blocks not indexed yet and the next blocks of the best chain from the checkpoint, and prunes
+/// the blocks older than the latest `history_window` blocks, returning whether blocks remain
+/// to index or to prune.
+fn index_logs<Block: BlockT, C>(
+	client: &C,
+	log_index: &fc_db::LogIndexDb<Block>,
+	retracted: Vec<Block::Hash>,
+	enacted: Vec<Block::Hash>,
+	history_window: Option<u64>,
+) -> Result<bool, String>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	let best_number: u64 = client.info().best_number.unique_saturated_into();
+	let from = log_index.checkpoint()?.unwrap_or(best_number);
+	let to = best_number.min(from.saturating_add(BATCH_SIZE - 1));
+
+	let mut hashes = Vec::new();
+	let mut indexed_enacted = Vec::new();
+	for hash in enacted {
+		if log_index.is_indexed(&hash)? {
+			indexed_enacted.push(hash);
+		} else {
+			hashes.push(hash);
+		}
+	}
+	for number in from..=to {
+		if let Some(hash) = client
+			.hash(number.unique_saturated_into())
+			.map_err(|e| format!("{:?}", e))?
+		{
+			hashes.push(hash);
+		}
+	}
+
+	// The blocks which cannot be indexed, their state being pruned, are scanned by the queries.
+	let mut commitments = Vec::new();
+	for hash in hashes {
+		match log_index_commitment::<Block, C>(client, hash) {
+			Ok(Some(commitment)) => commitments.push(commitment),
+			Ok(None) => (),
+			Err(e) => debug!(
+				target: "log-indexer",
+				"Indexing the logs of {:?} failed with error {:?}.",
+				hash,
+				e
+			),
+		}
+	}
+
+	let checkpoint = from.max(to.saturating_add(1));
+	log_index.write_batch(&retracted, &indexed_enacted, commitments, Some(checkpoint))?;
+	let have_pruning = match history_window {
+		Some(window) => log_index.prune(best_number.saturating_sub(window), PRUNE_BATCH_SIZE)?,
+		None => false,
+	};
+	Ok(checkpoint <= best_number || have_pruning)
+}
+
+pub struct LogIndexerWorker<Block: BlockT, C> {
+	import_notifications: ImportNotifications<Block>,
+	timeout: Duration,
+	inner_delay: Option<Delay>,
+
+	client: Arc<C>,
+	frontier_backend: Arc<fc_db::Backend<Block>>,
+	history_window: Option<u64>,
+
+	have_next: bool,
+	retracted: Vec<Block::Hash>,
+	enacted: Vec<Block::Hash>,
+}
+
+impl<Block: BlockT, C> LogIndexerWorker<Block, C> {
+	/// Indexes the best chain, keeping the latest `history_window` blocks only if any.
+	pub fn new(
+		import_notifications: ImportNotifications<Block>,
+		timeout: Duration,
+		client: Arc<C>,
+		frontier_backend: Arc<fc_db::Backend<Block>>,
+		history_window: Option<u64>,
+	) -> Self {
+		Self {
+			import_notifications,
+			timeout,
+			inner_delay: None,
+
+			client,
+			frontier_backend,
+			history_window,
+
+			have_next: true,
+			retracted: Vec::new(),
+			enacted: Vec::new(),
+		}
+	}
+}
+
+impl<Block: BlockT, C> Stream for LogIndexerWorker<Block, C>
+where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block>,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	type Item = ();
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<()>> {
+		let mut fire = false;
+
+		loop {
+			match Stream::poll_next(Pin::new(&mut self.import_notifications), cx) {
+				Poll::Pending => break,
+				Poll::Ready(Some(notification)) => {
+					fire = true;
+					// The tree route goes from the previous best block to the parent of the
+					// imported one.
+					match notification.tree_route.as_ref() {
+						Some(route)
+							if notification.is_new_best && !route.retracted().is_empty() =>
+						{
+							let retracted: Vec<_> =
+								route.retracted().iter().map(|block| block.hash).collect();
+							let enacted: Vec<_> =
+								route.enacted().iter().map(|block| block.hash).collect();
+							self.retracted.extend(retracted);
+							self.enacted.extend(enacted);
+							self.enacted.push(notification.hash);
+						}
+						_ => (),
+					}
+				}
+				Poll::Ready(None) => return Poll::Ready(None),
+			}
+		}
+
+		let timeout = self.timeout;
+		let inner_delay = self.inner_delay.get_or_insert_with(|| Delay::new(timeout));
+
+		match Future::poll(Pin::new(inner_delay), cx) {
+			Poll::Pending => (),
+			Poll::Ready(()) => {
+				fire = true;
+			}
+		}
+
+		if self.have_next {
+			fire = true;
+		}
+
+		if fire {
+			self.inner_delay = None;
+
+			let log_index = match self.frontier_backend.log_index() {
+				Some(log_index) => log_index.clone(),
+				None => return Poll::Ready(None),
+			};
+			let retracted = std::mem::take(&mut self.retracted);
+			let enacted = std::mem::take(&mut self.enacted);
+			match index_logs(
+				self.client.as_ref(),
+				log_index.as_ref(),
+				retracted,
+				enacted,
+				self.history_window,
+			) {
+				Ok(have_next) => {
+					self.have_next = have_next;
+					Poll::Ready(Some(()))
+				}
+				Err(e) => {
+					self.have_next = false;
+					debug!(target: "log-indexer", "Indexing failed with error {:?}, retrying.", e);
+					Poll::Ready(Some(()))
+				}
+			}
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn status(transaction_index: u32) -> TransactionStatus {
+		TransactionStatus {
+			transaction_hash: Keccak256::hash(&transaction_index.to_be_bytes()),
+			transaction_index,
+			..Default::default()
+		}
+	}
+
+	fn transfer(transaction_index: u32, value: u64) -> InternalTransfer {
+		InternalTransfer {
+			transaction_index,
+			from: Default::default(),
+			to: Default::default(),
+			value: value.into(),
+		}
+	}
+
+	fn entry(
+		transaction_index: u32,
+		transfer_index: u32,
+		value: u64,
+	) -> fc_db::InternalTransferEntry {
+		fc_db::InternalTransferEntry {
+			transaction_hash: status(transaction_index).transaction_hash,
+			transaction_index,
+			transfer_index,
+			from: Default::default(),
+			to: Default::default(),
+			value: value.into(),
+		}
+	}
+
+	#[test]
+	fn internal_transfers_are_numbered_in_their_transaction() {
+		let statuses = vec![status(0), status(1), status(2)];
+		let transfers = vec![
+			transfer(0, 1),
+			transfer(0, 2),
+			transfer(2, 3),
+			transfer(2, 4),
+			transfer(2, 5),
+		];
+		assert_eq!(
+			internal_transfer_entries(&statuses, transfers),
+			vec![
+				entry(0, 0, 1),
+				entry(0, 1, 2),
+				entry(2, 0, 3),
+				entry(2, 1, 4),
+				entry(2, 2, 5),
+			]
+		);
+	}
+
+	#[test]
+	fn internal_transfers_of_unknown_transactions_are_skipped() {
+		let statuses = vec![status(0)];
+		let transfers = vec![transfer(0, 1), transfer(1, 2), transfer(0, 3)];
+		assert_eq!(
+			internal_transfer_entries(&statuses, transfers),
+			vec![entry(0, 0, 1), entry(0, 1, 3)]
+		);
+		assert!(internal_transfer_entries(&[], vec![transfer(0, 1)]).is_empty());
+	}
+}
@@ -49,6 +49,7 @@ pub struct MappingSyncWorker<Block: BlockT, C, B> {
 	have_next: bool,
 
 	strategy: SyncStrategy,
+	tx_index_history: Option<u64>,
 }
 
 impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
@@ -59,6 +60,7 @@ impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
 		substrate_backend: Arc<B>,
 		frontier_backend: Arc<fc_db::Backend<Block>>,
 		strategy: SyncStrategy,
+		tx_index_history: Option<u64>,
 	) -> Self {
 		Self {
 			import_notifications,
@@ -72,6 +74,7 @@ impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
 			have_next: true,
 
 			strategy,
+			tx_index_history,
 		}
 	}
 }
@@ -120,6 +123,7 @@ where
 				self.frontier_backend.as_ref(),
 				LIMIT,
 				self.strategy,
+				self.tx_index_history,
 			) {
 				Ok(have_next) => {
 					self.have_next = have_next;
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Ethereum execution-apis conformance of the Frontier RPC.
+//!
+//! [`Specification`] holds the result schemas of the execution-apis OpenRPC specification,
+//! [`cases`] the curated requests checked against it and [`Compliance`] the compliance of
+//! each method, printed once all the requests ran so that a failure does not hide the others.
+
+use jsonschema::JSONSchema;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+/// Environment variable giving the path of the `openrpc.json` built from execution-apis.
+pub const SPEC_ENV: &str = "EXECUTION_APIS_SPEC";
+
+/// Request whose result is checked against the specification.
+pub struct Case {
+	/// RPC method.
+	pub method: &'static str,
+	/// Parameters of the request.
+	pub params: Value,
+	/// Check of the result on top of its schema.
+	pub check: Option<fn(&Value) -> Result<(), String>>,
+}
+
+impl Case {
+	fn new(method: &'static str, params: Value) -> Self {
+		Self {
+			method,
+			params,
+			check: None,
+		}
+	}
+
+	fn check(mut self, check: fn(&Value) -> Result<(), String>) -> Self {
+		self.check = Some(check);
+		self
+	}
+}
+
+/// Chain data the cases refer to: a block holding a transfer from a funded account.
+pub struct Fixture {
+	/// Funded account, sender of the transfer.
+	pub account: Value,
+	/// Recipient of the transfer.
+	pub recipient: Value,
+	/// Hash of the transfer.
+	pub transaction_hash: Value,
+	/// Hash of the block holding the transfer.
+	pub block_hash: Value,
+}
+
+fn is_null(result: &Value) -> Result<(), String> {
+	if result.is_null() {
+		Ok(())
+	} else {
+		Err(format!("expected null, got {}", result))
+	}
+}
+
+/// Curated requests, run once the block of the fixture is the latest one.
+pub fn cases(fixture: &Fixture) -> Vec<Case> {
+	let Fixture {
+		account,
+		recipient,
+		transaction_hash,
+		block_hash,
+	} = fixture;
+	let transfer = json!({ "from": account, "to": recipient, "value": "0x1" });
+
+	vec![
+		Case::new("eth_blockNumber", json!([])),
+		Case::new("eth_chainId", json!([])).check(|result| {
+			if result == "0x2a" {
+				Ok(())
+			} else {
+				Err(format!("expected the dev chain id 0x2a, got {}", result))
+			}
+		}),
+		Case::new("eth_gasPrice", json!([])),
+		Case::new("eth_maxPriorityFeePerGas", json!([])),
+		Case::new("eth_feeHistory", json!(["0x1", "latest", [50]])),
+		Case::new("eth_syncing", json!([])),
+		Case::new("eth_coinbase", json!([])),
+		Case::new("eth_accounts", json!([])),
+		Case::new("eth_getBalance", json!([account, "latest"])),
+		Case::new("eth_getTransactionCount", json!([account, "latest"])),
+		Case::new("eth_getCode", json!([account, "latest"])),
+		Case::new("eth_getStorageAt", json!([account, "0x0", "latest"])),
+		Case::new("eth_getBlockByNumber", json!(["latest", false])),
+		Case::new("eth_getBlockByNumber", json!(["latest", true])),
+		Case::new("eth_getBlockByNumber", json!(["0xffffff", false])).check(is_null),
+		Case::new("eth_getBlockByHash", json!([block_hash, true])),
+		Case::new("eth_getBlockTransactionCountByNumber", json!(["latest"])),
+		Case::new("eth_getBlockTransactionCountByHash", json!([block_hash])),
+		Case::new("eth_getUncleCountByBlockNumber", json!(["latest"])),
+		Case::new("eth_getTransactionByHash", json!([transaction_hash])),
+		Case::new(
+			"eth_getTransactionByBlockNumberAndIndex",
+			json!(["latest", "0x0"]),
+		),
+		Case::new(
+			"eth_getTransactionByBlockHashAndIndex",
+			json!([block_hash, "0x0"]),
+		),
+		Case::new("eth_getTransactionReceipt", json!([transaction_hash])),
+		Case::new(
+			"eth_getTransactionReceipt",
+			json!(["0x0000000000000000000000000000000000000000000000000000000000000000"]),
+		)
+		.check(is_null),
+		Case::new(
+			"eth_getLogs",
+			json!([{ "fromBlock": "0x0", "toBlock": "latest" }]),
+		),
+		Case::new(
+			"eth_call",
+			json!([{ "from": account, "to": recipient, "data": "0x" }, "latest"]),
+		),
+		Case::new("eth_estimateGas", json!([transfer])),
+		Case::new("eth_createAccessList", json!([transfer, "latest"])),
+	]
+}
+
+/// Result schemas of the methods of the specification.
+pub struct Specification {
+	methods: BTreeMap<String, Value>,
+	components: Value,
+}
+
+impl Specification {
+	/// Reads the result schemas of the OpenRPC document.
+	pub fn from_json(spec: &Value) -> Result<Self, String> {
+		let methods = spec["methods"]
+			.as_array()
+			.ok_or("the specification has no methods")?
+			.iter()
+			.filter_map(|method| {
+				let name = method["name"].as_str()?;
+				Some((name.to_string(), method["result"]["schema"].clone()))
+			})
+			.collect();
+
+		Ok(Self {
+			methods,
+			components: spec["components"].clone(),
+		})
+	}
+
+	/// Reads the OpenRPC document at the given path.
+	pub fn load(path: &Path) -> Result<Self, String> {
+		let spec = fs::read_to_string(path)
+			.map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+		let spec = serde_json::from_str(&spec)
+			.map_err(|err| format!("invalid specification {}: {}", path.display(), err))?;
+		Self::from_json(&spec)
+	}
+
+	/// Checks the result of the method against its schema.
+	pub fn validate(&self, method: &str, result: &Value) -> Result<(), String> {
+		let mut schema = self
+			.methods
+			.get(method)
+			.ok_or_else(|| format!("{} is missing from the specification", method))?
+			.clone();
+		// The schema references resolve against the components of the specification.
+		if let Value::Object(schema) = &mut schema {
+			schema.insert("components".to_string(), self.components.clone());
+		}
+
+		let schema = JSONSchema::compile(&schema)
+			.map_err(|err| format!("invalid schema of {}: {}", method, err))?;
+		// Collected before returning, the errors borrow the compiled schema.
+		let result = schema.validate(result).map_err(|errors| {
+			errors
+				.map(|err| err.to_string())
+				.collect::<Vec<_>>()
+				.join(", ")
+		});
+		result
+	}
+}
+
+/// Passed and failed cases of each method.
+#[derive(Default)]
+pub struct Compliance(BTreeMap<&'static str, (usize, usize)>);
+
+impl Compliance {
+	/// Records the outcome of a case of the method.
+	pub fn record(&mut self, method: &'static str, passed: bool) {
+		let (passes, failures) = self.0.entry(method).or_default();
+		if passed {
+			*passes += 1;
+		} else {
+			*failures += 1;
+		}
+	}
+
+	/// Whether all the cases passed.
+	pub fn is_compliant(&self) -> bool {
+		self.0.values().all(|(_, failures)| *failures == 0)
+	}
+}
+
+impl fmt::Display for Compliance {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "execution-apis compliance:")?;
+		for (method, (passes, failures)) in &self.0 {
+			writeln!(
+				f,
+				"  {} {} ({}/{})",
+				if *failures == 0 { "ok  " } else { "FAIL" },
+				method,
+				passes,
+				passes + failures
+			)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn specification() -> Specification {
+		Specification::from_json(&json!({
+			"methods": [{
+				"name": "eth_blockNumber",
+				"result": {
+					"name": "Block number",
+					"schema": { "$ref": "#/components/schemas/uint" },
+				},
+			}],
+			"components": {
+				"schemas": {
+					"uint": {
+						"type": "string",
+						"pattern": "^0x([1-9a-f]+[0-9a-f]*|0)$",
+					},
+				},
+			},
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn results_are_validated_against_the_referenced_schema() {
+		let spec = specification();
+		assert_eq!(spec.validate("eth_blockNumber", &json!("0x2a")), Ok(()));
+		assert!(spec.validate("eth_blockNumber", &json!("0x02a")).is_err());
+		assert!(spec.validate("eth_blockNumber", &json!(42)).is_err());
+	}
+
+	#[test]
+	fn missing_methods_fail() {
+		assert_eq!(
+			specification().validate("eth_chainId", &json!("0x2a")),
+			Err("eth_chainId is missing from the specification".to_string())
+		);
+	}
+
+	#[test]
+	fn compliance_is_reported_per_method() {
+		let mut compliance = Compliance::default();
+		compliance.record("eth_getBlockByNumber", true);
+		compliance.record("eth_getBlockByNumber", false);
+		compliance.record("eth_blockNumber", true);
+		assert!(!compliance.is_compliant());
+		assert_eq!(
+			compliance.to_string(),
+			"execution-apis compliance:\n  ok   eth_blockNumber (1/1)\n  FAIL eth_getBlockByNumber (1/2)\n"
+		);
+	}
+}
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Admin rpc interface, the subset of the geth admin namespace describing the network.
+use crate::types::{AdminNodeInfo, AdminPeerInfo};
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+
+pub use rpc_impl_AdminApi::gen_server::AdminApi as AdminApiServer;
+
+/// Admin rpc interface.
+#[rpc(server)]
+pub trait AdminApi {
+	/// Returns the connected peers.
+	#[rpc(name = "admin_peers")]
+	fn peers(&self) -> BoxFuture<Result<Vec<AdminPeerInfo>>>;
+
+	/// Returns the identity, addresses and chain of the node.
+	#[rpc(name = "admin_nodeInfo")]
+	fn node_info(&self) -> BoxFuture<Result<AdminNodeInfo>>;
+}
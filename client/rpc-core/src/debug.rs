@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Debug rpc interface.
+use crate::types::{
+	AccountRange, BadBlock, BlockNumber, BundlerTrace, Bytes, CallRequest, StateDump,
+	TraceCallOptions,
+};
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+
+pub use rpc_impl_DebugApi::gen_server::DebugApi as DebugApiServer;
+
+/// Debug rpc interface.
+#[rpc(server)]
+pub trait DebugApi {
+	/// Returns a page of the accounts at a block, ordered by key and starting from the `start`
+	/// key, with at most `max_results` accounts. Unsafe method.
+	#[rpc(name = "debug_accountRange")]
+	fn account_range(
+		&self,
+		number: BlockNumber,
+		start: Bytes,
+		max_results: u64,
+		no_code: bool,
+		no_storage: bool,
+	) -> BoxFuture<Result<AccountRange>>;
+
+	/// Returns the accounts at a block, with their code and storage. Large states are dumped in
+	/// pages, the next one starting from the `next` key of the previous. Unsafe method.
+	#[rpc(name = "debug_dumpBlock")]
+	fn dump_block(&self, number: BlockNumber, start: Option<Bytes>)
+		-> BoxFuture<Result<StateDump>>;
+
+	/// Returns the latest blocks rejected on import with the reason, the most recent first.
+	#[rpc(name = "debug_getBadBlocks")]
+	fn bad_blocks(&self) -> Result<Vec<BadBlock>>;
+
+	/// Executes a call at a block and returns its trace by the given tracer. Only the
+	/// `bundlerCollectorTracer` of the ERC-4337 bundlers is supported.
+	#[rpc(name = "debug_traceCall")]
+	fn trace_call(
+		&self,
+		request: CallRequest,
+		number: BlockNumber,
+		options: Option<TraceCallOptions>,
+	) -> Result<BundlerTrace>;
+}
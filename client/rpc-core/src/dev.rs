@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Dev rpc interface, compatible with the Hardhat and Ganache test helpers.
+use crate::types::Bytes;
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+
+pub use rpc_impl_DevApi::gen_server::DevApi as DevApiServer;
+
+/// Dev rpc interface.
+#[rpc(server)]
+pub trait DevApi {
+	/// Seals a new block, optionally with the given timestamp in seconds.
+	#[rpc(name = "evm_mine")]
+	fn mine(&self, timestamp: Option<U256>) -> BoxFuture<Result<U256>>;
+
+	/// Sets the timestamp in seconds of the next sealed block.
+	#[rpc(name = "evm_setNextBlockTimestamp")]
+	fn set_next_block_timestamp(&self, timestamp: U256) -> Result<U256>;
+
+	/// Moves the time of the next sealed blocks forward, returns the total time increase.
+	#[rpc(name = "evm_increaseTime")]
+	fn increase_time(&self, seconds: U256) -> Result<U256>;
+
+	/// Records the current best block, returns the snapshot id.
+	#[rpc(name = "evm_snapshot")]
+	fn snapshot(&self) -> Result<U256>;
+
+	/// Reverts the chain to the given snapshot, discarding it and the later ones.
+	#[rpc(name = "evm_revert")]
+	fn revert(&self, id: U256) -> Result<bool>;
+
+	/// Sets the balance of an account.
+	#[rpc(name = "hardhat_setBalance")]
+	fn set_balance(&self, address: H160, balance: U256) -> BoxFuture<Result<bool>>;
+
+	/// Replaces the code of an account.
+	#[rpc(name = "hardhat_setCode")]
+	fn set_code(&self, address: H160, code: Bytes) -> BoxFuture<Result<bool>>;
+
+	/// Sets a storage slot of an account.
+	#[rpc(name = "hardhat_setStorageAt")]
+	fn set_storage_at(&self, address: H160, index: U256, value: H256) -> BoxFuture<Result<bool>>;
+
+	/// Allows sending transactions from the given account without its key.
+	#[rpc(name = "hardhat_impersonateAccount")]
+	fn impersonate_account(&self, address: H160) -> Result<bool>;
+
+	/// Stops impersonating the given account.
+	#[rpc(name = "hardhat_stopImpersonatingAccount")]
+	fn stop_impersonating_account(&self, address: H160) -> Result<bool>;
+}
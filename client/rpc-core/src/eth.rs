@@ -23,8 +23,9 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
 use crate::types::{
-	BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index, Log, Receipt, RichBlock,
-	SyncStatus, Transaction, TransactionRequest, Work,
+	AccessListResult, BlockNumber, Bytes, CallRequest, ChainConfig, FeeHistory, Filter,
+	FilterChanges, Index, Log, Receipt, RichBlock, SyncStatus, Transaction, TransactionRequest,
+	Work,
 };
 pub use rpc_impl_EthApi::gen_server::EthApi as EthApiServer;
 pub use rpc_impl_EthFilterApi::gen_server::EthFilterApi as EthFilterApiServer;
@@ -58,10 +59,28 @@ pub trait EthApi {
 	#[rpc(name = "eth_chainId")]
 	fn chain_id(&self) -> Result<Option<U64>>;
 
+	/// Returns the Ethereum-level configuration of the chain at the current best block.
+	#[rpc(name = "eth_chainConfig")]
+	fn chain_config(&self) -> Result<ChainConfig>;
+
 	/// Returns current gas_price.
 	#[rpc(name = "eth_gasPrice")]
 	fn gas_price(&self) -> Result<U256>;
 
+	/// Returns the base fee, gas used ratio and the priority fees at the given reward
+	/// percentiles of a range of blocks.
+	#[rpc(name = "eth_feeHistory")]
+	fn fee_history(
+		&self,
+		_: U256,
+		_: BlockNumber,
+		_: Option<Vec<f64>>,
+	) -> BoxFuture<Result<FeeHistory>>;
+
+	/// Returns a suggestion of priority fee per gas.
+	#[rpc(name = "eth_maxPriorityFeePerGas")]
+	fn max_priority_fee_per_gas(&self) -> Result<U256>;
+
 	/// Returns accounts list.
 	#[rpc(name = "eth_accounts")]
 	fn accounts(&self) -> Result<Vec<H160>>;
@@ -80,11 +99,11 @@ pub trait EthApi {
 
 	/// Returns block with given hash.
 	#[rpc(name = "eth_getBlockByHash")]
-	fn block_by_hash(&self, _: H256, _: bool) -> Result<Option<RichBlock>>;
+	fn block_by_hash(&self, _: H256, _: bool) -> BoxFuture<Result<Option<RichBlock>>>;
 
 	/// Returns block with given number.
 	#[rpc(name = "eth_getBlockByNumber")]
-	fn block_by_number(&self, _: BlockNumber, _: bool) -> Result<Option<RichBlock>>;
+	fn block_by_number(&self, _: BlockNumber, _: bool) -> BoxFuture<Result<Option<RichBlock>>>;
 
 	/// Returns the number of transactions sent from given address at given time (block number).
 	#[rpc(name = "eth_getTransactionCount")]
@@ -110,6 +129,10 @@ pub trait EthApi {
 	#[rpc(name = "eth_getCode")]
 	fn code_at(&self, _: H160, _: Option<BlockNumber>) -> Result<Bytes>;
 
+	/// Signs the EIP-191 prefixed data with the given account.
+	#[rpc(name = "eth_sign")]
+	fn sign(&self, _: H160, _: Bytes) -> Result<Bytes>;
+
 	/// Sends transaction; will block waiting for signer to return the
 	/// transaction hash.
 	#[rpc(name = "eth_sendTransaction")]
@@ -121,11 +144,19 @@ pub trait EthApi {
 
 	/// Call contract, returning the output data.
 	#[rpc(name = "eth_call")]
-	fn call(&self, _: CallRequest, _: Option<BlockNumber>) -> Result<Bytes>;
+	fn call(&self, _: CallRequest, _: Option<BlockNumber>) -> BoxFuture<Result<Bytes>>;
 
 	/// Estimate gas needed for execution of given contract.
 	#[rpc(name = "eth_estimateGas")]
-	fn estimate_gas(&self, _: CallRequest, _: Option<BlockNumber>) -> Result<U256>;
+	fn estimate_gas(&self, _: CallRequest, _: Option<BlockNumber>) -> BoxFuture<Result<U256>>;
+
+	/// Returns the access list of a call at a block, with the gas it uses.
+	#[rpc(name = "eth_createAccessList")]
+	fn create_access_list(
+		&self,
+		_: CallRequest,
+		_: Option<BlockNumber>,
+	) -> BoxFuture<Result<AccessListResult>>;
 
 	/// Get transaction by its hash.
 	#[rpc(name = "eth_getTransactionByHash")]
@@ -146,7 +177,7 @@ pub trait EthApi {
 
 	/// Returns transaction receipt by transaction hash.
 	#[rpc(name = "eth_getTransactionReceipt")]
-	fn transaction_receipt(&self, _: H256) -> Result<Option<Receipt>>;
+	fn transaction_receipt(&self, _: H256) -> BoxFuture<Result<Option<Receipt>>>;
 
 	/// Returns an uncles at given block and index.
 	#[rpc(name = "eth_getUncleByBlockHashAndIndex")]
@@ -162,7 +193,7 @@ pub trait EthApi {
 
 	/// Returns logs matching given filter object.
 	#[rpc(name = "eth_getLogs")]
-	fn logs(&self, _: Filter) -> Result<Vec<Log>>;
+	fn logs(&self, _: Filter) -> BoxFuture<Result<Vec<Log>>>;
 
 	/// Returns the hash of the current block, the seedHash, and the boundary condition to be met.
 	#[rpc(name = "eth_getWork")]
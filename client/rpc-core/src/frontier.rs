@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Frontier rpc interface.
+use crate::types::{
+	AccountSummary, BackendInfo, BlockNumber, BlockOrAddress, Bytes, CacheSizes, ContractCreation,
+	Filter, FilterPoolStats, FrontierVersion, Health, HistorySample, InclusionProof,
+	InternalTransaction, LogCursor, LogsPage, MappingSyncHead, PendingRemoval,
+	RawTransactionValidation, ResponseCacheStats, SchemaCacheEntry, SlowQueryStats,
+	TokenTransferCursor, TokenTransferFilter, TokenTransfersPage,
+};
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+pub use rpc_impl_FrontierApi::gen_server::FrontierApi as FrontierApiServer;
+
+/// Frontier rpc interface.
+#[rpc(server)]
+pub trait FrontierApi {
+	/// Returns the health of the frontier subsystems.
+	#[rpc(name = "frontier_health")]
+	fn health(&self) -> Result<Health>;
+
+	/// Returns true when the mapping sync caught up with the best block, fails otherwise.
+	#[rpc(name = "frontier_ready")]
+	fn ready(&self) -> Result<bool>;
+
+	/// Returns the type and path of the frontier backend database.
+	#[rpc(name = "frontier_backend")]
+	fn backend(&self) -> Result<BackendInfo>;
+
+	/// Returns the cached Ethereum storage schemas, by first block.
+	#[rpc(name = "frontier_schemaCache")]
+	fn schema_cache(&self) -> Result<Vec<SchemaCacheEntry>>;
+
+	/// Returns the latest block mapped by the mapping sync.
+	#[rpc(name = "frontier_mappingSyncHead")]
+	fn mapping_sync_head(&self) -> Result<MappingSyncHead>;
+
+	/// Returns the number of stored filters, by type.
+	#[rpc(name = "frontier_filterPool")]
+	fn filter_pool(&self) -> Result<FilterPoolStats>;
+
+	/// Returns the number of entries of the in-memory caches.
+	#[rpc(name = "frontier_cacheSizes")]
+	fn cache_sizes(&self) -> Result<CacheSizes>;
+
+	/// Returns the requests which exceeded the slow query threshold, by method.
+	#[rpc(name = "frontier_slowQueries")]
+	fn slow_queries(&self) -> Result<Vec<SlowQueryStats>>;
+
+	/// Returns the hits and misses of the response cache, by method.
+	#[rpc(name = "frontier_responseCache")]
+	fn response_cache(&self) -> Result<Vec<ResponseCacheStats>>;
+
+	/// Returns the Substrate account id of an EVM address under the address mapping of the
+	/// runtime, which holds its EVM balance.
+	#[rpc(name = "frontier_addressToAccount")]
+	fn address_to_account(&self, address: H160) -> Result<H256>;
+
+	/// Returns the EVM address of a Substrate account id under the withdraw origin of the
+	/// runtime, which the account can deposit to and withdraw from.
+	#[rpc(name = "frontier_accountToAddress")]
+	fn account_to_address(&self, account: H256) -> Result<H160>;
+
+	/// Returns the self-destructed contracts of the best block whose storage is still being
+	/// removed.
+	#[rpc(name = "frontier_pendingRemovals")]
+	fn pending_removals(&self) -> Result<Vec<PendingRemoval>>;
+
+	/// Returns the versions of the node, its runtime and the EVM hard fork it follows.
+	#[rpc(name = "frontier_version")]
+	fn version(&self) -> Result<FrontierVersion>;
+
+	/// Returns a page of the logs matching the filter in ascending order, from the cursor of the
+	/// previous page and with at most `limit` logs, the maximum number of logs in a query by
+	/// default. The page stops early when the query takes too long.
+	#[rpc(name = "frontier_getLogs")]
+	fn logs(
+		&self,
+		filter: Filter,
+		cursor: Option<LogCursor>,
+		limit: Option<u32>,
+	) -> Result<LogsPage>;
+
+	/// Performs the checks of `eth_sendRawTransaction` against the best block, signature
+	/// recovery, chain id, intrinsic gas, gas limit, gas price, balance and nonce, without
+	/// submitting the transaction to the pool.
+	#[rpc(name = "frontier_validateRawTransaction")]
+	fn validate_raw_transaction(&self, bytes: Bytes) -> Result<RawTransactionValidation>;
+
+	/// Proof of the inclusion of a transaction in the `transactionsRoot` of its block, None if
+	/// the transaction is unknown.
+	#[rpc(name = "frontier_getTransactionProof")]
+	fn transaction_proof(&self, hash: H256) -> Result<Option<InclusionProof>>;
+
+	/// Proof of the inclusion of the receipt of a transaction in the `receiptsRoot` of its
+	/// block, None if the transaction is unknown.
+	#[rpc(name = "frontier_getReceiptProof")]
+	fn receipt_proof(&self, hash: H256) -> Result<Option<InclusionProof>>;
+
+	/// Returns the balance of an address at the blocks `fromBlock` to `toBlock`, sampled every
+	/// `step` blocks and at `toBlock`, with at most 1000 samples.
+	#[rpc(name = "frontier_getBalanceHistory")]
+	fn balance_history(
+		&self,
+		address: H160,
+		from_block: BlockNumber,
+		to_block: BlockNumber,
+		step: U256,
+	) -> Result<Vec<HistorySample>>;
+
+	/// Returns the nonce of an address at the blocks `fromBlock` to `toBlock`, sampled every
+	/// `step` blocks and at `toBlock`, with at most 1000 samples.
+	#[rpc(name = "frontier_getNonceHistory")]
+	fn nonce_history(
+		&self,
+		address: H160,
+		from_block: BlockNumber,
+		to_block: BlockNumber,
+		step: U256,
+	) -> Result<Vec<HistorySample>>;
+
+	/// Returns a page of the ERC-20, ERC-721 and ERC-1155 transfers matching the filter in
+	/// ascending order, from the cursor of the previous page and with at most `limit`
+	/// transfers, the maximum number of logs in a query by default. Requires the SQL log index,
+	/// the transfers of the blocks it misses are not returned.
+	#[rpc(name = "frontier_getTokenTransfers")]
+	fn token_transfers(
+		&self,
+		filter: TokenTransferFilter,
+		cursor: Option<TokenTransferCursor>,
+		limit: Option<u32>,
+	) -> Result<TokenTransfersPage>;
+
+	/// Returns the creation of a contract, None if unknown. Requires the SQL log index, and
+	/// only knows the contracts created by the transactions themselves in the indexed blocks.
+	#[rpc(name = "frontier_getContractCreation")]
+	fn contract_creation(&self, address: H160) -> Result<Option<ContractCreation>>;
+
+	/// Returns the value transfers below the top level calls of the transactions of a block, or
+	/// sent or received by an address, in ascending order and at most the maximum number of
+	/// logs in a query. Requires the SQL log index and a runtime recording the transfers, the
+	/// transfers of the blocks the index misses are not returned.
+	#[rpc(name = "frontier_getInternalTransactions")]
+	fn internal_transactions(
+		&self,
+		block_or_address: BlockOrAddress,
+	) -> Result<Vec<InternalTransaction>>;
+
+	/// Returns the balance, nonce, code hash and code size of at most 1000 accounts at a block,
+	/// the latest by default, read in a single runtime API call.
+	#[rpc(name = "frontier_getAccounts")]
+	fn accounts(
+		&self,
+		addresses: Vec<H160>,
+		number: Option<BlockNumber>,
+	) -> Result<Vec<AccountSummary>>;
+}
@@ -18,12 +18,26 @@
 
 pub mod types;
 
+mod admin;
+mod debug;
+mod dev;
 mod eth;
 mod eth_pubsub;
+mod frontier;
+mod frontier_pubsub;
 mod net;
+mod parity;
+mod personal;
 mod web3;
 
+pub use admin::{AdminApi, AdminApiServer};
+pub use debug::{DebugApi, DebugApiServer};
+pub use dev::{DevApi, DevApiServer};
 pub use eth::{EthApi, EthApiServer, EthFilterApi, EthFilterApiServer};
 pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer};
+pub use frontier::{FrontierApi, FrontierApiServer};
+pub use frontier_pubsub::{FrontierPubSubApi, FrontierPubSubApiServer};
 pub use net::{NetApi, NetApiServer};
+pub use parity::{ParityApi, ParityApiServer};
+pub use personal::{PersonalApi, PersonalApiServer};
 pub use web3::{Web3Api, Web3ApiServer};
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parity rpc interface.
+use crate::types::{LocalTransactionStatus, Transaction};
+use ethereum_types::H256;
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+use std::collections::BTreeMap;
+
+pub use rpc_impl_ParityApi::gen_server::ParityApi as ParityApiServer;
+
+/// Parity rpc interface.
+#[rpc(server)]
+pub trait ParityApi {
+	/// Returns the transactions submitted through this node and their status. Unsafe method.
+	#[rpc(name = "parity_localTransactions")]
+	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>>;
+
+	/// Removes a transaction submitted through this node from the pool. Returns the removed
+	/// transaction, or `None` if it is no longer in the pool.
+	#[rpc(name = "parity_removeTransaction")]
+	fn remove_transaction(&self, _: H256) -> Result<Option<Transaction>>;
+
+	/// Submits a transaction submitted through this node to the pool again if it was dropped.
+	/// Returns whether it was resubmitted.
+	#[rpc(name = "parity_rebroadcastTransaction")]
+	fn rebroadcast_transaction(&self, _: H256) -> BoxFuture<Result<bool>>;
+}
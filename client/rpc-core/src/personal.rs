@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Personal rpc interface.
+use crate::types::Bytes;
+use ethereum_types::H160;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+pub use rpc_impl_PersonalApi::gen_server::PersonalApi as PersonalApiServer;
+
+/// Personal rpc interface.
+#[rpc(server)]
+pub trait PersonalApi {
+	/// Signs the EIP-191 prefixed message with the given account. The password is ignored, the
+	/// accounts of the node signers are always unlocked.
+	#[rpc(name = "personal_sign")]
+	fn sign(&self, _: Bytes, _: H160, _: Option<String>) -> Result<Bytes>;
+
+	/// Returns the account which signed the EIP-191 prefixed message.
+	#[rpc(name = "personal_ecRecover")]
+	fn ec_recover(&self, _: Bytes, _: Bytes) -> Result<H160>;
+}
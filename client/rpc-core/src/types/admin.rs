@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use ethereum_types::{H256, U256};
+use serde::Serialize;
+
+/// Connected peer, in the format of geth `admin_peers`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminPeerInfo {
+	/// Peer id.
+	pub id: String,
+	/// Agent version of the peer, empty when unknown.
+	pub name: String,
+	/// Address of the peer, as a multiaddr ending with its peer id.
+	pub enode: String,
+	/// Roles of the peer.
+	pub roles: String,
+	/// Connection to the peer.
+	pub network: AdminPeerNetwork,
+	/// Chain of the peer.
+	pub protocols: AdminProtocols,
+}
+
+/// Connection to a peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminPeerNetwork {
+	/// Whether the peer dialed the node.
+	pub inbound: bool,
+	/// Local address of the connection, when known.
+	pub local_address: Option<String>,
+	/// Remote address of the connection, when known.
+	pub remote_address: Option<String>,
+}
+
+/// Chain protocols of a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminProtocols {
+	/// Ethereum protocol.
+	pub eth: AdminEthProtocol,
+}
+
+/// Chain of a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminEthProtocol {
+	/// Protocol version, as `eth_protocolVersion`.
+	pub version: u64,
+	/// Hash of the best block.
+	pub head: H256,
+	/// Number of the best block.
+	pub number: U256,
+	/// Chain id, for the local node only.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub network: Option<u64>,
+	/// Hash of the genesis block, for the local node only.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub genesis: Option<H256>,
+}
+
+/// Local node, in the format of geth `admin_nodeInfo`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminNodeInfo {
+	/// Peer id.
+	pub id: String,
+	/// Client name and version.
+	pub name: String,
+	/// First listen address, as a multiaddr ending with the peer id.
+	pub enode: Option<String>,
+	/// First listen address.
+	pub listen_addr: Option<String>,
+	/// All listen addresses.
+	pub listen_addrs: Vec<String>,
+	/// Chain of the node.
+	pub protocols: AdminProtocols,
+}
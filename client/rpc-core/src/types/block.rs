@@ -20,10 +20,11 @@ use std::{collections::BTreeMap, ops::Deref};
 
 use crate::types::{Bytes, Transaction};
 use ethereum_types::{Bloom as H2048, H160, H256, U256};
-use serde::{ser::Error, Serialize, Serializer};
+use serde::{de::Error as DeError, ser::Error, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Block Transactions
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
 pub enum BlockTransactions {
 	/// Only hashes
 	Hashes(Vec<H256>),
@@ -44,7 +45,7 @@ impl Serialize for BlockTransactions {
 }
 
 /// Block representation
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Block {
 	/// Header of the block
@@ -61,7 +62,7 @@ pub struct Block {
 }
 
 /// Block header representation.
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Header {
 	/// Hash of the block
@@ -72,6 +73,7 @@ pub struct Header {
 	#[serde(rename = "sha3Uncles")]
 	pub uncles_hash: H256,
 	/// Authors address
+	#[serde(default)]
 	pub author: H160,
 	/// Alias of `author`
 	pub miner: H160,
@@ -96,6 +98,7 @@ pub struct Header {
 	/// Difficulty
 	pub difficulty: U256,
 	/// Seal fields
+	#[serde(default)]
 	pub seal_fields: Vec<Bytes>,
 	/// Size in bytes
 	pub size: Option<U256>,
@@ -145,3 +148,119 @@ impl<T: Serialize> Serialize for Rich<T> {
 		}
 	}
 }
+
+impl<'de, T: Serialize + Deserialize<'de>> Deserialize<'de> for Rich<T> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		use serde_json::{from_value, to_value, Map, Value};
+
+		let object = Map::<String, Value>::deserialize(deserializer)?;
+		let inner: T = from_value(Value::Object(object.clone())).map_err(D::Error::custom)?;
+		// The extra info are the string fields which are not part of the standard value.
+		let known = match to_value(&inner) {
+			Ok(Value::Object(known)) => known,
+			_ => {
+				return Err(D::Error::custom(
+					"Unserializable structures: expected objects",
+				))
+			}
+		};
+		let extra_info = object
+			.into_iter()
+			.filter(|(key, _)| !known.contains_key(key))
+			.filter_map(|(key, value)| match value {
+				Value::String(value) => Some((key, value)),
+				_ => None,
+			})
+			.collect();
+
+		Ok(Rich { inner, extra_info })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn geth_block(transactions: &str) -> String {
+		format!(
+			r#"{{
+				"difficulty": "0x0",
+				"extraData": "0x",
+				"gasLimit": "0x1c9c380",
+				"gasUsed": "0x5208",
+				"hash": "0x1d59ff54b1eb26b013ce3cb5fc9dab3705b415a67127a003c3e61eb445bb8df2",
+				"logsBloom": "0x{}",
+				"miner": "0x0000000000000000000000000000000000000000",
+				"mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+				"nonce": "0x0000000000000000",
+				"number": "0x5daf3b",
+				"parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+				"receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+				"sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+				"size": "0x27f",
+				"stateRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+				"timestamp": "0x62b03a2d",
+				"totalDifficulty": "0x0",
+				"transactions": {},
+				"transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+				"uncles": []
+			}}"#,
+			"00".repeat(256),
+			transactions
+		)
+	}
+
+	#[test]
+	fn geth_block_round_trip() {
+		let json =
+			geth_block(r#"["0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b"]"#);
+
+		let block: RichBlock = serde_json::from_str(&json).unwrap();
+		assert_eq!(block.header.number, Some(U256::from(0x5daf3b)));
+		assert_eq!(block.size, Some(U256::from(0x27f)));
+		assert!(
+			matches!(block.transactions, BlockTransactions::Hashes(ref hashes) if hashes.len() == 1)
+		);
+		// Fields unknown to the block are kept as extra info.
+		assert_eq!(
+			block.extra_info.get("nonce").map(String::as_str),
+			Some("0x0000000000000000")
+		);
+
+		let round_trip: RichBlock =
+			serde_json::from_value(serde_json::to_value(&block).unwrap()).unwrap();
+		assert_eq!(round_trip, block);
+	}
+
+	#[test]
+	fn geth_block_with_full_transactions() {
+		let json = geth_block(
+			r#"[{
+				"hash": "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b",
+				"nonce": "0x0",
+				"from": "0xa7d9ddbe1f17865597fbd27ec712455208b6b76d",
+				"to": null,
+				"value": "0x0",
+				"gasPrice": "0x1",
+				"gas": "0x5208",
+				"input": "0x",
+				"type": "0x0",
+				"v": "0x1b",
+				"r": "0x1",
+				"s": "0x1"
+			}]"#,
+		);
+
+		let block: RichBlock = serde_json::from_str(&json).unwrap();
+		match &block.transactions {
+			BlockTransactions::Full(transactions) => {
+				assert_eq!(transactions.len(), 1);
+				assert_eq!(transactions[0].to, None);
+			}
+			BlockTransactions::Hashes(_) => panic!("expected full transactions"),
+		}
+	}
+}
@@ -17,11 +17,11 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::types::Bytes;
-use ethereum_types::{H160, U256};
-use serde::Deserialize;
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
 
 /// Call request
-#[derive(Debug, Default, PartialEq, Deserialize, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Hash, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
 pub struct CallRequest {
@@ -31,12 +31,57 @@ pub struct CallRequest {
 	pub to: Option<H160>,
 	/// Gas Price
 	pub gas_price: Option<U256>,
+	/// EIP-1559 Max fee per gas
+	pub max_fee_per_gas: Option<U256>,
+	/// EIP-1559 Max priority fee per gas
+	pub max_priority_fee_per_gas: Option<U256>,
 	/// Gas
 	pub gas: Option<U256>,
 	/// Value
 	pub value: Option<U256>,
 	/// Data
 	pub data: Option<Bytes>,
+	/// Input, the newer name of `data`
+	pub input: Option<Bytes>,
 	/// Nonce
 	pub nonce: Option<U256>,
+	/// EIP-2930 access list
+	pub access_list: Option<Vec<AccessListItem>>,
+}
+
+/// Address and storage keys of an EIP-2930 access list.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+	/// Address
+	pub address: H160,
+	/// Storage keys
+	pub storage_keys: Vec<H256>,
+}
+
+/// Result of `eth_createAccessList`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+	/// Addresses and storage keys accessed by the call.
+	pub access_list: Vec<AccessListItem>,
+	/// Gas used by the call with the access list.
+	pub gas_used: U256,
+	/// Error of a failed call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+impl CallRequest {
+	/// Calldata of the request, `input` taking precedence over `data`.
+	pub fn call_data(&self) -> Option<&Bytes> {
+		self.input.as_ref().or(self.data.as_ref())
+	}
+}
+
+impl AccessListItem {
+	/// The item in the form the EVM gas functions take.
+	pub fn to_tuple(&self) -> (H160, Vec<H256>) {
+		(self.address, self.storage_keys.clone())
+	}
 }
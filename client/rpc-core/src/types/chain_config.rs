@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use ethereum_types::{H160, U256, U64};
+use serde::Serialize;
+
+/// Ethereum-level configuration of the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+	/// Chain id used for transaction signing.
+	pub chain_id: U64,
+	/// Hard fork the EVM config follows, None for the runtimes predating the API.
+	pub evm_fork: Option<String>,
+	/// Optional features the EVM config enables, None for the runtimes predating the API.
+	pub evm_features: Option<Vec<String>>,
+	/// Maximum size of the deployed code, None if unlimited or unknown.
+	pub create_contract_limit: Option<U64>,
+	/// Gas limit of the blocks, None if unknown.
+	pub block_gas_limit: Option<U256>,
+	/// Minimum gas price of the transactions.
+	pub min_gas_price: U256,
+	/// Bounds of the minimum gas price adjustments, None if fixed or unknown.
+	pub min_gas_price_bounds: Option<GasPriceBounds>,
+	/// Addresses of the precompiles, None for the runtimes predating the API.
+	pub precompiles: Option<Vec<H160>>,
+}
+
+/// Bounds of the minimum gas price adjustments between blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasPriceBounds {
+	/// Divisor of the minimum gas price giving the maximum change per block.
+	pub bound_divisor: U256,
+	/// Lowest minimum gas price.
+	pub lower_bound: U256,
+	/// Highest minimum gas price.
+	pub upper_bound: U256,
+}
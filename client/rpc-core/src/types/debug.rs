@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::types::Bytes;
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Account of a state dump.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpAccount {
+	/// Balance.
+	pub balance: U256,
+	/// Nonce.
+	pub nonce: U256,
+	/// Keccak256 hash of the code.
+	pub code_hash: H256,
+	/// Code, None when not requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// Storage slots, None when not requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<H256, H256>>,
+	/// Whether the storage was cut at the maximum number of slots dumped per account.
+	#[serde(skip_serializing_if = "std::ops::Not::not")]
+	pub storage_truncated: bool,
+	/// Key the account is iterated by, its hashed address.
+	pub key: Bytes,
+}
+
+/// State of the accounts at a block, a page of them on large states.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateDump {
+	/// Ethereum state root of the block.
+	pub root: H256,
+	/// Accounts, by address.
+	pub accounts: BTreeMap<H160, DumpAccount>,
+	/// Key of the first account of the next page, None when the dump is complete.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub next: Option<Bytes>,
+}
+
+/// Page of the accounts at a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRange {
+	/// Ethereum state root of the block.
+	pub root: H256,
+	/// Accounts, by address.
+	pub accounts: BTreeMap<H160, DumpAccount>,
+	/// Key of the first account of the next page, None on the last page.
+	pub next: Option<Bytes>,
+}
+
+/// Block rejected on import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadBlock {
+	/// Substrate hash of the block.
+	pub hash: H256,
+	/// Number of the block.
+	pub number: U256,
+	/// Hash of the Ethereum block of the digest, None if the digest has none.
+	pub ethereum_hash: Option<H256>,
+	/// Reason of the rejection.
+	pub reason: String,
+	/// SCALE encoded header.
+	pub header: Bytes,
+	/// SCALE encoded extrinsics, None if the import was not given the body or the block was
+	/// rejected on execution.
+	pub body: Option<Bytes>,
+}
+
+/// Options of `debug_traceCall`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCallOptions {
+	/// Name of the tracer.
+	pub tracer: Option<String>,
+}
+
+/// Result of the `bundlerCollectorTracer` of the ERC-4337 bundlers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlerTrace {
+	/// Accesses of each call of the EntryPoint, until its `BeforeExecution` event.
+	pub calls_from_entry_point: Vec<TopLevelCallInfo>,
+	/// Inputs of the KECCAK256 opcodes of the inner calls.
+	pub keccak: Vec<Bytes>,
+	/// Logs of the inner calls.
+	pub logs: Vec<BundlerLog>,
+	/// Entries and exits of the inner calls.
+	pub calls: Vec<BundlerCall>,
+	/// Debug messages, always empty.
+	pub debug: Vec<String>,
+}
+
+/// Accesses of a call of the EntryPoint and of its inner calls.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopLevelCallInfo {
+	/// Selector of the called method.
+	pub top_level_method_sig: Bytes,
+	/// Called address.
+	pub top_level_target_address: H160,
+	/// Number of executions of each opcode, by name.
+	pub opcodes: BTreeMap<String, u64>,
+	/// Storage read and written, by address.
+	pub access: BTreeMap<H160, StorageAccessInfo>,
+	/// Code size of the accessed accounts, by address.
+	pub contract_size: BTreeMap<H160, ContractSizeInfo>,
+	/// Opcode following an EXTCODE* access, by address.
+	pub ext_code_access_info: BTreeMap<H160, String>,
+	/// Whether an inner call ran out of gas.
+	pub oog: bool,
+}
+
+/// Storage accesses of an address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageAccessInfo {
+	/// Value of the slots read before being written.
+	pub reads: BTreeMap<H256, H256>,
+	/// Number of writes of the slots.
+	pub writes: BTreeMap<H256, u64>,
+}
+
+/// Code size of an account accessed by an opcode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractSizeInfo {
+	/// Code size in bytes.
+	pub contract_size: u64,
+	/// Name of the opcode of the first access.
+	pub opcode: String,
+}
+
+/// Entry or exit of an inner call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum BundlerCall {
+	/// Entry, whose type is the opcode of the call.
+	#[serde(rename_all = "camelCase")]
+	Enter {
+		#[serde(rename = "type")]
+		kind: String,
+		from: H160,
+		to: H160,
+		method: Bytes,
+		gas: u64,
+		value: U256,
+	},
+	/// Exit, of type `RETURN` or `REVERT`.
+	#[serde(rename_all = "camelCase")]
+	Exit {
+		#[serde(rename = "type")]
+		kind: String,
+		gas_used: u64,
+		data: Bytes,
+	},
+}
+
+/// Log of an inner call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlerLog {
+	/// Topics.
+	pub topics: Vec<H256>,
+	/// Data.
+	pub data: Bytes,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bundler_trace_serializes_as_the_tracer_result() {
+		let address = H160::repeat_byte(0x11);
+		let slot = H256::from_low_u64_be(1);
+		let trace = BundlerTrace {
+			calls_from_entry_point: vec![TopLevelCallInfo {
+				top_level_method_sig: Bytes(vec![0x12, 0x34, 0x56, 0x78]),
+				top_level_target_address: address,
+				opcodes: vec![("SLOAD".to_string(), 1)].into_iter().collect(),
+				access: vec![(
+					address,
+					StorageAccessInfo {
+						reads: vec![(slot, H256::zero())].into_iter().collect(),
+						writes: BTreeMap::new(),
+					},
+				)]
+				.into_iter()
+				.collect(),
+				contract_size: BTreeMap::new(),
+				ext_code_access_info: BTreeMap::new(),
+				oog: false,
+			}],
+			keccak: vec![],
+			logs: vec![],
+			calls: vec![
+				BundlerCall::Enter {
+					kind: "STATICCALL".to_string(),
+					from: address,
+					to: address,
+					method: Bytes(vec![0x12, 0x34, 0x56, 0x78]),
+					gas: 100,
+					value: U256::zero(),
+				},
+				BundlerCall::Exit {
+					kind: "RETURN".to_string(),
+					gas_used: 50,
+					data: Bytes(vec![]),
+				},
+			],
+			debug: vec![],
+		};
+
+		let address = "0x1111111111111111111111111111111111111111";
+		let slot = "0x0000000000000000000000000000000000000000000000000000000000000001";
+		let zero = "0x0000000000000000000000000000000000000000000000000000000000000000";
+		assert_eq!(
+			serde_json::to_value(&trace).unwrap(),
+			serde_json::json!({
+				"callsFromEntryPoint": [{
+					"topLevelMethodSig": "0x12345678",
+					"topLevelTargetAddress": address,
+					"opcodes": { "SLOAD": 1 },
+					"access": { address: { "reads": { slot: zero }, "writes": {} } },
+					"contractSize": {},
+					"extCodeAccessInfo": {},
+					"oog": false,
+				}],
+				"keccak": [],
+				"logs": [],
+				"calls": [
+					{
+						"type": "STATICCALL",
+						"from": address,
+						"to": address,
+						"method": "0x12345678",
+						"gas": 100,
+						"value": "0x0",
+					},
+					{ "type": "RETURN", "gasUsed": 50, "data": "0x" },
+				],
+				"debug": [],
+			})
+		);
+	}
+
+	#[test]
+	fn trace_call_options_take_the_tracer() {
+		let options: TraceCallOptions =
+			serde_json::from_str(r#"{"tracer":"bundlerCollectorTracer","timeout":"10s"}"#).unwrap();
+		assert_eq!(options.tracer.as_deref(), Some("bundlerCollectorTracer"));
+	}
+}
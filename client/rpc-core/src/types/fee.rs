@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use ethereum_types::U256;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	sync::{Arc, Mutex},
+};
+
+/// Fee history response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+	/// Lowest number block of the returned range.
+	pub oldest_block: U256,
+	/// Base fee of each block in the range, plus the one of the block after the newest.
+	pub base_fee_per_gas: Vec<U256>,
+	/// Gas used to gas limit ratio of each block in the range.
+	pub gas_used_ratio: Vec<f64>,
+	/// Effective priority fees per gas at the requested percentiles, for each block in the range.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub reward: Option<Vec<Vec<U256>>>,
+}
+
+/// Fee data of an imported block.
+#[derive(Debug, Clone, Default)]
+pub struct FeeHistoryCacheItem {
+	/// Base fee of the block.
+	pub base_fee: U256,
+	/// Gas used to gas limit ratio.
+	pub gas_used_ratio: f64,
+	/// Effective priority fee and gas used of each transaction, sorted by fee.
+	pub rewards: Vec<(U256, u64)>,
+}
+
+impl FeeHistoryCacheItem {
+	/// Effective priority fee paid at the given percentile of the block's gas used.
+	pub fn reward_at(&self, percentile: f64) -> U256 {
+		let total_gas: u64 = self.rewards.iter().map(|(_, gas)| gas).sum();
+		let threshold = total_gas as f64 * percentile / 100.0;
+
+		let mut cumulative_gas = 0u64;
+		for (reward, gas) in self.rewards.iter() {
+			cumulative_gas += gas;
+			if cumulative_gas as f64 >= threshold {
+				return *reward;
+			}
+		}
+
+		self.rewards
+			.last()
+			.map(|(reward, _)| *reward)
+			.unwrap_or_default()
+	}
+}
+
+/// On-memory fee data of the latest imported blocks, by block number.
+pub type FeeHistoryCache = Arc<Mutex<BTreeMap<u64, FeeHistoryCacheItem>>>;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reward_at_is_weighted_by_gas_used() {
+		let item = FeeHistoryCacheItem {
+			base_fee: U256::one(),
+			gas_used_ratio: 0.5,
+			rewards: vec![
+				(U256::from(1), 21_000),
+				(U256::from(2), 63_000),
+				(U256::from(10), 21_000),
+			],
+		};
+
+		assert_eq!(item.reward_at(0.0), U256::from(1));
+		assert_eq!(item.reward_at(20.0), U256::from(1));
+		assert_eq!(item.reward_at(50.0), U256::from(2));
+		assert_eq!(item.reward_at(80.0), U256::from(2));
+		assert_eq!(item.reward_at(100.0), U256::from(10));
+	}
+
+	#[test]
+	fn reward_at_empty_block_is_zero() {
+		assert_eq!(FeeHistoryCacheItem::default().reward_at(50.0), U256::zero());
+	}
+
+	#[test]
+	fn geth_fee_history_round_trip() {
+		let json = r#"{
+			"oldestBlock": "0xfab8ac",
+			"baseFeePerGas": ["0x3da8e7618", "0x3e1ba3b1b", "0x3dfd72b90"],
+			"gasUsedRatio": [0.5290747666666666, 0.49240453333333334],
+			"reward": [["0x59682f00", "0x9502f900"], ["0x59682f00", "0x9502f900"]]
+		}"#;
+
+		let fee_history: FeeHistory = serde_json::from_str(json).unwrap();
+		assert_eq!(fee_history.oldest_block, U256::from(0xfab8ac));
+		assert_eq!(fee_history.base_fee_per_gas.len(), 3);
+
+		let round_trip: FeeHistory =
+			serde_json::from_value(serde_json::to_value(&fee_history).unwrap()).unwrap();
+		assert_eq!(round_trip, fee_history);
+	}
+}
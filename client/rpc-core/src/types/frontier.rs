@@ -0,0 +1,368 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::types::{BlockNumber, Bytes, FilterAddress, Log};
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// Frontier backend database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendInfo {
+	/// Database type.
+	pub kind: String,
+	/// Database path, None for the in-memory databases.
+	pub path: Option<String>,
+	/// SQL log index, None when disabled.
+	pub log_index: Option<LogIndexInfo>,
+}
+
+/// Progress of the SQL log index. `eth_getLogs` scans the blocks missing from the index, so the
+/// queries out of the indexed range are slower but complete.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogIndexInfo {
+	/// Number of indexed blocks, forks included.
+	pub indexed_blocks: U256,
+	/// Number of indexed blocks retracted from the best chain, kept for analysis.
+	pub orphaned_blocks: U256,
+	/// Lowest indexed block number.
+	pub lowest_block: Option<U256>,
+	/// Highest indexed block number.
+	pub highest_block: Option<U256>,
+}
+
+/// Ethereum storage schema used from a block on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaCacheEntry {
+	/// Storage schema version.
+	pub schema: String,
+	/// Substrate hash of the first block using the schema.
+	pub from_block_hash: H256,
+	/// Number of the first block using the schema, None if its header is unknown.
+	pub from_block_number: Option<U256>,
+	/// Number of the last block using the schema, None for the current schema.
+	pub to_block_number: Option<U256>,
+}
+
+/// Progress of the mapping sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingSyncHead {
+	/// Substrate hash of the latest mapped best chain block, None if not within the sync lag.
+	pub hash: Option<H256>,
+	/// Number of the latest mapped best chain block, None if not within the sync lag.
+	pub number: Option<U256>,
+	/// Substrate hashes of the blocks the mapping sync resumes from.
+	pub syncing_tips: Vec<H256>,
+}
+
+/// Filters stored by the `eth_newFilter`, `eth_newBlockFilter` and
+/// `eth_newPendingTransactionFilter` RPCs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterPoolStats {
+	/// Number of log filters.
+	pub log: usize,
+	/// Number of block filters.
+	pub block: usize,
+	/// Number of pending transaction filters.
+	pub pending_transaction: usize,
+	/// Lowest block number a filter was created at.
+	pub oldest_at_block: Option<U256>,
+}
+
+/// Number of entries of the in-memory caches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheSizes {
+	/// Cached Ethereum blocks.
+	pub blocks: usize,
+	/// Cached transaction statuses, by block.
+	pub transaction_statuses: usize,
+	/// Blocks of the fee history cache.
+	pub fee_history: usize,
+}
+
+/// Requests of a method which exceeded the slow query threshold.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQueryStats {
+	/// Method name.
+	pub method: String,
+	/// Number of slow requests.
+	pub count: u64,
+	/// Duration of the slowest request, in milliseconds.
+	pub max_duration_ms: u64,
+}
+
+/// Response cache activity of a method.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCacheStats {
+	/// Method name.
+	pub method: String,
+	/// Requests served from the cache.
+	pub hits: u64,
+	/// Requests executed and their response cached.
+	pub misses: u64,
+	/// Responses of the method currently cached.
+	pub entries: u64,
+}
+
+/// Value of an account field at a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySample {
+	/// Block number.
+	pub block_number: U256,
+	/// Value at the block.
+	pub value: U256,
+}
+
+/// Self-destructed contract whose storage is still being removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRemoval {
+	/// Contract address.
+	pub address: H160,
+	/// Number of storage keys left, counted up to `pallet_evm::SUICIDE_REMOVAL_LIMIT`.
+	pub remaining_keys: u32,
+}
+
+/// Versions of the node, its runtime and the EVM it executes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontierVersion {
+	/// Name of the Frontier RPC crate.
+	pub client: String,
+	/// Version of the Frontier RPC crate.
+	pub client_version: String,
+	/// Runtime spec name.
+	pub spec_name: String,
+	/// Runtime spec version.
+	pub spec_version: u32,
+	/// Runtime implementation version.
+	pub impl_version: u32,
+	/// Version of the Ethereum runtime API, None if the runtime does not implement it.
+	pub runtime_api_version: Option<u32>,
+	/// Hard fork the EVM config follows, None for the runtimes predating the API.
+	pub evm_fork: Option<String>,
+	/// Block from which the min gas price follows the EIP-1559 base fee rules, None before
+	/// London is activated or for the runtimes predating version 10 of the API.
+	pub london_block: Option<U256>,
+}
+
+/// Position of a log in the chain, to resume a `frontier_getLogs` query from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct LogCursor {
+	/// Number of the block.
+	pub block_number: U256,
+	/// Index of the log in the block.
+	pub log_index: U256,
+}
+
+/// Page of the logs matching a filter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsPage {
+	/// Logs, in ascending order.
+	pub logs: Vec<Log>,
+	/// Position of the first log of the next page, None on the last page.
+	pub next: Option<LogCursor>,
+}
+
+/// Token transfers selected by `frontier_getTokenTransfers`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTransferFilter {
+	/// First block, the earliest by default.
+	pub from_block: Option<BlockNumber>,
+	/// Last block, the latest by default.
+	pub to_block: Option<BlockNumber>,
+	/// Sender or recipient of the transfers.
+	pub address: Option<H160>,
+	/// Token contract or contracts.
+	pub token: Option<FilterAddress>,
+}
+
+/// ERC-20, ERC-721 or ERC-1155 token transfer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTransfer {
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// Number of the block.
+	pub block_number: U256,
+	/// Hash of the transaction.
+	pub transaction_hash: H256,
+	/// Index of the transaction in the block.
+	pub transaction_index: U256,
+	/// Index of the log in the block.
+	pub log_index: U256,
+	/// Index of the transfer in an ERC-1155 batch, 0 otherwise.
+	pub batch_index: U256,
+	/// Token contract.
+	pub token: H160,
+	/// Token standard, `erc20`, `erc721` or `erc1155`.
+	pub standard: String,
+	/// Sender.
+	pub from: H160,
+	/// Recipient.
+	pub to: H160,
+	/// Token id, None for ERC-20.
+	pub token_id: Option<U256>,
+	/// Amount transferred, 1 for ERC-721.
+	pub value: U256,
+}
+
+/// Position of a token transfer in the chain, to resume a `frontier_getTokenTransfers` query
+/// from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTransferCursor {
+	/// Number of the block.
+	pub block_number: U256,
+	/// Index of the log in the block.
+	pub log_index: U256,
+	/// Index of the transfer in an ERC-1155 batch.
+	pub batch_index: U256,
+}
+
+/// Page of the token transfers matching a filter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTransfersPage {
+	/// Transfers, in ascending order.
+	pub transfers: Vec<TokenTransfer>,
+	/// Position of the first transfer of the next page, None on the last page.
+	pub next: Option<TokenTransferCursor>,
+}
+
+/// Creation of a contract by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractCreation {
+	/// Address of the contract.
+	pub address: H160,
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// Number of the block.
+	pub block_number: U256,
+	/// Hash of the creating transaction.
+	pub transaction_hash: H256,
+	/// Index of the creating transaction in the block.
+	pub transaction_index: U256,
+	/// Sender of the creating transaction.
+	pub creator: H160,
+	/// Keccak hash of the init code.
+	pub init_code_hash: H256,
+}
+
+/// Address, or block, whose internal transactions `frontier_getInternalTransactions` returns.
+/// A 20 bytes hex string is an address.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum BlockOrAddress {
+	Address(H160),
+	Block(BlockNumber),
+}
+
+/// Value transfer below the top level call of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalTransaction {
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// Number of the block.
+	pub block_number: U256,
+	/// Hash of the transaction.
+	pub transaction_hash: H256,
+	/// Index of the transaction in the block.
+	pub transaction_index: U256,
+	/// Index of the transfer in the transfers of its transaction, in execution order.
+	pub transfer_index: U256,
+	/// Sender.
+	pub from: H160,
+	/// Recipient.
+	pub to: H160,
+	/// Amount transferred.
+	pub value: U256,
+}
+
+/// Balance, nonce and code of an account at a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSummary {
+	/// Address of the account.
+	pub address: H160,
+	pub balance: U256,
+	pub nonce: U256,
+	/// Keccak hash of the code, the hash of the empty code for the accounts without code.
+	pub code_hash: H256,
+	/// Size of the code in bytes.
+	pub code_size: U256,
+}
+
+/// Outcome of the checks `frontier_validateRawTransaction` performs on a raw transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTransactionValidation {
+	/// Hash of the transaction.
+	pub hash: H256,
+	/// Sender recovered from the signature, None if it cannot be recovered.
+	pub from: Option<H160>,
+	/// Nonce of the transaction, None if it cannot be decoded.
+	pub nonce: Option<U256>,
+	/// Nonce of the sender at the best block.
+	pub account_nonce: Option<U256>,
+	/// Whether the nonce leaves a gap after the account nonce, so the pool would queue the
+	/// transaction until the gap is filled.
+	pub queued: bool,
+	/// Gas charged before the execution.
+	pub intrinsic_gas: Option<U256>,
+	/// Whether the transaction passes all the checks.
+	pub valid: bool,
+	/// Reason of the first failed check, with the error message of geth.
+	pub error: Option<String>,
+}
+
+/// Merkle proof of the inclusion of a transaction or a receipt in the trie of its block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProof {
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// Number of the block.
+	pub block_number: U256,
+	/// Index of the transaction in the block.
+	pub transaction_index: U256,
+	/// `transactionsRoot` or `receiptsRoot` of the block.
+	pub root: H256,
+	/// Key of the item in the trie, the RLP encoding of its index.
+	pub key: Bytes,
+	/// RLP encoding of the transaction or of the receipt.
+	pub value: Bytes,
+	/// RLP encoded trie nodes from the root to the item.
+	pub proof: Vec<Bytes>,
+}
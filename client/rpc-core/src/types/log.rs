@@ -18,10 +18,10 @@
 
 use crate::types::Bytes;
 use ethereum_types::{H160, H256, U256};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Log
-#[derive(Debug, Serialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Log {
 	/// H160
@@ -46,3 +46,147 @@ pub struct Log {
 	#[serde(default)]
 	pub removed: bool,
 }
+
+impl Log {
+	/// Logs of a transaction, numbered from the log `first_log_index` of its block.
+	pub fn transaction_logs(
+		block_hash: H256,
+		block_number: U256,
+		transaction_hash: H256,
+		transaction_index: U256,
+		first_log_index: u32,
+		logs: Vec<ethereum::Log>,
+	) -> Vec<Log> {
+		logs.into_iter()
+			.enumerate()
+			.map(|(i, log)| Log {
+				address: log.address,
+				topics: log.topics,
+				data: Bytes(log.data),
+				block_hash: Some(block_hash),
+				block_number: Some(block_number),
+				transaction_hash: Some(transaction_hash),
+				transaction_index: Some(transaction_index),
+				log_index: Some(U256::from(first_log_index.saturating_add(i as u32))),
+				transaction_log_index: Some(U256::from(i)),
+				removed: false,
+			})
+			.collect()
+	}
+
+	/// Logs of the transactions of a block, by transaction. The log index counts the logs of
+	/// the whole block, the transaction log index those of the transaction.
+	pub fn block_logs(
+		block_hash: H256,
+		block_number: U256,
+		transactions: Vec<(H256, Vec<ethereum::Log>)>,
+	) -> Vec<Vec<Log>> {
+		let mut first_log_index = 0u32;
+		transactions
+			.into_iter()
+			.enumerate()
+			.map(|(transaction_index, (transaction_hash, logs))| {
+				let logs = Log::transaction_logs(
+					block_hash,
+					block_number,
+					transaction_hash,
+					U256::from(transaction_index),
+					first_log_index,
+					logs,
+				);
+				first_log_index = first_log_index.saturating_add(logs.len() as u32);
+				logs
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn geth_log_round_trip() {
+		let json = r#"{
+			"address": "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+			"topics": [
+				"0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+			],
+			"data": "0x00000000000000000000000000000000000000000000000000000000000003e8",
+			"blockNumber": "0x2",
+			"transactionHash": "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b",
+			"transactionIndex": "0x0",
+			"blockHash": "0x1d59ff54b1eb26b013ce3cb5fc9dab3705b415a67127a003c3e61eb445bb8df2",
+			"logIndex": "0x0",
+			"removed": false
+		}"#;
+
+		let log: Log = serde_json::from_str(json).unwrap();
+		assert_eq!(log.block_number, Some(U256::from(2)));
+		assert_eq!(log.transaction_log_index, None);
+
+		let round_trip: Log = serde_json::from_value(serde_json::to_value(&log).unwrap()).unwrap();
+		assert_eq!(round_trip, log);
+	}
+
+	fn ethereum_logs(count: usize) -> Vec<ethereum::Log> {
+		(0..count)
+			.map(|i| ethereum::Log {
+				address: H160::from_low_u64_be(i as u64),
+				topics: vec![H256::from_low_u64_be(i as u64)],
+				data: vec![i as u8],
+			})
+			.collect()
+	}
+
+	proptest::proptest! {
+		#[test]
+		fn block_logs_are_numbered_across_the_block(
+			counts in proptest::collection::vec(0..8usize, 0..16),
+		) {
+			let block_hash = H256::repeat_byte(1);
+			let transactions = counts
+				.iter()
+				.enumerate()
+				.map(|(i, count)| (H256::from_low_u64_be(i as u64), ethereum_logs(*count)))
+				.collect::<Vec<_>>();
+			let block_logs = Log::block_logs(block_hash, U256::one(), transactions.clone());
+
+			proptest::prop_assert_eq!(block_logs.len(), counts.len());
+			let logs = block_logs.iter().flatten().collect::<Vec<_>>();
+			proptest::prop_assert_eq!(logs.len(), counts.iter().sum::<usize>());
+			for (i, log) in logs.iter().enumerate() {
+				proptest::prop_assert_eq!(log.log_index, Some(U256::from(i)));
+				proptest::prop_assert_eq!(log.block_hash, Some(block_hash));
+			}
+
+			let mut first_log_index = 0u32;
+			for (transaction_index, ((transaction_hash, logs), block_logs)) in
+				transactions.into_iter().zip(block_logs).enumerate()
+			{
+				for (i, log) in block_logs.iter().enumerate() {
+					proptest::prop_assert_eq!(log.transaction_log_index, Some(U256::from(i)));
+					proptest::prop_assert_eq!(log.transaction_hash, Some(transaction_hash));
+					proptest::prop_assert_eq!(
+						log.transaction_index,
+						Some(U256::from(transaction_index))
+					);
+				}
+				// A receipt built alone numbers its logs the same as the block does.
+				let count = logs.len() as u32;
+				proptest::prop_assert_eq!(
+					Log::transaction_logs(
+						block_hash,
+						U256::one(),
+						transaction_hash,
+						U256::from(transaction_index),
+						first_log_index,
+						logs,
+					),
+					block_logs
+				);
+				first_log_index += count;
+			}
+		}
+	}
+}
@@ -19,14 +19,21 @@
 //! RPC types
 
 mod account_info;
+mod admin;
 mod block;
 mod block_number;
 mod bytes;
 mod call_request;
+mod chain_config;
+mod debug;
+mod fee;
 mod filter;
+mod frontier;
+mod health;
 mod index;
 mod log;
 mod receipt;
+mod request_validation;
 mod sync;
 mod transaction;
 mod transaction_request;
@@ -36,22 +43,40 @@ pub mod pubsub;
 
 pub use self::{
 	account_info::{AccountInfo, EthAccount, ExtAccountInfo, RecoveredAccount, StorageProof},
+	admin::{AdminEthProtocol, AdminNodeInfo, AdminPeerInfo, AdminPeerNetwork, AdminProtocols},
 	block::{Block, BlockTransactions, Header, Rich, RichBlock, RichHeader},
 	block_number::BlockNumber,
 	bytes::Bytes,
-	call_request::CallRequest,
+	call_request::{AccessListItem, AccessListResult, CallRequest},
+	chain_config::{ChainConfig, GasPriceBounds},
+	debug::{
+		AccountRange, BadBlock, BundlerCall, BundlerLog, BundlerTrace, ContractSizeInfo,
+		DumpAccount, StateDump, StorageAccessInfo, TopLevelCallInfo, TraceCallOptions,
+	},
+	fee::{FeeHistory, FeeHistoryCache, FeeHistoryCacheItem},
 	filter::{
 		Filter, FilterAddress, FilterChanges, FilterPool, FilterPoolItem, FilterType,
 		FilteredParams, Topic, VariadicValue,
 	},
+	frontier::{
+		AccountSummary, BackendInfo, BlockOrAddress, CacheSizes, ContractCreation, FilterPoolStats,
+		FrontierVersion, HistorySample, InclusionProof, InternalTransaction, LogCursor,
+		LogIndexInfo, LogsPage, MappingSyncHead, PendingRemoval, RawTransactionValidation,
+		ResponseCacheStats, SchemaCacheEntry, SlowQueryStats, TokenTransfer, TokenTransferCursor,
+		TokenTransferFilter, TokenTransfersPage,
+	},
+	health::Health,
 	index::Index,
 	log::Log,
 	receipt::Receipt,
+	request_validation::{validate_call_data, validate_fees, RequestValidationError},
 	sync::{
 		ChainStatus, EthProtocolInfo, PeerCount, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 		Peers, PipProtocolInfo, SyncInfo, SyncStatus, TransactionStats,
 	},
-	transaction::{LocalTransactionStatus, RichRawTransaction, Transaction},
+	transaction::{
+		create_address, LocalTransactionStatus, RichRawTransaction, Transaction, TransactionBuilder,
+	},
 	transaction_request::TransactionRequest,
 	work::Work,
 };
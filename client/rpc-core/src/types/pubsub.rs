@@ -18,10 +18,11 @@
 
 //! Pub-Sub types.
 
-use crate::types::{Filter, Log, RichHeader};
-use ethereum_types::H256;
+use crate::types::{Filter, FilterAddress, Log, RichHeader};
+use ethereum_types::{H160, H256, U256};
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{from_value, Value};
+use std::collections::BTreeMap;
 
 /// Subscription result.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -34,6 +35,8 @@ pub enum Result {
 	TransactionHash(H256),
 	/// SyncStatus
 	SyncState(PubSubSyncStatus),
+	/// Transaction lifecycle
+	TransactionLifecycle(PubSubTransactionLifecycle),
 }
 
 /// PubSbub sync status
@@ -44,6 +47,48 @@ pub struct PubSubSyncStatus {
 	pub syncing: bool,
 }
 
+/// Stage of a transaction followed by a `transactionLifecycle` subscription.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionLifecycleStatus {
+	/// Ready in the transaction pool.
+	Pending,
+	/// Included in a block of the best chain.
+	Included,
+	/// Included in a finalized block.
+	Finalized,
+	/// Another transaction of the sender used its nonce.
+	Replaced,
+	/// Left the transaction pool without being included.
+	Dropped,
+}
+
+impl TransactionLifecycleStatus {
+	/// Whether the transaction cannot change stage anymore.
+	pub fn is_final(&self) -> bool {
+		matches!(
+			self,
+			TransactionLifecycleStatus::Finalized
+				| TransactionLifecycleStatus::Replaced
+				| TransactionLifecycleStatus::Dropped
+		)
+	}
+}
+
+/// PubSub transaction lifecycle
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PubSubTransactionLifecycle {
+	/// Stage of the transaction.
+	pub status: TransactionLifecycleStatus,
+	/// Hash of the block including the transaction.
+	pub block_hash: Option<H256>,
+	/// Number of the block including the transaction.
+	pub block_number: Option<U256>,
+	/// Index of the transaction in the block.
+	pub transaction_index: Option<U256>,
+}
+
 impl Serialize for Result {
 	fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
 	where
@@ -54,6 +99,7 @@ impl Serialize for Result {
 			Result::Log(ref log) => log.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
 			Result::SyncState(ref sync) => sync.serialize(serializer),
+			Result::TransactionLifecycle(ref lifecycle) => lifecycle.serialize(serializer),
 		}
 	}
 }
@@ -71,6 +117,8 @@ pub enum Kind {
 	NewPendingTransactions,
 	/// Node syncing status subscription.
 	Syncing,
+	/// Lifecycle of a transaction subscription.
+	TransactionLifecycle,
 }
 
 /// Subscription kind.
@@ -80,6 +128,8 @@ pub enum Params {
 	None,
 	/// Log parameters.
 	Logs(Filter),
+	/// Transaction lifecycle parameters.
+	TransactionHash(H256),
 }
 
 impl Default for Params {
@@ -99,8 +149,244 @@ impl<'a> Deserialize<'a> for Params {
 			return Ok(Params::None);
 		}
 
+		if v.is_string() {
+			return from_value(v)
+				.map(Params::TransactionHash)
+				.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)));
+		}
+
 		from_value(v.clone())
 			.map(Params::Logs)
 			.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
 	}
 }
+
+/// Frontier subscription kind.
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub enum FrontierKind {
+	/// Storage and balance changes of the watched addresses, by block.
+	StateChanges,
+	/// Re-organisations of the best chain.
+	Reorgs,
+	/// New best and finalized blocks, re-organisations and mapping sync lag, in one stream.
+	ChainHead,
+}
+
+/// Frontier subscription result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontierResult {
+	/// Changes of the watched addresses in a block.
+	StateChanges(Box<StateChanges>),
+	/// Re-organisation of the best chain.
+	Reorg(Box<Reorg>),
+	/// Event of the chain head.
+	ChainHead(Box<ChainHeadEvent>),
+}
+
+impl Serialize for FrontierResult {
+	fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		match *self {
+			FrontierResult::StateChanges(ref changes) => changes.serialize(serializer),
+			FrontierResult::Reorg(ref reorg) => reorg.serialize(serializer),
+			FrontierResult::ChainHead(ref event) => event.serialize(serializer),
+		}
+	}
+}
+
+/// Addresses watched by a `stateChanges` subscription.
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChangesFilter {
+	/// Watched address or addresses.
+	pub address: FilterAddress,
+}
+
+impl StateChangesFilter {
+	/// Watched addresses, without duplicates.
+	pub fn addresses(&self) -> Vec<H160> {
+		let mut addresses = match &self.address {
+			FilterAddress::Single(address) => vec![*address],
+			FilterAddress::Multiple(addresses) => addresses.clone(),
+			FilterAddress::Null => Vec::new(),
+		};
+		addresses.sort();
+		addresses.dedup();
+		addresses
+	}
+}
+
+/// Previous and new value of a changed field.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueChange<T> {
+	/// Value at the parent block.
+	pub from: T,
+	/// Value at the block.
+	pub to: T,
+}
+
+/// Changes of a watched address in a block.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountStateChanges {
+	/// Address.
+	pub address: H160,
+	/// Balance change, None if unchanged.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<ValueChange<U256>>,
+	/// Nonce change, None if unchanged.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<ValueChange<U256>>,
+	/// Changed storage slots.
+	pub storage: BTreeMap<H256, ValueChange<H256>>,
+}
+
+/// Changes of the watched addresses in a block of the best chain.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChanges {
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// Number of the block.
+	pub block_number: U256,
+	/// Changed addresses, by address.
+	pub accounts: Vec<AccountStateChanges>,
+}
+
+/// Ethereum hash and number of a block involved in a re-organisation.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorgBlock {
+	/// Hash of the block.
+	pub hash: H256,
+	/// Number of the block.
+	pub number: U256,
+}
+
+/// Re-organisation of the best chain. Both segments are ordered by ascending number and start
+/// after the common ancestor; the old one ends with the previous head, the new one with the new
+/// head.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Reorg {
+	/// Last block shared by both chains.
+	pub common_ancestor: ReorgBlock,
+	/// Retracted blocks.
+	pub old_chain: Vec<ReorgBlock>,
+	/// Enacted blocks.
+	pub new_chain: Vec<ReorgBlock>,
+}
+
+/// New best block, with the lag of the mapping sync behind it.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainHead {
+	/// Hash of the block.
+	pub hash: H256,
+	/// Hash of the parent block.
+	pub parent_hash: H256,
+	/// Number of the block.
+	pub number: U256,
+	/// Number of best chain blocks not yet mapped, None when the mapping sync lags further
+	/// than the node tolerates.
+	pub mapping_sync_lag: Option<U256>,
+}
+
+/// Event of the `chainHead` subscription, tagged by `event`. A re-organisation is sent before
+/// the new head it leads to.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum ChainHeadEvent {
+	/// New best block.
+	NewHead(ChainHead),
+	/// New finalized block.
+	Finalized(ReorgBlock),
+	/// Re-organisation of the best chain.
+	Reorg(Reorg),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn state_changes_filter_takes_one_or_more_addresses() {
+		let address = H160::repeat_byte(0x11);
+		let filter: StateChangesFilter =
+			serde_json::from_str(r#"{"address":"0x1111111111111111111111111111111111111111"}"#)
+				.unwrap();
+		assert_eq!(filter.addresses(), vec![address]);
+
+		let filter: StateChangesFilter = serde_json::from_str(
+			r#"{"address":["0x1111111111111111111111111111111111111111","0x1111111111111111111111111111111111111111"]}"#,
+		)
+		.unwrap();
+		assert_eq!(filter.addresses(), vec![address]);
+	}
+
+	#[test]
+	fn state_changes_skip_the_unchanged_fields() {
+		let changes = AccountStateChanges {
+			address: H160::repeat_byte(0x11),
+			balance: Some(ValueChange {
+				from: U256::from(1),
+				to: U256::from(2),
+			}),
+			nonce: None,
+			storage: BTreeMap::new(),
+		};
+		assert_eq!(
+			serde_json::to_value(&changes).unwrap(),
+			serde_json::json!({
+				"address": "0x1111111111111111111111111111111111111111",
+				"balance": { "from": "0x1", "to": "0x2" },
+				"storage": {},
+			})
+		);
+	}
+
+	#[test]
+	fn reorg_serializes_both_segments() {
+		let block = |byte: u8, number: u64| ReorgBlock {
+			hash: H256::repeat_byte(byte),
+			number: U256::from(number),
+		};
+		let reorg = FrontierResult::Reorg(Box::new(Reorg {
+			common_ancestor: block(0x01, 1),
+			old_chain: vec![block(0x02, 2)],
+			new_chain: vec![block(0x03, 2), block(0x04, 3)],
+		}));
+		let value = serde_json::to_value(&reorg).unwrap();
+		assert_eq!(value["commonAncestor"]["number"], "0x1");
+		assert_eq!(value["oldChain"].as_array().unwrap().len(), 1);
+		assert_eq!(value["newChain"][1]["number"], "0x3");
+	}
+
+	#[test]
+	fn chain_head_events_are_tagged() {
+		let head = FrontierResult::ChainHead(Box::new(ChainHeadEvent::NewHead(ChainHead {
+			hash: H256::repeat_byte(0x02),
+			parent_hash: H256::repeat_byte(0x01),
+			number: U256::from(2),
+			mapping_sync_lag: Some(U256::zero()),
+		})));
+		let value = serde_json::to_value(&head).unwrap();
+		assert_eq!(value["event"], "newHead");
+		assert_eq!(value["number"], "0x2");
+		assert_eq!(value["mappingSyncLag"], "0x0");
+
+		let finalized = ChainHeadEvent::Finalized(ReorgBlock {
+			hash: H256::repeat_byte(0x01),
+			number: U256::from(1),
+		});
+		let value = serde_json::to_value(&finalized).unwrap();
+		assert_eq!(value["event"], "finalized");
+		assert_eq!(value["number"], "0x1");
+	}
+}
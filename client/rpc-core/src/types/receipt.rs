@@ -18,10 +18,10 @@
 
 use crate::types::Log;
 use ethereum_types::{Bloom as H2048, H160, H256, U256, U64};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Receipt
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Receipt {
 	/// Transaction Hash
@@ -40,6 +40,15 @@ pub struct Receipt {
 	pub cumulative_gas_used: U256,
 	/// Gas used
 	pub gas_used: Option<U256>,
+	/// Price paid per unit of gas, including the priority fee
+	#[serde(default)]
+	pub effective_gas_price: U256,
+	/// EIP-2718 type of the transaction
+	#[serde(default, rename = "type")]
+	pub transaction_type: U64,
+	/// Account which paid the fees instead of the sender, for sponsored transactions only
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sponsor: Option<H160>,
 	/// Contract address
 	pub contract_address: Option<H160>,
 	/// Logs
@@ -55,3 +64,43 @@ pub struct Receipt {
 	#[serde(skip_serializing_if = "Option::is_none", rename = "status")]
 	pub status_code: Option<U64>,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn geth_receipt_round_trip() {
+		let json = format!(
+			r#"{{
+				"blockHash": "0x1d59ff54b1eb26b013ce3cb5fc9dab3705b415a67127a003c3e61eb445bb8df2",
+				"blockNumber": "0x5daf3b",
+				"contractAddress": null,
+				"cumulativeGasUsed": "0x33bc",
+				"effectiveGasPrice": "0x4a817c800",
+				"from": "0xa7d9ddbe1f17865597fbd27ec712455208b6b76d",
+				"gasUsed": "0x4dc",
+				"logs": [],
+				"logsBloom": "0x{}",
+				"status": "0x1",
+				"to": "0xf02c1c8e6114b1dbe8937a39260b5b0a374432bb",
+				"transactionHash": "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b",
+				"transactionIndex": "0x41",
+				"type": "0x0"
+			}}"#,
+			"00".repeat(256)
+		);
+
+		let receipt: Receipt = serde_json::from_str(&json).unwrap();
+		assert_eq!(receipt.status_code, Some(U64::one()));
+		assert_eq!(receipt.state_root, None);
+		assert_eq!(receipt.gas_used, Some(U256::from(0x4dc)));
+		assert_eq!(receipt.effective_gas_price, U256::from(0x4a817c800u64));
+		assert_eq!(receipt.transaction_type, U64::zero());
+		assert_eq!(receipt.sponsor, None);
+
+		let round_trip: Receipt =
+			serde_json::from_value(serde_json::to_value(&receipt).unwrap()).unwrap();
+		assert_eq!(round_trip, receipt);
+	}
+}
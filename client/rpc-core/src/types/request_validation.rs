@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Validation of `CallRequest` and `TransactionRequest` fields
+
+use crate::types::{Bytes, CallRequest, TransactionRequest};
+use ethereum_types::{H160, U256};
+use std::fmt;
+
+/// Contradictory fields of a request. The messages are those of geth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestValidationError {
+	/// `gasPrice` given together with an EIP-1559 fee field.
+	GasPriceWithDynamicFee,
+	/// `maxPriorityFeePerGas` above `maxFeePerGas`.
+	PriorityFeeAboveMaxFee {
+		/// Max fee per gas
+		max_fee_per_gas: U256,
+		/// Max priority fee per gas
+		max_priority_fee_per_gas: U256,
+	},
+	/// `data` and `input` given with different calldata.
+	DataInputMismatch,
+	/// Contract creation without init code.
+	CreateWithoutData,
+}
+
+impl fmt::Display for RequestValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::GasPriceWithDynamicFee => write!(
+				f,
+				"both gasPrice and (maxFeePerGas or maxPriorityFeePerGas) specified"
+			),
+			Self::PriorityFeeAboveMaxFee {
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+			} => write!(
+				f,
+				"maxFeePerGas ({}) < maxPriorityFeePerGas ({})",
+				max_fee_per_gas, max_priority_fee_per_gas
+			),
+			Self::DataInputMismatch => write!(
+				f,
+				"both \"data\" and \"input\" are set and not equal. Please use \"input\" to pass transaction call data"
+			),
+			Self::CreateWithoutData => write!(f, "contract creation without any data provided"),
+		}
+	}
+}
+
+impl std::error::Error for RequestValidationError {}
+
+/// Rejects `gas_price` together with the EIP-1559 fee fields, and a priority fee above the max
+/// fee.
+pub fn validate_fees(
+	gas_price: Option<U256>,
+	max_fee_per_gas: Option<U256>,
+	max_priority_fee_per_gas: Option<U256>,
+) -> Result<(), RequestValidationError> {
+	if gas_price.is_some() && (max_fee_per_gas.is_some() || max_priority_fee_per_gas.is_some()) {
+		return Err(RequestValidationError::GasPriceWithDynamicFee);
+	}
+	match (max_fee_per_gas, max_priority_fee_per_gas) {
+		(Some(max_fee_per_gas), Some(max_priority_fee_per_gas))
+			if max_priority_fee_per_gas > max_fee_per_gas =>
+		{
+			Err(RequestValidationError::PriorityFeeAboveMaxFee {
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+			})
+		}
+		_ => Ok(()),
+	}
+}
+
+/// Rejects `data` and `input` carrying different calldata.
+pub fn validate_call_data(
+	data: Option<&Bytes>,
+	input: Option<&Bytes>,
+) -> Result<(), RequestValidationError> {
+	match (data, input) {
+		(Some(data), Some(input)) if data != input => {
+			Err(RequestValidationError::DataInputMismatch)
+		}
+		_ => Ok(()),
+	}
+}
+
+/// Rejects a contract creation without init code. Calls may create without init code unless they
+/// carry value, which would only be moved to an address without code.
+fn validate_create(
+	to: Option<H160>,
+	value: Option<U256>,
+	data: Option<&Bytes>,
+	is_transaction: bool,
+) -> Result<(), RequestValidationError> {
+	let has_data = data.map_or(false, |data| !data.0.is_empty());
+	let has_value = value.map_or(false, |value| !value.is_zero());
+	if to.is_none() && !has_data && (is_transaction || has_value) {
+		return Err(RequestValidationError::CreateWithoutData);
+	}
+	Ok(())
+}
+
+impl CallRequest {
+	/// Checks the request for contradictory fields.
+	pub fn validate(&self) -> Result<(), RequestValidationError> {
+		validate_fees(
+			self.gas_price,
+			self.max_fee_per_gas,
+			self.max_priority_fee_per_gas,
+		)?;
+		validate_call_data(self.data.as_ref(), self.input.as_ref())?;
+		validate_create(self.to, self.value, self.call_data(), false)
+	}
+}
+
+impl TransactionRequest {
+	/// Checks the request for contradictory fields.
+	pub fn validate(&self) -> Result<(), RequestValidationError> {
+		validate_call_data(self.data.as_ref(), self.input.as_ref())?;
+		validate_create(self.to, self.value, self.call_data(), true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn gas_price_and_dynamic_fee_are_exclusive() {
+		let request = CallRequest {
+			gas_price: Some(U256::one()),
+			max_priority_fee_per_gas: Some(U256::one()),
+			..Default::default()
+		};
+		assert_eq!(
+			request.validate().unwrap_err().to_string(),
+			"both gasPrice and (maxFeePerGas or maxPriorityFeePerGas) specified"
+		);
+		assert_eq!(
+			validate_fees(None, Some(U256::one()), Some(U256::from(2))),
+			Err(RequestValidationError::PriorityFeeAboveMaxFee {
+				max_fee_per_gas: U256::one(),
+				max_priority_fee_per_gas: U256::from(2),
+			})
+		);
+		assert_eq!(
+			validate_fees(None, Some(U256::from(2)), Some(U256::one())),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn creation_requires_data() {
+		let call = CallRequest {
+			value: Some(U256::one()),
+			..Default::default()
+		};
+		assert_eq!(
+			call.validate(),
+			Err(RequestValidationError::CreateWithoutData)
+		);
+		assert_eq!(CallRequest::default().validate(), Ok(()));
+		assert_eq!(
+			TransactionRequest::default().validate(),
+			Err(RequestValidationError::CreateWithoutData)
+		);
+		let transaction = TransactionRequest {
+			data: Some(Bytes(vec![0x60])),
+			..Default::default()
+		};
+		assert_eq!(transaction.validate(), Ok(()));
+	}
+
+	#[test]
+	fn data_and_input_must_match() {
+		let (data, input) = (Bytes(vec![1]), Bytes(vec![2]));
+		assert_eq!(validate_call_data(Some(&data), Some(&data)), Ok(()));
+		assert_eq!(validate_call_data(Some(&data), None), Ok(()));
+		assert_eq!(
+			validate_call_data(Some(&data), Some(&input)),
+			Err(RequestValidationError::DataInputMismatch)
+		);
+
+		let request = CallRequest {
+			to: Some(H160::default()),
+			input: Some(input.clone()),
+			..Default::default()
+		};
+		assert_eq!(request.validate(), Ok(()));
+		assert_eq!(request.call_data(), Some(&input));
+		let request = CallRequest {
+			data: Some(data),
+			..request
+		};
+		assert_eq!(
+			request.validate(),
+			Err(RequestValidationError::DataInputMismatch)
+		);
+	}
+}
@@ -17,13 +17,18 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::types::Bytes;
+use ethereum::{TransactionAction, TransactionV0};
 use ethereum_types::{H160, H256, H512, U256, U64};
-use serde::{ser::SerializeStruct, Serialize, Serializer};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use sha3::{Digest, Keccak256};
 
 /// Transaction
-#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
+	/// EIP-2718 type of the transaction, `0x0` for legacy transactions.
+	#[serde(rename = "type")]
+	pub transaction_type: U64,
 	/// Hash
 	pub hash: H256,
 	/// Nonce
@@ -49,21 +54,136 @@ pub struct Transaction {
 	/// Creates contract
 	pub creates: Option<H160>,
 	/// Raw transaction data
+	#[serde(default)]
 	pub raw: Bytes,
 	/// Public key of the signer.
 	pub public_key: Option<H512>,
 	/// The network id of the transaction, if any.
 	pub chain_id: Option<U64>,
 	/// The standardised V field of the signature (0 or 1).
+	#[serde(default)]
 	pub standard_v: U256,
 	/// The standardised V field of the signature.
 	pub v: U256,
+	/// The parity of the signature's y coordinate, for typed transactions only.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub y_parity: Option<U64>,
 	/// The R field of the signature.
 	pub r: U256,
 	/// The S field of the signature.
 	pub s: U256,
 }
 
+/// Assembles the RPC representation of a transaction from its context.
+pub struct TransactionBuilder {
+	transaction: TransactionV0,
+	hash: Option<H256>,
+	block: Option<(H256, U256)>,
+	transaction_index: Option<U256>,
+	sender: Option<(H160, Option<H512>)>,
+	contract_address: Option<Option<H160>>,
+}
+
+impl TransactionBuilder {
+	pub fn new(transaction: TransactionV0) -> Self {
+		Self {
+			transaction,
+			hash: None,
+			block: None,
+			transaction_index: None,
+			sender: None,
+			contract_address: None,
+		}
+	}
+
+	/// Hash of the transaction, computed from the transaction when not given.
+	pub fn hash(mut self, hash: H256) -> Self {
+		self.hash = Some(hash);
+		self
+	}
+
+	/// Hash and number of the block including the transaction.
+	pub fn block(mut self, hash: H256, number: U256) -> Self {
+		self.block = Some((hash, number));
+		self
+	}
+
+	/// Index of the transaction in its block.
+	pub fn transaction_index(mut self, index: U256) -> Self {
+		self.transaction_index = Some(index);
+		self
+	}
+
+	/// Sender of the transaction, with the public key it was recovered from if known.
+	pub fn sender(mut self, from: H160, public_key: Option<H512>) -> Self {
+		self.sender = Some((from, public_key));
+		self
+	}
+
+	/// Address of the created contract as found in the receipt. When not given, contract
+	/// creations report the address derived from the sender and nonce.
+	pub fn contract_address(mut self, address: Option<H160>) -> Self {
+		self.contract_address = Some(address);
+		self
+	}
+
+	pub fn build(self) -> Transaction {
+		let transaction = self.transaction;
+		let raw = rlp::encode(&transaction).to_vec();
+		let hash = self
+			.hash
+			.unwrap_or_else(|| H256::from_slice(Keccak256::digest(&raw).as_slice()));
+		let (from, public_key) = self.sender.unwrap_or_default();
+		let to = match transaction.action {
+			TransactionAction::Call(to) => Some(to),
+			TransactionAction::Create => None,
+		};
+		let creates = match self.contract_address {
+			Some(address) => address,
+			None if to.is_none() && self.sender.is_some() => {
+				Some(create_address(from, transaction.nonce))
+			}
+			None => None,
+		};
+
+		Transaction {
+			// Only legacy transactions are supported, their `v` folds the chain id (EIP-155)
+			// and their gas price is the effective gas price.
+			transaction_type: U64::zero(),
+			hash,
+			nonce: transaction.nonce,
+			block_hash: self.block.map(|(hash, _)| hash),
+			block_number: self.block.map(|(_, number)| number),
+			transaction_index: self.transaction_index,
+			from,
+			to,
+			value: transaction.value,
+			gas_price: transaction.gas_price,
+			gas: transaction.gas_limit,
+			creates,
+			raw: Bytes(raw),
+			public_key,
+			chain_id: transaction.signature.chain_id().map(U64::from),
+			standard_v: U256::from(transaction.signature.standard_v()),
+			v: U256::from(transaction.signature.v()),
+			y_parity: None,
+			r: U256::from(transaction.signature.r().as_bytes()),
+			s: U256::from(transaction.signature.s().as_bytes()),
+			input: Bytes(transaction.input),
+		}
+	}
+}
+
+/// Address of the contract created by the transaction of `sender` with `nonce`.
+pub fn create_address(sender: H160, nonce: U256) -> H160 {
+	let mut stream = rlp::RlpStream::new_list(2);
+	stream.append(&sender);
+	stream.append(&nonce);
+	H160::from(H256::from_slice(
+		Keccak256::digest(&stream.out()).as_slice(),
+	))
+}
+
 /// Local Transaction Status
 #[derive(Debug)]
 pub enum LocalTransactionStatus {
@@ -154,3 +274,128 @@ pub struct RichRawTransaction {
 	#[serde(rename = "tx")]
 	pub transaction: Transaction,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn legacy_transaction_serialization() {
+		let transaction = Transaction {
+			v: U256::from(37),
+			..Default::default()
+		};
+		let serialized = serde_json::to_value(&transaction).unwrap();
+
+		assert_eq!(serialized["type"], "0x0");
+		assert_eq!(serialized["v"], "0x25");
+		assert!(serialized.get("yParity").is_none());
+	}
+
+	#[test]
+	fn typed_transaction_serialization() {
+		let transaction = Transaction {
+			transaction_type: U64::from(2),
+			y_parity: Some(U64::one()),
+			..Default::default()
+		};
+		let serialized = serde_json::to_value(&transaction).unwrap();
+
+		assert_eq!(serialized["type"], "0x2");
+		assert_eq!(serialized["yParity"], "0x1");
+	}
+
+	#[test]
+	fn create_address_from_sender_and_nonce() {
+		let sender: H160 = "6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0".parse().unwrap();
+
+		assert_eq!(
+			create_address(sender, U256::zero()),
+			"cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d".parse().unwrap()
+		);
+		assert_eq!(
+			create_address(sender, U256::one()),
+			"343c43a37d37dff08ae8c4a11544c718abb4fcf8".parse().unwrap()
+		);
+	}
+
+	#[test]
+	fn geth_transaction_round_trip() {
+		let json = r#"{
+			"blockHash": "0x1d59ff54b1eb26b013ce3cb5fc9dab3705b415a67127a003c3e61eb445bb8df2",
+			"blockNumber": "0x5daf3b",
+			"from": "0xa7d9ddbe1f17865597fbd27ec712455208b6b76d",
+			"gas": "0xc350",
+			"gasPrice": "0x4a817c800",
+			"hash": "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b",
+			"input": "0x68656c6c6f21",
+			"nonce": "0x15",
+			"to": "0xf02c1c8e6114b1dbe8937a39260b5b0a374432bb",
+			"transactionIndex": "0x41",
+			"value": "0xf3dbb76162000",
+			"type": "0x0",
+			"v": "0x25",
+			"r": "0x1b5e176d927f8e9ab405058b2d2457392da3e20f328b16ddabcebc33eaac5fea",
+			"s": "0x4ba69724e8f69de52f0125ad8b3c5c2cef33019bac3249e2c0a2192766d1721c"
+		}"#;
+
+		let transaction: Transaction = serde_json::from_str(json).unwrap();
+		assert_eq!(transaction.nonce, U256::from(0x15));
+		assert_eq!(transaction.input, Bytes(b"hello!".to_vec()));
+		assert_eq!(transaction.creates, None);
+
+		let round_trip: Transaction =
+			serde_json::from_value(serde_json::to_value(&transaction).unwrap()).unwrap();
+		assert_eq!(round_trip, transaction);
+	}
+
+	proptest::proptest! {
+		#[test]
+		fn legacy_transaction_round_trip(
+			nonce: u64,
+			gas_price: u64,
+			gas_limit: u64,
+			to: Option<[u8; 20]>,
+			value: u128,
+			input in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..256),
+			chain_id in proptest::option::of(1..u64::from(u32::MAX)),
+			r in 1..u64::MAX,
+			s in 1..u64::MAX,
+		) {
+			let v = match chain_id {
+				Some(chain_id) => 2 * chain_id + 35,
+				None => 27,
+			};
+			let transaction = TransactionV0 {
+				nonce: nonce.into(),
+				gas_price: gas_price.into(),
+				gas_limit: gas_limit.into(),
+				action: match to {
+					Some(to) => TransactionAction::Call(H160::from(to)),
+					None => TransactionAction::Create,
+				},
+				value: value.into(),
+				input,
+				signature: ethereum::TransactionSignature::new(
+					v,
+					H256::from_low_u64_be(r),
+					H256::from_low_u64_be(s),
+				)
+				.unwrap(),
+			};
+
+			let raw = rlp::encode(&transaction).to_vec();
+			proptest::prop_assert_eq!(
+				rlp::decode::<TransactionV0>(&raw).unwrap(),
+				transaction.clone()
+			);
+
+			let rpc = TransactionBuilder::new(transaction).build();
+			proptest::prop_assert_eq!(&rpc.raw.0, &raw);
+			proptest::prop_assert_eq!(rpc.chain_id, chain_id.map(U64::from));
+			let round_trip: Transaction =
+				serde_json::from_value(serde_json::to_value(&rpc).unwrap()).unwrap();
+			proptest::prop_assert_eq!(round_trip, rpc);
+		}
+	}
+}
@@ -19,7 +19,7 @@
 //! `TransactionRequest` type
 
 use crate::types::Bytes;
-use ethereum_types::{H160, U256};
+use ethereum_types::{H160, U256, U64};
 use serde::{Deserialize, Serialize};
 
 /// Transaction request coming from RPC
@@ -39,6 +39,18 @@ pub struct TransactionRequest {
 	pub value: Option<U256>,
 	/// Additional data sent with transaction
 	pub data: Option<Bytes>,
+	/// Input, the newer name of `data`
+	pub input: Option<Bytes>,
 	/// Transaction's nonce
 	pub nonce: Option<U256>,
+	/// EIP-2718 type of the transaction, only legacy transactions are supported
+	#[serde(rename = "type")]
+	pub transaction_type: Option<U64>,
+}
+
+impl TransactionRequest {
+	/// Calldata of the request, `input` taking precedence over `data`.
+	pub fn call_data(&self) -> Option<&Bytes> {
+		self.input.as_ref().or(self.data.as_ref())
+	}
 }
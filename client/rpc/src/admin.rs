@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Geth style admin namespace, translating the Substrate network state.
+
+use crate::internal_err;
+use ethereum_types::{H256, U256};
+use fc_rpc_core::{
+	types::{AdminEthProtocol, AdminNodeInfo, AdminPeerInfo, AdminPeerNetwork, AdminProtocols},
+	AdminApi as AdminApiT,
+};
+use fp_rpc::EthereumRuntimeRPCApi;
+use futures::channel::oneshot;
+use jsonrpc_core::{BoxFuture, Result};
+use sc_rpc::{system::Request, DenyUnsafe};
+use sc_utils::mpsc::TracingUnboundedSender;
+use serde_json::Value;
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, UniqueSaturatedInto};
+use std::sync::Arc;
+
+pub use fc_rpc_core::AdminApiServer;
+
+/// Version reported for the Ethereum protocol, as `eth_protocolVersion`.
+const PROTOCOL_VERSION: u64 = 1;
+
+pub struct AdminApi<B: BlockT, C> {
+	client: Arc<C>,
+	system_rpc_tx: TracingUnboundedSender<Request<B>>,
+	name: String,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<B: BlockT, C> AdminApi<B, C> {
+	/// `system_rpc_tx` is the channel of the Substrate system RPC to the network worker, `name`
+	/// the client name and version reported by `admin_nodeInfo`.
+	pub fn new(
+		client: Arc<C>,
+		system_rpc_tx: TracingUnboundedSender<Request<B>>,
+		name: String,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self {
+			client,
+			system_rpc_tx,
+			name,
+			deny_unsafe,
+		}
+	}
+}
+
+/// Sends a request to the network worker and waits for its answer.
+async fn request<B: BlockT, T>(
+	system_rpc_tx: TracingUnboundedSender<Request<B>>,
+	request: impl FnOnce(oneshot::Sender<T>) -> Request<B>,
+) -> Result<T> {
+	let (sender, receiver) = oneshot::channel();
+	system_rpc_tx
+		.unbounded_send(request(sender))
+		.map_err(|_| internal_err("network worker stopped"))?;
+	receiver
+		.await
+		.map_err(|_| internal_err("network worker stopped"))
+}
+
+/// Connection to a peer, from its entry in the `connectedPeers` of the network state.
+fn peer_network(peer: &Value) -> AdminPeerNetwork {
+	let endpoint = &peer["endpoint"];
+	let address = |value: &Value| value.as_str().map(str::to_string);
+	match endpoint.get("listening") {
+		Some(listening) => AdminPeerNetwork {
+			inbound: true,
+			local_address: address(&listening["localAddr"]),
+			remote_address: address(&listening["sendBackAddr"]),
+		},
+		None => AdminPeerNetwork {
+			inbound: false,
+			local_address: None,
+			remote_address: address(&endpoint["dialing"]),
+		},
+	}
+}
+
+impl<B, C> AdminApiT for AdminApi<B, C>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+{
+	fn peers(&self) -> BoxFuture<Result<Vec<AdminPeerInfo>>> {
+		let system_rpc_tx = self.system_rpc_tx.clone();
+		let deny_unsafe = self.deny_unsafe;
+		Box::pin(async move {
+			deny_unsafe.check_if_safe()?;
+			let peers = request(system_rpc_tx.clone(), Request::Peers).await?;
+			let state = request(system_rpc_tx, Request::NetworkState).await?;
+
+			Ok(peers
+				.into_iter()
+				.map(|peer| {
+					let connected = &state["connectedPeers"][&peer.peer_id];
+					let network = peer_network(connected);
+					let best_number: u64 = peer.best_number.unique_saturated_into();
+					AdminPeerInfo {
+						enode: match &network.remote_address {
+							Some(address) => format!("{}/p2p/{}", address, peer.peer_id),
+							None => format!("/p2p/{}", peer.peer_id),
+						},
+						name: connected["versionString"]
+							.as_str()
+							.unwrap_or_default()
+							.to_string(),
+						id: peer.peer_id,
+						roles: peer.roles,
+						network,
+						protocols: AdminProtocols {
+							eth: AdminEthProtocol {
+								version: PROTOCOL_VERSION,
+								head: peer.best_hash,
+								number: U256::from(best_number),
+								network: None,
+								genesis: None,
+							},
+						},
+					}
+				})
+				.collect())
+		})
+	}
+
+	fn node_info(&self) -> BoxFuture<Result<AdminNodeInfo>> {
+		let system_rpc_tx = self.system_rpc_tx.clone();
+		let deny_unsafe = self.deny_unsafe;
+		let name = self.name.clone();
+		let info = self.client.info();
+		let chain_id = self
+			.client
+			.runtime_api()
+			.chain_id(&BlockId::Hash(info.best_hash))
+			.map_err(|err| internal_err(format!("fetch runtime chain id failed: {:?}", err)));
+		Box::pin(async move {
+			deny_unsafe.check_if_safe()?;
+			let id = request(system_rpc_tx.clone(), Request::LocalPeerId).await?;
+			// The listen addresses end with the local peer id.
+			let listen_addrs = request(system_rpc_tx, Request::LocalListenAddresses).await?;
+			let best_number: u64 = info.best_number.unique_saturated_into();
+			let enode = listen_addrs.first().cloned();
+			let suffix = format!("/p2p/{}", id);
+
+			Ok(AdminNodeInfo {
+				listen_addr: enode
+					.as_ref()
+					.map(|address| address.trim_end_matches(&suffix).to_string()),
+				enode,
+				id,
+				name,
+				listen_addrs,
+				protocols: AdminProtocols {
+					eth: AdminEthProtocol {
+						version: PROTOCOL_VERSION,
+						head: info.best_hash,
+						number: U256::from(best_number),
+						network: Some(chain_id?),
+						genesis: Some(info.genesis_hash),
+					},
+				},
+			})
+		})
+	}
+}
@@ -0,0 +1,327 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fallback to an upstream archive node for the history a pruned node does not serve.
+//!
+//! The requests at a block older than the earliest block served locally are forwarded to the
+//! upstream node, as are the requests failing locally with a pruned error. The log queries
+//! spanning both histories are split, and their logs merged.
+
+use crate::{frontier_backend_client, internal_err, remote::RemoteNode};
+use fc_rpc_core::types::BlockNumber;
+use futures::FutureExt;
+use jsonrpc_core::{
+	BoxFuture, Error, IoHandler, Metadata, Params, RemoteProcedure, Result, RpcMethod, Value,
+};
+use parking_lot::Mutex;
+use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use serde_json::json;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, UniqueSaturatedInto};
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+/// Index of the block parameter of the methods reading the state at a block.
+fn block_param_index(method: &str) -> Option<usize> {
+	match method {
+		"eth_getBalance"
+		| "eth_getCode"
+		| "eth_getTransactionCount"
+		| "eth_call"
+		| "eth_estimateGas"
+		| "eth_createAccessList"
+		| "debug_traceCall" => Some(1),
+		"eth_getStorageAt" => Some(2),
+		_ => None,
+	}
+}
+
+/// Whether a local error reports pruned history, see `state_pruned_err` and
+/// `history_pruned_err`.
+fn is_pruned_err(error: &Error) -> bool {
+	error
+		.data
+		.as_ref()
+		.and_then(|data| data.get("earliestBlock"))
+		.is_some()
+}
+
+/// Number of a block parameter, None for the tags other than `earliest` and the hashes.
+fn block_number(block: Option<&Value>) -> Option<u64> {
+	match block.map(|block| serde_json::from_value::<BlockNumber>(block.clone())) {
+		Some(Ok(BlockNumber::Num(number))) => Some(number),
+		Some(Ok(BlockNumber::Earliest)) => Some(0),
+		_ => None,
+	}
+}
+
+/// Whether the block parameter refers to a block before `earliest`, the earliest block served
+/// locally. The block hashes are left to the local node, which fails with a pruned error for
+/// them.
+fn is_pruned(block: Option<&Value>, earliest: u64) -> bool {
+	block_number(block).map_or(false, |number| number < earliest)
+}
+
+/// Splits a log query at `earliest`, the earliest block served locally: the upstream part, if
+/// any, and the local part, if any.
+fn split_logs_query(params: &Params, earliest: u64) -> (Option<Params>, Option<Params>) {
+	let filter = match params {
+		Params::Array(values) => values.get(0).and_then(|filter| filter.as_object()),
+		_ => None,
+	};
+	let filter = match filter {
+		Some(filter) if !filter.contains_key("blockHash") => filter,
+		_ => return (None, Some(params.clone())),
+	};
+
+	match (
+		block_number(filter.get("fromBlock")),
+		block_number(filter.get("toBlock")),
+	) {
+		(Some(from), _) if from >= earliest => (None, Some(params.clone())),
+		(Some(_), Some(to)) if to < earliest => (Some(params.clone()), None),
+		(Some(_), _) => {
+			let mut upstream = filter.clone();
+			upstream.insert(
+				"toBlock".to_string(),
+				json!(format!("0x{:x}", earliest - 1)),
+			);
+			let mut local = filter.clone();
+			local.insert("fromBlock".to_string(), json!(format!("0x{:x}", earliest)));
+			(
+				Some(Params::Array(vec![Value::Object(upstream)])),
+				Some(Params::Array(vec![Value::Object(local)])),
+			)
+		}
+		_ => (None, Some(params.clone())),
+	}
+}
+
+/// Upstream archive node serving the pruned history.
+pub struct ArchiveFallback<B: BlockT, C, BE> {
+	remote: RemoteNode,
+	client: Arc<C>,
+	history_window: Option<u64>,
+	/// Best block number and earliest block served locally at that block.
+	earliest_block: Mutex<Option<(u64, u64)>>,
+	_marker: PhantomData<(B, BE)>,
+}
+
+impl<B, C, BE> ArchiveFallback<B, C, BE>
+where
+	B: BlockT,
+	C: StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	/// Falls back to the node at `url` for the blocks whose state was pruned, or older than
+	/// the latest `history_window` blocks.
+	pub fn new(
+		url: String,
+		timeout: Duration,
+		client: Arc<C>,
+		history_window: Option<u64>,
+	) -> Self {
+		Self {
+			remote: RemoteNode::new(url, timeout),
+			client,
+			history_window,
+			earliest_block: Mutex::new(None),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Earliest block served locally. Its bisection of the available states runs once per
+	/// best block.
+	fn earliest_block(&self) -> u64 {
+		let best_number: u64 = self.client.info().best_number.unique_saturated_into();
+		let mut cached = self.earliest_block.lock();
+		match *cached {
+			Some((number, earliest)) if number == best_number => earliest,
+			_ => {
+				let earliest = frontier_backend_client::earliest_history_block::<B, C, BE>(
+					self.client.as_ref(),
+					self.history_window,
+				);
+				*cached = Some((best_number, earliest));
+				earliest
+			}
+		}
+	}
+
+	/// Wraps all the methods of `io` so that they fall back to the archive node.
+	pub fn apply<M: Metadata>(self: Arc<Self>, io: IoHandler<M>) -> IoHandler<M>
+	where
+		B: 'static,
+	{
+		let mut wrapped = IoHandler::default();
+		wrapped.extend_with(io.iter().map(|(name, procedure)| {
+			let procedure = match procedure {
+				RemoteProcedure::Method(method) if name.starts_with("eth_") => {
+					RemoteProcedure::Method(Arc::new(ArchiveFallbackMethod {
+						name: name.clone(),
+						method: method.clone(),
+						archive: self.clone(),
+					}) as Arc<dyn RpcMethod<M>>)
+				}
+				other => other.clone(),
+			};
+			(name.clone(), procedure)
+		}));
+		wrapped
+	}
+}
+
+struct ArchiveFallbackMethod<M: Metadata, B: BlockT, C, BE> {
+	name: String,
+	method: Arc<dyn RpcMethod<M>>,
+	archive: Arc<ArchiveFallback<B, C, BE>>,
+}
+
+impl<M, B, C, BE> RpcMethod<M> for ArchiveFallbackMethod<M, B, C, BE>
+where
+	M: Metadata,
+	B: BlockT,
+	C: StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Result<Value>> {
+		let remote = self.archive.remote.clone();
+		let name = self.name.clone();
+
+		if self.name == "eth_getLogs" {
+			return match split_logs_query(&params, self.archive.earliest_block()) {
+				(Some(upstream), None) => {
+					Box::pin(async move { remote.request(&name, upstream).await })
+				}
+				(Some(upstream), Some(local)) => {
+					let local = self.method.call(local, meta);
+					Box::pin(async move {
+						let upstream = match remote.request(&name, upstream).await? {
+							Value::Array(logs) => logs,
+							_ => return Err(internal_err("invalid archive logs")),
+						};
+						match local.await? {
+							Value::Array(logs) => {
+								Ok(Value::Array(upstream.into_iter().chain(logs).collect()))
+							}
+							other => Ok(other),
+						}
+					})
+				}
+				(None, _) => self.call_local(params, meta),
+			};
+		}
+
+		let block = match (&params, block_param_index(&self.name)) {
+			(Params::Array(values), Some(index)) => values.get(index),
+			_ => None,
+		};
+		if is_pruned(block, self.archive.earliest_block()) {
+			return Box::pin(async move { remote.request(&name, params).await });
+		}
+		self.call_local(params, meta)
+	}
+}
+
+impl<M, B, C, BE> ArchiveFallbackMethod<M, B, C, BE>
+where
+	M: Metadata,
+	B: BlockT,
+	C: StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	/// Calls the local method, forwarding the request when it fails with a pruned error.
+	fn call_local(&self, params: Params, meta: M) -> BoxFuture<Result<Value>> {
+		let name = self.name.clone();
+		let remote = self.archive.remote.clone();
+		let local = self.method.call(params.clone(), meta);
+		Box::pin(local.then(move |result| async move {
+			match result {
+				Err(err) if is_pruned_err(&err) => remote.request(&name, params).await,
+				result => result,
+			}
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn params(value: Value) -> Params {
+		serde_json::from_value(value).unwrap()
+	}
+
+	#[test]
+	fn block_numbers_before_the_earliest_block_are_pruned() {
+		assert!(is_pruned(Some(&json!("0x9")), 10));
+		assert!(is_pruned(Some(&json!("earliest")), 10));
+		assert!(!is_pruned(Some(&json!("0xa")), 10));
+		assert!(!is_pruned(Some(&json!("latest")), 10));
+		assert!(!is_pruned(Some(&json!("pending")), 10));
+		assert!(!is_pruned(None, 10));
+		assert!(!is_pruned(Some(&json!("earliest")), 0));
+	}
+
+	#[test]
+	fn block_hashes_are_left_to_the_local_node() {
+		let hash = json!({ "blockHash": format!("0x{}", "11".repeat(32)) });
+		assert!(!is_pruned(Some(&hash), 10));
+		let query = params(json!([{ "blockHash": "0x01" }]));
+		assert_eq!(split_logs_query(&query, 10), (None, Some(query.clone())));
+	}
+
+	#[test]
+	fn local_log_queries_are_not_split() {
+		for filter in &[
+			json!([{ "fromBlock": "0xa", "toBlock": "0x20" }]),
+			json!([{ "fromBlock": "0xa" }]),
+			json!([{ "fromBlock": "latest" }]),
+			json!([{}]),
+		] {
+			let query = params(filter.clone());
+			assert_eq!(split_logs_query(&query, 10), (None, Some(query.clone())));
+		}
+	}
+
+	#[test]
+	fn pruned_log_queries_go_upstream() {
+		let query = params(json!([{ "fromBlock": "earliest", "toBlock": "0x9" }]));
+		assert_eq!(split_logs_query(&query, 10), (Some(query.clone()), None));
+	}
+
+	#[test]
+	fn log_queries_spanning_both_histories_are_split() {
+		let address = "0x1111111111111111111111111111111111111111";
+		let query =
+			params(json!([{ "fromBlock": "0x2", "toBlock": "latest", "address": address }]));
+		assert_eq!(
+			split_logs_query(&query, 10),
+			(
+				Some(params(
+					json!([{ "fromBlock": "0x2", "toBlock": "0x9", "address": address }])
+				)),
+				Some(params(
+					json!([{ "fromBlock": "0xa", "toBlock": "latest", "address": address }])
+				)),
+			)
+		);
+	}
+}
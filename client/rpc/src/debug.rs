@@ -0,0 +1,538 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	frontier_backend_client, internal_err, invalid_request_err, overrides::storage_prefix_build,
+	spawn_blocking_request, trace_call,
+};
+use codec::{Decode, Encode};
+use ethereum_types::{H160, H256, U256};
+use fc_rpc_core::{
+	types::{
+		AccountRange, BadBlock, BlockNumber, BundlerCall, BundlerLog, BundlerTrace, Bytes,
+		CallRequest, ContractSizeInfo, DumpAccount, StateDump, StorageAccessInfo, TopLevelCallInfo,
+		TraceCallOptions,
+	},
+	DebugApi as DebugApiT,
+};
+use fp_rpc::EthereumRuntimeRPCApi;
+use jsonrpc_core::{BoxFuture, Error, ErrorCode, Result};
+use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use sc_rpc::DenyUnsafe;
+use sc_service::SpawnTaskHandle;
+use sha3::{Digest, Keccak256};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, Header as HeaderT, UniqueSaturatedInto};
+use sp_storage::StorageKey;
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+pub use fc_rpc_core::DebugApiServer;
+
+/// Maximum number of accounts of a `debug_accountRange` page.
+const MAX_ACCOUNT_RANGE: u64 = 256;
+
+/// Maximum number of accounts of a `debug_dumpBlock` page.
+const MAX_DUMP_ACCOUNTS: usize = 64;
+
+/// Maximum number of storage slots dumped per account.
+const MAX_ACCOUNT_STORAGE: usize = 1024;
+
+/// Name of the ERC-4337 bundler tracer, the only tracer of `debug_traceCall`.
+const BUNDLER_COLLECTOR_TRACER: &str = "bundlerCollectorTracer";
+
+/// Name of an opcode, as geth gives it.
+fn opcode_name(opcode: u8) -> String {
+	let name = match opcode {
+		0x00 => "STOP",
+		0x01 => "ADD",
+		0x02 => "MUL",
+		0x03 => "SUB",
+		0x04 => "DIV",
+		0x05 => "SDIV",
+		0x06 => "MOD",
+		0x07 => "SMOD",
+		0x08 => "ADDMOD",
+		0x09 => "MULMOD",
+		0x0a => "EXP",
+		0x0b => "SIGNEXTEND",
+		0x10 => "LT",
+		0x11 => "GT",
+		0x12 => "SLT",
+		0x13 => "SGT",
+		0x14 => "EQ",
+		0x15 => "ISZERO",
+		0x16 => "AND",
+		0x17 => "OR",
+		0x18 => "XOR",
+		0x19 => "NOT",
+		0x1a => "BYTE",
+		0x1b => "SHL",
+		0x1c => "SHR",
+		0x1d => "SAR",
+		0x20 => "KECCAK256",
+		0x30 => "ADDRESS",
+		0x31 => "BALANCE",
+		0x32 => "ORIGIN",
+		0x33 => "CALLER",
+		0x34 => "CALLVALUE",
+		0x35 => "CALLDATALOAD",
+		0x36 => "CALLDATASIZE",
+		0x37 => "CALLDATACOPY",
+		0x38 => "CODESIZE",
+		0x39 => "CODECOPY",
+		0x3a => "GASPRICE",
+		0x3b => "EXTCODESIZE",
+		0x3c => "EXTCODECOPY",
+		0x3d => "RETURNDATASIZE",
+		0x3e => "RETURNDATACOPY",
+		0x3f => "EXTCODEHASH",
+		0x40 => "BLOCKHASH",
+		0x41 => "COINBASE",
+		0x42 => "TIMESTAMP",
+		0x43 => "NUMBER",
+		0x44 => "DIFFICULTY",
+		0x45 => "GASLIMIT",
+		0x46 => "CHAINID",
+		0x47 => "SELFBALANCE",
+		0x48 => "BASEFEE",
+		0x50 => "POP",
+		0x51 => "MLOAD",
+		0x52 => "MSTORE",
+		0x53 => "MSTORE8",
+		0x54 => "SLOAD",
+		0x55 => "SSTORE",
+		0x56 => "JUMP",
+		0x57 => "JUMPI",
+		0x58 => "PC",
+		0x59 => "MSIZE",
+		0x5a => "GAS",
+		0x5b => "JUMPDEST",
+		0x60..=0x7f => return format!("PUSH{}", opcode - 0x5f),
+		0x80..=0x8f => return format!("DUP{}", opcode - 0x7f),
+		0x90..=0x9f => return format!("SWAP{}", opcode - 0x8f),
+		0xa0..=0xa4 => return format!("LOG{}", opcode - 0xa0),
+		0xf0 => "CREATE",
+		0xf1 => "CALL",
+		0xf2 => "CALLCODE",
+		0xf3 => "RETURN",
+		0xf4 => "DELEGATECALL",
+		0xf5 => "CREATE2",
+		0xfa => "STATICCALL",
+		0xfd => "REVERT",
+		0xfe => "INVALID",
+		0xff => "SELFDESTRUCT",
+		_ => return format!("opcode {:#x} not defined", opcode),
+	};
+	name.to_string()
+}
+
+/// Result of the bundler collector tracer from the trace of the runtime.
+fn bundler_trace(trace: fp_evm::BundlerTrace) -> BundlerTrace {
+	BundlerTrace {
+		calls_from_entry_point: trace
+			.calls_from_entry_point
+			.into_iter()
+			.map(|call| TopLevelCallInfo {
+				top_level_method_sig: call.method_sig.into(),
+				top_level_target_address: call.target,
+				opcodes: call
+					.opcodes
+					.into_iter()
+					.map(|(opcode, count)| (opcode_name(opcode), count))
+					.collect(),
+				access: call
+					.access
+					.into_iter()
+					.map(|(address, access)| {
+						(
+							address,
+							StorageAccessInfo {
+								reads: access.reads,
+								writes: access.writes,
+							},
+						)
+					})
+					.collect(),
+				contract_size: call
+					.contract_size
+					.into_iter()
+					.map(|(address, (size, opcode))| {
+						(
+							address,
+							ContractSizeInfo {
+								contract_size: size,
+								opcode: opcode_name(opcode),
+							},
+						)
+					})
+					.collect(),
+				ext_code_access_info: call
+					.ext_code_access_info
+					.into_iter()
+					.map(|(address, opcode)| (address, opcode_name(opcode)))
+					.collect(),
+				oog: call.oog,
+			})
+			.collect(),
+		keccak: trace.keccak.into_iter().map(Bytes::new).collect(),
+		logs: trace
+			.logs
+			.into_iter()
+			.map(|log| BundlerLog {
+				topics: log.topics,
+				data: log.data.into(),
+			})
+			.collect(),
+		calls: trace
+			.calls
+			.into_iter()
+			.map(|call| match call {
+				fp_evm::CallFrame::Enter {
+					opcode,
+					from,
+					to,
+					method,
+					value,
+					gas,
+				} => BundlerCall::Enter {
+					kind: opcode_name(opcode),
+					from,
+					to,
+					method: method.into(),
+					gas,
+					value,
+				},
+				fp_evm::CallFrame::Exit {
+					reverted,
+					gas_used,
+					data,
+				} => BundlerCall::Exit {
+					kind: if reverted { "REVERT" } else { "RETURN" }.to_string(),
+					gas_used,
+					data: data.into(),
+				},
+			})
+			.collect(),
+		debug: Vec::new(),
+	}
+}
+
+pub struct DebugApi<B: BlockT, C, BE> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	deny_unsafe: DenyUnsafe,
+	spawn_handle: SpawnTaskHandle,
+	_marker: PhantomData<(B, BE)>,
+}
+
+impl<B: BlockT, C, BE> DebugApi<B, C, BE> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
+		deny_unsafe: DenyUnsafe,
+		spawn_handle: SpawnTaskHandle,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			deny_unsafe,
+			spawn_handle,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B: BlockT, C, BE> Clone for DebugApi<B, C, BE> {
+	fn clone(&self) -> Self {
+		Self {
+			client: self.client.clone(),
+			backend: self.backend.clone(),
+			deny_unsafe: self.deny_unsafe,
+			spawn_handle: self.spawn_handle.clone(),
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C, BE> DebugApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	fn block_id(&self, number: BlockNumber) -> Result<BlockId<B>> {
+		frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number),
+		)?
+		.ok_or_else(|| internal_err("block not found"))
+	}
+
+	fn state_root(&self, id: &BlockId<B>) -> Result<H256> {
+		Ok(self
+			.client
+			.runtime_api()
+			.current_block(id)
+			.map_err(|err| internal_err(format!("fetch runtime block failed: {:?}", err)))?
+			.map(|block| block.header.state_root)
+			.unwrap_or_default())
+	}
+
+	/// Prefix of the storage the accounts are iterated from: `System::Account` when the account
+	/// ids of the runtime are the Ethereum addresses, so that the externally owned accounts are
+	/// included, the contract accounts of `EVM::AccountCodes` otherwise. The externally owned
+	/// accounts of the runtimes hashing the addresses into their account ids can't be recovered
+	/// from the state. Both storages are keyed by the Blake2_128Concat hashed address.
+	fn accounts_prefix(&self, id: &BlockId<B>) -> Result<StorageKey> {
+		let system = StorageKey(storage_prefix_build(b"System", b"Account"));
+		let first = self
+			.client
+			.storage_keys_iter(id, Some(&system), None)
+			.map_err(|err| internal_err(format!("fetch storage failed: {:?}", err)))?
+			.next();
+
+		Ok(match first {
+			Some(key) if key.0.len() == system.0.len() + 16 + 20 => system,
+			_ => StorageKey(storage_prefix_build(b"EVM", b"AccountCodes")),
+		})
+	}
+
+	/// Dumps the account with the given Blake2_128Concat hashed address, with at most
+	/// `MAX_ACCOUNT_STORAGE` storage slots.
+	fn dump_account(
+		&self,
+		id: &BlockId<B>,
+		hashed_address: &[u8],
+		with_code: bool,
+		with_storage: bool,
+	) -> Result<(H160, DumpAccount)> {
+		let address = H160::from_slice(&hashed_address[16..]);
+
+		let mut code_key = storage_prefix_build(b"EVM", b"AccountCodes");
+		code_key.extend_from_slice(hashed_address);
+		let code = self
+			.client
+			.storage(id, &StorageKey(code_key))
+			.map_err(|err| internal_err(format!("fetch storage failed: {:?}", err)))?
+			.and_then(|data| Vec::<u8>::decode(&mut &data.0[..]).ok())
+			.unwrap_or_default();
+		let basic = self
+			.client
+			.runtime_api()
+			.account_basic(id, address)
+			.map_err(|err| {
+				internal_err(format!("fetch runtime account basic failed: {:?}", err))
+			})?;
+
+		let mut storage_truncated = false;
+		let storage = if with_storage {
+			let mut prefix = storage_prefix_build(b"EVM", b"AccountStorages");
+			prefix.extend_from_slice(hashed_address);
+			let prefix = StorageKey(prefix);
+			let mut keys = self
+				.client
+				.storage_keys_iter(id, Some(&prefix), None)
+				.map_err(|err| internal_err(format!("fetch storage failed: {:?}", err)))?;
+
+			let mut storage = BTreeMap::new();
+			for key in keys.by_ref().take(MAX_ACCOUNT_STORAGE) {
+				let value = self
+					.client
+					.storage(id, &key)
+					.map_err(|err| internal_err(format!("fetch storage failed: {:?}", err)))?
+					.and_then(|data| H256::decode(&mut &data.0[..]).ok());
+				if let Some(value) = value {
+					// The slot is the Blake2_128Concat hashed suffix of the key.
+					storage.insert(H256::from_slice(&key.0[prefix.0.len() + 16..]), value);
+				}
+			}
+			storage_truncated = keys.next().is_some();
+			Some(storage)
+		} else {
+			None
+		};
+
+		Ok((
+			address,
+			DumpAccount {
+				balance: basic.balance,
+				nonce: basic.nonce,
+				code_hash: H256::from_slice(Keccak256::digest(&code).as_slice()),
+				code: if with_code { Some(code.into()) } else { None },
+				storage,
+				storage_truncated,
+				key: hashed_address.to_vec().into(),
+			},
+		))
+	}
+
+	/// Dumps at most `max_results` accounts, from the `start` key on. Returns the key of the
+	/// first account left out, if any.
+	fn dump_accounts(
+		&self,
+		id: &BlockId<B>,
+		start: Vec<u8>,
+		max_results: usize,
+		with_code: bool,
+		with_storage: bool,
+	) -> Result<(BTreeMap<H160, DumpAccount>, Option<Bytes>)> {
+		let prefix = self.accounts_prefix(id)?;
+		let start_key = StorageKey([&prefix.0[..], &start[..]].concat());
+
+		// The iterator starts after the start key, which is itself part of the page when stored.
+		let first = self
+			.client
+			.storage_hash(id, &start_key)
+			.map_err(|err| internal_err(format!("fetch storage failed: {:?}", err)))?
+			.filter(|_| start_key.0.len() > prefix.0.len())
+			.map(|_| start_key.clone());
+		let mut keys = first.into_iter().chain(
+			self.client
+				.storage_keys_iter(id, Some(&prefix), Some(&start_key))
+				.map_err(|err| internal_err(format!("fetch storage failed: {:?}", err)))?,
+		);
+
+		let mut accounts = BTreeMap::new();
+		for key in keys.by_ref().take(max_results) {
+			let (address, account) =
+				self.dump_account(id, &key.0[prefix.0.len()..], with_code, with_storage)?;
+			accounts.insert(address, account);
+		}
+
+		Ok((
+			accounts,
+			keys.next()
+				.map(|key| key.0[prefix.0.len()..].to_vec().into()),
+		))
+	}
+}
+
+impl<B, C, BE> DebugApiT for DebugApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	fn account_range(
+		&self,
+		number: BlockNumber,
+		start: Bytes,
+		max_results: u64,
+		no_code: bool,
+		no_storage: bool,
+	) -> BoxFuture<Result<AccountRange>> {
+		let api = self.clone();
+
+		spawn_blocking_request(
+			&self.spawn_handle,
+			"frontier-rpc-account-range",
+			move || {
+				api.deny_unsafe.check_if_safe()?;
+				let id = api.block_id(number)?;
+				let max = max_results.min(MAX_ACCOUNT_RANGE) as usize;
+				let (accounts, next) =
+					api.dump_accounts(&id, start.into_vec(), max, !no_code, !no_storage)?;
+
+				Ok(AccountRange {
+					root: api.state_root(&id)?,
+					accounts,
+					next,
+				})
+			},
+		)
+	}
+
+	fn dump_block(
+		&self,
+		number: BlockNumber,
+		start: Option<Bytes>,
+	) -> BoxFuture<Result<StateDump>> {
+		let api = self.clone();
+
+		spawn_blocking_request(&self.spawn_handle, "frontier-rpc-dump-block", move || {
+			api.deny_unsafe.check_if_safe()?;
+			let id = api.block_id(number)?;
+			let start = start.map(Bytes::into_vec).unwrap_or_default();
+			let (accounts, next) = api.dump_accounts(&id, start, MAX_DUMP_ACCOUNTS, true, true)?;
+
+			Ok(StateDump {
+				root: api.state_root(&id)?,
+				accounts,
+				next,
+			})
+		})
+	}
+
+	fn bad_blocks(&self) -> Result<Vec<BadBlock>> {
+		let bad_blocks = self
+			.backend
+			.bad_blocks()
+			.bad_blocks()
+			.map_err(|err| internal_err(format!("fetch bad blocks failed: {:?}", err)))?;
+
+		Ok(bad_blocks
+			.into_iter()
+			.rev()
+			.map(|bad_block| {
+				let number: u64 = (*bad_block.header.number()).unique_saturated_into();
+				BadBlock {
+					hash: bad_block.header.hash(),
+					number: U256::from(number),
+					ethereum_hash: fp_consensus::find_log(bad_block.header.digest())
+						.ok()
+						.map(|log| log.into_hashes().block_hash),
+					reason: bad_block.reason,
+					header: bad_block.header.encode().into(),
+					body: bad_block.body.map(|body| body.encode().into()),
+				}
+			})
+			.collect())
+	}
+
+	fn trace_call(
+		&self,
+		request: CallRequest,
+		number: BlockNumber,
+		options: Option<TraceCallOptions>,
+	) -> Result<BundlerTrace> {
+		match options.and_then(|options| options.tracer) {
+			Some(tracer) if tracer == BUNDLER_COLLECTOR_TRACER => (),
+			tracer => {
+				return Err(Error {
+					code: ErrorCode::InvalidParams,
+					message: format!(
+						"unsupported tracer {}, only {} is supported",
+						tracer.as_deref().unwrap_or("struct logger"),
+						BUNDLER_COLLECTOR_TRACER
+					),
+					data: None,
+				})
+			}
+		}
+		request.validate().map_err(invalid_request_err)?;
+		let id = self.block_id(number)?;
+		let trace = trace_call::<B, C>(self.client.as_ref(), id, &request, None)?;
+		Ok(bundler_trace(trace.bundler))
+	}
+}
@@ -0,0 +1,399 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Dev chain helpers used by the Hardhat and Ganache test suites.
+//!
+//! Blocks are sealed through the manual seal engine, and the state changes are queued in the
+//! [`DevState`] until they are provided to `pallet-evm-dev` as inherent data of the next block.
+
+use crate::internal_err;
+use ethereum_types::{H160, H256, U256};
+use fc_rpc_core::{types::Bytes, DevApi as DevApiT};
+use futures::{
+	channel::{mpsc, oneshot},
+	SinkExt, TryFutureExt,
+};
+use jsonrpc_core::{BoxFuture, Result};
+use pallet_evm_dev::DevCommand;
+use parking_lot::Mutex;
+use sc_client_api::backend::Backend;
+use sc_consensus_manual_seal::rpc::EngineCommand;
+use sha3::{Digest, Keccak256};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, Saturating, Zero},
+};
+use std::{collections::BTreeSet, marker::PhantomData, mem, sync::Arc};
+
+pub use fc_rpc_core::DevApiServer;
+
+/// Timestamps of the sealed blocks, in milliseconds.
+#[derive(Default)]
+struct Clock {
+	last: u64,
+	next: Option<u64>,
+	pending_increase: u64,
+	total_increase: u64,
+}
+
+/// State shared by the dev RPC and the block authorship task.
+pub struct DevState<B: BlockT> {
+	command_sink: mpsc::Sender<EngineCommand<B::Hash>>,
+	slot_duration: u64,
+	commands: Mutex<Vec<DevCommand>>,
+	transaction_sinks: Mutex<Vec<mpsc::UnboundedSender<H256>>>,
+	clock: Mutex<Clock>,
+	impersonated: Mutex<BTreeSet<H160>>,
+	snapshots: Mutex<Vec<NumberFor<B>>>,
+}
+
+impl<B: BlockT> DevState<B> {
+	pub fn new(command_sink: mpsc::Sender<EngineCommand<B::Hash>>, slot_duration: u64) -> Self {
+		Self {
+			command_sink,
+			slot_duration,
+			commands: Mutex::new(Vec::new()),
+			transaction_sinks: Mutex::new(Vec::new()),
+			clock: Mutex::new(Clock::default()),
+			impersonated: Mutex::new(BTreeSet::new()),
+			snapshots: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Takes the state changes to apply in the next block.
+	pub fn take_commands(&self) -> Vec<DevCommand> {
+		mem::take(&mut *self.commands.lock())
+	}
+
+	/// Timestamp of the next block in milliseconds, a slot after the previous one unless it
+	/// was set or increased through the RPC.
+	pub fn next_timestamp(&self) -> u64 {
+		let mut clock = self.clock.lock();
+		clock.last = match clock.next.take() {
+			Some(timestamp) => timestamp,
+			None => clock.last + self.slot_duration + mem::take(&mut clock.pending_increase),
+		};
+		clock.last
+	}
+
+	/// Timestamp the next block will be sealed with, in milliseconds.
+	pub fn pending_timestamp(&self) -> u64 {
+		let clock = self.clock.lock();
+		clock
+			.next
+			.unwrap_or(clock.last + self.slot_duration + clock.pending_increase)
+	}
+
+	/// Queues state changes for the next block.
+	pub(crate) fn queue_commands(&self, commands: Vec<DevCommand>) {
+		self.commands.lock().extend(commands);
+	}
+
+	/// Transactions queued for the next block.
+	pub fn pending_transactions(&self) -> Vec<ethereum::TransactionV0> {
+		self.commands
+			.lock()
+			.iter()
+			.filter_map(|command| match command {
+				DevCommand::Transact(_, transaction) => Some(transaction.clone()),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Nonce of `address` once its transactions queued for the next block are applied.
+	pub fn pending_nonce(&self, address: H160, nonce: U256) -> U256 {
+		self.commands
+			.lock()
+			.iter()
+			.fold(nonce, |nonce, command| match command {
+				DevCommand::Transact(source, transaction)
+					if *source == address && transaction.nonce == nonce =>
+				{
+					nonce.saturating_add(U256::one())
+				}
+				_ => nonce,
+			})
+	}
+
+	/// Stream of the hashes of the transactions queued for the next block.
+	pub fn pending_transactions_stream(&self) -> mpsc::UnboundedReceiver<H256> {
+		let (sink, stream) = mpsc::unbounded();
+		self.transaction_sinks.lock().push(sink);
+		stream
+	}
+
+	/// Whether transactions from `address` are sent without signature.
+	pub fn is_impersonated(&self, address: &H160) -> bool {
+		self.impersonated.lock().contains(address)
+	}
+
+	/// Applies the transaction message on behalf of `from` in a new block, returns its hash.
+	pub fn transact(
+		&self,
+		message: ethereum::LegacyTransactionMessage,
+		from: H160,
+	) -> BoxFuture<Result<H256>> {
+		// The signature is never checked, it only needs to be well formed and to make the
+		// transaction hash unique.
+		let v = match message.chain_id {
+			None => 27,
+			Some(chain_id) => 2 * chain_id + 35,
+		};
+		let signature = match ethereum::TransactionSignature::new(
+			v,
+			H256::from(from),
+			H256::from_low_u64_be(1),
+		) {
+			Some(signature) => signature,
+			None => {
+				return Box::pin(futures::future::err(internal_err(
+					"cannot impersonate the zero address",
+				)))
+			}
+		};
+		let transaction = ethereum::TransactionV0 {
+			nonce: message.nonce,
+			gas_price: message.gas_price,
+			gas_limit: message.gas_limit,
+			action: message.action,
+			value: message.value,
+			input: message.input,
+			signature,
+		};
+		let transaction_hash =
+			H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+
+		self.commands
+			.lock()
+			.push(DevCommand::Transact(from, transaction));
+		self.transaction_sinks
+			.lock()
+			.retain(|sink| sink.unbounded_send(transaction_hash).is_ok());
+		Box::pin(self.seal().map_ok(move |_| transaction_hash))
+	}
+
+	/// Seals a new block, returns its hash.
+	pub fn seal(&self) -> BoxFuture<Result<B::Hash>> {
+		let mut command_sink = self.command_sink.clone();
+		Box::pin(async move {
+			let (sender, receiver) = oneshot::channel();
+			command_sink
+				.send(EngineCommand::SealNewBlock {
+					create_empty: true,
+					finalize: false,
+					parent_hash: None,
+					sender: Some(sender),
+				})
+				.await
+				.map_err(|err| internal_err(format!("send seal command failed: {:?}", err)))?;
+
+			match receiver.await {
+				Ok(Ok(block)) => Ok(block.hash),
+				Ok(Err(err)) => Err(internal_err(format!("seal block failed: {:?}", err))),
+				Err(_) => Err(internal_err("block authorship task stopped")),
+			}
+		})
+	}
+}
+
+pub struct DevApi<B: BlockT, C, BE> {
+	client: Arc<C>,
+	backend: Arc<BE>,
+	frontier_backend: Arc<fc_db::Backend<B>>,
+	state: Arc<DevState<B>>,
+	_marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C, BE> DevApi<B, C, BE> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<BE>,
+		frontier_backend: Arc<fc_db::Backend<B>>,
+		state: Arc<DevState<B>>,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			frontier_backend,
+			state,
+			_marker: PhantomData,
+		}
+	}
+
+	fn apply(&self, command: DevCommand) -> BoxFuture<Result<bool>> {
+		self.state.commands.lock().push(command);
+		Box::pin(self.state.seal().map_ok(|_| true))
+	}
+}
+
+impl<B, C, BE> DevApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+{
+	/// Headers of the best chain numbered above `number`.
+	fn best_headers_above(&self, number: NumberFor<B>) -> Result<Vec<B::Header>> {
+		let mut headers = Vec::new();
+		let mut hash = self.client.info().best_hash;
+		loop {
+			let header = self
+				.client
+				.header(BlockId::Hash(hash))
+				.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+				.ok_or_else(|| internal_err(format!("header {:?} not found", hash)))?;
+			if *header.number() <= number {
+				return Ok(headers);
+			}
+			hash = *header.parent_hash();
+			headers.push(header);
+		}
+	}
+
+	/// Removes the Ethereum mapping of the `headers` which were reverted, so their blocks and
+	/// transactions are no longer served and the mapping sync does not resume from them.
+	fn remove_mapping(&self, headers: Vec<B::Header>) -> std::result::Result<(), String> {
+		let mut reverted = Vec::new();
+		for header in headers {
+			let hash = header.hash();
+			if self
+				.client
+				.hash(*header.number())
+				.map_err(|e| format!("{:?}", e))?
+				== Some(hash)
+			{
+				continue;
+			}
+			// Blocks without Ethereum log only have their synced flag to remove.
+			let (ethereum_block_hash, ethereum_transaction_hashes) =
+				match fp_consensus::find_log(header.digest()) {
+					Ok(log) => {
+						let post_hashes = log.into_hashes();
+						(post_hashes.block_hash, post_hashes.transaction_hashes)
+					}
+					Err(_) => (H256::zero(), Vec::new()),
+				};
+			self.frontier_backend
+				.mapping()
+				.remove_block(fc_db::MappingCommitment {
+					block_hash: hash,
+					ethereum_block_hash,
+					ethereum_transaction_hashes,
+				})?;
+			reverted.push(hash);
+		}
+
+		let mut tips = self.frontier_backend.meta().current_syncing_tips()?;
+		tips.retain(|tip| !reverted.contains(tip));
+		self.frontier_backend
+			.meta()
+			.write_current_syncing_tips(tips)?;
+
+		if let Some(log_index) = self.frontier_backend.log_index() {
+			log_index.write_batch(&reverted, &[], Vec::new(), None)?;
+		}
+		Ok(())
+	}
+}
+
+impl<B, C, BE> DevApiT for DevApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+{
+	fn mine(&self, timestamp: Option<U256>) -> BoxFuture<Result<U256>> {
+		if let Some(timestamp) = timestamp {
+			if let Err(err) = self.set_next_block_timestamp(timestamp) {
+				return Box::pin(futures::future::err(err));
+			}
+		}
+		Box::pin(self.state.seal().map_ok(|_| U256::zero()))
+	}
+
+	fn set_next_block_timestamp(&self, timestamp: U256) -> Result<U256> {
+		let mut clock = self.state.clock.lock();
+		let next = timestamp.low_u64().saturating_mul(1000);
+		if next <= clock.last {
+			return Err(internal_err(format!(
+				"timestamp {} is not greater than the previous block timestamp {}",
+				timestamp,
+				clock.last / 1000
+			)));
+		}
+		clock.next = Some(next);
+		Ok(timestamp)
+	}
+
+	fn increase_time(&self, seconds: U256) -> Result<U256> {
+		let mut clock = self.state.clock.lock();
+		clock.pending_increase += seconds.low_u64().saturating_mul(1000);
+		clock.total_increase += seconds.low_u64();
+		Ok(U256::from(clock.total_increase))
+	}
+
+	fn snapshot(&self) -> Result<U256> {
+		let mut snapshots = self.state.snapshots.lock();
+		snapshots.push(self.client.info().best_number);
+		Ok(U256::from(snapshots.len()))
+	}
+
+	fn revert(&self, id: U256) -> Result<bool> {
+		let mut snapshots = self.state.snapshots.lock();
+		if id.is_zero() || id > U256::from(snapshots.len()) {
+			return Ok(false);
+		}
+		let number = snapshots[id.low_u64() as usize - 1];
+		snapshots.truncate(id.low_u64() as usize - 1);
+
+		let count = self.client.info().best_number.saturating_sub(number);
+		if !count.is_zero() {
+			let headers = self.best_headers_above(number)?;
+			self.backend
+				.revert(count, false)
+				.map_err(|err| internal_err(format!("revert blocks failed: {:?}", err)))?;
+			self.remove_mapping(headers)
+				.map_err(|err| internal_err(format!("remove reverted mapping failed: {}", err)))?;
+		}
+		Ok(true)
+	}
+
+	fn set_balance(&self, address: H160, balance: U256) -> BoxFuture<Result<bool>> {
+		self.apply(DevCommand::SetBalance(address, balance))
+	}
+
+	fn set_code(&self, address: H160, code: Bytes) -> BoxFuture<Result<bool>> {
+		self.apply(DevCommand::SetCode(address, code.into_vec()))
+	}
+
+	fn set_storage_at(&self, address: H160, index: U256, value: H256) -> BoxFuture<Result<bool>> {
+		let mut key = [0u8; 32];
+		index.to_big_endian(&mut key);
+		self.apply(DevCommand::SetStorage(address, H256::from(key), value))
+	}
+
+	fn impersonate_account(&self, address: H160) -> Result<bool> {
+		self.state.impersonated.lock().insert(address);
+		Ok(true)
+	}
+
+	fn stop_impersonating_account(&self, address: H160) -> Result<bool> {
+		Ok(self.state.impersonated.lock().remove(&address))
+	}
+}
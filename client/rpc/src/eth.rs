@@ -16,21 +16,28 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 use crate::{
-	error_on_execution_failure, frontier_backend_client, internal_err, public_key, EthSigner,
-	StorageOverride,
+	access_list_gas, access_lists_unsupported_err, account_limit_err, ensure_intrinsic_gas,
+	error_on_execution_failure, frontier_backend_client, internal_err, invalid_request_err,
+	metrics::{BlockActivity, EvmMetrics},
+	public_key, runtime_evm_config, sign_personal_message, spawn_blocking_request, trace_call,
+	transaction_pool_err, unsupported_transaction_type_err, DevState, EthSigner, StorageOverride,
 };
 use ethereum::{BlockV0 as EthereumBlock, TransactionV0 as EthereumTransaction};
 use ethereum_types::{H160, H256, H512, H64, U256, U64};
 use evm::{ExitError, ExitReason};
 use fc_rpc_core::{
 	types::{
-		Block, BlockNumber, BlockTransactions, Bytes, CallRequest, Filter, FilterChanges,
-		FilterPool, FilterPoolItem, FilterType, FilteredParams, Header, Index, Log, PeerCount,
-		Receipt, Rich, RichBlock, SyncInfo, SyncStatus, Transaction, TransactionRequest, Work,
+		AccessListItem, AccessListResult, Block, BlockNumber, BlockTransactions, Bytes,
+		CallRequest, ChainConfig, FeeHistory, FeeHistoryCache, FeeHistoryCacheItem, Filter,
+		FilterChanges, FilterPool, FilterPoolItem, FilterType, FilteredParams, GasPriceBounds,
+		Header, Index, Log, PeerCount, Receipt, Rich, RichBlock, SyncInfo, SyncStatus, Transaction,
+		TransactionBuilder, TransactionRequest, Work,
 	},
 	EthApi as EthApiT, EthFilterApi as EthFilterApiT, NetApi as NetApiT, Web3Api as Web3ApiT,
 };
-use fp_rpc::{ConvertTransaction, EthereumRuntimeRPCApi, TransactionStatus};
+use fp_rpc::{
+	ConvertTransaction, EthereumRuntimeRPCApi, IndexedTransactionStatus, TransactionStatus,
+};
 use futures::{future::TryFutureExt, StreamExt};
 use jsonrpc_core::{futures::future, BoxFuture, Result};
 use lru::LruCache;
@@ -39,23 +46,26 @@ use sc_client_api::{
 	client::BlockchainEvents,
 };
 use sc_network::{ExHashT, NetworkService};
+use sc_rpc::DenyUnsafe;
+use sc_service::SpawnTaskHandle;
 use sc_transaction_pool::{ChainApi, Pool};
 use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
 use sha3::{Digest, Keccak256};
-use sp_api::{BlockId, Core, HeaderT, ProvideRuntimeApi};
+use sp_api::{BlockId, HeaderT, ProvideRuntimeApi};
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 use sp_runtime::{
 	traits::{BlakeTwo256, Block as BlockT, NumberFor, One, Saturating, UniqueSaturatedInto, Zero},
 	transaction_validity::TransactionSource,
+	FixedPointNumber,
 };
 use std::{
-	collections::BTreeMap,
+	collections::{BTreeMap, BTreeSet, HashMap},
 	marker::PhantomData,
 	sync::{Arc, Mutex},
 	time,
 };
 
-use crate::overrides::OverrideHandle;
+use crate::{log_index::LogIndexPlan, overrides::OverrideHandle};
 use codec::{self, Decode, Encode};
 pub use fc_rpc_core::{EthApiServer, EthFilterApiServer, NetApiServer, Web3ApiServer};
 use pallet_ethereum::EthereumStorageSchema;
@@ -64,17 +74,100 @@ pub struct EthApi<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi> {
 	pool: Arc<P>,
 	graph: Arc<Pool<A>>,
 	client: Arc<C>,
-	convert_transaction: CT,
+	convert_transaction: Arc<CT>,
 	network: Arc<NetworkService<B, H>>,
 	is_authority: bool,
-	signers: Vec<Box<dyn EthSigner>>,
+	signers: Arc<Vec<Box<dyn EthSigner>>>,
 	overrides: Arc<OverrideHandle<B>>,
 	backend: Arc<fc_db::Backend<B>>,
 	max_past_logs: u32,
 	block_data_cache: Arc<EthBlockDataCache<B>>,
+	spawn_handle: SpawnTaskHandle,
+	fee_history_cache: FeeHistoryCache,
+	journal_local_transactions: bool,
+	dev_state: Option<Arc<DevState<B>>>,
+	estimate_gas_error_ratio: f64,
+	call_cache: Arc<EthCallCache<B>>,
+	allow_unprotected_txs: bool,
+	history_window: Option<u64>,
+	coinbase: Option<H160>,
+	sender_limits: SenderLimits,
+	deny_unsafe: DenyUnsafe,
 	_marker: PhantomData<(B, BE)>,
 }
 
+/// Caps on the transactions of a single sender in the pool accepted by
+/// `eth_sendRawTransaction`, geth's `accountslots` and `accountqueue`.
+#[derive(Clone, Debug, Default)]
+pub struct SenderLimits {
+	/// Maximum number of ready transactions of a sender.
+	pub pending: Option<usize>,
+	/// Maximum number of future transactions of a sender, waiting for a nonce gap to be filled.
+	pub queued: Option<usize>,
+	/// Future transactions accepted so far, to share between the RPC servers.
+	pub queued_transactions: Arc<QueuedTransactions>,
+}
+
+/// Number of senders tracked by `QueuedTransactions` above which the ones without a transaction
+/// left in the future queue are forgotten.
+const QUEUED_SENDERS_LIMIT: usize = 4096;
+
+/// Future transactions accepted by `eth_sendRawTransaction`, by sender, as nonce and pool hash.
+/// The future queue of the pool does not expose the tags of its transactions, counting there
+/// would recover the sender of every queued transaction.
+#[derive(Debug, Default)]
+pub struct QueuedTransactions(Mutex<HashMap<H160, Vec<(U256, H256)>>>);
+
+impl QueuedTransactions {
+	/// Number of transactions of `sender` still in the future queue, but the one of `nonce` a new
+	/// transaction would replace. `is_queued` tells whether a pool hash is in the future queue.
+	fn count(&self, sender: H160, nonce: U256, is_queued: impl Fn(&H256) -> bool) -> usize {
+		let mut senders = self
+			.0
+			.lock()
+			.expect("queued transactions lock poisoned; qed");
+		let count = match senders.get_mut(&sender) {
+			Some(transactions) => {
+				transactions.retain(|(_, hash)| is_queued(hash));
+				transactions
+					.iter()
+					.filter(|(queued_nonce, _)| *queued_nonce != nonce)
+					.count()
+			}
+			None => return 0,
+		};
+		if senders.get(&sender).map_or(false, Vec::is_empty) {
+			senders.remove(&sender);
+		}
+		count
+	}
+
+	/// Records a future transaction of `sender`.
+	fn insert(&self, sender: H160, nonce: U256, hash: H256, is_queued: impl Fn(&H256) -> bool) {
+		let mut senders = self
+			.0
+			.lock()
+			.expect("queued transactions lock poisoned; qed");
+		senders.entry(sender).or_default().push((nonce, hash));
+		if senders.len() > QUEUED_SENDERS_LIMIT {
+			senders.retain(|_, transactions| {
+				transactions.retain(|(_, hash)| is_queued(hash));
+				!transactions.is_empty()
+			});
+		}
+	}
+}
+
+/// Whether the transaction of `hash` is in the future queue of the pool.
+fn is_queued<B, A>(graph: &Pool<A>, hash: &H256) -> bool
+where
+	A: ChainApi<Block = B>,
+	B: BlockT<Hash = H256>,
+{
+	let pool = graph.validated_pool();
+	pool.check_is_known(hash, true).is_err() && pool.ready_by_hash(hash).is_none()
+}
+
 impl<B: BlockT, C, P, CT, BE, H: ExHashT, A: ChainApi> EthApi<B, C, P, CT, BE, H, A>
 where
 	C: ProvideRuntimeApi<B>,
@@ -95,19 +188,41 @@ where
 		is_authority: bool,
 		max_past_logs: u32,
 		block_data_cache: Arc<EthBlockDataCache<B>>,
+		spawn_handle: SpawnTaskHandle,
+		fee_history_cache: FeeHistoryCache,
+		journal_local_transactions: bool,
+		dev_state: Option<Arc<DevState<B>>>,
+		estimate_gas_error_ratio: f64,
+		call_cache: Arc<EthCallCache<B>>,
+		allow_unprotected_txs: bool,
+		history_window: Option<u64>,
+		coinbase: Option<H160>,
+		sender_limits: SenderLimits,
+		deny_unsafe: DenyUnsafe,
 	) -> Self {
 		Self {
 			client,
 			pool,
 			graph,
-			convert_transaction,
+			convert_transaction: Arc::new(convert_transaction),
 			network,
 			is_authority,
-			signers,
+			signers: Arc::new(signers),
 			overrides,
 			backend,
 			max_past_logs,
 			block_data_cache,
+			spawn_handle,
+			fee_history_cache,
+			journal_local_transactions,
+			dev_state,
+			estimate_gas_error_ratio,
+			call_cache,
+			allow_unprotected_txs,
+			history_window,
+			coinbase,
+			sender_limits,
+			deny_unsafe,
 			_marker: PhantomData,
 		}
 	}
@@ -119,12 +234,13 @@ fn rich_block_build(
 	hash: Option<H256>,
 	full_transactions: bool,
 ) -> RichBlock {
+	let hash = hash.unwrap_or_else(|| {
+		H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice())
+	});
 	Rich {
 		inner: Block {
 			header: Header {
-				hash: Some(hash.unwrap_or_else(|| {
-					H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice())
-				})),
+				hash: Some(hash),
 				parent_hash: block.header.parent_hash,
 				uncles_hash: block.header.ommers_hash,
 				author: block.header.beneficiary,
@@ -153,13 +269,20 @@ fn rich_block_build(
 						block
 							.transactions
 							.iter()
+							.zip(statuses.into_iter())
 							.enumerate()
-							.map(|(index, transaction)| {
-								transaction_build(
+							.map(|(index, (transaction, status))| {
+								let mut transaction = transaction_build(
 									transaction.clone(),
-									Some(block.clone()),
-									Some(statuses[index].clone().unwrap_or_default()),
-								)
+									Some((hash, block.header.number)),
+									status,
+								);
+								// Without a status the sender is recovered, but the index is
+								// still known from the block.
+								transaction
+									.transaction_index
+									.get_or_insert_with(|| U256::from(index));
+								transaction
 							})
 							.collect(),
 					)
@@ -168,11 +291,12 @@ fn rich_block_build(
 						block
 							.transactions
 							.iter()
-							.map(|transaction| {
-								H256::from_slice(
-									Keccak256::digest(&rlp::encode(&transaction.clone()))
-										.as_slice(),
-								)
+							.zip(statuses.into_iter())
+							.map(|(transaction, status)| match status {
+								Some(status) => status.transaction_hash,
+								None => H256::from_slice(
+									Keccak256::digest(&rlp::encode(transaction)).as_slice(),
+								),
 							})
 							.collect(),
 					)
@@ -184,62 +308,158 @@ fn rich_block_build(
 	}
 }
 
-fn transaction_build(
+/// Builds the RPC representation of a transaction, given the hash and number of its block.
+/// The sender is taken from the `status` when known, otherwise it is recovered from the
+/// signature.
+pub(crate) fn transaction_build(
 	transaction: EthereumTransaction,
-	block: Option<EthereumBlock>,
+	block: Option<(H256, U256)>,
 	status: Option<TransactionStatus>,
 ) -> Transaction {
-	let pubkey = match public_key(&transaction) {
-		Ok(p) => Some(p),
-		Err(_e) => None,
+	let pubkey = public_key(&transaction).ok();
+	let from = match (&status, pubkey) {
+		(Some(status), _) => status.from,
+		(None, Some(pk)) => H160::from(H256::from_slice(Keccak256::digest(&pk).as_slice())),
+		(None, None) => H160::default(),
 	};
 
-	Transaction {
-		hash: H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice()),
-		nonce: transaction.nonce,
-		block_hash: block.as_ref().map_or(None, |block| {
-			Some(H256::from_slice(
-				Keccak256::digest(&rlp::encode(&block.header)).as_slice(),
-			))
-		}),
-		block_number: block.as_ref().map(|block| block.header.number),
-		transaction_index: status.as_ref().map(|status| {
-			U256::from(UniqueSaturatedInto::<u32>::unique_saturated_into(
-				status.transaction_index,
+	let mut builder = TransactionBuilder::new(transaction);
+	if status.is_some() || pubkey.is_some() {
+		builder = builder.sender(from, pubkey.as_ref().map(H512::from));
+	}
+	if let Some((hash, number)) = block {
+		builder = builder.block(hash, number);
+	}
+	if let Some(status) = status {
+		builder = builder
+			.hash(status.transaction_hash)
+			.transaction_index(U256::from(
+				UniqueSaturatedInto::<u32>::unique_saturated_into(status.transaction_index),
 			))
-		}),
-		from: status.as_ref().map_or(
-			{
-				match pubkey {
-					Some(pk) => H160::from(H256::from_slice(Keccak256::digest(&pk).as_slice())),
-					_ => H160::default(),
-				}
-			},
-			|status| status.from,
+			.contract_address(status.contract_address);
+	}
+	builder.build()
+}
+
+/// Builds the RPC representation of the transaction at `index` of a block.
+fn block_transaction_build(
+	mut block: EthereumBlock,
+	mut statuses: Vec<TransactionStatus>,
+	index: usize,
+) -> Option<Transaction> {
+	if index >= block.transactions.len() || index >= statuses.len() {
+		return None;
+	}
+	let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+	Some(transaction_build(
+		block.transactions.swap_remove(index),
+		Some((hash, block.header.number)),
+		Some(statuses.swap_remove(index)),
+	))
+}
+
+/// Builds the receipt of a transaction from its status indexed by hash in the runtime.
+fn indexed_receipt_build(indexed: IndexedTransactionStatus) -> Receipt {
+	let IndexedTransactionStatus {
+		block_hash,
+		block_number,
+		status,
+		receipt,
+		cumulative_gas_used,
+		first_log_index,
+		gas_price,
+	} = indexed;
+
+	Receipt {
+		transaction_hash: Some(status.transaction_hash),
+		transaction_index: Some(status.transaction_index.into()),
+		block_hash: Some(block_hash),
+		from: Some(status.from),
+		to: status.to,
+		block_number: Some(block_number),
+		cumulative_gas_used,
+		gas_used: Some(receipt.used_gas),
+		// Legacy transactions pay their gas price.
+		effective_gas_price: gas_price,
+		transaction_type: U64::zero(),
+		sponsor: None,
+		contract_address: status.contract_address,
+		logs: Log::transaction_logs(
+			block_hash,
+			block_number,
+			status.transaction_hash,
+			status.transaction_index.into(),
+			first_log_index,
+			status.logs,
 		),
-		to: status.as_ref().map_or(
-			{
-				match transaction.action {
-					ethereum::TransactionAction::Call(to) => Some(to),
-					_ => None,
+		status_code: Some(U64::from(receipt.state_root.to_low_u64_be())),
+		logs_bloom: receipt.logs_bloom,
+		state_root: None,
+	}
+}
+
+/// Storage schemas of a block range iteration, by first block number.
+struct RangeSchemaCache<B: BlockT> {
+	local_cache: BTreeMap<NumberFor<B>, EthereumStorageSchema>,
+	cache_keys: Vec<NumberFor<B>>,
+}
+
+impl<B> RangeSchemaCache<B>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	/// Get schema cache. A single read before the block range iteration.
+	/// This prevents having to do an extra DB read per block range iteration to get the actual
+	/// schema.
+	fn load<C>(client: &C, backend: &fc_db::Backend<B>) -> Self
+	where
+		C: HeaderBackend<B>,
+	{
+		let mut local_cache: BTreeMap<NumberFor<B>, EthereumStorageSchema> = BTreeMap::new();
+		if let Ok(Some(schema_cache)) = frontier_backend_client::load_cached_schema::<B>(backend) {
+			for (schema, hash) in schema_cache {
+				if let Ok(Some(header)) = client.header(BlockId::Hash(hash)) {
+					let number = *header.number();
+					local_cache.insert(number, schema);
 				}
-			},
-			|status| status.to,
-		),
-		value: transaction.value,
-		gas_price: transaction.gas_price,
-		gas: transaction.gas_limit,
-		input: Bytes(transaction.clone().input),
-		creates: status
-			.as_ref()
-			.map_or(None, |status| status.contract_address),
-		raw: Bytes(rlp::encode(&transaction).to_vec()),
-		public_key: pubkey.as_ref().map(|pk| H512::from(pk)),
-		chain_id: transaction.signature.chain_id().map(U64::from),
-		standard_v: U256::from(transaction.signature.standard_v()),
-		v: U256::from(transaction.signature.v()),
-		r: U256::from(transaction.signature.r().as_bytes()),
-		s: U256::from(transaction.signature.s().as_bytes()),
+			}
+		}
+		let cache_keys: Vec<NumberFor<B>> = local_cache.keys().cloned().collect();
+		Self {
+			local_cache,
+			cache_keys,
+		}
+	}
+
+	fn schema_at<C, BE>(&self, client: &C, number: NumberFor<B>) -> EthereumStorageSchema
+	where
+		C: StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+	{
+		if self.cache_keys.len() == 1 {
+			// There is only one schema and that's the one we use.
+			return self.local_cache[&self.cache_keys[0]];
+		}
+		// If there are multiple schemas, we iterate over the - hopefully short - list
+		// of keys and assign the one belonging to the number.
+		// Because there are more than 1 schema, and number cannot be < 0,
+		// (i - 1) will always be >= 0.
+		let mut default_schema: Option<&EthereumStorageSchema> = None;
+		for (i, k) in self.cache_keys.iter().enumerate() {
+			if &number < k {
+				default_schema = self.local_cache.get(&self.cache_keys[i - 1]);
+			}
+		}
+		match default_schema {
+			Some(schema) => *schema,
+			// Fallback to DB read. This will happen i.e. when there is no cache
+			// task configured at service level.
+			_ => frontier_backend_client::onchain_storage_schema::<B, C, BE>(
+				client,
+				BlockId::Number(number),
+			),
+		}
 	}
 }
 
@@ -279,23 +499,8 @@ where
 	let address_bloom_filter = FilteredParams::adresses_bloom_filter(&filter.address);
 	let topics_bloom_filter = FilteredParams::topics_bloom_filter(&topics_input);
 
-	// Get schema cache. A single read before the block range iteration.
-	// This prevents having to do an extra DB read per block range iteration to getthe actual schema.
-	let mut local_cache: BTreeMap<NumberFor<B>, EthereumStorageSchema> = BTreeMap::new();
-	if let Ok(Some(schema_cache)) = frontier_backend_client::load_cached_schema::<B>(backend) {
-		for (schema, hash) in schema_cache {
-			if let Ok(Some(header)) = client.header(BlockId::Hash(hash)) {
-				let number = *header.number();
-				local_cache.insert(number, schema);
-			}
-		}
-	}
-	let cache_keys: Vec<NumberFor<B>> = local_cache.keys().cloned().collect();
-	let mut default_schema: Option<&EthereumStorageSchema> = None;
-	if cache_keys.len() == 1 {
-		// There is only one schema and that's the one we use.
-		default_schema = local_cache.get(&cache_keys[0]);
-	}
+	let schema_cache = RangeSchemaCache::load(client, backend);
+	let log_index_plan = LogIndexPlan::new(backend, filter, from, to);
 
 	while current_number >= from {
 		let id = BlockId::Number(current_number);
@@ -303,34 +508,20 @@ where
 			.expect_block_hash_from_id(&id)
 			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
 
-		let schema = match default_schema {
-			// If there is a single schema, we just assign.
-			Some(default_schema) => *default_schema,
-			_ => {
-				// If there are multiple schemas, we iterate over the - hopefully short - list
-				// of keys and assign the one belonging to the current_number.
-				// Because there are more than 1 schema, and current_number cannot be < 0,
-				// (i - 1) will always be >= 0.
-				let mut default_schema: Option<&EthereumStorageSchema> = None;
-				for (i, k) in cache_keys.iter().enumerate() {
-					if &current_number < k {
-						default_schema = local_cache.get(&cache_keys[i - 1]);
-					}
-				}
-				match default_schema {
-					Some(schema) => *schema,
-					// Fallback to DB read. This will happen i.e. when there is no cache
-					// task configured at service level.
-					_ => frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id),
-				}
-			}
-		};
+		let schema = schema_cache.schema_at::<C, BE>(client, current_number);
 		let handler = overrides
 			.schemas
 			.get(&schema)
 			.unwrap_or(&overrides.fallback);
 
-		let block = block_data_cache.current_block(handler, substrate_hash);
+		let block = if log_index_plan
+			.as_ref()
+			.map_or(false, |plan| plan.skips(&substrate_hash))
+		{
+			None
+		} else {
+			block_data_cache.current_block(handler, substrate_hash)
+		};
 
 		if let Some(block) = block {
 			if FilteredParams::address_in_bloom(block.header.logs_bloom, &address_bloom_filter)
@@ -365,6 +556,107 @@ where
 	Ok(())
 }
 
+/// Logs of the blocks `from` to `to` in ascending order, from the log `start_log_index` of the
+/// `from` block. Stops after `limit` logs or when the query takes too long, returning then the
+/// block and log index to resume from.
+pub(crate) fn filter_logs_page<B: BlockT, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	overrides: &OverrideHandle<B>,
+	block_data_cache: &EthBlockDataCache<B>,
+	filter: &Filter,
+	from: NumberFor<B>,
+	to: NumberFor<B>,
+	start_log_index: u32,
+	limit: usize,
+) -> Result<(Vec<Log>, Option<(NumberFor<B>, u32)>)>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError> + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: Send + Sync + 'static,
+{
+	// Max request duration of 10 seconds, at least one block is processed.
+	let max_duration = time::Duration::from_secs(10);
+	let begin_request = time::Instant::now();
+
+	let topics_input = if let Some(_) = &filter.topics {
+		let filtered_params = FilteredParams::new(Some(filter.clone()));
+		Some(filtered_params.flat_topics)
+	} else {
+		None
+	};
+	let address_bloom_filter = FilteredParams::adresses_bloom_filter(&filter.address);
+	let topics_bloom_filter = FilteredParams::topics_bloom_filter(&topics_input);
+	let schema_cache = RangeSchemaCache::load(client, backend);
+	let log_index_plan = LogIndexPlan::new(backend, filter, from, to);
+
+	let mut ret = Vec::new();
+	let mut current_number = from;
+	let mut start_log_index = start_log_index;
+	while current_number <= to {
+		let id = BlockId::Number(current_number);
+		let substrate_hash = client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+		let schema = schema_cache.schema_at::<C, BE>(client, current_number);
+		let handler = overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&overrides.fallback);
+
+		let mut block_logs = Vec::new();
+		let block = if log_index_plan
+			.as_ref()
+			.map_or(false, |plan| plan.skips(&substrate_hash))
+		{
+			None
+		} else {
+			block_data_cache.current_block(handler, substrate_hash)
+		};
+		if let Some(block) = block {
+			if FilteredParams::address_in_bloom(block.header.logs_bloom, &address_bloom_filter)
+				&& FilteredParams::topics_in_bloom(block.header.logs_bloom, &topics_bloom_filter)
+			{
+				let statuses =
+					block_data_cache.current_transaction_statuses(handler, substrate_hash);
+				if let Some(statuses) = statuses {
+					filter_block_logs(&mut block_logs, filter, block, statuses);
+				}
+			}
+		}
+		block_logs.retain(|log| {
+			log.log_index
+				.map_or(true, |index| index >= U256::from(start_log_index))
+		});
+
+		let remaining = limit - ret.len();
+		if block_logs.len() > remaining {
+			let next_log_index = block_logs[remaining]
+				.log_index
+				.map(|index| index.low_u32())
+				.unwrap_or_default();
+			ret.extend(block_logs.into_iter().take(remaining));
+			return Ok((ret, Some((current_number, next_log_index))));
+		}
+		ret.extend(block_logs);
+		start_log_index = 0;
+
+		if current_number == to {
+			break;
+		}
+		current_number = current_number.saturating_add(One::one());
+		if ret.len() == limit || begin_request.elapsed() > max_duration {
+			return Ok((ret, Some((current_number, 0))));
+		}
+	}
+	Ok((ret, None))
+}
+
 fn filter_block_logs<'a>(
 	ret: &'a mut Vec<Log>,
 	filter: &'a Filter,
@@ -372,25 +664,17 @@ fn filter_block_logs<'a>(
 	transaction_statuses: Vec<TransactionStatus>,
 ) -> &'a Vec<Log> {
 	let params = FilteredParams::new(Some(filter.clone()));
-	let mut block_log_index: u32 = 0;
 	let block_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
-	for status in transaction_statuses.iter() {
-		let logs = status.logs.clone();
-		let mut transaction_log_index: u32 = 0;
-		let transaction_hash = status.transaction_hash;
-		for ethereum_log in logs {
-			let mut log = Log {
-				address: ethereum_log.address.clone(),
-				topics: ethereum_log.topics.clone(),
-				data: Bytes(ethereum_log.data.clone()),
-				block_hash: None,
-				block_number: None,
-				transaction_hash: None,
-				transaction_index: None,
-				log_index: None,
-				transaction_log_index: None,
-				removed: false,
-			};
+	let block_logs = Log::block_logs(
+		block_hash,
+		block.header.number,
+		transaction_statuses
+			.into_iter()
+			.map(|status| (status.transaction_hash, status.logs))
+			.collect(),
+	);
+	for logs in block_logs {
+		for log in logs {
 			let mut add: bool = true;
 			if let (Some(_), Some(_)) = (filter.address.clone(), filter.topics.clone()) {
 				if !params.filter_address(&log) || !params.filter_topics(&log) {
@@ -406,118 +690,1501 @@ fn filter_block_logs<'a>(
 				}
 			}
 			if add {
-				log.block_hash = Some(block_hash);
-				log.block_number = Some(block.header.number.clone());
-				log.transaction_hash = Some(transaction_hash);
-				log.transaction_index = Some(U256::from(status.transaction_index));
-				log.log_index = Some(U256::from(block_log_index));
-				log.transaction_log_index = Some(U256::from(transaction_log_index));
 				ret.push(log);
 			}
-			transaction_log_index += 1;
-			block_log_index += 1;
 		}
 	}
 	ret
 }
 
-impl<B, C, P, CT, BE, H: ExHashT, A> EthApiT for EthApi<B, C, P, CT, BE, H, A>
+/// Attributes `rich_block` to the address the runtime maps the Substrate author of the block
+/// to, for runtimes implementing version 8 of `EthereumRuntimeRPCApi`.
+fn attribute_block_author<B, C>(
+	client: &C,
+	substrate_hash: B::Hash,
+	rich_block: &mut RichBlock,
+) -> Result<()>
 where
-	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
-	C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError> + 'static,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
 	C::Api: EthereumRuntimeRPCApi<B>,
-	BE: Backend<B> + 'static,
-	BE::State: StateBackend<BlakeTwo256>,
 	B: BlockT<Hash = H256> + Send + Sync + 'static,
-	C: Send + Sync + 'static,
-	P: TransactionPool<Block = B> + Send + Sync + 'static,
-	A: ChainApi<Block = B> + 'static,
-	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
 {
-	fn protocol_version(&self) -> Result<u64> {
-		Ok(1)
+	let id = BlockId::Hash(substrate_hash);
+	match frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id)? {
+		Some(api_version) if api_version >= 8 => {}
+		_ => return Ok(()),
 	}
+	let header = match client
+		.header(id)
+		.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+	{
+		Some(header) => header,
+		None => return Ok(()),
+	};
+	let author = client
+		.runtime_api()
+		.block_author(&id, header)
+		.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+	rich_block.inner.header.author = author;
+	rich_block.inner.header.miner = author;
+	Ok(())
+}
 
-	fn syncing(&self) -> Result<SyncStatus> {
-		if self.network.is_major_syncing() {
-			let block_number = U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(
-				self.client.info().best_number.clone(),
-			));
-			Ok(SyncStatus::Info(SyncInfo {
-				starting_block: U256::zero(),
-				current_block: block_number,
-				// TODO `highest_block` is not correct, should load `best_seen_block` from NetworkWorker,
-				// but afaik that is not currently possible in Substrate:
-				// https://github.com/paritytech/substrate/issues/7311
-				highest_block: block_number,
-				warp_chunks_amount: None,
-				warp_chunks_processed: None,
-			}))
-		} else {
-			Ok(SyncStatus::None)
-		}
+/// Reports in `rich_block` the base fee it was charged, the min gas price at its parent, for
+/// the blocks from the London block of runtimes implementing version 10 of
+/// `EthereumRuntimeRPCApi`.
+fn attribute_base_fee<B, C>(
+	client: &C,
+	substrate_hash: B::Hash,
+	rich_block: &mut RichBlock,
+) -> Result<()>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let id = BlockId::Hash(substrate_hash);
+	let london_block = match frontier_backend_client::london_block::<B, C>(client, id)? {
+		Some(london_block) => london_block,
+		None => return Ok(()),
+	};
+	if rich_block.inner.header.number.unwrap_or_default() < london_block {
+		return Ok(());
 	}
+	let parent_hash = match client
+		.header(id)
+		.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+	{
+		Some(header) => *header.parent_hash(),
+		None => return Ok(()),
+	};
+	let base_fee = client
+		.runtime_api()
+		.gas_price(&BlockId::Hash(parent_hash))
+		.map_err(|err| internal_err(format!("fetch runtime gas price failed: {:?}", err)))?;
+	rich_block
+		.extra_info
+		.insert("baseFeePerGas".to_string(), format!("0x{:x}", base_fee));
+	Ok(())
+}
 
-	fn hashrate(&self) -> Result<U256> {
-		Ok(U256::zero())
+/// Reports the `effectiveGasPrice` of `receipt` in units of the fee token paying its fees, at
+/// the rate of the parent of its block `id`, for the runtimes implementing version 11 of
+/// `EthereumRuntimeRPCApi`. The rate may have been updated by the block before the transaction.
+fn convert_effective_gas_price<B, C>(
+	client: &C,
+	id: BlockId<B>,
+	receipt: &mut Receipt,
+) -> Result<()>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let from = match receipt.from {
+		Some(from) => from,
+		None => return Ok(()),
+	};
+	let parent_id = match client
+		.header(id)
+		.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+	{
+		Some(header) => BlockId::Hash(*header.parent_hash()),
+		None => return Ok(()),
+	};
+	match frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, parent_id)? {
+		Some(api_version) if api_version >= 11 => {}
+		_ => return Ok(()),
 	}
-
-	fn author(&self) -> Result<H160> {
-		let block = BlockId::Hash(self.client.info().best_hash);
-		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
-			self.client.as_ref(),
-			block,
-		);
-
-		Ok(self
-			.overrides
-			.schemas
-			.get(&schema)
-			.unwrap_or(&self.overrides.fallback)
-			.current_block(&block)
-			.ok_or(internal_err("fetching author through override failed"))?
-			.header
-			.beneficiary)
+	let rate = client
+		.runtime_api()
+		.fee_token_rate(&parent_id, from, receipt.to)
+		.map_err(|err| internal_err(format!("fetch runtime fee token rate failed: {:?}", err)))?;
+	if let Some(rate) = rate {
+		receipt.effective_gas_price =
+			U256::from(rate.saturating_mul_int(receipt.effective_gas_price.low_u128()));
 	}
+	Ok(())
+}
 
-	fn is_mining(&self) -> Result<bool> {
-		Ok(self.is_authority)
+/// Reports in `receipt` the sponsor which paid the fees of the transaction, for the runtimes
+/// implementing version 12 of `EthereumRuntimeRPCApi`. The runtime records the sponsors by
+/// sender and nonce: `nonce` loads the nonce of the transaction, only when its sender had
+/// sponsored transactions in the block `id`.
+fn attribute_fee_sponsor<B, C, F>(
+	client: &C,
+	id: BlockId<B>,
+	receipt: &mut Receipt,
+	nonce: F,
+) -> Result<()>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	F: FnOnce() -> Result<Option<U256>>,
+{
+	let from = match receipt.from {
+		Some(from) => from,
+		None => return Ok(()),
+	};
+	match frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id)? {
+		Some(api_version) if api_version >= 12 => {}
+		_ => return Ok(()),
 	}
-
-	fn chain_id(&self) -> Result<Option<U64>> {
-		let hash = self.client.info().best_hash;
-		Ok(Some(
-			self.client
-				.runtime_api()
-				.chain_id(&BlockId::Hash(hash))
-				.map_err(|err| internal_err(format!("fetch runtime chain id failed: {:?}", err)))?
-				.into(),
-		))
+	let sponsors: Vec<(H160, U256, H160)> = client
+		.runtime_api()
+		.fee_sponsors(&id)
+		.map_err(|err| internal_err(format!("fetch runtime fee sponsors failed: {:?}", err)))?
+		.into_iter()
+		.filter(|(sender, _, _)| *sender == from)
+		.collect();
+	if sponsors.is_empty() {
+		return Ok(());
 	}
-
-	fn gas_price(&self) -> Result<U256> {
-		let block = BlockId::Hash(self.client.info().best_hash);
-
-		Ok(self
-			.client
-			.runtime_api()
-			.gas_price(&block)
-			.map_err(|err| internal_err(format!("fetch runtime chain id failed: {:?}", err)))?
-			.into())
+	if let Some(nonce) = nonce()? {
+		receipt.sponsor = sponsors
+			.into_iter()
+			.find(|(_, sponsored_nonce, _)| *sponsored_nonce == nonce)
+			.map(|(_, _, sponsor)| sponsor);
 	}
+	Ok(())
+}
 
-	fn accounts(&self) -> Result<Vec<H160>> {
-		let mut accounts = Vec::new();
-		for signer in &self.signers {
-			accounts.append(&mut signer.accounts());
+fn load_block_by_hash<B, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	overrides: &OverrideHandle<B>,
+	block_data_cache: &EthBlockDataCache<B>,
+	hash: H256,
+	full: bool,
+	history_window: Option<u64>,
+) -> Result<Option<RichBlock>>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	C: Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let id = match frontier_backend_client::load_hash::<B>(backend, hash)
+		.map_err(|err| internal_err(format!("{:?}", err)))?
+	{
+		Some(hash) => hash,
+		_ => return Ok(None),
+	};
+	frontier_backend_client::ensure_history_available::<B, C, BE>(client, id, history_window)?;
+	let substrate_hash = client
+		.expect_block_hash_from_id(&id)
+		.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+
+	let block = block_data_cache.current_block(handler, substrate_hash);
+	let statuses = block_data_cache.current_transaction_statuses(handler, substrate_hash);
+
+	match (block, statuses) {
+		(Some(block), Some(statuses)) => {
+			let mut rich_block = rich_block_build(
+				block,
+				statuses.into_iter().map(|s| Some(s)).collect(),
+				Some(hash),
+				full,
+			);
+			attribute_block_author(client, substrate_hash, &mut rich_block)?;
+			attribute_base_fee(client, substrate_hash, &mut rich_block)?;
+			Ok(Some(rich_block))
 		}
-		Ok(accounts)
+		_ => Ok(None),
 	}
+}
 
-	fn block_number(&self) -> Result<U256> {
-		Ok(U256::from(
-			UniqueSaturatedInto::<u128>::unique_saturated_into(
-				self.client.info().best_number.clone(),
+fn load_block_by_number<B, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	overrides: &OverrideHandle<B>,
+	block_data_cache: &EthBlockDataCache<B>,
+	number: BlockNumber,
+	full: bool,
+	history_window: Option<u64>,
+) -> Result<Option<RichBlock>>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	C: Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let id = match frontier_backend_client::native_block_id::<B, C>(client, backend, Some(number))?
+	{
+		Some(id) => id,
+		None => return Ok(None),
+	};
+	frontier_backend_client::ensure_history_available::<B, C, BE>(client, id, history_window)?;
+	let substrate_hash = client
+		.expect_block_hash_from_id(&id)
+		.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+
+	let block = block_data_cache.current_block(handler, substrate_hash);
+	let statuses = block_data_cache.current_transaction_statuses(handler, substrate_hash);
+
+	match (block, statuses) {
+		(Some(block), Some(statuses)) => {
+			let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+
+			let mut rich_block = rich_block_build(
+				block,
+				statuses.into_iter().map(|s| Some(s)).collect(),
+				Some(hash),
+				full,
+			);
+			attribute_block_author(client, substrate_hash, &mut rich_block)?;
+			attribute_base_fee(client, substrate_hash, &mut rich_block)?;
+			Ok(Some(rich_block))
+		}
+		_ => Ok(None),
+	}
+}
+
+/// Assembles the `eth_feeHistory` response of the `block_count` blocks up to `newest_block` from
+/// the fee history cache.
+fn load_fee_history<B, C>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	fee_history_cache: &FeeHistoryCache,
+	block_count: U256,
+	newest_block: BlockNumber,
+	reward_percentiles: Option<Vec<f64>>,
+) -> Result<FeeHistory>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	C: Send + Sync + 'static,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	// The spec limits the range to 1024 blocks.
+	let block_count = std::cmp::min(block_count, U256::from(1024)).as_u64();
+
+	let newest_block = match newest_block {
+		BlockNumber::Pending => BlockNumber::Latest,
+		number => number,
+	};
+	let id = match frontier_backend_client::native_block_id::<B, C>(
+		client,
+		backend,
+		Some(newest_block),
+	)? {
+		Some(id) => id,
+		None => return Err(internal_err("block not found")),
+	};
+	let newest_number: u64 = client
+		.expect_block_number_from_id(&id)
+		.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?
+		.unique_saturated_into();
+
+	if let Some(percentiles) = &reward_percentiles {
+		let mut previous = 0.0;
+		for percentile in percentiles {
+			if *percentile < previous || *percentile > 100.0 {
+				return Err(internal_err(format!(
+					"invalid reward percentile: {}",
+					percentile
+				)));
+			}
+			previous = *percentile;
+		}
+	}
+
+	let mut response = FeeHistory {
+		oldest_block: U256::from(newest_number.saturating_add(1)),
+		base_fee_per_gas: Vec::new(),
+		gas_used_ratio: Vec::new(),
+		reward: reward_percentiles.as_ref().map(|_| Vec::new()),
+	};
+	if block_count == 0 {
+		return Ok(response);
+	}
+
+	let fee_history_cache = fee_history_cache
+		.lock()
+		.map_err(|err| internal_err(format!("fee history cache lock failed: {:?}", err)))?;
+	let oldest_number = newest_number.saturating_sub(block_count - 1);
+	for (number, item) in fee_history_cache.range(oldest_number..=newest_number) {
+		if response.base_fee_per_gas.is_empty() {
+			response.oldest_block = U256::from(*number);
+		}
+		response.base_fee_per_gas.push(item.base_fee);
+		response.gas_used_ratio.push(item.gas_used_ratio);
+		if let (Some(reward), Some(percentiles)) = (&mut response.reward, &reward_percentiles) {
+			reward.push(
+				percentiles
+					.iter()
+					.map(|percentile| item.reward_at(*percentile))
+					.collect(),
+			);
+		}
+	}
+
+	match response.base_fee_per_gas.last().cloned() {
+		// Without a base fee adjustment, the next block base fee is the newest one.
+		Some(next_base_fee) => response.base_fee_per_gas.push(next_base_fee),
+		None => {
+			return Err(internal_err(
+				"fee history is not available for the requested range",
+			))
+		}
+	}
+
+	Ok(response)
+}
+
+fn load_transaction_receipt<B, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	overrides: &OverrideHandle<B>,
+	block_data_cache: &EthBlockDataCache<B>,
+	hash: H256,
+	history_window: Option<u64>,
+) -> Result<Option<Receipt>>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	C: Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let transaction_hash = hash;
+	let (hash, index) =
+		match frontier_backend_client::load_transactions::<B, C>(client, backend, hash, true)
+			.map_err(|err| internal_err(format!("{:?}", err)))?
+		{
+			Some((hash, index)) => (hash, index as usize),
+			None => {
+				frontier_backend_client::ensure_transactions_indexed(backend)?;
+				return Ok(None);
+			}
+		};
+
+	let id = match frontier_backend_client::load_hash::<B>(backend, hash)
+		.map_err(|err| internal_err(format!("{:?}", err)))?
+	{
+		Some(hash) => hash,
+		_ => return Ok(None),
+	};
+	frontier_backend_client::ensure_history_available::<B, C, BE>(client, id, history_window)?;
+
+	// Runtimes from version 3 of the API may index the transactions by hash, sparing the
+	// decoding of the block. The receipt is built from the block otherwise.
+	if let Some(version) =
+		frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id)?
+	{
+		if version >= 3 {
+			let indexed = client
+				.runtime_api()
+				.transaction_status_by_hash(&id, transaction_hash)
+				.map_err(|err| {
+					internal_err(format!(
+						"fetch runtime transaction status failed: {:?}",
+						err
+					))
+				})?;
+			if let Some(mut receipt) = indexed.map(indexed_receipt_build) {
+				convert_effective_gas_price(client, id, &mut receipt)?;
+				attribute_fee_sponsor(client, id, &mut receipt, || {
+					let substrate_hash = client.expect_block_hash_from_id(&id).map_err(|_| {
+						internal_err(format!("Expect block number from id: {}", id))
+					})?;
+					let schema =
+						frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+					let handler = overrides
+						.schemas
+						.get(&schema)
+						.unwrap_or(&overrides.fallback);
+					Ok(block_data_cache
+						.current_block(handler, substrate_hash)
+						.and_then(|block| {
+							block
+								.transactions
+								.get(index)
+								.map(|transaction| transaction.nonce)
+						}))
+				})?;
+				return Ok(Some(receipt));
+			}
+		}
+	}
+
+	let substrate_hash = client
+		.expect_block_hash_from_id(&id)
+		.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+
+	let block = block_data_cache.current_block(handler, substrate_hash);
+	let statuses = block_data_cache.current_transaction_statuses(handler, substrate_hash);
+	let receipts = handler.current_receipts(&id);
+
+	match (block, statuses, receipts) {
+		(Some(block), Some(statuses), Some(receipts)) => {
+			let block_hash =
+				H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+			let receipt = receipts[index].clone();
+			let status = statuses[index].clone();
+			let mut cumulative_receipts = receipts.clone();
+			cumulative_receipts.truncate((status.transaction_index + 1) as usize);
+
+			let mut rpc_receipt = Receipt {
+				transaction_hash: Some(status.transaction_hash),
+				transaction_index: Some(status.transaction_index.into()),
+				block_hash: Some(block_hash),
+				from: Some(status.from),
+				to: status.to,
+				block_number: Some(block.header.number),
+				cumulative_gas_used: {
+					let cumulative_gas: u32 = cumulative_receipts
+						.iter()
+						.map(|r| r.used_gas.as_u32())
+						.sum();
+					U256::from(cumulative_gas)
+				},
+				gas_used: Some(receipt.used_gas),
+				// Legacy transactions pay their gas price.
+				effective_gas_price: block.transactions[index].gas_price,
+				transaction_type: U64::zero(),
+				sponsor: None,
+				contract_address: status.contract_address,
+				// The statuses keep the logs in compact receipts mode too.
+				logs: Log::transaction_logs(
+					block_hash,
+					block.header.number,
+					status.transaction_hash,
+					status.transaction_index.into(),
+					statuses[..index]
+						.iter()
+						.map(|status| status.logs.len() as u32)
+						.sum(),
+					status.logs.clone(),
+				),
+				status_code: Some(U64::from(receipt.state_root.to_low_u64_be())),
+				logs_bloom: receipt.logs_bloom,
+				state_root: None,
+			};
+			convert_effective_gas_price(client, id, &mut rpc_receipt)?;
+			Ok(Some(rpc_receipt))
+		}
+		_ => Ok(None),
+	}
+}
+
+fn load_logs<B, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	overrides: &OverrideHandle<B>,
+	block_data_cache: &EthBlockDataCache<B>,
+	filter: Filter,
+	max_past_logs: u32,
+	history_window: Option<u64>,
+) -> Result<Vec<Log>>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError> + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: Send + Sync + 'static,
+{
+	let mut ret: Vec<Log> = Vec::new();
+	if let Some(hash) = filter.block_hash.clone() {
+		let id = match frontier_backend_client::load_hash::<B>(backend, hash)
+			.map_err(|err| internal_err(format!("{:?}", err)))?
+		{
+			Some(hash) => hash,
+			_ => return Ok(Vec::new()),
+		};
+		frontier_backend_client::ensure_history_available::<B, C, BE>(client, id, history_window)?;
+		let substrate_hash = client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+		let handler = overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&overrides.fallback);
+
+		let block = block_data_cache.current_block(handler, substrate_hash);
+		let statuses = block_data_cache.current_transaction_statuses(handler, substrate_hash);
+		if let (Some(block), Some(statuses)) = (block, statuses) {
+			filter_block_logs(&mut ret, &filter, block, statuses);
+		}
+	} else {
+		let best_number = client.info().best_number;
+		let mut current_number = filter
+			.to_block
+			.clone()
+			.and_then(|v| v.to_min_block_num())
+			.map(|s| s.unique_saturated_into())
+			.unwrap_or(best_number);
+
+		if current_number > best_number {
+			current_number = best_number;
+		}
+
+		// A range starting in the pruned history, as `fromBlock` 0 does on a pruned node, is
+		// served from the earliest block available. A range fully pruned fails.
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			client,
+			BlockId::Number(current_number),
+			history_window,
+		)?;
+		let earliest_number: u64 =
+			frontier_backend_client::earliest_history_block::<B, C, BE>(client, history_window);
+		let from_number = filter
+			.from_block
+			.clone()
+			.and_then(|v| v.to_min_block_num())
+			.map(|s| s.unique_saturated_into())
+			.unwrap_or(client.info().best_number)
+			.max(earliest_number.unique_saturated_into());
+
+		let _ = filter_range_logs(
+			client,
+			backend,
+			overrides,
+			block_data_cache,
+			&mut ret,
+			max_past_logs,
+			&filter,
+			from_number,
+			current_number,
+		)?;
+	}
+	Ok(ret)
+}
+
+/// Next nonce of `address` once the ready transactions of the pool, and the queued dev
+/// transactions, are applied on top of the best block.
+fn pending_nonce<B, C, P>(
+	client: &C,
+	pool: &P,
+	dev_state: Option<&DevState<B>>,
+	address: H160,
+) -> Result<U256>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	P: TransactionPool<Block = B>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let block = BlockId::Hash(client.info().best_hash);
+
+	let nonce = client
+		.runtime_api()
+		.account_basic(&block, address)
+		.map_err(|err| internal_err(format!("fetch runtime account basic failed: {:?}", err)))?
+		.nonce;
+
+	let mut current_nonce = nonce;
+	let mut current_tag = (address, nonce).encode();
+	for tx in pool.ready() {
+		// since transactions in `ready()` need to be ordered by nonce
+		// it's fine to continue with current iterator.
+		if tx.provides().get(0) == Some(&current_tag) {
+			current_nonce = current_nonce.saturating_add(1.into());
+			current_tag = (address, current_nonce).encode();
+		}
+	}
+
+	if let Some(dev_state) = dev_state {
+		current_nonce = dev_state.pending_nonce(address, current_nonce);
+	}
+
+	Ok(current_nonce)
+}
+
+/// Checks that the pool holds fewer transactions of `sender` than its `limits` allow, in the
+/// queue `nonce` goes to given the `expected_nonce` of the sender. Replacements of a transaction
+/// of the sender are always allowed. Ready transactions are counted by their tags, queued ones
+/// among those `eth_sendRawTransaction` accepted.
+fn ensure_sender_limits<B, P, A>(
+	pool: &P,
+	graph: &Pool<A>,
+	limits: &SenderLimits,
+	sender: H160,
+	nonce: U256,
+	expected_nonce: U256,
+) -> Result<()>
+where
+	P: TransactionPool<Block = B>,
+	A: ChainApi<Block = B> + 'static,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	if nonce < expected_nonce {
+		return Ok(());
+	}
+	if nonce == expected_nonce {
+		if let Some(limit) = limits.pending {
+			let prefix = sender.encode();
+			let pending = pool
+				.ready()
+				.filter(|tx| {
+					tx.provides()
+						.get(0)
+						.map_or(false, |tag| tag.starts_with(&prefix))
+				})
+				.count();
+			if pending >= limit {
+				return Err(account_limit_err(limit));
+			}
+		}
+		return Ok(());
+	}
+
+	if let Some(limit) = limits.queued {
+		let queued = limits
+			.queued_transactions
+			.count(sender, nonce, |hash| is_queued(graph, hash));
+		if queued >= limit {
+			return Err(account_limit_err(limit));
+		}
+	}
+	Ok(())
+}
+
+/// Block the manual seal engine would seal next: the ready transactions of the pool and the
+/// queued dev transactions on top of the best block.
+fn load_pending_block<B, C, P, BE>(
+	client: &C,
+	pool: &P,
+	overrides: &OverrideHandle<B>,
+	block_data_cache: &EthBlockDataCache<B>,
+	dev_state: &DevState<B>,
+	full: bool,
+) -> Result<Option<RichBlock>>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	C: Send + Sync + 'static,
+	P: TransactionPool<Block = B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let best_hash = client.info().best_hash;
+	let id = BlockId::Hash(best_hash);
+
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+
+	let parent = match block_data_cache.current_block(handler, best_hash) {
+		Some(parent) => parent,
+		None => return Ok(None),
+	};
+
+	let mut transactions = dev_state.pending_transactions();
+	let extrinsics: Vec<<B as BlockT>::Extrinsic> =
+		pool.ready().map(|tx| tx.data().clone()).collect();
+	transactions.extend(
+		client
+			.runtime_api()
+			.extrinsic_filter(&id, extrinsics)
+			.map_err(|err| {
+				internal_err(format!("fetch runtime extrinsic filter failed: {:?}", err))
+			})?,
+	);
+
+	let partial_header = ethereum::PartialHeader {
+		parent_hash: H256::from_slice(Keccak256::digest(&rlp::encode(&parent.header)).as_slice()),
+		beneficiary: parent.header.beneficiary,
+		state_root: parent.header.state_root,
+		receipts_root: H256::default(),
+		logs_bloom: Default::default(),
+		difficulty: U256::zero(),
+		number: parent.header.number.saturating_add(U256::one()),
+		gas_limit: parent.header.gas_limit,
+		gas_used: U256::zero(),
+		timestamp: dev_state.pending_timestamp(),
+		extra_data: Vec::new(),
+		mix_hash: H256::default(),
+		nonce: H64::default(),
+	};
+	let statuses = vec![None; transactions.len()];
+	let block = EthereumBlock::new(partial_header, transactions, vec![]);
+
+	Ok(Some(rich_block_build(block, statuses, None, full)))
+}
+
+/// Block the runtime builds from the ready transactions of the pool on top of the best block.
+/// The minimum gas price the block would be charged is reported as `baseFeePerGas`.
+fn build_pending_block<B, C, P>(client: &C, pool: &P, full: bool) -> Result<Option<RichBlock>>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	P: TransactionPool<Block = B>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let id = BlockId::Hash(client.info().best_hash);
+	let extrinsics: Vec<<B as BlockT>::Extrinsic> =
+		pool.ready().map(|tx| tx.data().clone()).collect();
+
+	let api = client.runtime_api();
+	let (block, _, statuses) = api
+		.pending_block(&id, extrinsics)
+		.map_err(|err| internal_err(format!("build pending block failed: {:?}", err)))?;
+	let base_fee = api
+		.gas_price(&id)
+		.map_err(|err| internal_err(format!("fetch runtime gas price failed: {:?}", err)))?;
+
+	match (block, statuses) {
+		(Some(block), Some(statuses)) => {
+			let mut rich_block = rich_block_build(
+				block,
+				statuses.into_iter().map(|s| Some(s)).collect(),
+				None,
+				full,
+			);
+			// The runtimes reporting their London block only have a base fee once it is active.
+			let london =
+				match frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id)? {
+					Some(api_version) if api_version >= 10 => {
+						frontier_backend_client::london_block::<B, C>(client, id)?.is_some()
+					}
+					_ => true,
+				};
+			if london {
+				rich_block
+					.extra_info
+					.insert("baseFeePerGas".to_string(), format!("0x{:x}", base_fee));
+			}
+			Ok(Some(rich_block))
+		}
+		_ => Ok(None),
+	}
+}
+
+struct ExecutableResult {
+	data: Vec<u8>,
+	exit_reason: ExitReason,
+	used_gas: U256,
+}
+
+/// Executes `request` with the runtime `call` or `create` entry point. Runtimes before version 7
+/// of the API take neither the EIP-1559 fee fields nor the access list, so the gas of the access
+/// list is charged here instead.
+fn execute_request<B, C>(
+	client: &C,
+	hash: B::Hash,
+	request: CallRequest,
+	gas_limit: U256,
+	access_list_gas: u64,
+	estimate: bool,
+) -> Result<ExecutableResult>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let CallRequest {
+		from,
+		to,
+		gas_price,
+		max_fee_per_gas,
+		max_priority_fee_per_gas,
+		gas: _,
+		value,
+		data,
+		input,
+		nonce,
+		access_list,
+	} = request;
+
+	let id = BlockId::Hash(hash);
+	let api_version =
+		frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id)?.unwrap_or(1);
+	let from = from.unwrap_or_default();
+	let value = value.unwrap_or_default();
+	let data = input.or(data).map(|d| d.0).unwrap_or_default();
+	let runtime_err = |err| internal_err(format!("runtime error: {:?}", err));
+	let fatal_err = |err| internal_err(format!("execution fatal: {:?}", err));
+
+	if api_version >= 7 {
+		let max_fee_per_gas = max_fee_per_gas.or(gas_price);
+		let access_list = access_list.map(|list| list.iter().map(|item| item.to_tuple()).collect());
+		let (exit_reason, data, used_gas) = match to {
+			Some(to) => {
+				let info = client
+					.runtime_api()
+					.call(
+						&id,
+						from,
+						to,
+						data,
+						value,
+						gas_limit,
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						estimate,
+						access_list,
+					)
+					.map_err(runtime_err)?
+					.map_err(fatal_err)?;
+				(info.exit_reason, info.value, info.used_gas)
+			}
+			None => {
+				let info = client
+					.runtime_api()
+					.create(
+						&id,
+						from,
+						data,
+						value,
+						gas_limit,
+						max_fee_per_gas,
+						max_priority_fee_per_gas,
+						nonce,
+						estimate,
+						access_list,
+					)
+					.map_err(runtime_err)?
+					.map_err(fatal_err)?;
+				(info.exit_reason, info.value[..].to_vec(), info.used_gas)
+			}
+		};
+		return Ok(ExecutableResult {
+			data,
+			exit_reason,
+			used_gas,
+		});
+	}
+
+	// The access list is charged before the execution.
+	let gas_limit = gas_limit.saturating_sub(U256::from(access_list_gas));
+	let gas_price = gas_price.or(max_fee_per_gas);
+	#[allow(deprecated)]
+	let (exit_reason, data, used_gas) = match to {
+		Some(to) => {
+			let info = client
+				.runtime_api()
+				.call_before_version_7(
+					&id, from, to, data, value, gas_limit, gas_price, nonce, estimate,
+				)
+				.map_err(runtime_err)?
+				.map_err(fatal_err)?;
+			(info.exit_reason, info.value, info.used_gas)
+		}
+		None => {
+			let info = client
+				.runtime_api()
+				.create_before_version_7(
+					&id, from, data, value, gas_limit, gas_price, nonce, estimate,
+				)
+				.map_err(runtime_err)?
+				.map_err(fatal_err)?;
+			(info.exit_reason, info.value[..].to_vec(), info.used_gas)
+		}
+	};
+	Ok(ExecutableResult {
+		data,
+		exit_reason,
+		used_gas: used_gas.saturating_add(U256::from(access_list_gas)),
+	})
+}
+
+fn execute_call<B, C>(client: &C, hash: B::Hash, request: CallRequest) -> Result<Bytes>
+where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	// use given gas limit or query current block's limit
+	let gas_limit = match request.gas {
+		Some(amount) => amount,
+		None => {
+			let block = client
+				.runtime_api()
+				.current_block(&BlockId::Hash(hash))
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+			if let Some(block) = block {
+				block.header.gas_limit
+			} else {
+				return Err(internal_err(format!(
+					"block unavailable, cannot query gas limit"
+				)));
+			}
+		}
+	};
+	let is_create = request.to.is_none();
+	let access_list_gas = access_list_gas::<B, C>(
+		client,
+		BlockId::Hash(hash),
+		request.access_list.as_deref().unwrap_or(&[]),
+	)?;
+	ensure_intrinsic_gas(
+		&runtime_evm_config::<B, C>(client, BlockId::Hash(hash))?,
+		gas_limit,
+		is_create,
+		request.call_data().map(|data| &data.0[..]).unwrap_or(&[]),
+		access_list_gas,
+	)?;
+
+	let ExecutableResult {
+		data, exit_reason, ..
+	} = execute_request(client, hash, request, gas_limit, access_list_gas, false)?;
+	if is_create {
+		error_on_execution_failure(&exit_reason, &[])?;
+	} else {
+		error_on_execution_failure(&exit_reason, &data)?;
+	}
+
+	Ok(Bytes(data))
+}
+
+/// Maximum number of executions of `eth_createAccessList` before its access list settles.
+const MAX_ACCESS_LIST_ROUNDS: usize = 8;
+
+/// Executes `request` with the addresses and storage keys it accesses until its access list
+/// settles, as geth does: each round adds the keys accessed by the execution with the list of
+/// the previous round.
+fn execute_create_access_list<B, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	number: Option<BlockNumber>,
+	request: CallRequest,
+) -> Result<AccessListResult>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let id = frontier_backend_client::native_block_id::<B, C>(client, backend, number)?
+		.ok_or_else(|| internal_err("block not found"))?;
+	frontier_backend_client::ensure_state_available::<B, C, BE>(client, id)?;
+
+	let mut accessed = BTreeMap::<H160, BTreeSet<H256>>::new();
+	for item in request.access_list.iter().flatten() {
+		accessed
+			.entry(item.address)
+			.or_default()
+			.extend(item.storage_keys.iter().cloned());
+	}
+	for _ in 0..MAX_ACCESS_LIST_ROUNDS {
+		let access_list = accessed
+			.iter()
+			.map(|(address, keys)| AccessListItem {
+				address: *address,
+				storage_keys: keys.iter().cloned().collect(),
+			})
+			.collect::<Vec<_>>();
+		// Fails on the chains predating Berlin.
+		access_list_gas::<B, C>(client, id, &access_list)?;
+		let trace = trace_call::<B, C>(
+			client,
+			id,
+			&request,
+			Some(access_list.iter().map(AccessListItem::to_tuple).collect()),
+		)?;
+
+		let previous = accessed.clone();
+		for (address, keys) in trace.access_list {
+			accessed.entry(address).or_default().extend(keys);
+		}
+		if accessed == previous {
+			return Ok(AccessListResult {
+				access_list,
+				gas_used: trace.used_gas,
+				error: error_on_execution_failure(&trace.exit_reason, &trace.output)
+					.err()
+					.map(|err| err.message),
+			});
+		}
+	}
+	Err(internal_err("access list did not settle"))
+}
+
+fn execute_estimate_gas<B, C, BE>(
+	client: &C,
+	overrides: &OverrideHandle<B>,
+	block_data_cache: &EthBlockDataCache<B>,
+	estimate_gas_error_ratio: f64,
+	best_hash: B::Hash,
+	request: CallRequest,
+) -> Result<U256>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	// Get gas price
+	let gas_price = request
+		.gas_price
+		.or(request.max_fee_per_gas)
+		.unwrap_or_default();
+
+	let get_current_block_gas_limit = || -> Result<U256> {
+		let id = BlockId::Hash(best_hash);
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+		let handler = overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&overrides.fallback);
+		let block = block_data_cache.current_block(handler, best_hash);
+		if let Some(block) = block {
+			Ok(block.header.gas_limit)
+		} else {
+			return Err(internal_err("block unavailable, cannot query gas limit"));
+		}
+	};
+
+	// Determine the highest possible gas limits
+	let mut highest = match request.gas {
+		Some(gas) => gas,
+		None => {
+			// query current block's gas limit
+			get_current_block_gas_limit()?
+		}
+	};
+
+	// Recap the highest gas allowance with account's balance.
+	if let Some(from) = request.from {
+		if gas_price > U256::zero() {
+			let balance = client
+				.runtime_api()
+				.account_basic(&BlockId::Hash(best_hash), from)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.balance;
+			let mut available = balance;
+			if let Some(value) = request.value {
+				if value > available {
+					return Err(internal_err("insufficient funds for transfer"));
+				}
+				available -= value;
+			}
+			let allowance = available / gas_price;
+			if highest > allowance {
+				log::warn!(
+					"Gas estimation capped by limited funds original {} balance {} sent {} feecap {} fundable {}",
+					highest,
+					balance,
+					request.value.unwrap_or_default(),
+					gas_price,
+					allowance
+				);
+				highest = allowance;
+			}
+		}
+	}
+
+	let access_list_gas = access_list_gas::<B, C>(
+		client,
+		BlockId::Hash(best_hash),
+		request.access_list.as_deref().unwrap_or(&[]),
+	)?;
+
+	// Create a helper to check if a gas allowance results in an executable transaction
+	let executable = move |request: CallRequest, gas_limit| -> Result<ExecutableResult> {
+		// Use request gas limit only if it less than gas_limit parameter
+		let gas_limit = core::cmp::min(request.gas.unwrap_or(gas_limit), gas_limit);
+		let is_create = request.to.is_none();
+
+		let mut result =
+			execute_request(client, best_hash, request, gas_limit, access_list_gas, true)?;
+		if is_create {
+			result.data = Vec::new();
+		}
+		Ok(result)
+	};
+
+	let intrinsic_gas = ensure_intrinsic_gas(
+		&runtime_evm_config::<B, C>(client, BlockId::Hash(best_hash))?,
+		highest,
+		request.to.is_none(),
+		request.call_data().map(|data| &data.0[..]).unwrap_or(&[]),
+		access_list_gas,
+	)?;
+
+	// Verify that the transaction succeed with highest capacity
+	let cap = highest;
+	let ExecutableResult {
+		data,
+		exit_reason,
+		used_gas,
+	} = executable(request.clone(), highest)?;
+	match exit_reason {
+		ExitReason::Succeed(_) => (),
+		ExitReason::Error(ExitError::OutOfGas) => {
+			return Err(internal_err(format!(
+				"gas required exceeds allowance {}",
+				cap
+			)))
+		}
+		// If the transaction reverts, there are two possible cases,
+		// it can revert because the called contract feels that it does not have enough
+		// gas left to continue, or it can revert for another reason unrelated to gas.
+		ExitReason::Revert(revert) => {
+			if request.gas.is_some() || request.gas_price.is_some() {
+				// If the user has provided a gas limit or a gas price, then we have executed
+				// with less block gas limit, so we must reexecute with block gas limit to
+				// know if the revert is due to a lack of gas or not.
+				let ExecutableResult {
+					data,
+					exit_reason,
+					used_gas: _,
+				} = executable(request.clone(), get_current_block_gas_limit()?)?;
+				match exit_reason {
+					ExitReason::Succeed(_) => {
+						return Err(internal_err(format!(
+							"gas required exceeds allowance {}",
+							cap
+						)))
+					}
+					// The execution has been done with block gas limit, so it is not a lack of gas from the user.
+					other => error_on_execution_failure(&other, &data)?,
+				}
+			} else {
+				// The execution has already been done with block gas limit, so it is not a lack of gas from the user.
+				error_on_execution_failure(&ExitReason::Revert(revert), &data)?
+			}
+		}
+		other => error_on_execution_failure(&other, &data)?,
+	};
+
+	// Define the lower bound of the binary search, an execution cannot succeed with less gas
+	// than it used with the highest limit.
+	const CALL_STIPEND: U256 = U256([2_300, 0, 0, 0]);
+	let mut lowest = std::cmp::max(U256::from(intrinsic_gas), used_gas).saturating_sub(U256::one());
+
+	// A call only forwards 63/64 of the gas left to its subcalls (EIP-150), so a transaction
+	// typically needs its used gas plus the 1/64th retained at each call. Try that limit first,
+	// it is enough for most transactions and narrows the search otherwise.
+	let optimistic = used_gas
+		.saturating_add(CALL_STIPEND)
+		.saturating_mul(U256::from(64))
+		/ 63;
+	if optimistic > lowest && optimistic < highest {
+		let ExecutableResult {
+			data,
+			exit_reason,
+			used_gas: _,
+		} = executable(request.clone(), optimistic)?;
+		match exit_reason {
+			ExitReason::Succeed(_) => highest = optimistic,
+			ExitReason::Revert(_) | ExitReason::Error(ExitError::OutOfGas) => lowest = optimistic,
+			other => error_on_execution_failure(&other, &data)?,
+		}
+	}
+
+	// Execute the binary search and hone in on an executable gas limit.
+	while lowest.saturating_add(U256::one()) < highest {
+		// The estimate is accurate enough once the search interval is within the error ratio.
+		if (highest - lowest).low_u128() as f64 / (highest.low_u128() as f64)
+			< estimate_gas_error_ratio
+		{
+			break;
+		}
+
+		// Most transactions need far less gas than the cap, favor the lower part of the interval.
+		let mid = std::cmp::min((highest + lowest) / 2, lowest.saturating_mul(U256::from(2)));
+		let ExecutableResult {
+			data,
+			exit_reason,
+			used_gas: _,
+		} = executable(request.clone(), mid)?;
+		match exit_reason {
+			ExitReason::Succeed(_) => highest = mid,
+			// A revert can be due to the lack of gas, the execution at the highest limit
+			// already ruled out reverts unrelated to gas.
+			ExitReason::Revert(_) | ExitReason::Error(ExitError::OutOfGas) => lowest = mid,
+			other => error_on_execution_failure(&other, &data)?,
+		}
+	}
+
+	// Verify the candidate limit with a final execution, adding the 1/64th retained by each
+	// call until it succeeds: the gas left to deep subcalls is not linear in the gas limit.
+	loop {
+		let ExecutableResult {
+			data,
+			exit_reason,
+			used_gas: _,
+		} = executable(request.clone(), highest)?;
+		match exit_reason {
+			ExitReason::Succeed(_) => return Ok(highest),
+			ExitReason::Revert(_) | ExitReason::Error(ExitError::OutOfGas) if highest < cap => {
+				highest = std::cmp::min(cap, highest.saturating_add(highest / 64 + 1));
+			}
+			other => {
+				error_on_execution_failure(&other, &data)?;
+				return Ok(highest);
+			}
+		}
+	}
+}
+
+impl<B, C, P, CT, BE, H: ExHashT, A> EthApiT for EthApi<B, C, P, CT, BE, H, A>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError> + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: Send + Sync + 'static,
+	P: TransactionPool<Block = B> + Send + Sync + 'static,
+	A: ChainApi<Block = B> + 'static,
+	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
+{
+	fn protocol_version(&self) -> Result<u64> {
+		Ok(1)
+	}
+
+	fn syncing(&self) -> Result<SyncStatus> {
+		if self.network.is_major_syncing() {
+			let block_number = U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(
+				self.client.info().best_number.clone(),
+			));
+			Ok(SyncStatus::Info(SyncInfo {
+				starting_block: U256::zero(),
+				current_block: block_number,
+				// TODO `highest_block` is not correct, should load `best_seen_block` from NetworkWorker,
+				// but afaik that is not currently possible in Substrate:
+				// https://github.com/paritytech/substrate/issues/7311
+				highest_block: block_number,
+				warp_chunks_amount: None,
+				warp_chunks_processed: None,
+			}))
+		} else {
+			Ok(SyncStatus::None)
+		}
+	}
+
+	fn hashrate(&self) -> Result<U256> {
+		Ok(U256::zero())
+	}
+
+	fn author(&self) -> Result<H160> {
+		if let Some(coinbase) = self.coinbase {
+			return Ok(coinbase);
+		}
+
+		let block = BlockId::Hash(self.client.info().best_hash);
+		// The author of the best block, as mapped by the runtime `FindAuthor`.
+		if frontier_backend_client::ethereum_runtime_api_version::<B, C>(
+			self.client.as_ref(),
+			block,
+		)?
+		.is_some()
+		{
+			return self
+				.client
+				.runtime_api()
+				.author(&block)
+				.map_err(|err| internal_err(format!("fetch runtime author failed: {:?}", err)));
+		}
+
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
+			self.client.as_ref(),
+			block,
+		);
+
+		Ok(self
+			.overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&self.overrides.fallback)
+			.current_block(&block)
+			.ok_or(internal_err("fetching author through override failed"))?
+			.header
+			.beneficiary)
+	}
+
+	fn is_mining(&self) -> Result<bool> {
+		Ok(self.is_authority)
+	}
+
+	fn chain_id(&self) -> Result<Option<U64>> {
+		let hash = self.client.info().best_hash;
+		Ok(Some(
+			self.client
+				.runtime_api()
+				.chain_id(&BlockId::Hash(hash))
+				.map_err(|err| internal_err(format!("fetch runtime chain id failed: {:?}", err)))?
+				.into(),
+		))
+	}
+
+	fn chain_config(&self) -> Result<ChainConfig> {
+		let id = BlockId::Hash(self.client.info().best_hash);
+		let api = self.client.runtime_api();
+		let api_version = frontier_backend_client::ethereum_runtime_api_version::<B, C>(
+			self.client.as_ref(),
+			id,
+		)?;
+
+		if let Some(version) = api_version {
+			if version >= 5 {
+				let config = api.chain_config(&id).map_err(|err| {
+					internal_err(format!("fetch runtime chain config failed: {:?}", err))
+				})?;
+				return Ok(ChainConfig {
+					chain_id: config.chain_id.into(),
+					evm_fork: Some(String::from_utf8_lossy(&config.evm_fork).into_owned()),
+					evm_features: Some(
+						config
+							.evm_features
+							.iter()
+							.map(|feature| String::from_utf8_lossy(feature).into_owned())
+							.collect(),
+					),
+					create_contract_limit: config.create_contract_limit.map(U64::from),
+					block_gas_limit: Some(config.block_gas_limit),
+					min_gas_price: config.min_gas_price,
+					min_gas_price_bounds: config.min_gas_price_bounds.map(
+						|(bound_divisor, lower_bound, upper_bound)| GasPriceBounds {
+							bound_divisor,
+							lower_bound,
+							upper_bound,
+						},
+					),
+					precompiles: Some(config.precompiles),
+				});
+			}
+		}
+
+		// The runtimes predating the API only report what the other methods expose.
+		let evm_fork = match api_version {
+			Some(version) if version >= 4 => Some(
+				api.evm_fork(&id)
+					.map(|fork| String::from_utf8_lossy(&fork).into_owned())
+					.map_err(|err| {
+						internal_err(format!("fetch runtime evm fork failed: {:?}", err))
+					})?,
+			),
+			_ => None,
+		};
+		let block_gas_limit = api
+			.current_block(&id)
+			.map_err(|err| internal_err(format!("fetch runtime block failed: {:?}", err)))?
+			.map(|block| block.header.gas_limit);
+
+		Ok(ChainConfig {
+			chain_id: api
+				.chain_id(&id)
+				.map_err(|err| internal_err(format!("fetch runtime chain id failed: {:?}", err)))?
+				.into(),
+			evm_fork,
+			evm_features: None,
+			create_contract_limit: None,
+			block_gas_limit,
+			min_gas_price: api.gas_price(&id).map_err(|err| {
+				internal_err(format!("fetch runtime gas price failed: {:?}", err))
+			})?,
+			min_gas_price_bounds: None,
+			precompiles: None,
+		})
+	}
+
+	fn gas_price(&self) -> Result<U256> {
+		let cached_base_fee = self
+			.fee_history_cache
+			.lock()
+			.map_err(|err| internal_err(format!("fee history cache lock failed: {:?}", err)))?
+			.values()
+			.next_back()
+			.map(|item| item.base_fee);
+
+		let base_fee = match cached_base_fee {
+			Some(base_fee) => base_fee,
+			None => {
+				let block = BlockId::Hash(self.client.info().best_hash);
+				self.client.runtime_api().gas_price(&block).map_err(|err| {
+					internal_err(format!("fetch runtime gas price failed: {:?}", err))
+				})?
+			}
+		};
+
+		Ok(base_fee.saturating_add(self.max_priority_fee_per_gas()?))
+	}
+
+	fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumber,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> BoxFuture<Result<FeeHistory>> {
+		let client = self.client.clone();
+		let backend = self.backend.clone();
+		let fee_history_cache = self.fee_history_cache.clone();
+
+		spawn_blocking_request(&self.spawn_handle, "frontier-rpc-fee-history", move || {
+			load_fee_history::<B, C>(
+				client.as_ref(),
+				backend.as_ref(),
+				&fee_history_cache,
+				block_count,
+				newest_block,
+				reward_percentiles,
+			)
+		})
+	}
+
+	fn max_priority_fee_per_gas(&self) -> Result<U256> {
+		// Median of the 60th percentile rewards of the latest 20 blocks.
+		const BLOCK_COUNT: usize = 20;
+		const REWARD_PERCENTILE: f64 = 60.0;
+
+		let mut rewards: Vec<U256> = self
+			.fee_history_cache
+			.lock()
+			.map_err(|err| internal_err(format!("fee history cache lock failed: {:?}", err)))?
+			.values()
+			.rev()
+			.take(BLOCK_COUNT)
+			.map(|item| item.reward_at(REWARD_PERCENTILE))
+			.collect();
+		rewards.sort();
+
+		Ok(rewards.get(rewards.len() / 2).cloned().unwrap_or_default())
+	}
+
+	fn accounts(&self) -> Result<Vec<H160>> {
+		let mut accounts = Vec::new();
+		for signer in self.signers.iter() {
+			accounts.append(&mut signer.accounts());
+		}
+		Ok(accounts)
+	}
+
+	fn block_number(&self) -> Result<U256> {
+		Ok(U256::from(
+			UniqueSaturatedInto::<u128>::unique_saturated_into(
+				self.client.info().best_number.clone(),
 			),
 		))
 	}
@@ -528,6 +2195,15 @@ where
 			self.backend.as_ref(),
 			number,
 		) {
+			frontier_backend_client::ensure_state_available::<B, C, BE>(self.client.as_ref(), id)?;
+			if frontier_backend_client::ethereum_runtime_api_version::<B, C>(
+				self.client.as_ref(),
+				id,
+			)?
+			.is_none()
+			{
+				return Ok(U256::zero());
+			}
 			return Ok(self
 				.client
 				.runtime_api()
@@ -545,6 +2221,7 @@ where
 			self.backend.as_ref(),
 			number,
 		) {
+			frontier_backend_client::ensure_state_available::<B, C, BE>(self.client.as_ref(), id)?;
 			let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
 				self.client.as_ref(),
 				id,
@@ -560,110 +2237,113 @@ where
 		Ok(H256::default())
 	}
 
-	fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
-		let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), hash)
-			.map_err(|err| internal_err(format!("{:?}", err)))?
-		{
-			Some(hash) => hash,
-			_ => return Ok(None),
-		};
-		let substrate_hash = self
-			.client
-			.expect_block_hash_from_id(&id)
-			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
-
-		let schema =
-			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
-		let handler = self
-			.overrides
-			.schemas
-			.get(&schema)
-			.unwrap_or(&self.overrides.fallback);
-
-		let block = self.block_data_cache.current_block(handler, substrate_hash);
-		let statuses = self
-			.block_data_cache
-			.current_transaction_statuses(handler, substrate_hash);
-
-		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(rich_block_build(
-				block,
-				statuses.into_iter().map(|s| Some(s)).collect(),
-				Some(hash),
-				full,
-			))),
-			_ => Ok(None),
-		}
+	fn block_by_hash(&self, hash: H256, full: bool) -> BoxFuture<Result<Option<RichBlock>>> {
+		let client = self.client.clone();
+		let backend = self.backend.clone();
+		let overrides = self.overrides.clone();
+		let block_data_cache = self.block_data_cache.clone();
+		let history_window = self.history_window;
+
+		spawn_blocking_request(
+			&self.spawn_handle,
+			"frontier-rpc-block-by-hash",
+			move || {
+				load_block_by_hash::<B, C, BE>(
+					client.as_ref(),
+					backend.as_ref(),
+					overrides.as_ref(),
+					block_data_cache.as_ref(),
+					hash,
+					full,
+					history_window,
+				)
+			},
+		)
 	}
 
-	fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>> {
-		let id = match frontier_backend_client::native_block_id::<B, C>(
-			self.client.as_ref(),
-			self.backend.as_ref(),
-			Some(number),
-		)? {
-			Some(id) => id,
-			None => return Ok(None),
-		};
-		let substrate_hash = self
-			.client
-			.expect_block_hash_from_id(&id)
-			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
-
-		let schema =
-			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
-		let handler = self
-			.overrides
-			.schemas
-			.get(&schema)
-			.unwrap_or(&self.overrides.fallback);
+	fn block_by_number(
+		&self,
+		number: BlockNumber,
+		full: bool,
+	) -> BoxFuture<Result<Option<RichBlock>>> {
+		let client = self.client.clone();
+		let backend = self.backend.clone();
+		let overrides = self.overrides.clone();
+		let block_data_cache = self.block_data_cache.clone();
+		let history_window = self.history_window;
+
+		if let (BlockNumber::Pending, Some(dev_state)) = (&number, self.dev_state.clone()) {
+			let pool = self.pool.clone();
+			return spawn_blocking_request(
+				&self.spawn_handle,
+				"frontier-rpc-pending-block",
+				move || {
+					load_pending_block::<B, C, P, BE>(
+						client.as_ref(),
+						pool.as_ref(),
+						overrides.as_ref(),
+						block_data_cache.as_ref(),
+						dev_state.as_ref(),
+						full,
+					)
+				},
+			);
+		}
 
-		let block = self.block_data_cache.current_block(handler, substrate_hash);
-		let statuses = self
-			.block_data_cache
-			.current_transaction_statuses(handler, substrate_hash);
+		if let BlockNumber::Pending = number {
+			let pool = self.pool.clone();
+			return spawn_blocking_request(
+				&self.spawn_handle,
+				"frontier-rpc-pending-block",
+				move || {
+					let id = BlockId::Hash(client.info().best_hash);
+					match frontier_backend_client::ethereum_runtime_api_version::<B, C>(
+						client.as_ref(),
+						id,
+					)? {
+						Some(api_version) if api_version >= 6 => {
+							build_pending_block::<B, C, P>(client.as_ref(), pool.as_ref(), full)
+						}
+						// Older runtimes cannot build a block, report the latest one instead.
+						_ => load_block_by_number::<B, C, BE>(
+							client.as_ref(),
+							backend.as_ref(),
+							overrides.as_ref(),
+							block_data_cache.as_ref(),
+							number,
+							full,
+							history_window,
+						),
+					}
+				},
+			);
+		}
 
-		match (block, statuses) {
-			(Some(block), Some(statuses)) => {
-				let hash =
-					H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
-
-				Ok(Some(rich_block_build(
-					block,
-					statuses.into_iter().map(|s| Some(s)).collect(),
-					Some(hash),
+		spawn_blocking_request(
+			&self.spawn_handle,
+			"frontier-rpc-block-by-number",
+			move || {
+				load_block_by_number::<B, C, BE>(
+					client.as_ref(),
+					backend.as_ref(),
+					overrides.as_ref(),
+					block_data_cache.as_ref(),
+					number,
 					full,
-				)))
-			}
-			_ => Ok(None),
-		}
+					history_window,
+				)
+			},
+		)
 	}
 
 	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
 		if let Some(BlockNumber::Pending) = number {
-			let block = BlockId::Hash(self.client.info().best_hash);
-
-			let nonce = self
-				.client
-				.runtime_api()
-				.account_basic(&block, address)
-				.map_err(|err| {
-					internal_err(format!("fetch runtime account basic failed: {:?}", err))
-				})?
-				.nonce;
-
-			let mut current_nonce = nonce;
-			let mut current_tag = (address, nonce).encode();
-			for tx in self.pool.ready() {
-				// since transactions in `ready()` need to be ordered by nonce
-				// it's fine to continue with current iterator.
-				if tx.provides().get(0) == Some(&current_tag) {
-					current_nonce = current_nonce.saturating_add(1.into());
-					current_tag = (address, current_nonce).encode();
-				}
-			}
-
-			return Ok(current_nonce);
+			return pending_nonce(
+				self.client.as_ref(),
+				self.pool.as_ref(),
+				self.dev_state.as_deref(),
+				address,
+			);
 		}
 
 		let id = match frontier_backend_client::native_block_id::<B, C>(
@@ -674,6 +2354,12 @@ where
 			Some(id) => id,
 			None => return Ok(U256::zero()),
 		};
+		frontier_backend_client::ensure_state_available::<B, C, BE>(self.client.as_ref(), id)?;
+		if frontier_backend_client::ethereum_runtime_api_version::<B, C>(self.client.as_ref(), id)?
+			.is_none()
+		{
+			return Ok(U256::zero());
+		}
 
 		let nonce = self
 			.client
@@ -693,6 +2379,11 @@ where
 			Some(hash) => hash,
 			_ => return Ok(None),
 		};
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			id,
+			self.history_window,
+		)?;
 		let schema =
 			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
 		let block = self
@@ -717,6 +2408,11 @@ where
 			Some(id) => id,
 			None => return Ok(None),
 		};
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			id,
+			self.history_window,
+		)?;
 		let schema =
 			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
 		let block = self
@@ -746,6 +2442,7 @@ where
 			self.backend.as_ref(),
 			number,
 		) {
+			frontier_backend_client::ensure_state_available::<B, C, BE>(self.client.as_ref(), id)?;
 			let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
 				self.client.as_ref(),
 				id,
@@ -763,7 +2460,27 @@ where
 		Ok(Bytes(vec![]))
 	}
 
+	fn sign(&self, address: H160, data: Bytes) -> Result<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+		sign_personal_message(&self.signers, &data.0, &address).map(Bytes)
+	}
+
 	fn send_transaction(&self, request: TransactionRequest) -> BoxFuture<Result<H256>> {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			return Box::pin(future::err(err.into()));
+		}
+		if let Err(err) = request.validate() {
+			return Box::pin(future::err(invalid_request_err(err)));
+		}
+		match request.transaction_type {
+			Some(transaction_type) if !transaction_type.is_zero() => {
+				return Box::pin(future::err(unsupported_transaction_type_err(
+					transaction_type.as_u64(),
+				)))
+			}
+			_ => {}
+		}
+
 		let from = match request.from {
 			Some(from) => from,
 			None => {
@@ -797,7 +2514,11 @@ where
 			gas_price: request.gas_price.unwrap_or(U256::from(1)),
 			gas_limit: request.gas.unwrap_or(U256::max_value()),
 			value: request.value.unwrap_or(U256::zero()),
-			input: request.data.map(|s| s.into_vec()).unwrap_or_default(),
+			input: request
+				.input
+				.or(request.data)
+				.map(|s| s.into_vec())
+				.unwrap_or_default(),
 			action: match request.to {
 				Some(to) => ethereum::TransactionAction::Call(to),
 				None => ethereum::TransactionAction::Create,
@@ -805,367 +2526,234 @@ where
 			chain_id: chain_id.map(|s| s.as_u64()),
 		};
 
-		let mut transaction = None;
-
-		for signer in &self.signers {
-			if signer.accounts().contains(&from) {
-				match signer.sign(message, &from) {
-					Ok(t) => transaction = Some(t),
-					Err(e) => return Box::pin(future::err(e)),
-				}
-				break;
-			}
+		if let Some(dev_state) = self
+			.dev_state
+			.as_ref()
+			.filter(|dev_state| dev_state.is_impersonated(&from))
+		{
+			return dev_state.transact(message, from);
 		}
 
-		let transaction = match transaction {
-			Some(transaction) => transaction,
-			None => return Box::pin(future::err(internal_err("no signer available"))),
-		};
-		let transaction_hash =
-			H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
 		let hash = self.client.info().best_hash;
-		Box::pin(
-			self.pool
-				.submit_one(
-					&BlockId::hash(hash),
-					TransactionSource::Local,
-					self.convert_transaction
-						.convert_transaction(transaction.clone()),
-				)
-				.map_ok(move |_| transaction_hash)
-				.map_err(|err| {
-					internal_err(format!("submit transaction to pool failed: {:?}", err))
-				}),
+		let expected_nonce = pending_nonce(
+			self.client.as_ref(),
+			self.pool.as_ref(),
+			self.dev_state.as_deref(),
+			from,
 		)
+		.ok();
+		let journal_backend = if self.journal_local_transactions {
+			Some(self.backend.clone())
+		} else {
+			None
+		};
+		let pool = self.pool.clone();
+		let convert_transaction = self.convert_transaction.clone();
+
+		// Signing may wait for a remote signer, it does not run on the RPC thread.
+		let signers = self.signers.clone();
+		let signed =
+			spawn_blocking_request(
+				&self.spawn_handle,
+				"frontier-rpc-sign",
+				move || match signers
+					.iter()
+					.find(|signer| signer.accounts().contains(&from))
+				{
+					Some(signer) => signer.sign(message, &from),
+					None => Err(internal_err("no signer available")),
+				},
+			);
+		Box::pin(signed.and_then(move |transaction| {
+			let transaction_hash =
+				H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+			let journal =
+				journal_backend.map(|backend| (backend, rlp::encode(&transaction).to_vec()));
+			pool.submit_one(
+				&BlockId::hash(hash),
+				TransactionSource::Local,
+				convert_transaction.convert_transaction(transaction),
+			)
+			.map_ok(move |_| {
+				if let Some((backend, transaction)) = journal {
+					if let Err(err) = backend
+						.local_transactions()
+						.write(transaction_hash, transaction)
+					{
+						log::warn!("Failed to journal local transaction: {:?}", err);
+					}
+				}
+				transaction_hash
+			})
+			.map_err(move |err| transaction_pool_err(err, nonce, expected_nonce))
+		}))
 	}
 
 	fn send_raw_transaction(&self, bytes: Bytes) -> BoxFuture<Result<H256>> {
+		// EIP-2718 typed transactions start with their type, below the RLP list prefixes.
+		match bytes.0.first() {
+			// Access list transactions (EIP-2930) need an EVM following Berlin.
+			Some(&0x01) => {
+				let id = BlockId::Hash(self.client.info().best_hash);
+				return Box::pin(future::err(
+					match frontier_backend_client::evm_fork::<B, C>(self.client.as_ref(), id) {
+						Ok(Some(fork)) if fork == "berlin" => {
+							unsupported_transaction_type_err(0x01)
+						}
+						Ok(fork) => access_lists_unsupported_err(fork),
+						Err(err) => err,
+					},
+				));
+			}
+			Some(&transaction_type) if transaction_type <= 0x7f => {
+				return Box::pin(future::err(unsupported_transaction_type_err(
+					transaction_type as u64,
+				)))
+			}
+			_ => {}
+		}
 		let transaction = match rlp::decode::<ethereum::TransactionV0>(&bytes.0[..]) {
 			Ok(transaction) => transaction,
 			Err(_) => return Box::pin(future::err(internal_err("decode transaction failed"))),
 		};
+		if !self.allow_unprotected_txs && transaction.signature.chain_id().is_none() {
+			return Box::pin(future::err(internal_err(
+				"only replay-protected (EIP-155) transactions allowed over RPC",
+			)));
+		}
 		let transaction_hash =
 			H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
 		let hash = self.client.info().best_hash;
-		Box::pin(
-			self.pool
-				.submit_one(
-					&BlockId::hash(hash),
-					TransactionSource::Local,
-					self.convert_transaction
-						.convert_transaction(transaction.clone()),
-				)
-				.map_ok(move |_| transaction_hash)
-				.map_err(|err| {
-					internal_err(format!("submit transaction to pool failed: {:?}", err))
-				}),
-		)
-	}
-
-	fn call(&self, request: CallRequest, _: Option<BlockNumber>) -> Result<Bytes> {
-		let hash = self.client.info().best_hash;
-
-		let CallRequest {
-			from,
-			to,
-			gas_price,
-			gas,
-			value,
-			data,
-			nonce,
-		} = request;
-
-		// use given gas limit or query current block's limit
-		let gas_limit = match gas {
-			Some(amount) => amount,
-			None => {
-				let block = self
-					.client
-					.runtime_api()
-					.current_block(&BlockId::Hash(hash))
-					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
-				if let Some(block) = block {
-					block.header.gas_limit
-				} else {
-					return Err(internal_err(format!(
-						"block unavailable, cannot query gas limit"
-					)));
-				}
-			}
-		};
-		let data = data.map(|d| d.0).unwrap_or_default();
-
-		match to {
-			Some(to) => {
-				let info = self
-					.client
-					.runtime_api()
-					.call(
-						&BlockId::Hash(hash),
-						from.unwrap_or_default(),
-						to,
-						data,
-						value.unwrap_or_default(),
-						gas_limit,
-						gas_price,
-						nonce,
-						false,
-					)
-					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-					.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
-
-				error_on_execution_failure(&info.exit_reason, &info.value)?;
-
-				Ok(Bytes(info.value))
-			}
-			None => {
-				let info = self
-					.client
-					.runtime_api()
-					.create(
-						&BlockId::Hash(hash),
-						from.unwrap_or_default(),
-						data,
-						value.unwrap_or_default(),
-						gas_limit,
-						gas_price,
-						nonce,
-						false,
-					)
-					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-					.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
-
-				error_on_execution_failure(&info.exit_reason, &[])?;
-
-				Ok(Bytes(info.value[..].to_vec()))
-			}
-		}
-	}
-
-	fn estimate_gas(&self, request: CallRequest, _: Option<BlockNumber>) -> Result<U256> {
-		// Get best hash
-		let best_hash = self.client.info().best_hash;
-
-		// Get gas price
-		let gas_price = request.gas_price.unwrap_or_default();
-
-		let get_current_block_gas_limit = || -> Result<U256> {
-			let substrate_hash = self.client.info().best_hash;
-			let id = BlockId::Hash(substrate_hash);
-			let schema =
-				frontier_backend_client::onchain_storage_schema::<B, C, BE>(&self.client, id);
-			let handler = self
-				.overrides
-				.schemas
-				.get(&schema)
-				.unwrap_or(&self.overrides.fallback);
-			let block = self.block_data_cache.current_block(handler, substrate_hash);
-			if let Some(block) = block {
-				Ok(block.header.gas_limit)
-			} else {
-				return Err(internal_err("block unavailable, cannot query gas limit"));
-			}
-		};
-
-		// Determine the highest possible gas limits
-		let mut highest = match request.gas {
-			Some(gas) => gas,
-			None => {
-				// query current block's gas limit
-				get_current_block_gas_limit()?
-			}
-		};
-
-		// Recap the highest gas allowance with account's balance.
-		if let Some(from) = request.from {
-			if gas_price > U256::zero() {
-				let balance = self
-					.client
-					.runtime_api()
-					.account_basic(&BlockId::Hash(best_hash), from)
-					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-					.balance;
-				let mut available = balance;
-				if let Some(value) = request.value {
-					if value > available {
-						return Err(internal_err("insufficient funds for transfer"));
-					}
-					available -= value;
-				}
-				let allowance = available / gas_price;
-				if highest > allowance {
-					log::warn!(
-						"Gas estimation capped by limited funds original {} balance {} sent {} feecap {} fundable {}",
-						highest,
-						balance,
-						request.value.unwrap_or_default(),
-						gas_price,
-						allowance
-					);
-					highest = allowance;
-				}
+		let nonce = transaction.nonce;
+		let sender = public_key(&transaction)
+			.ok()
+			.map(|pk| H160::from(H256::from_slice(Keccak256::digest(&pk).as_slice())));
+		let expected_nonce = sender.and_then(|sender| {
+			pending_nonce(
+				self.client.as_ref(),
+				self.pool.as_ref(),
+				self.dev_state.as_deref(),
+				sender,
+			)
+			.ok()
+		});
+		if let (Some(sender), Some(expected_nonce)) = (sender, expected_nonce) {
+			if let Err(err) = ensure_sender_limits(
+				self.pool.as_ref(),
+				self.graph.as_ref(),
+				&self.sender_limits,
+				sender,
+				nonce,
+				expected_nonce,
+			) {
+				return Box::pin(future::err(err));
 			}
 		}
-
-		struct ExecutableResult {
-			data: Vec<u8>,
-			exit_reason: ExitReason,
-			used_gas: U256,
-		}
-
-		// Create a helper to check if a gas allowance results in an executable transaction
-		let executable = move |request: CallRequest, gas_limit| -> Result<ExecutableResult> {
-			let CallRequest {
-				from,
-				to,
-				gas_price,
-				gas,
-				value,
-				data,
-				nonce,
-			} = request;
-
-			// Use request gas limit only if it less than gas_limit parameter
-			let gas_limit = core::cmp::min(gas.unwrap_or(gas_limit), gas_limit);
-
-			let data = data.map(|d| d.0).unwrap_or_default();
-
-			let (exit_reason, data, used_gas) = match to {
-				Some(to) => {
-					let info = self
-						.client
-						.runtime_api()
-						.call(
-							&BlockId::Hash(best_hash),
-							from.unwrap_or_default(),
-							to,
-							data,
-							value.unwrap_or_default(),
-							gas_limit,
-							gas_price,
-							nonce,
-							true,
-						)
-						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-						.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
-
-					(info.exit_reason, info.value, info.used_gas)
-				}
-				None => {
-					let info = self
-						.client
-						.runtime_api()
-						.create(
-							&BlockId::Hash(best_hash),
-							from.unwrap_or_default(),
-							data,
-							value.unwrap_or_default(),
-							gas_limit,
-							gas_price,
-							nonce,
-							true,
-						)
-						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-						.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
-
-					(info.exit_reason, Vec::new(), info.used_gas)
-				}
-			};
-
-			Ok(ExecutableResult {
-				exit_reason,
-				data,
-				used_gas,
-			})
+		let journal = if self.journal_local_transactions {
+			Some((self.backend.clone(), rlp::encode(&transaction).to_vec()))
+		} else {
+			None
 		};
-
-		// Verify that the transaction succeed with highest capacity
-		let cap = highest;
-		let ExecutableResult {
-			data,
-			exit_reason,
-			used_gas,
-		} = executable(request.clone(), highest)?;
-		match exit_reason {
-			ExitReason::Succeed(_) => (),
-			ExitReason::Error(ExitError::OutOfGas) => {
-				return Err(internal_err(format!(
-					"gas required exceeds allowance {}",
-					cap
-				)))
+		let extrinsic = self
+			.convert_transaction
+			.convert_transaction(transaction.clone());
+		// Future transactions are tracked by sender for the queued limit.
+		let queued = match (sender, expected_nonce) {
+			(Some(sender), Some(expected_nonce))
+				if nonce > expected_nonce && self.sender_limits.queued.is_some() =>
+			{
+				Some((
+					sender,
+					self.graph.hash_of(&extrinsic),
+					self.graph.clone(),
+					self.sender_limits.queued_transactions.clone(),
+				))
 			}
-			// If the transaction reverts, there are two possible cases,
-			// it can revert because the called contract feels that it does not have enough
-			// gas left to continue, or it can revert for another reason unrelated to gas.
-			ExitReason::Revert(revert) => {
-				if request.gas.is_some() || request.gas_price.is_some() {
-					// If the user has provided a gas limit or a gas price, then we have executed
-					// with less block gas limit, so we must reexecute with block gas limit to
-					// know if the revert is due to a lack of gas or not.
-					let ExecutableResult {
-						data,
-						exit_reason,
-						used_gas: _,
-					} = executable(request.clone(), get_current_block_gas_limit()?)?;
-					match exit_reason {
-						ExitReason::Succeed(_) => {
-							return Err(internal_err(format!(
-								"gas required exceeds allowance {}",
-								cap
-							)))
+			_ => None,
+		};
+		Box::pin(
+			self.pool
+				.submit_one(&BlockId::hash(hash), TransactionSource::Local, extrinsic)
+				.map_ok(move |_| {
+					if let Some((sender, pool_hash, graph, queued_transactions)) = queued {
+						queued_transactions
+							.insert(sender, nonce, pool_hash, |hash| is_queued(&graph, hash));
+					}
+					if let Some((backend, transaction)) = journal {
+						if let Err(err) = backend
+							.local_transactions()
+							.write(transaction_hash, transaction)
+						{
+							log::warn!("Failed to journal local transaction: {:?}", err);
 						}
-						// The execution has been done with block gas limit, so it is not a lack of gas from the user.
-						other => error_on_execution_failure(&other, &data)?,
 					}
-				} else {
-					// The execution has already been done with block gas limit, so it is not a lack of gas from the user.
-					error_on_execution_failure(&ExitReason::Revert(revert), &data)?
-				}
-			}
-			other => error_on_execution_failure(&other, &data)?,
-		};
+					transaction_hash
+				})
+				.map_err(move |err| transaction_pool_err(err, nonce, expected_nonce)),
+		)
+	}
 
-		#[cfg(not(feature = "rpc_binary_search_estimate"))]
-		{
-			Ok(used_gas)
-		}
-		#[cfg(feature = "rpc_binary_search_estimate")]
-		{
-			// Define the lower bound of the binary search
-			const MIN_GAS_PER_TX: U256 = U256([21_000, 0, 0, 0]);
-			let mut lowest = MIN_GAS_PER_TX;
+	fn call(&self, request: CallRequest, _: Option<BlockNumber>) -> BoxFuture<Result<Bytes>> {
+		let client = self.client.clone();
+		let call_cache = self.call_cache.clone();
 
-			// Start close to the used gas for faster binary search
-			let mut mid = std::cmp::min(used_gas * 3, (highest + lowest) / 2);
+		spawn_blocking_request(&self.spawn_handle, "frontier-rpc-call", move || {
+			request.validate().map_err(invalid_request_err)?;
+			let hash = client.info().best_hash;
+			call_cache.call(hash, request, |request| {
+				execute_call::<B, C>(client.as_ref(), hash, request)
+			})
+		})
+	}
 
-			// Execute the binary search and hone in on an executable gas limit.
-			let mut previous_highest = highest;
-			while (highest - lowest) > U256::one() {
-				let ExecutableResult {
-					data,
-					exit_reason,
-					used_gas: _,
-				} = executable(request.clone(), highest)?;
-				match exit_reason {
-					ExitReason::Succeed(_) => {
-						highest = mid;
-						// If the variation in the estimate is less than 10%,
-						// then the estimate is considered sufficiently accurate.
-						if (previous_highest - highest) * 10 / previous_highest < U256::one() {
-							return Ok(highest);
-						}
-						previous_highest = highest;
-					}
-					ExitReason::Revert(_) | ExitReason::Error(ExitError::OutOfGas) => {
-						lowest = mid;
-					}
-					other => error_on_execution_failure(&other, &data)?,
-				}
-				mid = (highest + lowest) / 2;
-			}
+	fn estimate_gas(
+		&self,
+		request: CallRequest,
+		_: Option<BlockNumber>,
+	) -> BoxFuture<Result<U256>> {
+		let client = self.client.clone();
+		let overrides = self.overrides.clone();
+		let block_data_cache = self.block_data_cache.clone();
+		let estimate_gas_error_ratio = self.estimate_gas_error_ratio;
+		let call_cache = self.call_cache.clone();
+
+		spawn_blocking_request(&self.spawn_handle, "frontier-rpc-estimate-gas", move || {
+			request.validate().map_err(invalid_request_err)?;
+			let hash = client.info().best_hash;
+			call_cache.estimate_gas(hash, request, |request| {
+				execute_estimate_gas::<B, C, BE>(
+					client.as_ref(),
+					overrides.as_ref(),
+					block_data_cache.as_ref(),
+					estimate_gas_error_ratio,
+					hash,
+					request,
+				)
+			})
+		})
+	}
 
-			Ok(highest)
-		}
+	fn create_access_list(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> BoxFuture<Result<AccessListResult>> {
+		let client = self.client.clone();
+		let backend = self.backend.clone();
+
+		spawn_blocking_request(
+			&self.spawn_handle,
+			"frontier-rpc-create-access-list",
+			move || {
+				request.validate().map_err(invalid_request_err)?;
+				execute_create_access_list::<B, C, BE>(
+					client.as_ref(),
+					backend.as_ref(),
+					number,
+					request,
+				)
+			},
+		)
 	}
 
 	fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
@@ -1218,6 +2806,7 @@ where
 					}
 				}
 				// Unknown transaction.
+				frontier_backend_client::ensure_transactions_indexed(self.backend.as_ref())?;
 				return Ok(None);
 			}
 		};
@@ -1228,6 +2817,11 @@ where
 			Some(hash) => hash,
 			_ => return Ok(None),
 		};
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			id,
+			self.history_window,
+		)?;
 		let substrate_hash = self
 			.client
 			.expect_block_hash_from_id(&id)
@@ -1247,11 +2841,7 @@ where
 			.current_transaction_statuses(handler, substrate_hash);
 
 		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(transaction_build(
-				block.transactions[index].clone(),
-				Some(block),
-				Some(statuses[index].clone()),
-			))),
+			(Some(block), Some(statuses)) => Ok(block_transaction_build(block, statuses, index)),
 			_ => Ok(None),
 		}
 	}
@@ -1267,6 +2857,11 @@ where
 			Some(hash) => hash,
 			_ => return Ok(None),
 		};
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			id,
+			self.history_window,
+		)?;
 		let substrate_hash = self
 			.client
 			.expect_block_hash_from_id(&id)
@@ -1288,11 +2883,7 @@ where
 			.current_transaction_statuses(handler, substrate_hash);
 
 		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(transaction_build(
-				block.transactions[index].clone(),
-				Some(block),
-				Some(statuses[index].clone()),
-			))),
+			(Some(block), Some(statuses)) => Ok(block_transaction_build(block, statuses, index)),
 			_ => Ok(None),
 		}
 	}
@@ -1310,6 +2901,11 @@ where
 			Some(id) => id,
 			None => return Ok(None),
 		};
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			id,
+			self.history_window,
+		)?;
 		let substrate_hash = self
 			.client
 			.expect_block_hash_from_id(&id)
@@ -1330,116 +2926,32 @@ where
 			.current_transaction_statuses(handler, substrate_hash);
 
 		match (block, statuses) {
-			(Some(block), Some(statuses)) => Ok(Some(transaction_build(
-				block.transactions[index].clone(),
-				Some(block),
-				Some(statuses[index].clone()),
-			))),
+			(Some(block), Some(statuses)) => Ok(block_transaction_build(block, statuses, index)),
 			_ => Ok(None),
 		}
 	}
 
-	fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>> {
-		let (hash, index) = match frontier_backend_client::load_transactions::<B, C>(
-			self.client.as_ref(),
-			self.backend.as_ref(),
-			hash,
-			true,
+	fn transaction_receipt(&self, hash: H256) -> BoxFuture<Result<Option<Receipt>>> {
+		let client = self.client.clone();
+		let backend = self.backend.clone();
+		let overrides = self.overrides.clone();
+		let block_data_cache = self.block_data_cache.clone();
+		let history_window = self.history_window;
+
+		spawn_blocking_request(
+			&self.spawn_handle,
+			"frontier-rpc-transaction-receipt",
+			move || {
+				load_transaction_receipt::<B, C, BE>(
+					client.as_ref(),
+					backend.as_ref(),
+					overrides.as_ref(),
+					block_data_cache.as_ref(),
+					hash,
+					history_window,
+				)
+			},
 		)
-		.map_err(|err| internal_err(format!("{:?}", err)))?
-		{
-			Some((hash, index)) => (hash, index as usize),
-			None => return Ok(None),
-		};
-
-		let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), hash)
-			.map_err(|err| internal_err(format!("{:?}", err)))?
-		{
-			Some(hash) => hash,
-			_ => return Ok(None),
-		};
-		let substrate_hash = self
-			.client
-			.expect_block_hash_from_id(&id)
-			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
-
-		let schema =
-			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
-		let handler = self
-			.overrides
-			.schemas
-			.get(&schema)
-			.unwrap_or(&self.overrides.fallback);
-
-		let block = self.block_data_cache.current_block(handler, substrate_hash);
-		let statuses = self
-			.block_data_cache
-			.current_transaction_statuses(handler, substrate_hash);
-		let receipts = handler.current_receipts(&id);
-
-		match (block, statuses, receipts) {
-			(Some(block), Some(statuses), Some(receipts)) => {
-				let block_hash =
-					H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
-				let receipt = receipts[index].clone();
-				let status = statuses[index].clone();
-				let mut cumulative_receipts = receipts.clone();
-				cumulative_receipts.truncate((status.transaction_index + 1) as usize);
-
-				return Ok(Some(Receipt {
-					transaction_hash: Some(status.transaction_hash),
-					transaction_index: Some(status.transaction_index.into()),
-					block_hash: Some(block_hash),
-					from: Some(status.from),
-					to: status.to,
-					block_number: Some(block.header.number),
-					cumulative_gas_used: {
-						let cumulative_gas: u32 = cumulative_receipts
-							.iter()
-							.map(|r| r.used_gas.as_u32())
-							.sum();
-						U256::from(cumulative_gas)
-					},
-					gas_used: Some(receipt.used_gas),
-					contract_address: status.contract_address,
-					logs: {
-						let mut pre_receipts_log_index = None;
-						if cumulative_receipts.len() > 0 {
-							cumulative_receipts.truncate(cumulative_receipts.len() - 1);
-							pre_receipts_log_index = Some(
-								cumulative_receipts
-									.iter()
-									.map(|r| r.logs.len() as u32)
-									.sum::<u32>(),
-							);
-						}
-						receipt
-							.logs
-							.iter()
-							.enumerate()
-							.map(|(i, log)| Log {
-								address: log.address,
-								topics: log.topics.clone(),
-								data: Bytes(log.data.clone()),
-								block_hash: Some(block_hash),
-								block_number: Some(block.header.number),
-								transaction_hash: Some(status.transaction_hash),
-								transaction_index: Some(status.transaction_index.into()),
-								log_index: Some(U256::from(
-									(pre_receipts_log_index.unwrap_or(0)) + i as u32,
-								)),
-								transaction_log_index: Some(U256::from(i)),
-								removed: false,
-							})
-							.collect()
-					},
-					status_code: Some(U64::from(receipt.state_root.to_low_u64_be())),
-					logs_bloom: receipt.logs_bloom,
-					state_root: None,
-				}));
-			}
-			_ => Ok(None),
-		}
 	}
 
 	fn uncle_by_block_hash_and_index(&self, _: H256, _: Index) -> Result<Option<RichBlock>> {
@@ -1454,70 +2966,25 @@ where
 		Ok(None)
 	}
 
-	fn logs(&self, filter: Filter) -> Result<Vec<Log>> {
-		let mut ret: Vec<Log> = Vec::new();
-		if let Some(hash) = filter.block_hash.clone() {
-			let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), hash)
-				.map_err(|err| internal_err(format!("{:?}", err)))?
-			{
-				Some(hash) => hash,
-				_ => return Ok(Vec::new()),
-			};
-			let substrate_hash = self
-				.client
-				.expect_block_hash_from_id(&id)
-				.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
-
-			let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
-				self.client.as_ref(),
-				id,
-			);
-			let handler = self
-				.overrides
-				.schemas
-				.get(&schema)
-				.unwrap_or(&self.overrides.fallback);
-
-			let block = self.block_data_cache.current_block(handler, substrate_hash);
-			let statuses = self
-				.block_data_cache
-				.current_transaction_statuses(handler, substrate_hash);
-			if let (Some(block), Some(statuses)) = (block, statuses) {
-				filter_block_logs(&mut ret, &filter, block, statuses);
-			}
-		} else {
-			let best_number = self.client.info().best_number;
-			let mut current_number = filter
-				.to_block
-				.clone()
-				.and_then(|v| v.to_min_block_num())
-				.map(|s| s.unique_saturated_into())
-				.unwrap_or(best_number);
-
-			if current_number > best_number {
-				current_number = best_number;
-			}
-
-			let from_number = filter
-				.from_block
-				.clone()
-				.and_then(|v| v.to_min_block_num())
-				.map(|s| s.unique_saturated_into())
-				.unwrap_or(self.client.info().best_number);
-
-			let _ = filter_range_logs(
-				self.client.as_ref(),
-				self.backend.as_ref(),
-				&self.overrides,
-				&self.block_data_cache,
-				&mut ret,
-				self.max_past_logs,
-				&filter,
-				from_number,
-				current_number,
-			)?;
-		}
-		Ok(ret)
+	fn logs(&self, filter: Filter) -> BoxFuture<Result<Vec<Log>>> {
+		let client = self.client.clone();
+		let backend = self.backend.clone();
+		let overrides = self.overrides.clone();
+		let block_data_cache = self.block_data_cache.clone();
+		let max_past_logs = self.max_past_logs;
+		let history_window = self.history_window;
+
+		spawn_blocking_request(&self.spawn_handle, "frontier-rpc-logs", move || {
+			load_logs::<B, C, BE>(
+				client.as_ref(),
+				backend.as_ref(),
+				overrides.as_ref(),
+				block_data_cache.as_ref(),
+				filter,
+				max_past_logs,
+				history_window,
+			)
+		})
 	}
 
 	fn work(&self) -> Result<Work> {
@@ -1542,6 +3009,7 @@ pub struct NetApi<B: BlockT, BE, C, H: ExHashT> {
 	client: Arc<C>,
 	network: Arc<NetworkService<B, H>>,
 	peer_count_as_hex: bool,
+	network_id: Option<u64>,
 	_marker: PhantomData<BE>,
 }
 
@@ -1550,11 +3018,13 @@ impl<B: BlockT, BE, C, H: ExHashT> NetApi<B, BE, C, H> {
 		client: Arc<C>,
 		network: Arc<NetworkService<B, H>>,
 		peer_count_as_hex: bool,
+		network_id: Option<u64>,
 	) -> Self {
 		Self {
 			client,
 			network,
 			peer_count_as_hex,
+			network_id,
 			_marker: PhantomData,
 		}
 	}
@@ -1571,7 +3041,9 @@ where
 	B: BlockT<Hash = H256> + Send + Sync + 'static,
 {
 	fn is_listening(&self) -> Result<bool> {
-		Ok(true)
+		let state = futures::executor::block_on(self.network.network_state())
+			.map_err(|_| internal_err("fetch network state failed"))?;
+		Ok(!state.listened_addresses.is_empty())
 	}
 
 	fn peer_count(&self) -> Result<PeerCount> {
@@ -1583,6 +3055,10 @@ where
 	}
 
 	fn version(&self) -> Result<String> {
+		if let Some(network_id) = self.network_id {
+			return Ok(network_id.to_string());
+		}
+
 		let hash = self.client.info().best_hash;
 		Ok(self
 			.client
@@ -1616,20 +3092,20 @@ where
 	B: BlockT<Hash = H256> + Send + Sync + 'static,
 {
 	fn client_version(&self) -> Result<String> {
-		let hash = self.client.info().best_hash;
-		let version = self
-			.client
-			.runtime_api()
-			.version(&BlockId::Hash(hash))
-			.map_err(|err| internal_err(format!("fetch runtime version failed: {:?}", err)))?;
-		Ok(format!(
-			"{spec_name}/v{spec_version}.{impl_version}/{pkg_name}-{pkg_version}",
+		let version = crate::frontier::version_info::<B, C>(self.client.as_ref())?;
+		let mut client_version = format!(
+			"{spec_name}/v{spec_version}.{impl_version}/{client}-{client_version}",
 			spec_name = version.spec_name,
 			spec_version = version.spec_version,
 			impl_version = version.impl_version,
-			pkg_name = env!("CARGO_PKG_NAME"),
-			pkg_version = env!("CARGO_PKG_VERSION")
-		))
+			client = version.client,
+			client_version = version.client_version,
+		);
+		// The runtimes predating the API do not report their EVM hard fork.
+		if let Some(evm_fork) = version.evm_fork {
+			client_version.push_str(&format!("/evm-{}", evm_fork));
+		}
+		Ok(client_version)
 	}
 
 	fn sha3(&self, input: Bytes) -> Result<H256> {
@@ -1939,6 +3415,114 @@ where
 	}
 }
 
+fn fee_history_cache_item<B, C, BE>(
+	client: &C,
+	overrides: &OverrideHandle<B>,
+	hash: H256,
+) -> Option<(u64, FeeHistoryCacheItem)>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let id = BlockId::Hash(hash);
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+
+	let block = handler.current_block(&id)?;
+	let receipts = handler.current_receipts(&id)?;
+	// The minimum gas price acts as the base fee, there is none before the runtime API.
+	let base_fee =
+		match frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id).ok()? {
+			Some(_) => client.runtime_api().gas_price(&id).ok()?,
+			None => U256::zero(),
+		};
+
+	let gas_used_ratio = if block.header.gas_limit.is_zero() {
+		0.0
+	} else {
+		block.header.gas_used.low_u64() as f64 / block.header.gas_limit.low_u64() as f64
+	};
+
+	let mut previous_cumulative_gas = U256::zero();
+	let mut rewards: Vec<(U256, u64)> = block
+		.transactions
+		.iter()
+		.zip(receipts.iter())
+		.map(|(transaction, receipt)| {
+			let gas_used = receipt.used_gas.saturating_sub(previous_cumulative_gas);
+			previous_cumulative_gas = receipt.used_gas;
+			(
+				transaction.gas_price.saturating_sub(base_fee),
+				gas_used.low_u64(),
+			)
+		})
+		.collect();
+	rewards.sort_by(|a, b| a.0.cmp(&b.0));
+
+	Some((
+		block.header.number.low_u64(),
+		FeeHistoryCacheItem {
+			base_fee,
+			gas_used_ratio,
+			rewards,
+		},
+	))
+}
+
+/// EVM activity of the best chain block `hash`, for the `EvmMetrics`.
+fn block_activity<B, C, BE>(
+	client: &C,
+	overrides: &OverrideHandle<B>,
+	hash: H256,
+) -> Option<BlockActivity>
+where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+{
+	let id = BlockId::Hash(hash);
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+
+	let block = handler.current_block(&id)?;
+	let statuses = handler.current_transaction_statuses(&id)?;
+	let receipts = handler.current_receipts(&id)?;
+	let creates = statuses.iter().filter(|status| status.to.is_none()).count() as u64;
+	let contracts_created = statuses
+		.iter()
+		.zip(receipts.iter())
+		.filter(|(status, receipt)| {
+			status.to.is_none() && receipt.state_root == H256::from_low_u64_be(1)
+		})
+		.count() as u64;
+	let precompile_calls =
+		match frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id).ok()? {
+			Some(version) if version >= 9 => client.runtime_api().precompile_calls(&id).ok()?,
+			_ => Vec::new(),
+		};
+
+	Some(BlockActivity {
+		gas_used: block.header.gas_used.low_u64(),
+		calls: statuses.len() as u64 - creates,
+		creates,
+		contracts_created,
+		precompile_calls,
+	})
+}
+
 pub struct EthTask<B, C>(PhantomData<(B, C)>);
 
 impl<B, C> EthTask<B, C>
@@ -1946,24 +3530,46 @@ where
 	C: ProvideRuntimeApi<B> + BlockchainEvents<B> + HeaderBackend<B>,
 	B: BlockT<Hash = H256>,
 {
+	/// Rebuilds the storage schema cache from the runtime storage when it is missing or
+	/// disagrees with it.
+	pub fn heal_schema_cache<BE>(client: &C, backend: &fc_db::Backend<B>)
+	where
+		C: StorageProvider<B, BE> + Send + Sync + 'static,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: Send + Sync + 'static,
+	{
+		match frontier_backend_client::load_cached_schema::<B>(backend) {
+			Ok(Some(cache))
+				if frontier_backend_client::is_schema_cache_valid::<B, C, BE>(client, &cache) =>
+			{
+				return
+			}
+			Ok(None) => {}
+			_ => log::warn!("Schema cache disagrees with the runtime storage, rebuilding it"),
+		}
+
+		if let Err(err) = frontier_backend_client::compute_schema_cache::<B, C, BE>(client)
+			.and_then(|cache| frontier_backend_client::write_cached_schema::<B>(backend, cache))
+		{
+			log::warn!("Error schema cache rebuild: {:?}", err);
+		}
+	}
+
 	/// Task that caches at which best hash a new EthereumStorageSchema was inserted in the Runtime Storage.
-	pub async fn ethereum_schema_cache_task(client: Arc<C>, backend: Arc<fc_db::Backend<B>>) {
+	/// A missing or inconsistent cache is rebuilt first.
+	pub async fn ethereum_schema_cache_task<BE>(client: Arc<C>, backend: Arc<fc_db::Backend<B>>)
+	where
+		C: StorageProvider<B, BE> + Send + Sync + 'static,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: Send + Sync + 'static,
+	{
 		use fp_storage::PALLET_ETHEREUM_SCHEMA;
 		use log::warn;
 		use sp_storage::{StorageData, StorageKey};
 
-		if let Ok(None) = frontier_backend_client::load_cached_schema::<B>(backend.as_ref()) {
-			let mut cache: Vec<(EthereumStorageSchema, H256)> = Vec::new();
-			if let Ok(Some(header)) = client.header(BlockId::Number(Zero::zero())) {
-				cache.push((EthereumStorageSchema::V1, header.hash()));
-				let _ = frontier_backend_client::write_cached_schema::<B>(backend.as_ref(), cache)
-					.map_err(|err| {
-						warn!("Error schema cache insert for genesis: {:?}", err);
-					});
-			} else {
-				warn!("Error genesis header unreachable");
-			}
-		}
+		Self::heal_schema_cache::<BE>(client.as_ref(), backend.as_ref());
 
 		// Subscribe to changes for the pallet-ethereum Schema.
 		if let Ok(mut stream) = client.storage_changes_notification_stream(
@@ -2017,6 +3623,7 @@ where
 								}
 							} else {
 								warn!("Error schema cache is corrupted");
+								Self::heal_schema_cache::<BE>(client.as_ref(), backend.as_ref());
 							}
 						}
 					}
@@ -2025,6 +3632,189 @@ where
 		}
 	}
 
+	/// Task that maintains the fee data of the latest `block_limit` best blocks.
+	pub async fn fee_history_task<BE>(
+		client: Arc<C>,
+		overrides: Arc<OverrideHandle<B>>,
+		fee_history_cache: FeeHistoryCache,
+		block_limit: u64,
+	) where
+		C: StorageProvider<B, BE> + Send + Sync + 'static,
+		C::Api: EthereumRuntimeRPCApi<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: Send + Sync + 'static,
+	{
+		let mut notification_st = client.import_notification_stream();
+
+		while let Some(notification) = notification_st.next().await {
+			if !notification.is_new_best {
+				continue;
+			}
+
+			// On a re-org the enacted blocks replace the retracted ones at the same heights.
+			let mut hashes: Vec<H256> = notification
+				.tree_route
+				.as_ref()
+				.map(|route| route.enacted().iter().map(|block| block.hash).collect())
+				.unwrap_or_default();
+			hashes.push(notification.hash);
+
+			let items: Vec<(u64, FeeHistoryCacheItem)> = hashes
+				.into_iter()
+				.filter_map(|hash| {
+					fee_history_cache_item::<B, C, BE>(client.as_ref(), overrides.as_ref(), hash)
+				})
+				.collect();
+
+			if let Ok(fee_history_cache) = &mut fee_history_cache.lock() {
+				for (number, item) in items {
+					fee_history_cache.insert(number, item);
+					// Drop the entries above a retracted head, and the ones out of the window.
+					let stale: Vec<u64> = fee_history_cache
+						.keys()
+						.filter(|&&k| k > number || k.saturating_add(block_limit) <= number)
+						.cloned()
+						.collect();
+					for key in stale {
+						fee_history_cache.remove(&key);
+					}
+				}
+			}
+		}
+	}
+
+	/// Task that updates the `EvmMetrics` with the blocks enacted on the best chain.
+	pub async fn evm_metrics_task<BE>(
+		client: Arc<C>,
+		overrides: Arc<OverrideHandle<B>>,
+		metrics: EvmMetrics,
+	) where
+		C: StorageProvider<B, BE> + Send + Sync + 'static,
+		C::Api: EthereumRuntimeRPCApi<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: Send + Sync + 'static,
+	{
+		let mut notification_st = client.import_notification_stream();
+
+		while let Some(notification) = notification_st.next().await {
+			if !notification.is_new_best {
+				continue;
+			}
+
+			let mut hashes: Vec<H256> = notification
+				.tree_route
+				.as_ref()
+				.map(|route| route.enacted().iter().map(|block| block.hash).collect())
+				.unwrap_or_default();
+			hashes.push(notification.hash);
+
+			for hash in hashes {
+				if let Some(activity) =
+					block_activity::<B, C, BE>(client.as_ref(), overrides.as_ref(), hash)
+				{
+					metrics.observe_block(activity);
+				}
+			}
+		}
+	}
+
+	/// Task that re-injects the journaled local transactions into the pool on startup, and
+	/// drops the ones which were mined or can no longer be included, then the ones included in
+	/// the new best blocks.
+	pub async fn local_transactions_task<P, CT, BE>(
+		client: Arc<C>,
+		pool: Arc<P>,
+		backend: Arc<fc_db::Backend<B>>,
+		overrides: Arc<OverrideHandle<B>>,
+		convert_transaction: CT,
+	) where
+		C: StorageProvider<B, BE> + Send + Sync + 'static,
+		C::Api: EthereumRuntimeRPCApi<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		P: TransactionPool<Block = B> + Send + Sync + 'static,
+		CT: ConvertTransaction<<B as BlockT>::Extrinsic>,
+		B: Send + Sync + 'static,
+	{
+		use log::{debug, warn};
+
+		let journal = backend.local_transactions();
+		let hashes = match journal.hashes() {
+			Ok(hashes) => hashes,
+			Err(err) => {
+				warn!("Failed to read the local transactions journal: {:?}", err);
+				return;
+			}
+		};
+
+		let best_hash = client.info().best_hash;
+		for transaction_hash in hashes {
+			let transaction = match journal.transaction(&transaction_hash) {
+				Ok(Some(raw)) => rlp::decode::<EthereumTransaction>(&raw[..]).ok(),
+				_ => None,
+			};
+			let transaction = match transaction {
+				Some(transaction) => transaction,
+				None => {
+					let _ = journal.remove(&transaction_hash);
+					continue;
+				}
+			};
+
+			let mined = frontier_backend_client::load_transactions::<B, C>(
+				client.as_ref(),
+				backend.as_ref(),
+				transaction_hash,
+				true,
+			)
+			.map(|found| found.is_some())
+			.unwrap_or(false);
+			if mined {
+				let _ = journal.remove(&transaction_hash);
+				continue;
+			}
+
+			if let Err(err) = pool
+				.submit_one(
+					&BlockId::Hash(best_hash),
+					TransactionSource::Local,
+					convert_transaction.convert_transaction(transaction),
+				)
+				.await
+			{
+				debug!(
+					"Dropping local transaction {:?} from the journal: {:?}",
+					transaction_hash, err
+				);
+				let _ = journal.remove(&transaction_hash);
+			}
+		}
+
+		let mut notification_st = client.import_notification_stream();
+		while let Some(notification) = notification_st.next().await {
+			if !notification.is_new_best {
+				continue;
+			}
+			let id = BlockId::Hash(notification.hash);
+			let schema =
+				frontier_backend_client::onchain_storage_schema::<B, C, BE>(client.as_ref(), id);
+			let handler = overrides
+				.schemas
+				.get(&schema)
+				.unwrap_or(&overrides.fallback);
+			let statuses = handler
+				.current_transaction_statuses(&id)
+				.unwrap_or_default();
+			for status in statuses {
+				if let Err(err) = journal.remove(&status.transaction_hash) {
+					warn!("Failed to prune the local transactions journal: {:?}", err);
+				}
+			}
+		}
+	}
+
 	pub async fn filter_pool_task(
 		client: Arc<C>,
 		filter_pool: Arc<Mutex<BTreeMap<U256, FilterPoolItem>>>,
@@ -2079,6 +3869,16 @@ impl<B: BlockT> EthBlockDataCache<B> {
 		}
 	}
 
+	/// Number of cached blocks.
+	pub fn blocks_len(&self) -> usize {
+		self.blocks.lock().len()
+	}
+
+	/// Number of blocks with cached transaction statuses.
+	pub fn statuses_len(&self) -> usize {
+		self.statuses.lock().len()
+	}
+
 	/// Cache for `handler.current_block`.
 	pub fn current_block(
 		&self,
@@ -2127,3 +3927,110 @@ impl<B: BlockT> EthBlockDataCache<B> {
 		None
 	}
 }
+
+type CallCacheKey<B> = (<B as BlockT>::Hash, CallRequest);
+
+/// Stores the results of the latest `eth_call` and `eth_estimateGas` requests, by block and
+/// request. Front-ends frequently issue the same requests against the best block, the results
+/// are kept for a short time only to bound the memory used.
+pub struct EthCallCache<B: BlockT> {
+	ttl: time::Duration,
+	calls: parking_lot::Mutex<LruCache<CallCacheKey<B>, (time::Instant, Bytes)>>,
+	estimates: parking_lot::Mutex<LruCache<CallCacheKey<B>, (time::Instant, U256)>>,
+}
+
+impl<B: BlockT> EthCallCache<B> {
+	/// Create a new cache with provided cache size and time to live of the results.
+	pub fn new(cache_size: usize, ttl: time::Duration) -> Self {
+		Self {
+			ttl,
+			calls: parking_lot::Mutex::new(LruCache::new(cache_size)),
+			estimates: parking_lot::Mutex::new(LruCache::new(cache_size)),
+		}
+	}
+
+	/// Cache for `eth_call`.
+	pub fn call<F>(&self, hash: B::Hash, request: CallRequest, execute: F) -> Result<Bytes>
+	where
+		F: FnOnce(CallRequest) -> Result<Bytes>,
+	{
+		Self::cached(&self.calls, self.ttl, hash, request, execute)
+	}
+
+	/// Cache for `eth_estimateGas`.
+	pub fn estimate_gas<F>(&self, hash: B::Hash, request: CallRequest, execute: F) -> Result<U256>
+	where
+		F: FnOnce(CallRequest) -> Result<U256>,
+	{
+		Self::cached(&self.estimates, self.ttl, hash, request, execute)
+	}
+
+	fn cached<T: Clone, F>(
+		cache: &parking_lot::Mutex<LruCache<CallCacheKey<B>, (time::Instant, T)>>,
+		ttl: time::Duration,
+		hash: B::Hash,
+		request: CallRequest,
+		execute: F,
+	) -> Result<T>
+	where
+		F: FnOnce(CallRequest) -> Result<T>,
+	{
+		let key = (hash, request);
+		{
+			let mut cache = cache.lock();
+			match cache.get(&key) {
+				Some((inserted, result)) if inserted.elapsed() < ttl => return Ok(result.clone()),
+				Some(_) => {
+					cache.pop(&key);
+				}
+				None => (),
+			}
+		}
+
+		// Errors are not cached, they may be transient.
+		let result = execute(key.1.clone())?;
+		cache
+			.lock()
+			.put(key, (time::Instant::now(), result.clone()));
+
+		Ok(result)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn queued_transactions_count_those_left_in_the_queue() {
+		let queued_transactions = QueuedTransactions::default();
+		let sender = H160::repeat_byte(1);
+		let in_queue = |hash: &H256| *hash != H256::repeat_byte(2);
+		for (nonce, hash) in (5..8).zip(1..4) {
+			queued_transactions.insert(
+				sender,
+				U256::from(nonce),
+				H256::repeat_byte(hash),
+				in_queue,
+			);
+		}
+
+		// The second transaction left the queue, the replaced third one is not counted.
+		assert_eq!(
+			queued_transactions.count(sender, U256::from(7), in_queue),
+			1
+		);
+		assert_eq!(
+			queued_transactions.count(sender, U256::from(9), in_queue),
+			2
+		);
+		assert_eq!(
+			queued_transactions.count(H160::repeat_byte(2), U256::zero(), in_queue),
+			0
+		);
+
+		// Senders without queued transactions are forgotten.
+		queued_transactions.count(sender, U256::zero(), |_| false);
+		assert!(queued_transactions.0.lock().unwrap().is_empty());
+	}
+}
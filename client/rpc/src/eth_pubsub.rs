@@ -24,16 +24,23 @@ use sc_client_api::{
 	client::BlockchainEvents,
 };
 use sc_rpc::Metadata;
-use sc_transaction_pool_api::TransactionPool;
+use sc_transaction_pool_api::{InPoolTransaction, TransactionPool, TxHash};
 use sp_api::{BlockId, ProvideRuntimeApi};
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
-use sp_runtime::traits::{BlakeTwo256, Block as BlockT, UniqueSaturatedInto};
+use sp_runtime::traits::{
+	BlakeTwo256, Block as BlockT, Header as HeaderT, NumberFor, UniqueSaturatedInto,
+};
 use std::{collections::BTreeMap, iter, marker::PhantomData, sync::Arc};
 
+use codec::Encode;
+use ethereum::TransactionV0 as EthereumTransaction;
 use ethereum_types::{H256, U256};
 use fc_rpc_core::{
 	types::{
-		pubsub::{Kind, Params, PubSubSyncStatus, Result as PubSubResult},
+		pubsub::{
+			Kind, Params, PubSubSyncStatus, PubSubTransactionLifecycle, Result as PubSubResult,
+			TransactionLifecycleStatus,
+		},
 		Bytes, FilteredParams, Header, Log, Rich,
 	},
 	EthPubSubApi::{self as EthPubSubApiT},
@@ -48,12 +55,12 @@ use sha3::{Digest, Keccak256};
 pub use fc_rpc_core::EthPubSubApiServer;
 use futures::{FutureExt as _, SinkExt as _, StreamExt as _};
 
-use fp_rpc::EthereumRuntimeRPCApi;
+use fp_rpc::{EthereumRuntimeRPCApi, TransactionStatus};
 use jsonrpc_core::Result as JsonRpcResult;
 
 use sc_network::{ExHashT, NetworkService};
 
-use crate::{frontier_backend_client, overrides::OverrideHandle};
+use crate::{eth::transaction_build, frontier_backend_client, overrides::OverrideHandle, DevState};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct HexEncodedIdProvider {
@@ -85,6 +92,8 @@ pub struct EthPubSubApi<B: BlockT, P, C, BE, H: ExHashT> {
 	network: Arc<NetworkService<B, H>>,
 	subscriptions: SubscriptionManager<HexEncodedIdProvider>,
 	overrides: Arc<OverrideHandle<B>>,
+	backend: Arc<fc_db::Backend<B>>,
+	dev_state: Option<Arc<DevState<B>>>,
 	_marker: PhantomData<(B, BE)>,
 }
 
@@ -101,6 +110,8 @@ where
 		network: Arc<NetworkService<B, H>>,
 		subscriptions: SubscriptionManager<HexEncodedIdProvider>,
 		overrides: Arc<OverrideHandle<B>>,
+		backend: Arc<fc_db::Backend<B>>,
+		dev_state: Option<Arc<DevState<B>>>,
 	) -> Self {
 		Self {
 			pool: pool.clone(),
@@ -108,6 +119,8 @@ where
 			network,
 			subscriptions,
 			overrides,
+			backend,
+			dev_state,
 			_marker: PhantomData,
 		}
 	}
@@ -150,71 +163,38 @@ impl SubscriptionResult {
 	pub fn logs(
 		&self,
 		block: ethereum::BlockV0,
-		receipts: Vec<ethereum::Receipt>,
+		statuses: Vec<TransactionStatus>,
 		params: &FilteredParams,
 	) -> Vec<Log> {
-		let block_hash = Some(H256::from_slice(
-			Keccak256::digest(&rlp::encode(&block.header)).as_slice(),
-		));
-		let mut logs: Vec<Log> = vec![];
-		let mut log_index: u32 = 0;
-		for (receipt_index, receipt) in receipts.into_iter().enumerate() {
-			let mut transaction_log_index: u32 = 0;
-			let transaction_hash: Option<H256> = if receipt.logs.len() > 0 {
-				Some(H256::from_slice(
-					Keccak256::digest(&rlp::encode(&block.transactions[receipt_index as usize]))
-						.as_slice(),
-				))
-			} else {
-				None
-			};
-			for log in receipt.logs {
-				if self.add_log(block_hash.unwrap(), &log, &block, params) {
-					logs.push(Log {
-						address: log.address,
-						topics: log.topics,
-						data: Bytes(log.data),
-						block_hash: block_hash,
-						block_number: Some(block.header.number),
-						transaction_hash: transaction_hash,
-						transaction_index: Some(U256::from(receipt_index)),
-						log_index: Some(U256::from(log_index)),
-						transaction_log_index: Some(U256::from(transaction_log_index)),
-						removed: false,
-					});
-				}
-				log_index += 1;
-				transaction_log_index += 1;
-			}
-		}
-		logs
+		let block_hash =
+			H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+		Log::block_logs(
+			block_hash,
+			block.header.number,
+			statuses
+				.into_iter()
+				.map(|status| (status.transaction_hash, status.logs))
+				.collect(),
+		)
+		.into_iter()
+		.flatten()
+		.filter(|log| self.add_log(block_hash, log, &block, params))
+		.collect()
 	}
 	fn add_log(
 		&self,
 		block_hash: H256,
-		ethereum_log: &ethereum::Log,
+		log: &Log,
 		block: &ethereum::BlockV0,
 		params: &FilteredParams,
 	) -> bool {
-		let log = Log {
-			address: ethereum_log.address.clone(),
-			topics: ethereum_log.topics.clone(),
-			data: Bytes(ethereum_log.data.clone()),
-			block_hash: None,
-			block_number: None,
-			transaction_hash: None,
-			transaction_index: None,
-			log_index: None,
-			transaction_log_index: None,
-			removed: false,
-		};
 		if let Some(_) = params.filter {
 			let block_number =
 				UniqueSaturatedInto::<u64>::unique_saturated_into(block.header.number);
 			if !params.filter_block_range(block_number)
 				|| !params.filter_block_hash(block_hash)
-				|| !params.filter_address(&log)
-				|| !params.filter_topics(&log)
+				|| !params.filter_address(log)
+				|| !params.filter_topics(log)
 			{
 				return false;
 			}
@@ -223,6 +203,222 @@ impl SubscriptionResult {
 	}
 }
 
+enum LifecycleEvent<H, N> {
+	PoolImport(H),
+	NewBest(H256),
+	Finalized(N),
+}
+
+/// Block of the best chain including the followed transaction.
+struct Inclusion {
+	substrate_hash: H256,
+	block_hash: H256,
+	block_number: U256,
+	transaction_index: U256,
+}
+
+/// Follows a transaction from the pool to a finalized block.
+struct TransactionLifecycle<B: BlockT, P, C, BE> {
+	client: Arc<C>,
+	pool: Arc<P>,
+	overrides: Arc<OverrideHandle<B>>,
+	transaction_hash: H256,
+	transaction: Option<EthereumTransaction>,
+	inclusion: Option<Inclusion>,
+	_marker: PhantomData<BE>,
+}
+
+impl<B, P, C, BE> TransactionLifecycle<B, P, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	P: TransactionPool<Block = B> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	fn new(
+		client: Arc<C>,
+		pool: Arc<P>,
+		overrides: Arc<OverrideHandle<B>>,
+		transaction_hash: H256,
+	) -> Self {
+		Self {
+			client,
+			pool,
+			overrides,
+			transaction_hash,
+			transaction: None,
+			inclusion: None,
+			_marker: PhantomData,
+		}
+	}
+
+	fn update(&self, status: TransactionLifecycleStatus) -> PubSubTransactionLifecycle {
+		PubSubTransactionLifecycle {
+			status,
+			block_hash: self
+				.inclusion
+				.as_ref()
+				.map(|inclusion| inclusion.block_hash),
+			block_number: self
+				.inclusion
+				.as_ref()
+				.map(|inclusion| inclusion.block_number),
+			transaction_index: self
+				.inclusion
+				.as_ref()
+				.map(|inclusion| inclusion.transaction_index),
+		}
+	}
+
+	fn is_followed(&self, transaction: &EthereumTransaction) -> bool {
+		H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice())
+			== self.transaction_hash
+	}
+
+	/// Stage of the transaction when the subscription starts.
+	fn start(&mut self, backend: &fc_db::Backend<B>) -> Vec<PubSubTransactionLifecycle> {
+		let client = self.client.as_ref();
+		let substrate_hash = frontier_backend_client::load_transactions::<B, C>(
+			client,
+			backend,
+			self.transaction_hash,
+			true,
+		)
+		.ok()
+		.flatten()
+		.and_then(|(ethereum_block_hash, _)| {
+			frontier_backend_client::load_hash::<B>(backend, ethereum_block_hash)
+				.ok()
+				.flatten()
+		})
+		.and_then(|id| client.block_hash_from_id(&id).ok().flatten());
+		if let Some(substrate_hash) = substrate_hash {
+			if self.include(substrate_hash) {
+				let mut updates = vec![self.update(TransactionLifecycleStatus::Included)];
+				updates.extend(self.on_finalized(client.info().finalized_number));
+				return updates;
+			}
+		}
+
+		let best = BlockId::Hash(client.info().best_hash);
+		let extrinsics = self.pool.ready().map(|tx| tx.data().clone()).collect();
+		let transaction = client
+			.runtime_api()
+			.extrinsic_filter(&best, extrinsics)
+			.ok()
+			.and_then(|transactions| transactions.into_iter().find(|t| self.is_followed(t)));
+		match transaction {
+			Some(transaction) => {
+				self.transaction = Some(transaction);
+				vec![self.update(TransactionLifecycleStatus::Pending)]
+			}
+			None => Vec::new(),
+		}
+	}
+
+	/// Records the block as including the transaction if it does.
+	fn include(&mut self, substrate_hash: H256) -> bool {
+		let id = BlockId::Hash(substrate_hash);
+		let schema =
+			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
+		let handler = self
+			.overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&self.overrides.fallback);
+
+		let block = match handler.current_block(&id) {
+			Some(block) => block,
+			None => return false,
+		};
+		match block.transactions.iter().position(|t| self.is_followed(t)) {
+			Some(index) => {
+				self.transaction = Some(block.transactions[index].clone());
+				self.inclusion = Some(Inclusion {
+					substrate_hash,
+					block_hash: H256::from_slice(
+						Keccak256::digest(&rlp::encode(&block.header)).as_slice(),
+					),
+					block_number: block.header.number,
+					transaction_index: U256::from(index),
+				});
+				true
+			}
+			None => false,
+		}
+	}
+
+	fn on_pool_import(&mut self, hash: TxHash<P>) -> Option<PubSubTransactionLifecycle> {
+		if self.transaction.is_some() {
+			return None;
+		}
+		let xt = self.pool.ready_transaction(&hash)?;
+		let best = BlockId::Hash(self.client.info().best_hash);
+		let transaction = self
+			.client
+			.runtime_api()
+			.extrinsic_filter(&best, vec![xt.data().clone()])
+			.ok()?
+			.into_iter()
+			.find(|t| self.is_followed(t))?;
+
+		self.transaction = Some(transaction);
+		Some(self.update(TransactionLifecycleStatus::Pending))
+	}
+
+	fn on_new_best(&mut self, substrate_hash: H256) -> Option<PubSubTransactionLifecycle> {
+		if let Some(inclusion) = &self.inclusion {
+			if frontier_backend_client::is_canon::<B, C>(
+				self.client.as_ref(),
+				inclusion.substrate_hash,
+			) {
+				return None;
+			}
+		}
+		if self.include(substrate_hash) {
+			return Some(self.update(TransactionLifecycleStatus::Included));
+		}
+		// The pool resubmits the transactions of retracted blocks on its next maintenance.
+		if self.inclusion.take().is_some() {
+			return Some(self.update(TransactionLifecycleStatus::Pending));
+		}
+
+		let transaction = transaction_build(self.transaction.clone()?, None, None);
+		let tag = (transaction.from, transaction.nonce).encode();
+		if self.pool.ready().any(|tx| tx.provides().contains(&tag)) {
+			return None;
+		}
+		let nonce = self
+			.client
+			.runtime_api()
+			.account_basic(&BlockId::Hash(substrate_hash), transaction.from)
+			.ok()?
+			.nonce;
+		if transaction.nonce < nonce {
+			Some(self.update(TransactionLifecycleStatus::Replaced))
+		} else {
+			Some(self.update(TransactionLifecycleStatus::Dropped))
+		}
+	}
+
+	fn on_finalized(&self, number: NumberFor<B>) -> Option<PubSubTransactionLifecycle> {
+		let inclusion = self.inclusion.as_ref()?;
+		let included = self.client.number(inclusion.substrate_hash).ok()??;
+		if included <= number
+			&& frontier_backend_client::is_canon::<B, C>(
+				self.client.as_ref(),
+				inclusion.substrate_hash,
+			) {
+			Some(self.update(TransactionLifecycleStatus::Finalized))
+		} else {
+			None
+		}
+	}
+}
+
 impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApiT for EthPubSubApi<B, P, C, BE, H>
 where
 	B: BlockT<Hash = H256> + Send + Sync + 'static,
@@ -242,6 +438,10 @@ where
 		kind: Kind,
 		params: Option<Params>,
 	) {
+		let transaction_hash = match &params {
+			Some(Params::TransactionHash(hash)) => Some(*hash),
+			_ => None,
+		};
 		let filtered_params = match params {
 			Some(Params::Logs(filter)) => FilteredParams::new(Some(filter)),
 			_ => FilteredParams::default(),
@@ -271,11 +471,11 @@ where
 									.unwrap_or(&overrides.fallback);
 
 								let block = handler.current_block(&id);
-								let receipts = handler.current_receipts(&id);
+								let statuses = handler.current_transaction_statuses(&id);
 
-								match (receipts, block) {
-									(Some(receipts), Some(block)) => {
-										futures::future::ready(Some((block, receipts)))
+								match (block, statuses) {
+									(Some(block), Some(statuses)) => {
+										futures::future::ready(Some((block, statuses)))
 									}
 									_ => futures::future::ready(None),
 								}
@@ -283,10 +483,10 @@ where
 								futures::future::ready(None)
 							}
 						})
-						.flat_map(move |(block, receipts)| {
+						.flat_map(move |(block, statuses)| {
 							futures::stream::iter(SubscriptionResult::new().logs(
 								block,
-								receipts,
+								statuses,
 								&filtered_params,
 							))
 						})
@@ -337,10 +537,9 @@ where
 				});
 			}
 			Kind::NewPendingTransactions => {
-				use sc_transaction_pool_api::InPoolTransaction;
-
+				let dev_state = self.dev_state.clone();
 				self.subscriptions.add(subscriber, move |sink| {
-					let stream = pool
+					let pool_transactions = pool
 						.import_notification_stream()
 						.filter_map(move |txhash| {
 							if let Some(xt) = pool.ready_transaction(&txhash) {
@@ -364,12 +563,24 @@ where
 							}
 						})
 						.map(|transaction| {
-							return Ok::<Result<PubSubResult, jsonrpc_core::types::error::Error>, ()>(
-								Ok(PubSubResult::TransactionHash(H256::from_slice(
-									Keccak256::digest(&rlp::encode(&transaction)).as_slice(),
-								))),
-							);
+							H256::from_slice(
+								Keccak256::digest(&rlp::encode(&transaction)).as_slice(),
+							)
 						});
+					// Transactions of impersonated accounts are queued without going through the pool.
+					let transactions = match dev_state {
+						Some(dev_state) => futures::stream::select(
+							pool_transactions,
+							dev_state.pending_transactions_stream(),
+						)
+						.boxed(),
+						None => pool_transactions.boxed(),
+					};
+					let stream = transactions.map(|hash| {
+						return Ok::<Result<PubSubResult, jsonrpc_core::types::error::Error>, ()>(
+							Ok(PubSubResult::TransactionHash(hash)),
+						);
+					});
 					stream
 						.forward(
 							sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)),
@@ -405,6 +616,70 @@ where
 						.map(|_| ())
 				});
 			}
+			Kind::TransactionLifecycle => {
+				let transaction_hash = match transaction_hash {
+					Some(transaction_hash) => transaction_hash,
+					None => {
+						let _ = subscriber.reject(jsonrpc_core::Error::invalid_params(
+							"expected a transaction hash",
+						));
+						return;
+					}
+				};
+				let mut lifecycle = TransactionLifecycle::<B, P, C, BE>::new(
+					client.clone(),
+					pool.clone(),
+					overrides,
+					transaction_hash,
+				);
+				let initial = lifecycle.start(self.backend.as_ref());
+				self.subscriptions.add(subscriber, move |sink| {
+					let events = futures::stream::select(
+						futures::stream::select(
+							pool.import_notification_stream()
+								.map(LifecycleEvent::PoolImport),
+							client
+								.import_notification_stream()
+								.filter(|notification| {
+									futures::future::ready(notification.is_new_best)
+								})
+								.map(|notification| LifecycleEvent::NewBest(notification.hash)),
+						),
+						client.finality_notification_stream().map(|notification| {
+							LifecycleEvent::Finalized(*notification.header.number())
+						}),
+					);
+					let stream = futures::stream::iter(initial)
+						.chain(events.filter_map(move |event| {
+							futures::future::ready(match event {
+								LifecycleEvent::PoolImport(hash) => lifecycle.on_pool_import(hash),
+								LifecycleEvent::NewBest(hash) => lifecycle.on_new_best(hash),
+								LifecycleEvent::Finalized(number) => lifecycle.on_finalized(number),
+							})
+						}))
+						// End the subscription after the final stage.
+						.flat_map(|update| {
+							let end = if update.status.is_final() {
+								Some(None)
+							} else {
+								None
+							};
+							futures::stream::iter(iter::once(Some(update)).chain(end))
+						})
+						.take_while(|update| futures::future::ready(update.is_some()))
+						.filter_map(futures::future::ready)
+						.map(|update| {
+							return Ok::<Result<PubSubResult, jsonrpc_core::types::error::Error>, ()>(
+								Ok(PubSubResult::TransactionLifecycle(update)),
+							);
+						});
+					stream
+						.forward(
+							sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)),
+						)
+						.map(|_| ())
+				});
+			}
 		}
 	}
 
@@ -0,0 +1,468 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fork of a remote Ethereum compatible chain, for the dev node.
+//!
+//! The remote state is fetched at the pinned block the first time a RPC request references it:
+//! the accounts given as parameters and, for the requests executing a call, the accounts and
+//! storage slots of the access list the remote node creates for that call, which include the
+//! contracts only reached through internal calls. The state is written to the local chain by
+//! the dev commands of a new block, sealed before the request is handled, and is never fetched
+//! again so that the local changes overlay the remote state. A call whose local execution
+//! diverges from the remote one may still read state that was not fetched.
+
+use crate::{internal_err, public_key, remote::RemoteNode, DevState};
+use ethereum_types::{H160, H256, U256};
+use fc_rpc_core::types::Bytes;
+use futures::lock::Mutex;
+use jsonrpc_core::{
+	BoxFuture, IoHandler, Metadata, Params, RemoteProcedure, Result, RpcMethod, Value,
+};
+use log::debug;
+use pallet_evm_dev::DevCommand;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use sha3::{Digest, Keccak256};
+use sp_runtime::traits::Block as BlockT;
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+
+/// Remote state fetched so far.
+#[derive(Default)]
+struct Fetched {
+	/// Pinned remote block, resolved by the first fetch.
+	block_number: Option<U256>,
+	accounts: BTreeSet<H160>,
+	slots: BTreeSet<(H160, H256)>,
+}
+
+/// Remote state referenced by a request.
+#[derive(Debug, Default, PartialEq)]
+struct References {
+	accounts: Vec<H160>,
+	slots: Vec<(H160, H256)>,
+	/// Call whose access list gives the state it reaches.
+	call: Option<Value>,
+}
+
+impl References {
+	fn is_empty(&self) -> bool {
+		self.accounts.is_empty() && self.slots.is_empty() && self.call.is_none()
+	}
+}
+
+/// Remote chain the dev node is forked from.
+pub struct EthFork<B: BlockT> {
+	remote: RemoteNode,
+	block: Option<u64>,
+	/// Held while the state is fetched and sealed, so that the requests referencing the same
+	/// state wait for it to be written.
+	fetched: Mutex<Fetched>,
+	state: Arc<DevState<B>>,
+}
+
+impl<B: BlockT> EthFork<B> {
+	/// Forks the chain of the node at `url`, at the given block or at its latest block when
+	/// the fork is first used.
+	pub fn new(
+		url: String,
+		block: Option<u64>,
+		timeout: Duration,
+		state: Arc<DevState<B>>,
+	) -> Self {
+		Self {
+			remote: RemoteNode::new(url, timeout),
+			block,
+			fetched: Mutex::new(Fetched::default()),
+			state,
+		}
+	}
+
+	/// Number of the remote block the chain is forked at.
+	pub async fn block_number(&self) -> Result<U256> {
+		let mut fetched = self.fetched.lock().await;
+		self.pin(&mut fetched).await
+	}
+
+	async fn pin(&self, fetched: &mut Fetched) -> Result<U256> {
+		if let Some(number) = fetched.block_number {
+			return Ok(number);
+		}
+
+		let tag = match self.block {
+			Some(number) => json!(U256::from(number)),
+			None => json!("latest"),
+		};
+		let block = self
+			.remote
+			.request("eth_getBlockByNumber", json!([tag, false]))
+			.await?;
+		if block.is_null() {
+			return Err(internal_err(format!("unknown block {}", tag)));
+		}
+		let number = parse(&block["number"], "block number")?;
+		fetched.block_number = Some(number);
+		Ok(number)
+	}
+
+	/// Fetches the referenced state not fetched yet, and seals it in a new block.
+	async fn fetch(&self, references: References) -> Result<()> {
+		let mut fetched = self.fetched.lock().await;
+		let tag = json!(self.pin(&mut fetched).await?);
+
+		let References {
+			mut accounts,
+			mut slots,
+			call,
+		} = references;
+		if let Some(call) = call {
+			match self
+				.remote
+				.request("eth_createAccessList", json!([call, tag]))
+				.await
+			{
+				Ok(access_list) => {
+					for (address, keys) in access_list_entries(&access_list)? {
+						accounts.push(address);
+						slots.extend(keys.into_iter().map(|key| (address, key)));
+					}
+				}
+				Err(err) => debug!(
+					target: "eth-fork",
+					"No access list, only the accounts of the call are fetched: {}",
+					err.message,
+				),
+			}
+		}
+		accounts.sort();
+		accounts.dedup();
+		accounts.retain(|address| !fetched.accounts.contains(address));
+		slots.sort();
+		slots.dedup();
+		slots.retain(|slot| !fetched.slots.contains(slot));
+
+		let mut commands = Vec::new();
+		for address in &accounts {
+			commands.extend(self.fetch_account(*address, &tag).await?);
+		}
+		for (address, index) in &slots {
+			let value: H256 = parse(
+				&self
+					.remote
+					.request("eth_getStorageAt", json!([address, index, tag]))
+					.await?,
+				"storage value",
+			)?;
+			if !value.is_zero() {
+				commands.push(DevCommand::SetStorage(*address, *index, value));
+			}
+		}
+
+		if !commands.is_empty() {
+			self.state.queue_commands(commands);
+			self.state.seal().await?;
+		}
+		fetched.accounts.extend(accounts);
+		fetched.slots.extend(slots);
+		Ok(())
+	}
+
+	/// The accounts empty on the remote chain keep their local state, e.g. the dev accounts.
+	async fn fetch_account(&self, address: H160, tag: &Value) -> Result<Vec<DevCommand>> {
+		let (balance, nonce, code) = futures::try_join!(
+			self.remote.request("eth_getBalance", json!([address, tag])),
+			self.remote
+				.request("eth_getTransactionCount", json!([address, tag])),
+			self.remote.request("eth_getCode", json!([address, tag])),
+		)?;
+		let balance: U256 = parse(&balance, "balance")?;
+		let nonce: U256 = parse(&nonce, "nonce")?;
+		let code: Bytes = parse(&code, "code")?;
+
+		let mut commands = Vec::new();
+		if !balance.is_zero() {
+			commands.push(DevCommand::SetBalance(address, balance));
+		}
+		if !nonce.is_zero() {
+			commands.push(DevCommand::SetNonce(address, nonce));
+		}
+		if !code.0.is_empty() {
+			commands.push(DevCommand::SetCode(address, code.into_vec()));
+		}
+		Ok(commands)
+	}
+
+	/// Wraps the methods of `io` so that the state they reference is fetched first.
+	pub fn apply<M>(self: Arc<Self>, io: IoHandler<M>) -> IoHandler<M>
+	where
+		M: Metadata + Default,
+		B: 'static,
+	{
+		let mut forked = IoHandler::default();
+		forked.extend_with(io.iter().map(|(name, procedure)| {
+			let procedure = match procedure {
+				RemoteProcedure::Method(method) => RemoteProcedure::Method(Arc::new(ForkedMethod {
+					name: name.clone(),
+					method: method.clone(),
+					fork: self.clone(),
+				})
+					as Arc<dyn RpcMethod<M>>),
+				other => other.clone(),
+			};
+			(name.clone(), procedure)
+		}));
+		forked
+	}
+}
+
+struct ForkedMethod<M: Metadata, B: BlockT> {
+	name: String,
+	method: Arc<dyn RpcMethod<M>>,
+	fork: Arc<EthFork<B>>,
+}
+
+impl<M: Metadata, B: BlockT + 'static> RpcMethod<M> for ForkedMethod<M, B> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Result<Value>> {
+		let references = referenced_state(&self.name, &params);
+		if references.is_empty() {
+			return self.method.call(params, meta);
+		}
+
+		let fork = self.fork.clone();
+		let method = self.method.clone();
+		Box::pin(async move {
+			fork.fetch(references).await.map_err(|err| {
+				internal_err(format!("fetching the forked state failed: {}", err.message))
+			})?;
+			method.call(params, meta).await
+		})
+	}
+}
+
+fn parse<T: DeserializeOwned>(value: &Value, name: &str) -> Result<T> {
+	serde_json::from_value(value.clone())
+		.map_err(|err| internal_err(format!("invalid remote {}: {}", name, err)))
+}
+
+/// Accounts and storage keys of an `eth_createAccessList` result.
+fn access_list_entries(result: &Value) -> Result<Vec<(H160, Vec<H256>)>> {
+	result["accessList"]
+		.as_array()
+		.ok_or_else(|| internal_err("missing remote access list"))?
+		.iter()
+		.map(|entry| {
+			Ok((
+				parse(&entry["address"], "access list address")?,
+				parse(&entry["storageKeys"], "access list storage keys")?,
+			))
+		})
+		.collect()
+}
+
+/// Remote state read or written by a request.
+fn referenced_state(method: &str, params: &Params) -> References {
+	let params = match params {
+		Params::Array(params) if !params.is_empty() => params,
+		_ => return References::default(),
+	};
+	let address = |value: &Value| serde_json::from_value::<H160>(value.clone()).ok();
+
+	match method {
+		"eth_getBalance" | "eth_getCode" | "eth_getTransactionCount" => References {
+			accounts: address(&params[0]).into_iter().collect(),
+			..Default::default()
+		},
+		"eth_getStorageAt" => {
+			let address = match address(&params[0]) {
+				Some(address) => address,
+				None => return References::default(),
+			};
+			let index = params
+				.get(1)
+				.and_then(|index| serde_json::from_value::<U256>(index.clone()).ok());
+			References {
+				accounts: vec![address],
+				slots: index
+					.map(|index| {
+						let mut key = [0u8; 32];
+						index.to_big_endian(&mut key);
+						(address, H256::from(key))
+					})
+					.into_iter()
+					.collect(),
+				call: None,
+			}
+		}
+		"eth_call" | "eth_estimateGas" | "eth_createAccessList" | "eth_sendTransaction" => {
+			if !params[0].is_object() {
+				return References::default();
+			}
+			References {
+				accounts: ["from", "to"]
+					.iter()
+					.filter_map(|field| address(&params[0][*field]))
+					.collect(),
+				slots: Vec::new(),
+				call: Some(params[0].clone()),
+			}
+		}
+		"eth_sendRawTransaction" => {
+			let transaction = match serde_json::from_value::<Bytes>(params[0].clone())
+				.ok()
+				.and_then(|bytes| rlp::decode::<ethereum::TransactionV0>(&bytes.0).ok())
+			{
+				Some(transaction) => transaction,
+				None => return References::default(),
+			};
+			let from = public_key(&transaction)
+				.ok()
+				.map(|public| H160::from(H256::from_slice(Keccak256::digest(&public).as_slice())));
+			let to = match transaction.action {
+				ethereum::TransactionAction::Call(to) => Some(to),
+				ethereum::TransactionAction::Create => None,
+			};
+			References {
+				accounts: from.into_iter().chain(to).collect(),
+				slots: Vec::new(),
+				call: Some(json!({
+					"from": from,
+					"to": to,
+					"gas": transaction.gas_limit,
+					"gasPrice": transaction.gas_price,
+					"value": transaction.value,
+					"data": Bytes(transaction.input),
+				})),
+			}
+		}
+		_ => References::default(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{secret_key_address, sign_with_keys};
+
+	fn references(method: &str, params: Value) -> References {
+		referenced_state(method, &serde_json::from_value(params).unwrap())
+	}
+
+	#[test]
+	fn account_parameters_are_referenced() {
+		let address = H160::repeat_byte(0x11);
+		for method in &["eth_getBalance", "eth_getCode", "eth_getTransactionCount"] {
+			assert_eq!(
+				references(method, json!([address, "latest"])),
+				References {
+					accounts: vec![address],
+					..Default::default()
+				}
+			);
+		}
+		assert_eq!(
+			references("eth_getStorageAt", json!([address, "0x2a", "latest"])),
+			References {
+				accounts: vec![address],
+				slots: vec![(address, H256::from_low_u64_be(0x2a))],
+				call: None,
+			}
+		);
+		assert!(references("eth_getBalance", json!(["0x1234", "latest"])).is_empty());
+		assert!(references("eth_blockNumber", json!([])).is_empty());
+	}
+
+	#[test]
+	fn calls_reference_their_accounts_and_access_list() {
+		let (from, to) = (H160::repeat_byte(0x11), H160::repeat_byte(0x22));
+		let call = json!({ "from": from, "to": to, "data": "0x" });
+		for method in &[
+			"eth_call",
+			"eth_estimateGas",
+			"eth_createAccessList",
+			"eth_sendTransaction",
+		] {
+			assert_eq!(
+				references(method, json!([call, "latest"])),
+				References {
+					accounts: vec![from, to],
+					slots: Vec::new(),
+					call: Some(call.clone()),
+				}
+			);
+		}
+
+		let create = json!({ "from": from, "data": "0x00" });
+		assert_eq!(references("eth_call", json!([create])).accounts, vec![from]);
+	}
+
+	#[test]
+	fn raw_transactions_reference_their_sender_and_recipient() {
+		let key = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+		let from = secret_key_address(&key);
+		let to = H160::repeat_byte(0x22);
+		let transaction = sign_with_keys(
+			&[key],
+			ethereum::LegacyTransactionMessage {
+				nonce: U256::zero(),
+				gas_price: U256::from(1),
+				gas_limit: U256::from(21000),
+				action: ethereum::TransactionAction::Call(to),
+				value: U256::from(5),
+				input: vec![1, 2],
+				chain_id: Some(42),
+			},
+			&from,
+		)
+		.unwrap();
+
+		let references = references(
+			"eth_sendRawTransaction",
+			json!([Bytes(rlp::encode(&transaction).to_vec())]),
+		);
+		assert_eq!(references.accounts, vec![from, to]);
+		assert_eq!(
+			references.call,
+			Some(json!({
+				"from": from,
+				"to": to,
+				"gas": U256::from(21000),
+				"gasPrice": U256::from(1),
+				"value": U256::from(5),
+				"data": "0x0102",
+			}))
+		);
+		assert!(referenced_state(
+			"eth_sendRawTransaction",
+			&Params::Array(vec![json!("0x1234")])
+		)
+		.is_empty());
+	}
+
+	#[test]
+	fn access_list_entries_are_parsed() {
+		let (address, key) = (H160::repeat_byte(0x33), H256::repeat_byte(0x44));
+		assert_eq!(
+			access_list_entries(&json!({
+				"accessList": [{ "address": address, "storageKeys": [key] }],
+				"gasUsed": "0x5208",
+			}))
+			.unwrap(),
+			vec![(address, vec![key])]
+		);
+		assert!(access_list_entries(&json!({})).is_err());
+	}
+}
@@ -0,0 +1,1041 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	ensure_intrinsic_gas, eth::filter_logs_page, frontier_backend_client, internal_err, public_key,
+	restore_receipt_logs, runtime_evm_config, EthBlockDataCache, OverrideHandle, ResponseCache,
+	SlowQueryTracer,
+};
+use ethereum_types::{H160, H256, U256};
+use fc_db::DatabaseSettingsSrc;
+use fc_rpc_core::{
+	types::{
+		AccountSummary, BackendInfo, BlockNumber, BlockOrAddress, Bytes, CacheSizes,
+		ContractCreation, FeeHistoryCache, Filter, FilterPool, FilterPoolStats, FilterType,
+		FrontierVersion, Health, HistorySample, InclusionProof, InternalTransaction, LogCursor,
+		LogIndexInfo, LogsPage, MappingSyncHead, PendingRemoval, RawTransactionValidation,
+		ResponseCacheStats, SchemaCacheEntry, SlowQueryStats, TokenTransfer, TokenTransferCursor,
+		TokenTransferFilter, TokenTransfersPage, VariadicValue,
+	},
+	FrontierApi as FrontierApiT,
+};
+use fp_rpc::EthereumRuntimeRPCApi;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use sha3::{Digest, Keccak256};
+use sp_api::{BlockId, Core, HeaderT, ProvideRuntimeApi};
+use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, NumberFor, UniqueSaturatedInto, Zero};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Maximum number of samples of an account history.
+const MAX_HISTORY_SAMPLES: u64 = 1000;
+
+/// Maximum number of accounts of a `frontier_getAccounts` request.
+const MAX_ACCOUNTS: usize = 1000;
+
+pub use fc_rpc_core::FrontierApiServer;
+
+/// Versions of the node, of the runtime at the best block and of the EVM hard fork it follows.
+pub(crate) fn version_info<B, C>(client: &C) -> Result<FrontierVersion>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+{
+	let id = BlockId::Hash(client.info().best_hash);
+	let version = client
+		.runtime_api()
+		.version(&id)
+		.map_err(|err| internal_err(format!("fetch runtime version failed: {:?}", err)))?;
+	let runtime_api_version =
+		frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, id)?;
+	let evm_fork = frontier_backend_client::evm_fork::<B, C>(client, id)?;
+	let london_block = frontier_backend_client::london_block::<B, C>(client, id)?;
+
+	Ok(FrontierVersion {
+		client: env!("CARGO_PKG_NAME").to_string(),
+		client_version: env!("CARGO_PKG_VERSION").to_string(),
+		spec_name: version.spec_name.to_string(),
+		spec_version: version.spec_version,
+		impl_version: version.impl_version,
+		runtime_api_version,
+		evm_fork,
+		london_block,
+	})
+}
+
+/// Latest mapped best chain block and its distance to the best block, walking back at most
+/// `max_sync_lag` blocks.
+pub(crate) fn latest_mapped_block<B, C>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	max_sync_lag: u64,
+) -> Result<Option<(u64, H256)>>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+{
+	let mut hash = client.info().best_hash;
+	for lag in 0..=max_sync_lag {
+		if backend
+			.mapping()
+			.is_synced(&hash)
+			.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?
+		{
+			return Ok(Some((lag, hash)));
+		}
+
+		match client
+			.header(BlockId::Hash(hash))
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+		{
+			Some(header) if !header.number().is_zero() => hash = *header.parent_hash(),
+			_ => break,
+		}
+	}
+
+	Ok(None)
+}
+
+pub struct FrontierApi<B: BlockT, C, BE> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	max_sync_lag: u64,
+	filter_pool: Option<FilterPool>,
+	block_data_cache: Arc<EthBlockDataCache<B>>,
+	fee_history_cache: FeeHistoryCache,
+	slow_query_tracer: Option<Arc<SlowQueryTracer>>,
+	response_cache: Option<Arc<ResponseCache>>,
+	overrides: Arc<OverrideHandle<B>>,
+	max_past_logs: u32,
+	history_window: Option<u64>,
+	_marker: PhantomData<(B, BE)>,
+}
+
+impl<B: BlockT, C, BE> FrontierApi<B, C, BE> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
+		max_sync_lag: u64,
+		filter_pool: Option<FilterPool>,
+		block_data_cache: Arc<EthBlockDataCache<B>>,
+		fee_history_cache: FeeHistoryCache,
+		slow_query_tracer: Option<Arc<SlowQueryTracer>>,
+		response_cache: Option<Arc<ResponseCache>>,
+		overrides: Arc<OverrideHandle<B>>,
+		max_past_logs: u32,
+		history_window: Option<u64>,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			max_sync_lag,
+			filter_pool,
+			block_data_cache,
+			fee_history_cache,
+			slow_query_tracer,
+			response_cache,
+			overrides,
+			max_past_logs,
+			history_window,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C, BE> FrontierApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+{
+	/// Best block, if its runtime exposes the address mapping of `frontier_addressToAccount`
+	/// and `frontier_accountToAddress`.
+	fn address_mapping_block(&self) -> Result<BlockId<B>> {
+		let id = BlockId::Hash(self.client.info().best_hash);
+		match frontier_backend_client::ethereum_runtime_api_version::<B, C>(
+			self.client.as_ref(),
+			id,
+		)? {
+			Some(version) if version >= 16 => Ok(id),
+			_ => Err(internal_err(
+				"the runtime does not expose its address mapping",
+			)),
+		}
+	}
+
+	/// Number of best chain blocks not yet mapped, walking back at most `max_sync_lag` blocks.
+	fn mapping_sync_lag(&self) -> Result<Option<u64>> {
+		Ok(self.latest_mapped_block()?.map(|(lag, _)| lag))
+	}
+
+	/// Runs the checks of `frontier_validateRawTransaction` on a decoded transaction, filling
+	/// `validation` as they pass. The error is the reason of the first failed check.
+	fn check_raw_transaction(
+		&self,
+		transaction: &ethereum::TransactionV0,
+		validation: &mut RawTransactionValidation,
+	) -> Result<std::result::Result<(), String>> {
+		let id = BlockId::Hash(self.client.info().best_hash);
+		let api = self.client.runtime_api();
+
+		let sender = match public_key(transaction) {
+			Ok(pk) => H160::from(H256::from_slice(Keccak256::digest(&pk).as_slice())),
+			Err(_) => return Ok(Err("invalid sender".to_string())),
+		};
+		validation.from = Some(sender);
+
+		let chain_id = api
+			.chain_id(&id)
+			.map_err(|err| internal_err(format!("fetch runtime chain id failed: {:?}", err)))?;
+		if let Some(transaction_chain_id) = transaction.signature.chain_id() {
+			if transaction_chain_id != chain_id {
+				return Ok(Err("invalid chain id for signer".to_string()));
+			}
+		}
+
+		let is_create = matches!(transaction.action, ethereum::TransactionAction::Create);
+		let config = runtime_evm_config::<B, C>(&*self.client, id)?;
+		match ensure_intrinsic_gas(
+			&config,
+			transaction.gas_limit,
+			is_create,
+			&transaction.input,
+			0,
+		) {
+			Ok(intrinsic_gas) => validation.intrinsic_gas = Some(U256::from(intrinsic_gas)),
+			Err(err) => return Ok(Err(err.message)),
+		}
+
+		let block_gas_limit = api
+			.current_block(&id)
+			.map_err(|err| internal_err(format!("fetch runtime block failed: {:?}", err)))?
+			.map(|block| block.header.gas_limit);
+		if let Some(block_gas_limit) = block_gas_limit {
+			if transaction.gas_limit > block_gas_limit {
+				return Ok(Err("exceeds block gas limit".to_string()));
+			}
+		}
+
+		let min_gas_price = api
+			.gas_price(&id)
+			.map_err(|err| internal_err(format!("fetch runtime gas price failed: {:?}", err)))?;
+		if transaction.gas_price < min_gas_price {
+			return Ok(Err("transaction underpriced".to_string()));
+		}
+
+		let account = api.account_basic(&id, sender).map_err(|err| {
+			internal_err(format!("fetch runtime account basic failed: {:?}", err))
+		})?;
+		validation.account_nonce = Some(account.nonce);
+		if transaction.nonce < account.nonce {
+			return Ok(Err("nonce too low".to_string()));
+		}
+		validation.queued = transaction.nonce > account.nonce;
+
+		let cost = transaction
+			.gas_price
+			.saturating_mul(transaction.gas_limit)
+			.saturating_add(transaction.value);
+		if account.balance < cost {
+			return Ok(Err("insufficient funds for gas * price + value".to_string()));
+		}
+
+		Ok(Ok(()))
+	}
+
+	/// Latest mapped best chain block and its distance to the best block, walking back at most
+	/// `max_sync_lag` blocks.
+	fn latest_mapped_block(&self) -> Result<Option<(u64, H256)>> {
+		latest_mapped_block::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			self.max_sync_lag,
+		)
+	}
+}
+
+impl<B, C, BE> FrontierApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + Send + Sync + 'static,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	/// Proof of the inclusion of the transaction `hash`, or of its receipt, in the trie of its
+	/// block. The root of the trie built from the stored block is checked against its header.
+	fn inclusion_proof(&self, hash: H256, receipt: bool) -> Result<Option<InclusionProof>> {
+		let (block_hash, index) = match frontier_backend_client::load_transactions::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			hash,
+			true,
+		)? {
+			Some((block_hash, index)) => (block_hash, index as usize),
+			None => return Ok(None),
+		};
+		let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), block_hash)? {
+			Some(id) => id,
+			None => return Ok(None),
+		};
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			id,
+			self.history_window,
+		)?;
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {}", id)))?;
+
+		let schema =
+			frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
+		let handler = self
+			.overrides
+			.schemas
+			.get(&schema)
+			.unwrap_or(&self.overrides.fallback);
+		let block = match self.block_data_cache.current_block(handler, substrate_hash) {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+
+		let (expected_root, items) = if receipt {
+			let mut receipts = match handler.current_receipts(&id) {
+				Some(receipts) => receipts,
+				None => return Ok(None),
+			};
+			// The receipts root commits to the logs, stored in the statuses in compact receipts
+			// mode.
+			if let Some(statuses) = self
+				.block_data_cache
+				.current_transaction_statuses(handler, substrate_hash)
+			{
+				restore_receipt_logs(&mut receipts, &statuses);
+			}
+			(
+				block.header.receipts_root,
+				receipts
+					.iter()
+					.map(|receipt| rlp::encode(receipt).to_vec())
+					.collect::<Vec<_>>(),
+			)
+		} else {
+			(
+				block.header.transactions_root,
+				block
+					.transactions
+					.iter()
+					.map(|transaction| rlp::encode(transaction).to_vec())
+					.collect::<Vec<_>>(),
+			)
+		};
+		if index >= items.len() {
+			return Ok(None);
+		}
+
+		let (root, proof) = ordered_trie_proof(&items, index);
+		if root != expected_root {
+			return Err(internal_err(format!(
+				"rebuilt trie root {:?} does not match the block root {:?}",
+				root, expected_root
+			)));
+		}
+
+		Ok(Some(InclusionProof {
+			block_hash: H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice()),
+			block_number: block.header.number,
+			transaction_index: U256::from(index),
+			root,
+			key: Bytes(rlp::encode(&index).to_vec()),
+			value: Bytes(items[index].clone()),
+			proof: proof.into_iter().map(Bytes).collect(),
+		}))
+	}
+
+	/// Number of the block `number`, which must be known.
+	fn block_number(&self, number: BlockNumber) -> Result<u64> {
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number),
+		)?
+		.ok_or_else(|| internal_err("block not found"))?;
+		let number = self
+			.client
+			.block_number_from_id(&id)
+			.map_err(|err| internal_err(format!("fetch block number failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("block not found"))?;
+		Ok(number.unique_saturated_into())
+	}
+
+	/// Balance and nonce of `address` at the best chain blocks `from_block` to `to_block`,
+	/// every `step` blocks and at `to_block`, read through a single runtime API instance.
+	fn account_history(
+		&self,
+		address: H160,
+		from_block: BlockNumber,
+		to_block: BlockNumber,
+		step: U256,
+	) -> Result<Vec<(u64, fp_evm::Account)>> {
+		let from = self.block_number(from_block)?;
+		let to = self.block_number(to_block)?;
+		if step.is_zero() || step > U256::from(u64::MAX) {
+			return Err(internal_err("the step must be between 1 and 2^64 - 1"));
+		}
+		let step = step.low_u64();
+		if from > to {
+			return Ok(Vec::new());
+		}
+		let samples = (to - from) / step + 1 + ((to - from) % step != 0) as u64;
+		if samples > MAX_HISTORY_SAMPLES {
+			return Err(internal_err(format!(
+				"{} samples requested, the maximum is {}",
+				samples, MAX_HISTORY_SAMPLES
+			)));
+		}
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			BlockId::Number(from.unique_saturated_into()),
+			self.history_window,
+		)?;
+
+		let api = self.client.runtime_api();
+		let mut numbers: Vec<u64> = (0..samples - 1).map(|i| from + i * step).collect();
+		numbers.push(to);
+		numbers
+			.into_iter()
+			.map(|number| {
+				let hash = self
+					.client
+					.hash(number.unique_saturated_into())
+					.map_err(|err| internal_err(format!("fetch block hash failed: {:?}", err)))?
+					.ok_or_else(|| internal_err(format!("block {} not found", number)))?;
+				let account = api
+					.account_basic(&BlockId::Hash(hash), address)
+					.map_err(|err| {
+						internal_err(format!("fetch runtime account basic failed: {:?}", err))
+					})?;
+				Ok((number, account))
+			})
+			.collect()
+	}
+}
+
+impl<B, C, BE> FrontierApiT for FrontierApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + Send + Sync + 'static,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	fn health(&self) -> Result<Health> {
+		let best_block: u64 = self.client.info().best_number.unique_saturated_into();
+		let mapping_sync_lag = self.mapping_sync_lag()?;
+
+		Ok(Health {
+			best_block: U256::from(best_block),
+			mapping_sync_lag: mapping_sync_lag.map(U256::from),
+			is_ready: mapping_sync_lag.is_some(),
+		})
+	}
+
+	fn ready(&self) -> Result<bool> {
+		match self.mapping_sync_lag()? {
+			Some(_) => Ok(true),
+			None => Err(Error {
+				code: ErrorCode::ServerError(-32000),
+				message: format!(
+					"mapping sync is more than {} blocks behind the best block",
+					self.max_sync_lag
+				),
+				data: None,
+			}),
+		}
+	}
+
+	fn backend(&self) -> Result<BackendInfo> {
+		let log_index = match self.backend.log_index() {
+			Some(log_index) => {
+				let status = log_index.status().map_err(internal_err)?;
+				Some(LogIndexInfo {
+					indexed_blocks: U256::from(status.indexed_blocks),
+					orphaned_blocks: U256::from(status.orphaned_blocks),
+					lowest_block: status.lowest_block.map(U256::from),
+					highest_block: status.highest_block.map(U256::from),
+				})
+			}
+			None => None,
+		};
+		Ok(match self.backend.source() {
+			DatabaseSettingsSrc::RocksDb { path, .. } => BackendInfo {
+				kind: "rocksdb".to_string(),
+				path: Some(path.display().to_string()),
+				log_index,
+			},
+		})
+	}
+
+	fn schema_cache(&self) -> Result<Vec<SchemaCacheEntry>> {
+		let cache = frontier_backend_client::load_cached_schema::<B>(self.backend.as_ref())?
+			.unwrap_or_default();
+
+		let mut entries = cache
+			.into_iter()
+			.map(|(schema, hash)| {
+				let header = self
+					.client
+					.header(BlockId::Hash(hash))
+					.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?;
+				Ok(SchemaCacheEntry {
+					schema: format!("{:?}", schema),
+					from_block_hash: hash,
+					from_block_number: header.map(|header| {
+						U256::from(UniqueSaturatedInto::<u64>::unique_saturated_into(
+							*header.number(),
+						))
+					}),
+					to_block_number: None,
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		// Each schema is used until the block before the first block of the next one.
+		for i in 1..entries.len() {
+			entries[i - 1].to_block_number = entries[i]
+				.from_block_number
+				.map(|number| number.saturating_sub(U256::one()));
+		}
+		Ok(entries)
+	}
+
+	fn mapping_sync_head(&self) -> Result<MappingSyncHead> {
+		let best_block: u64 = self.client.info().best_number.unique_saturated_into();
+		let latest_mapped_block = self.latest_mapped_block()?;
+		let syncing_tips = self
+			.backend
+			.meta()
+			.current_syncing_tips()
+			.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?;
+
+		Ok(MappingSyncHead {
+			hash: latest_mapped_block.map(|(_, hash)| hash),
+			number: latest_mapped_block.map(|(lag, _)| U256::from(best_block - lag)),
+			syncing_tips,
+		})
+	}
+
+	fn filter_pool(&self) -> Result<FilterPoolStats> {
+		let mut stats = FilterPoolStats {
+			log: 0,
+			block: 0,
+			pending_transaction: 0,
+			oldest_at_block: None,
+		};
+
+		if let Some(filter_pool) = &self.filter_pool {
+			let pool = filter_pool
+				.lock()
+				.map_err(|err| internal_err(format!("filter pool lock failed: {:?}", err)))?;
+			for item in pool.values() {
+				match item.filter_type {
+					FilterType::Log(_) => stats.log += 1,
+					FilterType::Block => stats.block += 1,
+					FilterType::PendingTransaction => stats.pending_transaction += 1,
+				}
+			}
+			stats.oldest_at_block = pool
+				.values()
+				.map(|item| item.at_block)
+				.min()
+				.map(U256::from);
+		}
+
+		Ok(stats)
+	}
+
+	fn cache_sizes(&self) -> Result<CacheSizes> {
+		let fee_history = self
+			.fee_history_cache
+			.lock()
+			.map_err(|err| internal_err(format!("fee history cache lock failed: {:?}", err)))?
+			.len();
+
+		Ok(CacheSizes {
+			blocks: self.block_data_cache.blocks_len(),
+			transaction_statuses: self.block_data_cache.statuses_len(),
+			fee_history,
+		})
+	}
+
+	fn slow_queries(&self) -> Result<Vec<SlowQueryStats>> {
+		Ok(self
+			.slow_query_tracer
+			.as_ref()
+			.map(|tracer| tracer.stats())
+			.unwrap_or_default())
+	}
+
+	fn response_cache(&self) -> Result<Vec<ResponseCacheStats>> {
+		Ok(self
+			.response_cache
+			.as_ref()
+			.map(|cache| cache.stats())
+			.unwrap_or_default())
+	}
+
+	fn address_to_account(&self, address: H160) -> Result<H256> {
+		let id = self.address_mapping_block()?;
+		let account = self
+			.client
+			.runtime_api()
+			.address_to_account(&id, address)
+			.map_err(|err| internal_err(format!("fetch runtime account id failed: {:?}", err)))?;
+		// The RPC serves the chains with 32 bytes account ids.
+		if account.len() != 32 {
+			return Err(internal_err(format!(
+				"account ids of {} bytes are not supported",
+				account.len()
+			)));
+		}
+		Ok(H256::from_slice(&account))
+	}
+
+	fn account_to_address(&self, account: H256) -> Result<H160> {
+		let id = self.address_mapping_block()?;
+		self.client
+			.runtime_api()
+			.account_to_address(&id, account.as_bytes().to_vec())
+			.map_err(|err| internal_err(format!("fetch runtime address failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("the account has no EVM address"))
+	}
+
+	fn pending_removals(&self) -> Result<Vec<PendingRemoval>> {
+		let id = BlockId::Hash(self.client.info().best_hash);
+		// The runtimes predating the API remove the storage of self-destructed contracts at once.
+		match frontier_backend_client::ethereum_runtime_api_version::<B, C>(
+			self.client.as_ref(),
+			id,
+		)? {
+			Some(version) if version >= 2 => {}
+			_ => return Ok(Vec::new()),
+		}
+
+		let removals = self
+			.client
+			.runtime_api()
+			.pending_removals(&id)
+			.map_err(|err| {
+				internal_err(format!("fetch runtime pending removals failed: {:?}", err))
+			})?;
+		Ok(removals
+			.into_iter()
+			.map(|(address, remaining_keys)| PendingRemoval {
+				address,
+				remaining_keys,
+			})
+			.collect())
+	}
+
+	fn version(&self) -> Result<FrontierVersion> {
+		version_info::<B, C>(self.client.as_ref())
+	}
+
+	fn logs(
+		&self,
+		filter: Filter,
+		cursor: Option<LogCursor>,
+		limit: Option<u32>,
+	) -> Result<LogsPage> {
+		let best_number = self.client.info().best_number;
+		let (from_number, to_number) = if let Some(hash) = filter.block_hash {
+			let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), hash)? {
+				Some(id) => id,
+				None => {
+					return Ok(LogsPage {
+						logs: Vec::new(),
+						next: None,
+					})
+				}
+			};
+			let number = self
+				.client
+				.block_number_from_id(&id)
+				.map_err(|err| internal_err(format!("fetch block number failed: {:?}", err)))?
+				.ok_or_else(|| internal_err("block not found"))?;
+			(number, number)
+		} else {
+			let to_number: NumberFor<B> = filter
+				.to_block
+				.clone()
+				.and_then(|v| v.to_min_block_num())
+				.map(|s| s.unique_saturated_into())
+				.unwrap_or(best_number);
+			let from_number: NumberFor<B> = filter
+				.from_block
+				.clone()
+				.and_then(|v| v.to_min_block_num())
+				.map(|s| s.unique_saturated_into())
+				.unwrap_or(best_number);
+			(from_number, to_number.min(best_number))
+		};
+
+		let (from_number, start_log_index) = match cursor {
+			Some(cursor) => {
+				let number: NumberFor<B> = cursor.block_number.low_u64().unique_saturated_into();
+				if number > from_number {
+					(number, cursor.log_index.low_u32())
+				} else {
+					(from_number, 0)
+				}
+			}
+			None => (from_number, 0),
+		};
+		let limit = limit.unwrap_or(self.max_past_logs).min(self.max_past_logs);
+		if limit == 0 {
+			return Err(internal_err("the limit must be at least 1"));
+		}
+		if from_number > to_number {
+			return Ok(LogsPage {
+				logs: Vec::new(),
+				next: None,
+			});
+		}
+
+		frontier_backend_client::ensure_history_available::<B, C, BE>(
+			self.client.as_ref(),
+			BlockId::Number(from_number),
+			self.history_window,
+		)?;
+
+		let (logs, next) = filter_logs_page(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			&self.overrides,
+			&self.block_data_cache,
+			&filter,
+			from_number,
+			to_number,
+			start_log_index,
+			limit as usize,
+		)?;
+
+		Ok(LogsPage {
+			logs,
+			next: next.map(|(number, log_index)| LogCursor {
+				block_number: U256::from(UniqueSaturatedInto::<u64>::unique_saturated_into(number)),
+				log_index: U256::from(log_index),
+			}),
+		})
+	}
+
+	fn validate_raw_transaction(&self, bytes: Bytes) -> Result<RawTransactionValidation> {
+		let mut validation = RawTransactionValidation {
+			hash: H256::from_slice(Keccak256::digest(&bytes.0).as_slice()),
+			from: None,
+			nonce: None,
+			account_nonce: None,
+			queued: false,
+			intrinsic_gas: None,
+			valid: false,
+			error: None,
+		};
+
+		let outcome = match bytes.0.first() {
+			// EIP-2718 typed transactions start with their type, below the RLP list prefixes.
+			Some(&transaction_type) if transaction_type <= 0x7f => {
+				Err("transaction type not supported".to_string())
+			}
+			_ => match rlp::decode::<ethereum::TransactionV0>(&bytes.0[..]) {
+				Ok(transaction) => {
+					validation.hash =
+						H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+					validation.nonce = Some(transaction.nonce);
+					self.check_raw_transaction(&transaction, &mut validation)?
+				}
+				Err(_) => Err("decode transaction failed".to_string()),
+			},
+		};
+
+		match outcome {
+			Ok(()) => validation.valid = true,
+			Err(error) => validation.error = Some(error),
+		}
+		Ok(validation)
+	}
+
+	fn transaction_proof(&self, hash: H256) -> Result<Option<InclusionProof>> {
+		self.inclusion_proof(hash, false)
+	}
+
+	fn receipt_proof(&self, hash: H256) -> Result<Option<InclusionProof>> {
+		self.inclusion_proof(hash, true)
+	}
+
+	fn balance_history(
+		&self,
+		address: H160,
+		from_block: BlockNumber,
+		to_block: BlockNumber,
+		step: U256,
+	) -> Result<Vec<HistorySample>> {
+		Ok(self
+			.account_history(address, from_block, to_block, step)?
+			.into_iter()
+			.map(|(number, account)| HistorySample {
+				block_number: U256::from(number),
+				value: account.balance,
+			})
+			.collect())
+	}
+
+	fn nonce_history(
+		&self,
+		address: H160,
+		from_block: BlockNumber,
+		to_block: BlockNumber,
+		step: U256,
+	) -> Result<Vec<HistorySample>> {
+		Ok(self
+			.account_history(address, from_block, to_block, step)?
+			.into_iter()
+			.map(|(number, account)| HistorySample {
+				block_number: U256::from(number),
+				value: account.nonce,
+			})
+			.collect())
+	}
+
+	fn token_transfers(
+		&self,
+		filter: TokenTransferFilter,
+		cursor: Option<TokenTransferCursor>,
+		limit: Option<u32>,
+	) -> Result<TokenTransfersPage> {
+		let log_index = self
+			.backend
+			.log_index()
+			.ok_or_else(|| internal_err("the token transfer index requires the SQL log index"))?;
+		let from = match filter.from_block {
+			Some(from_block) => self.block_number(from_block)?,
+			None => 0,
+		};
+		let to = match filter.to_block {
+			Some(to_block) => self.block_number(to_block)?,
+			None => self.client.info().best_number.unique_saturated_into(),
+		};
+		let limit = limit.unwrap_or(self.max_past_logs).min(self.max_past_logs) as usize;
+		if limit == 0 {
+			return Err(internal_err("the limit must be at least 1"));
+		}
+		let query = fc_db::TokenTransferQuery {
+			address: filter.address,
+			tokens: match filter.token {
+				Some(VariadicValue::Single(token)) => vec![token],
+				Some(VariadicValue::Multiple(tokens)) => tokens,
+				_ => Vec::new(),
+			},
+		};
+		let start = cursor.map(|cursor| {
+			(
+				cursor.block_number.low_u64(),
+				cursor.log_index.low_u32(),
+				cursor.batch_index.low_u32(),
+			)
+		});
+
+		// One more transfer than the page holds tells where the next page starts.
+		let mut transfers = log_index
+			.token_transfers(&query, from, to, start, limit + 1)
+			.map_err(internal_err)?;
+		let next = if transfers.len() > limit {
+			transfers.pop().map(|next| TokenTransferCursor {
+				block_number: U256::from(next.block_number),
+				log_index: U256::from(next.transfer.log_index),
+				batch_index: U256::from(next.transfer.batch_index),
+			})
+		} else {
+			None
+		};
+
+		Ok(TokenTransfersPage {
+			transfers: transfers
+				.into_iter()
+				.map(|transfer| {
+					let fc_db::TokenTransfer {
+						block_number,
+						ethereum_block_hash,
+						transfer,
+					} = transfer;
+					TokenTransfer {
+						block_hash: ethereum_block_hash,
+						block_number: U256::from(block_number),
+						transaction_hash: transfer.transaction_hash,
+						transaction_index: U256::from(transfer.transaction_index),
+						log_index: U256::from(transfer.log_index),
+						batch_index: U256::from(transfer.batch_index),
+						token: transfer.token,
+						standard: format!("erc{}", transfer.standard.number()),
+						from: transfer.from,
+						to: transfer.to,
+						token_id: transfer.token_id,
+						value: transfer.value,
+					}
+				})
+				.collect(),
+			next,
+		})
+	}
+
+	fn contract_creation(&self, address: H160) -> Result<Option<ContractCreation>> {
+		let log_index = self.backend.log_index().ok_or_else(|| {
+			internal_err("the contract creation index requires the SQL log index")
+		})?;
+		Ok(log_index
+			.contract_creation(address)
+			.map_err(internal_err)?
+			.map(|creation| ContractCreation {
+				address,
+				block_hash: creation.ethereum_block_hash,
+				block_number: U256::from(creation.block_number),
+				transaction_hash: creation.creation.transaction_hash,
+				transaction_index: U256::from(creation.creation.transaction_index),
+				creator: creation.creation.creator,
+				init_code_hash: creation.creation.init_code_hash,
+			}))
+	}
+
+	fn internal_transactions(
+		&self,
+		block_or_address: BlockOrAddress,
+	) -> Result<Vec<InternalTransaction>> {
+		let log_index = self.backend.log_index().ok_or_else(|| {
+			internal_err("the internal transaction index requires the SQL log index")
+		})?;
+		let (address, from, to) = match block_or_address {
+			BlockOrAddress::Address(address) => (
+				Some(address),
+				0,
+				self.client.info().best_number.unique_saturated_into(),
+			),
+			BlockOrAddress::Block(number) => {
+				let number = self.block_number(number)?;
+				(None, number, number)
+			}
+		};
+
+		let limit = self.max_past_logs as usize;
+		let transfers = log_index
+			.internal_transfers(address, from, to, limit + 1)
+			.map_err(internal_err)?;
+		if transfers.len() > limit {
+			return Err(internal_err(format!(
+				"query returned more than {} results",
+				self.max_past_logs
+			)));
+		}
+
+		Ok(transfers
+			.into_iter()
+			.map(|transfer| {
+				let fc_db::InternalTransfer {
+					block_number,
+					ethereum_block_hash,
+					transfer,
+				} = transfer;
+				InternalTransaction {
+					block_hash: ethereum_block_hash,
+					block_number: U256::from(block_number),
+					transaction_hash: transfer.transaction_hash,
+					transaction_index: U256::from(transfer.transaction_index),
+					transfer_index: U256::from(transfer.transfer_index),
+					from: transfer.from,
+					to: transfer.to,
+					value: transfer.value,
+				}
+			})
+			.collect())
+	}
+
+	fn accounts(
+		&self,
+		addresses: Vec<H160>,
+		number: Option<BlockNumber>,
+	) -> Result<Vec<AccountSummary>> {
+		if addresses.len() > MAX_ACCOUNTS {
+			return Err(internal_err(format!(
+				"{} accounts requested, the maximum is {}",
+				addresses.len(),
+				MAX_ACCOUNTS
+			)));
+		}
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+		)?
+		.ok_or_else(|| internal_err("block not found"))?;
+		frontier_backend_client::ensure_state_available::<B, C, BE>(self.client.as_ref(), id)?;
+
+		let api = self.client.runtime_api();
+		let accounts = match frontier_backend_client::ethereum_runtime_api_version::<B, C>(
+			self.client.as_ref(),
+			id,
+		)? {
+			Some(version) if version >= 15 => api
+				.accounts(&id, addresses.clone())
+				.map_err(|err| internal_err(format!("fetch runtime accounts failed: {:?}", err)))?,
+			// Older runtimes are queried account by account, and the code hashed here.
+			Some(_) => addresses
+				.iter()
+				.map(|address| {
+					let account = api.account_basic(&id, *address).map_err(|err| {
+						internal_err(format!("fetch runtime account basic failed: {:?}", err))
+					})?;
+					let code = api.account_code_at(&id, *address).map_err(|err| {
+						internal_err(format!("fetch runtime account code failed: {:?}", err))
+					})?;
+					Ok(fp_rpc::AccountSummary {
+						balance: account.balance,
+						nonce: account.nonce,
+						code_hash: H256::from_slice(Keccak256::digest(&code).as_slice()),
+						code_size: code.len() as u64,
+					})
+				})
+				.collect::<Result<Vec<_>>>()?,
+			None => return Err(internal_err("the block has no Ethereum runtime API")),
+		};
+
+		Ok(addresses
+			.into_iter()
+			.zip(accounts)
+			.map(|(address, account)| AccountSummary {
+				address,
+				balance: account.balance,
+				nonce: account.nonce,
+				code_hash: account.code_hash,
+				code_size: U256::from(account.code_size),
+			})
+			.collect())
+	}
+}
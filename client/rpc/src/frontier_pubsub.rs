@@ -0,0 +1,473 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Frontier subscriptions: `stateChanges` streams the storage, balance and nonce changes of
+//! watched addresses in the blocks of the best chain. The storage changes are taken from the
+//! storage notifications of the imported blocks, and compared with the state of their parent.
+//! `reorgs` streams the re-organisations of the best chain, translated from the tree route of the
+//! import notifications. `chainHead` streams in one subscription the new best blocks with the
+//! mapping sync lag, the re-organisations and the finalized blocks, for the load balancers
+//! ranking the nodes.
+
+use codec::Decode;
+use ethereum_types::{H160, H256, U256};
+use fc_rpc_core::{
+	types::pubsub::{
+		AccountStateChanges, ChainHead, ChainHeadEvent, FrontierKind, FrontierResult, Reorg,
+		ReorgBlock, StateChanges, StateChangesFilter, ValueChange,
+	},
+	FrontierPubSubApi as FrontierPubSubApiT,
+};
+use fp_rpc::EthereumRuntimeRPCApi;
+use futures::{FutureExt as _, SinkExt as _, StreamExt as _};
+use jsonrpc_core::Result as JsonRpcResult;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use log::warn;
+use sc_client_api::{
+	backend::{Backend, StateBackend, StorageProvider},
+	client::{BlockImportNotification, BlockchainEvents},
+	notifications::StorageChangeSet,
+};
+use sc_rpc::Metadata;
+use sha3::{Digest, Keccak256};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, Header as HeaderT};
+use sp_storage::StorageKey;
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+pub use fc_rpc_core::FrontierPubSubApiServer;
+
+use crate::{
+	frontier::latest_mapped_block,
+	frontier_backend_client, internal_err,
+	overrides::{blake2_128_extend, storage_prefix_build, OverrideHandle},
+	HexEncodedIdProvider,
+};
+
+/// Maximum number of addresses watched by a `stateChanges` subscription.
+const MAX_WATCHED_ADDRESSES: usize = 64;
+
+pub struct FrontierPubSubApi<B: BlockT, C, BE> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	max_sync_lag: u64,
+	subscriptions: SubscriptionManager<HexEncodedIdProvider>,
+	overrides: Arc<OverrideHandle<B>>,
+	chain_head_only: bool,
+	_marker: PhantomData<BE>,
+}
+
+impl<B: BlockT, C, BE> FrontierPubSubApi<B, C, BE> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
+		max_sync_lag: u64,
+		subscriptions: SubscriptionManager<HexEncodedIdProvider>,
+		overrides: Arc<OverrideHandle<B>>,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			max_sync_lag,
+			subscriptions,
+			overrides,
+			chain_head_only: false,
+			_marker: PhantomData,
+		}
+	}
+
+	/// Serves the `chainHead` subscriptions alone, rejecting the other kinds.
+	pub fn chain_head_only(self) -> Self {
+		Self {
+			chain_head_only: true,
+			..self
+		}
+	}
+}
+
+/// Computes the changes of the watched addresses in the imported blocks.
+struct StateChangesWatcher<B: BlockT, C, BE> {
+	client: Arc<C>,
+	overrides: Arc<OverrideHandle<B>>,
+	/// Watched addresses, with the prefix of their `pallet_evm::AccountStorages` keys.
+	addresses: Vec<(H160, Vec<u8>)>,
+	_marker: PhantomData<BE>,
+}
+
+impl<B, C, BE> StateChangesWatcher<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + HeaderBackend<B>,
+	C: Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	fn new(client: Arc<C>, overrides: Arc<OverrideHandle<B>>, addresses: Vec<H160>) -> Self {
+		let storage_prefix = storage_prefix_build(b"EVM", b"AccountStorages");
+		Self {
+			client,
+			overrides,
+			addresses: addresses
+				.into_iter()
+				.map(|address| {
+					let mut prefix = storage_prefix.clone();
+					prefix.extend(blake2_128_extend(address.as_bytes()));
+					(address, prefix)
+				})
+				.collect(),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Changes of the watched addresses in the block `hash`, whose storage changes are
+	/// `changes`. None if they did not change.
+	fn changes(&self, hash: H256, changes: &StorageChangeSet) -> Option<StateChanges> {
+		let client = self.client.as_ref();
+		let id = BlockId::Hash(hash);
+		let parent = BlockId::Hash(*client.header(id).ok()??.parent_hash());
+
+		// The storage keys end with the Blake2_128Concat hashed index.
+		let mut storage = BTreeMap::<H160, BTreeMap<H256, ValueChange<H256>>>::new();
+		for (child, key, value) in changes.iter() {
+			if child.is_some() {
+				continue;
+			}
+			let address = self
+				.addresses
+				.iter()
+				.find(|(_, prefix)| key.0.len() == prefix.len() + 48 && key.0.starts_with(prefix));
+			if let Some((address, _)) = address {
+				let index = H256::from_slice(&key.0[key.0.len() - 32..]);
+				let from = self.storage_value(&parent, key);
+				let to = value
+					.and_then(|data| H256::decode(&mut &data.0[..]).ok())
+					.unwrap_or_default();
+				if from != to {
+					storage
+						.entry(*address)
+						.or_default()
+						.insert(index, ValueChange { from, to });
+				}
+			}
+		}
+
+		let accounts: Vec<_> = self
+			.addresses
+			.iter()
+			.filter_map(|(address, _)| {
+				let before = client.runtime_api().account_basic(&parent, *address).ok()?;
+				let after = client.runtime_api().account_basic(&id, *address).ok()?;
+				let storage = storage.remove(address).unwrap_or_default();
+				if before == after && storage.is_empty() {
+					return None;
+				}
+				Some(AccountStateChanges {
+					address: *address,
+					balance: (before.balance != after.balance).then(|| ValueChange {
+						from: before.balance,
+						to: after.balance,
+					}),
+					nonce: (before.nonce != after.nonce).then(|| ValueChange {
+						from: before.nonce,
+						to: after.nonce,
+					}),
+					storage,
+				})
+			})
+			.collect();
+		if accounts.is_empty() {
+			return None;
+		}
+
+		let block = ethereum_block::<B, C, BE>(client, self.overrides.as_ref(), hash)?;
+		Some(StateChanges {
+			block_hash: block.hash,
+			block_number: block.number,
+			accounts,
+		})
+	}
+
+	fn storage_value(&self, id: &BlockId<B>, key: &StorageKey) -> H256 {
+		self.client
+			.storage(id, key)
+			.ok()
+			.flatten()
+			.and_then(|data| H256::decode(&mut &data.0[..]).ok())
+			.unwrap_or_default()
+	}
+}
+
+/// Ethereum hash and number of the block `hash`.
+fn ethereum_block<B, C, BE>(
+	client: &C,
+	overrides: &OverrideHandle<B>,
+	hash: H256,
+) -> Option<ReorgBlock>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: StorageProvider<B, BE> + Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	let id = BlockId::Hash(hash);
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+	let block = handler.current_block(&id)?;
+	Some(ReorgBlock {
+		hash: H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice()),
+		number: block.header.number,
+	})
+}
+
+/// Re-organisation of the best chain done by the import of a new best block, if any.
+fn reorg<B, C, BE>(
+	client: &C,
+	overrides: &OverrideHandle<B>,
+	notification: &BlockImportNotification<B>,
+) -> Option<Reorg>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: StorageProvider<B, BE> + Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	if !notification.is_new_best {
+		return None;
+	}
+	// The tree route goes from the previous best block to the parent of the imported one.
+	let route = notification.tree_route.as_ref()?;
+	if route.retracted().is_empty() {
+		return None;
+	}
+	let ethereum = |hash: H256| ethereum_block::<B, C, BE>(client, overrides, hash);
+
+	// The retracted blocks are listed from the previous best block downwards.
+	let old_chain = route
+		.retracted()
+		.iter()
+		.rev()
+		.map(|block| ethereum(block.hash))
+		.collect::<Option<Vec<_>>>()?;
+	let new_chain = route
+		.enacted()
+		.iter()
+		.map(|block| block.hash)
+		.chain(std::iter::once(notification.hash))
+		.map(ethereum)
+		.collect::<Option<Vec<_>>>()?;
+	Some(Reorg {
+		common_ancestor: ethereum(route.common_block().hash)?,
+		old_chain,
+		new_chain,
+	})
+}
+
+/// New best block `hash`, with the lag of the mapping sync behind the best block.
+fn chain_head<B, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	max_sync_lag: u64,
+	overrides: &OverrideHandle<B>,
+	hash: H256,
+) -> Option<ChainHead>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: StorageProvider<B, BE> + HeaderBackend<B> + Send + Sync + 'static,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	let id = BlockId::Hash(hash);
+	let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+	let handler = overrides
+		.schemas
+		.get(&schema)
+		.unwrap_or(&overrides.fallback);
+	let block = handler.current_block(&id)?;
+	let mapping_sync_lag = match latest_mapped_block::<B, C>(client, backend, max_sync_lag) {
+		Ok(latest) => latest.map(|(lag, _)| U256::from(lag)),
+		Err(err) => {
+			warn!("Mapping sync lag unavailable: {:?}", err);
+			None
+		}
+	};
+	Some(ChainHead {
+		hash: H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice()),
+		parent_hash: block.header.parent_hash,
+		number: block.header.number,
+		mapping_sync_lag,
+	})
+}
+
+impl<B, C, BE> FrontierPubSubApiT for FrontierPubSubApi<B, C, BE>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + BlockchainEvents<B>,
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	type Metadata = Metadata;
+
+	fn subscribe(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<FrontierResult>,
+		kind: FrontierKind,
+		filter: Option<StateChangesFilter>,
+	) {
+		if self.chain_head_only && kind != FrontierKind::ChainHead {
+			let _ = subscriber.reject(jsonrpc_core::Error::invalid_params(
+				"only chainHead subscriptions are served",
+			));
+			return;
+		}
+		match kind {
+			FrontierKind::StateChanges => {
+				let addresses = filter.map(|filter| filter.addresses()).unwrap_or_default();
+				if addresses.is_empty() || addresses.len() > MAX_WATCHED_ADDRESSES {
+					let _ = subscriber.reject(jsonrpc_core::Error::invalid_params(format!(
+						"expected 1 to {} addresses",
+						MAX_WATCHED_ADDRESSES
+					)));
+					return;
+				}
+				let stream = match self.client.storage_changes_notification_stream(None, None) {
+					Ok(stream) => stream,
+					Err(err) => {
+						let _ = subscriber.reject(internal_err(format!(
+							"storage notifications unavailable: {:?}",
+							err
+						)));
+						return;
+					}
+				};
+				let watcher = StateChangesWatcher::<B, C, BE>::new(
+					self.client.clone(),
+					self.overrides.clone(),
+					addresses,
+				);
+				self.subscriptions.add(subscriber, move |sink| {
+					stream
+						.filter_map(move |(hash, changes)| {
+							futures::future::ready(watcher.changes(hash, &changes))
+						})
+						.map(|changes| {
+							Ok::<Result<FrontierResult, jsonrpc_core::types::error::Error>, ()>(Ok(
+								FrontierResult::StateChanges(Box::new(changes)),
+							))
+						})
+						.forward(
+							sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)),
+						)
+						.map(|_| ())
+				});
+			}
+			FrontierKind::Reorgs => {
+				let client = self.client.clone();
+				let overrides = self.overrides.clone();
+				self.subscriptions.add(subscriber, move |sink| {
+					client
+						.import_notification_stream()
+						.filter_map(move |notification| {
+							futures::future::ready(reorg::<B, C, BE>(
+								client.as_ref(),
+								overrides.as_ref(),
+								&notification,
+							))
+						})
+						.map(|reorg| {
+							Ok::<Result<FrontierResult, jsonrpc_core::types::error::Error>, ()>(Ok(
+								FrontierResult::Reorg(Box::new(reorg)),
+							))
+						})
+						.forward(
+							sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)),
+						)
+						.map(|_| ())
+				});
+			}
+			FrontierKind::ChainHead => {
+				let client = self.client.clone();
+				let backend = self.backend.clone();
+				let max_sync_lag = self.max_sync_lag;
+				let overrides = self.overrides.clone();
+				let heads = self
+					.client
+					.import_notification_stream()
+					.filter(|notification| futures::future::ready(notification.is_new_best))
+					.map(move |notification| {
+						let reorg =
+							reorg::<B, C, BE>(client.as_ref(), overrides.as_ref(), &notification)
+								.map(ChainHeadEvent::Reorg);
+						let head = chain_head::<B, C, BE>(
+							client.as_ref(),
+							backend.as_ref(),
+							max_sync_lag,
+							overrides.as_ref(),
+							notification.hash,
+						)
+						.map(ChainHeadEvent::NewHead);
+						futures::stream::iter(reorg.into_iter().chain(head))
+					})
+					.flatten();
+
+				let client = self.client.clone();
+				let overrides = self.overrides.clone();
+				let finalized =
+					self.client
+						.finality_notification_stream()
+						.filter_map(move |notification| {
+							futures::future::ready(
+								ethereum_block::<B, C, BE>(
+									client.as_ref(),
+									overrides.as_ref(),
+									notification.hash,
+								)
+								.map(ChainHeadEvent::Finalized),
+							)
+						});
+
+				self.subscriptions.add(subscriber, move |sink| {
+					futures::stream::select(heads, finalized)
+						.map(|event| {
+							Ok::<Result<FrontierResult, jsonrpc_core::types::error::Error>, ()>(Ok(
+								FrontierResult::ChainHead(Box::new(event)),
+							))
+						})
+						.forward(
+							sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)),
+						)
+						.map(|_| ())
+				});
+			}
+		}
+	}
+
+	fn unsubscribe(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		subscription_id: SubscriptionId,
+	) -> JsonRpcResult<bool> {
+		Ok(self.subscriptions.cancel(subscription_id))
+	}
+}
@@ -16,35 +16,77 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+mod admin;
+mod archive;
+mod debug;
+mod dev;
 mod eth;
 mod eth_pubsub;
+mod fork;
+mod frontier;
+mod frontier_pubsub;
+mod limits;
+mod log_index;
+mod metrics;
 mod overrides;
+mod parity;
+mod personal;
+mod rate_limit;
+mod remote;
+mod response_cache;
+mod signer;
+mod slow_query;
+mod trie;
 
+pub use admin::{AdminApi, AdminApiServer};
+pub use archive::ArchiveFallback;
+pub use debug::{DebugApi, DebugApiServer};
+pub use dev::{DevApi, DevApiServer, DevState};
 pub use eth::{
-	EthApi, EthApiServer, EthBlockDataCache, EthFilterApi, EthFilterApiServer, EthTask, NetApi,
-	NetApiServer, Web3Api, Web3ApiServer,
+	EthApi, EthApiServer, EthBlockDataCache, EthCallCache, EthFilterApi, EthFilterApiServer,
+	EthTask, NetApi, NetApiServer, QueuedTransactions, SenderLimits, Web3Api, Web3ApiServer,
 };
 pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer, HexEncodedIdProvider};
+pub use fork::EthFork;
+pub use frontier::{FrontierApi, FrontierApiServer};
+pub use frontier_pubsub::{FrontierPubSubApi, FrontierPubSubApiServer};
+pub use limits::{RpcLimits, RpcLimitsConfig};
+pub use metrics::EvmMetrics;
 pub use overrides::{OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override, StorageOverride};
+pub use parity::{ParityApi, ParityApiServer};
+pub use personal::{PersonalApi, PersonalApiServer};
+pub use rate_limit::{default_method_cost, RateLimitConfig, RateLimiter};
+pub use response_cache::{default_method_ttl, ResponseCache, ResponseCacheConfig};
+pub use signer::{EthKeystoreSigner, EthRemoteSigner, RemoteSignerProtocol};
+pub use slow_query::SlowQueryTracer;
 
 use ethereum::{
 	LegacyTransactionMessage as EthereumTransactionMessage, TransactionV0 as EthereumTransaction,
 };
-use ethereum_types::{H160, H256};
+use ethereum_types::{H160, H256, U256};
 use evm::ExitError;
-use jsonrpc_core::{Error, ErrorCode, Value};
+use fc_rpc_core::types::{AccessListItem, CallRequest, RequestValidationError};
+use fp_rpc::{EthereumRuntimeRPCApi, TransactionStatus};
+use futures::{channel::oneshot, FutureExt};
+use jsonrpc_core::{BoxFuture, Error, ErrorCode, Value};
+use pallet_ethereum::TransactionValidationError;
 use pallet_evm::ExitReason;
 use rustc_hex::ToHex;
+use sc_transaction_pool_api::error::{Error as PoolError, IntoPoolError};
 use sha3::{Digest, Keccak256};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_runtime::{traits::Block as BlockT, transaction_validity::InvalidTransaction};
+use std::iter;
 
 pub mod frontier_backend_client {
 
-	use super::internal_err;
+	use super::{history_pruned_err, internal_err, state_pruned_err};
 
 	use fc_rpc_core::types::BlockNumber;
+	use fp_rpc::EthereumRuntimeRPCApi;
 	use fp_storage::PALLET_ETHEREUM_SCHEMA;
 	use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
-	use sp_api::{BlockId, HeaderT};
+	use sp_api::{ApiExt, BlockId, HeaderT, ProvideRuntimeApi};
 	use sp_blockchain::HeaderBackend;
 	use sp_runtime::traits::{BlakeTwo256, Block as BlockT, UniqueSaturatedInto, Zero};
 	use sp_storage::StorageKey;
@@ -143,6 +185,320 @@ pub mod frontier_backend_client {
 		}
 	}
 
+	/// Version of the `EthereumRuntimeRPCApi` implemented by the runtime of the given block,
+	/// `None` for the blocks of a runtime predating it, e.g. before Frontier was added to the
+	/// chain in a runtime upgrade. Calls to older versions of the API go through this check.
+	pub fn ethereum_runtime_api_version<B: BlockT, C>(
+		client: &C,
+		at: BlockId<B>,
+	) -> RpcResult<Option<u32>>
+	where
+		B: BlockT,
+		C: ProvideRuntimeApi<B>,
+		C::Api: EthereumRuntimeRPCApi<B>,
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: Send + Sync + 'static,
+	{
+		client
+			.runtime_api()
+			.api_version::<dyn EthereumRuntimeRPCApi<B>>(&at)
+			.map_err(|err| internal_err(format!("fetch runtime api version failed: {:?}", err)))
+	}
+
+	/// Block from which the min gas price follows the EIP-1559 base fee rules at `at`. None
+	/// before London is activated, and for the runtimes predating version 10 of the API, whose
+	/// min gas price acts as the base fee without the London rules.
+	pub fn london_block<B: BlockT, C>(client: &C, at: BlockId<B>) -> RpcResult<Option<U256>>
+	where
+		B: BlockT,
+		C: ProvideRuntimeApi<B>,
+		C::Api: EthereumRuntimeRPCApi<B>,
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: Send + Sync + 'static,
+	{
+		match ethereum_runtime_api_version::<B, C>(client, at)? {
+			Some(version) if version >= 10 => {
+				client.runtime_api().london_block(&at).map_err(|err| {
+					internal_err(format!("fetch runtime london block failed: {:?}", err))
+				})
+			}
+			_ => Ok(None),
+		}
+	}
+
+	/// Hard fork the EVM of the runtime follows, `None` before `EthereumRuntimeRPCApi` version 4.
+	pub fn evm_fork<B: BlockT, C>(client: &C, at: BlockId<B>) -> RpcResult<Option<String>>
+	where
+		B: BlockT,
+		C: ProvideRuntimeApi<B>,
+		C::Api: EthereumRuntimeRPCApi<B>,
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: Send + Sync + 'static,
+	{
+		match ethereum_runtime_api_version::<B, C>(client, at)? {
+			Some(api_version) if api_version >= 4 => client
+				.runtime_api()
+				.evm_fork(&at)
+				.map(|fork| Some(String::from_utf8_lossy(&fork).into_owned()))
+				.map_err(|err| internal_err(format!("fetch runtime evm fork failed: {:?}", err))),
+			_ => Ok(None),
+		}
+	}
+
+	/// Fails with a `state_pruned_err` when the state of the known block `at` was discarded.
+	pub fn ensure_state_available<B: BlockT, C, BE>(client: &C, at: BlockId<B>) -> RpcResult<()>
+	where
+		B: BlockT,
+		C: StorageProvider<B, BE> + HeaderBackend<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: Send + Sync + 'static,
+	{
+		// An unknown block is not a pruning issue, let the caller handle it.
+		let header = match client.header(at) {
+			Ok(Some(header)) => header,
+			_ => return Ok(()),
+		};
+		if has_state::<B, C, BE>(client, at) {
+			return Ok(());
+		}
+
+		Err(state_pruned_err(
+			header.hash(),
+			earliest_state_block::<B, C, BE>(client),
+		))
+	}
+
+	/// Fails with a `history_pruned_err` when the Ethereum data (body, receipts) of the known
+	/// block `at` is not served: its state was pruned, or it is older than the latest
+	/// `history_window` blocks. The hash mappings are kept, so these blocks and their
+	/// transactions are still known.
+	pub fn ensure_history_available<B: BlockT, C, BE>(
+		client: &C,
+		at: BlockId<B>,
+		history_window: Option<u64>,
+	) -> RpcResult<()>
+	where
+		B: BlockT,
+		C: StorageProvider<B, BE> + HeaderBackend<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: Send + Sync + 'static,
+	{
+		let header = match client.header(at) {
+			Ok(Some(header)) => header,
+			_ => return Ok(()),
+		};
+		let best_number: u64 = client.info().best_number.unique_saturated_into();
+		let number: u64 = (*header.number()).unique_saturated_into();
+		if number >= history_window_start(best_number, history_window)
+			&& has_state::<B, C, BE>(client, at)
+		{
+			return Ok(());
+		}
+
+		Err(history_pruned_err(
+			header.hash(),
+			earliest_history_block::<B, C, BE>(client, history_window),
+		))
+	}
+
+	/// Number of the earliest best chain block whose Ethereum data is served, see
+	/// `ensure_history_available`.
+	pub fn earliest_history_block<B: BlockT, C, BE>(client: &C, history_window: Option<u64>) -> u64
+	where
+		B: BlockT,
+		C: StorageProvider<B, BE> + HeaderBackend<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+	{
+		let best_number: u64 = client.info().best_number.unique_saturated_into();
+		history_window_start(best_number, history_window)
+			.max(earliest_state_block::<B, C, BE>(client))
+	}
+
+	/// First block of the latest `history_window` blocks, the genesis without a window.
+	pub fn history_window_start(best_number: u64, history_window: Option<u64>) -> u64 {
+		history_window
+			.map(|window| best_number.saturating_sub(window))
+			.unwrap_or(0)
+	}
+
+	/// Number of the earliest best chain block whose state is available.
+	pub fn earliest_state_block<B: BlockT, C, BE>(client: &C) -> u64
+	where
+		B: BlockT,
+		C: StorageProvider<B, BE> + HeaderBackend<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+	{
+		// Pruning discards a contiguous range of the oldest states.
+		let best_number: u64 = client.info().best_number.unique_saturated_into();
+		let (mut low, mut high) = (0u64, best_number);
+		while low < high {
+			let mid = low + (high - low) / 2;
+			if has_state::<B, C, BE>(client, BlockId::Number(mid.unique_saturated_into())) {
+				high = mid;
+			} else {
+				low = mid + 1;
+			}
+		}
+		low
+	}
+
+	/// Storage schema cache of the best chain, read from the on-chain schemas: the schemas
+	/// with the hash of their first block, the first one at genesis. The schema only moves
+	/// forward, so the first block of each schema is found by bisection. The blocks whose
+	/// state was pruned are given the schema of the earliest available state.
+	pub fn compute_schema_cache<B: BlockT, C, BE>(
+		client: &C,
+	) -> RpcResult<Vec<(EthereumStorageSchema, H256)>>
+	where
+		B: BlockT,
+		C: StorageProvider<B, BE> + HeaderBackend<B>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: Send + Sync + 'static,
+	{
+		let schema_at = |number: u64| {
+			onchain_storage_schema::<B, C, BE>(
+				client,
+				BlockId::Number(number.unique_saturated_into()),
+			)
+		};
+		let hash_at = |number: u64| {
+			client
+				.hash(number.unique_saturated_into())
+				.map_err(|err| internal_err(format!("fetch block hash failed: {:?}", err)))?
+				.ok_or_else(|| internal_err(format!("block {} not found", number)))
+		};
+
+		let best_number: u64 = client.info().best_number.unique_saturated_into();
+		schema_cache_of(
+			best_number,
+			earliest_state_block::<B, C, BE>(client),
+			schema_at,
+			hash_at,
+		)
+	}
+
+	/// Schema cache of a chain of `best_number + 1` blocks, given the schema at each number of
+	/// the available states, from `earliest` on, see `compute_schema_cache`.
+	fn schema_cache_of(
+		best_number: u64,
+		earliest: u64,
+		schema_at: impl Fn(u64) -> EthereumStorageSchema,
+		hash_at: impl Fn(u64) -> RpcResult<H256>,
+	) -> RpcResult<Vec<(EthereumStorageSchema, H256)>> {
+		let best_schema = schema_at(best_number);
+		let mut start = earliest;
+		let mut schema = schema_at(start);
+		let mut cache = vec![(schema, hash_at(0)?)];
+
+		while schema != best_schema {
+			// First block after `start` with a different schema.
+			let (mut low, mut high) = (start + 1, best_number);
+			while low < high {
+				let mid = low + (high - low) / 2;
+				if schema_at(mid) != schema {
+					high = mid;
+				} else {
+					low = mid + 1;
+				}
+			}
+			start = low;
+			schema = schema_at(start);
+			cache.push((schema, hash_at(start)?));
+		}
+
+		Ok(cache)
+	}
+
+	/// Whether the storage schema cache agrees with the on-chain schemas: its blocks are in the
+	/// best chain, the schema of the best block is the last one, and, where the state is
+	/// available, each block has its cached schema and its parent a different one.
+	pub fn is_schema_cache_valid<B: BlockT, C, BE>(
+		client: &C,
+		cache: &[(EthereumStorageSchema, H256)],
+	) -> bool
+	where
+		B: BlockT,
+		C: StorageProvider<B, BE> + HeaderBackend<B> + 'static,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		B: BlockT<Hash = H256> + Send + Sync + 'static,
+		C: Send + Sync + 'static,
+	{
+		let schema_at = |hash: H256| {
+			let at = BlockId::Hash(hash);
+			if has_state::<B, C, BE>(client, at) {
+				Some(onchain_storage_schema::<B, C, BE>(client, at))
+			} else {
+				None
+			}
+		};
+
+		let parent_hash = |hash: H256| match client.header(BlockId::Hash(hash)) {
+			Ok(Some(header)) => Some(*header.parent_hash()),
+			_ => None,
+		};
+
+		is_schema_cache_valid_for(
+			cache,
+			client.info().best_hash,
+			schema_at,
+			|hash| is_canon::<B, C>(client, hash),
+			parent_hash,
+		)
+	}
+
+	/// Checks of `is_schema_cache_valid` over a chain: `schema_at` is `None` where the state is
+	/// pruned and `parent_hash` is `None` for unknown blocks.
+	fn is_schema_cache_valid_for(
+		cache: &[(EthereumStorageSchema, H256)],
+		best_hash: H256,
+		schema_at: impl Fn(H256) -> Option<EthereumStorageSchema>,
+		is_canon: impl Fn(H256) -> bool,
+		parent_hash: impl Fn(H256) -> Option<H256>,
+	) -> bool {
+		let best_schema = schema_at(best_hash);
+		match cache.last() {
+			Some((schema, _)) if best_schema.map_or(true, |best| best == *schema) => {}
+			_ => return false,
+		}
+
+		cache.iter().enumerate().all(|(i, (schema, hash))| {
+			if !is_canon(*hash) {
+				return false;
+			}
+			if schema_at(*hash).map_or(false, |onchain| onchain != *schema) {
+				return false;
+			}
+			match parent_hash(*hash) {
+				Some(parent) if i > 0 => {
+					schema_at(parent).map_or(true, |parent_schema| parent_schema != *schema)
+				}
+				Some(_) => true,
+				None => false,
+			}
+		})
+	}
+
+	fn has_state<B: BlockT, C, BE>(client: &C, at: BlockId<B>) -> bool
+	where
+		B: BlockT,
+		C: StorageProvider<B, BE>,
+		BE: Backend<B> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+	{
+		client
+			.storage(&at, &StorageKey(PALLET_ETHEREUM_SCHEMA.to_vec()))
+			.is_ok()
+	}
+
 	pub fn is_canon<B: BlockT, C>(client: &C, target_hash: H256) -> bool
 	where
 		B: BlockT,
@@ -158,6 +514,23 @@ pub mod frontier_backend_client {
 		false
 	}
 
+	/// Fails with a `transaction_indexing_err` while the mapping sync walks back the chain and
+	/// has not indexed the transaction history it retains yet, see `--tx-index-history`.
+	pub fn ensure_transactions_indexed<B: BlockT>(backend: &fc_db::Backend<B>) -> RpcResult<()> {
+		let tail = backend
+			.meta()
+			.transaction_index_tail()
+			.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?;
+		let tips = backend
+			.meta()
+			.current_syncing_tips()
+			.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?;
+		if tail.is_none() && !tips.is_empty() {
+			return Err(crate::transaction_indexing_err());
+		}
+		Ok(())
+	}
+
 	pub fn load_transactions<B: BlockT, C>(
 		client: &C,
 		backend: &fc_db::Backend<B>,
@@ -192,6 +565,134 @@ pub mod frontier_backend_client {
 				|meta| Ok(Some((meta.ethereum_block_hash, meta.ethereum_index))),
 			)
 	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+
+		// Blocks `0..=20`, block `n` has hash `n + 1`, the schema is set at block 8.
+		const BEST: u64 = 20;
+		const UPGRADE: u64 = 8;
+
+		fn hash(number: u64) -> H256 {
+			H256::from_low_u64_be(number + 1)
+		}
+
+		fn number(hash: H256) -> Option<u64> {
+			let number = hash.to_low_u64_be().checked_sub(1)?;
+			if number <= BEST && hash == self::hash(number) {
+				Some(number)
+			} else {
+				None
+			}
+		}
+
+		fn schema(number: u64) -> EthereumStorageSchema {
+			if number < UPGRADE {
+				EthereumStorageSchema::Undefined
+			} else {
+				EthereumStorageSchema::V1
+			}
+		}
+
+		fn compute(best: u64, earliest: u64) -> Vec<(EthereumStorageSchema, H256)> {
+			schema_cache_of(best, earliest, schema, |number| Ok(hash(number))).unwrap()
+		}
+
+		fn validate(cache: &[(EthereumStorageSchema, H256)], earliest: u64) -> bool {
+			is_schema_cache_valid_for(
+				cache,
+				hash(BEST),
+				|hash| number(hash).filter(|n| *n >= earliest).map(schema),
+				|hash| number(hash).is_some(),
+				|hash| number(hash).map(|n| self::hash(n.saturating_sub(1))),
+			)
+		}
+
+		#[test]
+		fn history_window_starts_at_the_latest_blocks() {
+			assert_eq!(history_window_start(100, None), 0);
+			assert_eq!(history_window_start(100, Some(10)), 90);
+			assert_eq!(history_window_start(5, Some(10)), 0);
+		}
+
+		#[test]
+		fn single_schema_is_cached_at_genesis() {
+			assert_eq!(
+				compute(UPGRADE - 1, 0),
+				vec![(EthereumStorageSchema::Undefined, hash(0))]
+			);
+		}
+
+		#[test]
+		fn schema_change_is_found_by_bisection() {
+			let cache = compute(BEST, 0);
+			assert_eq!(
+				cache,
+				vec![
+					(EthereumStorageSchema::Undefined, hash(0)),
+					(EthereumStorageSchema::V1, hash(UPGRADE)),
+				]
+			);
+			assert!(validate(&cache, 0));
+		}
+
+		#[test]
+		fn pruned_blocks_get_the_earliest_available_schema() {
+			let cache = compute(BEST, UPGRADE + 2);
+			assert_eq!(cache, vec![(EthereumStorageSchema::V1, hash(0))]);
+			assert!(validate(&cache, UPGRADE + 2));
+		}
+
+		#[test]
+		fn hash_lookup_failure_is_reported() {
+			assert!(schema_cache_of(BEST, 0, schema, |_| Err(internal_err("no hash"))).is_err());
+		}
+
+		#[test]
+		fn empty_cache_is_invalid() {
+			assert!(!validate(&[], 0));
+		}
+
+		#[test]
+		fn stale_last_schema_is_invalid() {
+			assert!(!validate(&[(EthereumStorageSchema::Undefined, hash(0))], 0));
+		}
+
+		#[test]
+		fn non_canon_block_is_invalid() {
+			let cache = vec![
+				(EthereumStorageSchema::Undefined, hash(0)),
+				(EthereumStorageSchema::V1, H256::repeat_byte(0xff)),
+			];
+			assert!(!validate(&cache, 0));
+		}
+
+		#[test]
+		fn wrong_boundary_is_invalid() {
+			// Too late: the block has the schema but its parent too.
+			let late = vec![
+				(EthereumStorageSchema::Undefined, hash(0)),
+				(EthereumStorageSchema::V1, hash(UPGRADE + 1)),
+			];
+			assert!(!validate(&late, 0));
+			// Too early: the block does not have the cached schema.
+			let early = vec![
+				(EthereumStorageSchema::Undefined, hash(0)),
+				(EthereumStorageSchema::V1, hash(UPGRADE - 1)),
+			];
+			assert!(!validate(&early, 0));
+		}
+
+		#[test]
+		fn pruned_boundary_is_trusted() {
+			let cache = vec![
+				(EthereumStorageSchema::Undefined, hash(0)),
+				(EthereumStorageSchema::V1, hash(UPGRADE - 1)),
+			];
+			assert!(validate(&cache, UPGRADE));
+		}
+	}
 }
 
 pub fn internal_err<T: ToString>(message: T) -> Error {
@@ -202,6 +703,292 @@ pub fn internal_err<T: ToString>(message: T) -> Error {
 	}
 }
 
+/// Error returned for the EIP-2718 transaction types which are not supported, only legacy
+/// transactions are.
+pub fn unsupported_transaction_type_err(transaction_type: u64) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("transaction type {:#x} not supported", transaction_type),
+		data: Some(Value::String(format!("{:#x}", transaction_type))),
+	}
+}
+
+/// Error returned for a request with contradictory fields.
+pub fn invalid_request_err(err: RequestValidationError) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: err.to_string(),
+		data: None,
+	}
+}
+
+/// Restores the logs of receipts stored by a runtime in compact receipts mode, which are kept
+/// in the transaction statuses instead.
+pub fn restore_receipt_logs(receipts: &mut [ethereum::Receipt], statuses: &[TransactionStatus]) {
+	for (receipt, status) in receipts.iter_mut().zip(statuses) {
+		if receipt.logs.is_empty() {
+			receipt.logs = status.logs.clone();
+		}
+	}
+}
+
+/// Error returned when the state of a block was pruned, carrying the earliest block whose
+/// state is still available.
+pub fn state_pruned_err(block_hash: H256, earliest_block: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(-32000),
+		message: format!(
+			"missing trie node for block {:?}: state not available, try an archive node",
+			block_hash
+		),
+		data: Some(Value::Object(
+			iter::once((
+				"earliestBlock".to_string(),
+				Value::String(format!("0x{:x}", earliest_block)),
+			))
+			.collect(),
+		)),
+	}
+}
+
+/// Error returned when the pool refuses a submitted transaction. Nonce errors are classified as
+/// `nonce too low` or `nonce too high` against the `expected_nonce` of the sender, the next
+/// nonce the pool would make ready, which is carried as `expectedNonce` for wallets to repair
+/// their nonce. The rejections of pallet-ethereum and of its `ExtraValidation` hook are
+/// reported with geth-like messages, the custom codes of the hook are carried as `code`.
+pub fn transaction_pool_err<E: IntoPoolError>(
+	err: E,
+	nonce: U256,
+	expected_nonce: Option<U256>,
+) -> Error {
+	let nonce_err = |message: &str| Error {
+		code: ErrorCode::ServerError(-32000),
+		message: message.to_string(),
+		data: expected_nonce.map(|expected_nonce| {
+			Value::Object(
+				iter::once((
+					"expectedNonce".to_string(),
+					Value::String(format!("0x{:x}", expected_nonce)),
+				))
+				.collect(),
+			)
+		}),
+	};
+	let server_err = |message: &str| Error {
+		code: ErrorCode::ServerError(-32000),
+		message: message.to_string(),
+		data: None,
+	};
+	let is_gap = expected_nonce.map_or(false, |expected_nonce| nonce > expected_nonce);
+
+	match err.into_pool_error() {
+		Ok(PoolError::InvalidTransaction(InvalidTransaction::Stale)) => nonce_err("nonce too low"),
+		Ok(PoolError::InvalidTransaction(InvalidTransaction::Future)) => {
+			nonce_err("nonce too high")
+		}
+		Ok(PoolError::RejectedFutureTransaction) | Ok(PoolError::ImmediatelyDropped) if is_gap => {
+			nonce_err("nonce too high")
+		}
+		Ok(PoolError::InvalidTransaction(InvalidTransaction::Payment)) => {
+			server_err("insufficient funds for gas * price + value")
+		}
+		Ok(PoolError::AlreadyImported(_)) | Ok(PoolError::TemporarilyBanned) => {
+			server_err("already known")
+		}
+		Ok(PoolError::TooLowPriority { .. }) => server_err("replacement transaction underpriced"),
+		Ok(PoolError::InvalidTransaction(InvalidTransaction::BadSigner)) => {
+			server_err("sender not allowed")
+		}
+		Ok(PoolError::InvalidTransaction(InvalidTransaction::Call)) => {
+			server_err("transaction not allowed")
+		}
+		Ok(PoolError::InvalidTransaction(InvalidTransaction::Custom(code))) => {
+			match TransactionValidationError::from_code(code) {
+				Some(TransactionValidationError::InvalidChainId) => server_err("invalid chain id"),
+				Some(TransactionValidationError::InvalidSignature) => server_err("invalid sender"),
+				Some(TransactionValidationError::InvalidGasLimit) => {
+					server_err("exceeds block gas limit")
+				}
+				Some(TransactionValidationError::IntrinsicGasTooLow) => {
+					server_err("intrinsic gas too low")
+				}
+				Some(TransactionValidationError::GasPriceTooLow) => {
+					server_err("transaction underpriced")
+				}
+				// Codes of the `ExtraValidation` of the runtime.
+				_ => Error {
+					code: ErrorCode::ServerError(-32000),
+					message: "transaction rejected by the runtime".to_string(),
+					data: Some(Value::Object(
+						iter::once(("code".to_string(), Value::Number(code.into()))).collect(),
+					)),
+				},
+			}
+		}
+		Ok(err) => internal_err(format!("submit transaction to pool failed: {:?}", err)),
+		Err(err) => internal_err(format!("submit transaction to pool failed: {:?}", err)),
+	}
+}
+
+/// Error returned when the pool already holds as many transactions of the sender as it accepts.
+pub fn account_limit_err(limit: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(-32000),
+		message: "account limit exceeded".to_string(),
+		data: Some(Value::Object(
+			iter::once(("limit".to_string(), Value::Number(limit.into()))).collect(),
+		)),
+	}
+}
+
+/// Error returned when the Ethereum data of a known block was pruned, carrying the earliest
+/// block whose data is still served.
+pub fn history_pruned_err(block_hash: H256, earliest_block: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(-32000),
+		message: format!(
+			"ethereum data of block {:?} pruned, try an archive node",
+			block_hash
+		),
+		data: Some(Value::Object(
+			iter::once((
+				"earliestBlock".to_string(),
+				Value::String(format!("0x{:x}", earliest_block)),
+			))
+			.collect(),
+		)),
+	}
+}
+
+/// Error returned, as geth does, for a transaction not found by hash while the mapping sync has
+/// not indexed the transaction history yet.
+pub fn transaction_indexing_err() -> Error {
+	Error {
+		code: ErrorCode::ServerError(-32000),
+		message: "transaction indexing is in progress".to_string(),
+		data: Some(Value::String(
+			"transaction indexing is in progress".to_string(),
+		)),
+	}
+}
+
+/// Runs a synchronous, potentially heavy request handler (runtime API executions, block
+/// assembly) on the blocking pool of `spawn_handle`, so that it does not stall the
+/// async executor serving the cheap requests.
+///
+/// The `eth_` methods reading a single account or storage value (`eth_getBalance`,
+/// `eth_getStorageAt`, `eth_getTransactionCount`, `eth_getCode`), a single transaction, or only
+/// the in-memory caches (`eth_gasPrice`, `eth_maxPriorityFeePerGas`) stay on the async executor,
+/// the pool would cost them more than it spares.
+pub fn spawn_blocking_request<T, F>(
+	spawn_handle: &sc_service::SpawnTaskHandle,
+	name: &'static str,
+	handler: F,
+) -> BoxFuture<Result<T, Error>>
+where
+	T: Send + 'static,
+	F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+	let (tx, rx) = oneshot::channel();
+	spawn_handle.spawn_blocking(name, async move {
+		let _ = tx.send(handler());
+	});
+
+	Box::pin(rx.map(|result| {
+		result.unwrap_or_else(|_| Err(internal_err("request handler task was cancelled")))
+	}))
+}
+
+/// Error returned for an access list on a chain whose EVM predates Berlin.
+pub fn access_lists_unsupported_err(evm_fork: Option<String>) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: format!(
+			"access lists require the berlin fork, the chain follows {}",
+			evm_fork.as_deref().unwrap_or("an older fork")
+		),
+		data: None,
+	}
+}
+
+/// Gas charged for an access list (EIP-2930). Fails if the EVM of the runtime at `at` predates
+/// Berlin.
+pub fn access_list_gas<B, C>(
+	client: &C,
+	at: BlockId<B>,
+	access_list: &[AccessListItem],
+) -> Result<u64, Error>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+{
+	if access_list.is_empty() {
+		return Ok(0);
+	}
+	match frontier_backend_client::evm_fork::<B, C>(client, at)? {
+		Some(fork) if fork == "berlin" => {
+			let access_list = access_list
+				.iter()
+				.map(AccessListItem::to_tuple)
+				.collect::<Vec<_>>();
+			Ok(fp_evm::access_list_gas(
+				&evm::Config::berlin(),
+				&access_list,
+			))
+		}
+		fork => Err(access_lists_unsupported_err(fork)),
+	}
+}
+
+/// EVM config whose transaction costs are those of the given hard fork, as named by the
+/// `evm_fork` runtime API. Runtimes predating that API follow Istanbul.
+pub fn fork_config(evm_fork: Option<&str>) -> evm::Config {
+	match evm_fork {
+		Some("berlin") => evm::Config::berlin(),
+		Some("frontier") => evm::Config::frontier(),
+		// Homestead raised the cost of the contract creations, Istanbul then lowered the cost of
+		// the non-zero data bytes.
+		Some("homestead") | Some("byzantium") | Some("constantinople") => {
+			let mut config = evm::Config::frontier();
+			config.gas_transaction_create = evm::Config::istanbul().gas_transaction_create;
+			config
+		}
+		_ => evm::Config::istanbul(),
+	}
+}
+
+/// EVM config of the runtime at `at`, for the costs checked before executing a transaction.
+pub fn runtime_evm_config<B, C>(client: &C, at: BlockId<B>) -> Result<evm::Config, Error>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+{
+	let evm_fork = frontier_backend_client::evm_fork::<B, C>(client, at)?;
+	Ok(fork_config(evm_fork.as_deref()))
+}
+
+/// Gas charged before the execution of a transaction under the given EVM config, with the gas
+/// of its access list. Fails if the given gas limit does not cover it.
+pub fn ensure_intrinsic_gas(
+	config: &evm::Config,
+	gas_limit: U256,
+	is_create: bool,
+	input: &[u8],
+	access_list_gas: u64,
+) -> Result<u64, Error> {
+	let intrinsic_gas =
+		fp_evm::intrinsic_gas(config, is_create, input, &[]).saturating_add(access_list_gas);
+	if gas_limit < U256::from(intrinsic_gas) {
+		return Err(internal_err(format!(
+			"intrinsic gas too low: have {}, want {}",
+			gas_limit, intrinsic_gas
+		)));
+	}
+	Ok(intrinsic_gas)
+}
+
 pub fn error_on_execution_failure(reason: &ExitReason, data: &[u8]) -> Result<(), Error> {
 	match reason {
 		ExitReason::Succeed(_) => Ok(()),
@@ -245,6 +1032,62 @@ pub fn error_on_execution_failure(reason: &ExitReason, data: &[u8]) -> Result<()
 	}
 }
 
+/// Traces `request` at `at` with the `trace_call` runtime API, with the given access list or
+/// else the one of the request. Fails before version 13 of the API.
+pub fn trace_call<B, C>(
+	client: &C,
+	at: BlockId<B>,
+	request: &CallRequest,
+	access_list: Option<Vec<(H160, Vec<H256>)>>,
+) -> Result<fp_evm::CallTrace, Error>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+{
+	let api_version =
+		frontier_backend_client::ethereum_runtime_api_version::<B, C>(client, at)?.unwrap_or(1);
+	if api_version < 13 {
+		return Err(internal_err("the runtime does not support call tracing"));
+	}
+	let runtime_err = |err| internal_err(format!("runtime error: {:?}", err));
+	let gas_limit = match request.gas {
+		Some(gas) => gas,
+		None => client
+			.runtime_api()
+			.current_block(&at)
+			.map_err(runtime_err)?
+			.map(|block| block.header.gas_limit)
+			.ok_or_else(|| internal_err("block unavailable, cannot query gas limit"))?,
+	};
+	let access_list = access_list.or_else(|| {
+		request
+			.access_list
+			.as_ref()
+			.map(|list| list.iter().map(AccessListItem::to_tuple).collect())
+	});
+
+	client
+		.runtime_api()
+		.trace_call(
+			&at,
+			request.from.unwrap_or_default(),
+			request.to,
+			request
+				.call_data()
+				.map(|data| data.0.clone())
+				.unwrap_or_default(),
+			request.value.unwrap_or_default(),
+			gas_limit,
+			request.max_fee_per_gas.or(request.gas_price),
+			request.max_priority_fee_per_gas,
+			request.nonce,
+			access_list,
+		)
+		.map_err(runtime_err)?
+		.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))
+}
+
 pub fn public_key(transaction: &EthereumTransaction) -> Result<[u8; 64], sp_io::EcdsaVerifyError> {
 	let mut sig = [0u8; 65];
 	let mut msg = [0u8; 32];
@@ -266,6 +1109,50 @@ pub trait EthSigner: Send + Sync {
 		message: ethereum::LegacyTransactionMessage,
 		address: &H160,
 	) -> Result<ethereum::TransactionV0, Error>;
+	/// Sign the EIP-191 prefixed message using the given account, returning the 65 bytes
+	/// `r || s || v` signature.
+	fn sign_message(&self, _message: &[u8], _address: &H160) -> Result<Vec<u8>, Error> {
+		Err(internal_err("message signing not supported by the signer"))
+	}
+}
+
+/// Signs the EIP-191 prefixed message with the signer of `address`.
+pub(crate) fn sign_personal_message(
+	signers: &[Box<dyn EthSigner>],
+	message: &[u8],
+	address: &H160,
+) -> Result<Vec<u8>, Error> {
+	signers
+		.iter()
+		.find(|signer| signer.accounts().contains(address))
+		.ok_or(internal_err("no signer available"))?
+		.sign_message(message, address)
+}
+
+/// EIP-191 hash of a personal message.
+pub fn personal_message_hash(message: &[u8]) -> H256 {
+	let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+	prefixed.extend_from_slice(message);
+	H256::from_slice(Keccak256::digest(&prefixed).as_slice())
+}
+
+/// Recovers the account which signed the EIP-191 prefixed message.
+pub fn recover_personal_message_signer(message: &[u8], signature: &[u8]) -> Result<H160, Error> {
+	if signature.len() != 65 {
+		return Err(internal_err("invalid signature length"));
+	}
+	let mut sig = [0u8; 65];
+	sig.copy_from_slice(signature);
+	// Accept both the `{0, 1}` and the `{27, 28}` recovery ids.
+	if sig[64] >= 27 {
+		sig[64] -= 27;
+	}
+
+	let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&sig, &personal_message_hash(message).0)
+		.map_err(|_| internal_err("invalid signature"))?;
+	Ok(H160::from(H256::from_slice(
+		Keccak256::digest(&pubkey).as_slice(),
+	)))
 }
 
 pub struct EthDevSigner {
@@ -287,16 +1174,7 @@ impl EthDevSigner {
 
 impl EthSigner for EthDevSigner {
 	fn accounts(&self) -> Vec<H160> {
-		self.keys
-			.iter()
-			.map(|secret| {
-				let public = secp256k1::PublicKey::from_secret_key(secret);
-				let mut res = [0u8; 64];
-				res.copy_from_slice(&public.serialize()[1..65]);
-
-				H160::from(H256::from_slice(Keccak256::digest(&res).as_slice()))
-			})
-			.collect()
+		self.keys.iter().map(secret_key_address).collect()
 	}
 
 	fn sign(
@@ -304,44 +1182,75 @@ impl EthSigner for EthDevSigner {
 		message: ethereum::LegacyTransactionMessage,
 		address: &H160,
 	) -> Result<ethereum::TransactionV0, Error> {
-		let mut transaction = None;
-
-		for secret in &self.keys {
-			let key_address = {
-				let public = secp256k1::PublicKey::from_secret_key(secret);
-				let mut res = [0u8; 64];
-				res.copy_from_slice(&public.serialize()[1..65]);
-				H160::from(H256::from_slice(Keccak256::digest(&res).as_slice()))
-			};
-
-			if &key_address == address {
-				let signing_message = secp256k1::Message::parse_slice(&message.hash()[..])
-					.map_err(|_| internal_err("invalid signing message"))?;
-				let (signature, recid) = secp256k1::sign(&signing_message, secret);
-
-				let v = match message.chain_id {
-					None => 27 + recid.serialize() as u64,
-					Some(chain_id) => 2 * chain_id + 35 + recid.serialize() as u64,
-				};
-				let rs = signature.serialize();
-				let r = H256::from_slice(&rs[0..32]);
-				let s = H256::from_slice(&rs[32..64]);
-
-				transaction = Some(ethereum::TransactionV0 {
-					nonce: message.nonce,
-					gas_price: message.gas_price,
-					gas_limit: message.gas_limit,
-					action: message.action,
-					value: message.value,
-					input: message.input.clone(),
-					signature: ethereum::TransactionSignature::new(v, r, s)
-						.ok_or(internal_err("signer generated invalid signature"))?,
-				});
-
-				break;
-			}
-		}
+		sign_with_keys(&self.keys, message, address)
+	}
 
-		transaction.ok_or(internal_err("signer not available"))
+	fn sign_message(&self, message: &[u8], address: &H160) -> Result<Vec<u8>, Error> {
+		sign_message_with_keys(&self.keys, message, address)
 	}
 }
+
+/// Address of the account of a secret key.
+pub(crate) fn secret_key_address(secret: &secp256k1::SecretKey) -> H160 {
+	let public = secp256k1::PublicKey::from_secret_key(secret);
+	let mut res = [0u8; 64];
+	res.copy_from_slice(&public.serialize()[1..65]);
+	H160::from(H256::from_slice(Keccak256::digest(&res).as_slice()))
+}
+
+fn find_key<'a>(
+	keys: &'a [secp256k1::SecretKey],
+	address: &H160,
+) -> Result<&'a secp256k1::SecretKey, Error> {
+	keys.iter()
+		.find(|secret| &secret_key_address(secret) == address)
+		.ok_or(internal_err("signer not available"))
+}
+
+/// Signs an EIP-191 prefixed message with the key of `address` among `keys`.
+pub(crate) fn sign_message_with_keys(
+	keys: &[secp256k1::SecretKey],
+	message: &[u8],
+	address: &H160,
+) -> Result<Vec<u8>, Error> {
+	let secret = find_key(keys, address)?;
+
+	let signing_message = secp256k1::Message::parse(&personal_message_hash(message).0);
+	let (signature, recid) = secp256k1::sign(&signing_message, secret);
+
+	let mut res = signature.serialize().to_vec();
+	res.push(27 + recid.serialize());
+	Ok(res)
+}
+
+/// Signs a transaction message with the key of `address` among `keys`.
+pub(crate) fn sign_with_keys(
+	keys: &[secp256k1::SecretKey],
+	message: ethereum::LegacyTransactionMessage,
+	address: &H160,
+) -> Result<ethereum::TransactionV0, Error> {
+	let secret = find_key(keys, address)?;
+
+	let signing_message = secp256k1::Message::parse_slice(&message.hash()[..])
+		.map_err(|_| internal_err("invalid signing message"))?;
+	let (signature, recid) = secp256k1::sign(&signing_message, secret);
+
+	let v = match message.chain_id {
+		None => 27 + recid.serialize() as u64,
+		Some(chain_id) => 2 * chain_id + 35 + recid.serialize() as u64,
+	};
+	let rs = signature.serialize();
+	let r = H256::from_slice(&rs[0..32]);
+	let s = H256::from_slice(&rs[32..64]);
+
+	Ok(ethereum::TransactionV0 {
+		nonce: message.nonce,
+		gas_price: message.gas_price,
+		gas_limit: message.gas_limit,
+		action: message.action,
+		value: message.value,
+		input: message.input,
+		signature: ethereum::TransactionSignature::new(v, r, s)
+			.ok_or(internal_err("signer generated invalid signature"))?,
+	})
+}
@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Size limits of the Ethereum RPC requests and responses.
+//!
+//! The limits of the parameters and results of the individual calls are enforced by wrapping
+//! the methods of an `IoHandler`. The length of the batches, which the methods do not see, is
+//! limited by the `Middleware` implementation, for the servers built on a `MetaIoHandler`.
+//!
+//! The sizes are counted by serializing into a sink which keeps no bytes and stops at the
+//! limit, so that the values are never buffered and the oversized ones are not walked whole.
+
+use futures::FutureExt;
+use jsonrpc_core::{
+	futures::future::{self, Either},
+	middleware::NoopCallFuture,
+	BoxFuture, Error, ErrorCode, IoHandler, Metadata, Middleware, Params, RemoteProcedure, Request,
+	Response, Result, RpcMethod, Value, Version,
+};
+use serde::Serialize;
+use std::{io, sync::Arc};
+
+/// Prefixes of the methods subject to the limits.
+const LIMITED_NAMESPACES: &[&str] = &["eth_", "net_", "web3_"];
+
+/// Limits configuration, the defaults match geth.
+#[derive(Clone, Debug)]
+pub struct RpcLimitsConfig {
+	/// Maximum number of calls in a batch.
+	pub max_batch_len: usize,
+	/// Maximum size of the parameters of a call, in bytes.
+	pub max_request_size: usize,
+	/// Maximum size of the result of a call or of the responses of a batch, in bytes.
+	pub max_response_size: usize,
+}
+
+impl Default for RpcLimitsConfig {
+	fn default() -> Self {
+		Self {
+			max_batch_len: 1000,
+			max_request_size: 5 * 1024 * 1024,
+			max_response_size: 25 * 1024 * 1024,
+		}
+	}
+}
+
+fn batch_too_large_err(limit: usize, len: usize) -> Response {
+	let error = Error {
+		code: ErrorCode::InvalidRequest,
+		message: "batch too large".to_string(),
+		data: Some(serde_json::json!({ "limit": limit, "size": len })),
+	};
+	Response::from(error, Some(Version::V2))
+}
+
+/// The size is not given, the counting stops at the limit.
+fn too_large_err(code: ErrorCode, message: &str, limit: usize) -> Error {
+	Error {
+		code,
+		message: message.to_string(),
+		data: Some(serde_json::json!({ "limit": limit })),
+	}
+}
+
+fn request_too_large_err(limit: usize) -> Error {
+	too_large_err(ErrorCode::InvalidRequest, "request too large", limit)
+}
+
+/// Same code as geth's `response too large` error.
+fn response_too_large_err(limit: usize) -> Error {
+	too_large_err(ErrorCode::ServerError(-32003), "response too large", limit)
+}
+
+/// Counts the serialized bytes, failing once they exceed the limit.
+struct SizeCounter {
+	size: usize,
+	limit: usize,
+}
+
+impl io::Write for SizeCounter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.size += buf.len();
+		if self.size > self.limit {
+			return Err(io::ErrorKind::Other.into());
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Whether the serialized value is larger than `limit` bytes.
+fn exceeds<T: Serialize>(value: &T, limit: usize) -> bool {
+	serde_json::to_writer(&mut SizeCounter { size: 0, limit }, value).is_err()
+}
+
+/// Enforces the Ethereum RPC limits.
+#[derive(Clone)]
+pub struct RpcLimits {
+	config: RpcLimitsConfig,
+}
+
+impl RpcLimits {
+	pub fn new(config: RpcLimitsConfig) -> Self {
+		Self { config }
+	}
+
+	/// Wraps the Ethereum methods of `io` so that their parameters and results are limited.
+	pub fn apply<M: Metadata>(self: Arc<Self>, io: IoHandler<M>) -> IoHandler<M> {
+		let mut limited = IoHandler::default();
+		limited.extend_with(io.iter().map(|(name, procedure)| {
+			let procedure = match procedure {
+				RemoteProcedure::Method(method)
+					if LIMITED_NAMESPACES.iter().any(|ns| name.starts_with(ns)) =>
+				{
+					RemoteProcedure::Method(Arc::new(LimitedMethod {
+						method: method.clone(),
+						limits: self.clone(),
+					}) as Arc<dyn RpcMethod<M>>)
+				}
+				other => other.clone(),
+			};
+			(name.clone(), procedure)
+		}));
+		limited
+	}
+}
+
+impl<M: Metadata> Middleware<M> for RpcLimits {
+	type Future = BoxFuture<Option<Response>>;
+	type CallFuture = NoopCallFuture;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+	where
+		F: Fn(Request, M) -> X + Send + Sync,
+		X: std::future::Future<Output = Option<Response>> + Send + 'static,
+	{
+		match request {
+			Request::Batch(calls) if calls.len() > self.config.max_batch_len => {
+				let response = batch_too_large_err(self.config.max_batch_len, calls.len());
+				Either::Left(Box::pin(future::ready(Some(response))))
+			}
+			Request::Batch(calls) => {
+				let max_response_size = self.config.max_response_size;
+				Either::Left(Box::pin(next(Request::Batch(calls), meta).map(
+					move |response| match response {
+						Some(Response::Batch(outputs)) if exceeds(&outputs, max_response_size) => {
+							let error = response_too_large_err(max_response_size);
+							Some(Response::from(error, Some(Version::V2)))
+						}
+						other => other,
+					},
+				)))
+			}
+			request => Either::Right(next(request, meta)),
+		}
+	}
+}
+
+struct LimitedMethod<M: Metadata> {
+	method: Arc<dyn RpcMethod<M>>,
+	limits: Arc<RpcLimits>,
+}
+
+impl<M: Metadata> RpcMethod<M> for LimitedMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Result<Value>> {
+		let config = &self.limits.config;
+		if exceeds(&params, config.max_request_size) {
+			return Box::pin(future::err(request_too_large_err(config.max_request_size)));
+		}
+
+		let max_response_size = config.max_response_size;
+		Box::pin(self.method.call(params, meta).map(move |result| {
+			let value = result?;
+			if exceeds(&value, max_response_size) {
+				return Err(response_too_large_err(max_response_size));
+			}
+			Ok(value)
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpc_core::MetaIoHandler;
+
+	fn config() -> RpcLimitsConfig {
+		RpcLimitsConfig {
+			max_batch_len: 2,
+			max_request_size: 64,
+			max_response_size: 64,
+		}
+	}
+
+	/// `eth_repeat` returns a string of the given length.
+	fn handler() -> MetaIoHandler<(), RpcLimits> {
+		let mut io = IoHandler::new();
+		for name in &["eth_repeat", "other_repeat"] {
+			io.add_sync_method(name, |params: Params| {
+				let (len,): (usize,) = params.parse()?;
+				Ok(Value::String("a".repeat(len)))
+			});
+		}
+		let limits = RpcLimits::new(config());
+		let mut limited = MetaIoHandler::with_middleware(limits.clone());
+		let io = Arc::new(limits).apply(io);
+		limited.extend_with(
+			io.iter()
+				.map(|(name, procedure)| (name.clone(), procedure.clone())),
+		);
+		limited
+	}
+
+	fn call(id: u64, len: usize, method: &str) -> String {
+		format!(
+			r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":[{}]}}"#,
+			id, method, len
+		)
+	}
+
+	fn handle(request: &str) -> Value {
+		serde_json::from_str(&handler().handle_request_sync(request, ()).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn sizes_are_counted_up_to_the_limit() {
+		assert!(!exceeds(&"a".repeat(8), 10));
+		assert!(exceeds(&"a".repeat(9), 10));
+		assert!(!exceeds(&Value::Null, 4));
+		assert!(exceeds(&Value::Null, 3));
+	}
+
+	#[test]
+	fn calls_within_the_limits_are_served() {
+		assert_eq!(handle(&call(1, 8, "eth_repeat"))["result"], "a".repeat(8));
+	}
+
+	#[test]
+	fn oversized_requests_and_responses_fail() {
+		let response = handle(&call(1, 100, "eth_repeat"));
+		assert_eq!(response["error"]["code"], -32003);
+		assert_eq!(response["error"]["message"], "response too large");
+		assert_eq!(response["error"]["data"]["limit"], 64);
+
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","id":1,"method":"eth_repeat","params":[1,"{}"]}}"#,
+			"a".repeat(100)
+		);
+		let response = handle(&request);
+		assert_eq!(response["error"]["code"], -32600);
+		assert_eq!(response["error"]["message"], "request too large");
+	}
+
+	#[test]
+	fn other_namespaces_are_not_limited() {
+		assert_eq!(
+			handle(&call(1, 100, "other_repeat"))["result"],
+			"a".repeat(100)
+		);
+	}
+
+	#[test]
+	fn batches_are_limited() {
+		let batch = |calls: Vec<String>| format!("[{}]", calls.join(","));
+
+		let response = handle(&batch(vec![
+			call(1, 1, "eth_repeat"),
+			call(2, 1, "eth_repeat"),
+			call(3, 1, "eth_repeat"),
+		]));
+		assert_eq!(response["error"]["message"], "batch too large");
+		assert_eq!(
+			response["error"]["data"],
+			serde_json::json!({ "limit": 2, "size": 3 })
+		);
+
+		let response = handle(&batch(vec![
+			call(1, 1, "eth_repeat"),
+			call(2, 1, "eth_repeat"),
+		]));
+		assert_eq!(response.as_array().map(Vec::len), Some(2));
+
+		// Each result is within the limit, not the whole batch.
+		let response = handle(&batch(vec![
+			call(1, 30, "eth_repeat"),
+			call(2, 30, "eth_repeat"),
+		]));
+		assert_eq!(response["error"]["message"], "response too large");
+	}
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Planning of the log queries over the SQL log index.
+//!
+//! The index gives the blocks of a range holding logs which may match a filter. The indexed
+//! blocks not among them are skipped, the others and the blocks missing from the index are
+//! read and filtered as without the index.
+
+use ethereum_types::H256;
+use fc_db::{LogIndexQuery, INDEXED_TOPICS};
+use fc_rpc_core::types::{Filter, VariadicValue};
+use log::warn;
+use sp_runtime::traits::{Block as BlockT, NumberFor, UniqueSaturatedInto};
+use std::collections::BTreeSet;
+
+/// Condition on the indexed logs matching at least the logs matching `filter`.
+pub fn log_index_query(filter: &Filter) -> LogIndexQuery {
+	let addresses = match &filter.address {
+		Some(VariadicValue::Single(address)) => vec![*address],
+		Some(VariadicValue::Multiple(addresses)) => addresses.clone(),
+		_ => Vec::new(),
+	};
+	// A position matches any topic when it is null or lists a null.
+	let topics = match &filter.topics {
+		Some(VariadicValue::Single(Some(topic))) => vec![position_topics(topic)],
+		Some(VariadicValue::Multiple(positions)) => positions
+			.iter()
+			.take(INDEXED_TOPICS)
+			.map(|position| position.as_ref().map(position_topics).unwrap_or_default())
+			.collect(),
+		_ => Vec::new(),
+	};
+	LogIndexQuery { addresses, topics }
+}
+
+fn position_topics(position: &VariadicValue<Option<H256>>) -> Vec<H256> {
+	match position {
+		VariadicValue::Single(Some(topic)) => vec![*topic],
+		VariadicValue::Multiple(topics) => topics
+			.iter()
+			.cloned()
+			.collect::<Option<_>>()
+			.unwrap_or_default(),
+		_ => Vec::new(),
+	}
+}
+
+/// Blocks of a range the log index tells apart.
+pub struct LogIndexPlan<B: BlockT> {
+	indexed: BTreeSet<B::Hash>,
+	matching: BTreeSet<B::Hash>,
+}
+
+impl<B: BlockT<Hash = H256>> LogIndexPlan<B> {
+	/// Plan of the query of the logs matching `filter` in the blocks `from` to `to`, None when
+	/// the index is disabled or fails.
+	pub fn new(
+		backend: &fc_db::Backend<B>,
+		filter: &Filter,
+		from: NumberFor<B>,
+		to: NumberFor<B>,
+	) -> Option<Self> {
+		let log_index = backend.log_index()?;
+		let from: u64 = from.unique_saturated_into();
+		let to: u64 = to.unique_saturated_into();
+		let plan = log_index.indexed_blocks(from, to).and_then(|indexed| {
+			let matching = log_index.matching_blocks(&log_index_query(filter), from, to)?;
+			Ok(Self {
+				indexed: indexed.into_iter().collect(),
+				matching: matching.into_iter().collect(),
+			})
+		});
+		match plan {
+			Ok(plan) => Some(plan),
+			Err(err) => {
+				warn!("Log index query failed, scanning the blocks: {}", err);
+				None
+			}
+		}
+	}
+
+	/// Whether the block `hash` holds no log matching the filter according to the index.
+	pub fn skips(&self, hash: &B::Hash) -> bool {
+		self.indexed.contains(hash) && !self.matching.contains(hash)
+	}
+}
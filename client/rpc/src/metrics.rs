@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the EVM activity of the best chain.
+//!
+//! The metrics are computed from the Ethereum blocks, statuses and receipts the runtime stores,
+//! as the blocks become best, so operators can chart the EVM activity without an indexer. The
+//! counters only grow: the blocks retracted by a re-org are not subtracted.
+
+use ethereum_types::H160;
+use prometheus_endpoint::{
+	register, Counter, CounterVec, Gauge, Opts, PrometheusError, Registry, U64,
+};
+
+/// EVM activity of a block of the best chain.
+pub(crate) struct BlockActivity {
+	/// Gas used by the block.
+	pub gas_used: u64,
+	/// Number of message call transactions.
+	pub calls: u64,
+	/// Number of contract creation transactions.
+	pub creates: u64,
+	/// Number of contracts successfully created.
+	pub contracts_created: u64,
+	/// Precompiles called in the block with their number of calls, empty unless the runtime
+	/// counts them.
+	pub precompile_calls: Vec<(H160, u32)>,
+}
+
+/// EVM activity metrics, updated by `EthTask::evm_metrics_task`.
+#[derive(Clone)]
+pub struct EvmMetrics {
+	best_block_gas_used: Gauge<U64>,
+	gas_used: Counter<U64>,
+	transactions: CounterVec<U64>,
+	contracts_created: Counter<U64>,
+	precompile_calls: CounterVec<U64>,
+}
+
+impl EvmMetrics {
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			best_block_gas_used: register(
+				Gauge::new(
+					"frontier_evm_best_block_gas_used",
+					"Gas used by the best Ethereum block",
+				)?,
+				registry,
+			)?,
+			gas_used: register(
+				Counter::new(
+					"frontier_evm_gas_used_total",
+					"Gas used by the Ethereum blocks of the best chain",
+				)?,
+				registry,
+			)?,
+			transactions: register(
+				CounterVec::new(
+					Opts::new(
+						"frontier_evm_transactions_total",
+						"Ethereum transactions of the best chain, by transaction type and kind",
+					),
+					&["type", "kind"],
+				)?,
+				registry,
+			)?,
+			contracts_created: register(
+				Counter::new(
+					"frontier_evm_contracts_created_total",
+					"Contracts successfully created by the Ethereum transactions of the best chain",
+				)?,
+				registry,
+			)?,
+			precompile_calls: register(
+				CounterVec::new(
+					Opts::new(
+						"frontier_evm_precompile_calls_total",
+						"Precompile calls of the best chain, by precompile address",
+					),
+					&["address"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	pub(crate) fn observe_block(&self, activity: BlockActivity) {
+		self.best_block_gas_used.set(activity.gas_used);
+		self.gas_used.inc_by(activity.gas_used);
+		// The runtime only executes legacy transactions.
+		self.transactions
+			.with_label_values(&["legacy", "call"])
+			.inc_by(activity.calls);
+		self.transactions
+			.with_label_values(&["legacy", "create"])
+			.inc_by(activity.creates);
+		self.contracts_created.inc_by(activity.contracts_created);
+		for (address, calls) in activity.precompile_calls {
+			self.precompile_calls
+				.with_label_values(&[&format!("{:?}", address)])
+				.inc_by(calls as u64);
+		}
+	}
+}
@@ -55,11 +55,11 @@ pub trait StorageOverride<Block: BlockT> {
 	) -> Option<Vec<TransactionStatus>>;
 }
 
-fn storage_prefix_build(module: &[u8], storage: &[u8]) -> Vec<u8> {
+pub(crate) fn storage_prefix_build(module: &[u8], storage: &[u8]) -> Vec<u8> {
 	[twox_128(module), twox_128(storage)].concat().to_vec()
 }
 
-fn blake2_128_extend(bytes: &[u8]) -> Vec<u8> {
+pub(crate) fn blake2_128_extend(bytes: &[u8]) -> Vec<u8> {
 	let mut ext: Vec<u8> = blake2_128(bytes).to_vec();
 	ext.extend_from_slice(bytes);
 	ext
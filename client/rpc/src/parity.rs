@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{eth::transaction_build, frontier_backend_client, internal_err};
+use codec::Encode;
+use ethereum::TransactionV0 as EthereumTransaction;
+use ethereum_types::{H256, U256};
+use fc_rpc_core::{
+	types::{LocalTransactionStatus, Transaction},
+	ParityApi as ParityApiT,
+};
+use fp_rpc::{ConvertTransaction, EthereumRuntimeRPCApi};
+use futures::future::TryFutureExt;
+use jsonrpc_core::{futures::future, BoxFuture, Result};
+use sc_rpc::DenyUnsafe;
+use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	traits::{Block as BlockT, UniqueSaturatedInto},
+	transaction_validity::TransactionSource,
+};
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+pub use fc_rpc_core::ParityApiServer;
+
+pub struct ParityApi<B: BlockT, C, P, CT> {
+	client: Arc<C>,
+	pool: Arc<P>,
+	convert_transaction: CT,
+	backend: Arc<fc_db::Backend<B>>,
+	deny_unsafe: DenyUnsafe,
+	_marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C, P, CT> ParityApi<B, C, P, CT> {
+	pub fn new(
+		client: Arc<C>,
+		pool: Arc<P>,
+		convert_transaction: CT,
+		backend: Arc<fc_db::Backend<B>>,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self {
+			client,
+			pool,
+			convert_transaction,
+			backend,
+			deny_unsafe,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C, P, CT> ParityApi<B, C, P, CT>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	P: TransactionPool<Block = B> + Send + Sync + 'static,
+	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
+{
+	fn local_transaction(&self, transaction_hash: H256) -> Result<EthereumTransaction> {
+		let raw = self
+			.backend
+			.local_transactions()
+			.transaction(&transaction_hash)
+			.map_err(|err| internal_err(format!("fetch local transaction failed: {:?}", err)))?;
+		raw.and_then(|raw| rlp::decode(&raw[..]).ok())
+			.ok_or_else(|| internal_err("transaction was not submitted through this node"))
+	}
+
+	fn local_transaction_status(
+		&self,
+		transaction_hash: H256,
+		transaction: EthereumTransaction,
+	) -> Result<LocalTransactionStatus> {
+		let mut rich_transaction = transaction_build(transaction, None, None);
+
+		if let Some((ethereum_block_hash, index)) =
+			frontier_backend_client::load_transactions::<B, C>(
+				self.client.as_ref(),
+				self.backend.as_ref(),
+				transaction_hash,
+				true,
+			)? {
+			let block_number = frontier_backend_client::load_hash::<B>(
+				self.backend.as_ref(),
+				ethereum_block_hash,
+			)?
+			.and_then(|id| self.client.block_number_from_id(&id).ok().flatten())
+			.map(|number| U256::from(UniqueSaturatedInto::<u64>::unique_saturated_into(number)));
+
+			rich_transaction.block_hash = Some(ethereum_block_hash);
+			rich_transaction.block_number = block_number;
+			rich_transaction.transaction_index = Some(U256::from(index));
+			return Ok(LocalTransactionStatus::Mined(rich_transaction));
+		}
+
+		let tag = (rich_transaction.from, rich_transaction.nonce).encode();
+		if self
+			.pool
+			.ready()
+			.any(|transaction| transaction.provides().contains(&tag))
+		{
+			return Ok(LocalTransactionStatus::Pending);
+		}
+
+		let best_nonce = self
+			.client
+			.runtime_api()
+			.account_basic(
+				&BlockId::Hash(self.client.info().best_hash),
+				rich_transaction.from,
+			)
+			.map_err(|err| internal_err(format!("fetch runtime account basic failed: {:?}", err)))?
+			.nonce;
+
+		if rich_transaction.nonce < best_nonce {
+			// The nonce was used by another transaction.
+			Ok(LocalTransactionStatus::Culled(rich_transaction))
+		} else {
+			Ok(LocalTransactionStatus::Future)
+		}
+	}
+}
+
+impl<B, C, P, CT> ParityApiT for ParityApi<B, C, P, CT>
+where
+	B: BlockT<Hash = H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	P: TransactionPool<Block = B> + Send + Sync + 'static,
+	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
+{
+	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
+		self.deny_unsafe.check_if_safe()?;
+		let journal = self.backend.local_transactions();
+		let hashes = journal
+			.hashes()
+			.map_err(|err| internal_err(format!("fetch local transactions failed: {:?}", err)))?;
+
+		let mut statuses = BTreeMap::new();
+		for transaction_hash in hashes {
+			let raw = journal.transaction(&transaction_hash).map_err(|err| {
+				internal_err(format!("fetch local transaction failed: {:?}", err))
+			})?;
+			let transaction = match raw.and_then(|raw| rlp::decode(&raw[..]).ok()) {
+				Some(transaction) => transaction,
+				None => continue,
+			};
+
+			statuses.insert(
+				transaction_hash,
+				self.local_transaction_status(transaction_hash, transaction)?,
+			);
+		}
+
+		Ok(statuses)
+	}
+
+	fn remove_transaction(&self, transaction_hash: H256) -> Result<Option<Transaction>> {
+		self.deny_unsafe.check_if_safe()?;
+		let transaction = self.local_transaction(transaction_hash)?;
+		let pool_hash = self.pool.hash_of(
+			&self
+				.convert_transaction
+				.convert_transaction(transaction.clone()),
+		);
+
+		// The pool bans the removed transaction, so it cannot come back from the network either.
+		if self.pool.remove_invalid(&[pool_hash]).is_empty() {
+			return Ok(None);
+		}
+		self.backend
+			.local_transactions()
+			.remove(&transaction_hash)
+			.map_err(|err| internal_err(format!("remove local transaction failed: {:?}", err)))?;
+
+		Ok(Some(transaction_build(transaction, None, None)))
+	}
+
+	fn rebroadcast_transaction(&self, transaction_hash: H256) -> BoxFuture<Result<bool>> {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			return Box::pin(future::err(err.into()));
+		}
+		let transaction = match self.local_transaction(transaction_hash) {
+			Ok(transaction) => transaction,
+			Err(err) => return Box::pin(future::err(err)),
+		};
+		let extrinsic = self.convert_transaction.convert_transaction(transaction);
+
+		// The network keeps propagating the ready transactions to the peers lacking them.
+		if self
+			.pool
+			.ready_transaction(&self.pool.hash_of(&extrinsic))
+			.is_some()
+		{
+			return Box::pin(future::ok(false));
+		}
+
+		let hash = self.client.info().best_hash;
+		Box::pin(
+			self.pool
+				.submit_one(&BlockId::hash(hash), TransactionSource::Local, extrinsic)
+				.map_ok(|_| true)
+				.map_err(|err| {
+					internal_err(format!("submit transaction to pool failed: {:?}", err))
+				}),
+		)
+	}
+}
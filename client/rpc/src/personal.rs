@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{recover_personal_message_signer, sign_personal_message, EthSigner};
+use ethereum_types::H160;
+use fc_rpc_core::{types::Bytes, PersonalApi as PersonalApiT};
+use jsonrpc_core::Result;
+use sc_rpc::DenyUnsafe;
+
+pub use fc_rpc_core::PersonalApiServer;
+
+pub struct PersonalApi {
+	signers: Vec<Box<dyn EthSigner>>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl PersonalApi {
+	pub fn new(signers: Vec<Box<dyn EthSigner>>, deny_unsafe: DenyUnsafe) -> Self {
+		Self {
+			signers,
+			deny_unsafe,
+		}
+	}
+}
+
+impl PersonalApiT for PersonalApi {
+	fn sign(&self, data: Bytes, address: H160, _password: Option<String>) -> Result<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+		sign_personal_message(&self.signers, &data.0, &address).map(Bytes)
+	}
+
+	fn ec_recover(&self, data: Bytes, signature: Bytes) -> Result<H160> {
+		recover_personal_message_signer(&data.0, &signature.0)
+	}
+}
@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Cost based throttling of the RPC methods.
+//!
+//! Every method is assigned a cost, which is taken from a global token bucket and, for
+//! connections carrying a session (websocket, ipc), from a per-connection token bucket.
+//! Requests are rejected with a `limit exceeded` error when either bucket is exhausted.
+//!
+//! HTTP requests carry no session: they are only subject to the global bucket, whatever the
+//! per-connection rate. Throttling HTTP clients individually is left to a reverse proxy.
+
+use jsonrpc_core::{
+	futures::future, BoxFuture, Error, ErrorCode, IoHandler, Metadata, Params, RemoteProcedure,
+	Result, RpcMethod, Value,
+};
+use jsonrpc_pubsub::PubSubMetadata;
+use parking_lot::Mutex;
+use std::{
+	collections::{BTreeMap, HashMap},
+	sync::Arc,
+	time::Instant,
+};
+
+/// Rate limiting configuration.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitConfig {
+	/// Tokens per second shared by all the connections. `None` disables the global limit.
+	pub global_rate: Option<u32>,
+	/// Tokens per second available to each connection. `None` disables the per-connection limit.
+	pub connection_rate: Option<u32>,
+	/// Method costs overriding the defaults.
+	pub method_costs: BTreeMap<String, u32>,
+}
+
+/// Default cost of a method.
+pub fn default_method_cost(method: &str) -> u32 {
+	match method {
+		"eth_getLogs" | "eth_getFilterLogs" | "eth_getFilterChanges" | "frontier_getLogs" => 50,
+		"eth_call" | "eth_estimateGas" | "eth_createAccessList" => 20,
+		"eth_getBlockByHash"
+		| "eth_getBlockByNumber"
+		| "eth_getTransactionReceipt"
+		| "eth_sendTransaction"
+		| "eth_sendRawTransaction"
+		| "parity_rebroadcastTransaction" => 5,
+		method if method.starts_with("debug_") || method.starts_with("trace_") => 100,
+		_ => 1,
+	}
+}
+
+struct TokenBucket {
+	capacity: f64,
+	tokens: f64,
+	last_refill: Instant,
+}
+
+impl TokenBucket {
+	fn new(rate: u32) -> Self {
+		Self {
+			capacity: rate as f64,
+			tokens: rate as f64,
+			last_refill: Instant::now(),
+		}
+	}
+
+	fn refill(&mut self) {
+		let now = Instant::now();
+		let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+		self.tokens = (self.tokens + elapsed * self.capacity).min(self.capacity);
+		self.last_refill = now;
+	}
+
+	/// A cost above the capacity would never be satisfied, it is capped to a full bucket.
+	fn cost(&self, cost: u32) -> f64 {
+		(cost as f64).min(self.capacity)
+	}
+
+	fn has(&mut self, cost: u32) -> bool {
+		self.refill();
+		self.tokens >= self.cost(cost)
+	}
+
+	fn take(&mut self, cost: u32) {
+		self.tokens -= self.cost(cost);
+	}
+}
+
+/// Shared token buckets.
+pub struct RateLimiter {
+	config: RateLimitConfig,
+	global: Option<Mutex<TokenBucket>>,
+	connections: Arc<Mutex<HashMap<usize, TokenBucket>>>,
+}
+
+impl RateLimiter {
+	pub fn new(config: RateLimitConfig) -> Self {
+		Self {
+			global: config
+				.global_rate
+				.map(|rate| Mutex::new(TokenBucket::new(rate))),
+			connections: Arc::new(Mutex::new(HashMap::new())),
+			config,
+		}
+	}
+
+	/// Cost of the given method.
+	pub fn method_cost(&self, method: &str) -> u32 {
+		self.config
+			.method_costs
+			.get(method)
+			.cloned()
+			.unwrap_or_else(|| default_method_cost(method))
+	}
+
+	/// Takes `cost` tokens from the buckets, returns false if any of them is exhausted.
+	fn try_acquire<M: PubSubMetadata>(&self, meta: &M, cost: u32) -> bool {
+		let mut global = self.global.as_ref().map(|global| global.lock());
+		if let Some(global) = global.as_mut() {
+			if !global.has(cost) {
+				return false;
+			}
+		}
+
+		if let (Some(rate), Some(session)) = (self.config.connection_rate, meta.session()) {
+			let id = Arc::as_ptr(&session) as usize;
+			let mut connections = self.connections.lock();
+			let bucket = connections.entry(id).or_insert_with(|| {
+				let connections = self.connections.clone();
+				session.on_drop(move || {
+					connections.lock().remove(&id);
+				});
+				TokenBucket::new(rate)
+			});
+			if !bucket.has(cost) {
+				return false;
+			}
+			bucket.take(cost);
+		}
+
+		if let Some(global) = global.as_mut() {
+			global.take(cost);
+		}
+
+		true
+	}
+
+	/// Wraps all the methods of `io` so that they are throttled by this rate limiter.
+	pub fn apply<M>(self: Arc<Self>, io: IoHandler<M>) -> IoHandler<M>
+	where
+		M: PubSubMetadata + Default,
+	{
+		let mut limited = IoHandler::default();
+		limited.extend_with(io.iter().map(|(name, procedure)| {
+			let procedure = match procedure {
+				RemoteProcedure::Method(method) => {
+					RemoteProcedure::Method(Arc::new(RateLimitedMethod {
+						method: method.clone(),
+						cost: self.method_cost(name),
+						limiter: self.clone(),
+					}) as Arc<dyn RpcMethod<M>>)
+				}
+				other => other.clone(),
+			};
+			(name.clone(), procedure)
+		}));
+		limited
+	}
+}
+
+struct RateLimitedMethod<M: Metadata> {
+	method: Arc<dyn RpcMethod<M>>,
+	cost: u32,
+	limiter: Arc<RateLimiter>,
+}
+
+impl<M: PubSubMetadata> RpcMethod<M> for RateLimitedMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Result<Value>> {
+		if !self.limiter.try_acquire(&meta, self.cost) {
+			return Box::pin(future::err(Error {
+				// EIP-1474 `limit exceeded`.
+				code: ErrorCode::ServerError(-32005),
+				message: "request rate limit exceeded".to_string(),
+				data: None,
+			}));
+		}
+		self.method.call(params, meta)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::channel::mpsc;
+	use jsonrpc_pubsub::Session;
+
+	#[derive(Clone, Default)]
+	struct TestMetadata(Option<Arc<Session>>);
+
+	impl Metadata for TestMetadata {}
+
+	impl PubSubMetadata for TestMetadata {
+		fn session(&self) -> Option<Arc<Session>> {
+			self.0.clone()
+		}
+	}
+
+	fn session() -> TestMetadata {
+		let (sender, _) = mpsc::unbounded();
+		TestMetadata(Some(Arc::new(Session::new(sender))))
+	}
+
+	fn limited_io(config: RateLimitConfig) -> IoHandler<TestMetadata> {
+		let mut io = IoHandler::default();
+		io.add_sync_method("eth_chainId", |_| Ok(Value::String("0x2a".to_string())));
+		io.add_sync_method("eth_getLogs", |_| Ok(Value::Array(Vec::new())));
+		Arc::new(RateLimiter::new(config)).apply(io)
+	}
+
+	fn call(io: &IoHandler<TestMetadata>, method: &str, meta: TestMetadata) -> bool {
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":[],"id":1}}"#,
+			method
+		);
+		let response = io.handle_request_sync(&request, meta).unwrap();
+		!response.contains("-32005")
+	}
+
+	#[test]
+	fn method_costs() {
+		let limiter = RateLimiter::new(RateLimitConfig {
+			method_costs: vec![("eth_call".to_string(), 7)].into_iter().collect(),
+			..Default::default()
+		});
+		assert_eq!(limiter.method_cost("eth_call"), 7);
+		assert_eq!(limiter.method_cost("eth_getLogs"), 50);
+		assert_eq!(limiter.method_cost("debug_traceTransaction"), 100);
+		assert_eq!(limiter.method_cost("eth_chainId"), 1);
+	}
+
+	#[test]
+	fn global_limit_applies_to_all_connections() {
+		let io = limited_io(RateLimitConfig {
+			global_rate: Some(2),
+			..Default::default()
+		});
+		assert!(call(&io, "eth_chainId", TestMetadata::default()));
+		assert!(call(&io, "eth_chainId", session()));
+		assert!(!call(&io, "eth_chainId", TestMetadata::default()));
+		assert!(!call(&io, "eth_chainId", session()));
+	}
+
+	#[test]
+	fn connection_limit_applies_to_sessions_only() {
+		let io = limited_io(RateLimitConfig {
+			connection_rate: Some(1),
+			..Default::default()
+		});
+		let first = session();
+		assert!(call(&io, "eth_chainId", first.clone()));
+		assert!(!call(&io, "eth_chainId", first));
+		assert!(call(&io, "eth_chainId", session()));
+
+		// HTTP requests have no session to account them to.
+		for _ in 0..3 {
+			assert!(call(&io, "eth_chainId", TestMetadata::default()));
+		}
+	}
+
+	#[test]
+	fn cost_above_capacity_takes_a_full_bucket() {
+		let io = limited_io(RateLimitConfig {
+			global_rate: Some(10),
+			..Default::default()
+		});
+		assert!(call(&io, "eth_getLogs", TestMetadata::default()));
+		assert!(!call(&io, "eth_chainId", TestMetadata::default()));
+	}
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Asynchronous JSON-RPC client of the remote nodes the RPC forwards requests to.
+
+use crate::internal_err;
+use hyper::{client::HttpConnector, header, Body, Client, Request};
+use hyper_rustls::HttpsConnector;
+use jsonrpc_core::{Result, Value};
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// Remote Ethereum compatible node, reached over HTTP(S).
+#[derive(Clone)]
+pub struct RemoteNode {
+	url: String,
+	timeout: Duration,
+	client: Client<HttpsConnector<HttpConnector>>,
+}
+
+impl RemoteNode {
+	pub fn new(url: String, timeout: Duration) -> Self {
+		Self {
+			url,
+			timeout,
+			client: Client::builder().build(HttpsConnector::with_native_roots()),
+		}
+	}
+
+	/// Sends the request, returns its result or the error returned by the remote node.
+	pub async fn request<P: Serialize>(&self, method: &str, params: P) -> Result<Value> {
+		let request = Request::post(self.url.as_str())
+			.header(header::CONTENT_TYPE, "application/json")
+			.body(Body::from(
+				json!({
+					"jsonrpc": "2.0",
+					"id": 1,
+					"method": method,
+					"params": params,
+				})
+				.to_string(),
+			))
+			.map_err(|err| internal_err(format!("invalid remote request: {}", err)))?;
+		let body = async {
+			let response = self.client.request(request).await?;
+			hyper::body::to_bytes(response.into_body()).await
+		};
+		let body = tokio::time::timeout(self.timeout, body)
+			.await
+			.map_err(|_| internal_err(format!("no remote response after {:?}", self.timeout)))?
+			.map_err(|err| internal_err(format!("remote request failed: {}", err)))?;
+
+		let mut response: Value = serde_json::from_slice(&body)
+			.map_err(|err| internal_err(format!("invalid remote response: {}", err)))?;
+		if let Some(error) = response.get("error") {
+			return Err(serde_json::from_value(error.clone())
+				.unwrap_or_else(|_| internal_err(format!("remote error: {}", error))));
+		}
+		match response.get_mut("result") {
+			Some(result) => Ok(result.take()),
+			None => Err(internal_err("missing remote result")),
+		}
+	}
+}
@@ -0,0 +1,396 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! In-memory cache of the responses of the idempotent read methods.
+//!
+//! Successful responses are cached by method, parameters and best block hash for the time to
+//! live of their method, so the bursts of identical requests of polling front-ends are served
+//! without executing them again. A new best block changes the key, the cached responses of the
+//! previous head are never served for the new one and expire with their time to live. The
+//! requests of the `pending` block depend on the transaction pool, they are never cached. When
+//! full, the least recently used response makes room for the new one.
+
+use ethereum_types::H256;
+use fc_rpc_core::types::ResponseCacheStats;
+use futures::FutureExt;
+use jsonrpc_core::{
+	futures::future, BoxFuture, IoHandler, Metadata, Params, RemoteProcedure, Result, RpcMethod,
+	Value,
+};
+use jsonrpc_pubsub::PubSubMetadata;
+use lru::LruCache;
+use parking_lot::Mutex;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	collections::BTreeMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Response cache configuration.
+#[derive(Clone, Debug)]
+pub struct ResponseCacheConfig {
+	/// Maximum number of cached responses.
+	pub max_entries: usize,
+	/// Method times to live overriding the defaults, a zero duration disables the caching of
+	/// the method.
+	pub method_ttls: BTreeMap<String, Duration>,
+}
+
+impl Default for ResponseCacheConfig {
+	fn default() -> Self {
+		Self {
+			max_entries: 10_000,
+			method_ttls: BTreeMap::new(),
+		}
+	}
+}
+
+/// Default time to live of the responses of a method, None for the methods which are not
+/// cached. `eth_getTransactionByHash` is not, it returns the transactions of the pool.
+pub fn default_method_ttl(method: &str) -> Option<Duration> {
+	match method {
+		"eth_chainId" | "net_version" | "web3_clientVersion" => Some(Duration::from_secs(60)),
+		"eth_gasPrice" | "eth_maxPriorityFeePerGas" => Some(Duration::from_secs(1)),
+		"eth_blockNumber"
+		| "eth_getTransactionCount"
+		| "eth_getBalance"
+		| "eth_getCode"
+		| "eth_getStorageAt"
+		| "eth_call"
+		| "eth_estimateGas"
+		| "eth_getBlockByHash"
+		| "eth_getBlockByNumber"
+		| "eth_getBlockTransactionCountByHash"
+		| "eth_getBlockTransactionCountByNumber"
+		| "eth_getTransactionByBlockHashAndIndex"
+		| "eth_getTransactionByBlockNumberAndIndex"
+		| "eth_getTransactionReceipt"
+		| "eth_getLogs"
+		| "eth_feeHistory" => Some(Duration::from_secs(2)),
+		_ => None,
+	}
+}
+
+/// Whether the parameters refer to the `pending` block, whose state depends on the transaction
+/// pool.
+fn is_pending(params: &Params) -> bool {
+	fn is_pending_value(value: &Value) -> bool {
+		match value {
+			Value::String(tag) => tag == "pending",
+			Value::Array(values) => values.iter().any(is_pending_value),
+			Value::Object(map) => map.values().any(is_pending_value),
+			_ => false,
+		}
+	}
+	match params {
+		Params::None => false,
+		Params::Array(values) => values.iter().any(is_pending_value),
+		Params::Map(map) => map.values().any(is_pending_value),
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+	method: String,
+	params: String,
+	head: H256,
+}
+
+struct CacheEntry {
+	response: Value,
+	expires: Instant,
+}
+
+#[derive(Default)]
+struct MethodStats {
+	hits: u64,
+	misses: u64,
+}
+
+/// Cached responses, shared by all the connections.
+pub struct ResponseCache {
+	config: ResponseCacheConfig,
+	best_hash: Box<dyn Fn() -> H256 + Send + Sync>,
+	entries: Mutex<LruCache<CacheKey, CacheEntry>>,
+	stats: Mutex<BTreeMap<String, MethodStats>>,
+}
+
+impl ResponseCache {
+	pub fn new<B, C>(client: Arc<C>, config: ResponseCacheConfig) -> Self
+	where
+		B: BlockT<Hash = H256>,
+		C: HeaderBackend<B> + Send + Sync + 'static,
+	{
+		Self::with_best_hash(config, Box::new(move || client.info().best_hash))
+	}
+
+	fn with_best_hash(
+		config: ResponseCacheConfig,
+		best_hash: Box<dyn Fn() -> H256 + Send + Sync>,
+	) -> Self {
+		Self {
+			entries: Mutex::new(LruCache::new(config.max_entries)),
+			config,
+			best_hash,
+			stats: Mutex::new(BTreeMap::new()),
+		}
+	}
+
+	/// Time to live of the responses of the given method, None if they are not cached.
+	pub fn method_ttl(&self, method: &str) -> Option<Duration> {
+		self.config
+			.method_ttls
+			.get(method)
+			.cloned()
+			.or_else(|| default_method_ttl(method))
+			.filter(|ttl| *ttl > Duration::from_secs(0))
+	}
+
+	/// Hits and misses counted so far, by method.
+	pub fn stats(&self) -> Vec<ResponseCacheStats> {
+		let mut stats: Vec<_> = self
+			.stats
+			.lock()
+			.iter()
+			.map(|(method, stats)| ResponseCacheStats {
+				method: method.clone(),
+				hits: stats.hits,
+				misses: stats.misses,
+				entries: 0,
+			})
+			.collect();
+		for (key, _) in self.entries.lock().iter() {
+			if let Some(stats) = stats.iter_mut().find(|stats| stats.method == key.method) {
+				stats.entries += 1;
+			}
+		}
+		stats
+	}
+
+	fn get(&self, key: &CacheKey) -> Option<Value> {
+		let now = Instant::now();
+		let mut entries = self.entries.lock();
+		let response = match entries.get(key) {
+			Some(entry) if entry.expires > now => Some(entry.response.clone()),
+			Some(_) => {
+				entries.pop(key);
+				None
+			}
+			None => None,
+		};
+		drop(entries);
+
+		let mut stats = self.stats.lock();
+		let stats = stats.entry(key.method.clone()).or_default();
+		if response.is_some() {
+			stats.hits += 1;
+		} else {
+			stats.misses += 1;
+		}
+		response
+	}
+
+	fn insert(&self, key: CacheKey, response: Value, ttl: Duration) {
+		if self.config.max_entries == 0 {
+			return;
+		}
+		self.entries.lock().put(
+			key,
+			CacheEntry {
+				response,
+				expires: Instant::now() + ttl,
+			},
+		);
+	}
+
+	/// Wraps the cached methods of `io` so that their responses are served from this cache.
+	pub fn apply<M>(self: Arc<Self>, io: IoHandler<M>) -> IoHandler<M>
+	where
+		M: PubSubMetadata + Default,
+	{
+		let mut cached = IoHandler::default();
+		cached.extend_with(io.iter().map(|(name, procedure)| {
+			let procedure = match (procedure, self.method_ttl(name)) {
+				(RemoteProcedure::Method(method), Some(ttl)) => {
+					RemoteProcedure::Method(Arc::new(CachedMethod {
+						name: name.clone(),
+						method: method.clone(),
+						ttl,
+						cache: self.clone(),
+					}) as Arc<dyn RpcMethod<M>>)
+				}
+				(other, _) => other.clone(),
+			};
+			(name.clone(), procedure)
+		}));
+		cached
+	}
+}
+
+struct CachedMethod<M: Metadata> {
+	name: String,
+	method: Arc<dyn RpcMethod<M>>,
+	ttl: Duration,
+	cache: Arc<ResponseCache>,
+}
+
+impl<M: PubSubMetadata> RpcMethod<M> for CachedMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Result<Value>> {
+		if is_pending(&params) {
+			return self.method.call(params, meta);
+		}
+		let params_key = match &params {
+			Params::None => "[]".to_string(),
+			Params::Array(values) => Value::Array(values.clone()).to_string(),
+			Params::Map(map) => Value::Object(map.clone()).to_string(),
+		};
+		let key = CacheKey {
+			method: self.name.clone(),
+			params: params_key,
+			head: (self.cache.best_hash)(),
+		};
+		if let Some(response) = self.cache.get(&key) {
+			return Box::pin(future::ok(response));
+		}
+
+		let cache = self.cache.clone();
+		let ttl = self.ttl;
+		Box::pin(self.method.call(params, meta).map(move |result| {
+			// Errors are not cached, they may be transient.
+			if let Ok(response) = &result {
+				cache.insert(key, response.clone(), ttl);
+			}
+			result
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethereum_types::H160;
+	use jsonrpc_pubsub::Session;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	#[derive(Clone, Default)]
+	struct TestMetadata;
+
+	impl Metadata for TestMetadata {}
+
+	impl PubSubMetadata for TestMetadata {
+		fn session(&self) -> Option<Arc<Session>> {
+			None
+		}
+	}
+
+	/// Handler counting the executed requests, cached by a cache of `max_entries` responses at
+	/// the best block `head`.
+	fn cached_io(
+		max_entries: usize,
+		head: Arc<Mutex<H256>>,
+	) -> (IoHandler<TestMetadata>, Arc<AtomicUsize>) {
+		let calls = Arc::new(AtomicUsize::new(0));
+		let mut io = IoHandler::default();
+		for method in &[
+			"eth_getBalance",
+			"eth_getTransactionCount",
+			"eth_sendRawTransaction",
+		] {
+			let calls = calls.clone();
+			io.add_sync_method(method, move |_| {
+				Ok(Value::from(calls.fetch_add(1, Ordering::SeqCst)))
+			});
+		}
+		let cache = ResponseCache::with_best_hash(
+			ResponseCacheConfig {
+				max_entries,
+				method_ttls: BTreeMap::new(),
+			},
+			Box::new(move || *head.lock()),
+		);
+		(Arc::new(cache).apply(io), calls)
+	}
+
+	fn call(io: &IoHandler<TestMetadata>, method: &str, params: &str) -> String {
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":{},"id":1}}"#,
+			method, params
+		);
+		io.handle_request_sync(&request, TestMetadata).unwrap()
+	}
+
+	#[test]
+	fn responses_are_cached_by_parameters_and_head() {
+		let head = Arc::new(Mutex::new(H256::repeat_byte(1)));
+		let (io, calls) = cached_io(10, head.clone());
+		let params = r#"["0x0000000000000000000000000000000000000001","latest"]"#;
+
+		let first = call(&io, "eth_getBalance", params);
+		assert_eq!(call(&io, "eth_getBalance", params), first);
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+		call(&io, "eth_getTransactionCount", params);
+		assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+		*head.lock() = H256::repeat_byte(2);
+		assert_ne!(call(&io, "eth_getBalance", params), first);
+		assert_eq!(calls.load(Ordering::SeqCst), 3);
+	}
+
+	#[test]
+	fn pending_requests_and_uncached_methods_are_executed() {
+		let (io, calls) = cached_io(10, Arc::new(Mutex::new(H256::zero())));
+		let params = r#"["0x0000000000000000000000000000000000000001","pending"]"#;
+
+		call(&io, "eth_getTransactionCount", params);
+		call(&io, "eth_getTransactionCount", params);
+		call(&io, "eth_sendRawTransaction", r#"["0x00"]"#);
+		call(&io, "eth_sendRawTransaction", r#"["0x00"]"#);
+		assert_eq!(calls.load(Ordering::SeqCst), 4);
+	}
+
+	#[test]
+	fn the_least_recently_used_response_makes_room() {
+		let (io, calls) = cached_io(2, Arc::new(Mutex::new(H256::zero())));
+		let params = |byte: u8| format!(r#"["{:?}","latest"]"#, H160::repeat_byte(byte));
+
+		call(&io, "eth_getBalance", &params(1));
+		call(&io, "eth_getBalance", &params(2));
+		call(&io, "eth_getBalance", &params(1));
+		call(&io, "eth_getBalance", &params(3));
+		assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+		call(&io, "eth_getBalance", &params(1));
+		assert_eq!(calls.load(Ordering::SeqCst), 3);
+		call(&io, "eth_getBalance", &params(2));
+		assert_eq!(calls.load(Ordering::SeqCst), 4);
+	}
+
+	#[test]
+	fn is_pending_looks_into_the_request_objects() {
+		let params = |json: &str| serde_json::from_str::<Params>(json).unwrap();
+		assert!(is_pending(&params(r#"["0x01","pending"]"#)));
+		assert!(is_pending(&params(
+			r#"[{"fromBlock":"0x1","toBlock":"pending"}]"#
+		)));
+		assert!(!is_pending(&params(r#"["0x01","latest"]"#)));
+		assert!(!is_pending(&params("[]")));
+	}
+}
@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Signers of the `eth_sendTransaction` transactions besides the development accounts.
+
+use crate::{
+	internal_err, public_key, secret_key_address, sign_message_with_keys, sign_with_keys, EthSigner,
+};
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core::Error;
+use log::{info, warn};
+use parking_lot::Mutex;
+use rustc_hex::{FromHex, ToHex};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::{
+	fs,
+	path::Path,
+	str::FromStr,
+	sync::Arc,
+	thread,
+	time::{Duration, Instant},
+};
+
+/// Signer of the accounts of a keystore directory, in the web3 secret storage format of geth.
+#[derive(Clone)]
+pub struct EthKeystoreSigner {
+	keys: Vec<secp256k1::SecretKey>,
+}
+
+impl EthKeystoreSigner {
+	/// Decrypts the keyfiles of `path` with `password`. Files which are not keyfiles are skipped.
+	pub fn from_dir(path: &Path, password: &str) -> Result<Self, String> {
+		let entries = fs::read_dir(path)
+			.map_err(|err| format!("Cannot read keystore {}: {:?}", path.display(), err))?;
+
+		let mut keys = Vec::new();
+		for entry in entries {
+			let file = entry
+				.map_err(|err| format!("Cannot read keystore {}: {:?}", path.display(), err))?
+				.path();
+			if !file.is_file() {
+				continue;
+			}
+
+			let secret = match eth_keystore::decrypt_key(&file, password) {
+				Ok(secret) => secret,
+				Err(err) => {
+					warn!(
+						target: "eth-keystore",
+						"Skipping keystore file {}: {:?}",
+						file.display(),
+						err,
+					);
+					continue;
+				}
+			};
+			let key = secp256k1::SecretKey::parse_slice(&secret)
+				.map_err(|_| format!("Invalid secret key in {}", file.display()))?;
+			keys.push(key);
+		}
+
+		Ok(Self { keys })
+	}
+}
+
+impl EthSigner for EthKeystoreSigner {
+	fn accounts(&self) -> Vec<H160> {
+		self.keys.iter().map(secret_key_address).collect()
+	}
+
+	fn sign(
+		&self,
+		message: ethereum::LegacyTransactionMessage,
+		address: &H160,
+	) -> Result<ethereum::TransactionV0, Error> {
+		sign_with_keys(&self.keys, message, address)
+	}
+
+	fn sign_message(&self, message: &[u8], address: &H160) -> Result<Vec<u8>, Error> {
+		sign_message_with_keys(&self.keys, message, address)
+	}
+}
+
+/// JSON-RPC dialect of a remote signer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteSignerProtocol {
+	/// Clef, `account_list` and `account_signTransaction`.
+	Clef,
+	/// Web3Signer, `eth_accounts` and `eth_signTransaction`.
+	Web3Signer,
+}
+
+impl FromStr for RemoteSignerProtocol {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"clef" => Ok(RemoteSignerProtocol::Clef),
+			"web3signer" => Ok(RemoteSignerProtocol::Web3Signer),
+			_ => Err(format!("Unknown remote signer protocol {}", s)),
+		}
+	}
+}
+
+impl RemoteSignerProtocol {
+	fn accounts_method(&self) -> &'static str {
+		match self {
+			RemoteSignerProtocol::Clef => "account_list",
+			RemoteSignerProtocol::Web3Signer => "eth_accounts",
+		}
+	}
+
+	fn sign_transaction_method(&self) -> &'static str {
+		match self {
+			RemoteSignerProtocol::Clef => "account_signTransaction",
+			RemoteSignerProtocol::Web3Signer => "eth_signTransaction",
+		}
+	}
+}
+
+/// How long the accounts listed by a remote signer are served before being listed again.
+const REMOTE_ACCOUNTS_TTL: Duration = Duration::from_secs(30);
+
+/// Accounts of a remote signer, listed in the background.
+#[derive(Default)]
+struct RemoteAccounts {
+	accounts: Vec<H160>,
+	listed_at: Option<Instant>,
+	listing: bool,
+}
+
+/// Signer forwarding the signing requests to an external signer over HTTP, so that no key is
+/// held by the node. Every signing request is audited under the `eth-signer-audit` log target.
+///
+/// The accounts are listed on a background thread and cached for `REMOTE_ACCOUNTS_TTL`, so
+/// that `eth_accounts` never waits for the signer. Signing blocks for at most `timeout`, the
+/// callers run it off the RPC threads.
+#[derive(Clone)]
+pub struct EthRemoteSigner {
+	url: String,
+	protocol: RemoteSignerProtocol,
+	timeout: Duration,
+	accounts: Arc<Mutex<RemoteAccounts>>,
+}
+
+impl EthRemoteSigner {
+	pub fn new(url: String, protocol: RemoteSignerProtocol, timeout: Duration) -> Self {
+		let signer = Self {
+			url,
+			protocol,
+			timeout,
+			accounts: Default::default(),
+		};
+		signer.refresh_accounts();
+		signer
+	}
+
+	/// Lists the accounts of the signer on a background thread, unless a listing is running.
+	fn refresh_accounts(&self) {
+		{
+			let mut cached = self.accounts.lock();
+			if cached.listing {
+				return;
+			}
+			cached.listing = true;
+		}
+
+		let signer = self.clone();
+		let spawned = thread::Builder::new()
+			.name("eth-remote-signer".into())
+			.spawn(move || {
+				let listed = signer
+					.request(signer.protocol.accounts_method(), json!([]))
+					.and_then(|result| {
+						serde_json::from_value::<Vec<H160>>(result)
+							.map_err(|err| format!("invalid accounts: {}", err))
+					});
+
+				let mut cached = signer.accounts.lock();
+				cached.listing = false;
+				match listed {
+					Ok(accounts) => {
+						cached.accounts = accounts;
+						cached.listed_at = Some(Instant::now());
+					}
+					Err(err) => warn!(
+						target: "eth-remote-signer",
+						"Cannot list the accounts of {}: {}",
+						signer.url,
+						err,
+					),
+				}
+			});
+		if let Err(err) = spawned {
+			warn!(
+				target: "eth-remote-signer",
+				"Cannot list the accounts of {}: {}",
+				self.url,
+				err,
+			);
+			self.accounts.lock().listing = false;
+		}
+	}
+
+	fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+		let response: Value = ureq::post(&self.url)
+			.timeout(self.timeout)
+			.send_json(json!({
+				"jsonrpc": "2.0",
+				"id": 1,
+				"method": method,
+				"params": params,
+			}))
+			.map_err(|err| format!("request failed: {}", err))?
+			.into_json()
+			.map_err(|err| format!("invalid response: {}", err))?;
+
+		if let Some(error) = response.get("error") {
+			return Err(format!("signer error: {}", error));
+		}
+		response
+			.get("result")
+			.cloned()
+			.ok_or_else(|| "missing result".to_string())
+	}
+
+	fn sign_remote(
+		&self,
+		message: &ethereum::LegacyTransactionMessage,
+		address: &H160,
+	) -> Result<ethereum::TransactionV0, String> {
+		let mut request = json!({
+			"from": address,
+			"gas": message.gas_limit,
+			"gasPrice": message.gas_price,
+			"value": message.value,
+			"nonce": message.nonce,
+			"data": format!("0x{}", message.input.to_hex::<String>()),
+		});
+		if let ethereum::TransactionAction::Call(to) = message.action {
+			request["to"] = json!(to);
+		}
+		if let Some(chain_id) = message.chain_id {
+			request["chainId"] = json!(U256::from(chain_id));
+		}
+
+		let result = self.request(self.protocol.sign_transaction_method(), json!([request]))?;
+		let raw = match &result {
+			Value::String(raw) => raw,
+			Value::Object(object) => match object.get("raw") {
+				Some(Value::String(raw)) => raw,
+				_ => return Err("missing raw transaction".to_string()),
+			},
+			_ => return Err("unexpected result".to_string()),
+		};
+		let bytes: Vec<u8> = raw
+			.trim_start_matches("0x")
+			.from_hex()
+			.map_err(|_| "invalid raw transaction".to_string())?;
+		let transaction = rlp::decode::<ethereum::TransactionV0>(&bytes)
+			.map_err(|_| "invalid raw transaction".to_string())?;
+
+		// The signer must have signed the requested transaction, with the requested account.
+		if ethereum::LegacyTransactionMessage::from(transaction.clone()).hash() != message.hash() {
+			return Err("signed transaction does not match the request".to_string());
+		}
+		let signer = public_key(&transaction)
+			.map(|pubkey| H160::from(H256::from_slice(Keccak256::digest(&pubkey).as_slice())))
+			.map_err(|_| "invalid signature".to_string())?;
+		if &signer != address {
+			return Err("transaction signed by another account".to_string());
+		}
+
+		Ok(transaction)
+	}
+}
+
+impl EthSigner for EthRemoteSigner {
+	fn accounts(&self) -> Vec<H160> {
+		let (accounts, stale) = {
+			let cached = self.accounts.lock();
+			let stale = cached
+				.listed_at
+				.map_or(true, |listed_at| listed_at.elapsed() >= REMOTE_ACCOUNTS_TTL);
+			(cached.accounts.clone(), stale)
+		};
+		if stale {
+			self.refresh_accounts();
+		}
+		accounts
+	}
+
+	fn sign(
+		&self,
+		message: ethereum::LegacyTransactionMessage,
+		address: &H160,
+	) -> Result<ethereum::TransactionV0, Error> {
+		let audit = format!(
+			"from={:?} to={:?} nonce={} value={} gas_limit={} gas_price={} chain_id={:?} message={:?}",
+			address,
+			match message.action {
+				ethereum::TransactionAction::Call(to) => Some(to),
+				ethereum::TransactionAction::Create => None,
+			},
+			message.nonce,
+			message.value,
+			message.gas_limit,
+			message.gas_price,
+			message.chain_id,
+			message.hash(),
+		);
+
+		match self.sign_remote(&message, address) {
+			Ok(transaction) => {
+				info!(
+					target: "eth-signer-audit",
+					"Signed by {}: {} hash={:?}",
+					self.url,
+					audit,
+					H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice()),
+				);
+				Ok(transaction)
+			}
+			Err(err) => {
+				warn!(
+					target: "eth-signer-audit",
+					"Signing refused or failed by {}: {} error={}",
+					self.url,
+					audit,
+					err,
+				);
+				Err(internal_err(format!("remote signer: {}", err)))
+			}
+		}
+	}
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logging of the slow RPC requests.
+//!
+//! Requests taking longer than a threshold are logged with their method, parameters, duration,
+//! result size and connection, and counted by method. The parameters of the methods carrying
+//! transactions, signatures or passwords are redacted, long strings of the others truncated.
+
+use fc_rpc_core::types::SlowQueryStats;
+use futures::FutureExt;
+use jsonrpc_core::{
+	BoxFuture, IoHandler, Metadata, Params, RemoteProcedure, Result, RpcMethod, Value,
+};
+use jsonrpc_pubsub::PubSubMetadata;
+use parking_lot::Mutex;
+use std::{
+	collections::BTreeMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Longest string parameter logged as is.
+const MAX_LOGGED_STRING_LEN: usize = 66;
+
+/// Methods whose parameters are never logged.
+fn is_redacted(method: &str) -> bool {
+	matches!(
+		method,
+		"eth_sendRawTransaction" | "eth_sendTransaction" | "eth_sign" | "eth_signTransaction"
+	) || method.starts_with("eth_signTypedData")
+		|| method.starts_with("personal_")
+}
+
+/// Copy of `value` with the long strings truncated.
+fn truncate(value: &Value) -> Value {
+	match value {
+		Value::String(s) if s.len() > MAX_LOGGED_STRING_LEN => Value::String(format!(
+			"{}...({} chars)",
+			s.chars().take(MAX_LOGGED_STRING_LEN).collect::<String>(),
+			s.len()
+		)),
+		Value::Array(values) => Value::Array(values.iter().map(truncate).collect()),
+		Value::Object(map) => Value::Object(
+			map.iter()
+				.map(|(key, value)| (key.clone(), truncate(value)))
+				.collect(),
+		),
+		other => other.clone(),
+	}
+}
+
+#[derive(Default)]
+struct MethodStats {
+	count: u64,
+	max_duration: Duration,
+}
+
+/// Logs and counts the requests exceeding a duration threshold.
+pub struct SlowQueryTracer {
+	threshold: Duration,
+	stats: Mutex<BTreeMap<String, MethodStats>>,
+}
+
+impl SlowQueryTracer {
+	pub fn new(threshold: Duration) -> Self {
+		Self {
+			threshold,
+			stats: Mutex::new(BTreeMap::new()),
+		}
+	}
+
+	/// Slow requests counted so far, by method.
+	pub fn stats(&self) -> Vec<SlowQueryStats> {
+		self.stats
+			.lock()
+			.iter()
+			.map(|(method, stats)| SlowQueryStats {
+				method: method.clone(),
+				count: stats.count,
+				max_duration_ms: stats.max_duration.as_millis() as u64,
+			})
+			.collect()
+	}
+
+	fn record(
+		&self,
+		method: &str,
+		params: &Params,
+		connection: Option<usize>,
+		duration: Duration,
+		result: &Result<Value>,
+	) {
+		if duration < self.threshold {
+			return;
+		}
+
+		{
+			let mut stats = self.stats.lock();
+			let stats = stats.entry(method.to_string()).or_default();
+			stats.count += 1;
+			stats.max_duration = stats.max_duration.max(duration);
+		}
+
+		let params = if is_redacted(method) {
+			"[redacted]".to_string()
+		} else {
+			match params {
+				Params::None => "[]".to_string(),
+				Params::Array(values) => {
+					Value::Array(values.iter().map(truncate).collect()).to_string()
+				}
+				Params::Map(map) => truncate(&Value::Object(map.clone())).to_string(),
+			}
+		};
+		let result = match result {
+			Ok(value) => format!(
+				"{} bytes",
+				serde_json::to_vec(value)
+					.map(|v| v.len())
+					.unwrap_or_default()
+			),
+			Err(err) => format!("error {}", err.code.code()),
+		};
+		let connection = connection
+			.map(|id| format!("{:x}", id))
+			.unwrap_or_else(|| "http".to_string());
+		log::warn!(
+			target: "rpc",
+			"Slow request {} params={} duration={}ms result={} connection={}",
+			method,
+			params,
+			duration.as_millis(),
+			result,
+			connection,
+		);
+	}
+
+	/// Wraps all the methods of `io` so that their slow requests are traced.
+	pub fn apply<M>(self: Arc<Self>, io: IoHandler<M>) -> IoHandler<M>
+	where
+		M: PubSubMetadata + Default,
+	{
+		let mut traced = IoHandler::default();
+		traced.extend_with(io.iter().map(|(name, procedure)| {
+			let procedure = match procedure {
+				RemoteProcedure::Method(method) => RemoteProcedure::Method(Arc::new(TracedMethod {
+					name: name.clone(),
+					method: method.clone(),
+					tracer: self.clone(),
+				})
+					as Arc<dyn RpcMethod<M>>),
+				other => other.clone(),
+			};
+			(name.clone(), procedure)
+		}));
+		traced
+	}
+}
+
+struct TracedMethod<M: Metadata> {
+	name: String,
+	method: Arc<dyn RpcMethod<M>>,
+	tracer: Arc<SlowQueryTracer>,
+}
+
+impl<M: PubSubMetadata> RpcMethod<M> for TracedMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Result<Value>> {
+		let connection = meta.session().map(|session| Arc::as_ptr(&session) as usize);
+		let name = self.name.clone();
+		let tracer = self.tracer.clone();
+		let start = Instant::now();
+		Box::pin(self.method.call(params.clone(), meta).map(move |result| {
+			tracer.record(&name, &params, connection, start.elapsed(), &result);
+			result
+		}))
+	}
+}
@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle proofs of the ordered tries behind the `transactionsRoot` and `receiptsRoot` of the
+//! Ethereum blocks.
+//!
+//! The tries map the RLP encoding of the index of each item to the item, and are built in
+//! memory the way `ethereum::util::ordered_trie_root` computes their root. The proofs hold the
+//! nodes on the path from the root to the item, as `eth_getProof` does: the root, then every
+//! node referenced by its hash. The nodes shorter than a hash are embedded in their parent.
+
+use ethereum_types::H256;
+use rlp::RlpStream;
+use sha3::{Digest, Keccak256};
+
+/// Root of the ordered trie of `items` and proof of the item at `index`.
+pub(crate) fn ordered_trie_proof(items: &[Vec<u8>], index: usize) -> (H256, Vec<Vec<u8>>) {
+	let mut entries: Vec<(Vec<u8>, &[u8])> = items
+		.iter()
+		.enumerate()
+		.map(|(i, item)| (nibbles(&rlp::encode(&i)), &item[..]))
+		.collect();
+	entries.sort_by(|a, b| a.0.cmp(&b.0));
+	let target = nibbles(&rlp::encode(&index));
+
+	let mut proof = Vec::new();
+	let root = encode_node(&entries, 0, Some(&target), &mut proof);
+	let root_hash = keccak(&root);
+	if !proof.last().map_or(false, |node| node == &root) {
+		proof.push(root);
+	}
+	// The nodes are pushed as their encoding completes, from the item up to the root.
+	proof.reverse();
+
+	(root_hash, proof)
+}
+
+/// Encodes the node holding `entries`, whose keys share their first `depth` nibbles. Pushes
+/// to `proof` the nodes referenced by their hash on the path to `target`.
+fn encode_node(
+	entries: &[(Vec<u8>, &[u8])],
+	depth: usize,
+	target: Option<&[u8]>,
+	proof: &mut Vec<Vec<u8>>,
+) -> Vec<u8> {
+	let encoded = match entries {
+		[] => rlp::NULL_RLP.to_vec(),
+		[(key, value)] => {
+			let mut stream = RlpStream::new_list(2);
+			stream.append(&hex_prefix(&key[depth..], true));
+			stream.append(&value.to_vec());
+			stream.out().to_vec()
+		}
+		_ => {
+			let first = &entries[0].0;
+			let last = &entries[entries.len() - 1].0;
+			let shared = first[depth..]
+				.iter()
+				.zip(&last[depth..])
+				.take_while(|(a, b)| a == b)
+				.count();
+			if shared > 0 {
+				let mut stream = RlpStream::new_list(2);
+				stream.append(&hex_prefix(&first[depth..depth + shared], false));
+				append_child(
+					&mut stream,
+					encode_node(entries, depth + shared, target, proof),
+				);
+				stream.out().to_vec()
+			} else {
+				let mut stream = RlpStream::new_list(17);
+				// Keys are sorted, an entry ending at this depth comes first.
+				let (value, mut rest) = match entries.split_first() {
+					Some(((key, value), rest)) if key.len() == depth => (Some(*value), rest),
+					_ => (None, entries),
+				};
+				for nibble in 0..16u8 {
+					let len = rest
+						.iter()
+						.take_while(|(key, _)| key[depth] == nibble)
+						.count();
+					let (children, remaining) = rest.split_at(len);
+					rest = remaining;
+					if children.is_empty() {
+						stream.append_empty_data();
+					} else {
+						let child_target =
+							target.filter(|target| target.get(depth) == Some(&nibble));
+						append_child(
+							&mut stream,
+							encode_node(children, depth + 1, child_target, proof),
+						);
+					}
+				}
+				match value {
+					Some(value) => stream.append(&value.to_vec()),
+					None => stream.append_empty_data(),
+				};
+				stream.out().to_vec()
+			}
+		}
+	};
+
+	if target.is_some() && encoded.len() >= 32 {
+		proof.push(encoded.clone());
+	}
+	encoded
+}
+
+/// Appends a reference to a child node, embedded if shorter than its hash.
+fn append_child(stream: &mut RlpStream, child: Vec<u8>) {
+	if child.len() < 32 {
+		stream.append_raw(&child, 1);
+	} else {
+		stream.append(&keccak(&child));
+	}
+}
+
+/// Compact encoding of a path of nibbles, flagging leaves and odd lengths in the first nibble.
+fn hex_prefix(path: &[u8], leaf: bool) -> Vec<u8> {
+	let flag = if leaf { 0x20 } else { 0x00 };
+	let mut encoded = Vec::with_capacity(path.len() / 2 + 1);
+	let rest = if path.len() % 2 == 1 {
+		encoded.push(flag | 0x10 | path[0]);
+		&path[1..]
+	} else {
+		encoded.push(flag);
+		path
+	};
+	encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+	encoded
+}
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+	bytes
+		.iter()
+		.flat_map(|byte| [byte >> 4, byte & 0x0f])
+		.collect()
+}
+
+fn keccak(bytes: &[u8]) -> H256 {
+	H256::from_slice(Keccak256::digest(bytes).as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rlp::Rlp;
+
+	/// Item `index` of `count`, some shorter than a hash so that their leaves get embedded.
+	fn item(index: usize) -> Vec<u8> {
+		if index % 3 == 0 {
+			vec![index as u8]
+		} else {
+			vec![index as u8; 40 + index % 7]
+		}
+	}
+
+	fn decode_hex_prefix(encoded: &[u8]) -> (Vec<u8>, bool) {
+		let flag = encoded[0] >> 4;
+		let mut path = Vec::new();
+		if flag & 1 == 1 {
+			path.push(encoded[0] & 0x0f);
+		}
+		path.extend(nibbles(&encoded[1..]));
+		(path, flag & 2 == 2)
+	}
+
+	/// Value under `key` in the trie of `root` proven by `proof`, None if the proof does not
+	/// lead to it or holds nodes off the path.
+	fn verify(root: H256, key: &[u8], proof: &[Vec<u8>]) -> Option<Vec<u8>> {
+		let path = nibbles(key);
+		let mut nodes = proof.iter();
+		let mut node = nodes.next()?.clone();
+		if keccak(&node) != root {
+			return None;
+		}
+		let mut depth = 0;
+		let value = loop {
+			let rlp = Rlp::new(&node);
+			let child = match rlp.item_count().ok()? {
+				2 => {
+					let (partial, leaf) = decode_hex_prefix(rlp.at(0).ok()?.data().ok()?);
+					if !path[depth..].starts_with(&partial) {
+						return None;
+					}
+					depth += partial.len();
+					if leaf {
+						if depth != path.len() {
+							return None;
+						}
+						break rlp.at(1).ok()?.data().ok()?.to_vec();
+					}
+					rlp.at(1).ok()?.as_raw().to_vec()
+				}
+				17 => {
+					if depth == path.len() {
+						break rlp.at(16).ok()?.data().ok()?.to_vec();
+					}
+					let child = rlp.at(path[depth] as usize).ok()?.as_raw().to_vec();
+					depth += 1;
+					child
+				}
+				_ => return None,
+			};
+			node = if Rlp::new(&child).is_list() {
+				child
+			} else {
+				let hash = Rlp::new(&child).data().ok()?.to_vec();
+				let next = nodes.next()?.clone();
+				if hash.len() != 32 || keccak(&next).as_bytes() != &hash[..] {
+					return None;
+				}
+				next
+			};
+		};
+
+		if nodes.next().is_some() {
+			return None;
+		}
+		Some(value)
+	}
+
+	#[test]
+	fn root_of_an_empty_trie() {
+		let (root, proof) = ordered_trie_proof(&[], 0);
+		assert_eq!(
+			root,
+			ethereum::util::ordered_trie_root(Vec::<Vec<u8>>::new())
+		);
+		assert_eq!(proof, vec![rlp::NULL_RLP.to_vec()]);
+	}
+
+	#[test]
+	fn roots_and_proofs_match_the_ordered_trie() {
+		for count in [1, 2, 16, 17, 128, 129, 300] {
+			let items: Vec<Vec<u8>> = (0..count).map(item).collect();
+			let expected_root = ethereum::util::ordered_trie_root(items.iter());
+
+			for index in 0..count {
+				let (root, proof) = ordered_trie_proof(&items, index);
+				assert_eq!(root, expected_root, "root of {} items", count);
+				assert_eq!(
+					verify(root, &rlp::encode(&index), &proof),
+					Some(items[index].clone()),
+					"proof of item {} of {}",
+					index,
+					count
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn proofs_do_not_prove_other_items() {
+		let items: Vec<Vec<u8>> = (0..17).map(item).collect();
+		let (root, proof) = ordered_trie_proof(&items, 4);
+		assert_eq!(verify(root, &rlp::encode(&5usize), &proof), None);
+
+		let mut tampered = proof.clone();
+		tampered.last_mut().unwrap()[4] ^= 1;
+		assert_eq!(verify(root, &rlp::encode(&4usize), &tampered), None);
+	}
+}
@@ -0,0 +1,329 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration test harness of Frontier nodes.
+//!
+//! [`FrontierNode::spawn`] starts a manual seal dev node in the test process, on free ports
+//! with a temporary database, and stops it when dropped. The node service is built by the
+//! given function from the command line arguments of the dev chain, so that downstream chains
+//! test their own node the same way as the template node does:
+//!
+//! ```ignore
+//! let node = FrontierNode::spawn(NodeConfig::default(), |args| {
+//! 	let cli = Cli::from_iter_safe(args).map_err(|err| err.to_string())?;
+//! 	let config = cli
+//! 		.create_configuration(&cli.run.base, tokio::runtime::Handle::current())
+//! 		.map_err(|err| err.to_string())?;
+//! 	service::new_full(config, &cli).map_err(|err| err.to_string())
+//! })
+//! .await?;
+//! ```
+//!
+//! The node is reached through an `ethers` [`Provider`], and the helpers cover the usual steps
+//! of a RPC test: signing with the prefunded development account, sealing blocks, deploying
+//! contracts and checking receipts.
+
+use ethers::{
+	prelude::{
+		Address, Bytes, Http, LocalWallet, Middleware, Provider, Signer, TransactionReceipt,
+		TransactionRequest, H256, U256, U64,
+	},
+	types::transaction::eip2718::TypedTransaction,
+};
+use sc_service::TaskManager;
+use std::{convert::TryFrom, env, fmt, net::TcpListener, sync::Once, time::Duration};
+
+/// Private key of the account prefunded by the template node dev chain.
+pub const DEV_ACCOUNT_PRIVATE_KEY: &str =
+	"99b3c12287537e38c90a9219d4cb074a89a16e9cdb20bf85728ebd97c343e342";
+
+/// Errors of the harness, holding the message of the underlying failure.
+#[derive(Debug)]
+pub enum Error {
+	/// The node service could not be started, or its RPC did not answer in time.
+	Spawn(String),
+	/// A RPC request failed, or its response could not be decoded.
+	Rpc(String),
+	/// The development key is invalid, or a transaction could not be signed with it.
+	Signer(String),
+	/// A transaction has no receipt once its block is sealed, e.g. it was not included in the
+	/// block because of its nonce or gas price.
+	MissingReceipt(H256),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Spawn(err) => write!(f, "cannot start the node: {}", err),
+			Error::Rpc(err) => write!(f, "RPC request failed: {}", err),
+			Error::Signer(err) => write!(f, "cannot sign the transaction: {}", err),
+			Error::MissingReceipt(hash) => write!(f, "no receipt for transaction {:?}", hash),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// How to start the node. The defaults fit the dev chain of the template node.
+#[derive(Clone, Debug)]
+pub struct NodeConfig {
+	/// Arguments added to those of the dev chain, which run the manual seal on free ports with
+	/// a temporary database, e.g. to enable more RPC namespaces.
+	pub extra_args: Vec<String>,
+	/// Private key, hex encoded, of an account funded by the genesis of the dev chain. The
+	/// helpers sign their transactions with it.
+	pub dev_account_private_key: String,
+	/// Maximum time for the RPC of the node to answer once its service is started.
+	pub ready_timeout: Duration,
+	/// Filter of the node logs written to stderr, `FRONTIER_LOG` by default and no logs when
+	/// unset. The logger is set by the first node started with a filter, and shared by the
+	/// nodes of the test process.
+	pub log_filter: Option<String>,
+}
+
+impl Default for NodeConfig {
+	fn default() -> Self {
+		Self {
+			extra_args: Vec::new(),
+			dev_account_private_key: DEV_ACCOUNT_PRIVATE_KEY.to_string(),
+			ready_timeout: Duration::from_secs(60),
+			log_filter: env::var("FRONTIER_LOG").ok(),
+		}
+	}
+}
+
+/// A dev node running in the test process, stopped when dropped.
+///
+/// Its RPC endpoints listen on localhost, and [`FrontierNode::provider`] is a client of the
+/// HTTP one. Blocks are only sealed on request, by [`FrontierNode::seal_block`] and the
+/// helpers sending transactions.
+pub struct FrontierNode {
+	_task_manager: TaskManager,
+	rpc_port: u16,
+	ws_port: u16,
+	provider: Provider<Http>,
+	wallet: LocalWallet,
+}
+
+fn free_port() -> Result<u16> {
+	TcpListener::bind("127.0.0.1:0")
+		.and_then(|listener| listener.local_addr())
+		.map(|address| address.port())
+		.map_err(|err| Error::Spawn(format!("no free port: {}", err)))
+}
+
+fn init_logger(filter: &str) {
+	static INIT: Once = Once::new();
+	INIT.call_once(|| {
+		if let Err(err) = sc_tracing::logging::LoggerBuilder::new(filter).init() {
+			eprintln!("Cannot set the node logger: {}", err);
+		}
+	});
+}
+
+impl FrontierNode {
+	/// Starts the node service with `start`, given the command line arguments of the dev
+	/// chain (the first one being the binary name), and waits for its RPC to answer. Called
+	/// from a Tokio runtime, which runs the tasks of the node.
+	pub async fn spawn<F>(config: NodeConfig, start: F) -> Result<Self>
+	where
+		F: FnOnce(&[String]) -> std::result::Result<TaskManager, String>,
+	{
+		if let Some(filter) = &config.log_filter {
+			init_logger(filter);
+		}
+
+		let (port, rpc_port, ws_port) = (free_port()?, free_port()?, free_port()?);
+		let args: Vec<String> = [
+			"frontier-node",
+			"--chain=dev",
+			"--validator",
+			"--execution=Native",
+			"--no-telemetry",
+			"--no-prometheus",
+			"--sealing=Manual",
+			"--no-grandpa",
+			"--force-authoring",
+			"--tmp",
+		]
+		.iter()
+		.map(|arg| arg.to_string())
+		.chain(vec![
+			format!("--port={}", port),
+			format!("--rpc-port={}", rpc_port),
+			format!("--ws-port={}", ws_port),
+		])
+		.chain(config.extra_args.iter().cloned())
+		.collect();
+		let task_manager = start(&args).map_err(Error::Spawn)?;
+
+		let provider = Provider::<Http>::try_from(format!("http://127.0.0.1:{}", rpc_port))
+			.map_err(|err| Error::Rpc(err.to_string()))?
+			.interval(Duration::from_millis(100));
+		let chain_id = tokio::time::timeout(config.ready_timeout, async {
+			loop {
+				match provider.get_chainid().await {
+					Ok(chain_id) => return chain_id,
+					Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+				}
+			}
+		})
+		.await
+		.map_err(|_| {
+			Error::Spawn(format!(
+				"RPC not answering after {:?}",
+				config.ready_timeout
+			))
+		})?;
+		let wallet = config
+			.dev_account_private_key
+			.trim_start_matches("0x")
+			.parse::<LocalWallet>()
+			.map_err(|err| Error::Signer(err.to_string()))?
+			.with_chain_id(chain_id.as_u64());
+
+		Ok(Self {
+			_task_manager: task_manager,
+			rpc_port,
+			ws_port,
+			provider,
+			wallet,
+		})
+	}
+
+	/// HTTP RPC endpoint.
+	pub fn http_url(&self) -> String {
+		format!("http://127.0.0.1:{}", self.rpc_port)
+	}
+
+	/// Websocket RPC endpoint.
+	pub fn ws_url(&self) -> String {
+		format!("ws://127.0.0.1:{}", self.ws_port)
+	}
+
+	/// Client of the HTTP RPC endpoint.
+	pub fn provider(&self) -> &Provider<Http> {
+		&self.provider
+	}
+
+	/// Prefunded development account.
+	pub fn dev_wallet(&self) -> &LocalWallet {
+		&self.wallet
+	}
+
+	/// Seals a block with the pending transactions, returns its hash.
+	pub async fn seal_block(&self) -> Result<H256> {
+		let block: serde_json::Value = self
+			.provider
+			.request("engine_createBlock", (true, true, Option::<H256>::None))
+			.await
+			.map_err(|err| Error::Rpc(err.to_string()))?;
+		serde_json::from_value(block["hash"].clone()).map_err(|err| Error::Rpc(err.to_string()))
+	}
+
+	/// Signs the legacy transaction with the development account, filling its nonce, gas and
+	/// gas price when missing, and submits it. Returns the transaction hash.
+	pub async fn send_transaction(&self, mut request: TransactionRequest) -> Result<H256> {
+		let rpc = |err: ethers::providers::ProviderError| Error::Rpc(err.to_string());
+		request.from = Some(self.wallet.address());
+		if request.nonce.is_none() {
+			request.nonce = Some(
+				self.provider
+					.get_transaction_count(self.wallet.address(), None)
+					.await
+					.map_err(rpc)?,
+			);
+		}
+		if request.gas_price.is_none() {
+			request.gas_price = Some(self.provider.get_gas_price().await.map_err(rpc)?);
+		}
+		let mut transaction = TypedTransaction::Legacy(request);
+		if transaction.gas().is_none() {
+			let gas = self
+				.provider
+				.estimate_gas(&transaction)
+				.await
+				.map_err(rpc)?;
+			transaction.set_gas(gas);
+		}
+		let signature = self
+			.wallet
+			.sign_transaction(&transaction)
+			.await
+			.map_err(|err| Error::Signer(err.to_string()))?;
+		let pending = self
+			.provider
+			.send_raw_transaction(transaction.rlp_signed(self.wallet.chain_id(), &signature))
+			.await
+			.map_err(rpc)?;
+		Ok(*pending)
+	}
+
+	/// Sends the transaction, seals it in a block and returns its receipt.
+	pub async fn send_and_seal(&self, request: TransactionRequest) -> Result<TransactionReceipt> {
+		let hash = self.send_transaction(request).await?;
+		self.seal_block().await?;
+		self.provider
+			.get_transaction_receipt(hash)
+			.await
+			.map_err(|err| Error::Rpc(err.to_string()))?
+			.ok_or(Error::MissingReceipt(hash))
+	}
+
+	/// Deploys a contract with the development account, returns its address.
+	pub async fn deploy(&self, bytecode: Bytes) -> Result<Address> {
+		let receipt = self
+			.send_and_seal(TransactionRequest::new().data(bytecode))
+			.await?;
+		assert_receipt_success(&receipt);
+		Ok(receipt
+			.contract_address
+			.expect("successful contract creations have an address; qed"))
+	}
+
+	/// Value transfer from the development account.
+	pub async fn transfer(&self, to: Address, value: U256) -> Result<TransactionReceipt> {
+		self.send_and_seal(TransactionRequest::new().to(to).value(value))
+			.await
+	}
+}
+
+/// Panics with the receipt unless the transaction succeeded, i.e. its status is 1.
+pub fn assert_receipt_success(receipt: &TransactionReceipt) {
+	assert_eq!(
+		receipt.status,
+		Some(U64::one()),
+		"transaction {:?} failed: {:?}",
+		receipt.transaction_hash,
+		receipt
+	);
+}
+
+/// Panics with the receipt unless the transaction reverted or ran out of gas, i.e. its status
+/// is 0. Transactions whose receipt is missing fail earlier, with `Error::MissingReceipt`.
+pub fn assert_receipt_reverted(receipt: &TransactionReceipt) {
+	assert_eq!(
+		receipt.status,
+		Some(U64::zero()),
+		"transaction {:?} did not revert: {:?}",
+		receipt.transaction_hash,
+		receipt
+	);
+}
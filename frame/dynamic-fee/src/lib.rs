@@ -20,18 +20,29 @@
 
 use async_trait::async_trait;
 use frame_support::inherent::IsFatalError;
+use frame_support::traits::Get;
 use sp_core::U256;
 use sp_inherents::{InherentData, InherentIdentifier};
+use sp_runtime::{
+	traits::{One, UniqueSaturatedInto},
+	Permill,
+};
 use sp_std::{
 	cmp::{max, min},
+	marker::PhantomData,
 	result,
 };
 
 pub use pallet::*;
 
+pub mod migrations;
 #[cfg(test)]
 mod tests;
 
+/// Maximum change of the base fee in a block once London is active, as a fraction of the base
+/// fee, as in EIP-1559.
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -44,8 +55,16 @@ pub mod pallet {
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
-		/// Bound divisor for min gas price.
+		/// The overarching event type.
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+		/// Bound divisor for min gas price, until set by `set_bound_divisor`.
 		type MinGasPriceBoundDivisor: Get<U256>;
+		/// Lowest min gas price, until set by `set_min_gas_price_bounds`.
+		type MinGasPriceLowerBound: Get<U256>;
+		/// Highest min gas price, until set by `set_min_gas_price_bounds`.
+		type MinGasPriceUpperBound: Get<U256>;
+		/// Congestion signal moving the min gas price in the blocks without target.
+		type BlockFullness: BlockFullness;
 	}
 
 	#[pallet::hooks]
@@ -53,18 +72,43 @@ pub mod pallet {
 		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
 			TargetMinGasPrice::<T>::kill();
 
-			T::DbWeight::get().writes(1)
+			// The London block and schedule are read in `on_finalize`.
+			T::DbWeight::get().reads_writes(2, 1)
 		}
 
-		fn on_finalize(_n: BlockNumberFor<T>) {
-			if let Some(target) = TargetMinGasPrice::<T>::take() {
-				let bound =
-					MinGasPrice::<T>::get() / T::MinGasPriceBoundDivisor::get() + U256::one();
+		fn on_finalize(n: BlockNumberFor<T>) {
+			// Once London is active the min gas price is the base fee, which follows the block
+			// fullness only. The author targets are ignored.
+			if LondonBlock::<T>::get().is_some() {
+				TargetMinGasPrice::<T>::kill();
+				if let Some(base_fee) = Self::next_base_fee() {
+					MinGasPrice::<T>::set(Self::clamp(base_fee));
+				}
+				return;
+			}
+
+			// London is activated at the end of the block preceding the scheduled one, so the
+			// state of the parent of every London block holds its base fee.
+			let next = n + One::one();
+			match LondonSchedule::<T>::get() {
+				Some((at, initial_base_fee)) if next >= at => {
+					LondonSchedule::<T>::kill();
+					TargetMinGasPrice::<T>::kill();
+					LondonBlock::<T>::put(next);
+					MinGasPrice::<T>::put(initial_base_fee);
+					Self::deposit_event(Event::LondonActivated(initial_base_fee));
+					return;
+				}
+				_ => {}
+			}
+
+			if let Some(target) = TargetMinGasPrice::<T>::take().or_else(Self::fullness_target) {
+				let bound = MinGasPrice::<T>::get() / BoundDivisor::<T>::get() + U256::one();
 
 				let upper_limit = MinGasPrice::<T>::get().saturating_add(bound);
 				let lower_limit = MinGasPrice::<T>::get().saturating_sub(bound);
 
-				MinGasPrice::<T>::set(min(upper_limit, max(lower_limit, target)));
+				MinGasPrice::<T>::set(Self::clamp(min(upper_limit, max(lower_limit, target))));
 			}
 		}
 	}
@@ -82,6 +126,86 @@ pub mod pallet {
 			TargetMinGasPrice::<T>::set(Some(target));
 			Ok(())
 		}
+
+		/// Sets the divisor of the min gas price giving its maximum change in a block.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_bound_divisor(origin: OriginFor<T>, divisor: U256) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!divisor.is_zero(), Error::<T>::ZeroBoundDivisor);
+
+			BoundDivisor::<T>::set(divisor);
+			Self::deposit_event(Event::BoundDivisorChanged(divisor));
+			Ok(())
+		}
+
+		/// Sets the range the min gas price is kept within, clamping the current one.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 3))]
+		pub fn set_min_gas_price_bounds(
+			origin: OriginFor<T>,
+			lower: U256,
+			upper: U256,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(lower <= upper, Error::<T>::InvalidBounds);
+
+			LowerBound::<T>::set(lower);
+			UpperBound::<T>::set(upper);
+			MinGasPrice::<T>::mutate(|min_gas_price| *min_gas_price = Self::clamp(*min_gas_price));
+			Self::deposit_event(Event::MinGasPriceBoundsChanged(lower, upper));
+			Ok(())
+		}
+
+		/// Schedules the switch of the min gas price to the EIP-1559 base fee rules at block
+		/// `at`, starting from `initial_base_fee`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 1))]
+		pub fn schedule_london(
+			origin: OriginFor<T>,
+			at: T::BlockNumber,
+			initial_base_fee: U256,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(
+				LondonBlock::<T>::get().is_none(),
+				Error::<T>::LondonAlreadyActive
+			);
+			ensure!(
+				at > frame_system::Pallet::<T>::block_number(),
+				Error::<T>::LondonBlockInThePast
+			);
+
+			LondonSchedule::<T>::put((at, initial_base_fee));
+			Self::deposit_event(Event::LondonScheduled(
+				U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(at)),
+				initial_base_fee,
+			));
+			Ok(())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		/// The bound divisor was changed. \[divisor\]
+		BoundDivisorChanged(U256),
+		/// The min gas price bounds were changed. \[lower, upper\]
+		MinGasPriceBoundsChanged(U256, U256),
+		/// The switch to the EIP-1559 base fee rules was scheduled. \[block, initial_base_fee\]
+		LondonScheduled(U256, U256),
+		/// The min gas price follows the EIP-1559 base fee rules from the next block on.
+		/// \[initial_base_fee\]
+		LondonActivated(U256),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The bound divisor must not be zero.
+		ZeroBoundDivisor,
+		/// The lower bound is above the upper bound.
+		InvalidBounds,
+		/// London is already active.
+		LondonAlreadyActive,
+		/// London cannot be scheduled at a past block.
+		LondonBlockInThePast,
 	}
 
 	#[pallet::genesis_config]
@@ -112,6 +236,31 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type TargetMinGasPrice<T: Config> = StorageValue<_, U256>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn bound_divisor)]
+	pub(super) type BoundDivisor<T: Config> =
+		StorageValue<_, U256, ValueQuery, T::MinGasPriceBoundDivisor>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn lower_bound)]
+	pub(super) type LowerBound<T: Config> =
+		StorageValue<_, U256, ValueQuery, T::MinGasPriceLowerBound>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn upper_bound)]
+	pub(super) type UpperBound<T: Config> =
+		StorageValue<_, U256, ValueQuery, T::MinGasPriceUpperBound>;
+
+	/// Block scheduled to switch the min gas price to the EIP-1559 base fee rules, with the
+	/// initial base fee.
+	#[pallet::storage]
+	pub type LondonSchedule<T: Config> = StorageValue<_, (T::BlockNumber, U256)>;
+
+	/// Block from which the min gas price follows the EIP-1559 base fee rules.
+	#[pallet::storage]
+	#[pallet::getter(fn london_block)]
+	pub type LondonBlock<T: Config> = StorageValue<_, T::BlockNumber>;
+
 	#[derive(Encode, Decode, RuntimeDebug)]
 	pub enum InherentError {}
 
@@ -146,12 +295,92 @@ pub mod pallet {
 	}
 }
 
+impl<T: Config> Pallet<T> {
+	/// Target of a block more than half full is the highest min gas price and the one of a block
+	/// less than half full the lowest, so that the min gas price moves by a full bound.
+	fn fullness_target() -> Option<U256> {
+		let fullness = T::BlockFullness::block_fullness()?;
+		let half = Permill::from_percent(50);
+		if fullness > half {
+			Some(U256::max_value())
+		} else if fullness < half {
+			Some(U256::zero())
+		} else {
+			None
+		}
+	}
+
+	/// Whether the min gas price follows the EIP-1559 base fee rules.
+	///
+	/// The base fee is the only London rule applied: transactions are validated against the min
+	/// gas price, which is the base fee from London on, and pay their gas price in full. There
+	/// are no EIP-1559 typed transactions to accept and the EVM keeps the configuration of the
+	/// runtime, without the EIP-3529 refunds and the EIP-3541 code prefix check. Nothing reads
+	/// this flag, runtimes check it to switch any rule of their own.
+	pub fn is_london() -> bool {
+		LondonBlock::<T>::get().is_some()
+	}
+
+	/// EIP-1559 base fee of the next block, moving by at most `1 / BASE_FEE_MAX_CHANGE_DENOMINATOR`
+	/// of the base fee, up when the block is more than half full and down when it is less.
+	fn next_base_fee() -> Option<U256> {
+		let fullness = T::BlockFullness::block_fullness()?.deconstruct();
+		let base_fee = MinGasPrice::<T>::get();
+		let half = Permill::from_percent(50).deconstruct();
+		// The gas target is half of the block, so the change relative to the target is
+		// `(fullness - half) / half`.
+		let denominator = U256::from(half) * U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+		if fullness > half {
+			let delta = base_fee.saturating_mul(U256::from(fullness - half)) / denominator;
+			Some(base_fee.saturating_add(max(delta, U256::one())))
+		} else if fullness < half {
+			let delta = base_fee.saturating_mul(U256::from(half - fullness)) / denominator;
+			Some(base_fee.saturating_sub(delta))
+		} else {
+			None
+		}
+	}
+
+	fn clamp(min_gas_price: U256) -> U256 {
+		min(
+			UpperBound::<T>::get(),
+			max(LowerBound::<T>::get(), min_gas_price),
+		)
+	}
+}
+
 impl<T: Config> pallet_evm::FeeCalculator for Pallet<T> {
 	fn min_gas_price() -> U256 {
 		MinGasPrice::<T>::get()
 	}
 }
 
+/// Fullness of the current block.
+pub trait BlockFullness {
+	/// Share of the block capacity used, `None` when unknown.
+	fn block_fullness() -> Option<Permill>;
+}
+
+impl BlockFullness for () {
+	fn block_fullness() -> Option<Permill> {
+		None
+	}
+}
+
+/// Fullness given by the weight of all the extrinsics of the block, EVM or not.
+pub struct BlockWeightFullness<T>(PhantomData<T>);
+
+impl<T: frame_system::Config> BlockFullness for BlockWeightFullness<T> {
+	fn block_fullness() -> Option<Permill> {
+		let used = frame_system::Pallet::<T>::block_weight().total();
+		let max_block = T::BlockWeights::get().max_block;
+		if max_block == 0 {
+			return None;
+		}
+		Some(Permill::from_rational(min(used, max_block), max_block))
+	}
+}
+
 pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"dynfee0_";
 
 pub type InherentType = U256;
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime upgrades of the dynamic fee pallet.
+
+use crate::{Config, LondonBlock, LondonSchedule};
+use frame_support::{
+	traits::{Get, OnRuntimeUpgrade},
+	weights::Weight,
+};
+use sp_core::U256;
+use sp_std::marker::PhantomData;
+
+/// Schedules the switch of the min gas price to the EIP-1559 base fee rules at block `At`,
+/// starting from `InitialBaseFee`, on an existing chain. Does nothing when London is already
+/// scheduled or active, so it can stay in the runtime upgrades.
+pub struct ScheduleLondon<T, At, InitialBaseFee>(PhantomData<(T, At, InitialBaseFee)>);
+
+impl<T, At, InitialBaseFee> OnRuntimeUpgrade for ScheduleLondon<T, At, InitialBaseFee>
+where
+	T: Config,
+	At: Get<T::BlockNumber>,
+	InitialBaseFee: Get<U256>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		if LondonBlock::<T>::get().is_some() || LondonSchedule::<T>::get().is_some() {
+			return T::DbWeight::get().reads(2);
+		}
+
+		LondonSchedule::<T>::put((At::get(), InitialBaseFee::get()));
+		T::DbWeight::get().reads_writes(2, 1)
+	}
+}
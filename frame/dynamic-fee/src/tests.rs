@@ -19,8 +19,9 @@ use super::*;
 use crate as pallet_dynamic_fee;
 
 use frame_support::{
-	assert_ok, parameter_types,
+	assert_noop, assert_ok, parameter_types,
 	traits::{OnFinalize, OnInitialize},
+	weights::DispatchClass,
 };
 use sp_core::{H256, U256};
 use sp_io::TestExternalities;
@@ -46,7 +47,7 @@ parameter_types! {
 }
 impl frame_system::Config for Test {
 	type BaseCallFilter = frame_support::traits::Everything;
-	type BlockWeights = ();
+	type BlockWeights = BlockWeights;
 	type BlockLength = ();
 	type DbWeight = ();
 	type Origin = Origin;
@@ -82,9 +83,15 @@ impl pallet_timestamp::Config for Test {
 
 frame_support::parameter_types! {
 	pub BoundDivision: U256 = 1024.into();
+	pub MinGasPriceLowerBound: U256 = U256::zero();
+	pub MinGasPriceUpperBound: U256 = U256::max_value();
 }
 impl Config for Test {
+	type Event = Event;
 	type MinGasPriceBoundDivisor = BoundDivision;
+	type MinGasPriceLowerBound = MinGasPriceLowerBound;
+	type MinGasPriceUpperBound = MinGasPriceUpperBound;
+	type BlockFullness = BlockWeightFullness<Self>;
 }
 
 frame_support::construct_runtime!(
@@ -95,7 +102,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage},
-		DynamicFee: pallet_dynamic_fee::{Pallet, Call, Storage, Inherent},
+		DynamicFee: pallet_dynamic_fee::{Pallet, Call, Storage, Inherent, Event},
 	}
 );
 
@@ -124,3 +131,146 @@ fn double_set_in_a_block_failed() {
 		));
 	});
 }
+
+#[test]
+fn bound_divisor_limits_the_change() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_ok!(DynamicFee::set_min_gas_price_bounds(
+			Origin::root(),
+			U256::from(100),
+			U256::from(1000)
+		));
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(100));
+
+		assert_ok!(DynamicFee::set_bound_divisor(Origin::root(), U256::from(2)));
+		assert_ok!(DynamicFee::note_min_gas_price_target(
+			Origin::none(),
+			U256::from(10_000)
+		));
+		run_to_block(2);
+		// 100 + 100 / 2 + 1
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(151));
+
+		for n in 3..10 {
+			assert_ok!(DynamicFee::note_min_gas_price_target(
+				Origin::none(),
+				U256::from(10_000)
+			));
+			run_to_block(n);
+		}
+		// Clamped to the upper bound.
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(1000));
+	});
+}
+
+#[test]
+fn parameters_are_set_by_root_only() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			DynamicFee::set_bound_divisor(Origin::signed(1), U256::from(2)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			DynamicFee::set_bound_divisor(Origin::root(), U256::zero()),
+			Error::<Test>::ZeroBoundDivisor
+		);
+		assert_noop!(
+			DynamicFee::set_min_gas_price_bounds(Origin::root(), U256::from(2), U256::one()),
+			Error::<Test>::InvalidBounds
+		);
+		assert_eq!(DynamicFee::bound_divisor(), U256::from(1024));
+	});
+}
+
+#[test]
+fn block_weight_drives_min_gas_price_without_target() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_ok!(DynamicFee::set_min_gas_price_bounds(
+			Origin::root(),
+			U256::from(100),
+			U256::from(1000)
+		));
+
+		// Empty block.
+		run_to_block(2);
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(100));
+
+		// Full block of non EVM extrinsics.
+		System::register_extra_weight_unchecked(1024, DispatchClass::Normal);
+		run_to_block(3);
+		// 100 + 100 / 1024 + 1
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(101));
+
+		// An author target takes precedence.
+		System::register_extra_weight_unchecked(1024, DispatchClass::Normal);
+		assert_ok!(DynamicFee::note_min_gas_price_target(
+			Origin::none(),
+			U256::from(100)
+		));
+		run_to_block(4);
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(100));
+	});
+}
+
+#[test]
+fn london_switches_to_the_base_fee_rules() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_noop!(
+			DynamicFee::schedule_london(Origin::root(), 1, U256::from(1000)),
+			Error::<Test>::LondonBlockInThePast
+		);
+		assert_ok!(DynamicFee::schedule_london(
+			Origin::root(),
+			3,
+			U256::from(1000)
+		));
+
+		run_to_block(2);
+		assert!(!DynamicFee::is_london());
+		run_to_block(3);
+		assert_eq!(DynamicFee::london_block(), Some(3));
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(1000));
+
+		// An empty block lowers the base fee by an eighth.
+		run_to_block(4);
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(875));
+
+		// A full block raises it by an eighth, the author target is ignored.
+		System::register_extra_weight_unchecked(1024, DispatchClass::Normal);
+		assert_ok!(DynamicFee::note_min_gas_price_target(
+			Origin::none(),
+			U256::from(100)
+		));
+		run_to_block(5);
+		assert_eq!(DynamicFee::min_gas_price(), U256::from(984));
+
+		assert_noop!(
+			DynamicFee::schedule_london(Origin::root(), 10, U256::from(1000)),
+			Error::<Test>::LondonAlreadyActive
+		);
+	});
+}
+
+#[test]
+fn london_is_scheduled_once_by_the_migration() {
+	parameter_types! {
+		pub const LondonAt: u64 = 5;
+		pub InitialBaseFee: U256 = U256::from(7);
+	}
+	type Migration = migrations::ScheduleLondon<Test, LondonAt, InitialBaseFee>;
+
+	new_test_ext().execute_with(|| {
+		use frame_support::traits::OnRuntimeUpgrade;
+
+		Migration::on_runtime_upgrade();
+		assert_eq!(LondonSchedule::<Test>::get(), Some((5, U256::from(7))));
+
+		run_to_block(5);
+		assert_eq!(DynamicFee::london_block(), Some(5));
+		Migration::on_runtime_upgrade();
+		assert_eq!(LondonSchedule::<Test>::get(), None);
+	});
+}
@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Ethereum XCM pallet
+//!
+//! Executes EVM calls and creations on behalf of the account given by a privileged origin,
+//! typically an XCM `Transact` whose origin is converted to an H160, or a local proxy.
+//!
+//! They are recorded in the Ethereum block as transactions, with receipts and events. Not
+//! being signed, they carry the signature `r = sender, s = 2` which tells them apart.
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use ethereum::{TransactionAction, TransactionSignature, TransactionV0};
+use frame_support::traits::Get;
+use pallet_evm::{FeeCalculator, GasWeightMapping};
+use sp_core::{H160, H256, U256};
+use sp_runtime::{traits::UniqueSaturatedInto, RuntimeDebug};
+use sp_std::vec::Vec;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+/// `s` of the signature of the XCM transactions.
+pub const XCM_TRANSACTION_SIGNATURE_S: u64 = 2;
+
+/// An EVM call or creation requested through XCM.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct EthereumXcmTransaction {
+	/// Gas limit of the execution.
+	pub gas_limit: U256,
+	/// Call or creation.
+	pub action: TransactionAction,
+	/// Value transferred.
+	pub value: U256,
+	/// Call data or init code.
+	pub input: Vec<u8>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_evm::Config + pallet_ethereum::Config {
+		/// Origin allowed to execute transactions, giving the account they are executed for.
+		type XcmEthereumOrigin: EnsureOrigin<Self::Origin, Success = H160>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The sender cannot be represented in a transaction signature.
+		InvalidSender,
+		/// The transaction does not pass the Ethereum transaction validation.
+		InvalidTransaction,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Executes the transaction on behalf of the account of the origin, paying the min gas
+		/// price.
+		#[pallet::weight(<T as pallet_evm::Config>::GasWeightMapping::gas_to_weight(
+			xcm_transaction.gas_limit.unique_saturated_into()
+		))]
+		pub fn transact(
+			origin: OriginFor<T>,
+			xcm_transaction: EthereumXcmTransaction,
+		) -> DispatchResultWithPostInfo {
+			let source = T::XcmEthereumOrigin::ensure_origin(origin)?;
+			let transaction = Self::transaction(source, xcm_transaction)?;
+
+			pallet_ethereum::Pallet::<T>::validate_transaction_in_block(source, &transaction)
+				.map_err(|_| Error::<T>::InvalidTransaction)?;
+
+			Ok(pallet_ethereum::Pallet::<T>::apply_validated_transaction(
+				source,
+				transaction,
+			))
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	fn transaction(
+		source: H160,
+		xcm_transaction: EthereumXcmTransaction,
+	) -> Result<TransactionV0, Error<T>> {
+		let chain_id = <T as pallet_evm::Config>::ChainId::get();
+		let signature = TransactionSignature::new(
+			2 * chain_id + 35,
+			H256::from(source),
+			H256::from_low_u64_be(XCM_TRANSACTION_SIGNATURE_S),
+		)
+		.ok_or(Error::<T>::InvalidSender)?;
+
+		Ok(TransactionV0 {
+			nonce: pallet_evm::Pallet::<T>::account_basic(&source).nonce,
+			gas_price: <T as pallet_evm::Config>::FeeCalculator::min_gas_price(),
+			gas_limit: xcm_transaction.gas_limit,
+			action: xcm_transaction.action,
+			value: xcm_transaction.value,
+			input: xcm_transaction.input,
+			signature,
+		})
+	}
+}
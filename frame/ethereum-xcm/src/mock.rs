@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Test utilities
+
+use super::*;
+use frame_support::{
+	parameter_types,
+	traits::{EnsureOrigin, FindAuthor, GenesisBuild},
+	ConsensusEngineId,
+};
+use pallet_evm::AddressMapping;
+use pallet_evm::{EnsureAddressTruncated, FeeCalculator};
+use sp_core::{H160, H256, U256};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	AccountId32,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime! {
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage},
+		EVM: pallet_evm::{Pallet, Call, Storage, Config, Event<T>},
+		Ethereum: pallet_ethereum::{Pallet, Call, Storage, Event, Origin},
+		EthereumXcm: crate::{Pallet, Call},
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Call = Call;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = ();
+}
+
+parameter_types! {
+	pub const MinimumPeriod: u64 = 6000 / 2;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+pub struct FixedGasPrice;
+impl FeeCalculator for FixedGasPrice {
+	fn min_gas_price() -> U256 {
+		1.into()
+	}
+}
+
+pub struct FindAuthorTruncated;
+impl FindAuthor<H160> for FindAuthorTruncated {
+	fn find_author<'a, I>(_digests: I) -> Option<H160>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		Some(H160::default())
+	}
+}
+
+parameter_types! {
+	pub const ChainId: u64 = 42;
+	pub const BlockGasLimit: U256 = U256::MAX;
+}
+
+pub struct HashedAddressMapping;
+
+impl AddressMapping<AccountId32> for HashedAddressMapping {
+	fn into_account_id(address: H160) -> AccountId32 {
+		let mut data = [0u8; 32];
+		data[0..20].copy_from_slice(&address[..]);
+		AccountId32::from(Into::<[u8; 32]>::into(data))
+	}
+}
+
+impl pallet_evm::Config for Test {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = ();
+	type CallOrigin = EnsureAddressTruncated;
+	type WithdrawOrigin = EnsureAddressTruncated;
+	type AddressMapping = HashedAddressMapping;
+	type Currency = Balances;
+	type Event = Event;
+	type Precompiles = ();
+	type Runner = pallet_evm::runner::stack::Runner<Self>;
+	type ChainId = ChainId;
+	type BlockGasLimit = pallet_ethereum::EthereumBlockGasLimit<Self>;
+	type OnChargeTransaction = ();
+	type FindAuthor = FindAuthorTruncated;
+	type BlockHashMapping = pallet_ethereum::EthereumBlockHashMapping<Self>;
+	type CountPrecompileCalls = ();
+}
+
+impl pallet_ethereum::Config for Test {
+	type Event = Event;
+	type StateRoot = pallet_ethereum::IntermediateStateRoot;
+	type HeaderFields = pallet_ethereum::EvmAuthorHeaderFields<Self>;
+	type PoolMinGasPrice = ();
+	type CompactReceipts = ();
+	type GasLimitOrigin = frame_system::EnsureRoot<AccountId32>;
+	type DefaultBlockGasLimit = BlockGasLimit;
+	type GasLimitBoundDivisor = ();
+	type BlockMetrics = ();
+	type RecordInternalTransfers = ();
+	type MaxInternalTransfers = ();
+	type IndexTransactionsByHash = ();
+	type ExtraValidation = ();
+}
+
+/// Gives the address truncated from the signed origins, as an XCM origin converter would.
+pub struct EnsureSignedTruncated;
+impl EnsureOrigin<Origin> for EnsureSignedTruncated {
+	type Success = H160;
+
+	fn try_origin(o: Origin) -> Result<H160, Origin> {
+		Into::<Result<frame_system::RawOrigin<AccountId32>, Origin>>::into(o).and_then(
+			|o| match o {
+				frame_system::RawOrigin::Signed(who) => {
+					Ok(H160::from_slice(&AsRef::<[u8; 32]>::as_ref(&who)[0..20]))
+				}
+				r => Err(Origin::from(r)),
+			},
+		)
+	}
+}
+
+impl crate::Config for Test {
+	type XcmEthereumOrigin = EnsureSignedTruncated;
+}
+
+pub fn new_test_ext(funded: &[H160]) -> sp_io::TestExternalities {
+	let mut ext = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+
+	let balances = funded
+		.iter()
+		.map(|address| (HashedAddressMapping::into_account_id(*address), 10_000_000))
+		.collect();
+	GenesisBuild::<Test>::assimilate_storage(
+		&pallet_balances::GenesisConfig::<Test> { balances },
+		&mut ext,
+	)
+	.unwrap();
+
+	let mut ext: sp_io::TestExternalities = ext.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{mock::*, Error, EthereumXcmTransaction, XCM_TRANSACTION_SIGNATURE_S};
+use ethereum::TransactionAction;
+use frame_support::{assert_noop, assert_ok, traits::OnFinalize};
+use pallet_evm::AddressMapping;
+use sp_core::{H160, H256, U256};
+use sp_runtime::{AccountId32, DispatchError};
+
+fn origin_of(address: H160) -> Origin {
+	let mut data = [0u8; 32];
+	data[0..20].copy_from_slice(&address[..]);
+	Origin::signed(AccountId32::from(data))
+}
+
+fn transfer(to: H160, value: u64) -> EthereumXcmTransaction {
+	EthereumXcmTransaction {
+		gas_limit: U256::from(21_000),
+		action: TransactionAction::Call(to),
+		value: U256::from(value),
+		input: Vec::new(),
+	}
+}
+
+#[test]
+fn transact_executes_on_behalf_of_the_origin() {
+	let source = H160::repeat_byte(0x11);
+	let target = H160::repeat_byte(0x22);
+
+	new_test_ext(&[source]).execute_with(|| {
+		assert_ok!(EthereumXcm::transact(
+			origin_of(source),
+			transfer(target, 1_000)
+		));
+		assert_ok!(EthereumXcm::transact(
+			origin_of(source),
+			transfer(target, 1_000)
+		));
+
+		assert_eq!(
+			Balances::free_balance(&HashedAddressMapping::into_account_id(target)),
+			2_000
+		);
+		assert_eq!(EVM::account_basic(&source).nonce, U256::from(2));
+		let executed = System::events()
+			.into_iter()
+			.filter(|record| {
+				matches!(
+					record.event,
+					Event::Ethereum(pallet_ethereum::Event::Executed(from, to, _, _))
+						if from == source && to == target
+				)
+			})
+			.count();
+		assert_eq!(executed, 2);
+
+		Ethereum::on_finalize(1);
+		let block = Ethereum::current_block().unwrap();
+		assert_eq!(block.transactions.len(), 2);
+		for (nonce, transaction) in block.transactions.iter().enumerate() {
+			assert_eq!(transaction.nonce, U256::from(nonce));
+			assert_eq!(transaction.signature.r(), &H256::from(source));
+			assert_eq!(
+				transaction.signature.s(),
+				&H256::from_low_u64_be(XCM_TRANSACTION_SIGNATURE_S)
+			);
+			assert_eq!(transaction.signature.chain_id(), Some(ChainId::get()));
+		}
+	});
+}
+
+#[test]
+fn transact_requires_the_xcm_origin() {
+	let source = H160::repeat_byte(0x11);
+
+	new_test_ext(&[source]).execute_with(|| {
+		assert_noop!(
+			EthereumXcm::transact(Origin::root(), transfer(H160::repeat_byte(0x22), 1)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			EthereumXcm::transact(Origin::none(), transfer(H160::repeat_byte(0x22), 1)),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn transact_rejects_invalid_transactions() {
+	let source = H160::repeat_byte(0x11);
+	let unfunded = H160::repeat_byte(0x33);
+
+	new_test_ext(&[source]).execute_with(|| {
+		// The sender cannot pay for the gas.
+		assert_noop!(
+			EthereumXcm::transact(origin_of(unfunded), transfer(source, 1)),
+			Error::<Test>::InvalidTransaction
+		);
+
+		// The gas limit does not cover the intrinsic gas.
+		let mut transaction = transfer(unfunded, 1);
+		transaction.gas_limit = U256::from(20_999);
+		assert_noop!(
+			EthereumXcm::transact(origin_of(source), transaction),
+			Error::<Test>::InvalidTransaction
+		);
+	});
+}
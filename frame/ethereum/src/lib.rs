@@ -33,10 +33,12 @@ use frame_support::{
 	dispatch::DispatchResultWithPostInfo,
 	ensure,
 	traits::{EnsureOrigin, Get},
-	weights::{Pays, PostDispatchInfo, Weight},
+	weights::{DispatchClass, Pays, PostDispatchInfo, Weight},
 };
 use frame_system::pallet_prelude::OriginFor;
-use pallet_evm::{BlockHashMapping, FeeCalculator, GasWeightMapping, Runner};
+use pallet_evm::{
+	BlockHashMapping, FeeCalculator, GasWeightMapping, OnChargeEVMTransaction, Runner,
+};
 use sha3::{Digest, Keccak256};
 use sp_runtime::{
 	generic::DigestItem,
@@ -52,7 +54,7 @@ pub use ethereum::{
 	BlockV0 as Block, LegacyTransactionMessage, Log, Receipt, TransactionAction,
 	TransactionV0 as Transaction,
 };
-pub use fp_rpc::TransactionStatus;
+pub use fp_rpc::{IndexedTransactionStatus, InternalTransfer, TransactionStatus};
 
 #[cfg(all(feature = "std", test))]
 mod mock;
@@ -163,6 +165,40 @@ pub mod pallet {
 		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
 		/// How Ethereum state root is calculated.
 		type StateRoot: Get<H256>;
+		/// Fields of the Ethereum block headers not derived from the execution.
+		type HeaderFields: HeaderFields;
+		/// Lowest gas price of the transactions accepted in the pool, on top of the fee
+		/// calculator min gas price which also applies to the block transactions.
+		type PoolMinGasPrice: Get<U256>;
+		/// Whether the receipts are stored without their logs, which are already part of the
+		/// transaction statuses. The receipts root still commits to the full receipts, and the
+		/// RPC restores the logs from the statuses.
+		///
+		/// Only this duplication is removed: the status, cumulative gas used and bloom of every
+		/// receipt stay in the state, the receipts are never rebuilt by re-execution.
+		type CompactReceipts: Get<bool>;
+		/// Origin allowed to set the block gas limit target.
+		type GasLimitOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+		/// Block gas limit until a target is set.
+		type DefaultBlockGasLimit: Get<U256>;
+		/// When set, the block gas limit moves toward its target by at most `limit / divisor`
+		/// per block, as geth does with 1024, otherwise it is set to the target at once.
+		type GasLimitBoundDivisor: Get<Option<U256>>;
+		/// Whether a `BlockMetrics` event summarizing the EVM activity of each block is deposited
+		/// when the block is finalized.
+		type BlockMetrics: Get<bool>;
+		/// Whether the value transfers below the top level calls of the transactions are
+		/// recorded, for the internal transactions of the explorers, at the cost of tracing the
+		/// calls of the transactions. Requires the `tracing` feature.
+		type RecordInternalTransfers: Get<bool>;
+		/// Maximum number of internal transfers recorded per block, the ones beyond are dropped.
+		type MaxInternalTransfers: Get<u32>;
+		/// Whether the transactions of the current block are indexed by hash, so that
+		/// `transaction_status_by_hash` finds them without decoding the block.
+		type IndexTransactionsByHash: Get<bool>;
+		/// Chain-specific rules the transactions must follow, checked after the pallet rules in
+		/// the pool and in the block.
+		type ExtraValidation: ExtraValidation;
 	}
 
 	#[pallet::pallet]
@@ -196,6 +232,16 @@ pub mod pallet {
 
 		fn on_initialize(_: T::BlockNumber) -> Weight {
 			Pending::<T>::kill();
+			let mut weight = Self::step_block_gas_limit();
+			if T::IndexTransactionsByHash::get() {
+				// Bounded by the transactions of the previous block.
+				let cleared = TransactionIndexByHash::<T>::drain().count() as Weight;
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(cleared, cleared));
+			}
+			if T::RecordInternalTransfers::get() {
+				InternalTransfers::<T>::kill();
+				weight = weight.saturating_add(T::DbWeight::get().writes(1));
+			}
 
 			// If the digest contain an existing ethereum block(encoded as PreLog), If contains,
 			// execute the imported block firstly and disable transact dispatch function.
@@ -214,7 +260,14 @@ pub mod pallet {
 				}
 			}
 
-			0
+			weight
+		}
+
+		fn integrity_test() {
+			assert!(
+				cfg!(feature = "tracing") || !T::RecordInternalTransfers::get(),
+				"RecordInternalTransfers requires the tracing feature of pallet-ethereum",
+			);
 		}
 	}
 
@@ -238,6 +291,18 @@ pub mod pallet {
 
 			Ok(Self::apply_validated_transaction(source, transaction))
 		}
+
+		/// Set the block gas limit target, reached at once or stepwise depending on
+		/// `GasLimitBoundDivisor`.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_block_gas_limit(origin: OriginFor<T>, target: U256) -> DispatchResult {
+			T::GasLimitOrigin::ensure_origin(origin)?;
+			ensure!(!target.is_zero(), Error::<T>::ZeroBlockGasLimit);
+
+			BlockGasLimitTarget::<T>::put(target);
+			Self::deposit_event(Event::BlockGasLimitTargetSet(target));
+			Ok(())
+		}
 	}
 
 	#[pallet::event]
@@ -245,6 +310,10 @@ pub mod pallet {
 	pub enum Event {
 		/// An ethereum transaction was successfully executed. [from, to/contract_address, transaction_hash, exit_reason]
 		Executed(H160, H160, H256, ExitReason),
+		/// The block gas limit target was set. [target]
+		BlockGasLimitTargetSet(U256),
+		/// EVM activity of the block. [gas_used, transactions, contracts_created]
+		BlockMetrics(U256, u32, u32),
 	}
 
 	#[pallet::error]
@@ -253,6 +322,8 @@ pub mod pallet {
 		InvalidSignature,
 		/// Pre-log is present, therefore transact is not allowed.
 		PreLogExists,
+		/// The block gas limit cannot be zero.
+		ZeroBlockGasLimit,
 	}
 
 	/// Current building block's transactions and receipts.
@@ -272,6 +343,31 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type CurrentTransactionStatuses<T: Config> = StorageValue<_, Vec<TransactionStatus>>;
 
+	/// The index and gas price of the transactions of the current block, by hash, when
+	/// `IndexTransactionsByHash`.
+	#[pallet::storage]
+	pub(super) type TransactionIndexByHash<T: Config> = StorageMap<_, Identity, H256, (u32, U256)>;
+
+	/// The value transfers below the top level calls of the transactions of the current block,
+	/// when `RecordInternalTransfers`, at most `MaxInternalTransfers`.
+	#[pallet::storage]
+	pub(super) type InternalTransfers<T: Config> =
+		StorageValue<_, Vec<InternalTransfer>, ValueQuery>;
+
+	#[pallet::type_value]
+	pub(super) fn DefaultBlockGasLimit<T: Config>() -> U256 {
+		T::DefaultBlockGasLimit::get()
+	}
+
+	/// The current block gas limit.
+	#[pallet::storage]
+	pub(super) type BlockGasLimit<T: Config> =
+		StorageValue<_, U256, ValueQuery, DefaultBlockGasLimit<T>>;
+
+	/// The block gas limit being stepped toward.
+	#[pallet::storage]
+	pub(super) type BlockGasLimitTarget<T: Config> = StorageValue<_, U256>;
+
 	// Mapping for block number and hashes.
 	#[pallet::storage]
 	pub(super) type BlockHash<T: Config> = StorageMap<_, Twox64Concat, U256, H256, ValueQuery>;
@@ -315,8 +411,9 @@ impl<T: Config> Pallet<T> {
 		for (transaction, status, receipt) in Pending::<T>::get() {
 			transactions.push(transaction);
 			statuses.push(status);
-			receipts.push(receipt.clone());
-			Self::logs_bloom(receipt.logs.clone(), &mut logs_bloom);
+			// The block bloom is the union of the receipt blooms.
+			logs_bloom.accrue_bloom(&receipt.logs_bloom);
+			receipts.push(receipt);
 		}
 
 		let ommers = Vec::<ethereum::Header>::new();
@@ -324,11 +421,11 @@ impl<T: Config> Pallet<T> {
 			ethereum::util::ordered_trie_root(receipts.iter().map(|r| rlp::encode(r)));
 		let partial_header = ethereum::PartialHeader {
 			parent_hash: Self::current_block_hash().unwrap_or_default(),
-			beneficiary: pallet_evm::Pallet::<T>::find_author(),
+			beneficiary: T::HeaderFields::beneficiary(),
 			state_root: T::StateRoot::get(),
 			receipts_root,
 			logs_bloom,
-			difficulty: U256::zero(),
+			difficulty: T::HeaderFields::difficulty(),
 			number: block_number,
 			gas_limit: T::BlockGasLimit::get(),
 			gas_used: receipts
@@ -338,16 +435,53 @@ impl<T: Config> Pallet<T> {
 			timestamp: UniqueSaturatedInto::<u64>::unique_saturated_into(
 				pallet_timestamp::Pallet::<T>::get(),
 			),
-			extra_data: Vec::new(),
-			mix_hash: H256::default(),
-			nonce: H64::default(),
+			extra_data: T::HeaderFields::extra_data(),
+			mix_hash: T::HeaderFields::mix_hash(),
+			nonce: T::HeaderFields::nonce(),
 		};
 		let block = ethereum::Block::new(partial_header, transactions.clone(), ommers);
 
+		let block_hash = block.header.hash();
+		if T::IndexTransactionsByHash::get() {
+			for (transaction, status) in transactions.iter().zip(statuses.iter()) {
+				TransactionIndexByHash::<T>::insert(
+					status.transaction_hash,
+					(status.transaction_index, transaction.gas_price),
+				);
+			}
+		}
+
+		if T::BlockMetrics::get() {
+			let contracts_created = statuses
+				.iter()
+				.zip(receipts.iter())
+				.filter(|(status, receipt)| {
+					status.to.is_none() && receipt.state_root == H256::from_low_u64_be(1)
+				})
+				.count();
+			Self::deposit_event(Event::BlockMetrics(
+				block.header.gas_used,
+				transactions.len() as u32,
+				contracts_created as u32,
+			));
+		}
+
 		CurrentBlock::<T>::put(block.clone());
-		CurrentReceipts::<T>::put(receipts.clone());
+		if T::CompactReceipts::get() {
+			CurrentReceipts::<T>::put(
+				receipts
+					.into_iter()
+					.map(|receipt| ethereum::Receipt {
+						logs: Vec::new(),
+						..receipt
+					})
+					.collect::<Vec<_>>(),
+			);
+		} else {
+			CurrentReceipts::<T>::put(receipts);
+		}
 		CurrentTransactionStatuses::<T>::put(statuses.clone());
-		BlockHash::<T>::insert(block_number, block.header.hash());
+		BlockHash::<T>::insert(block_number, block_hash);
 
 		if post_log {
 			let digest = DigestItem::<T::Hash>::Consensus(
@@ -376,19 +510,16 @@ impl<T: Config> Pallet<T> {
 	) -> Result<U256, TransactionValidityError> {
 		// We must ensure a transaction can pay the cost of its data bytes.
 		// If it can't it should not be included in a block.
-		let mut gasometer = evm::gasometer::Gasometer::new(
-			transaction.gas_limit.low_u64(),
+		let intrinsic_gas = fp_evm::intrinsic_gas(
 			<T as pallet_evm::Config>::config(),
+			matches!(transaction.action, TransactionAction::Create),
+			&transaction.input,
+			// Legacy transactions carry no access list.
+			&[],
 		);
-		let transaction_cost = match transaction.action {
-			TransactionAction::Call(_) => evm::gasometer::call_transaction_cost(&transaction.input),
-			TransactionAction::Create => {
-				evm::gasometer::create_transaction_cost(&transaction.input)
-			}
-		};
-		if gasometer.record_transaction(transaction_cost).is_err() {
+		if transaction.gas_limit < U256::from(intrinsic_gas) {
 			return Err(InvalidTransaction::Custom(
-				TransactionValidationError::InvalidGasLimit as u8,
+				TransactionValidationError::IntrinsicGasTooLow as u8,
 			)
 			.into());
 		}
@@ -412,7 +543,16 @@ impl<T: Config> Pallet<T> {
 		let account_data = pallet_evm::Pallet::<T>::account_basic(&origin);
 
 		let fee = transaction.gas_price.saturating_mul(transaction.gas_limit);
-		let total_payment = transaction.value.saturating_add(fee);
+		let target = match transaction.action {
+			TransactionAction::Call(target) => Some(target),
+			TransactionAction::Create => None,
+		};
+		// The part of the fee paid in another asset is not covered by the balance.
+		let native_fee = <T as pallet_evm::Config>::OnChargeTransaction::ensure_fee_payable(
+			&origin, target, fee,
+		)
+		.map_err(|_| InvalidTransaction::Payment)?;
+		let total_payment = transaction.value.saturating_add(native_fee);
 		if account_data.balance < total_payment {
 			return Err(InvalidTransaction::Payment.into());
 		}
@@ -423,6 +563,8 @@ impl<T: Config> Pallet<T> {
 			return Err(InvalidTransaction::Payment.into());
 		}
 
+		T::ExtraValidation::validate(&origin, transaction)?;
+
 		Ok(account_data.nonce)
 	}
 
@@ -439,6 +581,13 @@ impl<T: Config> Pallet<T> {
 			return Err(InvalidTransaction::Stale.into());
 		}
 
+		if transaction.gas_price < T::PoolMinGasPrice::get() {
+			return Err(InvalidTransaction::Custom(
+				TransactionValidationError::GasPriceTooLow as u8,
+			)
+			.into());
+		}
+
 		// The tag provides and requires must be filled correctly according to the nonce.
 		let mut builder = ValidTransactionBuilder::default()
 			.and_provides((origin, transaction.nonce))
@@ -455,22 +604,52 @@ impl<T: Config> Pallet<T> {
 		builder.build()
 	}
 
-	fn apply_validated_transaction(source: H160, transaction: Transaction) -> PostDispatchInfo {
+	/// Apply a transaction from the given source, which must have been validated beforehand.
+	pub fn apply_validated_transaction(source: H160, transaction: Transaction) -> PostDispatchInfo {
 		let transaction_hash =
 			H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
 		let transaction_index = Pending::<T>::get().len() as u32;
 
-		let (to, _, info) = Self::execute(
-			source,
-			transaction.input.clone(),
-			transaction.value,
-			transaction.gas_limit,
-			Some(transaction.gas_price),
-			Some(transaction.nonce),
-			transaction.action,
-			None,
-		)
-		.expect("transaction is already validated; error indicates that the block is invalid");
+		let execute = || {
+			Self::execute(
+				source,
+				transaction.input.clone(),
+				transaction.value,
+				transaction.gas_limit,
+				Some(transaction.gas_price),
+				Some(transaction.nonce),
+				transaction.action,
+				None,
+			)
+		};
+		#[cfg(feature = "tracing")]
+		let (executed, transfers) = if T::RecordInternalTransfers::get() {
+			pallet_evm::tracer::record_transfers(execute)
+		} else {
+			(execute(), Vec::new())
+		};
+		#[cfg(not(feature = "tracing"))]
+		let (executed, transfers) = (execute(), Vec::<evm::Transfer>::new());
+		let (to, _, info) = executed
+			.expect("transaction is already validated; error indicates that the block is invalid");
+		if !transfers.is_empty() {
+			let recorded = InternalTransfers::<T>::decode_len().unwrap_or_default() as u32;
+			let room = T::MaxInternalTransfers::get().saturating_sub(recorded);
+			let transfers = transfers.into_iter().take(room as usize);
+			let count = transfers.len() as Weight;
+			for transfer in transfers {
+				InternalTransfers::<T>::append(InternalTransfer {
+					transaction_index,
+					from: transfer.source,
+					to: transfer.target,
+					value: transfer.value,
+				});
+			}
+			frame_system::Pallet::<T>::register_extra_weight_unchecked(
+				T::DbWeight::get().reads_writes(1, count),
+				DispatchClass::Normal,
+			);
+		}
 
 		let (reason, status, used_gas, dest) = match info {
 			CallOrCreateInfo::Call(info) => (
@@ -545,6 +724,47 @@ impl<T: Config> Pallet<T> {
 		CurrentTransactionStatuses::<T>::get()
 	}
 
+	/// Get the status and receipt of a transaction of the current block by hash, None unless
+	/// `IndexTransactionsByHash`.
+	pub fn transaction_status_by_hash(hash: H256) -> Option<IndexedTransactionStatus> {
+		let (index, gas_price) = TransactionIndexByHash::<T>::get(hash)?;
+		let index = index as usize;
+		let mut statuses = CurrentTransactionStatuses::<T>::get()?;
+		let receipts = CurrentReceipts::<T>::get()?;
+		if index >= statuses.len() || index >= receipts.len() {
+			return None;
+		}
+		let first_log_index = statuses[..index].iter().fold(0u32, |acc, status| {
+			acc.saturating_add(status.logs.len() as u32)
+		});
+		let cumulative_gas_used = receipts[..=index]
+			.iter()
+			.fold(U256::zero(), |acc, receipt| {
+				acc.saturating_add(receipt.used_gas)
+			});
+		let block_number = U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(
+			frame_system::Pallet::<T>::block_number(),
+		));
+		Some(IndexedTransactionStatus {
+			block_hash: BlockHash::<T>::get(block_number),
+			block_number,
+			status: statuses.swap_remove(index),
+			receipt: ethereum::Receipt {
+				logs: Vec::new(),
+				..receipts[index].clone()
+			},
+			cumulative_gas_used,
+			first_log_index,
+			gas_price,
+		})
+	}
+
+	/// Get the value transfers below the top level calls of the transactions of the current
+	/// block, empty unless `RecordInternalTransfers`.
+	pub fn internal_transfers() -> Vec<InternalTransfer> {
+		InternalTransfers::<T>::get()
+	}
+
 	/// Get current block.
 	pub fn current_block() -> Option<ethereum::BlockV0> {
 		CurrentBlock::<T>::get()
@@ -555,7 +775,37 @@ impl<T: Config> Pallet<T> {
 		Self::current_block().map(|block| block.header.hash())
 	}
 
-	/// Get receipts by number.
+	/// Get the current block gas limit.
+	pub fn block_gas_limit() -> U256 {
+		BlockGasLimit::<T>::get()
+	}
+
+	fn step_block_gas_limit() -> Weight {
+		let target = match BlockGasLimitTarget::<T>::get() {
+			Some(target) => target,
+			None => return T::DbWeight::get().reads(1),
+		};
+		let current = BlockGasLimit::<T>::get();
+		let next = match T::GasLimitBoundDivisor::get() {
+			Some(divisor) if !divisor.is_zero() => {
+				let step = (current / divisor).max(U256::one());
+				if target > current {
+					target.min(current.saturating_add(step))
+				} else {
+					target.max(current.saturating_sub(step))
+				}
+			}
+			_ => target,
+		};
+
+		BlockGasLimit::<T>::put(next);
+		if next == target {
+			BlockGasLimitTarget::<T>::kill();
+		}
+		T::DbWeight::get().reads_writes(2, 2)
+	}
+
+	/// Get receipts by number. In compact receipts mode their logs are left empty.
 	pub fn current_receipts() -> Option<Vec<ethereum::Receipt>> {
 		CurrentReceipts::<T>::get()
 	}
@@ -647,6 +897,54 @@ impl Default for EthereumStorageSchema {
 	}
 }
 
+/// Fields of the Ethereum block headers which are not derived from the execution. The defaults
+/// follow the post-merge conventions.
+pub trait HeaderFields {
+	/// Beneficiary of the block, its `miner`.
+	fn beneficiary() -> H160;
+	/// Extra data of the block.
+	fn extra_data() -> Vec<u8> {
+		Vec::new()
+	}
+	/// Difficulty of the block.
+	fn difficulty() -> U256 {
+		U256::zero()
+	}
+	/// Mix hash of the block.
+	fn mix_hash() -> H256 {
+		H256::default()
+	}
+	/// Nonce of the block.
+	fn nonce() -> H64 {
+		H64::default()
+	}
+}
+
+/// Chain-specific validity rules of the Ethereum transactions, e.g. sender allowlists or paused
+/// contracts, imposed by the runtime without forking the pallet.
+///
+/// The RPC reports the standard rejections meaningfully: `BadSigner` as `sender not allowed`,
+/// `Call` as `transaction not allowed` and `Payment` as insufficient funds. Custom codes must
+/// not collide with the `TransactionValidationError` codes, and are reported with the code.
+pub trait ExtraValidation {
+	/// Validates `transaction` sent by `origin`.
+	fn validate(origin: &H160, transaction: &Transaction) -> Result<(), InvalidTransaction>;
+}
+
+impl ExtraValidation for () {
+	fn validate(_origin: &H160, _transaction: &Transaction) -> Result<(), InvalidTransaction> {
+		Ok(())
+	}
+}
+
+/// Header fields with the block author given by the `FindAuthor` of pallet-evm as beneficiary.
+pub struct EvmAuthorHeaderFields<T>(PhantomData<T>);
+impl<T: Config> HeaderFields for EvmAuthorHeaderFields<T> {
+	fn beneficiary() -> H160 {
+		pallet_evm::Pallet::<T>::find_author()
+	}
+}
+
 pub struct IntermediateStateRoot;
 impl Get<H256> for IntermediateStateRoot {
 	fn get() -> H256 {
@@ -655,6 +953,14 @@ impl Get<H256> for IntermediateStateRoot {
 	}
 }
 
+/// Block gas limit stored by the pallet, to be used as `pallet_evm::Config::BlockGasLimit`.
+pub struct EthereumBlockGasLimit<T>(PhantomData<T>);
+impl<T: Config> Get<U256> for EthereumBlockGasLimit<T> {
+	fn get() -> U256 {
+		BlockGasLimit::<T>::get()
+	}
+}
+
 /// Returns the Ethereum block hash by number.
 pub struct EthereumBlockHashMapping<T>(PhantomData<T>);
 impl<T: Config> BlockHashMapping for EthereumBlockHashMapping<T> {
@@ -663,11 +969,29 @@ impl<T: Config> BlockHashMapping for EthereumBlockHashMapping<T> {
 	}
 }
 
+/// Custom codes of the transactions rejected by the pallet rules.
 #[repr(u8)]
-enum TransactionValidationError {
-	#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum TransactionValidationError {
 	UnknownError,
 	InvalidChainId,
 	InvalidSignature,
 	InvalidGasLimit,
+	IntrinsicGasTooLow,
+	GasPriceTooLow,
+}
+
+impl TransactionValidationError {
+	/// Error of the custom validity `code`, None for the codes of `ExtraValidation`.
+	pub fn from_code(code: u8) -> Option<Self> {
+		match code {
+			0 => Some(Self::UnknownError),
+			1 => Some(Self::InvalidChainId),
+			2 => Some(Self::InvalidSignature),
+			3 => Some(Self::InvalidGasLimit),
+			4 => Some(Self::IntrinsicGasTooLow),
+			5 => Some(Self::GasPriceTooLow),
+			_ => None,
+		}
+	}
 }
@@ -133,6 +133,13 @@ impl FindAuthor<H160> for FindAuthorTruncated {
 parameter_types! {
 	pub const TransactionByteFee: u64 = 1;
 	pub const ChainId: u64 = 42;
+	pub static PoolMinGasPrice: U256 = U256::zero();
+	pub static CompactReceipts: bool = false;
+	pub static GasLimitBoundDivisor: Option<U256> = None;
+	pub static PausedContract: Option<H160> = None;
+	pub static RecordInternalTransfers: bool = false;
+	pub static MaxInternalTransfers: u32 = 16;
+	pub static IndexTransactionsByHash: bool = false;
 	pub const EVMModuleId: PalletId = PalletId(*b"py/evmpa");
 	pub const BlockGasLimit: U256 = U256::MAX;
 }
@@ -158,15 +165,43 @@ impl pallet_evm::Config for Test {
 	type Precompiles = ();
 	type Runner = pallet_evm::runner::stack::Runner<Self>;
 	type ChainId = ChainId;
-	type BlockGasLimit = BlockGasLimit;
+	type BlockGasLimit = crate::EthereumBlockGasLimit<Self>;
 	type OnChargeTransaction = ();
 	type FindAuthor = FindAuthorTruncated;
 	type BlockHashMapping = crate::EthereumBlockHashMapping<Self>;
+	type CountPrecompileCalls = ();
 }
 
 impl crate::Config for Test {
 	type Event = Event;
 	type StateRoot = IntermediateStateRoot;
+	type HeaderFields = crate::EvmAuthorHeaderFields<Self>;
+	type PoolMinGasPrice = PoolMinGasPrice;
+	type CompactReceipts = CompactReceipts;
+	type GasLimitOrigin = frame_system::EnsureRoot<AccountId32>;
+	type DefaultBlockGasLimit = BlockGasLimit;
+	type GasLimitBoundDivisor = GasLimitBoundDivisor;
+	type BlockMetrics = ();
+	type RecordInternalTransfers = RecordInternalTransfers;
+	type MaxInternalTransfers = MaxInternalTransfers;
+	type IndexTransactionsByHash = IndexTransactionsByHash;
+	type ExtraValidation = PausedContractValidation;
+}
+
+/// Rejects the calls to `PausedContract`.
+pub struct PausedContractValidation;
+impl crate::ExtraValidation for PausedContractValidation {
+	fn validate(
+		_origin: &H160,
+		transaction: &crate::Transaction,
+	) -> Result<(), InvalidTransaction> {
+		match (&transaction.action, PausedContract::get()) {
+			(TransactionAction::Call(to), Some(paused)) if *to == paused => {
+				Err(InvalidTransaction::Call)
+			}
+			_ => Ok(()),
+		}
+	}
 }
 
 impl fp_self_contained::SelfContainedCall for Call {
@@ -18,7 +18,8 @@
 //! Consensus extension module tests for BABE consensus.
 
 use crate::{
-	mock::*, CallOrCreateInfo, Error, RawOrigin, Transaction, TransactionAction, H160, H256, U256,
+	mock::*, Bloom, CallOrCreateInfo, Error, RawOrigin, Transaction, TransactionAction, H160, H256,
+	U256,
 };
 use ethereum::TransactionSignature;
 use frame_support::{
@@ -95,6 +96,47 @@ fn transaction_without_enough_gas_should_not_work() {
 	});
 }
 
+#[test]
+fn transaction_with_intrinsic_gas_too_low_should_not_work() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		// The create base cost alone is 53_000.
+		let mut transaction = default_erc20_creation_unsigned_transaction();
+		transaction.gas_limit = U256::from(53_000);
+
+		let signed = transaction.sign(&alice.private_key);
+		let call = crate::Call::<Test>::transact(signed);
+		let source = call.check_self_contained().unwrap().unwrap();
+
+		assert_err!(
+			call.validate_self_contained(&source).unwrap(),
+			InvalidTransaction::Custom(crate::TransactionValidationError::IntrinsicGasTooLow as u8)
+		);
+	});
+}
+
+#[test]
+fn transaction_below_pool_min_gas_price_should_not_work() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		PoolMinGasPrice::set(U256::from(2));
+
+		let transaction = default_erc20_creation_unsigned_transaction();
+		let signed = transaction.sign(&alice.private_key);
+		let call = crate::Call::<Test>::transact(signed);
+		let source = call.check_self_contained().unwrap().unwrap();
+
+		assert_err!(
+			call.validate_self_contained(&source).unwrap(),
+			InvalidTransaction::Custom(crate::TransactionValidationError::GasPriceTooLow as u8)
+		);
+	});
+}
+
 #[test]
 fn transaction_with_to_low_nonce_should_not_work() {
 	let (pairs, mut ext) = new_test_ext(1);
@@ -196,6 +238,45 @@ fn transaction_with_invalid_chain_id_should_fail_in_block() {
 	});
 }
 
+#[test]
+fn transaction_rejected_by_extra_validation_should_not_work() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let paused = H160::repeat_byte(0xee);
+		PausedContract::set(Some(paused));
+
+		let mut transaction = default_erc20_creation_unsigned_transaction();
+		transaction.action = TransactionAction::Call(paused);
+		transaction.input = Vec::new();
+
+		let call = crate::Call::<Test>::transact(transaction.sign(&alice.private_key));
+		let source = call.check_self_contained().unwrap().unwrap();
+		assert_err!(
+			call.validate_self_contained(&source).unwrap(),
+			InvalidTransaction::Call
+		);
+
+		let extrinsic = fp_self_contained::CheckedExtrinsic::<_, _, SignedExtra, _> {
+			signed: fp_self_contained::CheckedSignature::SelfContained(source),
+			function: Call::Ethereum(call),
+		};
+		use frame_support::weights::GetDispatchInfo as _;
+		let dispatch_info = extrinsic.get_dispatch_info();
+		assert_err!(
+			extrinsic.apply::<Test>(&dispatch_info, 0),
+			TransactionValidityError::Invalid(InvalidTransaction::Call)
+		);
+
+		// The other contracts are still callable.
+		transaction.action = TransactionAction::Call(H160::repeat_byte(0xaa));
+		let call = crate::Call::<Test>::transact(transaction.sign(&alice.private_key));
+		let source = call.check_self_contained().unwrap().unwrap();
+		assert_ok!(call.validate_self_contained(&source).unwrap());
+	});
+}
+
 #[test]
 fn contract_constructor_should_get_executed() {
 	let (pairs, mut ext) = new_test_ext(1);
@@ -381,3 +462,223 @@ fn call_should_handle_errors() {
 		.unwrap();
 	});
 }
+
+#[test]
+fn compact_receipts_should_not_store_logs() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		CompactReceipts::set(true);
+
+		let t = default_erc20_creation_transaction(alice);
+		Ethereum::apply_validated_transaction(alice.address, t);
+		Ethereum::store_block(false, U256::one());
+
+		let receipts = Ethereum::current_receipts().unwrap();
+		let statuses = Ethereum::current_transaction_statuses().unwrap();
+		assert!(receipts[0].logs.is_empty());
+		assert!(!statuses[0].logs.is_empty());
+
+		// The receipts root commits to the receipts with their logs.
+		let full_receipt = ethereum::Receipt {
+			logs: statuses[0].logs.clone(),
+			..receipts[0].clone()
+		};
+		assert_eq!(
+			Ethereum::current_block().unwrap().header.receipts_root,
+			ethereum::util::ordered_trie_root(vec![rlp::encode(&full_receipt)]),
+		);
+	});
+}
+
+#[test]
+fn block_logs_bloom_should_be_union_of_receipt_blooms() {
+	let (pairs, mut ext) = new_test_ext(2);
+	let alice = &pairs[0];
+	let bob = &pairs[1];
+
+	ext.execute_with(|| {
+		Ethereum::apply_validated_transaction(
+			alice.address,
+			default_erc20_creation_transaction(alice),
+		);
+		Ethereum::apply_validated_transaction(bob.address, default_erc20_creation_transaction(bob));
+		Ethereum::store_block(false, U256::one());
+
+		let receipts = Ethereum::current_receipts().unwrap();
+		let mut logs_bloom = Bloom::default();
+		for receipt in receipts.iter() {
+			assert_ne!(receipt.logs_bloom, Bloom::default());
+			logs_bloom.accrue_bloom(&receipt.logs_bloom);
+		}
+		assert_eq!(
+			Ethereum::current_block().unwrap().header.logs_bloom,
+			logs_bloom
+		);
+	});
+}
+
+#[test]
+fn block_gas_limit_should_step_toward_target() {
+	let (_, mut ext) = new_test_ext(1);
+
+	ext.execute_with(|| {
+		use frame_support::traits::Hooks;
+
+		assert_noop!(
+			Ethereum::set_block_gas_limit(Origin::root(), U256::zero()),
+			Error::<Test>::ZeroBlockGasLimit
+		);
+
+		assert_ok!(Ethereum::set_block_gas_limit(
+			Origin::root(),
+			U256::from(1_000_000)
+		));
+		Ethereum::on_initialize(1);
+		assert_eq!(Ethereum::block_gas_limit(), U256::from(1_000_000));
+
+		GasLimitBoundDivisor::set(Some(U256::from(1024)));
+		assert_ok!(Ethereum::set_block_gas_limit(
+			Origin::root(),
+			U256::from(1_001_000)
+		));
+		Ethereum::on_initialize(2);
+		assert_eq!(Ethereum::block_gas_limit(), U256::from(1_000_976));
+		Ethereum::on_initialize(3);
+		assert_eq!(Ethereum::block_gas_limit(), U256::from(1_001_000));
+		Ethereum::on_initialize(4);
+		assert_eq!(Ethereum::block_gas_limit(), U256::from(1_001_000));
+	});
+}
+
+#[test]
+fn transaction_status_should_be_indexed_by_hash() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		use frame_support::traits::Hooks;
+
+		IndexTransactionsByHash::set(true);
+		System::set_block_number(1);
+		let t = default_erc20_creation_transaction(alice);
+		Ethereum::apply_validated_transaction(alice.address, t.clone());
+		let t = UnsignedTransaction {
+			nonce: U256::one(),
+			..default_erc20_creation_unsigned_transaction()
+		}
+		.sign(&alice.private_key);
+		Ethereum::apply_validated_transaction(alice.address, t.clone());
+		Ethereum::store_block(false, U256::one());
+
+		let statuses = Ethereum::current_transaction_statuses().unwrap();
+		let hash = statuses[1].transaction_hash;
+		let receipts = Ethereum::current_receipts().unwrap();
+		let indexed = Ethereum::transaction_status_by_hash(hash).unwrap();
+		assert_eq!(indexed.status, statuses[1]);
+		assert!(indexed.receipt.logs.is_empty());
+		assert_eq!(indexed.block_number, U256::one());
+		assert_eq!(indexed.block_hash, Ethereum::current_block_hash().unwrap());
+		assert_eq!(
+			indexed.cumulative_gas_used,
+			receipts[0].used_gas + receipts[1].used_gas
+		);
+		assert_eq!(indexed.first_log_index, statuses[0].logs.len() as u32);
+		assert_eq!(indexed.gas_price, t.gas_price);
+
+		// The index only holds the transactions of the current block.
+		Ethereum::on_initialize(2);
+		assert_eq!(Ethereum::transaction_status_by_hash(hash), None);
+	});
+}
+
+#[test]
+fn transactions_should_not_be_indexed_by_hash_unless_enabled() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let t = default_erc20_creation_transaction(alice);
+		Ethereum::apply_validated_transaction(alice.address, t);
+		Ethereum::store_block(false, U256::zero());
+
+		let statuses = Ethereum::current_transaction_statuses().unwrap();
+		assert_eq!(
+			Ethereum::transaction_status_by_hash(statuses[0].transaction_hash),
+			None
+		);
+	});
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn internal_transfers_should_be_recorded() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+	let recipient = H160::repeat_byte(0x11);
+	// CALL(gas, recipient, 1000, 0, 0, 0, 0) from the init code, then STOP or REVERT.
+	let init_code = |end: &str| -> Vec<u8> {
+		format!("60006000600060006103e873{}5af1{}", "11".repeat(20), end)
+			.from_hex()
+			.unwrap()
+	};
+	let creation = |nonce: u64, input: Vec<u8>| {
+		UnsignedTransaction {
+			nonce: U256::from(nonce),
+			value: U256::from(2000),
+			input,
+			..default_erc20_creation_unsigned_transaction()
+		}
+		.sign(&alice.private_key)
+	};
+
+	ext.execute_with(|| {
+		use frame_support::traits::Hooks;
+
+		RecordInternalTransfers::set(true);
+		Ethereum::apply_validated_transaction(alice.address, creation(0, init_code("00")));
+		// The transfers of a reverted transaction are dropped.
+		Ethereum::apply_validated_transaction(alice.address, creation(1, init_code("60006000fd")));
+
+		assert_eq!(
+			Ethereum::internal_transfers(),
+			vec![crate::InternalTransfer {
+				transaction_index: 0,
+				from: contract_address(alice.address, 0),
+				to: recipient,
+				value: U256::from(1000),
+			}]
+		);
+		assert_eq!(EVM::account_basic(&recipient).balance, U256::from(1000));
+
+		Ethereum::on_initialize(2);
+		assert!(Ethereum::internal_transfers().is_empty());
+	});
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn internal_transfers_should_be_bounded() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+	let recipient = H160::repeat_byte(0x11);
+	// Two CALLs of 1000 to the recipient from the init code.
+	let call = format!("60006000600060006103e873{}5af150", "11".repeat(20));
+	let transaction = UnsignedTransaction {
+		nonce: U256::zero(),
+		value: U256::from(2000),
+		input: format!("{}{}00", call, call).from_hex().unwrap(),
+		..default_erc20_creation_unsigned_transaction()
+	}
+	.sign(&alice.private_key);
+
+	ext.execute_with(|| {
+		RecordInternalTransfers::set(true);
+		MaxInternalTransfers::set(1);
+		Ethereum::apply_validated_transaction(alice.address, transaction);
+
+		assert_eq!(Ethereum::internal_transfers().len(), 1);
+		assert_eq!(EVM::account_basic(&recipient).balance, U256::from(2000));
+	});
+}
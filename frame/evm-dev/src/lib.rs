@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # EVM dev pallet
+//!
+//! Applies the state changes requested through the development RPC (balances, code,
+//! storage and impersonated transactions) at the beginning of the next sealed block.
+//!
+//! The commands are provided as inherent data by the block author and are not checked
+//! in any way: this pallet must only be included in runtimes of development chains. It is
+//! inert unless enabled in the genesis config, blocks carrying dev commands on a chain which
+//! did not enable it fail the inherent check.
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use async_trait::async_trait;
+use codec::{Decode, Encode};
+use frame_support::{inherent::IsFatalError, traits::Currency, weights::Weight};
+use pallet_evm::{AddressMapping, GasWeightMapping};
+use sp_core::{H160, H256, U256};
+use sp_inherents::{InherentData, InherentIdentifier};
+use sp_runtime::{traits::UniqueSaturatedInto, RuntimeDebug};
+use sp_std::{result, vec::Vec};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+/// A state change requested through the development RPC.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum DevCommand {
+	/// Set the free balance of an account.
+	SetBalance(H160, U256),
+	/// Set the nonce of an account.
+	SetNonce(H160, U256),
+	/// Replace the code of an account, removing it if empty.
+	SetCode(H160, Vec<u8>),
+	/// Set a storage slot of an account.
+	SetStorage(H160, H256, H256),
+	/// Apply a transaction on behalf of the given account, without checking its signature.
+	Transact(H160, ethereum::TransactionV0),
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_evm::Config + pallet_ethereum::Config {}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig {
+		/// Whether the chain is a development chain accepting the dev commands.
+		pub enabled: bool,
+	}
+
+	#[cfg(feature = "std")]
+	impl Default for GenesisConfig {
+		fn default() -> Self {
+			Self { enabled: false }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			Enabled::<T>::put(self.enabled);
+		}
+	}
+
+	/// Whether the dev commands are accepted, set at genesis for development chains only.
+	#[pallet::storage]
+	#[pallet::getter(fn enabled)]
+	pub type Enabled<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The chain is not a development chain.
+		NotDevChain,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight((
+			commands_weight::<T>(commands),
+			DispatchClass::Mandatory,
+			Pays::No
+		))]
+		pub fn apply_dev_commands(
+			origin: OriginFor<T>,
+			commands: Vec<DevCommand>,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			ensure!(Self::enabled(), Error::<T>::NotDevChain);
+
+			let mut weight: Weight = 0;
+			for command in commands {
+				if !matches!(command, DevCommand::Transact(..)) {
+					weight = weight.saturating_add(T::DbWeight::get().writes(1));
+				}
+
+				match command {
+					DevCommand::SetBalance(address, balance) => {
+						let account_id =
+							<T as pallet_evm::Config>::AddressMapping::into_account_id(address);
+						<T as pallet_evm::Config>::Currency::make_free_balance_be(
+							&account_id,
+							UniqueSaturatedInto::<u128>::unique_saturated_into(balance)
+								.unique_saturated_into(),
+						);
+					}
+					DevCommand::SetNonce(address, nonce) => {
+						let account_id =
+							<T as pallet_evm::Config>::AddressMapping::into_account_id(address);
+						frame_system::Account::<T>::mutate(&account_id, |account| {
+							account.nonce =
+								UniqueSaturatedInto::<u64>::unique_saturated_into(nonce)
+									.unique_saturated_into();
+						});
+					}
+					DevCommand::SetCode(address, code) => {
+						if code.is_empty() {
+							pallet_evm::AccountCodes::<T>::remove(address);
+						} else {
+							pallet_evm::Pallet::<T>::create_account(address, code);
+						}
+					}
+					DevCommand::SetStorage(address, index, value) => {
+						pallet_evm::AccountStorages::<T>::insert(address, index, value);
+					}
+					DevCommand::Transact(source, transaction) => {
+						// Invalid transactions are skipped, they would make the block invalid.
+						if pallet_ethereum::Pallet::<T>::validate_transaction_in_block(
+							source,
+							&transaction,
+						)
+						.is_ok()
+						{
+							let gas_limit = transaction.gas_limit;
+							let post_info =
+								pallet_ethereum::Pallet::<T>::apply_validated_transaction(
+									source,
+									transaction,
+								);
+							weight = weight.saturating_add(
+								post_info
+									.actual_weight
+									.unwrap_or_else(|| transact_weight::<T>(gas_limit)),
+							);
+						}
+					}
+				}
+			}
+
+			Ok((Some(weight), Pays::No).into())
+		}
+	}
+
+	#[derive(Encode, Decode, RuntimeDebug)]
+	pub enum InherentError {
+		/// Dev commands were included in a block of a chain which is not a development chain.
+		NotDevChain,
+	}
+
+	impl IsFatalError for InherentError {
+		fn is_fatal_error(&self) -> bool {
+			match *self {
+				InherentError::NotDevChain => true,
+			}
+		}
+	}
+
+	#[pallet::inherent]
+	impl<T: Config> ProvideInherent for Pallet<T> {
+		type Call = Call<T>;
+		type Error = InherentError;
+		const INHERENT_IDENTIFIER: InherentIdentifier = INHERENT_IDENTIFIER;
+
+		fn create_inherent(data: &InherentData) -> Option<Self::Call> {
+			if !Self::enabled() {
+				return None;
+			}
+
+			let commands = data.get_data::<InherentType>(&INHERENT_IDENTIFIER).ok()??;
+			if commands.is_empty() {
+				return None;
+			}
+
+			Some(Call::apply_dev_commands(commands))
+		}
+
+		fn check_inherent(
+			_call: &Self::Call,
+			_data: &InherentData,
+		) -> result::Result<(), Self::Error> {
+			if Self::enabled() {
+				Ok(())
+			} else {
+				Err(InherentError::NotDevChain)
+			}
+		}
+
+		fn is_inherent(call: &Self::Call) -> bool {
+			matches!(call, Call::apply_dev_commands(_))
+		}
+	}
+}
+
+/// Weight of a transaction applied with `gas_limit`, the one charged for its whole gas limit.
+fn transact_weight<T: Config>(gas_limit: U256) -> Weight {
+	<T as pallet_evm::Config>::GasWeightMapping::gas_to_weight(gas_limit.unique_saturated_into())
+}
+
+/// Weight charged before dispatch for `commands`, a storage write per state change and the
+/// gas limit of the transactions.
+fn commands_weight<T: Config>(commands: &[DevCommand]) -> Weight {
+	commands.iter().fold(0, |weight: Weight, command| {
+		weight.saturating_add(match command {
+			DevCommand::Transact(_, transaction) => transact_weight::<T>(transaction.gas_limit),
+			_ => T::DbWeight::get().writes(1),
+		})
+	})
+}
+
+pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"evmdev0_";
+
+pub type InherentType = Vec<DevCommand>;
+
+#[cfg(feature = "std")]
+pub struct InherentDataProvider(pub InherentType);
+
+#[cfg(feature = "std")]
+#[async_trait]
+impl sp_inherents::InherentDataProvider for InherentDataProvider {
+	fn provide_inherent_data(
+		&self,
+		inherent_data: &mut InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		inherent_data.put_data(INHERENT_IDENTIFIER, &self.0)
+	}
+
+	async fn try_handle_error(
+		&self,
+		identifier: &InherentIdentifier,
+		error: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		if *identifier != INHERENT_IDENTIFIER {
+			return None;
+		}
+
+		let error = InherentError::decode(&mut &error[..]).ok()?;
+		Some(Err(sp_inherents::Error::Application(Box::from(format!(
+			"{:?}",
+			error
+		)))))
+	}
+}
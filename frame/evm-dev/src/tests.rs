@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2021 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{mock::*, DevCommand, Error, InherentError, INHERENT_IDENTIFIER};
+use frame_support::{
+	assert_noop, assert_ok,
+	inherent::ProvideInherent,
+	weights::{GetDispatchInfo, Pays},
+};
+use pallet_evm::AddressMapping;
+use sp_core::{H160, H256, U256};
+use sp_inherents::InherentData;
+
+fn inherent_data(commands: Vec<DevCommand>) -> InherentData {
+	let mut data = InherentData::new();
+	data.put_data(INHERENT_IDENTIFIER, &commands).unwrap();
+	data
+}
+
+#[test]
+fn commands_are_applied_on_dev_chain() {
+	new_test_ext(true).execute_with(|| {
+		let address = H160::repeat_byte(0x11);
+		let account_id = HashedAddressMapping::into_account_id(address);
+
+		assert_ok!(EVMDev::apply_dev_commands(
+			Origin::none(),
+			vec![
+				DevCommand::SetBalance(address, U256::from(1_000)),
+				DevCommand::SetNonce(address, U256::from(7)),
+				DevCommand::SetCode(address, vec![0x60, 0x00]),
+				DevCommand::SetStorage(address, H256::repeat_byte(1), H256::repeat_byte(2)),
+			],
+		));
+
+		assert_eq!(Balances::free_balance(&account_id), 1_000);
+		assert_eq!(System::account_nonce(&account_id), 7);
+		assert_eq!(
+			pallet_evm::AccountCodes::<Test>::get(address),
+			vec![0x60, 0x00]
+		);
+		assert_eq!(
+			pallet_evm::AccountStorages::<Test>::get(address, H256::repeat_byte(1)),
+			H256::repeat_byte(2)
+		);
+
+		assert_ok!(EVMDev::apply_dev_commands(
+			Origin::none(),
+			vec![DevCommand::SetCode(address, Vec::new())],
+		));
+		assert!(pallet_evm::AccountCodes::<Test>::get(address).is_empty());
+	});
+}
+
+#[test]
+fn overflowing_balance_and_nonce_saturate() {
+	new_test_ext(true).execute_with(|| {
+		let address = H160::repeat_byte(0x22);
+		let account_id = HashedAddressMapping::into_account_id(address);
+
+		// Both values have their low bits cleared, a truncation would set them to zero.
+		assert_ok!(EVMDev::apply_dev_commands(
+			Origin::none(),
+			vec![
+				DevCommand::SetBalance(address, U256::one() << 128),
+				DevCommand::SetNonce(address, U256::one() << 64),
+			],
+		));
+
+		assert_eq!(Balances::free_balance(&account_id), u64::MAX);
+		assert_eq!(System::account_nonce(&account_id), u64::MAX);
+	});
+}
+
+#[test]
+fn transactions_are_charged_their_gas_limit() {
+	let transaction = ethereum::TransactionV0 {
+		nonce: U256::zero(),
+		gas_price: U256::one(),
+		gas_limit: U256::from(100_000),
+		action: ethereum::TransactionAction::Call(H160::repeat_byte(0x55)),
+		value: U256::zero(),
+		input: Vec::new(),
+		signature: ethereum::TransactionSignature::new(
+			27,
+			H256::repeat_byte(0x11),
+			H256::repeat_byte(0x22),
+		)
+		.unwrap(),
+	};
+	let call = crate::Call::<Test>::apply_dev_commands(vec![
+		DevCommand::SetNonce(H160::repeat_byte(0x55), U256::one()),
+		DevCommand::Transact(H160::repeat_byte(0x66), transaction),
+	]);
+
+	// The mock maps a gas unit to a weight unit and its storage writes are free.
+	assert_eq!(call.get_dispatch_info().weight, 100_000);
+	assert_eq!(call.get_dispatch_info().pays_fee, Pays::No);
+}
+
+#[test]
+fn commands_are_rejected_on_other_chains() {
+	new_test_ext(false).execute_with(|| {
+		let address = H160::repeat_byte(0x33);
+
+		assert_noop!(
+			EVMDev::apply_dev_commands(
+				Origin::none(),
+				vec![DevCommand::SetBalance(address, U256::from(1_000))],
+			),
+			Error::<Test>::NotDevChain
+		);
+	});
+}
+
+#[test]
+fn inherent_is_only_created_and_accepted_on_dev_chain() {
+	let commands = vec![DevCommand::SetNonce(H160::repeat_byte(0x44), U256::one())];
+	let call = crate::Call::<Test>::apply_dev_commands(commands.clone());
+
+	new_test_ext(true).execute_with(|| {
+		assert_eq!(
+			EVMDev::create_inherent(&inherent_data(commands.clone())),
+			Some(call.clone())
+		);
+		assert_eq!(EVMDev::create_inherent(&inherent_data(Vec::new())), None);
+		assert!(EVMDev::check_inherent(&call, &InherentData::new()).is_ok());
+	});
+
+	new_test_ext(false).execute_with(|| {
+		assert_eq!(
+			EVMDev::create_inherent(&inherent_data(commands.clone())),
+			None
+		);
+		assert!(matches!(
+			EVMDev::check_inherent(&call, &InherentData::new()),
+			Err(InherentError::NotDevChain)
+		));
+	});
+}
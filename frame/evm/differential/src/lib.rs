@@ -0,0 +1,509 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # EVM pallet differential tests
+//!
+//! Replays the Ethereum general state tests through the EVM pallet and through
+//! [revm](https://github.com/bluealloy/revm), and compares the outcomes: status, used gas and
+//! post state. Both engines run the same transaction in the same block context, so that any
+//! difference comes from the engines, e.g. after bumping the `evm` crate or changing the EVM
+//! config.
+//!
+//! The block context is the one the pallet provides: the test `currentNumber`,
+//! `currentTimestamp`, `currentCoinbase` and `currentGasLimit`, a zero difficulty and chain id.
+//! The fees are burnt by the pallet and paid to the coinbase by revm, the coinbase account is
+//! left out of the comparison.
+
+pub mod mock;
+
+use frame_support::traits::Currency;
+use mock::{Balances, System, Test, Timestamp};
+use pallet_evm::Runner;
+use serde::Deserialize;
+use sp_core::{H160, H256, U256};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// State of an account.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountState {
+	pub balance: U256,
+	pub nonce: U256,
+	pub code: Vec<u8>,
+	/// Non zero storage slots.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+impl AccountState {
+	/// Empty accounts are removed at the end of a transaction (EIP-161).
+	fn is_empty(&self) -> bool {
+		self.balance.is_zero() && self.nonce.is_zero() && self.code.is_empty()
+	}
+}
+
+/// Block context of a state test.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateTestEnv {
+	pub coinbase: H160,
+	pub gas_limit: U256,
+	pub number: U256,
+	pub timestamp: U256,
+}
+
+/// Transaction of a state test case, with the data, gas and value of its indexes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateTestTransaction {
+	pub sender: H160,
+	/// `None` for contract creations.
+	pub to: Option<H160>,
+	pub nonce: U256,
+	pub gas_price: U256,
+	pub gas_limit: U256,
+	pub value: U256,
+	pub data: Vec<u8>,
+}
+
+/// A transaction of a state test, applied to its pre state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateTestCase {
+	/// Test name and `data`, `gas` and `value` indexes.
+	pub name: String,
+	pub env: StateTestEnv,
+	pub pre: BTreeMap<H160, AccountState>,
+	pub transaction: StateTestTransaction,
+}
+
+/// How a transaction ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+	Succeeded,
+	/// Reverted or failed, the gas is charged.
+	Failed,
+	/// Invalid transaction, nothing is applied.
+	Rejected,
+}
+
+/// Result of a state test case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+	pub status: Status,
+	pub used_gas: U256,
+	/// Non empty accounts after the transaction.
+	pub accounts: BTreeMap<H160, AccountState>,
+}
+
+impl Outcome {
+	/// Differences with `other`, ignoring the `coinbase` account.
+	pub fn differences(&self, other: &Outcome, coinbase: H160) -> Vec<String> {
+		let mut differences = Vec::new();
+		if self.status != other.status {
+			differences.push(format!("status {:?} != {:?}", self.status, other.status));
+		}
+		if self.used_gas != other.used_gas {
+			differences.push(format!("used gas {} != {}", self.used_gas, other.used_gas));
+		}
+
+		let empty = AccountState::default();
+		let addresses = self.accounts.keys().chain(other.accounts.keys());
+		for address in addresses.collect::<std::collections::BTreeSet<_>>() {
+			if *address == coinbase {
+				continue;
+			}
+			let left = self.accounts.get(address).unwrap_or(&empty);
+			let right = other.accounts.get(address).unwrap_or(&empty);
+			if left.balance != right.balance {
+				differences.push(format!(
+					"{:?} balance {} != {}",
+					address, left.balance, right.balance
+				));
+			}
+			if left.nonce != right.nonce {
+				differences.push(format!(
+					"{:?} nonce {} != {}",
+					address, left.nonce, right.nonce
+				));
+			}
+			if left.code != right.code {
+				differences.push(format!("{:?} code differs", address));
+			}
+			if left.storage != right.storage {
+				differences.push(format!(
+					"{:?} storage {:?} != {:?}",
+					address, left.storage, right.storage
+				));
+			}
+		}
+		differences
+	}
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonEnv {
+	current_coinbase: String,
+	current_gas_limit: String,
+	current_number: String,
+	current_timestamp: String,
+}
+
+#[derive(Deserialize)]
+struct JsonAccount {
+	balance: String,
+	code: String,
+	nonce: String,
+	storage: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTransaction {
+	data: Vec<String>,
+	gas_limit: Vec<String>,
+	gas_price: String,
+	nonce: String,
+	secret_key: String,
+	sender: Option<String>,
+	to: String,
+	value: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct JsonIndexes {
+	data: usize,
+	gas: usize,
+	value: usize,
+}
+
+#[derive(Deserialize)]
+struct JsonPost {
+	indexes: JsonIndexes,
+}
+
+#[derive(Deserialize)]
+struct JsonStateTest {
+	env: JsonEnv,
+	pre: BTreeMap<String, JsonAccount>,
+	transaction: JsonTransaction,
+	post: BTreeMap<String, Vec<JsonPost>>,
+}
+
+/// Cases of the state test file at `path` for the given fork, e.g. `Istanbul`.
+pub fn load_state_tests(path: &Path, fork: &str) -> Result<Vec<StateTestCase>, String> {
+	let json = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+	let tests: BTreeMap<String, JsonStateTest> =
+		serde_json::from_slice(&json).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+	let mut cases = Vec::new();
+	for (name, test) in tests {
+		let posts = match test.post.get(fork) {
+			Some(posts) => posts,
+			None => continue,
+		};
+		let env = StateTestEnv {
+			coinbase: parse_address(&test.env.current_coinbase)?,
+			gas_limit: parse_u256(&test.env.current_gas_limit)?,
+			number: parse_u256(&test.env.current_number)?,
+			timestamp: parse_u256(&test.env.current_timestamp)?,
+		};
+		let mut pre = BTreeMap::new();
+		for (address, account) in &test.pre {
+			let mut storage = BTreeMap::new();
+			for (key, value) in &account.storage {
+				let value = parse_word(value)?;
+				if !value.is_zero() {
+					storage.insert(parse_word(key)?, value);
+				}
+			}
+			pre.insert(
+				parse_address(address)?,
+				AccountState {
+					balance: parse_u256(&account.balance)?,
+					nonce: parse_u256(&account.nonce)?,
+					code: parse_bytes(&account.code)?,
+					storage,
+				},
+			);
+		}
+		let sender = match &test.transaction.sender {
+			Some(sender) => parse_address(sender)?,
+			None => secret_key_address(&test.transaction.secret_key)?,
+		};
+		let to = match test.transaction.to.as_str() {
+			"" => None,
+			to => Some(parse_address(to)?),
+		};
+
+		for post in posts {
+			let JsonIndexes { data, gas, value } = post.indexes;
+			let index = |values: &Vec<String>, index: usize| {
+				values
+					.get(index)
+					.cloned()
+					.ok_or_else(|| format!("{}: index {} out of bounds", name, index))
+			};
+			cases.push(StateTestCase {
+				name: format!("{} (data {}, gas {}, value {})", name, data, gas, value),
+				env: env.clone(),
+				pre: pre.clone(),
+				transaction: StateTestTransaction {
+					sender,
+					to,
+					nonce: parse_u256(&test.transaction.nonce)?,
+					gas_price: parse_u256(&test.transaction.gas_price)?,
+					gas_limit: parse_u256(&index(&test.transaction.gas_limit, gas)?)?,
+					value: parse_u256(&index(&test.transaction.value, value)?)?,
+					data: parse_bytes(&index(&test.transaction.data, data)?)?,
+				},
+			});
+		}
+	}
+	Ok(cases)
+}
+
+fn parse_u256(value: &str) -> Result<U256, String> {
+	let invalid = || format!("Invalid quantity {}", value);
+	match value.strip_prefix("0x") {
+		Some("") => Ok(U256::zero()),
+		Some(hex) => U256::from_str_radix(hex, 16).map_err(|_| invalid()),
+		None => U256::from_dec_str(value).map_err(|_| invalid()),
+	}
+}
+
+fn parse_word(value: &str) -> Result<H256, String> {
+	let mut word = H256::zero();
+	parse_u256(value)?.to_big_endian(word.as_bytes_mut());
+	Ok(word)
+}
+
+fn parse_bytes(value: &str) -> Result<Vec<u8>, String> {
+	hex::decode(value.trim_start_matches("0x"))
+		.map_err(|e| format!("Invalid bytes {}: {}", value, e))
+}
+
+fn parse_address(value: &str) -> Result<H160, String> {
+	let bytes = parse_bytes(value)?;
+	if bytes.len() != 20 {
+		return Err(format!("Invalid address {}", value));
+	}
+	Ok(H160::from_slice(&bytes))
+}
+
+fn secret_key_address(secret_key: &str) -> Result<H160, String> {
+	let secret_key = secp256k1::SecretKey::parse_slice(&parse_bytes(secret_key)?)
+		.map_err(|e| format!("Invalid secret key: {:?}", e))?;
+	let public_key = secp256k1::PublicKey::from_secret_key(&secret_key).serialize();
+	Ok(H160::from_slice(
+		&sp_io::hashing::keccak_256(&public_key[1..])[12..],
+	))
+}
+
+/// Runs the case through the EVM pallet.
+pub fn execute_pallet_evm(case: &StateTestCase) -> Result<Outcome, String> {
+	mock::Coinbase::set(case.env.coinbase);
+	mock::BlockGasLimit::set(case.env.gas_limit);
+
+	let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.map_err(|e| e.to_string())?
+		.into();
+	ext.execute_with(|| {
+		System::set_block_number(case.env.number.low_u64());
+		Timestamp::set_timestamp(case.env.timestamp.low_u64().saturating_mul(1000));
+
+		for (address, account) in &case.pre {
+			if account.balance > U256::from(u128::max_value()) {
+				return Err(format!("Balance of {:?} does not fit the runtime", address));
+			}
+			Balances::make_free_balance_be(address, account.balance.low_u128());
+			frame_system::Account::<Test>::mutate(address, |system_account| {
+				system_account.nonce = account.nonce.low_u64();
+			});
+			pallet_evm::Pallet::<Test>::create_account(*address, account.code.clone());
+			for (index, value) in &account.storage {
+				pallet_evm::AccountStorages::<Test>::insert(address, index, value);
+			}
+		}
+
+		let transaction = &case.transaction;
+		let config = <Test as pallet_evm::Config>::config();
+		let result = match transaction.to {
+			Some(to) => <Test as pallet_evm::Config>::Runner::call(
+				transaction.sender,
+				to,
+				transaction.data.clone(),
+				transaction.value,
+				transaction.gas_limit.low_u64(),
+				Some(transaction.gas_price),
+				Some(transaction.nonce),
+				config,
+			)
+			.map(|info| (info.exit_reason.is_succeed(), info.used_gas)),
+			None => <Test as pallet_evm::Config>::Runner::create(
+				transaction.sender,
+				transaction.data.clone(),
+				transaction.value,
+				transaction.gas_limit.low_u64(),
+				Some(transaction.gas_price),
+				Some(transaction.nonce),
+				config,
+			)
+			.map(|info| (info.exit_reason.is_succeed(), info.used_gas)),
+		};
+		let (status, used_gas) = match result {
+			Ok((true, used_gas)) => (Status::Succeeded, used_gas),
+			Ok((false, used_gas)) => (Status::Failed, used_gas),
+			Err(_) => (Status::Rejected, U256::zero()),
+		};
+
+		// The address mapping is the identity, all the accounts are enumerable.
+		let mut accounts = BTreeMap::new();
+		let addresses = frame_system::Account::<Test>::iter_keys()
+			.chain(pallet_evm::AccountCodes::<Test>::iter_keys())
+			.chain(pallet_evm::AccountStorages::<Test>::iter_keys().map(|(address, _)| address))
+			.collect::<std::collections::BTreeSet<_>>();
+		for address in addresses {
+			let basic = pallet_evm::Pallet::<Test>::account_basic(&address);
+			let account = AccountState {
+				balance: basic.balance,
+				nonce: basic.nonce,
+				code: pallet_evm::AccountCodes::<Test>::get(&address),
+				storage: pallet_evm::AccountStorages::<Test>::iter_prefix(&address)
+					.filter(|(_, value)| !value.is_zero())
+					.collect(),
+			};
+			if !account.is_empty() {
+				accounts.insert(address, account);
+			}
+		}
+
+		Ok(Outcome {
+			status,
+			used_gas,
+			accounts,
+		})
+	})
+}
+
+fn to_revm_address(address: H160) -> revm::H160 {
+	revm::H160::from_slice(address.as_bytes())
+}
+
+fn from_revm_address(address: revm::H160) -> H160 {
+	H160::from_slice(address.as_bytes())
+}
+
+fn to_revm_u256(value: U256) -> revm::U256 {
+	let mut bytes = [0u8; 32];
+	value.to_big_endian(&mut bytes);
+	revm::U256::from_big_endian(&bytes)
+}
+
+fn from_revm_u256(value: revm::U256) -> U256 {
+	let mut bytes = [0u8; 32];
+	value.to_big_endian(&mut bytes);
+	U256::from_big_endian(&bytes)
+}
+
+fn from_revm_word(value: revm::U256) -> H256 {
+	let mut word = H256::zero();
+	value.to_big_endian(word.as_bytes_mut());
+	word
+}
+
+/// Runs the case through revm, in the block context of the pallet.
+pub fn execute_revm(case: &StateTestCase) -> Outcome {
+	let mut db = revm::InMemoryDB::default();
+	for (address, account) in &case.pre {
+		db.insert_account_info(
+			to_revm_address(*address),
+			revm::AccountInfo::new(
+				to_revm_u256(account.balance),
+				account.nonce.low_u64(),
+				revm::Bytecode::new_raw(bytes::Bytes::from(account.code.clone())),
+			),
+		);
+		for (index, value) in &account.storage {
+			db.insert_account_storage(
+				to_revm_address(*address),
+				revm::U256::from_big_endian(index.as_bytes()),
+				revm::U256::from_big_endian(value.as_bytes()),
+			)
+			.expect("the account was just inserted; qed");
+		}
+	}
+
+	let mut evm = revm::new();
+	evm.database(db);
+	evm.env.cfg.spec_id = revm::SpecId::ISTANBUL;
+	evm.env.cfg.chain_id = revm::U256::zero();
+	evm.env.block.number = to_revm_u256(case.env.number);
+	evm.env.block.coinbase = to_revm_address(case.env.coinbase);
+	evm.env.block.timestamp = to_revm_u256(case.env.timestamp);
+	evm.env.block.difficulty = revm::U256::zero();
+	evm.env.block.gas_limit = to_revm_u256(case.env.gas_limit);
+
+	let transaction = &case.transaction;
+	evm.env.tx.caller = to_revm_address(transaction.sender);
+	evm.env.tx.transact_to = match transaction.to {
+		Some(to) => revm::TransactTo::Call(to_revm_address(to)),
+		None => revm::TransactTo::create(),
+	};
+	evm.env.tx.nonce = Some(transaction.nonce.low_u64());
+	evm.env.tx.gas_price = to_revm_u256(transaction.gas_price);
+	evm.env.tx.gas_limit = transaction.gas_limit.low_u64();
+	evm.env.tx.value = to_revm_u256(transaction.value);
+	evm.env.tx.data = bytes::Bytes::from(transaction.data.clone());
+
+	let (result, state) = evm.transact();
+	let status = match result.exit_reason {
+		revm::Return::Continue
+		| revm::Return::Stop
+		| revm::Return::Return
+		| revm::Return::SelfDestruct => Status::Succeeded,
+		_ if result.gas_used == 0 => Status::Rejected,
+		_ => Status::Failed,
+	};
+
+	let mut accounts = case.pre.clone();
+	for (address, account) in state {
+		let address = from_revm_address(address);
+		if account.is_destroyed {
+			accounts.remove(&address);
+			continue;
+		}
+		let entry = accounts.entry(address).or_default();
+		entry.balance = from_revm_u256(account.info.balance);
+		entry.nonce = U256::from(account.info.nonce);
+		if let Some(code) = &account.info.code {
+			entry.code = code.bytes()[..code.len()].to_vec();
+		}
+		for (index, slot) in account.storage {
+			let value = from_revm_word(slot.present_value);
+			if value.is_zero() {
+				entry.storage.remove(&from_revm_word(index));
+			} else {
+				entry.storage.insert(from_revm_word(index), value);
+			}
+		}
+	}
+	accounts.retain(|_, account| !account.is_empty());
+
+	Outcome {
+		status,
+		used_gas: U256::from(result.gas_used),
+		accounts,
+	}
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runs the `GeneralStateTests` of a checkout of https://github.com/ethereum/tests, given by
+//! `ETHEREUM_TESTS_PATH`, through the EVM pallet and revm. `STATE_TEST_FILTER` restricts the
+//! run to the files whose path contains it.
+
+use pallet_evm_differential_tests::{execute_pallet_evm, execute_revm, load_state_tests};
+use std::{env, fs, path::PathBuf};
+
+fn state_test_files(dir: PathBuf, files: &mut Vec<PathBuf>) {
+	let mut entries = fs::read_dir(&dir)
+		.unwrap_or_else(|e| panic!("{}: {}", dir.display(), e))
+		.map(|entry| entry.unwrap().path())
+		.collect::<Vec<_>>();
+	entries.sort();
+	for path in entries {
+		if path.is_dir() {
+			state_test_files(path, files);
+		} else if path
+			.extension()
+			.map_or(false, |extension| extension == "json")
+		{
+			files.push(path);
+		}
+	}
+}
+
+#[test]
+fn general_state_tests_match_revm() {
+	let tests_path = match env::var("ETHEREUM_TESTS_PATH") {
+		Ok(path) => PathBuf::from(path),
+		Err(_) => {
+			println!("ETHEREUM_TESTS_PATH is not set, skipping the differential tests");
+			return;
+		}
+	};
+	let filter = env::var("STATE_TEST_FILTER").unwrap_or_default();
+
+	let mut files = Vec::new();
+	state_test_files(tests_path.join("GeneralStateTests"), &mut files);
+
+	let (mut cases, mut divergences) = (0, Vec::new());
+	for file in files
+		.iter()
+		.filter(|file| file.to_string_lossy().contains(&filter))
+	{
+		for case in load_state_tests(file, "Istanbul").unwrap() {
+			cases += 1;
+			let pallet = match execute_pallet_evm(&case) {
+				Ok(outcome) => outcome,
+				Err(e) => {
+					println!("skipped {}: {}", case.name, e);
+					continue;
+				}
+			};
+			let differences = pallet.differences(&execute_revm(&case), case.env.coinbase);
+			if !differences.is_empty() {
+				divergences.push(format!("{}: {}", case.name, differences.join(", ")));
+			}
+		}
+	}
+
+	for divergence in &divergences {
+		println!("{}", divergence);
+	}
+	assert!(
+		divergences.is_empty(),
+		"{} of {} cases diverge from revm",
+		divergences.len(),
+		cases
+	);
+}
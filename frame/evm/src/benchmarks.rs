@@ -18,8 +18,9 @@
 #![cfg(feature = "runtime-benchmarks")]
 
 //! Benchmarking
-use crate::{runner::Runner, Config, FeeCalculator, Module};
+use crate::{runner::Runner, Config, FeeCalculator, LinearCostPrecompile, Module};
 use frame_benchmarking::{account, benchmarks};
+use pallet_evm_precompile_sha3fips::Sha3FIPS256;
 use rlp::RlpStream;
 use sha3::{Digest, Keccak256};
 use sp_core::{H160, U256};
@@ -115,6 +116,16 @@ benchmarks! {
 		);
 		assert_eq!(call_runner_results.is_ok(), true, "call() failed");
 	}
+
+	// Example of the benchmark of a linear cost precompile for `BenchmarkedPrecompile`: the
+	// input grows by `w` words, the intercept of the regression is the `base` weight and its
+	// slope the `per_word` weight.
+	sha3_fips_256 {
+		let w in 0..1024;
+		let input = vec![0xffu8; w as usize * 32];
+	}: {
+		Sha3FIPS256::execute(&input, 0).expect("any input is hashed");
+	}
 }
 
 #[cfg(test)]
@@ -137,4 +148,11 @@ mod tests {
 			assert_ok!(test_benchmark_runner_execute::<Test>());
 		});
 	}
+
+	#[test]
+	fn test_sha3_fips_256() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_sha3_fips_256::<Test>());
+		});
+	}
 }
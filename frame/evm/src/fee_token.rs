@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Payment of the EVM fees in an asset other than the native currency, e.g. a pallet-assets
+//! token, at a rate provided by an oracle.
+//!
+//! [`EVMFeeTokenAdapter`] charges the senders, or the transactions to the contracts, selected
+//! by a [`FeeTokenOracle`] in their fee token, and the other transactions in the native currency
+//! as [`EVMCurrencyAdapter`] does. The fee token is transferred to a collector account.
+
+use crate::{
+	AddressMapping, Config, EVMCurrencyAdapter, Error, NegativeImbalanceOf, OnChargeEVMTransaction,
+};
+use frame_support::traits::{
+	tokens::{fungibles, WithdrawConsequence},
+	Currency, Get,
+};
+use sp_core::{H160, U256};
+use sp_runtime::{
+	traits::{Saturating, UniqueSaturatedFrom},
+	FixedPointNumber, FixedU128,
+};
+use sp_std::marker::PhantomData;
+
+/// Selects the transactions paying their fees in a fee token.
+pub trait FeeTokenOracle<AssetId> {
+	/// Fee token of the transactions of `who` to `target`, None for the contract creations,
+	/// with its rate: the units of the token paying one unit of the native currency. None for
+	/// the transactions paying in the native currency.
+	fn fee_token(who: &H160, target: Option<H160>) -> Option<(AssetId, FixedU128)>;
+}
+
+impl<AssetId> FeeTokenOracle<AssetId> for () {
+	fn fee_token(_who: &H160, _target: Option<H160>) -> Option<(AssetId, FixedU128)> {
+		None
+	}
+}
+
+/// Fees secured by [`EVMFeeTokenAdapter`] before the execution.
+pub enum FeeTokenLiquidity<N, AssetId, Balance> {
+	/// Fees withdrawn from the native balance.
+	Native(N),
+	/// Fees transferred in `asset` to the collector, at `rate`.
+	Token {
+		asset: AssetId,
+		rate: FixedU128,
+		paid: Balance,
+	},
+}
+
+impl<N: Default, AssetId, Balance> Default for FeeTokenLiquidity<N, AssetId, Balance> {
+	fn default() -> Self {
+		Self::Native(N::default())
+	}
+}
+
+/// Charges the transactions selected by `O` in their fee token of `F`, transferred to
+/// `Collector`, and the other transactions in `C` through `EVMCurrencyAdapter<C, OU>`.
+pub struct EVMFeeTokenAdapter<C, OU, F, O, Collector>(PhantomData<(C, OU, F, O, Collector)>);
+
+/// Amount of fee token paying `fee` at `rate`. Fees beyond `u128` saturate, so that they cannot
+/// be paid.
+fn token_amount<Balance: UniqueSaturatedFrom<u128>>(fee: U256, rate: FixedU128) -> Balance {
+	let fee = if fee > U256::from(u128::max_value()) {
+		u128::max_value()
+	} else {
+		fee.low_u128()
+	};
+	Balance::unique_saturated_from(rate.saturating_mul_int(fee))
+}
+
+impl<T, I, C, OU, F, O, Collector> OnChargeEVMTransaction<T, I>
+	for EVMFeeTokenAdapter<C, OU, F, O, Collector>
+where
+	T: Config<I>,
+	I: 'static,
+	EVMCurrencyAdapter<C, OU>:
+		OnChargeEVMTransaction<T, I, LiquidityInfo = Option<NegativeImbalanceOf<C, T>>>,
+	C: Currency<<T as frame_system::Config>::AccountId>,
+	F: fungibles::Transfer<<T as frame_system::Config>::AccountId>,
+	O: FeeTokenOracle<F::AssetId>,
+	Collector: Get<<T as frame_system::Config>::AccountId>,
+{
+	type LiquidityInfo =
+		FeeTokenLiquidity<Option<NegativeImbalanceOf<C, T>>, F::AssetId, F::Balance>;
+
+	fn withdraw_fee(who: &H160, fee: U256) -> Result<Self::LiquidityInfo, Error<T, I>> {
+		Self::withdraw_fee_for(who, None, fee)
+	}
+
+	fn ensure_fee_payable(
+		who: &H160,
+		target: Option<H160>,
+		fee: U256,
+	) -> Result<U256, Error<T, I>> {
+		match O::fee_token(who, target) {
+			Some((asset, rate)) => {
+				let account_id = T::AddressMapping::into_account_id(*who);
+				match F::can_withdraw(asset, &account_id, token_amount(fee, rate)) {
+					WithdrawConsequence::Success => Ok(U256::zero()),
+					_ => Err(Error::<T, I>::BalanceLow),
+				}
+			}
+			None => Ok(fee),
+		}
+	}
+
+	fn withdraw_fee_for(
+		who: &H160,
+		target: Option<H160>,
+		fee: U256,
+	) -> Result<Self::LiquidityInfo, Error<T, I>> {
+		match O::fee_token(who, target) {
+			Some((asset, rate)) => {
+				let account_id = T::AddressMapping::into_account_id(*who);
+				let paid = F::transfer(
+					asset,
+					&account_id,
+					&Collector::get(),
+					token_amount(fee, rate),
+					false,
+				)
+				.map_err(|_| Error::<T, I>::BalanceLow)?;
+				Ok(FeeTokenLiquidity::Token { asset, rate, paid })
+			}
+			None => {
+				<EVMCurrencyAdapter<C, OU> as OnChargeEVMTransaction<T, I>>::withdraw_fee(who, fee)
+					.map(FeeTokenLiquidity::Native)
+			}
+		}
+	}
+
+	fn correct_and_deposit_fee(
+		who: &H160,
+		corrected_fee: U256,
+		already_withdrawn: Self::LiquidityInfo,
+	) {
+		match already_withdrawn {
+			FeeTokenLiquidity::Native(paid) => {
+				<EVMCurrencyAdapter<C, OU> as OnChargeEVMTransaction<T, I>>::correct_and_deposit_fee(
+					who,
+					corrected_fee,
+					paid,
+				)
+			}
+			FeeTokenLiquidity::Token { asset, rate, paid } => {
+				let account_id = T::AddressMapping::into_account_id(*who);
+				let refund = paid.saturating_sub(token_amount(corrected_fee, rate));
+				// As with the native currency, nothing is refunded if the transfer fails.
+				let _ = F::transfer(asset, &Collector::get(), &account_id, refund, false);
+			}
+		}
+	}
+}
@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Programmatic construction of the EVM genesis accounts, for instance to pre-fund the dev
+//! accounts of anvil and hardhat so that local testing works with their defaults.
+//!
+//! Contracts can be deployed from their init code: the constructor is executed when the genesis
+//! is built, so that their storage does not have to be computed by hand.
+
+use crate::{GenesisAccount, GenesisConfig};
+use evm::{
+	backend::{Apply, MemoryAccount, MemoryBackend, MemoryVicinity},
+	executor::{MemoryStackState, StackExecutor, StackSubstateMetadata},
+	CreateScheme,
+};
+use sp_core::{H160, H256, U256};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Mnemonic of the dev accounts of anvil and hardhat.
+pub const DEV_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+/// Addresses derived from [`DEV_MNEMONIC`] along `m/44'/60'/0'/0/{index}`.
+pub const DEV_ACCOUNTS: [&str; 10] = [
+	"f39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+	"70997970C51812dc3A010C7d01b50e0d17dc79C8",
+	"3C44CdDdB6a900fa2b585dd299e03d12FA4293BC",
+	"90F79bf6EB2c4f870365E785982E1f101E93b906",
+	"15d34AAf54267DB7D7c367839AAf71A00a2C6A65",
+	"9965507D1a55bcC2695C58ba16FB37d819B0A4dc",
+	"976EA74026E726554dB657fA54763abd0C3a0aa9",
+	"14dC79964da2C08b23698B3D3cc7Ca32193d9955",
+	"23618e81E3f5cdF7f54C3d65f7FBc0aBf5B21E8F",
+	"a0Ee7A142d267C1f36714E4a8F75612F20a79720",
+];
+
+/// Address of the deterministic deployment proxy, deploying the contracts it is called with by
+/// `CREATE2` so that they get the same address on every chain.
+pub const DETERMINISTIC_DEPLOYMENT_PROXY: &str = "4e59b44847b379578588920cA78FbF26c0B4956C";
+
+/// Runtime code of the [`DETERMINISTIC_DEPLOYMENT_PROXY`].
+pub const DETERMINISTIC_DEPLOYMENT_PROXY_CODE: &str = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3";
+
+/// Address of Multicall3 on the chains it is deployed on.
+pub const MULTICALL3: &str = "cA11bde05977b3631167028862bE2a173976CA11";
+
+/// Keccak256 hash of the runtime code of the mainnet deployment of [`MULTICALL3`].
+pub const MULTICALL3_CODE_HASH: &str =
+	"d5c15df687b16f2ff992fc8d767b4216323184a2bbc6ee2f9c398c318e770891";
+
+/// Checks that `code` is the runtime code of the mainnet deployment of Multicall3.
+pub fn check_multicall3_code(code: &[u8]) -> Result<(), String> {
+	let expected = H256::from_str(MULTICALL3_CODE_HASH).expect("internal H256 is valid; qed");
+	let hash = H256::from(sp_io::hashing::keccak_256(code));
+	if hash != expected {
+		return Err(format!(
+			"Multicall3 code hash {:?} differs from the mainnet deployment {:?}",
+			hash, expected
+		));
+	}
+	Ok(())
+}
+
+/// Balance anvil and hardhat give their dev accounts, 10000 ether.
+pub fn default_dev_balance() -> U256 {
+	U256::from(10_000u64) * U256::exp10(18)
+}
+
+/// Builder of the EVM genesis accounts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GenesisBuilder {
+	accounts: BTreeMap<H160, GenesisAccount>,
+	chain_id: u64,
+}
+
+impl GenesisBuilder {
+	/// Builder without accounts.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Builder starting from the accounts of `config`.
+	pub fn from_config(config: GenesisConfig) -> Self {
+		Self {
+			accounts: config.accounts,
+			chain_id: 0,
+		}
+	}
+
+	/// Chain id the constructors of the deployments read.
+	pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+		self.chain_id = chain_id;
+		self
+	}
+
+	/// Funds the first `count` of the [`DEV_ACCOUNTS`], at most all ten, with `balance`.
+	pub fn with_dev_accounts(self, count: usize, balance: U256) -> Self {
+		DEV_ACCOUNTS
+			.iter()
+			.take(count)
+			.fold(self, |builder, address| {
+				let address = H160::from_str(address).expect("internal H160 is valid; qed");
+				builder.with_balance(address, balance)
+			})
+	}
+
+	/// Sets the balance of `address`, keeping its code and storage.
+	pub fn with_balance(mut self, address: H160, balance: U256) -> Self {
+		self.account(address).balance = balance;
+		self
+	}
+
+	/// Deploys the runtime `code` at `address` with the given storage, keeping its balance.
+	pub fn with_contract(
+		mut self,
+		address: H160,
+		code: Vec<u8>,
+		storage: BTreeMap<H256, H256>,
+	) -> Self {
+		let account = self.account(address);
+		account.code = code;
+		account.storage = storage;
+		self
+	}
+
+	/// Installs the deterministic deployment proxy at its canonical address.
+	pub fn with_deterministic_deployment_proxy(self) -> Self {
+		let address =
+			H160::from_str(DETERMINISTIC_DEPLOYMENT_PROXY).expect("internal H160 is valid; qed");
+		let code = hex::decode(DETERMINISTIC_DEPLOYMENT_PROXY_CODE)
+			.expect("internal code is valid hex; qed");
+		self.with_contract(address, code, BTreeMap::new())
+	}
+
+	/// Installs the runtime `code` of Multicall3, which holds no storage, at its canonical
+	/// address. Fails unless it is the code of the mainnet deployment, see
+	/// [`check_multicall3_code`].
+	pub fn with_multicall3(self, code: Vec<u8>) -> Result<Self, String> {
+		check_multicall3_code(&code)?;
+		let address = H160::from_str(MULTICALL3).expect("internal H160 is valid; qed");
+		Ok(self.with_contract(address, code, BTreeMap::new()))
+	}
+
+	/// Deploys the contract created by `init_code` at `address`. The constructor is executed with
+	/// `config` against the accounts built so far, and the runtime code and storage it leaves are
+	/// placed at `address`, keeping its balance.
+	///
+	/// The constructor runs as a creation of the zero address, which is the `address(this)` it
+	/// sees, and its effects on other accounts are dropped.
+	pub fn with_deployment(
+		self,
+		address: H160,
+		init_code: Vec<u8>,
+		config: &evm::Config,
+	) -> Result<Self, String> {
+		let vicinity = MemoryVicinity {
+			gas_price: U256::zero(),
+			origin: H160::zero(),
+			chain_id: U256::from(self.chain_id),
+			block_hashes: Vec::new(),
+			block_number: U256::zero(),
+			block_coinbase: H160::zero(),
+			block_timestamp: U256::zero(),
+			block_difficulty: U256::zero(),
+			block_gas_limit: U256::from(u64::max_value()),
+		};
+		let state = self
+			.accounts
+			.iter()
+			.map(|(address, account)| {
+				let account = MemoryAccount {
+					nonce: account.nonce,
+					balance: account.balance,
+					storage: account.storage.clone(),
+					code: account.code.clone(),
+				};
+				(*address, account)
+			})
+			.collect();
+		let backend = MemoryBackend::new(&vicinity, state);
+		let metadata = StackSubstateMetadata::new(u64::max_value(), config);
+		let mut executor = StackExecutor::new(MemoryStackState::new(metadata, &backend), config);
+
+		let created = executor.create_address(CreateScheme::Legacy {
+			caller: H160::zero(),
+		});
+		let reason =
+			executor.transact_create(H160::zero(), U256::zero(), init_code, u64::max_value());
+		if !reason.is_succeed() {
+			return Err(format!("Deployment at {:?} failed: {:?}", address, reason));
+		}
+
+		let (applies, _) = executor.into_state().deconstruct();
+		let (code, storage) = applies
+			.into_iter()
+			.find_map(|apply| match apply {
+				Apply::Modify {
+					address,
+					code,
+					storage,
+					..
+				} if address == created => Some((
+					code.unwrap_or_default(),
+					storage
+						.into_iter()
+						.filter(|(_, value)| !value.is_zero())
+						.collect(),
+				)),
+				_ => None,
+			})
+			.unwrap_or_default();
+
+		Ok(self.with_contract(address, code, storage))
+	}
+
+	/// Accounts built so far.
+	pub fn accounts(&self) -> &BTreeMap<H160, GenesisAccount> {
+		&self.accounts
+	}
+
+	/// The genesis config of the accounts.
+	pub fn build(self) -> GenesisConfig {
+		GenesisConfig {
+			accounts: self.accounts,
+		}
+	}
+
+	fn account(&mut self, address: H160) -> &mut GenesisAccount {
+		self.accounts
+			.entry(address)
+			.or_insert_with(|| GenesisAccount {
+				nonce: U256::zero(),
+				balance: U256::zero(),
+				storage: BTreeMap::new(),
+				code: Vec::new(),
+			})
+	}
+}
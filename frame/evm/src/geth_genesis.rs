@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Import of the genesis files of geth (`alloc`) and OpenEthereum (`accounts`), so that an
+//! existing EVM chain can start on Frontier with the same genesis state.
+//!
+//! A [`GethGenesis`] can also be written back as a geth genesis file, or SCALE encoded as a
+//! binary snapshot, to carry the EVM state of a running chain over to a new one.
+
+use crate::{GenesisAccount, GenesisConfig};
+use codec::{Decode, Encode};
+use serde_json::{json, Map, Value};
+use sp_core::{H160, H256, U256};
+use std::collections::BTreeMap;
+
+/// EVM state and chain parameters of a genesis file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct GethGenesis {
+	/// Chain id, which must match the `ChainId` of the runtime.
+	pub chain_id: Option<u64>,
+	/// Block gas limit of the genesis block.
+	pub gas_limit: Option<U256>,
+	/// Accounts allocated at genesis.
+	pub accounts: BTreeMap<H160, GenesisAccount>,
+}
+
+impl GethGenesis {
+	/// Parses a geth or OpenEthereum genesis file.
+	pub fn from_json(json: &[u8]) -> Result<Self, String> {
+		let genesis: Value =
+			serde_json::from_slice(json).map_err(|e| format!("Invalid genesis JSON: {}", e))?;
+
+		let chain_id = match (
+			genesis.pointer("/config/chainId"),
+			genesis.pointer("/params/chainID"),
+			genesis.pointer("/params/networkID"),
+		) {
+			(Some(chain_id), _, _) | (None, Some(chain_id), _) | (None, None, Some(chain_id)) => {
+				Some(parse_u256(chain_id, "chain id")?.low_u64())
+			}
+			(None, None, None) => None,
+		};
+		let gas_limit = genesis
+			.get("gasLimit")
+			.or_else(|| genesis.pointer("/genesis/gasLimit"))
+			.map(|gas_limit| parse_u256(gas_limit, "gas limit"))
+			.transpose()?;
+
+		let alloc = genesis
+			.get("alloc")
+			.or_else(|| genesis.get("accounts"))
+			.and_then(Value::as_object)
+			.ok_or("Genesis has neither `alloc` nor `accounts`")?;
+
+		let mut accounts = BTreeMap::new();
+		for (address, account) in alloc {
+			let address = parse_address(address)?;
+			let account = account
+				.as_object()
+				.ok_or_else(|| format!("Invalid account {:?}", address))?;
+			// OpenEthereum builtin only entries are precompiles, provided by the runtime.
+			if account.contains_key("builtin") && !account.contains_key("balance") {
+				continue;
+			}
+			accounts.insert(address, parse_account(account)?);
+		}
+
+		Ok(Self {
+			chain_id,
+			gas_limit,
+			accounts,
+		})
+	}
+
+	/// Writes a geth genesis file, which `from_json` reads back.
+	pub fn to_json(&self) -> Vec<u8> {
+		let alloc: Map<String, Value> = self
+			.accounts
+			.iter()
+			.map(|(address, account)| (format!("{:?}", address), account_json(account)))
+			.collect();
+
+		let mut genesis = Map::new();
+		if let Some(chain_id) = self.chain_id {
+			genesis.insert("config".into(), json!({ "chainId": chain_id }));
+		}
+		if let Some(gas_limit) = self.gas_limit {
+			genesis.insert("gasLimit".into(), json!(format!("{:#x}", gas_limit)));
+		}
+		genesis.insert("alloc".into(), Value::Object(alloc));
+
+		serde_json::to_vec_pretty(&genesis).expect("genesis is valid JSON; qed")
+	}
+
+	/// EVM pallet genesis config allocating the accounts.
+	pub fn evm_config(&self) -> GenesisConfig {
+		GenesisConfig {
+			accounts: self.accounts.clone(),
+		}
+	}
+}
+
+fn parse_account(account: &Map<String, Value>) -> Result<GenesisAccount, String> {
+	let field = |name: &str| account.get(name).filter(|value| !value.is_null());
+
+	let mut storage = BTreeMap::new();
+	if let Some(entries) = field("storage") {
+		let entries = entries.as_object().ok_or("Invalid account storage")?;
+		for (key, value) in entries {
+			storage.insert(
+				word(parse_u256(&Value::String(key.clone()), "storage key")?),
+				word(parse_u256(value, "storage value")?),
+			);
+		}
+	}
+
+	Ok(GenesisAccount {
+		nonce: field("nonce")
+			.map(|nonce| parse_u256(nonce, "nonce"))
+			.transpose()?
+			.unwrap_or_default(),
+		balance: field("balance")
+			.map(|balance| parse_u256(balance, "balance"))
+			.transpose()?
+			.unwrap_or_default(),
+		storage,
+		code: field("code")
+			.map(|code| parse_bytes(code, "code"))
+			.transpose()?
+			.unwrap_or_default(),
+	})
+}
+
+fn account_json(account: &GenesisAccount) -> Value {
+	let mut json = Map::new();
+	json.insert("balance".into(), json!(format!("{:#x}", account.balance)));
+	if !account.nonce.is_zero() {
+		json.insert("nonce".into(), json!(format!("{:#x}", account.nonce)));
+	}
+	if !account.code.is_empty() {
+		json.insert(
+			"code".into(),
+			json!(format!("0x{}", hex::encode(&account.code))),
+		);
+	}
+	if !account.storage.is_empty() {
+		let storage: Map<String, Value> = account
+			.storage
+			.iter()
+			.map(|(key, value)| (format!("{:?}", key), json!(format!("{:?}", value))))
+			.collect();
+		json.insert("storage".into(), Value::Object(storage));
+	}
+	Value::Object(json)
+}
+
+fn word(value: U256) -> H256 {
+	let mut word = H256::default();
+	value.to_big_endian(word.as_bytes_mut());
+	word
+}
+
+fn parse_address(address: &str) -> Result<H160, String> {
+	let bytes = hex::decode(address.trim_start_matches("0x"))
+		.map_err(|e| format!("Invalid address {}: {}", address, e))?;
+	if bytes.len() != 20 {
+		return Err(format!("Invalid address {}: expected 20 bytes", address));
+	}
+	Ok(H160::from_slice(&bytes))
+}
+
+/// Parses a quantity given as a number, a decimal string or a `0x` prefixed hex string.
+fn parse_u256(value: &Value, name: &str) -> Result<U256, String> {
+	let invalid = || format!("Invalid {}: {}", name, value);
+	match value {
+		Value::Number(number) => number.as_u64().map(U256::from).ok_or_else(invalid),
+		Value::String(string) => match string.strip_prefix("0x") {
+			Some("") => Ok(U256::zero()),
+			Some(hex) => U256::from_str_radix(hex, 16).map_err(|_| invalid()),
+			None => U256::from_dec_str(string).map_err(|_| invalid()),
+		},
+		_ => Err(invalid()),
+	}
+}
+
+fn parse_bytes(value: &Value, name: &str) -> Result<Vec<u8>, String> {
+	let string = value
+		.as_str()
+		.ok_or_else(|| format!("Invalid {}: {}", name, value))?;
+	hex::decode(string.trim_start_matches("0x")).map_err(|e| format!("Invalid {}: {}", name, e))
+}
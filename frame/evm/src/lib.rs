@@ -49,15 +49,33 @@
 //! private key, one should be able to convert any Ethereum transaction into a transaction compatible with this pallet.
 //!
 //! The gas configurations are configurable. Right now, a pre-defined Istanbul hard fork configuration option is provided.
+//!
+//! ## Instances
+//!
+//! The pallet is instantiable: each instance has its own code and storage, chain id, precompile set and runner.
+//! The instances share the Substrate accounts, so an address has the same nonce and balance in all of them
+//! unless they map the addresses to distinct accounts, for example with a [`PrefixedHashedAddressMapping`]
+//! of their own prefix, or use distinct currencies.
+//!
+//! Only the default instance is served over the Ethereum RPC: `pallet-ethereum`, the runtime APIs and the
+//! `eth_` methods are bound to it. A second instance is reached through the `call` and `create` extrinsics.
 
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod fee_token;
+#[cfg(feature = "std")]
+pub mod genesis_builder;
+#[cfg(feature = "std")]
+pub mod geth_genesis;
 #[cfg(test)]
 mod mock;
 pub mod runner;
+pub mod sponsor;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "tracing")]
+pub mod tracer;
 
 #[cfg(any(test, feature = "runtime-benchmarks"))]
 pub mod benchmarks;
@@ -65,8 +83,8 @@ pub mod benchmarks;
 pub use crate::runner::Runner;
 pub use evm::{ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed};
 pub use fp_evm::{
-	Account, CallInfo, CreateInfo, ExecutionInfo, LinearCostPrecompile, Log, Precompile,
-	PrecompileSet, Vicinity,
+	Account, BundlerTrace, CallInfo, CallTrace, Context, CreateInfo, ExecutionInfo,
+	LinearCostPrecompile, Log, Precompile, PrecompileOutput, PrecompileSet, Vicinity,
 };
 
 #[cfg(feature = "std")]
@@ -84,14 +102,25 @@ use frame_system::RawOrigin;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use sp_core::{Hasher, H160, H256, U256};
+use sp_io::KillStorageResult;
 use sp_runtime::{
 	traits::{BadOrigin, Saturating, UniqueSaturatedInto},
-	AccountId32,
+	AccountId32, DispatchError,
 };
 use sp_std::vec::Vec;
 
 pub use pallet::*;
 
+// Precompile calls of the transaction being executed, counted in memory by the runner.
+environmental::environmental!(precompile_call_counts: Vec<(H160, u32)>);
+
+fn add_precompile_calls(calls: &mut Vec<(H160, u32)>, address: H160, count: u32) {
+	match calls.iter_mut().find(|(called, _)| *called == address) {
+		Some((_, total)) => *total = total.saturating_add(count),
+		None => calls.push((address, count)),
+	}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -100,10 +129,10 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
-	pub struct Pallet<T>(_);
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config + pallet_timestamp::Config {
+	pub trait Config<I: 'static = ()>: frame_system::Config + pallet_timestamp::Config {
 		/// Calculator for current gas price.
 		type FeeCalculator: FeeCalculator;
 
@@ -124,7 +153,7 @@ pub mod pallet {
 		type Currency: Currency<Self::AccountId> + Inspect<Self::AccountId>;
 
 		/// The overarching event type.
-		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Event: From<Event<Self, I>> + IsType<<Self as frame_system::Config>::Event>;
 		/// Precompiles associated with this EVM engine.
 		type Precompiles: PrecompileSet;
 		/// Chain ID of EVM.
@@ -132,30 +161,61 @@ pub mod pallet {
 		/// The block gas limit. Can be a simple constant, or an adjustment algorithm in another pallet.
 		type BlockGasLimit: Get<U256>;
 		/// EVM execution runner.
-		type Runner: Runner<Self>;
+		type Runner: Runner<Self, I>;
 
 		/// To handle fee deduction for EVM transactions. An example is this pallet being used by `pallet_ethereum`
 		/// where the chain implementing `pallet_ethereum` should be able to configure what happens to the fees
 		/// Similar to `OnChargeTransaction` of `pallet_transaction_payment`
-		type OnChargeTransaction: OnChargeEVMTransaction<Self>;
+		type OnChargeTransaction: OnChargeEVMTransaction<Self, I>;
 
 		/// Find author for the current block.
 		type FindAuthor: FindAuthor<H160>;
 
+		/// Whether the precompile calls of each block are counted in `PrecompileCalls` and
+		/// reported in a `PrecompileCalls` event when the block is finalized.
+		type CountPrecompileCalls: Get<bool>;
+
 		/// EVM config used in the module.
 		fn config() -> &'static EvmConfig {
 			&ISTANBUL_CONFIG
 		}
 	}
 
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_initialize(_: BlockNumberFor<T>) -> Weight {
+			FeeSponsors::<T, I>::kill();
+			if T::CountPrecompileCalls::get() {
+				PrecompileCalls::<T, I>::kill();
+				// The calls are read again in `on_finalize`.
+				T::DbWeight::get().reads_writes(1, 2)
+			} else {
+				T::DbWeight::get().writes(1)
+			}
+		}
+
+		fn on_finalize(_: BlockNumberFor<T>) {
+			if T::CountPrecompileCalls::get() {
+				let calls = PrecompileCalls::<T, I>::get();
+				if !calls.is_empty() {
+					Self::deposit_event(Event::PrecompileCalls(calls));
+				}
+			}
+		}
+
+		fn on_idle(_: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::remove_suicided_storage(remaining_weight)
+		}
+	}
+
 	#[pallet::call]
-	impl<T: Config> Pallet<T> {
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		/// Withdraw balance from EVM into currency/balances pallet.
 		#[pallet::weight(0)]
 		pub fn withdraw(
 			origin: OriginFor<T>,
 			address: H160,
-			value: BalanceOf<T>,
+			value: BalanceOf<T, I>,
 		) -> DispatchResult {
 			let destination = T::WithdrawOrigin::ensure_address_origin(&address, origin)?;
 			let address_account_id = T::AddressMapping::into_account_id(address);
@@ -167,6 +227,11 @@ pub mod pallet {
 				ExistenceRequirement::AllowDeath,
 			)?;
 
+			Pallet::<T, I>::deposit_event(Event::<T, I>::BalanceWithdraw(
+				destination,
+				address,
+				U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value)),
+			));
 			Ok(())
 		}
 
@@ -197,10 +262,10 @@ pub mod pallet {
 
 			match info.exit_reason {
 				ExitReason::Succeed(_) => {
-					Pallet::<T>::deposit_event(Event::<T>::Executed(target));
+					Pallet::<T, I>::deposit_event(Event::<T, I>::Executed(target));
 				}
 				_ => {
-					Pallet::<T>::deposit_event(Event::<T>::ExecutedFailed(target));
+					Pallet::<T, I>::deposit_event(Event::<T, I>::ExecutedFailed(target));
 				}
 			};
 
@@ -242,14 +307,14 @@ pub mod pallet {
 					value: create_address,
 					..
 				} => {
-					Pallet::<T>::deposit_event(Event::<T>::Created(create_address));
+					Pallet::<T, I>::deposit_event(Event::<T, I>::Created(create_address));
 				}
 				CreateInfo {
 					exit_reason: _,
 					value: create_address,
 					..
 				} => {
-					Pallet::<T>::deposit_event(Event::<T>::CreatedFailed(create_address));
+					Pallet::<T, I>::deposit_event(Event::<T, I>::CreatedFailed(create_address));
 				}
 			}
 
@@ -292,14 +357,14 @@ pub mod pallet {
 					value: create_address,
 					..
 				} => {
-					Pallet::<T>::deposit_event(Event::<T>::Created(create_address));
+					Pallet::<T, I>::deposit_event(Event::<T, I>::Created(create_address));
 				}
 				CreateInfo {
 					exit_reason: _,
 					value: create_address,
 					..
 				} => {
-					Pallet::<T>::deposit_event(Event::<T>::CreatedFailed(create_address));
+					Pallet::<T, I>::deposit_event(Event::<T, I>::CreatedFailed(create_address));
 				}
 			}
 
@@ -310,12 +375,126 @@ pub mod pallet {
 				pays_fee: Pays::No,
 			})
 		}
+
+		/// Deposit balance from currency/balances pallet into EVM. On chains with a hashed
+		/// address mapping, the `address` of a Substrate account is usually its truncated
+		/// account id, which it can withdraw from with `EnsureAddressTruncated`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 2).saturating_add(DEPOSIT_WEIGHT))]
+		pub fn deposit(
+			origin: OriginFor<T>,
+			address: H160,
+			value: BalanceOf<T, I>,
+		) -> DispatchResult {
+			let source = ensure_signed(origin)?;
+			let address_account_id = T::AddressMapping::into_account_id(address);
+
+			T::Currency::transfer(
+				&source,
+				&address_account_id,
+				value,
+				ExistenceRequirement::AllowDeath,
+			)?;
+
+			Pallet::<T, I>::deposit_event(Event::<T, I>::BalanceDeposit(
+				source,
+				address,
+				U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value)),
+			));
+			Ok(())
+		}
+
+		/// Offers `beneficiary` a sponsorship by `sponsor`, paying up to `allowance` of fees, and
+		/// up to `max_fee` per transaction, for the transactions to `beneficiary`, when it is a
+		/// contract, and of `beneficiary`, with `sponsor::EVMSponsoredAdapter`. The sponsorship
+		/// starts when the beneficiary accepts it. Updates it when `sponsor` already sponsors
+		/// `beneficiary`. A zero allowance withdraws the offer, or ends the sponsorship.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_sponsorship(
+			origin: OriginFor<T>,
+			sponsor: H160,
+			beneficiary: H160,
+			allowance: U256,
+			max_fee: U256,
+		) -> DispatchResult {
+			T::CallOrigin::ensure_address_origin(&sponsor, origin)?;
+
+			match Sponsorships::<T, I>::get(beneficiary) {
+				Some((current, _, _)) if current == sponsor => {
+					if allowance.is_zero() {
+						Sponsorships::<T, I>::remove(beneficiary);
+						Pallet::<T, I>::deposit_event(Event::<T, I>::SponsorshipRemoved(
+							sponsor,
+							beneficiary,
+						));
+					} else {
+						Sponsorships::<T, I>::insert(beneficiary, (sponsor, allowance, max_fee));
+						Pallet::<T, I>::deposit_event(Event::<T, I>::SponsorshipSet(
+							sponsor,
+							beneficiary,
+							allowance,
+							max_fee,
+						));
+					}
+				}
+				_ => {
+					if allowance.is_zero() {
+						SponsorshipOffers::<T, I>::remove(beneficiary, sponsor);
+					} else {
+						SponsorshipOffers::<T, I>::insert(
+							beneficiary,
+							sponsor,
+							(allowance, max_fee),
+						);
+					}
+					Pallet::<T, I>::deposit_event(Event::<T, I>::SponsorshipOffered(
+						sponsor,
+						beneficiary,
+						allowance,
+						max_fee,
+					));
+				}
+			}
+			Ok(())
+		}
+
+		/// Accepts the sponsorship `sponsor` offers to `beneficiary`, replacing its current
+		/// sponsorship. Root accepts for the contracts, whose addresses have no origin.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 2))]
+		pub fn accept_sponsorship(
+			origin: OriginFor<T>,
+			beneficiary: H160,
+			sponsor: H160,
+		) -> DispatchResult {
+			Self::ensure_beneficiary_origin(&beneficiary, origin)?;
+			let (allowance, max_fee) = SponsorshipOffers::<T, I>::take(beneficiary, sponsor)
+				.ok_or(Error::<T, I>::NoSponsorshipOffer)?;
+
+			Sponsorships::<T, I>::insert(beneficiary, (sponsor, allowance, max_fee));
+			Pallet::<T, I>::deposit_event(Event::<T, I>::SponsorshipSet(
+				sponsor,
+				beneficiary,
+				allowance,
+				max_fee,
+			));
+			Ok(())
+		}
+
+		/// Ends the sponsorship of `beneficiary`. Root ends it for the contracts.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_sponsorship(origin: OriginFor<T>, beneficiary: H160) -> DispatchResult {
+			Self::ensure_beneficiary_origin(&beneficiary, origin)?;
+			let (sponsor, _, _) =
+				Sponsorships::<T, I>::take(beneficiary).ok_or(Error::<T, I>::NotSponsored)?;
+
+			Pallet::<T, I>::deposit_event(Event::<T, I>::SponsorshipRemoved(sponsor, beneficiary));
+			Ok(())
+		}
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	#[pallet::metadata(T::AccountId = "AccountId")]
-	pub enum Event<T: Config> {
+	pub enum Event<T: Config<I>, I: 'static = ()> {
 		/// Ethereum events from contracts.
 		Log(Log),
 		/// A contract has been created at given \[address\].
@@ -330,10 +509,21 @@ pub mod pallet {
 		BalanceDeposit(T::AccountId, H160, U256),
 		/// A withdrawal has been made from a given address. \[sender, address, value\]
 		BalanceWithdraw(T::AccountId, H160, U256),
+		/// Precompiles called in the block, with their number of calls. \[calls\]
+		PrecompileCalls(Vec<(H160, u32)>),
+		/// A sponsorship was offered, or its offer withdrawn with a zero allowance. \[sponsor,
+		/// beneficiary, allowance, max_fee\]
+		SponsorshipOffered(H160, H160, U256, U256),
+		/// A sponsorship was accepted or updated. \[sponsor, beneficiary, allowance, max_fee\]
+		SponsorshipSet(H160, H160, U256, U256),
+		/// A sponsorship ended, or its allowance was spent. \[sponsor, beneficiary\]
+		SponsorshipRemoved(H160, H160),
+		/// A sponsor paid the fee of a transaction. \[sponsor, sender, fee\]
+		FeeSponsored(H160, H160, U256),
 	}
 
 	#[pallet::error]
-	pub enum Error<T> {
+	pub enum Error<T, I = ()> {
 		/// Not enough balance to perform action
 		BalanceLow,
 		/// Calculating total fee overflowed
@@ -346,6 +536,10 @@ pub mod pallet {
 		GasPriceTooLow,
 		/// Nonce is invalid
 		InvalidNonce,
+		/// The sponsor offered no sponsorship to the beneficiary.
+		NoSponsorshipOffer,
+		/// The beneficiary has no sponsorship.
+		NotSponsored,
 	}
 
 	#[pallet::genesis_config]
@@ -363,7 +557,7 @@ pub mod pallet {
 	}
 
 	#[pallet::genesis_build]
-	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+	impl<T: Config<I>, I: 'static> GenesisBuild<T, I> for GenesisConfig {
 		fn build(&self) {
 			for (address, account) in &self.accounts {
 				let account_id = T::AddressMapping::into_account_id(*address);
@@ -379,10 +573,10 @@ pub mod pallet {
 					account.balance.low_u128().unique_saturated_into(),
 				);
 
-				<AccountCodes<T>>::insert(address, &account.code);
+				<AccountCodes<T, I>>::insert(address, &account.code);
 
 				for (index, value) in &account.storage {
-					<AccountStorages<T>>::insert(address, index, value);
+					<AccountStorages<T, I>>::insert(address, index, value);
 				}
 			}
 		}
@@ -390,17 +584,61 @@ pub mod pallet {
 
 	#[pallet::storage]
 	#[pallet::getter(fn account_codes)]
-	pub type AccountCodes<T: Config> = StorageMap<_, Blake2_128Concat, H160, Vec<u8>, ValueQuery>;
+	pub type AccountCodes<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, H160, Vec<u8>, ValueQuery>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn account_storages)]
-	pub type AccountStorages<T: Config> =
+	pub type AccountStorages<T: Config<I>, I: 'static = ()> =
 		StorageDoubleMap<_, Blake2_128Concat, H160, Blake2_128Concat, H256, H256, ValueQuery>;
+
+	/// Self-destructed contracts whose storage was too large to be removed in the block, and is
+	/// removed in the idle time of the following blocks.
+	#[pallet::storage]
+	pub type Suicided<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, H160, (), OptionQuery>;
+
+	/// Precompiles called in the current block, with their number of calls, when
+	/// `CountPrecompileCalls`. Written once per transaction, kept until the next block is
+	/// initialized.
+	#[pallet::storage]
+	#[pallet::getter(fn precompile_calls)]
+	pub type PrecompileCalls<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, Vec<(H160, u32)>, ValueQuery>;
+
+	/// Sponsor paying the fees of the transactions to a contract, or of the transactions of an
+	/// account, with the allowance left and the maximum fee per transaction. Removed when the
+	/// allowance is spent.
+	#[pallet::storage]
+	#[pallet::getter(fn sponsorship)]
+	pub type Sponsorships<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, H160, (H160, U256, U256), OptionQuery>;
+
+	/// Sponsorships offered to a beneficiary, by sponsor, with their allowance and maximum fee
+	/// per transaction, until the beneficiary accepts them.
+	#[pallet::storage]
+	#[pallet::getter(fn sponsorship_offer)]
+	pub type SponsorshipOffers<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		H160,
+		Blake2_128Concat,
+		H160,
+		(U256, U256),
+		OptionQuery,
+	>;
+
+	/// Sponsors of the transactions of the current block, by sender and nonce. Kept until the
+	/// next block is initialized.
+	#[pallet::storage]
+	#[pallet::getter(fn fee_sponsors)]
+	pub type FeeSponsors<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, Vec<(H160, U256, H160)>, ValueQuery>;
 }
 
 /// Type alias for currency balance.
-pub type BalanceOf<T> =
-	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+pub type BalanceOf<T, I = ()> =
+	<<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 /// Type alias for negative imbalance during fees
 type NegativeImbalanceOf<C, T> =
@@ -530,6 +768,24 @@ impl<H: Hasher<Out = H256>> AddressMapping<AccountId32> for HashedAddressMapping
 	}
 }
 
+/// Hashed address mapping with the prefix `P` in place of `evm:`, so that the accounts of an
+/// EVM instance using it have their own nonces and balances, apart from the ones of the
+/// instances using another prefix.
+pub struct PrefixedHashedAddressMapping<H, P>(sp_std::marker::PhantomData<(H, P)>);
+
+impl<H: Hasher<Out = H256>, P: Get<[u8; 4]>> AddressMapping<AccountId32>
+	for PrefixedHashedAddressMapping<H, P>
+{
+	fn into_account_id(address: H160) -> AccountId32 {
+		let mut data = [0u8; 24];
+		data[0..4].copy_from_slice(&P::get());
+		data[4..24].copy_from_slice(&address[..]);
+		let hash = H::hash(&data);
+
+		AccountId32::from(Into::<[u8; 32]>::into(hash))
+	}
+}
+
 /// A trait for getting a block hash by number.
 pub trait BlockHashMapping {
 	fn block_hash(number: u32) -> H256;
@@ -537,7 +793,7 @@ pub trait BlockHashMapping {
 
 /// Returns the Substrate block hash by number.
 pub struct SubstrateBlockHashMapping<T>(sp_std::marker::PhantomData<T>);
-impl<T: Config> BlockHashMapping for SubstrateBlockHashMapping<T> {
+impl<T: frame_system::Config> BlockHashMapping for SubstrateBlockHashMapping<T> {
 	fn block_hash(number: u32) -> H256 {
 		let number = T::BlockNumber::from(number);
 		H256::from_slice(frame_system::Pallet::<T>::block_hash(number).as_ref())
@@ -559,8 +815,87 @@ impl GasWeightMapping for () {
 	}
 }
 
+/// Weights of a linear cost precompile, as measured by its benchmarks.
+pub trait PrecompileWeightInfo {
+	/// Weight of a call with an empty input.
+	fn base() -> Weight;
+	/// Weight of each 32-byte word of the input.
+	fn per_word() -> Weight;
+}
+
+/// Linear cost precompile `P` whose gas cost is derived from its benchmarked weights `W`,
+/// converted to gas with the `GasWeightMapping` `M` of the runtime, in place of the `BASE` and
+/// `WORD` constants of `P`. The cost follows the performance of the host functions as the
+/// weights are regenerated.
+pub struct BenchmarkedPrecompile<P, W, M>(sp_std::marker::PhantomData<(P, W, M)>);
+
+impl<P, W, M> Precompile for BenchmarkedPrecompile<P, W, M>
+where
+	P: LinearCostPrecompile,
+	W: PrecompileWeightInfo,
+	M: GasWeightMapping,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_: &Context,
+	) -> Result<PrecompileOutput, ExitError> {
+		let cost = fp_evm::ensure_linear_cost(
+			target_gas,
+			input.len() as u64,
+			M::weight_to_gas(W::base()),
+			M::weight_to_gas(W::per_word()),
+		)?;
+
+		let (exit_status, output) = P::execute(input, cost)?;
+		Ok(PrecompileOutput {
+			exit_status,
+			cost,
+			output,
+			logs: Default::default(),
+		})
+	}
+}
+
 static ISTANBUL_CONFIG: EvmConfig = EvmConfig::istanbul();
 
+/// Execution weight of `deposit` on top of its storage accesses, the ones of a balance
+/// transfer between two accounts.
+pub const DEPOSIT_WEIGHT: Weight = 50_000_000;
+
+/// Number of storage keys of a self-destructed contract removed in its block, the rest is
+/// removed in the following blocks.
+pub const SUICIDE_REMOVAL_LIMIT: u32 = 1_000;
+
+/// Maximum number of self-destructed contracts returned by `Pallet::pending_removals`.
+pub const PENDING_REMOVALS_LIMIT: usize = 100;
+
+/// Keccak256 hash of the empty code.
+pub const EMPTY_CODE_HASH: [u8; 32] = [
+	0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+	0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+];
+
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+/// Size and hash of an account code.
+pub struct CodeMetadata {
+	/// Code length in bytes.
+	pub size: u64,
+	/// Keccak256 hash of the code.
+	pub hash: H256,
+}
+
+impl CodeMetadata {
+	/// Metadata of the given code.
+	pub fn from_code(code: &[u8]) -> Self {
+		Self {
+			size: code.len() as u64,
+			hash: H256::from(sp_io::hashing::keccak_256(code)),
+		}
+	}
+}
+
 #[cfg(feature = "std")]
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Debug, Serialize, Deserialize)]
 /// Account definition used for genesis block construction.
@@ -575,11 +910,118 @@ pub struct GenesisAccount {
 	pub code: Vec<u8>,
 }
 
-impl<T: Config> Pallet<T> {
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Name of the latest hard fork whose features the EVM config enables.
+	pub fn evm_fork() -> &'static str {
+		let config = T::config();
+		if config.increase_state_access_gas {
+			"berlin"
+		} else if config.has_chain_id && config.has_self_balance {
+			"istanbul"
+		} else if config.has_create2 && config.has_bitwise_shifting {
+			"constantinople"
+		} else if config.has_revert && config.has_return_data {
+			"byzantium"
+		} else if config.has_delegate_call {
+			"homestead"
+		} else {
+			"frontier"
+		}
+	}
+
+	/// Names of the optional features the EVM config enables.
+	pub fn evm_features() -> Vec<&'static str> {
+		let config = T::config();
+		[
+			("delegateCall", config.has_delegate_call),
+			("create2", config.has_create2),
+			("revert", config.has_revert),
+			("returnData", config.has_return_data),
+			("bitwiseShifting", config.has_bitwise_shifting),
+			("chainId", config.has_chain_id),
+			("selfBalance", config.has_self_balance),
+			("extCodeHash", config.has_ext_code_hash),
+			("accessLists", config.increase_state_access_gas),
+		]
+		.iter()
+		.filter(|(_, enabled)| *enabled)
+		.map(|(name, _)| *name)
+		.collect()
+	}
+
+	/// Gas price paid by a call with EIP-1559 fee fields. The minimum gas price acts as the base
+	/// fee: the call pays it with its priority fee, up to its maximum fee. Without a priority fee
+	/// it pays its maximum fee, as a legacy transaction pays its gas price.
+	pub fn effective_gas_price(
+		max_fee_per_gas: Option<U256>,
+		max_priority_fee_per_gas: Option<U256>,
+	) -> Option<U256> {
+		let base_fee = T::FeeCalculator::min_gas_price();
+		match (max_fee_per_gas, max_priority_fee_per_gas) {
+			(Some(max_fee), Some(priority_fee)) => {
+				Some(max_fee.min(base_fee.saturating_add(priority_fee)))
+			}
+			(Some(max_fee), None) => Some(max_fee),
+			(None, Some(priority_fee)) => Some(base_fee.saturating_add(priority_fee)),
+			(None, None) => None,
+		}
+	}
+
+	/// Gas charged for the access list of a call before its execution. Fails if `config`
+	/// predates Berlin.
+	pub fn access_list_gas(
+		config: &evm::Config,
+		access_list: &[(H160, Vec<H256>)],
+	) -> Result<u64, DispatchError> {
+		if !access_list.is_empty() && !fp_evm::supports_access_lists(config) {
+			return Err(DispatchError::Other("access lists require the berlin fork"));
+		}
+		Ok(fp_evm::access_list_gas(config, access_list))
+	}
+
+	/// Executes a call of `source` to `target`, or a contract creation if `target` is None, as
+	/// the `call` and `create` runtime APIs do, and traces it with [`tracer::trace`]. The access
+	/// list leaves out `source`, the target or the created contract, and the precompiles.
+	#[cfg(feature = "tracing")]
+	pub fn trace_call(
+		source: H160,
+		target: Option<H160>,
+		input: Vec<u8>,
+		value: U256,
+		gas_limit: U256,
+		max_fee_per_gas: Option<U256>,
+		max_priority_fee_per_gas: Option<U256>,
+		nonce: Option<U256>,
+		access_list: Vec<(H160, Vec<H256>)>,
+	) -> Result<CallTrace, DispatchError> {
+		let config = T::config();
+		let access_list_gas = Self::access_list_gas(config, &access_list)?;
+		let gas_limit = gas_limit.low_u64().saturating_sub(access_list_gas);
+		let gas_price = Self::effective_gas_price(max_fee_per_gas, max_priority_fee_per_gas);
+
+		let (result, tracer) = tracer::trace::<T, I, _>(|| match target {
+			Some(target) => T::Runner::call(
+				source, target, input, value, gas_limit, gas_price, nonce, config,
+			)
+			.map(|info| (info.exit_reason, info.value, info.used_gas, target)),
+			None => T::Runner::create(source, input, value, gas_limit, gas_price, nonce, config)
+				.map(|info| (info.exit_reason, Vec::new(), info.used_gas, info.value)),
+		});
+		let (exit_reason, output, used_gas, target) =
+			result.map_err(Into::<DispatchError>::into)?;
+
+		Ok(tracer.finish(
+			exit_reason,
+			output,
+			used_gas.saturating_add(access_list_gas.into()),
+			&[source, target],
+		))
+	}
+
 	/// Check whether an account is empty.
 	pub fn is_account_empty(address: &H160) -> bool {
 		let account = Self::account_basic(address);
-		let code_len = <AccountCodes<T>>::decode_len(address).unwrap_or(0);
+		let code_len = <AccountCodes<T, I>>::decode_len(address).unwrap_or(0);
 
 		account.nonce == U256::zero() && account.balance == U256::zero() && code_len == 0
 	}
@@ -593,27 +1035,89 @@ impl<T: Config> Pallet<T> {
 
 	/// Remove an account.
 	pub fn remove_account(address: &H160) {
-		if <AccountCodes<T>>::contains_key(address) {
+		if <AccountCodes<T, I>>::contains_key(address) {
 			let account_id = T::AddressMapping::into_account_id(*address);
 			let _ = frame_system::Pallet::<T>::dec_consumers(&account_id);
 		}
 
-		<AccountCodes<T>>::remove(address);
-		<AccountStorages<T>>::remove_prefix(address, None);
+		<AccountCodes<T, I>>::remove(address);
+		// The remaining storage of large contracts is removed in `on_idle`.
+		if let KillStorageResult::SomeRemaining(_) =
+			<AccountStorages<T, I>>::remove_prefix(address, Some(SUICIDE_REMOVAL_LIMIT))
+		{
+			<Suicided<T, I>>::insert(address, ());
+		}
 	}
 
-	/// Create an account.
+	/// Remove the storage of self-destructed contracts within the given weight, returning the
+	/// weight used.
+	pub fn remove_suicided_storage(remaining_weight: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let mut used_weight = db_weight.reads(1);
+		let address = match <Suicided<T, I>>::iter_keys().next() {
+			Some(address) => address,
+			None => return used_weight,
+		};
+
+		let write_weight = db_weight.writes(1).max(1);
+		let limit = remaining_weight.saturating_sub(used_weight + write_weight) / write_weight;
+		if limit == 0 {
+			return used_weight;
+		}
+		match <AccountStorages<T, I>>::remove_prefix(
+			address,
+			Some(limit.min(u32::max_value() as Weight) as u32),
+		) {
+			KillStorageResult::AllRemoved(removed) => {
+				<Suicided<T, I>>::remove(address);
+				used_weight += db_weight.writes(removed as Weight + 1);
+			}
+			KillStorageResult::SomeRemaining(removed) => {
+				used_weight += db_weight.writes(removed as Weight);
+			}
+		}
+		used_weight
+	}
+
+	/// Whether the storage of the self-destructed contract `address` is still being removed.
+	pub fn is_removal_pending(address: &H160) -> bool {
+		<Suicided<T, I>>::contains_key(address)
+	}
+
+	/// Self-destructed contracts whose storage is still being removed, at most
+	/// `PENDING_REMOVALS_LIMIT` of them, with the number of storage keys left, counted up to
+	/// `SUICIDE_REMOVAL_LIMIT`.
+	pub fn pending_removals() -> Vec<(H160, u32)> {
+		<Suicided<T, I>>::iter_keys()
+			.take(PENDING_REMOVALS_LIMIT)
+			.map(|address| {
+				let remaining = <AccountStorages<T, I>>::iter_key_prefix(address)
+					.take(SUICIDE_REMOVAL_LIMIT as usize)
+					.count();
+				(address, remaining as u32)
+			})
+			.collect()
+	}
+
+	/// Create an account. Does nothing at the address of a self-destructed contract whose
+	/// storage is still being removed, where the contract creations of the EVM collide, see
+	/// `is_removal_pending`.
 	pub fn create_account(address: H160, code: Vec<u8>) {
-		if code.is_empty() {
+		if code.is_empty() || Self::is_removal_pending(&address) {
 			return;
 		}
 
-		if !<AccountCodes<T>>::contains_key(&address) {
+		if !<AccountCodes<T, I>>::contains_key(&address) {
 			let account_id = T::AddressMapping::into_account_id(address);
 			let _ = frame_system::Pallet::<T>::inc_consumers(&account_id);
 		}
 
-		<AccountCodes<T>>::insert(address, code);
+		<AccountCodes<T, I>>::insert(address, code);
+	}
+
+	/// Get the size and hash of the code of an account.
+	pub fn account_code_metadata(address: H160) -> CodeMetadata {
+		CodeMetadata::from_code(&<AccountCodes<T, I>>::get(address))
 	}
 
 	/// Get the account basic in EVM format.
@@ -632,7 +1136,64 @@ impl<T: Config> Pallet<T> {
 
 	/// Get the author using the FindAuthor trait.
 	pub fn find_author() -> H160 {
-		let digest = <frame_system::Pallet<T>>::digest();
+		Self::find_author_of(&<frame_system::Pallet<T>>::digest())
+	}
+
+	/// Executes the precompile at `address` if any, counting the call in memory when the
+	/// runner counts the calls of the transaction.
+	pub fn execute_precompile(
+		address: H160,
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> Option<Result<PrecompileOutput, ExitError>> {
+		let result = T::Precompiles::execute(address, input, target_gas, context);
+		if result.is_some() {
+			precompile_call_counts::with(|calls| add_precompile_calls(calls, address, 1));
+		}
+		result
+	}
+
+	/// Adds the precompile calls of a transaction to the ones of the block, in a single write.
+	pub fn record_precompile_calls(transaction_calls: Vec<(H160, u32)>) {
+		if transaction_calls.is_empty() {
+			return;
+		}
+		PrecompileCalls::<T, I>::mutate(|calls| {
+			for (address, count) in transaction_calls {
+				add_precompile_calls(calls, address, count);
+			}
+		});
+	}
+
+	/// Ensures `origin` may accept or end the sponsorships of `beneficiary`: the origin of its
+	/// address, or root.
+	fn ensure_beneficiary_origin(
+		beneficiary: &H160,
+		origin: <T as frame_system::Config>::Origin,
+	) -> Result<(), BadOrigin> {
+		if frame_system::ensure_root(origin.clone()).is_ok() {
+			return Ok(());
+		}
+		T::CallOrigin::ensure_address_origin(beneficiary, origin).map(|_| ())
+	}
+
+	/// Sponsorship with enough allowance left, and a high enough maximum fee, to pay `fee` for a
+	/// transaction of `who` to `target`, None for the contract creations: the sponsorship of the
+	/// contract first, then the one of `who`. Returns the beneficiary and the sponsor.
+	pub fn fee_sponsor(who: &H160, target: Option<H160>, fee: U256) -> Option<(H160, H160)> {
+		target
+			.into_iter()
+			.chain(Some(*who))
+			.find_map(|beneficiary| {
+				Sponsorships::<T, I>::get(beneficiary)
+					.filter(|(_, allowance, max_fee)| *allowance >= fee && *max_fee >= fee)
+					.map(|(sponsor, _, _)| (beneficiary, sponsor))
+			})
+	}
+
+	/// Get the author of the block with the given digest using the FindAuthor trait.
+	pub fn find_author_of(digest: &frame_system::DigestOf<T>) -> H160 {
 		let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
 
 		T::FindAuthor::find_author(pre_runtime_digests).unwrap_or_default()
@@ -641,12 +1202,33 @@ impl<T: Config> Pallet<T> {
 
 /// Handle withdrawing, refunding and depositing of transaction fees.
 /// Similar to `OnChargeTransaction` of `pallet_transaction_payment`
-pub trait OnChargeEVMTransaction<T: Config> {
+pub trait OnChargeEVMTransaction<T: Config<I>, I: 'static = ()> {
 	type LiquidityInfo: Default;
 
 	/// Before the transaction is executed the payment of the transaction fees
 	/// need to be secured.
-	fn withdraw_fee(who: &H160, fee: U256) -> Result<Self::LiquidityInfo, Error<T>>;
+	fn withdraw_fee(who: &H160, fee: U256) -> Result<Self::LiquidityInfo, Error<T, I>>;
+
+	/// Checks that `who` can pay `fee` for a transaction to `target`, None for the contract
+	/// creations, and returns the part of the fee paid from its native balance, which the
+	/// balance checks of the transaction cover. The whole fee by default.
+	fn ensure_fee_payable(
+		_who: &H160,
+		_target: Option<H160>,
+		fee: U256,
+	) -> Result<U256, Error<T, I>> {
+		Ok(fee)
+	}
+
+	/// `withdraw_fee` for a transaction to `target`, None for the contract creations, for the
+	/// implementations charging the transactions differently by target.
+	fn withdraw_fee_for(
+		who: &H160,
+		_target: Option<H160>,
+		fee: U256,
+	) -> Result<Self::LiquidityInfo, Error<T, I>> {
+		Self::withdraw_fee(who, fee)
+	}
 
 	/// After the transaction was executed the actual fee can be calculated.
 	/// This function should refund any overpaid fees and optionally deposit
@@ -664,9 +1246,10 @@ pub trait OnChargeEVMTransaction<T: Config> {
 /// Similar to `CurrencyAdapter` of `pallet_transaction_payment`
 pub struct EVMCurrencyAdapter<C, OU>(sp_std::marker::PhantomData<(C, OU)>);
 
-impl<T, C, OU> OnChargeEVMTransaction<T> for EVMCurrencyAdapter<C, OU>
+impl<T, I, C, OU> OnChargeEVMTransaction<T, I> for EVMCurrencyAdapter<C, OU>
 where
-	T: Config,
+	T: Config<I>,
+	I: 'static,
 	C: Currency<<T as frame_system::Config>::AccountId>,
 	C::PositiveImbalance: Imbalance<
 		<C as Currency<<T as frame_system::Config>::AccountId>>::Balance,
@@ -681,7 +1264,7 @@ where
 	// Kept type as Option to satisfy bound of Default
 	type LiquidityInfo = Option<NegativeImbalanceOf<C, T>>;
 
-	fn withdraw_fee(who: &H160, fee: U256) -> Result<Self::LiquidityInfo, Error<T>> {
+	fn withdraw_fee(who: &H160, fee: U256) -> Result<Self::LiquidityInfo, Error<T, I>> {
 		let account_id = T::AddressMapping::into_account_id(*who);
 		let imbalance = C::withdraw(
 			&account_id,
@@ -689,7 +1272,7 @@ where
 			WithdrawReasons::FEE,
 			ExistenceRequirement::AllowDeath,
 		)
-		.map_err(|_| Error::<T>::BalanceLow)?;
+		.map_err(|_| Error::<T, I>::BalanceLow)?;
 		Ok(Some(imbalance))
 	}
 
@@ -721,9 +1304,10 @@ where
 }
 
 /// Implementation for () does not specify what to do with imbalance
-impl<T> OnChargeEVMTransaction<T> for ()
+impl<T, I> OnChargeEVMTransaction<T, I> for ()
 	where
-	T: Config,
+	T: Config<I>,
+	I: 'static,
 	<T::Currency as Currency<<T as frame_system::Config>::AccountId>>::PositiveImbalance:
 		Imbalance<<T::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance, Opposite = <T::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance>,
 	<T::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance:
@@ -734,8 +1318,8 @@ impl<T> OnChargeEVMTransaction<T> for ()
 	fn withdraw_fee(
 		who: &H160,
 		fee: U256,
-	) -> Result<Self::LiquidityInfo, Error<T>> {
-		EVMCurrencyAdapter::<<T as Config>::Currency, ()>::withdraw_fee(who, fee)
+	) -> Result<Self::LiquidityInfo, Error<T, I>> {
+		<EVMCurrencyAdapter::<<T as Config<I>>::Currency, ()> as OnChargeEVMTransaction<T, I>>::withdraw_fee(who, fee)
 	}
 
 	fn correct_and_deposit_fee(
@@ -743,6 +1327,6 @@ impl<T> OnChargeEVMTransaction<T> for ()
 		corrected_fee: U256,
 		already_withdrawn: Self::LiquidityInfo,
 	) {
-		<EVMCurrencyAdapter::<<T as Config>::Currency, ()> as OnChargeEVMTransaction<T>>::correct_and_deposit_fee(who, corrected_fee, already_withdrawn)
+		<EVMCurrencyAdapter::<<T as Config<I>>::Currency, ()> as OnChargeEVMTransaction<T, I>>::correct_and_deposit_fee(who, corrected_fee, already_withdrawn)
 	}
 }
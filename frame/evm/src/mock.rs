@@ -38,6 +38,7 @@ frame_support::construct_runtime! {
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage},
 		EVM: crate::{Pallet, Call, Storage, Config, Event<T>},
+		SecondEVM: crate::<Instance1>::{Pallet, Call, Storage, Config, Event<T>},
 	}
 }
 
@@ -133,4 +134,31 @@ impl crate::Config for Test {
 	type OnChargeTransaction = ();
 	type BlockHashMapping = crate::SubstrateBlockHashMapping<Self>;
 	type FindAuthor = FindAuthorTruncated;
+	type CountPrecompileCalls = ();
+}
+
+parameter_types! {
+	pub const SecondChainId: u64 = 43;
+}
+
+/// A second EVM environment, isolated from the default instance.
+impl crate::Config<crate::Instance1> for Test {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = ();
+
+	type CallOrigin = EnsureAddressRoot<Self::AccountId>;
+	type WithdrawOrigin = EnsureAddressNever<Self::AccountId>;
+
+	type AddressMapping = IdentityAddressMapping;
+	type Currency = Balances;
+	type Runner = crate::runner::stack::Runner<Self, crate::Instance1>;
+
+	type Event = Event;
+	type Precompiles = ();
+	type ChainId = SecondChainId;
+	type BlockGasLimit = ();
+	type OnChargeTransaction = ();
+	type BlockHashMapping = crate::SubstrateBlockHashMapping<Self>;
+	type FindAuthor = FindAuthorTruncated;
+	type CountPrecompileCalls = ();
 }
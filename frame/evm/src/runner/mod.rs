@@ -22,7 +22,7 @@ use fp_evm::{CallInfo, CreateInfo};
 use sp_core::{H160, H256, U256};
 use sp_std::vec::Vec;
 
-pub trait Runner<T: Config> {
+pub trait Runner<T: Config<I>, I: 'static = ()> {
 	type Error: Into<sp_runtime::DispatchError>;
 
 	fn call(
@@ -19,7 +19,7 @@
 
 use crate::{
 	runner::Runner as RunnerT, AccountCodes, AccountStorages, AddressMapping, BlockHashMapping,
-	Config, Error, Event, FeeCalculator, OnChargeEVMTransaction, Pallet, PrecompileSet,
+	Config, Error, Event, FeeCalculator, OnChargeEVMTransaction, Pallet,
 };
 use evm::{
 	backend::Backend as BackendT,
@@ -37,24 +37,25 @@ use sp_runtime::traits::UniqueSaturatedInto;
 use sp_std::{boxed::Box, collections::btree_set::BTreeSet, marker::PhantomData, mem, vec::Vec};
 
 #[derive(Default)]
-pub struct Runner<T: Config> {
-	_marker: PhantomData<T>,
+pub struct Runner<T: Config<I>, I: 'static = ()> {
+	_marker: PhantomData<(T, I)>,
 }
 
-impl<T: Config> Runner<T> {
+impl<T: Config<I>, I: 'static> Runner<T, I> {
 	/// Execute an EVM operation.
 	pub fn execute<'config, F, R>(
 		source: H160,
+		target: Option<H160>,
 		value: U256,
 		gas_limit: u64,
 		gas_price: Option<U256>,
 		nonce: Option<U256>,
 		config: &'config evm::Config,
 		f: F,
-	) -> Result<ExecutionInfo<R>, Error<T>>
+	) -> Result<ExecutionInfo<R>, Error<T, I>>
 	where
 		F: FnOnce(
-			&mut StackExecutor<'config, SubstrateStackState<'_, 'config, T>>,
+			&mut StackExecutor<'config, SubstrateStackState<'_, 'config, T, I>>,
 		) -> (ExitReason, R),
 	{
 		// Gas price check is skipped when performing a gas estimation.
@@ -62,7 +63,7 @@ impl<T: Config> Runner<T> {
 			Some(gas_price) => {
 				ensure!(
 					gas_price >= T::FeeCalculator::min_gas_price(),
-					Error::<T>::GasPriceTooLow
+					Error::<T, I>::GasPriceTooLow
 				);
 				gas_price
 			}
@@ -77,29 +78,39 @@ impl<T: Config> Runner<T> {
 		let metadata = StackSubstateMetadata::new(gas_limit, &config);
 		let state = SubstrateStackState::new(&vicinity, metadata);
 		let mut executor =
-			StackExecutor::new_with_precompile(state, config, T::Precompiles::execute);
+			StackExecutor::new_with_precompile(state, config, Pallet::<T, I>::execute_precompile);
 
 		let total_fee = gas_price
 			.checked_mul(U256::from(gas_limit))
-			.ok_or(Error::<T>::FeeOverflow)?;
+			.ok_or(Error::<T, I>::FeeOverflow)?;
+		// The part of the fee paid in another asset is not covered by the balance.
+		let native_fee = T::OnChargeTransaction::ensure_fee_payable(&source, target, total_fee)?;
 		let total_payment = value
-			.checked_add(total_fee)
-			.ok_or(Error::<T>::PaymentOverflow)?;
-		let source_account = Pallet::<T>::account_basic(&source);
+			.checked_add(native_fee)
+			.ok_or(Error::<T, I>::PaymentOverflow)?;
+		let source_account = Pallet::<T, I>::account_basic(&source);
 		ensure!(
 			source_account.balance >= total_payment,
-			Error::<T>::BalanceLow
+			Error::<T, I>::BalanceLow
 		);
 
 		if let Some(nonce) = nonce {
-			ensure!(source_account.nonce == nonce, Error::<T>::InvalidNonce);
+			ensure!(source_account.nonce == nonce, Error::<T, I>::InvalidNonce);
 		}
 
 		// Deduct fee from the `source` account.
-		let fee = T::OnChargeTransaction::withdraw_fee(&source, total_fee)?;
-
-		// Execute the EVM call.
-		let (reason, retv) = f(&mut executor);
+		let fee = T::OnChargeTransaction::withdraw_fee_for(&source, target, total_fee)?;
+
+		// Execute the EVM call. The precompile calls are counted in memory and written once.
+		let (reason, retv) = if T::CountPrecompileCalls::get() {
+			let mut precompile_calls = Vec::new();
+			let result =
+				crate::precompile_call_counts::using(&mut precompile_calls, || f(&mut executor));
+			Pallet::<T, I>::record_precompile_calls(precompile_calls);
+			result
+		} else {
+			f(&mut executor)
+		};
 
 		let used_gas = U256::from(executor.used_gas());
 		let actual_fee = executor.fee(gas_price);
@@ -124,7 +135,7 @@ impl<T: Config> Runner<T> {
 				"Deleting account at {:?}",
 				address
 			);
-			Pallet::<T>::remove_account(&address)
+			Pallet::<T, I>::remove_account(&address)
 		}
 
 		for log in &state.substate.logs {
@@ -137,7 +148,7 @@ impl<T: Config> Runner<T> {
 				log.data.len(),
 				log.data
 			);
-			Pallet::<T>::deposit_event(Event::<T>::Log(Log {
+			Pallet::<T, I>::deposit_event(Event::<T, I>::Log(Log {
 				address: log.address,
 				topics: log.topics.clone(),
 				data: log.data.clone(),
@@ -153,8 +164,8 @@ impl<T: Config> Runner<T> {
 	}
 }
 
-impl<T: Config> RunnerT<T> for Runner<T> {
-	type Error = Error<T>;
+impl<T: Config<I>, I: 'static> RunnerT<T, I> for Runner<T, I> {
+	type Error = Error<T, I>;
 
 	fn call(
 		source: H160,
@@ -168,6 +179,7 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 	) -> Result<CallInfo, Self::Error> {
 		Self::execute(
 			source,
+			Some(target),
 			value,
 			gas_limit,
 			gas_price,
@@ -188,6 +200,7 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 	) -> Result<CreateInfo, Self::Error> {
 		Self::execute(
 			source,
+			None,
 			value,
 			gas_limit,
 			gas_price,
@@ -216,6 +229,7 @@ impl<T: Config> RunnerT<T> for Runner<T> {
 		let code_hash = H256::from_slice(Keccak256::digest(&init).as_slice());
 		Self::execute(
 			source,
+			None,
 			value,
 			gas_limit,
 			gas_price,
@@ -322,13 +336,13 @@ impl<'config> SubstrateStackSubstate<'config> {
 }
 
 /// Substrate backend for EVM.
-pub struct SubstrateStackState<'vicinity, 'config, T> {
+pub struct SubstrateStackState<'vicinity, 'config, T, I = ()> {
 	vicinity: &'vicinity Vicinity,
 	substate: SubstrateStackSubstate<'config>,
-	_marker: PhantomData<T>,
+	_marker: PhantomData<(T, I)>,
 }
 
-impl<'vicinity, 'config, T: Config> SubstrateStackState<'vicinity, 'config, T> {
+impl<'vicinity, 'config, T: Config<I>, I: 'static> SubstrateStackState<'vicinity, 'config, T, I> {
 	/// Create a new backend with given vicinity.
 	pub fn new(vicinity: &'vicinity Vicinity, metadata: StackSubstateMetadata<'config>) -> Self {
 		Self {
@@ -344,7 +358,9 @@ impl<'vicinity, 'config, T: Config> SubstrateStackState<'vicinity, 'config, T> {
 	}
 }
 
-impl<'vicinity, 'config, T: Config> BackendT for SubstrateStackState<'vicinity, 'config, T> {
+impl<'vicinity, 'config, T: Config<I>, I: 'static> BackendT
+	for SubstrateStackState<'vicinity, 'config, T, I>
+{
 	fn gas_price(&self) -> U256 {
 		self.vicinity.gas_price
 	}
@@ -366,7 +382,7 @@ impl<'vicinity, 'config, T: Config> BackendT for SubstrateStackState<'vicinity,
 	}
 
 	fn block_coinbase(&self) -> H160 {
-		Pallet::<T>::find_author()
+		Pallet::<T, I>::find_author()
 	}
 
 	fn block_timestamp(&self) -> U256 {
@@ -391,20 +407,28 @@ impl<'vicinity, 'config, T: Config> BackendT for SubstrateStackState<'vicinity,
 	}
 
 	fn basic(&self, address: H160) -> evm::backend::Basic {
-		let account = Pallet::<T>::account_basic(&address);
+		let account = Pallet::<T, I>::account_basic(&address);
+
+		// The contract creations at the address of a self-destructed contract whose storage is
+		// still being removed collide, as the nonce of the address is not zero.
+		let nonce = if Pallet::<T, I>::is_removal_pending(&address) {
+			account.nonce.max(U256::one())
+		} else {
+			account.nonce
+		};
 
 		evm::backend::Basic {
 			balance: account.balance,
-			nonce: account.nonce,
+			nonce,
 		}
 	}
 
 	fn code(&self, address: H160) -> Vec<u8> {
-		<AccountCodes<T>>::get(&address)
+		<AccountCodes<T, I>>::get(&address)
 	}
 
 	fn storage(&self, address: H160, index: H256) -> H256 {
-		<AccountStorages<T>>::get(address, index)
+		<AccountStorages<T, I>>::get(address, index)
 	}
 
 	fn original_storage(&self, _address: H160, _index: H256) -> Option<H256> {
@@ -412,8 +436,8 @@ impl<'vicinity, 'config, T: Config> BackendT for SubstrateStackState<'vicinity,
 	}
 }
 
-impl<'vicinity, 'config, T: Config> StackStateT<'config>
-	for SubstrateStackState<'vicinity, 'config, T>
+impl<'vicinity, 'config, T: Config<I>, I: 'static> StackStateT<'config>
+	for SubstrateStackState<'vicinity, 'config, T, I>
 {
 	fn metadata(&self) -> &StackSubstateMetadata<'config> {
 		self.substate.metadata()
@@ -440,7 +464,7 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 	}
 
 	fn is_empty(&self, address: H160) -> bool {
-		Pallet::<T>::is_account_empty(&address)
+		Pallet::<T, I>::is_account_empty(&address)
 	}
 
 	fn deleted(&self, address: H160) -> bool {
@@ -460,7 +484,7 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 				address,
 				index,
 			);
-			<AccountStorages<T>>::remove(address, index);
+			<AccountStorages<T, I>>::remove(address, index);
 		} else {
 			log::debug!(
 				target: "evm",
@@ -469,12 +493,12 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 				index,
 				value,
 			);
-			<AccountStorages<T>>::insert(address, index, value);
+			<AccountStorages<T, I>>::insert(address, index, value);
 		}
 	}
 
 	fn reset_storage(&mut self, address: H160) {
-		<AccountStorages<T>>::remove_prefix(address, None);
+		<AccountStorages<T, I>>::remove_prefix(address, None);
 	}
 
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
@@ -492,7 +516,7 @@ impl<'vicinity, 'config, T: Config> StackStateT<'config>
 			code.len(),
 			address
 		);
-		Pallet::<T>::create_account(address, code);
+		Pallet::<T, I>::create_account(address, code);
 	}
 
 	fn transfer(&mut self, transfer: Transfer) -> Result<(), ExitError> {
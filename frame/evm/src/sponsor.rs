@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sponsored transactions, whose fees are paid by a sponsor instead of their sender.
+//!
+//! A sponsor offers a sponsorship with `set_sponsorship`, for the transactions to a contract or
+//! for the transactions of an account, up to an allowance of fees and a maximum fee per
+//! transaction. It starts when the beneficiary accepts it with `accept_sponsorship`, and ends
+//! when either side removes it or the allowance is spent. [`EVMSponsoredAdapter`] charges the
+//! sponsored transactions to their sponsor through the wrapped fee adapter, and records the
+//! sponsors of the block in `FeeSponsors` for the RPC to report them in the receipts.
+
+use crate::{Config, Error, Event, FeeSponsors, OnChargeEVMTransaction, Pallet, Sponsorships};
+use frame_support::ensure;
+use sp_core::{H160, U256};
+use sp_std::{cmp::min, marker::PhantomData};
+
+/// Fees secured by [`EVMSponsoredAdapter`] before the execution.
+#[derive(Default)]
+pub struct SponsoredLiquidity<L> {
+	/// Beneficiary and sponsor of a sponsored transaction, with the fee withdrawn from the
+	/// allowance.
+	sponsorship: Option<(H160, H160, U256)>,
+	inner: L,
+}
+
+/// Charges the sponsored transactions to their sponsor, and the other transactions to their
+/// sender, through `Inner`.
+pub struct EVMSponsoredAdapter<Inner>(PhantomData<Inner>);
+
+impl<T, I, Inner> OnChargeEVMTransaction<T, I> for EVMSponsoredAdapter<Inner>
+where
+	T: Config<I>,
+	I: 'static,
+	Inner: OnChargeEVMTransaction<T, I>,
+{
+	type LiquidityInfo = SponsoredLiquidity<Inner::LiquidityInfo>;
+
+	fn withdraw_fee(who: &H160, fee: U256) -> Result<Self::LiquidityInfo, Error<T, I>> {
+		Self::withdraw_fee_for(who, None, fee)
+	}
+
+	fn ensure_fee_payable(
+		who: &H160,
+		target: Option<H160>,
+		fee: U256,
+	) -> Result<U256, Error<T, I>> {
+		match Pallet::<T, I>::fee_sponsor(who, target, fee) {
+			Some((_, sponsor)) => {
+				let native_fee = Inner::ensure_fee_payable(&sponsor, target, fee)?;
+				ensure!(
+					Pallet::<T, I>::account_basic(&sponsor).balance >= native_fee,
+					Error::<T, I>::BalanceLow
+				);
+				Ok(U256::zero())
+			}
+			None => Inner::ensure_fee_payable(who, target, fee),
+		}
+	}
+
+	fn withdraw_fee_for(
+		who: &H160,
+		target: Option<H160>,
+		fee: U256,
+	) -> Result<Self::LiquidityInfo, Error<T, I>> {
+		match Pallet::<T, I>::fee_sponsor(who, target, fee) {
+			Some((beneficiary, sponsor)) => {
+				let inner = Inner::withdraw_fee_for(&sponsor, target, fee)?;
+				Sponsorships::<T, I>::mutate(beneficiary, |sponsorship| {
+					if let Some((_, allowance, _)) = sponsorship {
+						*allowance = allowance.saturating_sub(fee);
+					}
+				});
+				// The fee is withdrawn before the execution increments the nonce.
+				let nonce = Pallet::<T, I>::account_basic(who).nonce;
+				FeeSponsors::<T, I>::append((*who, nonce, sponsor));
+				Ok(SponsoredLiquidity {
+					sponsorship: Some((beneficiary, sponsor, fee)),
+					inner,
+				})
+			}
+			None => Ok(SponsoredLiquidity {
+				sponsorship: None,
+				inner: Inner::withdraw_fee_for(who, target, fee)?,
+			}),
+		}
+	}
+
+	fn correct_and_deposit_fee(
+		who: &H160,
+		corrected_fee: U256,
+		already_withdrawn: Self::LiquidityInfo,
+	) {
+		match already_withdrawn.sponsorship {
+			Some((beneficiary, sponsor, withdrawn)) => {
+				Inner::correct_and_deposit_fee(&sponsor, corrected_fee, already_withdrawn.inner);
+				// The unused part of the fee goes back to the allowance.
+				let refund = withdrawn.saturating_sub(corrected_fee);
+				let spent = Sponsorships::<T, I>::mutate_exists(beneficiary, |sponsorship| {
+					let spent = match sponsorship {
+						Some((current, allowance, _)) if *current == sponsor => {
+							*allowance = allowance.saturating_add(refund);
+							allowance.is_zero()
+						}
+						_ => false,
+					};
+					if spent {
+						*sponsorship = None;
+					}
+					spent
+				});
+				if spent {
+					Pallet::<T, I>::deposit_event(Event::<T, I>::SponsorshipRemoved(
+						sponsor,
+						beneficiary,
+					));
+				}
+				Pallet::<T, I>::deposit_event(Event::<T, I>::FeeSponsored(
+					sponsor,
+					*who,
+					min(corrected_fee, withdrawn),
+				));
+			}
+			None => Inner::correct_and_deposit_fee(who, corrected_fee, already_withdrawn.inner),
+		}
+	}
+}
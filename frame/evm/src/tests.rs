@@ -18,12 +18,22 @@
 #![cfg(test)]
 
 use super::*;
-use crate::mock::*;
+use crate::{
+	fee_token::{EVMFeeTokenAdapter, FeeTokenOracle},
+	mock::*,
+};
 
+#[cfg(feature = "tracing")]
+use fp_evm::CallFrame;
 use frame_support::{
-	assert_ok,
-	traits::{GenesisBuild, LockIdentifier, LockableCurrency, WithdrawReasons},
+	assert_noop, assert_ok, parameter_types,
+	traits::{
+		tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+		GenesisBuild, LockIdentifier, LockableCurrency, WithdrawReasons,
+	},
+	weights::GetDispatchInfo,
 };
+use sp_runtime::{traits::BlakeTwo256, FixedPointNumber, FixedU128};
 use std::{collections::BTreeMap, str::FromStr};
 
 type Balances = pallet_balances::Pallet<Test>;
@@ -144,3 +154,903 @@ fn reducible_balance() {
 		assert_eq!(reducible_balance, (genesis_balance - to_lock + existential));
 	});
 }
+
+#[test]
+fn geth_genesis_is_imported() {
+	let genesis = geth_genesis::GethGenesis::from_json(
+		br#"{
+			"config": { "chainId": 1337 },
+			"gasLimit": "0x1c9c380",
+			"alloc": {
+				"1000000000000000000000000000000000000001": { "balance": "1000000000000000000" },
+				"0x1000000000000000000000000000000000000002": {
+					"balance": "0x10",
+					"nonce": "0x1",
+					"code": "0x6000",
+					"storage": { "0x01": "0x02" }
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	assert_eq!(genesis.chain_id, Some(1337));
+	assert_eq!(genesis.gas_limit, Some(U256::from(30_000_000)));
+
+	let first =
+		&genesis.accounts[&H160::from_str("1000000000000000000000000000000000000001").unwrap()];
+	assert_eq!(first.balance, U256::exp10(18));
+	assert_eq!(first.nonce, U256::zero());
+
+	let second =
+		&genesis.accounts[&H160::from_str("1000000000000000000000000000000000000002").unwrap()];
+	assert_eq!(second.balance, U256::from(16));
+	assert_eq!(second.nonce, U256::one());
+	assert_eq!(second.code, vec![0x60, 0x00]);
+	assert_eq!(
+		second.storage.get(&H256::from_low_u64_be(1)),
+		Some(&H256::from_low_u64_be(2))
+	);
+}
+
+#[test]
+fn geth_genesis_round_trips() {
+	let mut accounts = BTreeMap::new();
+	accounts.insert(
+		H160::from_low_u64_be(1),
+		GenesisAccount {
+			nonce: U256::one(),
+			balance: U256::exp10(18),
+			storage: vec![(H256::from_low_u64_be(1), H256::from_low_u64_be(2))]
+				.into_iter()
+				.collect(),
+			code: vec![0x60, 0x00],
+		},
+	);
+	let genesis = geth_genesis::GethGenesis {
+		chain_id: Some(42),
+		gas_limit: Some(U256::from(15_000_000)),
+		accounts,
+	};
+
+	assert_eq!(
+		geth_genesis::GethGenesis::from_json(&genesis.to_json()),
+		Ok(genesis.clone())
+	);
+	assert_eq!(
+		geth_genesis::GethGenesis::decode(&mut &genesis.encode()[..]).unwrap(),
+		genesis
+	);
+}
+
+#[test]
+fn genesis_builder_funds_dev_accounts() {
+	let contract = H160::from_low_u64_be(1);
+	let config = genesis_builder::GenesisBuilder::new()
+		.with_contract(contract, vec![0x60, 0x00], BTreeMap::new())
+		.with_balance(contract, U256::one())
+		.with_dev_accounts(20, genesis_builder::default_dev_balance())
+		.build();
+
+	assert_eq!(config.accounts.len(), 11);
+	let dev_account = H160::from_str("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+	assert_eq!(
+		config.accounts[&dev_account].balance,
+		U256::from(10_000) * U256::exp10(18)
+	);
+	assert_eq!(config.accounts[&contract].code, vec![0x60, 0x00]);
+	assert_eq!(config.accounts[&contract].balance, U256::one());
+}
+
+#[test]
+fn genesis_builder_runs_constructors() {
+	let address = H160::from_low_u64_be(0x42);
+	// SSTORE(0, 42) then RETURN the single byte runtime code `STOP`.
+	let init_code = vec![0x60, 0x2a, 0x60, 0x00, 0x55, 0x60, 0x01, 0x60, 0x00, 0xf3];
+	let config = genesis_builder::GenesisBuilder::new()
+		.with_balance(address, U256::one())
+		.with_deployment(address, init_code, &evm::Config::istanbul())
+		.unwrap()
+		.build();
+
+	let account = &config.accounts[&address];
+	assert_eq!(account.code, vec![0x00]);
+	assert_eq!(
+		account.storage.get(&H256::zero()),
+		Some(&H256::from_low_u64_be(42))
+	);
+	assert_eq!(account.balance, U256::one());
+	assert_eq!(config.accounts.len(), 1);
+
+	assert!(genesis_builder::GenesisBuilder::new()
+		.with_deployment(address, vec![0xfd], &evm::Config::istanbul())
+		.is_err());
+}
+
+#[test]
+fn genesis_builder_installs_canonical_predeploys() {
+	let config = genesis_builder::GenesisBuilder::new()
+		.with_deterministic_deployment_proxy()
+		.build();
+
+	let proxy = H160::from_str(genesis_builder::DETERMINISTIC_DEPLOYMENT_PROXY).unwrap();
+	assert_eq!(config.accounts[&proxy].code.len(), 69);
+	assert_eq!(
+		H256::from(sp_io::hashing::keccak_256(&config.accounts[&proxy].code)),
+		H256::from_str("2fa86add0aed31f33a762c9d88e807c475bd51d0f52bd0955754b2608f7e4989").unwrap()
+	);
+
+	// Only the code of the mainnet deployment is installed at the address of Multicall3.
+	assert!(genesis_builder::GenesisBuilder::new()
+		.with_multicall3(vec![0x00])
+		.is_err());
+}
+
+#[test]
+fn instances_are_isolated() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::from_str("1000000000000000000000000000000000000004").unwrap();
+		// CHAINID PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+		let code = vec![0x46, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+		Pallet::<Test, Instance1>::create_account(contract, code.clone());
+
+		assert_eq!(AccountCodes::<Test, Instance1>::get(&contract), code);
+		assert!(!AccountCodes::<Test>::contains_key(&contract));
+
+		let info = <Test as Config<Instance1>>::Runner::call(
+			H160::default(),
+			contract,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config<Instance1>>::config(),
+		)
+		.unwrap();
+		assert_eq!(U256::from_big_endian(&info.value), U256::from(43));
+
+		let info = <Test as Config>::Runner::call(
+			H160::default(),
+			contract,
+			Vec::new(),
+			U256::default(),
+			1000000,
+			None,
+			None,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert!(info.value.is_empty());
+	});
+}
+
+#[test]
+fn code_metadata_follows_the_code() {
+	new_test_ext().execute_with(|| {
+		let address = H160::from_str("1000000000000000000000000000000000000005").unwrap();
+		let code = vec![0x00];
+
+		EVM::create_account(address, code.clone());
+		assert_eq!(
+			EVM::account_code_metadata(address),
+			CodeMetadata {
+				size: 1,
+				hash: H256::from(sp_io::hashing::keccak_256(&code)),
+			}
+		);
+
+		EVM::remove_account(&address);
+		assert_eq!(
+			EVM::account_code_metadata(address),
+			CodeMetadata {
+				size: 0,
+				hash: H256::from(EMPTY_CODE_HASH),
+			}
+		);
+	});
+}
+
+#[test]
+fn deposit_moves_balance_into_evm() {
+	new_test_ext().execute_with(|| {
+		let source = H160::from_str("1000000000000000000000000000000000000006").unwrap();
+		let address = H160::from_str("1000000000000000000000000000000000000007").unwrap();
+		let _ = <Test as Config>::Currency::deposit_creating(&source, 100);
+
+		assert_ok!(EVM::deposit(Origin::signed(source), address, 40));
+		assert_eq!(Balances::free_balance(&source), 60);
+		assert_eq!(EVM::account_basic(&address).balance, U256::from(40));
+	});
+}
+
+#[test]
+fn deposit_is_weighed() {
+	let call = crate::Call::<Test>::deposit(H160::default(), 40);
+	assert!(call.get_dispatch_info().weight >= DEPOSIT_WEIGHT);
+}
+
+#[test]
+fn suicided_storage_is_removed_in_idle_time() {
+	new_test_ext().execute_with(|| {
+		let address = H160::from_str("1000000000000000000000000000000000000008").unwrap();
+		EVM::create_account(address, vec![0x00]);
+		for index in 0..SUICIDE_REMOVAL_LIMIT + 10 {
+			AccountStorages::<Test>::insert(
+				address,
+				H256::from_low_u64_be(index as u64),
+				H256::repeat_byte(1),
+			);
+		}
+
+		EVM::remove_account(&address);
+		assert_eq!(EVM::pending_removals(), vec![(address, 10)]);
+
+		EVM::remove_suicided_storage(1_000_000);
+		assert!(EVM::pending_removals().is_empty());
+		assert_eq!(AccountStorages::<Test>::iter_prefix(address).count(), 0);
+	});
+}
+
+#[test]
+fn creation_collides_while_removal_is_pending() {
+	new_test_ext().execute_with(|| {
+		let source = H160::default();
+		// PUSH1 0x01 PUSH1 0x00 RETURN
+		let init = vec![0x60, 0x01, 0x60, 0x00, 0xf3];
+		let salt = H256::repeat_byte(0x42);
+		let mut preimage = vec![0xff];
+		preimage.extend_from_slice(source.as_bytes());
+		preimage.extend_from_slice(salt.as_bytes());
+		preimage.extend_from_slice(&sp_io::hashing::keccak_256(&init));
+		let address = H160::from_slice(&sp_io::hashing::keccak_256(&preimage)[12..]);
+		let create2 = || {
+			<Test as Config>::Runner::create2(
+				source,
+				init.clone(),
+				salt,
+				U256::default(),
+				1000000,
+				None,
+				None,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		// A self-destructed contract at the address, whose storage is being removed.
+		AccountStorages::<Test>::insert(address, H256::default(), H256::repeat_byte(1));
+		Suicided::<Test>::insert(address, ());
+
+		let info = create2();
+		assert_eq!(
+			info.exit_reason,
+			ExitReason::Error(ExitError::CreateCollision)
+		);
+		assert!(!AccountCodes::<Test>::contains_key(address));
+		EVM::create_account(address, vec![0x00]);
+		assert!(!AccountCodes::<Test>::contains_key(address));
+
+		EVM::remove_suicided_storage(1_000_000);
+		let info = create2();
+		assert_eq!(info.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(info.value, address);
+		assert_eq!(AccountCodes::<Test>::get(address), vec![0x00]);
+		assert_eq!(AccountStorages::<Test>::iter_prefix(address).count(), 0);
+	});
+}
+
+#[test]
+fn pending_removals_are_bounded() {
+	new_test_ext().execute_with(|| {
+		for i in 0..PENDING_REMOVALS_LIMIT as u64 + 1 {
+			Suicided::<Test>::insert(H160::from_low_u64_be(0x1000 + i), ());
+		}
+		let address = H160::from_low_u64_be(0x1000);
+		for index in 0..SUICIDE_REMOVAL_LIMIT as u64 + 1 {
+			AccountStorages::<Test>::insert(
+				address,
+				H256::from_low_u64_be(index),
+				H256::repeat_byte(1),
+			);
+		}
+
+		let removals = EVM::pending_removals();
+		assert_eq!(removals.len(), PENDING_REMOVALS_LIMIT);
+		let remaining = removals
+			.iter()
+			.find(|(removal, _)| *removal == address)
+			.map(|(_, remaining)| *remaining);
+		assert!(remaining.map_or(true, |remaining| remaining == SUICIDE_REMOVAL_LIMIT));
+	});
+}
+
+#[test]
+fn evm_fork_and_features_follow_config() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(EVM::evm_fork(), "istanbul");
+		assert!(EVM::evm_features().contains(&"chainId"));
+	});
+}
+
+#[test]
+fn access_lists_are_charged_from_berlin() {
+	let access_list = vec![(H160::default(), vec![H256::default(), H256::repeat_byte(1)])];
+	assert!(!fp_evm::supports_access_lists(&evm::Config::istanbul()));
+	assert_eq!(
+		fp_evm::access_list_gas(&evm::Config::istanbul(), &access_list),
+		0
+	);
+	assert!(fp_evm::supports_access_lists(&evm::Config::berlin()));
+	assert_eq!(
+		fp_evm::access_list_gas(&evm::Config::berlin(), &access_list),
+		2_400 + 2 * 1_900
+	);
+}
+
+#[test]
+fn effective_gas_price_is_capped_by_max_fee() {
+	new_test_ext().execute_with(|| {
+		// The mock base fee is zero.
+		let (max_fee, priority_fee) = (U256::from(10), U256::from(3));
+		assert_eq!(
+			EVM::effective_gas_price(Some(max_fee), Some(priority_fee)),
+			Some(priority_fee)
+		);
+		assert_eq!(
+			EVM::effective_gas_price(Some(max_fee), Some(U256::from(20))),
+			Some(max_fee)
+		);
+		assert_eq!(EVM::effective_gas_price(Some(max_fee), None), Some(max_fee));
+		assert_eq!(
+			EVM::effective_gas_price(None, Some(priority_fee)),
+			Some(priority_fee)
+		);
+		assert_eq!(EVM::effective_gas_price(None, None), None);
+	});
+}
+
+#[test]
+fn access_list_gas_rejects_lists_before_berlin() {
+	let access_list = vec![(H160::default(), vec![H256::default()])];
+	assert!(EVM::access_list_gas(&evm::Config::istanbul(), &access_list).is_err());
+	assert_eq!(EVM::access_list_gas(&evm::Config::istanbul(), &[]), Ok(0));
+	assert_eq!(
+		EVM::access_list_gas(&evm::Config::berlin(), &access_list),
+		Ok(2_400 + 1_900)
+	);
+}
+
+struct Echo;
+
+impl LinearCostPrecompile for Echo {
+	const BASE: u64 = 15;
+	const WORD: u64 = 3;
+
+	fn execute(input: &[u8], _: u64) -> Result<(evm::ExitSucceed, Vec<u8>), ExitError> {
+		Ok((evm::ExitSucceed::Returned, input.to_vec()))
+	}
+}
+
+struct EchoWeights;
+
+impl PrecompileWeightInfo for EchoWeights {
+	fn base() -> Weight {
+		1_000
+	}
+	fn per_word() -> Weight {
+		100
+	}
+}
+
+#[test]
+fn benchmarked_precompile_costs_follow_the_weights() {
+	type BenchmarkedEcho = BenchmarkedPrecompile<Echo, EchoWeights, ()>;
+	let context = Context {
+		address: H160::default(),
+		caller: H160::default(),
+		apparent_value: U256::zero(),
+	};
+	let input = [1u8; 33];
+
+	let output = BenchmarkedEcho::execute(&input, None, &context).unwrap();
+	assert_eq!(output.cost, 1_000 + 2 * 100);
+	assert_eq!(output.output, input.to_vec());
+	assert!(matches!(
+		BenchmarkedEcho::execute(&input, Some(1_199), &context),
+		Err(ExitError::OutOfGas)
+	));
+}
+
+thread_local! {
+	static ASSET_BALANCES: std::cell::RefCell<BTreeMap<(u32, AccountId32), u64>> =
+		Default::default();
+}
+
+/// Assets held in memory, standing in for pallet-assets.
+struct Assets;
+
+impl Assets {
+	fn set_balance(asset: u32, who: &AccountId32, amount: u64) {
+		ASSET_BALANCES.with(|balances| {
+			balances.borrow_mut().insert((asset, who.clone()), amount);
+		});
+	}
+}
+
+impl fungibles::Inspect<AccountId32> for Assets {
+	type AssetId = u32;
+	type Balance = u64;
+
+	fn total_issuance(asset: u32) -> u64 {
+		ASSET_BALANCES.with(|balances| {
+			balances
+				.borrow()
+				.iter()
+				.filter(|((id, _), _)| *id == asset)
+				.map(|(_, amount)| amount)
+				.sum()
+		})
+	}
+	fn minimum_balance(_asset: u32) -> u64 {
+		0
+	}
+	fn balance(asset: u32, who: &AccountId32) -> u64 {
+		ASSET_BALANCES.with(|balances| {
+			balances
+				.borrow()
+				.get(&(asset, who.clone()))
+				.copied()
+				.unwrap_or_default()
+		})
+	}
+	fn reducible_balance(asset: u32, who: &AccountId32, _keep_alive: bool) -> u64 {
+		Self::balance(asset, who)
+	}
+	fn can_deposit(_asset: u32, _who: &AccountId32, _amount: u64) -> DepositConsequence {
+		DepositConsequence::Success
+	}
+	fn can_withdraw(asset: u32, who: &AccountId32, amount: u64) -> WithdrawConsequence<u64> {
+		if Self::balance(asset, who) >= amount {
+			WithdrawConsequence::Success
+		} else {
+			WithdrawConsequence::NoFunds
+		}
+	}
+}
+
+impl fungibles::Transfer<AccountId32> for Assets {
+	fn transfer(
+		asset: u32,
+		source: &AccountId32,
+		dest: &AccountId32,
+		amount: u64,
+		_keep_alive: bool,
+	) -> Result<u64, DispatchError> {
+		let source_balance = Self::balance(asset, source)
+			.checked_sub(amount)
+			.ok_or(DispatchError::Other("insufficient asset balance"))?;
+		Self::set_balance(asset, source, source_balance);
+		Self::set_balance(asset, dest, Self::balance(asset, dest) + amount);
+		Ok(amount)
+	}
+}
+
+const FEE_TOKEN: u32 = 7;
+
+/// Charges the calls of `1000000000000000000000000000000000000003` in `FEE_TOKEN`, at 2 units
+/// of token per unit of native currency.
+struct FeeTokenPayers;
+
+impl FeeTokenOracle<u32> for FeeTokenPayers {
+	fn fee_token(who: &H160, _target: Option<H160>) -> Option<(u32, FixedU128)> {
+		if *who == H160::from_str("1000000000000000000000000000000000000003").unwrap() {
+			Some((FEE_TOKEN, FixedU128::saturating_from_integer(2)))
+		} else {
+			None
+		}
+	}
+}
+
+parameter_types! {
+	pub FeeCollector: AccountId32 = AccountId32::new([9u8; 32]);
+}
+
+type FeeTokenAdapter = EVMFeeTokenAdapter<Balances, (), Assets, FeeTokenPayers, FeeCollector>;
+
+#[test]
+fn fee_token_pays_the_fees_of_the_selected_senders() {
+	new_test_ext().execute_with(|| {
+		let payer = H160::from_str("1000000000000000000000000000000000000003").unwrap();
+		let payer_account = <Test as Config>::AddressMapping::into_account_id(payer);
+		Assets::set_balance(FEE_TOKEN, &payer_account, 100);
+
+		// The fee token pays the fee, the native balance does not need to cover it.
+		assert_eq!(
+			<FeeTokenAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&payer,
+				None,
+				U256::from(10)
+			)
+			.ok(),
+			Some(U256::zero())
+		);
+		assert!(
+			<FeeTokenAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&payer,
+				None,
+				U256::from(60)
+			)
+			.is_err()
+		);
+
+		// Withdraws 20 units of token for a fee of 10, then refunds 10 for a fee of 5.
+		let paid = <FeeTokenAdapter as OnChargeEVMTransaction<Test>>::withdraw_fee_for(
+			&payer,
+			None,
+			U256::from(10),
+		)
+		.unwrap();
+		assert_eq!(Assets::balance(FEE_TOKEN, &payer_account), 80);
+		assert_eq!(Assets::balance(FEE_TOKEN, &FeeCollector::get()), 20);
+		<FeeTokenAdapter as OnChargeEVMTransaction<Test>>::correct_and_deposit_fee(
+			&payer,
+			U256::from(5),
+			paid,
+		);
+		assert_eq!(Assets::balance(FEE_TOKEN, &payer_account), 90);
+		assert_eq!(Assets::balance(FEE_TOKEN, &FeeCollector::get()), 10);
+
+		// The other senders pay in the native currency.
+		let sender = H160::from_str("1000000000000000000000000000000000000004").unwrap();
+		let sender_account = <Test as Config>::AddressMapping::into_account_id(sender);
+		let _ = <Test as Config>::Currency::deposit_creating(&sender_account, 100);
+		assert_eq!(
+			<FeeTokenAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&sender,
+				None,
+				U256::from(10)
+			)
+			.ok(),
+			Some(U256::from(10))
+		);
+		let paid = <FeeTokenAdapter as OnChargeEVMTransaction<Test>>::withdraw_fee_for(
+			&sender,
+			None,
+			U256::from(10),
+		)
+		.unwrap();
+		assert_eq!(Balances::free_balance(&sender_account), 90);
+		<FeeTokenAdapter as OnChargeEVMTransaction<Test>>::correct_and_deposit_fee(
+			&sender,
+			U256::from(5),
+			paid,
+		);
+		assert_eq!(Balances::free_balance(&sender_account), 95);
+	});
+}
+
+#[test]
+fn fee_token_rejects_fees_overflowing_u128() {
+	new_test_ext().execute_with(|| {
+		let payer = H160::from_str("1000000000000000000000000000000000000003").unwrap();
+		let payer_account = <Test as Config>::AddressMapping::into_account_id(payer);
+		Assets::set_balance(FEE_TOKEN, &payer_account, 100);
+
+		// The fee of a huge gas price, above `u128` with low 128 bits of 10.
+		let fee = U256::from(u128::max_value()) + U256::from(11);
+		assert!(
+			<FeeTokenAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&payer, None, fee
+			)
+			.is_err()
+		);
+		assert!(
+			<FeeTokenAdapter as OnChargeEVMTransaction<Test>>::withdraw_fee_for(&payer, None, fee)
+				.is_err()
+		);
+		assert_eq!(Assets::balance(FEE_TOKEN, &payer_account), 100);
+	});
+}
+
+type SponsoredAdapter = crate::sponsor::EVMSponsoredAdapter<()>;
+
+#[test]
+fn sponsor_pays_the_fees_of_its_beneficiaries() {
+	new_test_ext().execute_with(|| {
+		let sponsor = H160::from_str("1000000000000000000000000000000000000005").unwrap();
+		let sponsor_account = <Test as Config>::AddressMapping::into_account_id(sponsor);
+		let _ = <Test as Config>::Currency::deposit_creating(&sponsor_account, 100);
+		let user = H160::from_str("1000000000000000000000000000000000000006").unwrap();
+		let contract = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+
+		// The offer sponsors nothing until the contract accepts it.
+		assert_ok!(EVM::set_sponsorship(
+			Origin::root(),
+			sponsor,
+			contract,
+			U256::from(30),
+			U256::from(20)
+		));
+		assert_eq!(EVM::sponsorship(contract), None);
+		assert_eq!(
+			EVM::fee_sponsor(&user, Some(contract), U256::from(10)),
+			None
+		);
+		assert_noop!(
+			EVM::accept_sponsorship(Origin::root(), contract, user),
+			Error::<Test>::NoSponsorshipOffer
+		);
+		assert_ok!(EVM::accept_sponsorship(Origin::root(), contract, sponsor));
+		assert_eq!(
+			EVM::sponsorship(contract),
+			Some((sponsor, U256::from(30), U256::from(20)))
+		);
+		assert_eq!(EVM::sponsorship_offer(contract, sponsor), None);
+
+		// The calls to the contract are paid by the sponsor, the user has no balance.
+		assert_eq!(
+			<SponsoredAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&user,
+				Some(contract),
+				U256::from(10)
+			)
+			.ok(),
+			Some(U256::zero())
+		);
+		let paid = <SponsoredAdapter as OnChargeEVMTransaction<Test>>::withdraw_fee_for(
+			&user,
+			Some(contract),
+			U256::from(10),
+		)
+		.unwrap();
+		assert_eq!(Balances::free_balance(&sponsor_account), 90);
+		assert_eq!(
+			EVM::sponsorship(contract),
+			Some((sponsor, U256::from(20), U256::from(20)))
+		);
+		<SponsoredAdapter as OnChargeEVMTransaction<Test>>::correct_and_deposit_fee(
+			&user,
+			U256::from(5),
+			paid,
+		);
+		assert_eq!(Balances::free_balance(&sponsor_account), 95);
+		assert_eq!(
+			EVM::sponsorship(contract),
+			Some((sponsor, U256::from(25), U256::from(20)))
+		);
+		assert_eq!(EVM::fee_sponsors(), vec![(user, U256::zero(), sponsor)]);
+
+		// Beyond the maximum fee, beyond the allowance, and for the other targets, the user pays.
+		assert_eq!(
+			<SponsoredAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&user,
+				Some(contract),
+				U256::from(21)
+			)
+			.ok(),
+			Some(U256::from(21))
+		);
+		assert_eq!(
+			<SponsoredAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&user,
+				Some(contract),
+				U256::from(30)
+			)
+			.ok(),
+			Some(U256::from(30))
+		);
+		assert_eq!(
+			<SponsoredAdapter as OnChargeEVMTransaction<Test>>::ensure_fee_payable(
+				&user,
+				None,
+				U256::from(10)
+			)
+			.ok(),
+			Some(U256::from(10))
+		);
+
+		// The sponsor updates its sponsorship, a zero allowance ends it.
+		assert_ok!(EVM::set_sponsorship(
+			Origin::root(),
+			sponsor,
+			contract,
+			U256::from(40),
+			U256::from(20)
+		));
+		assert_eq!(
+			EVM::sponsorship(contract),
+			Some((sponsor, U256::from(40), U256::from(20)))
+		);
+		assert_ok!(EVM::set_sponsorship(
+			Origin::root(),
+			sponsor,
+			contract,
+			U256::zero(),
+			U256::zero()
+		));
+		assert_eq!(EVM::sponsorship(contract), None);
+	});
+}
+
+#[test]
+fn sponsorship_is_removed_by_its_beneficiary_or_when_spent() {
+	new_test_ext().execute_with(|| {
+		let sponsor = H160::from_str("1000000000000000000000000000000000000005").unwrap();
+		let sponsor_account = <Test as Config>::AddressMapping::into_account_id(sponsor);
+		let _ = <Test as Config>::Currency::deposit_creating(&sponsor_account, 100);
+		let user = H160::from_str("1000000000000000000000000000000000000006").unwrap();
+
+		assert_noop!(
+			EVM::remove_sponsorship(Origin::root(), user),
+			Error::<Test>::NotSponsored
+		);
+		assert_ok!(EVM::set_sponsorship(
+			Origin::root(),
+			sponsor,
+			user,
+			U256::from(10),
+			U256::from(10)
+		));
+		assert_ok!(EVM::accept_sponsorship(Origin::root(), user, sponsor));
+		assert_ok!(EVM::remove_sponsorship(Origin::root(), user));
+		assert_eq!(EVM::sponsorship(user), None);
+
+		// The sponsorship ends when its allowance is spent.
+		assert_ok!(EVM::set_sponsorship(
+			Origin::root(),
+			sponsor,
+			user,
+			U256::from(10),
+			U256::from(10)
+		));
+		assert_ok!(EVM::accept_sponsorship(Origin::root(), user, sponsor));
+		let paid = <SponsoredAdapter as OnChargeEVMTransaction<Test>>::withdraw_fee_for(
+			&user,
+			None,
+			U256::from(10),
+		)
+		.unwrap();
+		<SponsoredAdapter as OnChargeEVMTransaction<Test>>::correct_and_deposit_fee(
+			&user,
+			U256::from(10),
+			paid,
+		);
+		assert_eq!(Balances::free_balance(&sponsor_account), 90);
+		assert_eq!(EVM::sponsorship(user), None);
+	});
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn trace_call_collects_the_access_list_and_the_bundler_trace() {
+	new_test_ext().execute_with(|| {
+		let source = H160::from_str("1000000000000000000000000000000000000007").unwrap();
+		let entry_point = H160::from_str("1000000000000000000000000000000000000008").unwrap();
+		let account = H160::from_str("1000000000000000000000000000000000000009").unwrap();
+
+		// Calls `account` with the method 0x12345678.
+		let mut code = vec![
+			0x63, 0x12, 0x34, 0x56, 0x78, // PUSH4 0x12345678
+			0x60, 0x00, // PUSH1 0
+			0x52, // MSTORE
+			0x60, 0x00, // PUSH1 0
+			0x60, 0x00, // PUSH1 0
+			0x60, 0x04, // PUSH1 4
+			0x60, 0x1c, // PUSH1 28
+			0x73, // PUSH20
+		];
+		code.extend_from_slice(account.as_bytes());
+		code.extend_from_slice(&[
+			0x5a, // GAS
+			0xfa, // STATICCALL
+			0x50, // POP
+			0x00, // STOP
+		]);
+		EVM::create_account(entry_point, code);
+		// Reads the slot 5 and hashes 32 bytes.
+		EVM::create_account(
+			account,
+			vec![
+				0x60, 0x05, // PUSH1 5
+				0x54, // SLOAD
+				0x50, // POP
+				0x60, 0x20, // PUSH1 32
+				0x60, 0x00, // PUSH1 0
+				0x20, // SHA3
+				0x50, // POP
+				0x00, // STOP
+			],
+		);
+
+		let trace = EVM::trace_call(
+			source,
+			Some(entry_point),
+			Vec::new(),
+			U256::zero(),
+			U256::from(1_000_000),
+			None,
+			None,
+			None,
+			Vec::new(),
+		)
+		.unwrap();
+		assert_eq!(trace.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(
+			trace.access_list,
+			vec![(account, vec![H256::from_low_u64_be(5)])]
+		);
+
+		let bundler = trace.bundler;
+		assert_eq!(bundler.calls_from_entry_point.len(), 1);
+		let level = &bundler.calls_from_entry_point[0];
+		assert_eq!(level.method_sig, vec![0x12, 0x34, 0x56, 0x78]);
+		assert_eq!(level.target, account);
+		assert_eq!(level.opcodes.get(&0x54), Some(&1));
+		assert_eq!(level.opcodes.get(&0x20), Some(&1));
+		assert_eq!(level.opcodes.get(&0x60), None);
+		assert_eq!(
+			level
+				.access
+				.get(&account)
+				.map(|access| access.reads.clone()),
+			Some(
+				vec![(H256::from_low_u64_be(5), H256::zero())]
+					.into_iter()
+					.collect()
+			)
+		);
+		assert!(!level.oog);
+		assert_eq!(bundler.keccak, vec![vec![0u8; 32]]);
+		assert!(matches!(
+			&bundler.calls[..],
+			[
+				CallFrame::Enter { opcode: 0xfa, from, to, method, .. },
+				CallFrame::Exit { reverted: false, .. },
+			] if *from == entry_point && *to == account && method == &vec![0x12, 0x34, 0x56, 0x78]
+		));
+	});
+}
+
+#[test]
+fn prefixed_address_mappings_are_distinct() {
+	parameter_types! {
+		pub const EvmPrefix: [u8; 4] = *b"evm:";
+		pub const SecondPrefix: [u8; 4] = *b"ev2:";
+	}
+	let address = H160::repeat_byte(0x11);
+	let default = <HashedAddressMapping<BlakeTwo256>>::into_account_id(address);
+
+	assert_eq!(
+		<PrefixedHashedAddressMapping<BlakeTwo256, EvmPrefix>>::into_account_id(address),
+		default
+	);
+	assert_ne!(
+		<PrefixedHashedAddressMapping<BlakeTwo256, SecondPrefix>>::into_account_id(address),
+		default
+	);
+}
+
+#[test]
+fn precompile_calls_are_added_once_per_transaction() {
+	new_test_ext().execute_with(|| {
+		let first = H160::from_low_u64_be(1);
+		let second = H160::from_low_u64_be(2);
+
+		// Calls outside of a counting runner are not counted.
+		precompile_call_counts::with(|calls| calls.push((first, 1)));
+
+		let mut transaction_calls = Vec::new();
+		precompile_call_counts::using(&mut transaction_calls, || {
+			for address in [first, second, first] {
+				precompile_call_counts::with(|calls| add_precompile_calls(calls, address, 1));
+			}
+		});
+		assert_eq!(transaction_calls, vec![(first, 2), (second, 1)]);
+
+		EVM::record_precompile_calls(transaction_calls);
+		EVM::record_precompile_calls(vec![(second, 3)]);
+		EVM::record_precompile_calls(Vec::new());
+		assert_eq!(EVM::precompile_calls(), vec![(first, 2), (second, 4)]);
+	});
+}
@@ -0,0 +1,527 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracing of the executions of the runner, through the tracing events of the EVM.
+//!
+//! [`trace`] collects the access list of an execution, for `eth_createAccessList`, and the data
+//! of the bundler collector tracer of geth, for `debug_traceCall`. The latter follows the
+//! `bundlerCollectorTracer` of the ERC-4337 reference bundler: the steps of the EntryPoint, at
+//! depth 1, start a new top level call at each CALL or STATICCALL, and the collection stops at
+//! its `BeforeExecution` event, once the user operations are validated.
+//!
+//! [`record_transfers`] only listens to the calls and exits, collecting the value transfers
+//! below the top level call of an execution, the internal transactions of the explorers.
+
+use crate::{Config, Pallet};
+use evm::{CreateScheme, ExitError, ExitReason, Opcode, Transfer};
+use fp_evm::{BundlerTrace, CallFrame, CallTrace, PrecompileSet, TopLevelCall, TraceLog};
+use sp_core::{H160, H256, U256};
+use sp_std::{
+	cell::RefCell,
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	marker::PhantomData,
+	rc::Rc,
+	vec::Vec,
+};
+
+/// Topic of the `BeforeExecution()` event of the EntryPoint.
+const BEFORE_EXECUTION_TOPIC: [u8; 32] = [
+	0xbb, 0x47, 0xee, 0x3e, 0x18, 0x3a, 0x55, 0x8b, 0x1a, 0x2f, 0xf0, 0x87, 0x4b, 0x07, 0x9f, 0x3f,
+	0xc5, 0x47, 0x8b, 0x74, 0x54, 0xea, 0xcf, 0x2b, 0xfc, 0x5a, 0xf2, 0xff, 0x58, 0x78, 0xf9, 0x72,
+];
+
+/// Maximum length of the returned data of the call exits, as the tracer truncates their hex
+/// encoding to 4000 characters.
+const MAX_EXIT_DATA: usize = 1999;
+
+/// Data collected while tracing an execution.
+pub struct Tracer<T, I> {
+	precompiles: Vec<H160>,
+	depth: usize,
+	/// Gas used by the call of each depth.
+	used_gas: Vec<u64>,
+	/// Set by the `BeforeExecution` event of the EntryPoint.
+	stop_collecting: bool,
+	/// Previous opcode executed below depth 1.
+	last_op: Option<Opcode>,
+	/// Previous EXTCODE* opcode executed below depth 1, with the accessed address.
+	last_ext: Option<(Opcode, H160)>,
+	accessed: BTreeMap<H160, BTreeSet<H256>>,
+	bundler: BundlerTrace,
+	_marker: PhantomData<(T, I)>,
+}
+
+impl<T: Config<I>, I: 'static> Tracer<T, I> {
+	fn new() -> Self {
+		Self {
+			precompiles: T::Precompiles::addresses(),
+			depth: 0,
+			used_gas: Vec::new(),
+			stop_collecting: false,
+			last_op: None,
+			last_ext: None,
+			accessed: BTreeMap::new(),
+			bundler: BundlerTrace::default(),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Builds the trace of the execution, whose access list leaves out the `excluded`
+	/// addresses and the precompiles.
+	pub fn finish(
+		self,
+		exit_reason: ExitReason,
+		output: Vec<u8>,
+		used_gas: U256,
+		excluded: &[H160],
+	) -> CallTrace {
+		let precompiles = self.precompiles;
+		CallTrace {
+			exit_reason,
+			output,
+			used_gas,
+			access_list: self
+				.accessed
+				.into_iter()
+				.filter(|(address, _)| {
+					!excluded.contains(address) && !precompiles.contains(address)
+				})
+				.map(|(address, keys)| (address, keys.into_iter().collect()))
+				.collect(),
+			bundler: self.bundler,
+		}
+	}
+
+	fn current_level(&mut self) -> Option<&mut TopLevelCall> {
+		self.bundler.calls_from_entry_point.last_mut()
+	}
+
+	fn on_evm_event(&mut self, event: evm::tracing::Event) {
+		match event {
+			evm::tracing::Event::Call {
+				code_address,
+				input,
+				target_gas,
+				is_static,
+				context,
+				..
+			} => {
+				let opcode = if is_static {
+					Opcode::STATICCALL
+				} else if context.address != code_address {
+					Opcode::DELEGATECALL
+				} else {
+					Opcode::CALL
+				};
+				self.enter(
+					opcode,
+					context.caller,
+					code_address,
+					input,
+					context.apparent_value,
+					target_gas,
+				);
+			}
+			evm::tracing::Event::Create {
+				caller,
+				address,
+				scheme,
+				value,
+				init_code,
+				target_gas,
+			} => {
+				let opcode = match scheme {
+					CreateScheme::Create2 { .. } => Opcode::CREATE2,
+					_ => Opcode::CREATE,
+				};
+				self.enter(opcode, caller, address, init_code, value, target_gas);
+			}
+			evm::tracing::Event::Exit {
+				reason,
+				return_value,
+			} => {
+				let gas_used = self.used_gas.pop().unwrap_or_default();
+				if matches!(reason, ExitReason::Error(ExitError::OutOfGas)) {
+					if let Some(level) = self.current_level() {
+						level.oog = true;
+					}
+				}
+				if self.depth > 1 && !self.stop_collecting {
+					let data = &return_value[..return_value.len().min(MAX_EXIT_DATA)];
+					self.bundler.calls.push(CallFrame::Exit {
+						reverted: !matches!(reason, ExitReason::Succeed(_)),
+						gas_used,
+						data: data.to_vec(),
+					});
+				}
+				self.depth = self.depth.saturating_sub(1);
+			}
+			_ => (),
+		}
+	}
+
+	fn enter(
+		&mut self,
+		opcode: Opcode,
+		from: H160,
+		to: H160,
+		input: &[u8],
+		value: U256,
+		gas: Option<u64>,
+	) {
+		if self.depth > 0 && !self.stop_collecting {
+			self.bundler.calls.push(CallFrame::Enter {
+				opcode: opcode.as_u8(),
+				from,
+				to,
+				method: input[..input.len().min(4)].to_vec(),
+				value,
+				gas: gas.unwrap_or_default(),
+			});
+		}
+		self.depth += 1;
+		self.used_gas.push(0);
+	}
+
+	fn on_runtime_event(&mut self, event: evm_runtime::tracing::Event) {
+		match event {
+			evm_runtime::tracing::Event::Step {
+				context,
+				opcode,
+				stack,
+				memory,
+				..
+			} => {
+				let peek = |n: usize| stack.peek(n).unwrap_or_default();
+				self.record_access(context.address, opcode, &peek);
+				if !self.stop_collecting {
+					self.step(opcode, &peek, memory.data());
+				}
+			}
+			evm_runtime::tracing::Event::SLoad {
+				address,
+				index,
+				value,
+			} if self.depth > 1 && !self.stop_collecting => {
+				if let Some(level) = self.current_level() {
+					let access = level.access.entry(address).or_default();
+					if !access.writes.contains_key(&index) {
+						access.reads.entry(index).or_insert(value);
+					}
+				}
+			}
+			evm_runtime::tracing::Event::SStore { address, index, .. }
+				if self.depth > 1 && !self.stop_collecting =>
+			{
+				if let Some(level) = self.current_level() {
+					let access = level.access.entry(address).or_default();
+					*access.writes.entry(index).or_default() += 1;
+				}
+			}
+			_ => (),
+		}
+	}
+
+	fn on_gasometer_event(&mut self, event: evm_gasometer::tracing::Event) {
+		use evm_gasometer::tracing::Event;
+
+		let used_gas = match event {
+			Event::RecordCost {
+				cost,
+				snapshot: Some(snapshot),
+			} => snapshot
+				.used_gas
+				.saturating_add(snapshot.memory_gas)
+				.saturating_add(cost),
+			Event::RecordDynamicCost {
+				gas_cost,
+				memory_gas,
+				snapshot: Some(snapshot),
+				..
+			} => snapshot
+				.used_gas
+				.saturating_add(snapshot.memory_gas.max(memory_gas))
+				.saturating_add(gas_cost),
+			Event::RecordRefund {
+				snapshot: Some(snapshot),
+				..
+			}
+			| Event::RecordStipend {
+				snapshot: Some(snapshot),
+				..
+			}
+			| Event::RecordTransaction {
+				snapshot: Some(snapshot),
+				..
+			} => snapshot.used_gas.saturating_add(snapshot.memory_gas),
+			_ => return,
+		};
+		if let Some(current) = self.used_gas.last_mut() {
+			*current = used_gas;
+		}
+	}
+
+	/// Records the addresses and storage keys accessed by `opcode` in the access list.
+	fn record_access(&mut self, address: H160, opcode: Opcode, peek: &dyn Fn(usize) -> H256) {
+		match opcode {
+			Opcode::SLOAD | Opcode::SSTORE => {
+				self.accessed.entry(address).or_default().insert(peek(0));
+			}
+			Opcode::EXTCODESIZE
+			| Opcode::EXTCODECOPY
+			| Opcode::EXTCODEHASH
+			| Opcode::BALANCE
+			| Opcode::SUICIDE => {
+				self.accessed.entry(peek(0).into()).or_default();
+			}
+			Opcode::CALL | Opcode::CALLCODE | Opcode::DELEGATECALL | Opcode::STATICCALL => {
+				self.accessed.entry(peek(1).into()).or_default();
+			}
+			_ => (),
+		}
+	}
+
+	/// Step of the bundler collector tracer.
+	fn step(&mut self, opcode: Opcode, peek: &dyn Fn(usize) -> H256, memory: &[u8]) {
+		if self.depth == 1 {
+			match opcode {
+				Opcode::CALL | Opcode::STATICCALL => {
+					let offset = peek(if opcode == Opcode::CALL { 3 } else { 2 });
+					self.bundler.calls_from_entry_point.push(TopLevelCall {
+						method_sig: memory_slice(memory, offset, 4),
+						target: peek(1).into(),
+						..Default::default()
+					});
+				}
+				Opcode::LOG1 if peek(2) == H256(BEFORE_EXECUTION_TOPIC) => {
+					self.stop_collecting = true;
+				}
+				_ => (),
+			}
+			self.last_op = None;
+			return;
+		}
+
+		let last_op = self.last_op.replace(opcode);
+		let last_ext = self.last_ext.take();
+		let precompiles = &self.precompiles;
+		if let Some(level) = self.bundler.calls_from_entry_point.last_mut() {
+			// GAS is only counted when it does not give the gas of a call.
+			if last_op == Some(Opcode::GAS) && !is_call(opcode) {
+				*level.opcodes.entry(Opcode::GAS.as_u8()).or_default() += 1;
+			}
+			if opcode != Opcode::GAS && !is_ignored(opcode) {
+				*level.opcodes.entry(opcode.as_u8()).or_default() += 1;
+			}
+
+			if let Some((ext, address)) = last_ext {
+				if !(ext == Opcode::EXTCODESIZE && opcode == Opcode::ISZERO) {
+					level.ext_code_access_info.insert(address, opcode.as_u8());
+				}
+			}
+
+			let accessed = if is_ext_code(opcode) {
+				Some(peek(0).into())
+			} else if is_call(opcode) {
+				Some(peek(1).into())
+			} else {
+				None
+			};
+			if let Some(address) = accessed {
+				if is_ext_code(opcode) {
+					self.last_ext = Some((opcode, address));
+				}
+				if !precompiles.contains(&address) && !level.contract_size.contains_key(&address) {
+					let size = Pallet::<T, I>::account_code_metadata(address).size;
+					level.contract_size.insert(address, (size, opcode.as_u8()));
+				}
+			}
+		}
+
+		match opcode {
+			Opcode::SHA3 => {
+				let len = U256::from_big_endian(peek(1).as_bytes());
+				if len > U256::from(20) && len < U256::from(512) {
+					self.bundler
+						.keccak
+						.push(memory_slice(memory, peek(0), len.as_usize()));
+				}
+			}
+			Opcode::LOG0 | Opcode::LOG1 | Opcode::LOG2 | Opcode::LOG3 | Opcode::LOG4 => {
+				let count = (opcode.as_u8() - Opcode::LOG0.as_u8()) as usize;
+				// Beyond the memory the data is zero, and the log runs out of gas.
+				let len = U256::from_big_endian(peek(1).as_bytes())
+					.min(U256::from(memory.len()))
+					.as_usize();
+				self.bundler.logs.push(TraceLog {
+					topics: (0..count).map(|i| peek(2 + i)).collect(),
+					data: memory_slice(memory, peek(0), len),
+				});
+			}
+			_ => (),
+		}
+	}
+}
+
+fn is_call(opcode: Opcode) -> bool {
+	matches!(
+		opcode,
+		Opcode::CALL | Opcode::CALLCODE | Opcode::DELEGATECALL | Opcode::STATICCALL
+	)
+}
+
+fn is_ext_code(opcode: Opcode) -> bool {
+	matches!(
+		opcode,
+		Opcode::EXTCODESIZE | Opcode::EXTCODECOPY | Opcode::EXTCODEHASH
+	)
+}
+
+/// Stack, arithmetic and comparison opcodes, which the tracer does not count.
+fn is_ignored(opcode: Opcode) -> bool {
+	matches!(
+		opcode.as_u8(),
+		0x01..=0x04 | 0x10..=0x17 | 0x19 | 0x1b | 0x1c | 0x50 | 0x60..=0x9f
+	)
+}
+
+/// `len` bytes of `memory` from `offset`, zero beyond the memory.
+fn memory_slice(memory: &[u8], offset: H256, len: usize) -> Vec<u8> {
+	let mut slice = sp_std::vec![0u8; len];
+	let offset = U256::from_big_endian(offset.as_bytes());
+	if offset < U256::from(memory.len()) {
+		let offset = offset.as_usize();
+		let available = &memory[offset..memory.len().min(offset.saturating_add(len))];
+		slice[..available.len()].copy_from_slice(available);
+	}
+	slice
+}
+
+struct EvmListener<T, I>(Rc<RefCell<Tracer<T, I>>>);
+
+impl<T: Config<I>, I: 'static> evm::tracing::EventListener for EvmListener<T, I> {
+	fn event(&mut self, event: evm::tracing::Event) {
+		self.0.borrow_mut().on_evm_event(event);
+	}
+}
+
+struct RuntimeListener<T, I>(Rc<RefCell<Tracer<T, I>>>);
+
+impl<T: Config<I>, I: 'static> evm_runtime::tracing::EventListener for RuntimeListener<T, I> {
+	fn event(&mut self, event: evm_runtime::tracing::Event) {
+		self.0.borrow_mut().on_runtime_event(event);
+	}
+}
+
+struct GasometerListener<T, I>(Rc<RefCell<Tracer<T, I>>>);
+
+impl<T: Config<I>, I: 'static> evm_gasometer::tracing::EventListener for GasometerListener<T, I> {
+	fn event(&mut self, event: evm_gasometer::tracing::Event) {
+		self.0.borrow_mut().on_gasometer_event(event);
+	}
+}
+
+/// Runs `f`, an execution of the runner, and returns its result with the data collected.
+pub fn trace<T: Config<I>, I: 'static, R>(f: impl FnOnce() -> R) -> (R, Tracer<T, I>) {
+	let tracer = Rc::new(RefCell::new(Tracer::new()));
+	let result = {
+		let mut evm_listener = EvmListener(tracer.clone());
+		let mut runtime_listener = RuntimeListener(tracer.clone());
+		let mut gasometer_listener = GasometerListener(tracer.clone());
+		evm::tracing::using(&mut evm_listener, || {
+			evm_runtime::tracing::using(&mut runtime_listener, || {
+				evm_gasometer::tracing::using(&mut gasometer_listener, f)
+			})
+		})
+	};
+	let tracer = match Rc::try_unwrap(tracer) {
+		Ok(tracer) => tracer.into_inner(),
+		Err(_) => unreachable!("the listeners are dropped; qed"),
+	};
+	(result, tracer)
+}
+
+/// Value transfers of the calls, contract creations and self-destructs below the top level call
+/// of an execution. The transfers of a reverted frame are dropped with those of its subcalls.
+#[derive(Default)]
+struct TransferRecorder {
+	/// Transfers of the frames being executed, by depth.
+	frames: Vec<Vec<Transfer>>,
+	transfers: Vec<Transfer>,
+}
+
+impl TransferRecorder {
+	fn enter(&mut self, transfer: Option<Transfer>) {
+		let mut frame = Vec::new();
+		// The transfer of the top level call is the one of the transaction.
+		if !self.frames.is_empty() {
+			frame.extend(transfer.filter(|transfer| !transfer.value.is_zero()));
+		}
+		self.frames.push(frame);
+	}
+}
+
+impl evm::tracing::EventListener for TransferRecorder {
+	fn event(&mut self, event: evm::tracing::Event) {
+		match event {
+			evm::tracing::Event::Call { transfer, .. } => self.enter(transfer.clone()),
+			evm::tracing::Event::Create {
+				caller,
+				address,
+				value,
+				..
+			} => self.enter(Some(Transfer {
+				source: caller,
+				target: address,
+				value,
+			})),
+			evm::tracing::Event::Suicide {
+				address,
+				target,
+				balance,
+			} => {
+				if let Some(frame) = self.frames.last_mut() {
+					if !balance.is_zero() {
+						frame.push(Transfer {
+							source: address,
+							target,
+							value: balance,
+						});
+					}
+				}
+			}
+			evm::tracing::Event::Exit { reason, .. } => {
+				let frame = self.frames.pop().unwrap_or_default();
+				if matches!(reason, ExitReason::Succeed(_)) {
+					match self.frames.last_mut() {
+						Some(parent) => parent.extend(frame),
+						None => self.transfers.extend(frame),
+					}
+				}
+			}
+			_ => (),
+		}
+	}
+}
+
+/// Runs `f`, an execution of the runner, and returns its result with the value transfers below
+/// its top level call which were not reverted, in execution order.
+pub fn record_transfers<R>(f: impl FnOnce() -> R) -> (R, Vec<Transfer>) {
+	let mut recorder = TransferRecorder::default();
+	let result = evm::tracing::using(&mut recorder, f);
+	(result, recorder.transfers)
+}
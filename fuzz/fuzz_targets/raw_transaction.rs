@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `eth_sendRawTransaction` payloads: RLP decoding, validation by the runtime and conversion
+//! to the RPC representation must never panic, whatever the bytes.
+
+#![no_main]
+
+use ethereum::TransactionV0;
+use ethereum_types::{H160, H512};
+use fc_rpc_core::types::{Transaction, TransactionBuilder};
+use frontier_template_runtime::Runtime;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	// Typed transaction envelopes are rejected before decoding, as by `eth_sendRawTransaction`.
+	if data.first().map_or(true, |byte| *byte <= 0x7f) {
+		return;
+	}
+	let transaction = match rlp::decode::<TransactionV0>(data) {
+		Ok(transaction) => transaction,
+		Err(_) => return,
+	};
+
+	let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default()
+		.build_storage::<Runtime>()
+		.unwrap()
+		.into();
+	ext.execute_with(|| {
+		let call = pallet_ethereum::Call::<Runtime>::transact(transaction.clone());
+		if let Some(Ok(origin)) = call.check_self_contained() {
+			let _ = call.validate_self_contained(&origin);
+			let _ = call.pre_dispatch_self_contained(&origin);
+		}
+
+		let mut builder = TransactionBuilder::new(transaction.clone());
+		if let Ok(public_key) = fc_rpc::public_key(&transaction) {
+			let from = H160::from_slice(&sp_io::hashing::keccak_256(&public_key)[12..]);
+			builder = builder.sender(from, Some(H512::from(public_key)));
+		}
+		let rpc = builder.build();
+
+		// The raw bytes served by the RPC decode to the submitted transaction.
+		assert_eq!(
+			rlp::decode::<TransactionV0>(&rpc.raw.0).unwrap(),
+			transaction
+		);
+		let round_trip: Transaction =
+			serde_json::from_value(serde_json::to_value(&rpc).unwrap()).unwrap();
+		assert_eq!(round_trip, rpc);
+	});
+});
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `eth_sendTransaction`, `eth_call` and `eth_estimateGas` request objects: deserialization
+//! must never panic and what deserializes must serialize back to the same request.
+
+#![no_main]
+
+use fc_rpc_core::types::{CallRequest, TransactionRequest};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(request) = serde_json::from_slice::<TransactionRequest>(data) {
+		let round_trip: TransactionRequest =
+			serde_json::from_value(serde_json::to_value(&request).unwrap()).unwrap();
+		assert_eq!(round_trip, request);
+	}
+	let _ = serde_json::from_slice::<CallRequest>(data);
+});
@@ -18,19 +18,21 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod precompile;
+mod trace;
 
 use codec::{Decode, Encode};
 pub use evm::ExitReason;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_core::{H160, U256};
+use sp_core::{H160, H256, U256};
 use sp_std::vec::Vec;
 
 pub use evm::backend::{Basic as Account, Log};
 pub use precompile::{
-	Context, ExitError, ExitSucceed, LinearCostPrecompile, Precompile, PrecompileOutput,
-	PrecompileSet,
+	ensure_linear_cost, Context, ExitError, ExitSucceed, LinearCostPrecompile, Precompile,
+	PrecompileOutput, PrecompileSet,
 };
+pub use trace::{BundlerTrace, CallFrame, CallTrace, StorageAccess, TopLevelCall, TraceLog};
 
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
@@ -60,3 +62,40 @@ pub enum CallOrCreateInfo {
 	Call(CallInfo),
 	Create(CreateInfo),
 }
+
+/// Gas charged to a transaction before its execution: the base cost of a call or a create, the
+/// cost of its zero and non-zero data bytes and the cost of its access list.
+pub fn intrinsic_gas(
+	config: &evm::Config,
+	is_create: bool,
+	input: &[u8],
+	access_list: &[(H160, Vec<H256>)],
+) -> u64 {
+	let transaction_cost = if is_create {
+		evm::gasometer::create_transaction_cost(input)
+	} else {
+		evm::gasometer::call_transaction_cost(input)
+	};
+	let mut gasometer = evm::gasometer::Gasometer::new(u64::max_value(), config);
+	// The cost of a transaction cannot exceed the maximum gas limit.
+	let _ = gasometer.record_transaction(transaction_cost);
+	gasometer
+		.total_used_gas()
+		.saturating_add(access_list_gas(config, access_list))
+}
+
+/// Gas charged for the addresses and storage keys of an EIP-2930 access list. Free before
+/// Berlin, whose configs do not support access lists.
+pub fn access_list_gas(config: &evm::Config, access_list: &[(H160, Vec<H256>)]) -> u64 {
+	access_list.iter().fold(0u64, |gas, (_, storage_keys)| {
+		gas.saturating_add(config.gas_access_list_address)
+			.saturating_add(
+				(storage_keys.len() as u64).saturating_mul(config.gas_access_list_storage_key),
+			)
+	})
+}
+
+/// Whether the config follows Berlin (EIP-2929), which introduced access lists.
+pub fn supports_access_lists(config: &evm::Config) -> bool {
+	config.increase_state_access_gas
+}
@@ -33,6 +33,11 @@ pub trait PrecompileSet {
 		target_gas: Option<u64>,
 		context: &Context,
 	) -> Option<core::result::Result<PrecompileOutput, ExitError>>;
+
+	/// Addresses of the precompiles of the set, none by default.
+	fn addresses() -> Vec<H160> {
+		Vec::new()
+	}
 }
 
 /// One single precompile used by EVM engine.
@@ -69,6 +74,16 @@ impl PrecompileSet for Tuple {
 
 		None
 	}
+
+	fn addresses() -> Vec<H160> {
+		let mut addresses = Vec::new();
+
+		for_tuples!( #(
+			addresses.push(H160::from_low_u64_be(addresses.len() as u64 + 1));
+		)* );
+
+		addresses
+	}
 }
 
 pub trait LinearCostPrecompile {
@@ -96,8 +111,8 @@ impl<T: LinearCostPrecompile> Precompile for T {
 	}
 }
 
-/// Linear gas cost
-fn ensure_linear_cost(
+/// Linear gas cost of an input of `len` bytes, failing when it exceeds the `target_gas`.
+pub fn ensure_linear_cost(
 	target_gas: Option<u64>,
 	len: u64,
 	base: u64,
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::{Decode, Encode};
+use evm::ExitReason;
+use sp_core::{H160, H256, U256};
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+/// Trace of a call executed with `trace_call`.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct CallTrace {
+	pub exit_reason: ExitReason,
+	/// Returned data of a call, empty for a contract creation.
+	pub output: Vec<u8>,
+	/// Gas used, access list included.
+	pub used_gas: U256,
+	/// Addresses and storage keys accessed by the execution, without the sender, the target
+	/// and the precompiles, as `eth_createAccessList` returns them.
+	pub access_list: Vec<(H160, Vec<H256>)>,
+	pub bundler: BundlerTrace,
+}
+
+/// Data collected by the ERC-4337 bundler collector tracer of geth, which the bundlers use to
+/// check the validation rules of the user operations simulated by an EntryPoint.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct BundlerTrace {
+	/// Accesses of each call of the EntryPoint, until its `BeforeExecution` event.
+	pub calls_from_entry_point: Vec<TopLevelCall>,
+	/// Inputs of the KECCAK256 opcodes of 21 to 511 bytes.
+	pub keccak: Vec<Vec<u8>>,
+	/// Entries and exits of the inner calls.
+	pub calls: Vec<CallFrame>,
+	/// Logs of the inner calls.
+	pub logs: Vec<TraceLog>,
+}
+
+/// Accesses of a call of the EntryPoint and of its inner calls.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TopLevelCall {
+	/// First four bytes of the input of the call.
+	pub method_sig: Vec<u8>,
+	pub target: H160,
+	/// Number of executions of each opcode, without the stack and arithmetic ones.
+	pub opcodes: BTreeMap<u8, u64>,
+	/// Storage read and written by each address.
+	pub access: BTreeMap<H160, StorageAccess>,
+	/// Code size of the accounts accessed with the EXTCODE* and CALL* opcodes, with the
+	/// opcode of the first access.
+	pub contract_size: BTreeMap<H160, (u64, u8)>,
+	/// Opcode following an EXTCODE* access of each address, other than an EXTCODESIZE
+	/// checked by ISZERO.
+	pub ext_code_access_info: BTreeMap<H160, u8>,
+	/// Whether an inner call ran out of gas.
+	pub oog: bool,
+}
+
+/// Storage accesses of an address.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct StorageAccess {
+	/// Value of the slots read before being written.
+	pub reads: BTreeMap<H256, H256>,
+	/// Number of writes of the slots.
+	pub writes: BTreeMap<H256, u64>,
+}
+
+/// Entry or exit of an inner call.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum CallFrame {
+	Enter {
+		/// CALL, CALLCODE, DELEGATECALL, STATICCALL, CREATE or CREATE2.
+		opcode: u8,
+		from: H160,
+		to: H160,
+		/// First four bytes of the input.
+		method: Vec<u8>,
+		value: U256,
+		gas: u64,
+	},
+	Exit {
+		reverted: bool,
+		gas_used: u64,
+		data: Vec<u8>,
+	},
+}
+
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TraceLog {
+	pub topics: Vec<H256>,
+	pub data: Vec<u8>,
+}
@@ -21,7 +21,7 @@ use codec::{Decode, Encode};
 use ethereum::{BlockV0 as EthereumBlock, Log};
 use ethereum_types::Bloom;
 use sp_core::{H160, H256, U256};
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::{traits::Block as BlockT, FixedU128};
 use sp_std::vec::Vec;
 
 #[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
@@ -49,8 +49,70 @@ impl Default for TransactionStatus {
 	}
 }
 
+/// Status and receipt of a transaction of the current block, indexed by its hash.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub struct IndexedTransactionStatus {
+	/// Hash of the Ethereum block.
+	pub block_hash: H256,
+	/// Number of the Ethereum block.
+	pub block_number: U256,
+	pub status: TransactionStatus,
+	/// Receipt of the transaction, without the logs, which are those of the status.
+	pub receipt: ethereum::Receipt,
+	/// Gas used by the transactions of the block up to this one.
+	pub cumulative_gas_used: U256,
+	/// Index in the block of the first log of the transaction.
+	pub first_log_index: u32,
+	/// Gas price paid by the transaction.
+	pub gas_price: U256,
+}
+
+/// Value transfer below the top level call of a transaction of the current block.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub struct InternalTransfer {
+	/// Index of the transaction in the block.
+	pub transaction_index: u32,
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+}
+
+/// Balance, nonce and code of an account.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub struct AccountSummary {
+	pub balance: U256,
+	pub nonce: U256,
+	/// Keccak256 hash of the code, the hash of the empty code for the accounts without code.
+	pub code_hash: H256,
+	/// Code length in bytes.
+	pub code_size: u64,
+}
+
+/// Ethereum-level configuration of the chain.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub struct ChainConfig {
+	/// Chain id used for transaction signing.
+	pub chain_id: u64,
+	/// Hard fork the EVM config follows.
+	pub evm_fork: Vec<u8>,
+	/// Optional features the EVM config enables.
+	pub evm_features: Vec<Vec<u8>>,
+	/// Maximum size of the deployed code, None if unlimited.
+	pub create_contract_limit: Option<u64>,
+	/// Gas limit of the blocks.
+	pub block_gas_limit: U256,
+	/// Minimum gas price of the transactions.
+	pub min_gas_price: U256,
+	/// Bounds of the minimum gas price adjustments, as the divisor of the maximum change and
+	/// the lower and upper bounds, None if the minimum gas price is fixed.
+	pub min_gas_price_bounds: Option<(U256, U256, U256)>,
+	/// Addresses of the precompiles.
+	pub precompiles: Vec<H160>,
+}
+
 sp_api::decl_runtime_apis! {
 	/// API necessary for Ethereum-compatibility layer.
+	#[api_version(16)]
 	pub trait EthereumRuntimeRPCApi {
 		/// Returns runtime defined pallet_evm::ChainId.
 		fn chain_id() -> u64;
@@ -65,6 +127,7 @@ sp_api::decl_runtime_apis! {
 		/// For a given account address and index, returns pallet_evm::AccountStorages.
 		fn storage_at(address: H160, index: U256) -> H256;
 		/// Returns a frame_ethereum::call response. If `estimate` is true,
+		#[changed_in(7)]
 		fn call(
 			from: H160,
 			to: H160,
@@ -75,7 +138,23 @@ sp_api::decl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 		) -> Result<fp_evm::CallInfo, sp_runtime::DispatchError>;
+		/// Returns a frame_ethereum::call response, paying the effective gas price of the fee
+		/// fields and charging the access list before the execution. If `estimate` is true,
+		/// the call is executed without committing its effects.
+		fn call(
+			from: H160,
+			to: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<fp_evm::CallInfo, sp_runtime::DispatchError>;
 		/// Returns a frame_ethereum::create response.
+		#[changed_in(7)]
 		fn create(
 			from: H160,
 			data: Vec<u8>,
@@ -85,6 +164,19 @@ sp_api::decl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 		) -> Result<fp_evm::CreateInfo, sp_runtime::DispatchError>;
+		/// Returns a frame_ethereum::create response, paying the effective gas price of the fee
+		/// fields and charging the access list before the execution.
+		fn create(
+			from: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<fp_evm::CreateInfo, sp_runtime::DispatchError>;
 		/// Return the current block.
 		fn current_block() -> Option<EthereumBlock>;
 		/// Return the current receipt.
@@ -101,6 +193,66 @@ sp_api::decl_runtime_apis! {
 		fn extrinsic_filter(
 			xts: Vec<<Block as BlockT>::Extrinsic>,
 		) -> Vec<ethereum::TransactionV0>;
+		/// Returns the self-destructed contracts whose storage is still being removed, with the
+		/// number of storage keys left. The runtime may bound both.
+		fn pending_removals() -> Vec<(H160, u32)>;
+		/// Returns the status and receipt of a transaction of the current block by its hash,
+		/// without decoding the statuses of the whole block.
+		fn transaction_status_by_hash(hash: H256) -> Option<IndexedTransactionStatus>;
+		/// Returns the name of the hard fork the EVM config follows.
+		fn evm_fork() -> Vec<u8>;
+		/// Returns the Ethereum-level configuration of the chain.
+		fn chain_config() -> ChainConfig;
+		/// Returns the Ethereum block, receipts and transaction statuses of a block applying the
+		/// given extrinsics on top of the current state.
+		fn pending_block(
+			xts: Vec<<Block as BlockT>::Extrinsic>,
+		) -> (
+			Option<EthereumBlock>,
+			Option<Vec<ethereum::Receipt>>,
+			Option<Vec<TransactionStatus>>,
+		);
+		/// Returns the address the Substrate author of `header` maps to, the `miner` of its
+		/// Ethereum block.
+		fn block_author(header: <Block as BlockT>::Header) -> H160;
+		/// Returns the precompiles called in the current block with their number of calls, empty
+		/// unless the runtime counts them.
+		fn precompile_calls() -> Vec<(H160, u32)>;
+		/// Returns the block from which the min gas price follows the EIP-1559 base fee rules,
+		/// None before London is activated.
+		fn london_block() -> Option<U256>;
+		/// Returns the rate of the fee token paying the fees of the transactions of `from` to
+		/// `to`, None for the contract creations: the units of the token paying one unit of the
+		/// native currency. None for the transactions paying in the native currency.
+		fn fee_token_rate(from: H160, to: Option<H160>) -> Option<FixedU128>;
+		/// Returns the sponsors of the fees of the transactions of the current block, by sender
+		/// and nonce.
+		fn fee_sponsors() -> Vec<(H160, U256, H160)>;
+		/// Executes a call, or a contract creation if `to` is None, as `call` and `create` do,
+		/// and returns its trace: its access list and its bundler collector trace.
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<fp_evm::CallTrace, sp_runtime::DispatchError>;
+		/// Returns the value transfers of the calls, contract creations and self-destructs below
+		/// the top level calls of the transactions of the current block, empty unless the
+		/// runtime records them.
+		fn internal_transfers() -> Vec<InternalTransfer>;
+		/// Returns the balance, nonce, code hash and code size of each of the accounts.
+		fn accounts(addresses: Vec<H160>) -> Vec<AccountSummary>;
+		/// Returns the SCALE encoded Substrate account id `address` maps to, which holds its
+		/// balance.
+		fn address_to_account(address: H160) -> Vec<u8>;
+		/// Returns the address the SCALE encoded Substrate account id `account` can deposit to
+		/// and withdraw from, None if it has none.
+		fn account_to_address(account: Vec<u8>) -> Option<H160>;
 	}
 }
 
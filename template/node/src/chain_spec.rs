@@ -1,13 +1,17 @@
 use frontier_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, EVMConfig, EthereumConfig, GenesisConfig, GrandpaConfig,
-	Signature, SudoConfig, SystemConfig, WASM_BINARY,
+	Runtime, Signature, SudoConfig, SystemConfig, WASM_BINARY,
+};
+use pallet_evm::{
+	genesis_builder::{self, GenesisBuilder},
+	geth_genesis::GethGenesis,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{sr25519, Pair, Public, H160, U256};
+use sp_core::{sr25519, Get, Pair, Public, H160, U256};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
-use std::{collections::BTreeMap, str::FromStr};
+use std::{collections::BTreeMap, path::Path, str::FromStr};
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -15,6 +19,55 @@ use std::{collections::BTreeMap, str::FromStr};
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
 
+/// EVM accounts added to the genesis of the built-in chains, see `--dev-accounts`.
+#[derive(Clone, Debug)]
+pub struct DevGenesis {
+	/// Number of the anvil and hardhat dev accounts to pre-fund, at most ten.
+	pub dev_accounts: usize,
+	/// Balance of the pre-funded dev accounts.
+	pub dev_account_balance: U256,
+	/// Runtime code deployed at the given addresses.
+	pub predeploys: Vec<(H160, Vec<u8>)>,
+	/// Install the deterministic deployment proxy at its canonical address.
+	pub canonical_predeploys: bool,
+	/// Runtime code of Multicall3, installed at its canonical address with the
+	/// `canonical_predeploys`.
+	pub multicall3_code: Option<Vec<u8>>,
+}
+
+impl DevGenesis {
+	fn apply(&self, evm: EVMConfig) -> EVMConfig {
+		let mut builder = GenesisBuilder::from_config(evm)
+			.with_dev_accounts(self.dev_accounts, self.dev_account_balance);
+		if self.canonical_predeploys {
+			builder = builder.with_deterministic_deployment_proxy();
+			if let Some(code) = &self.multicall3_code {
+				builder = builder
+					.with_multicall3(code.clone())
+					.expect("the Multicall3 code is checked when read; qed");
+			}
+		}
+		self.predeploys
+			.iter()
+			.fold(builder, |builder, (address, code)| {
+				builder.with_contract(*address, code.clone(), Default::default())
+			})
+			.build()
+	}
+}
+
+impl Default for DevGenesis {
+	fn default() -> Self {
+		Self {
+			dev_accounts: 0,
+			dev_account_balance: genesis_builder::default_dev_balance(),
+			predeploys: Vec::new(),
+			canonical_predeploys: false,
+			multicall3_code: None,
+		}
+	}
+}
+
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
 	TPublic::Pair::from_string(&format!("//{}", seed), None)
@@ -37,7 +90,7 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
 }
 
-pub fn development_config() -> Result<ChainSpec, String> {
+pub fn development_config(dev_genesis: DevGenesis) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -47,7 +100,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		"dev",
 		ChainType::Development,
 		move || {
-			testnet_genesis(
+			let mut genesis = testnet_genesis(
 				wasm_binary,
 				// Initial PoA authorities
 				vec![authority_keys_from_seed("Alice")],
@@ -61,7 +114,72 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
 				true,
-			)
+				&dev_genesis,
+			);
+			// Only the development chains accept the state changes of the dev RPC.
+			#[cfg(feature = "manual-seal")]
+			{
+				genesis.evm_dev.enabled = true;
+			}
+			genesis
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		// Properties
+		None,
+		// Extensions
+		None,
+	))
+}
+
+/// Development chain whose EVM accounts are those of a geth or OpenEthereum genesis file.
+pub fn geth_genesis_config(path: &Path, dev_genesis: DevGenesis) -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	let json = std::fs::read(path)
+		.map_err(|e| format!("Failed to read genesis file {}: {}", path.display(), e))?;
+	let geth_genesis = GethGenesis::from_json(&json)?;
+
+	let runtime_chain_id = <Runtime as pallet_evm::Config>::ChainId::get();
+	if let Some(chain_id) = geth_genesis.chain_id {
+		if chain_id != runtime_chain_id {
+			return Err(format!(
+				"Genesis chain id {} differs from the runtime chain id {}",
+				chain_id, runtime_chain_id
+			));
+		}
+	}
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Development",
+		// ID
+		"dev",
+		ChainType::Development,
+		move || {
+			let mut genesis = testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice")],
+				// Sudo account
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+				],
+				true,
+				&DevGenesis::default(),
+			);
+			genesis.evm = dev_genesis.apply(geth_genesis.evm_config());
+			#[cfg(feature = "manual-seal")]
+			{
+				genesis.evm_dev.enabled = true;
+			}
+			genesis
 		},
 		// Bootnodes
 		vec![],
@@ -76,7 +194,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 	))
 }
 
-pub fn local_testnet_config() -> Result<ChainSpec, String> {
+pub fn local_testnet_config(dev_genesis: DevGenesis) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -111,6 +229,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
 				true,
+				&dev_genesis,
 			)
 		},
 		// Bootnodes
@@ -133,6 +252,7 @@ fn testnet_genesis(
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
+	dev_genesis: &DevGenesis,
 ) -> GenesisConfig {
 	GenesisConfig {
 		system: SystemConfig {
@@ -161,7 +281,7 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		},
-		evm: EVMConfig {
+		evm: dev_genesis.apply(EVMConfig {
 			accounts: {
 				let mut map = BTreeMap::new();
 				map.insert(
@@ -194,8 +314,10 @@ fn testnet_genesis(
 				);
 				map
 			},
-		},
+		}),
 		ethereum: EthereumConfig {},
 		dynamic_fee: Default::default(),
+		#[cfg(feature = "manual-seal")]
+		evm_dev: Default::default(),
 	}
 }
@@ -1,6 +1,7 @@
-#[cfg(feature = "manual-seal")]
-use structopt::clap::arg_enum;
-use structopt::StructOpt;
+use fc_rpc::RemoteSignerProtocol;
+use sp_core::{H160, U256};
+use std::path::PathBuf;
+use structopt::{clap::arg_enum, StructOpt};
 
 #[cfg(feature = "manual-seal")]
 arg_enum! {
@@ -21,6 +22,27 @@ impl Default for Sealing {
 	}
 }
 
+arg_enum! {
+	/// Available Ethereum RPC namespaces.
+	#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+	pub enum EthApi {
+		// `eth_*`, including the filter and pubsub methods.
+		Eth,
+		// `net_*`.
+		Net,
+		// `web3_*`.
+		Web3,
+		// `personal_*`, message signing with the node signers.
+		Personal,
+		// `debug_*`.
+		Debug,
+		// `admin_*`, peers and node info.
+		Admin,
+		// `frontier_*`, including `frontier_subscribe`.
+		Frontier,
+	}
+}
+
 #[allow(missing_docs)]
 #[derive(Debug, StructOpt)]
 pub struct RunCmd {
@@ -36,13 +58,257 @@ pub struct RunCmd {
 	#[structopt(long = "enable-dev-signer")]
 	pub enable_dev_signer: bool,
 
+	/// Sign the `eth_sendTransaction` transactions with the keyfiles of the given directory,
+	/// in the web3 secret storage format. With `--rpc-external` or `--ws-external`, it requires
+	/// `--rpc-methods=Unsafe`.
+	#[structopt(long)]
+	pub eth_keystore: Option<PathBuf>,
+
+	/// File holding the password of the `--eth-keystore` keyfiles.
+	#[structopt(long, requires = "eth-keystore")]
+	pub eth_keystore_password_file: Option<PathBuf>,
+
+	/// Forward the `eth_sendTransaction` signing requests to the external signer at the given
+	/// HTTP endpoint. With `--rpc-external` or `--ws-external`, it requires
+	/// `--rpc-methods=Unsafe`.
+	#[structopt(long)]
+	pub eth_remote_signer: Option<String>,
+
+	/// JSON-RPC dialect of the `--eth-remote-signer`.
+	#[structopt(
+		long,
+		default_value = "clef",
+		possible_values = &["clef", "web3signer"],
+		case_insensitive = true
+	)]
+	pub eth_remote_signer_protocol: RemoteSignerProtocol,
+
+	/// Persist the locally submitted Ethereum transactions and re-inject them on startup.
+	#[structopt(long)]
+	pub journal_local_transactions: bool,
+
 	/// Maximum number of logs in a query.
 	#[structopt(long, default_value = "10000")]
 	pub max_past_logs: u32,
 
+	/// Relative error tolerated on `eth_estimateGas` results, a lower ratio takes more executions.
+	#[structopt(long, default_value = "0.015")]
+	pub estimate_gas_error_ratio: f64,
+
+	/// Accept the transactions without replay protection (pre EIP-155) in
+	/// `eth_sendRawTransaction`.
+	#[structopt(long)]
+	pub rpc_allow_unprotected_txs: bool,
+
+	/// Serve the Ethereum blocks, transactions, receipts and logs of the given number of latest
+	/// blocks only, the older ones answer with a pruned error. The blocks whose state was
+	/// pruned, see `--pruning`, always do. The SQL log index drops the older blocks, the hash
+	/// mappings are kept.
+	#[structopt(long)]
+	pub eth_history_window: Option<u64>,
+
+	/// Number of latest blocks whose Ethereum transactions are indexed by hash, or `all`. The
+	/// transactions of the older blocks are removed from the index and not found by hash, like
+	/// geth's `--history.transactions`; `0` also keeps them all.
+	#[structopt(long, default_value = "all", parse(try_from_str = parse_tx_index_history))]
+	pub tx_index_history: u64,
+
+	/// Index the Ethereum logs by address and topics in an SQL database next to the Frontier
+	/// database, serving `eth_getLogs` from it for the indexed blocks. The blocks from the
+	/// first run on are indexed, the older ones by the `backfill-log-index` command.
+	#[structopt(long)]
+	pub eth_log_index: bool,
+
+	/// Forward the Ethereum RPC requests for the history not served locally to the archive
+	/// node at the given HTTP endpoint.
+	#[structopt(long)]
+	pub archive_fallback_url: Option<String>,
+
+	/// Address returned by `eth_coinbase`, the author of the best block by default.
+	#[structopt(long)]
+	pub eth_coinbase: Option<H160>,
+
+	/// Maximum number of ready transactions of a sender accepted by `eth_sendRawTransaction`,
+	/// geth's `accountslots`.
+	#[structopt(long)]
+	pub eth_pool_account_slots: Option<usize>,
+
+	/// Maximum number of future transactions of a sender, waiting for a nonce gap to be filled,
+	/// accepted by `eth_sendRawTransaction`, geth's `accountqueue`.
+	#[structopt(long)]
+	pub eth_pool_account_queue: Option<usize>,
+
+	/// Network id returned by `net_version`, the chain id by default.
+	#[structopt(long)]
+	pub eth_network_id: Option<u64>,
+
 	/// The dynamic-fee pallet target gas price set by block author
 	#[structopt(long, default_value = "1")]
 	pub target_gas_price: u64,
+
+	/// Maximum number of blocks kept in the fee history cache.
+	#[structopt(long, default_value = "2048")]
+	pub fee_history_limit: u64,
+
+	/// Maximum number of blocks the mapping sync can lag behind the best block for the node
+	/// to be reported as ready.
+	#[structopt(long, default_value = "5")]
+	pub max_sync_lag: u64,
+
+	/// Serve the `/ready` readiness probe over HTTP on the given port, on the local interface.
+	/// Only `frontier_ready` is served there.
+	#[structopt(long)]
+	pub readiness_port: Option<u16>,
+
+	/// Serve the readiness probe on all interfaces, like `--prometheus-external`.
+	#[structopt(long)]
+	pub readiness_external: bool,
+
+	/// Serve the `chainHead` subscription alone over WebSocket on the given port, for the load
+	/// balancers ranking the nodes by their new heads, finality and mapping sync lag. Listens on
+	/// the local interface, on all interfaces with `--ws-external`.
+	#[structopt(long)]
+	pub chain_head_ws_port: Option<u16>,
+
+	/// Comma separated list of the Ethereum RPC namespaces to expose. The namespaces this node
+	/// does not provide are refused.
+	#[structopt(
+		long,
+		use_delimiter = true,
+		default_value = "eth,net,web3,frontier",
+		possible_values = &EthApi::variants(),
+		case_insensitive = true
+	)]
+	pub ethapi: Vec<EthApi>,
+
+	/// Serve the RPC over HTTP on the given local port, with the Ethereum RPC batches limited to
+	/// `--eth-rpc-max-batch-len` calls. The batches of the other RPC servers are only bounded by
+	/// their maximum request size.
+	#[structopt(long)]
+	pub eth_rpc_port: Option<u16>,
+
+	/// Maximum number of calls in a batch of the `--eth-rpc-port` server.
+	#[structopt(long, default_value = "1000")]
+	pub eth_rpc_max_batch_len: usize,
+
+	/// Maximum size of the parameters of an Ethereum RPC call, in megabytes. Also the maximum
+	/// size of the requests of the RPC servers, checked before parsing them, unless
+	/// `--rpc-max-payload` is given.
+	#[structopt(long, default_value = "5")]
+	pub eth_rpc_max_request_size: usize,
+
+	/// Maximum size of the result of an Ethereum RPC call, in megabytes.
+	#[structopt(long, default_value = "25")]
+	pub eth_rpc_max_response_size: usize,
+
+	/// Maximum RPC cost units served per second across all connections.
+	#[structopt(long)]
+	pub rpc_rate_limit: Option<u32>,
+
+	/// Maximum RPC cost units served per second to a single websocket or IPC connection. HTTP
+	/// requests are only subject to `--rpc-rate-limit`.
+	#[structopt(long)]
+	pub rpc_rate_limit_per_connection: Option<u32>,
+
+	/// Overrides the rate limiting cost of a RPC method, e.g. `eth_getLogs=100`.
+	#[structopt(long, parse(try_from_str = parse_method_cost))]
+	pub rpc_method_cost: Vec<(String, u32)>,
+
+	/// Log the RPC requests taking longer than the given number of milliseconds, and count
+	/// them by method in `frontier_slowQueries`.
+	#[structopt(long)]
+	pub rpc_slow_query_threshold: Option<u64>,
+
+	/// Cache up to the given number of responses of the idempotent read RPC methods, keyed by
+	/// method, parameters and best block, and count their hits in `frontier_responseCache`.
+	#[structopt(long)]
+	pub rpc_response_cache: Option<usize>,
+
+	/// Overrides the time to live in milliseconds of the cached responses of a RPC method,
+	/// e.g. `eth_call=500`. Zero disables the caching of the method.
+	#[structopt(long, requires = "rpc-response-cache", parse(try_from_str = parse_method_ttl))]
+	pub rpc_response_cache_ttl: Vec<(String, u64)>,
+
+	/// Fork the chain of the Ethereum compatible node at the given HTTP(S) endpoint: the accounts
+	/// and storage slots are fetched from it when first referenced by a RPC request. Requires
+	/// the manual seal.
+	#[structopt(long)]
+	pub fork_url: Option<String>,
+
+	/// Remote block the `--fork-url` chain is forked at, its latest block by default.
+	#[structopt(long, requires = "fork-url")]
+	pub fork_block: Option<u64>,
+
+	/// Serve the Ethereum GraphQL endpoint at `/graphql` on the given local port.
+	#[structopt(long)]
+	pub graphql_port: Option<u16>,
+
+	/// Pre-fund the given number of the anvil and hardhat dev accounts, at most ten, in the
+	/// EVM genesis of the built-in chains.
+	#[structopt(long, default_value = "0")]
+	pub dev_accounts: usize,
+
+	/// Balance of the `--dev-accounts` in wei, 10000 ether by default.
+	#[structopt(long, requires = "dev-accounts", parse(try_from_str = U256::from_dec_str))]
+	pub dev_account_balance: Option<U256>,
+
+	/// Deploy the runtime code of a hex file at an address in the EVM genesis of the built-in
+	/// chains, e.g. `0x4e59b44847b379578588920ca78fbf26c0b4956c=proxy.hex`.
+	#[structopt(long, parse(try_from_str = parse_predeploy))]
+	pub dev_predeploy: Vec<(H160, PathBuf)>,
+
+	/// Install the deterministic deployment proxy at its canonical address in the EVM genesis of
+	/// the built-in chains.
+	#[structopt(long)]
+	pub canonical_predeploys: bool,
+
+	/// Hex file of the Multicall3 runtime code installed at its canonical address with the
+	/// `--canonical-predeploys`. Refused unless its hash is the one of the mainnet deployment.
+	#[structopt(long, requires = "canonical-predeploys")]
+	pub multicall3_code: Option<PathBuf>,
+}
+
+fn parse_predeploy(input: &str) -> Result<(H160, PathBuf), String> {
+	let mut parts = input.splitn(2, '=');
+	match (parts.next(), parts.next()) {
+		(Some(address), Some(path)) if !path.is_empty() => address
+			.trim_start_matches("0x")
+			.parse()
+			.map(|address| (address, PathBuf::from(path)))
+			.map_err(|e| format!("Invalid predeploy address {}: {}", address, e)),
+		_ => Err(format!("Expected `address=path`, got {}", input)),
+	}
+}
+
+fn parse_tx_index_history(input: &str) -> Result<u64, String> {
+	match input {
+		"all" => Ok(0),
+		blocks => blocks
+			.parse()
+			.map_err(|e| format!("Expected a number of blocks or `all`, got {}: {}", input, e)),
+	}
+}
+
+fn parse_method_cost(input: &str) -> Result<(String, u32), String> {
+	let mut parts = input.splitn(2, '=');
+	match (parts.next(), parts.next()) {
+		(Some(method), Some(cost)) if !method.is_empty() => cost
+			.parse()
+			.map(|cost| (method.to_string(), cost))
+			.map_err(|e| format!("Invalid cost for method {}: {}", method, e)),
+		_ => Err(format!("Expected `method=cost`, got {}", input)),
+	}
+}
+
+fn parse_method_ttl(input: &str) -> Result<(String, u64), String> {
+	let mut parts = input.splitn(2, '=');
+	match (parts.next(), parts.next()) {
+		(Some(method), Some(ttl)) if !method.is_empty() => ttl
+			.parse()
+			.map(|ttl| (method.to_string(), ttl))
+			.map_err(|e| format!("Invalid time to live for method {}: {}", method, e)),
+		_ => Err(format!("Expected `method=milliseconds`, got {}", input)),
+	}
 }
 
 #[derive(Debug, StructOpt)]
@@ -58,6 +324,9 @@ pub struct Cli {
 pub enum Subcommand {
 	/// Key management cli utilities
 	Key(sc_cli::KeySubcommand),
+	/// Index the logs of a range of blocks in the SQL log index.
+	BackfillLogIndex(crate::log_index::BackfillLogIndexCmd),
+
 	/// Build a chain specification.
 	BuildSpec(sc_cli::BuildSpecCmd),
 
@@ -70,12 +339,24 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the Ethereum blocks in the RLP chain format of `geth export`.
+	ExportEthBlocks(crate::eth_blocks::ExportEthBlocksCmd),
+
+	/// Export the EVM accounts of a given block, as a geth genesis file or a binary snapshot.
+	ExportEvmState(crate::evm_state::ExportEvmStateCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Submit the transactions of a `geth export` RLP chain file to a running node.
+	ImportEthBlocks(crate::eth_blocks::ImportEthBlocksCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
+	/// Rebuild the Ethereum storage schema cache from the runtime storage, or check it.
+	RebuildSchemaCache(crate::schema_cache::RebuildSchemaCacheCmd),
+
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
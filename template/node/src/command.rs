@@ -51,8 +51,12 @@ impl SubstrateCli for Cli {
 
 	fn load_spec(&self, id: &str) -> Result<Box<dyn sc_service::ChainSpec>, String> {
 		Ok(match id {
-			"dev" => Box::new(chain_spec::development_config()?),
-			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
+			"dev" => Box::new(chain_spec::development_config(self.dev_genesis()?)?),
+			"" | "local" => Box::new(chain_spec::local_testnet_config(self.dev_genesis()?)?),
+			path if path.starts_with("geth-genesis:") => Box::new(chain_spec::geth_genesis_config(
+				std::path::Path::new(&path["geth-genesis:".len()..]),
+				self.dev_genesis()?,
+			)?),
 			path => Box::new(chain_spec::ChainSpec::from_json_file(
 				std::path::PathBuf::from(path),
 			)?),
@@ -64,12 +68,63 @@ impl SubstrateCli for Cli {
 	}
 }
 
+impl Cli {
+	/// EVM accounts the `--dev-accounts`, `--dev-predeploy` and `--canonical-predeploys` flags add
+	/// to the built-in chains.
+	fn dev_genesis(&self) -> Result<chain_spec::DevGenesis, String> {
+		let mut dev_genesis = chain_spec::DevGenesis {
+			dev_accounts: self.run.dev_accounts,
+			..Default::default()
+		};
+		if let Some(balance) = self.run.dev_account_balance {
+			dev_genesis.dev_account_balance = balance;
+		}
+		for (address, path) in &self.run.dev_predeploy {
+			dev_genesis.predeploys.push((*address, read_code(path)?));
+		}
+		dev_genesis.canonical_predeploys = self.run.canonical_predeploys;
+		dev_genesis.multicall3_code = self
+			.run
+			.multicall3_code
+			.as_deref()
+			.map(|path| {
+				let code = read_code(path)?;
+				pallet_evm::genesis_builder::check_multicall3_code(&code)?;
+				Ok::<_, String>(code)
+			})
+			.transpose()?;
+		Ok(dev_genesis)
+	}
+}
+
+/// Reads a hex file of contract code.
+fn read_code(path: &std::path::Path) -> Result<Vec<u8>, String> {
+	let code = std::fs::read_to_string(path)
+		.map_err(|e| format!("Failed to read code {}: {}", path.display(), e))?;
+	hex::decode(code.trim().trim_start_matches("0x"))
+		.map_err(|e| format!("Invalid code {}: {}", path.display(), e))
+}
+
 /// Parse and run command line arguments
 pub fn run() -> sc_cli::Result<()> {
 	let cli = Cli::from_args();
 
 	match &cli.subcommand {
 		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
+		Some(Subcommand::BackfillLogIndex(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents {
+					client,
+					task_manager,
+					..
+				} = service::new_partial(&config, &cli)?;
+				Ok((
+					cmd.run(client, service::frontier_log_index_path(&config)),
+					task_manager,
+				))
+			})
+		}
 		Some(Subcommand::BuildSpec(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
@@ -108,6 +163,28 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, config.chain_spec), task_manager))
 			})
 		}
+		Some(Subcommand::ExportEthBlocks(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents {
+					client,
+					task_manager,
+					..
+				} = service::new_partial(&config, &cli)?;
+				Ok((cmd.run(client), task_manager))
+			})
+		}
+		Some(Subcommand::ExportEvmState(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents {
+					client,
+					task_manager,
+					..
+				} = service::new_partial(&config, &cli)?;
+				Ok((cmd.run(client), task_manager))
+			})
+		}
 		Some(Subcommand::ImportBlocks(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.async_run(|config| {
@@ -120,6 +197,10 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		}
+		Some(Subcommand::ImportEthBlocks(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|_| cmd.run())
+		}
 		Some(Subcommand::PurgeChain(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| {
@@ -132,6 +213,18 @@ pub fn run() -> sc_cli::Result<()> {
 				cmd.run(config.database)
 			})
 		}
+		Some(Subcommand::RebuildSchemaCache(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents {
+					client,
+					task_manager,
+					other: (_, _, frontier_backend, _),
+					..
+				} = service::new_partial(&config, &cli)?;
+				Ok((cmd.run(client, frontier_backend), task_manager))
+			})
+		}
 		Some(Subcommand::Revert(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.async_run(|config| {
@@ -0,0 +1,311 @@
+//! Export and import of the Ethereum blocks in the RLP chain format of `geth export`.
+//!
+//! The export file is the concatenation of the RLP encoded blocks (header, transactions,
+//! ommers). The receipts, which geth does not export, can be written to a second file holding
+//! the RLP list of the receipts of every exported block, in the same order.
+
+use std::{fs, io::Write, path::PathBuf, sync::Arc, time::Duration};
+
+use fc_rpc::{frontier_backend_client, restore_receipt_logs};
+use frontier_template_runtime::opaque::Block;
+use sc_cli::{CliConfiguration, PruningParams, SharedParams};
+use sc_client_api::backend::{AuxStore, Backend, StateBackend, StorageProvider};
+use serde_json::{json, Value};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::BlakeTwo256;
+use structopt::StructOpt;
+
+/// Export the Ethereum blocks in the RLP chain format of `geth export`.
+#[derive(Debug, StructOpt)]
+pub struct ExportEthBlocksCmd {
+	/// Output file.
+	#[structopt(parse(from_os_str))]
+	pub output: PathBuf,
+
+	/// Number of the first block to export.
+	#[structopt(long, default_value = "0")]
+	pub from: u32,
+
+	/// Number of the last block to export, the best block by default.
+	#[structopt(long)]
+	pub to: Option<u32>,
+
+	/// Also write the receipts of the exported blocks to the given file.
+	#[structopt(long, parse(from_os_str))]
+	pub receipts: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl ExportEthBlocksCmd {
+	/// Run the export.
+	pub async fn run<C, BE>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		BE: Backend<Block> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + AuxStore,
+		C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+		C: Send + Sync + 'static,
+		C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
+	{
+		let overrides = crate::rpc::overrides_handle(client.clone());
+		let to = self.to.unwrap_or(client.info().best_number);
+
+		let mut blocks = fs::File::create(&self.output)?;
+		let mut receipts = match &self.receipts {
+			Some(path) => Some(fs::File::create(path)?),
+			None => None,
+		};
+
+		let mut exported = 0;
+		for number in self.from..=to {
+			let id = BlockId::Number(number);
+			let schema = frontier_backend_client::onchain_storage_schema::<Block, C, BE>(
+				client.as_ref(),
+				id,
+			);
+			let handler = overrides
+				.schemas
+				.get(&schema)
+				.unwrap_or(&overrides.fallback);
+
+			// The blocks before the Ethereum pallet was added have no Ethereum block.
+			let block = match handler.current_block(&id) {
+				Some(block) => block,
+				None => continue,
+			};
+			blocks.write_all(&rlp::encode(&block))?;
+
+			if let Some(file) = receipts.as_mut() {
+				let mut block_receipts = handler
+					.current_receipts(&id)
+					.ok_or_else(|| format!("Missing receipts of block {}", number))?;
+				let statuses = handler
+					.current_transaction_statuses(&id)
+					.ok_or_else(|| format!("Missing transaction statuses of block {}", number))?;
+				restore_receipt_logs(&mut block_receipts, &statuses);
+				file.write_all(&rlp::encode_list::<ethereum::Receipt, _>(&block_receipts))?;
+			}
+			exported += 1;
+		}
+
+		log::info!(
+			"Exported {} Ethereum blocks to {}",
+			exported,
+			self.output.display()
+		);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportEthBlocksCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
+
+/// Import the transactions of a `geth export` RLP chain file into a running node.
+#[derive(Debug, StructOpt)]
+pub struct ImportEthBlocksCmd {
+	/// Input file.
+	#[structopt(parse(from_os_str))]
+	pub input: PathBuf,
+
+	/// HTTP RPC endpoint of the node receiving the transactions.
+	#[structopt(long, default_value = "http://127.0.0.1:9933")]
+	pub rpc_url: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ImportEthBlocksCmd {
+	/// Run the import. The blocks are not imported as such, as they are not Substrate blocks:
+	/// their transactions are submitted in order through `eth_sendRawTransaction`.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let data = fs::read(&self.input)?;
+		let blocks = decode_blocks(&data)?;
+
+		let (mut submitted, mut rejected) = (0, 0);
+		for block in &blocks {
+			for transaction in &block.transactions {
+				match self.request(
+					"eth_sendRawTransaction",
+					json!([raw_transaction(transaction)]),
+				) {
+					Ok(_) => submitted += 1,
+					Err(err) => {
+						log::warn!(
+							"Transaction of block {} rejected: {}",
+							block.header.number,
+							err
+						);
+						rejected += 1;
+					}
+				}
+			}
+		}
+
+		log::info!(
+			"Submitted the transactions of {} Ethereum blocks: {} accepted, {} rejected",
+			blocks.len(),
+			submitted,
+			rejected
+		);
+		Ok(())
+	}
+
+	fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+		let response: Value = ureq::post(&self.rpc_url)
+			.timeout(Duration::from_secs(30))
+			.send_json(json!({
+				"jsonrpc": "2.0",
+				"id": 1,
+				"method": method,
+				"params": params,
+			}))
+			.map_err(|err| format!("request failed: {}", err))?
+			.into_json()
+			.map_err(|err| format!("invalid response: {}", err))?;
+
+		if let Some(error) = response.get("error") {
+			return Err(error.to_string());
+		}
+		Ok(response.get("result").cloned().unwrap_or(Value::Null))
+	}
+}
+
+impl CliConfiguration for ImportEthBlocksCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+/// Hex encoded transaction, as given to `eth_sendRawTransaction`.
+fn raw_transaction(transaction: &ethereum::TransactionV0) -> String {
+	format!("0x{}", hex::encode(rlp::encode(transaction)))
+}
+
+/// Decodes the concatenated RLP encoded blocks of a chain file.
+fn decode_blocks(mut data: &[u8]) -> Result<Vec<ethereum::BlockV0>, String> {
+	let mut blocks = Vec::new();
+	while !data.is_empty() {
+		let info = rlp::Rlp::new(data)
+			.payload_info()
+			.map_err(|err| format!("Invalid block {}: {:?}", blocks.len(), err))?;
+		let len = info.header_len + info.value_len;
+		if len > data.len() {
+			return Err(format!("Truncated block {}", blocks.len()));
+		}
+		let block = rlp::decode::<ethereum::BlockV0>(&data[..len])
+			.map_err(|err| format!("Invalid block {}: {:?}", blocks.len(), err))?;
+		blocks.push(block);
+		data = &data[len..];
+	}
+	Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{H160, H256, U256};
+
+	fn transaction(nonce: u64) -> ethereum::TransactionV0 {
+		ethereum::TransactionV0 {
+			nonce: U256::from(nonce),
+			gas_price: U256::from(1),
+			gas_limit: U256::from(21000),
+			action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+			value: U256::from(nonce * 1000),
+			input: vec![nonce as u8],
+			signature: ethereum::TransactionSignature::new(
+				27,
+				H256::repeat_byte(1),
+				H256::repeat_byte(2),
+			)
+			.unwrap(),
+		}
+	}
+
+	fn block(number: u64, transactions: Vec<ethereum::TransactionV0>) -> ethereum::BlockV0 {
+		ethereum::Block::new(
+			ethereum::PartialHeader {
+				parent_hash: H256::from_low_u64_be(number),
+				beneficiary: Default::default(),
+				state_root: Default::default(),
+				receipts_root: Default::default(),
+				logs_bloom: Default::default(),
+				difficulty: Default::default(),
+				number: U256::from(number),
+				gas_limit: U256::from(15_000_000),
+				gas_used: U256::from(21000 * transactions.len()),
+				timestamp: number * 6,
+				extra_data: Vec::new(),
+				mix_hash: Default::default(),
+				nonce: Default::default(),
+			},
+			transactions,
+			Vec::new(),
+		)
+	}
+
+	/// Chain file written as `ExportEthBlocksCmd` does.
+	fn export(blocks: &[ethereum::BlockV0]) -> Vec<u8> {
+		blocks
+			.iter()
+			.flat_map(|block| rlp::encode(block).to_vec())
+			.collect()
+	}
+
+	#[test]
+	fn exported_blocks_are_decoded() {
+		let blocks = vec![
+			block(1, Vec::new()),
+			block(2, vec![transaction(0), transaction(1)]),
+			block(3, vec![transaction(2)]),
+		];
+		assert_eq!(decode_blocks(&export(&blocks)), Ok(blocks));
+		assert_eq!(decode_blocks(&[]), Ok(Vec::new()));
+	}
+
+	#[test]
+	fn imported_transactions_are_the_exported_ones() {
+		let blocks = vec![block(1, vec![transaction(0), transaction(1)])];
+		let imported: Vec<_> = decode_blocks(&export(&blocks))
+			.unwrap()
+			.iter()
+			.flat_map(|block| block.transactions.iter().map(raw_transaction))
+			.map(|raw| {
+				rlp::decode::<ethereum::TransactionV0>(
+					&hex::decode(raw.trim_start_matches("0x")).unwrap(),
+				)
+				.unwrap()
+			})
+			.collect();
+		assert_eq!(imported, blocks[0].transactions);
+	}
+
+	#[test]
+	fn truncated_and_invalid_blocks_are_rejected() {
+		let data = export(&[block(1, Vec::new()), block(2, vec![transaction(0)])]);
+		assert_eq!(
+			decode_blocks(&data[..data.len() - 1]),
+			Err("Truncated block 1".to_string())
+		);
+		assert!(decode_blocks(&[0xc1, 0x80])
+			.unwrap_err()
+			.starts_with("Invalid block 0"));
+	}
+}
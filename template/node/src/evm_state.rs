@@ -0,0 +1,155 @@
+//! Export of the EVM state of a block, as a geth genesis file or a binary snapshot.
+
+use std::{collections::BTreeMap, fs, io::Write, path::PathBuf, sync::Arc};
+
+use codec::{Decode, Encode};
+use fp_rpc::EthereumRuntimeRPCApi;
+use pallet_evm::{geth_genesis::GethGenesis, GenesisAccount};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, PruningParams, SharedParams};
+use sc_client_api::{backend::Backend, StorageProvider};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::twox_128, storage::StorageKey, H160, H256};
+use sp_runtime::traits::Block as BlockT;
+use structopt::{clap::arg_enum, StructOpt};
+
+arg_enum! {
+	/// Output formats of the EVM state export.
+	#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+	pub enum EvmStateFormat {
+		// geth genesis file, which the `geth-genesis:<path>` chain reads back.
+		Json,
+		// SCALE encoded `GethGenesis`.
+		Binary,
+	}
+}
+
+/// Dump the EVM accounts (balance, nonce, code and storage) of a block.
+#[derive(Debug, StructOpt)]
+pub struct ExportEvmStateCmd {
+	/// Block hash or number to export, the best block by default.
+	#[structopt(value_name = "HASH or NUMBER")]
+	pub input: Option<BlockNumberOrHash>,
+
+	/// Output file, stdout by default.
+	#[structopt(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Format of the export.
+	#[structopt(
+		long,
+		default_value = "json",
+		possible_values = &EvmStateFormat::variants(),
+		case_insensitive = true
+	)]
+	pub format: EvmStateFormat,
+
+	/// Additional accounts to export. Accounts without code nor storage can not be enumerated
+	/// from the state, as the substrate accounts only hold the hash of the EVM address.
+	#[structopt(long)]
+	pub address: Vec<H160>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl ExportEvmStateCmd {
+	/// Run the export.
+	pub async fn run<B, BA, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: BlockT<Hash = H256>,
+		BA: Backend<B>,
+		C: ProvideRuntimeApi<B> + StorageProvider<B, BA> + HeaderBackend<B>,
+		C::Api: EthereumRuntimeRPCApi<B>,
+	{
+		let at = match &self.input {
+			Some(input) => input.parse::<B>()?,
+			None => BlockId::Hash(client.info().best_hash),
+		};
+		log::info!("Exporting the EVM state of {}", at);
+
+		let mut accounts = BTreeMap::<H160, GenesisAccount>::new();
+		for (key, code) in
+			client.storage_pairs(&at, &StorageKey(storage_prefix(b"AccountCodes")))?
+		{
+			// twox_128(pallet) ++ twox_128(storage) ++ blake2_128(address) ++ address
+			let address = H160::from_slice(&key.0[48..68]);
+			let code = Vec::<u8>::decode(&mut &code.0[..])
+				.map_err(|e| format!("Invalid code of {:?}: {}", address, e))?;
+			accounts.entry(address).or_insert_with(empty_account).code = code;
+		}
+		for (key, value) in
+			client.storage_pairs(&at, &StorageKey(storage_prefix(b"AccountStorages")))?
+		{
+			// ... ++ blake2_128(address) ++ address ++ blake2_128(index) ++ index
+			let address = H160::from_slice(&key.0[48..68]);
+			let index = H256::from_slice(&key.0[84..116]);
+			let value = H256::decode(&mut &value.0[..])
+				.map_err(|e| format!("Invalid storage of {:?}: {}", address, e))?;
+			accounts
+				.entry(address)
+				.or_insert_with(empty_account)
+				.storage
+				.insert(index, value);
+		}
+		for address in &self.address {
+			accounts.entry(*address).or_insert_with(empty_account);
+		}
+
+		let api = client.runtime_api();
+		for (address, account) in accounts.iter_mut() {
+			let basic = api
+				.account_basic(&at, *address)
+				.map_err(|e| format!("Failed to fetch the account {:?}: {:?}", address, e))?;
+			account.balance = basic.balance;
+			account.nonce = basic.nonce;
+		}
+		let chain_id = api
+			.chain_id(&at)
+			.map_err(|e| format!("Failed to fetch the chain id: {:?}", e))?;
+
+		log::info!("Exported {} EVM accounts", accounts.len());
+		let genesis = GethGenesis {
+			chain_id: Some(chain_id),
+			gas_limit: None,
+			accounts,
+		};
+		let bytes = match self.format {
+			EvmStateFormat::Json => genesis.to_json(),
+			EvmStateFormat::Binary => genesis.encode(),
+		};
+		match &self.output {
+			Some(path) => fs::write(path, bytes)?,
+			None => std::io::stdout().write_all(&bytes)?,
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportEvmStateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
+
+fn storage_prefix(storage: &[u8]) -> Vec<u8> {
+	[twox_128(b"EVM"), twox_128(storage)].concat()
+}
+
+fn empty_account() -> GenesisAccount {
+	GenesisAccount {
+		nonce: Default::default(),
+		balance: Default::default(),
+		storage: Default::default(),
+		code: Default::default(),
+	}
+}
@@ -0,0 +1,12 @@
+//! Substrate Node Template CLI library, also used by the integration tests of the node.
+
+mod chain_spec;
+#[macro_use]
+pub mod service;
+pub mod cli;
+pub mod command;
+mod eth_blocks;
+mod evm_state;
+mod log_index;
+mod rpc;
+mod schema_cache;
@@ -0,0 +1,148 @@
+//! Backfill of the SQL log index of the frontier database.
+
+use std::{
+	path::PathBuf,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	thread,
+};
+
+use fc_mapping_sync::log_index_commitment;
+use fp_rpc::EthereumRuntimeRPCApi;
+use frontier_template_runtime::opaque::Block;
+use sc_cli::{CliConfiguration, PruningParams, SharedParams};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::UniqueSaturatedInto;
+use structopt::StructOpt;
+
+/// Number of blocks indexed per batch. The batches are aligned on their size, so a backfill
+/// of the same range resumes from the batches it did not finish.
+const BATCH_SIZE: u64 = 256;
+
+/// Index the logs of a range of blocks of the best chain in the SQL log index, resuming from
+/// the batches a previous run did not finish.
+#[derive(Debug, StructOpt)]
+pub struct BackfillLogIndexCmd {
+	/// First block to index.
+	#[structopt(long, default_value = "0")]
+	pub from: u64,
+
+	/// Last block to index, the best block by default.
+	#[structopt(long)]
+	pub to: Option<u64>,
+
+	/// Number of batches indexed in parallel.
+	#[structopt(long, default_value = "4")]
+	pub workers: usize,
+
+	/// Index again the batches a previous run finished.
+	#[structopt(long)]
+	pub rebuild: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl BackfillLogIndexCmd {
+	/// Run the backfill into the log index at `path`.
+	pub async fn run<C>(&self, client: Arc<C>, path: PathBuf) -> sc_cli::Result<()>
+	where
+		C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+		C::Api: EthereumRuntimeRPCApi<Block>,
+	{
+		if let Some(dir) = path.parent() {
+			std::fs::create_dir_all(dir)?;
+		}
+		let log_index = Arc::new(fc_db::LogIndexDb::<Block>::open(&path)?);
+
+		let best_number: u64 = client.info().best_number.unique_saturated_into();
+		let to = self.to.unwrap_or(best_number).min(best_number);
+		if self.from > to {
+			return Err(format!("No block to index from {} to {}", self.from, to).into());
+		}
+
+		let mut batches = Vec::new();
+		for batch in self.from / BATCH_SIZE..=to / BATCH_SIZE {
+			let batch_from = (batch * BATCH_SIZE).max(self.from);
+			let batch_to = (batch * BATCH_SIZE + BATCH_SIZE - 1).min(to);
+			if self.rebuild || !log_index.is_backfilled(batch_from, batch_to)? {
+				batches.push((batch_from, batch_to));
+			}
+		}
+		log::info!(
+			"Indexing the logs of blocks {} to {}, {} batches of up to {} blocks left",
+			self.from,
+			to,
+			batches.len(),
+			BATCH_SIZE
+		);
+
+		let batches = Arc::new(batches);
+		let next_batch = Arc::new(AtomicUsize::new(0));
+		let workers: Vec<_> = (0..self.workers.max(1))
+			.map(|_| {
+				let client = client.clone();
+				let log_index = log_index.clone();
+				let batches = batches.clone();
+				let next_batch = next_batch.clone();
+				thread::spawn(move || -> Result<(), String> {
+					while let Some(&(from, to)) =
+						batches.get(next_batch.fetch_add(1, Ordering::SeqCst))
+					{
+						let mut commitments = Vec::new();
+						for number in from..=to {
+							let hash = match client
+								.hash(number.unique_saturated_into())
+								.map_err(|e| format!("{:?}", e))?
+							{
+								Some(hash) => hash,
+								None => continue,
+							};
+							let commitment =
+								log_index_commitment::<Block, C>(client.as_ref(), hash).map_err(
+									|e| format!("Block {} cannot be indexed, {}", number, e),
+								)?;
+							commitments.extend(commitment);
+						}
+						log_index.write_backfill_batch(commitments, from, to)?;
+						log::info!("Indexed the logs of blocks {} to {}", from, to);
+					}
+					Ok(())
+				})
+			})
+			.collect();
+
+		// The batches indexed by the other workers stay recorded when one fails.
+		let results: Vec<_> = workers
+			.into_iter()
+			.map(|worker| {
+				worker
+					.join()
+					.unwrap_or_else(|_| Err("A backfill worker panicked".to_string()))
+			})
+			.collect();
+		for result in results {
+			result?;
+		}
+		log::info!("Indexed the logs of blocks {} to {}", self.from, to);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for BackfillLogIndexCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
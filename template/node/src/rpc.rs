@@ -1,11 +1,14 @@
 //! A collection of node-specific RPC methods.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
+use crate::cli::EthApi as EthApiCmd;
 use fc_rpc::{
-	EthBlockDataCache, OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override, StorageOverride,
+	ArchiveFallback, DevState, EthBlockDataCache, EthFork, EthKeystoreSigner, EthRemoteSigner,
+	OverrideHandle, RateLimiter, ResponseCache, RpcLimits, RuntimeApiStorageOverride,
+	SchemaV1Override, SenderLimits, SlowQueryTracer, StorageOverride,
 };
-use fc_rpc_core::types::FilterPool;
+use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
 use frontier_template_runtime::{opaque::Block, AccountId, Balance, Hash, Index};
 use jsonrpc_pubsub::manager::SubscriptionManager;
 use pallet_ethereum::EthereumStorageSchema;
@@ -17,11 +20,13 @@ use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
 use sc_network::NetworkService;
 use sc_rpc::SubscriptionTaskExecutor;
 use sc_rpc_api::DenyUnsafe;
-use sc_service::TransactionPool;
+use sc_service::{SpawnTaskHandle, TransactionPool};
 use sc_transaction_pool::{ChainApi, Pool};
+use sc_utils::mpsc::TracingUnboundedSender;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_core::H160;
 use sp_runtime::traits::BlakeTwo256;
 use std::collections::BTreeMap;
 
@@ -38,7 +43,7 @@ pub struct LightDeps<C, F, P> {
 }
 
 /// Full client dependencies.
-pub struct FullDeps<C, P, A: ChainApi> {
+pub struct FullDeps<C, P, BE, A: ChainApi> {
 	/// The client instance to use.
 	pub client: Arc<C>,
 	/// Transaction pool instance.
@@ -51,22 +56,93 @@ pub struct FullDeps<C, P, A: ChainApi> {
 	pub is_authority: bool,
 	/// Whether to enable dev signer
 	pub enable_dev_signer: bool,
+	/// Signer of the Ethereum keystore accounts
+	pub keystore_signer: Option<EthKeystoreSigner>,
+	/// Signer forwarding to an external signer
+	pub remote_signer: Option<EthRemoteSigner>,
+	/// Whether to journal the locally submitted transactions
+	pub journal_local_transactions: bool,
 	/// Network service
 	pub network: Arc<NetworkService<Block, Hash>>,
+	/// Channel of the system RPC requests to the network worker.
+	pub system_rpc_tx: TracingUnboundedSender<sc_rpc::system::Request<Block>>,
+	/// Node name returned by `admin_nodeInfo`.
+	pub client_name: String,
 	/// EthFilterApi pool.
 	pub filter_pool: Option<FilterPool>,
 	/// Backend.
 	pub backend: Arc<fc_db::Backend<Block>>,
+	/// Substrate backend.
+	pub substrate_backend: Arc<BE>,
 	/// Maximum number of logs in a query.
 	pub max_past_logs: u32,
+	/// Relative error tolerated on gas estimations.
+	pub estimate_gas_error_ratio: f64,
+	/// Whether to accept the transactions without replay protection
+	pub allow_unprotected_txs: bool,
+	/// Number of latest blocks whose Ethereum data is served, all when `None`.
+	pub history_window: Option<u64>,
+	/// Archive node serving the history not served locally.
+	pub archive_url: Option<String>,
+	/// Address returned by `eth_coinbase`, the block author when `None`.
+	pub coinbase: Option<H160>,
+	/// Caps on the pool transactions of a sender accepted by `eth_sendRawTransaction`.
+	pub sender_limits: SenderLimits,
+	/// Network id returned by `net_version`, the chain id when `None`.
+	pub network_id: Option<u64>,
+	/// Handle used to run heavy requests off the async executor.
+	pub spawn_handle: SpawnTaskHandle,
+	/// Ethereum RPC namespaces to expose.
+	pub ethapi: Vec<EthApiCmd>,
+	/// Size limits of the Ethereum RPC requests and responses.
+	pub rpc_limits: Arc<RpcLimits>,
+	/// Throttles the RPC methods when set.
+	pub rate_limiter: Option<Arc<RateLimiter>>,
+	/// Logs the slow RPC requests when set.
+	pub slow_query_tracer: Option<Arc<SlowQueryTracer>>,
+	/// Serves the idempotent read methods from memory when set.
+	pub response_cache: Option<Arc<ResponseCache>>,
+	/// Ethereum data access overrides.
+	pub overrides: Arc<OverrideHandle<Block>>,
+	/// Fee data of the latest blocks.
+	pub fee_history_cache: FeeHistoryCache,
+	/// Maximum mapping sync lag for the node to be ready.
+	pub max_sync_lag: u64,
 	/// Manual seal command sink
 	pub command_sink:
 		Option<futures::channel::mpsc::Sender<sc_consensus_manual_seal::rpc::EngineCommand<Hash>>>,
+	/// State shared with the manual seal authorship task, exposes the dev RPC when set
+	pub dev_state: Option<Arc<DevState<Block>>>,
+	/// Remote chain the dev chain is forked from
+	pub fork: Option<Arc<EthFork<Block>>>,
+}
+
+/// Ethereum data access overrides, by storage schema.
+pub fn overrides_handle<C, BE>(client: Arc<C>) -> Arc<OverrideHandle<Block>>
+where
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + AuxStore,
+	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+	C: Send + Sync + 'static,
+	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
+	BE: Backend<Block> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+{
+	let mut overrides_map = BTreeMap::new();
+	overrides_map.insert(
+		EthereumStorageSchema::V1,
+		Box::new(SchemaV1Override::new(client.clone()))
+			as Box<dyn StorageOverride<_> + Send + Sync>,
+	);
+
+	Arc::new(OverrideHandle {
+		schemas: overrides_map,
+		fallback: Box::new(RuntimeApiStorageOverride::new(client.clone())),
+	})
 }
 
 /// Instantiate all Full RPC extensions.
 pub fn create_full<C, P, BE, A>(
-	deps: FullDeps<C, P, A>,
+	deps: FullDeps<C, P, BE, A>,
 	subscription_task_executor: SubscriptionTaskExecutor,
 ) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
 where
@@ -84,9 +160,11 @@ where
 	A: ChainApi<Block = Block> + 'static,
 {
 	use fc_rpc::{
-		EthApi, EthApiServer, EthDevSigner, EthFilterApi, EthFilterApiServer, EthPubSubApi,
-		EthPubSubApiServer, EthSigner, HexEncodedIdProvider, NetApi, NetApiServer, Web3Api,
-		Web3ApiServer,
+		AdminApi, AdminApiServer, DebugApi, DebugApiServer, DevApi, DevApiServer, EthApi,
+		EthApiServer, EthCallCache, EthDevSigner, EthFilterApi, EthFilterApiServer, EthPubSubApi,
+		EthPubSubApiServer, EthSigner, FrontierApi, FrontierApiServer, FrontierPubSubApi,
+		FrontierPubSubApiServer, HexEncodedIdProvider, NetApi, NetApiServer, ParityApi,
+		ParityApiServer, PersonalApi, PersonalApiServer, Web3Api, Web3ApiServer,
 	};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
@@ -99,11 +177,35 @@ where
 		deny_unsafe,
 		is_authority,
 		network,
+		system_rpc_tx,
+		client_name,
 		filter_pool,
 		command_sink,
 		backend,
+		substrate_backend,
 		max_past_logs,
+		estimate_gas_error_ratio,
+		allow_unprotected_txs,
+		history_window,
+		archive_url,
+		coinbase,
+		sender_limits,
+		network_id,
+		spawn_handle,
+		ethapi,
+		rpc_limits,
+		rate_limiter,
+		slow_query_tracer,
+		response_cache,
+		overrides,
+		fee_history_cache,
+		max_sync_lag,
 		enable_dev_signer,
+		keystore_signer,
+		remote_signer,
+		journal_local_transactions,
+		dev_state,
+		fork,
 	} = deps;
 
 	io.extend_with(SystemApi::to_delegate(FullSystem::new(
@@ -115,69 +217,160 @@ where
 		client.clone(),
 	)));
 
-	let mut signers = Vec::new();
-	if enable_dev_signer {
-		signers.push(Box::new(EthDevSigner::new()) as Box<dyn EthSigner>);
-	}
-	let mut overrides_map = BTreeMap::new();
-	overrides_map.insert(
-		EthereumStorageSchema::V1,
-		Box::new(SchemaV1Override::new(client.clone()))
-			as Box<dyn StorageOverride<_> + Send + Sync>,
+	let subscriptions = SubscriptionManager::<HexEncodedIdProvider>::with_id_provider(
+		HexEncodedIdProvider::default(),
+		Arc::new(subscription_task_executor),
 	);
 
-	let overrides = Arc::new(OverrideHandle {
-		schemas: overrides_map,
-		fallback: Box::new(RuntimeApiStorageOverride::new(client.clone())),
-	});
-
+	let signers = || {
+		let mut signers = Vec::new();
+		if enable_dev_signer {
+			signers.push(Box::new(EthDevSigner::new()) as Box<dyn EthSigner>);
+		}
+		if let Some(keystore_signer) = &keystore_signer {
+			signers.push(Box::new(keystore_signer.clone()) as Box<dyn EthSigner>);
+		}
+		if let Some(remote_signer) = &remote_signer {
+			signers.push(Box::new(remote_signer.clone()) as Box<dyn EthSigner>);
+		}
+		signers
+	};
 	let block_data_cache = Arc::new(EthBlockDataCache::new(50, 50));
+	let call_cache = Arc::new(EthCallCache::new(100, Duration::from_secs(2)));
 
-	io.extend_with(EthApiServer::to_delegate(EthApi::new(
-		client.clone(),
-		pool.clone(),
-		graph,
-		frontier_template_runtime::TransactionConverter,
-		network.clone(),
-		signers,
-		overrides.clone(),
-		backend.clone(),
-		is_authority,
-		max_past_logs,
-		block_data_cache.clone(),
-	)));
-
-	if let Some(filter_pool) = filter_pool {
-		io.extend_with(EthFilterApiServer::to_delegate(EthFilterApi::new(
+	if ethapi.contains(&EthApiCmd::Eth) {
+		io.extend_with(EthApiServer::to_delegate(EthApi::new(
 			client.clone(),
-			backend,
-			filter_pool.clone(),
-			500 as usize, // max stored filters
+			pool.clone(),
+			graph,
+			frontier_template_runtime::TransactionConverter,
+			network.clone(),
+			signers(),
 			overrides.clone(),
+			backend.clone(),
+			is_authority,
 			max_past_logs,
 			block_data_cache.clone(),
+			spawn_handle.clone(),
+			fee_history_cache.clone(),
+			journal_local_transactions,
+			dev_state.clone(),
+			estimate_gas_error_ratio,
+			call_cache,
+			allow_unprotected_txs,
+			history_window,
+			coinbase,
+			sender_limits,
+			deny_unsafe,
+		)));
+
+		// The `parity_` methods serve the local transactions journal.
+		if journal_local_transactions {
+			io.extend_with(ParityApiServer::to_delegate(ParityApi::new(
+				client.clone(),
+				pool.clone(),
+				frontier_template_runtime::TransactionConverter,
+				backend.clone(),
+				deny_unsafe,
+			)));
+		}
+
+		if let Some(filter_pool) = filter_pool.clone() {
+			io.extend_with(EthFilterApiServer::to_delegate(EthFilterApi::new(
+				client.clone(),
+				backend.clone(),
+				filter_pool.clone(),
+				500 as usize, // max stored filters
+				overrides.clone(),
+				max_past_logs,
+				block_data_cache.clone(),
+			)));
+		}
+
+		io.extend_with(EthPubSubApiServer::to_delegate(EthPubSubApi::new(
+			pool.clone(),
+			client.clone(),
+			network.clone(),
+			subscriptions.clone(),
+			overrides.clone(),
+			backend.clone(),
+			dev_state.clone(),
 		)));
 	}
 
-	io.extend_with(NetApiServer::to_delegate(NetApi::new(
-		client.clone(),
-		network.clone(),
-		// Whether to format the `peer_count` response as Hex (default) or not.
-		true,
-	)));
+	if ethapi.contains(&EthApiCmd::Net) {
+		io.extend_with(NetApiServer::to_delegate(NetApi::new(
+			client.clone(),
+			network.clone(),
+			// Whether to format the `peer_count` response as Hex (default) or not.
+			true,
+			network_id,
+		)));
+	}
 
-	io.extend_with(Web3ApiServer::to_delegate(Web3Api::new(client.clone())));
+	if ethapi.contains(&EthApiCmd::Web3) {
+		io.extend_with(Web3ApiServer::to_delegate(Web3Api::new(client.clone())));
+	}
 
-	io.extend_with(EthPubSubApiServer::to_delegate(EthPubSubApi::new(
-		pool.clone(),
-		client.clone(),
-		network.clone(),
-		SubscriptionManager::<HexEncodedIdProvider>::with_id_provider(
-			HexEncodedIdProvider::default(),
-			Arc::new(subscription_task_executor),
-		),
-		overrides,
-	)));
+	if ethapi.contains(&EthApiCmd::Personal) {
+		io.extend_with(PersonalApiServer::to_delegate(PersonalApi::new(
+			signers(),
+			deny_unsafe,
+		)));
+	}
+
+	if ethapi.contains(&EthApiCmd::Debug) {
+		io.extend_with(DebugApiServer::to_delegate(DebugApi::new(
+			client.clone(),
+			backend.clone(),
+			deny_unsafe,
+			spawn_handle.clone(),
+		)));
+	}
+
+	if ethapi.contains(&EthApiCmd::Admin) {
+		io.extend_with(AdminApiServer::to_delegate(AdminApi::new(
+			client.clone(),
+			system_rpc_tx,
+			client_name,
+			deny_unsafe,
+		)));
+	}
+
+	if ethapi.contains(&EthApiCmd::Frontier) {
+		io.extend_with(FrontierPubSubApiServer::to_delegate(
+			FrontierPubSubApi::new(
+				client.clone(),
+				backend.clone(),
+				max_sync_lag,
+				subscriptions,
+				overrides.clone(),
+			),
+		));
+
+		io.extend_with(FrontierApiServer::to_delegate(FrontierApi::new(
+			client.clone(),
+			backend.clone(),
+			max_sync_lag,
+			filter_pool,
+			block_data_cache,
+			fee_history_cache,
+			slow_query_tracer.clone(),
+			response_cache.clone(),
+			overrides,
+			max_past_logs,
+			history_window,
+		)));
+	}
+
+	if let Some(dev_state) = dev_state {
+		io.extend_with(DevApiServer::to_delegate(DevApi::new(
+			client.clone(),
+			substrate_backend,
+			backend.clone(),
+			dev_state,
+		)));
+	}
 
 	match command_sink {
 		Some(command_sink) => {
@@ -190,7 +383,38 @@ where
 		_ => {}
 	}
 
-	io
+	let io = match fork {
+		Some(fork) => fork.apply(io),
+		None => io,
+	};
+
+	let io = match archive_url {
+		Some(url) => Arc::new(ArchiveFallback::<Block, C, BE>::new(
+			url,
+			Duration::from_secs(30),
+			client,
+			history_window,
+		))
+		.apply(io),
+		None => io,
+	};
+
+	let io = rpc_limits.apply(io);
+
+	let io = match slow_query_tracer {
+		Some(slow_query_tracer) => slow_query_tracer.apply(io),
+		None => io,
+	};
+
+	let io = match response_cache {
+		Some(response_cache) => response_cache.apply(io),
+		None => io,
+	};
+
+	match rate_limiter {
+		Some(rate_limiter) => rate_limiter.apply(io),
+		None => io,
+	}
 }
 
 /// Instantiate all Light RPC extensions.
@@ -0,0 +1,86 @@
+//! Maintenance of the Ethereum storage schema cache of the frontier database.
+
+use std::sync::Arc;
+
+use fc_rpc::frontier_backend_client;
+use frontier_template_runtime::opaque::Block;
+use sc_cli::{CliConfiguration, PruningParams, SharedParams};
+use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::BlakeTwo256;
+use structopt::StructOpt;
+
+/// Rebuild the Ethereum storage schema cache from the runtime storage.
+#[derive(Debug, StructOpt)]
+pub struct RebuildSchemaCacheCmd {
+	/// Only check the cache against the runtime storage, failing when they disagree.
+	#[structopt(long)]
+	pub check: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl RebuildSchemaCacheCmd {
+	/// Run the check or the rebuild.
+	pub async fn run<C, BE>(
+		&self,
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<Block>>,
+	) -> sc_cli::Result<()>
+	where
+		BE: Backend<Block> + 'static,
+		BE::State: StateBackend<BlakeTwo256>,
+		C: StorageProvider<Block, BE> + HeaderBackend<Block> + Send + Sync + 'static,
+	{
+		let cache = frontier_backend_client::load_cached_schema::<Block>(backend.as_ref())
+			.map_err(|err| err.message)?;
+		let valid = match &cache {
+			Some(cache) => frontier_backend_client::is_schema_cache_valid::<Block, C, BE>(
+				client.as_ref(),
+				cache,
+			),
+			None => false,
+		};
+
+		if self.check {
+			for (schema, hash) in cache.unwrap_or_default() {
+				log::info!("{:?} from block {:?}", schema, hash);
+			}
+			return if valid {
+				log::info!("The schema cache agrees with the runtime storage");
+				Ok(())
+			} else {
+				Err("The schema cache is missing or disagrees with the runtime storage".into())
+			};
+		}
+
+		if !valid {
+			log::warn!("The schema cache is missing or disagrees with the runtime storage");
+		}
+		let cache = frontier_backend_client::compute_schema_cache::<Block, C, BE>(client.as_ref())
+			.map_err(|err| err.message)?;
+		for (schema, hash) in &cache {
+			log::info!("{:?} from block {:?}", schema, hash);
+		}
+		frontier_backend_client::write_cached_schema::<Block>(backend.as_ref(), cache)
+			.map_err(|err| err.message)?;
+		log::info!("Rebuilt the schema cache");
+		Ok(())
+	}
+}
+
+impl CliConfiguration for RebuildSchemaCacheCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
@@ -5,29 +5,41 @@ use crate::cli::Cli;
 use crate::cli::Sealing;
 use async_trait::async_trait;
 use fc_consensus::FrontierBlockImport;
-use fc_mapping_sync::{MappingSyncWorker, SyncStrategy};
-use fc_rpc::EthTask;
-use fc_rpc_core::types::FilterPool;
+use fc_mapping_sync::{LogIndexerWorker, MappingSyncWorker, SyncStrategy};
+use fc_rpc::{
+	DevState, EthBlockDataCache, EthFork, EthKeystoreSigner, EthRemoteSigner, EthSigner, EthTask,
+	EvmMetrics, FrontierApi, FrontierPubSubApi, FrontierPubSubApiServer, HexEncodedIdProvider,
+	RateLimitConfig, RateLimiter, ResponseCache, ResponseCacheConfig, RpcLimits, RpcLimitsConfig,
+	SenderLimits, SlowQueryTracer,
+};
+use fc_rpc_core::{
+	types::{FeeHistoryCache, FilterPool},
+	FrontierApi as FrontierApiT,
+};
+use fp_rpc::EthereumRuntimeRPCApi;
 use frontier_template_runtime::{self, opaque::Block, RuntimeApi, SLOT_DURATION};
 use futures::StreamExt;
+use jsonrpc_pubsub::manager::SubscriptionManager;
 use sc_cli::SubstrateCli;
 use sc_client_api::{BlockchainEvents, ExecutorProvider, RemoteBackend};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 #[cfg(feature = "manual-seal")]
-use sc_consensus_manual_seal::{self as manual_seal};
+use sc_consensus_manual_seal::{self as manual_seal, rpc::EngineCommand};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_network::warp_request_handler::WarpSyncProvider;
 use sc_service::{error::Error as ServiceError, BasePath, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
 use sp_consensus::SlotData;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
 use sp_core::U256;
 use sp_inherents::{InherentData, InherentIdentifier};
 use std::{
-	cell::RefCell,
 	collections::{BTreeMap, HashMap},
+	net::SocketAddr,
 	sync::{Arc, Mutex},
 	time::Duration,
 };
@@ -69,23 +81,19 @@ pub type ConsensusResult = (
 );
 
 /// Provide a mock duration starting at 0 in millisecond for timestamp inherent.
-/// Each call will increment timestamp by slot_duration making Aura think time has passed.
-pub struct MockTimestampInherentDataProvider;
+/// Each call will increment timestamp by slot_duration making Aura think time has passed,
+/// unless the timestamp of the block was set or increased through the dev RPC.
+pub struct MockTimestampInherentDataProvider(pub Arc<DevState<Block>>);
 
 pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"timstap0";
 
-thread_local!(static TIMESTAMP: RefCell<u64> = RefCell::new(0));
-
 #[async_trait]
 impl sp_inherents::InherentDataProvider for MockTimestampInherentDataProvider {
 	fn provide_inherent_data(
 		&self,
 		inherent_data: &mut InherentData,
 	) -> Result<(), sp_inherents::Error> {
-		TIMESTAMP.with(|x| {
-			*x.borrow_mut() += SLOT_DURATION;
-			inherent_data.put_data(INHERENT_IDENTIFIER, &*x.borrow())
-		})
+		inherent_data.put_data(INHERENT_IDENTIFIER, &self.0.next_timestamp())
 	}
 
 	async fn try_handle_error(
@@ -98,7 +106,7 @@ impl sp_inherents::InherentDataProvider for MockTimestampInherentDataProvider {
 	}
 }
 
-pub fn frontier_database_dir(config: &Configuration) -> std::path::PathBuf {
+fn frontier_dir(config: &Configuration) -> std::path::PathBuf {
 	let config_dir = config
 		.base_path
 		.as_ref()
@@ -107,16 +115,35 @@ pub fn frontier_database_dir(config: &Configuration) -> std::path::PathBuf {
 			BasePath::from_project("", "", &crate::cli::Cli::executable_name())
 				.config_dir(config.chain_spec.id())
 		});
-	config_dir.join("frontier").join("db")
+	config_dir.join("frontier")
+}
+
+pub fn frontier_database_dir(config: &Configuration) -> std::path::PathBuf {
+	frontier_dir(config).join("db")
+}
+
+pub fn frontier_log_index_path(config: &Configuration) -> std::path::PathBuf {
+	frontier_dir(config).join("log_index.sqlite")
 }
 
-pub fn open_frontier_backend(config: &Configuration) -> Result<Arc<fc_db::Backend<Block>>, String> {
+pub fn open_frontier_backend(
+	config: &Configuration,
+	log_index: bool,
+) -> Result<Arc<fc_db::Backend<Block>>, String> {
+	if log_index {
+		std::fs::create_dir_all(frontier_dir(config)).map_err(|e| format!("{:?}", e))?;
+	}
 	Ok(Arc::new(fc_db::Backend::<Block>::new(
 		&fc_db::DatabaseSettings {
 			source: fc_db::DatabaseSettingsSrc::RocksDb {
 				path: frontier_database_dir(&config),
 				cache_size: 0,
 			},
+			log_index: if log_index {
+				Some(frontier_log_index_path(config))
+			} else {
+				None
+			},
 		},
 	)?))
 }
@@ -188,7 +215,7 @@ pub fn new_partial(
 
 	let filter_pool: Option<FilterPool> = Some(Arc::new(Mutex::new(BTreeMap::new())));
 
-	let frontier_backend = open_frontier_backend(config)?;
+	let frontier_backend = open_frontier_backend(config, cli.run.eth_log_index)?;
 
 	#[cfg(feature = "manual-seal")]
 	{
@@ -350,6 +377,8 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 
 	// Channel for the rpc handler to communicate with the authorship task.
 	let (command_sink, commands_stream) = futures::channel::mpsc::channel(1000);
+	// State shared by the dev rpc handler and the authorship task.
+	let dev_state = Arc::new(DevState::new(command_sink.clone(), SLOT_DURATION));
 
 	if config.offchain_worker.enabled {
 		sc_service::build_offchain_workers(
@@ -368,16 +397,170 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 	let prometheus_registry = config.prometheus_registry().cloned();
 	let is_authority = config.role.is_authority();
 	let enable_dev_signer = cli.run.enable_dev_signer;
+	let journal_local_transactions = cli.run.journal_local_transactions;
+	let external_rpc = cli.run.base.rpc_external
+		|| cli.run.base.unsafe_rpc_external
+		|| cli.run.base.ws_external
+		|| cli.run.base.unsafe_ws_external;
+	// The node accounts would sign for anyone reaching the public endpoints.
+	if (cli.run.eth_keystore.is_some() || cli.run.eth_remote_signer.is_some())
+		&& external_rpc
+		&& !matches!(cli.run.base.rpc_methods, sc_cli::RpcMethods::Unsafe)
+	{
+		return Err(ServiceError::Other(
+			"`--eth-keystore` or `--eth-remote-signer` with `--rpc-external` or \
+			 `--ws-external` requires `--rpc-methods=Unsafe`"
+				.to_string(),
+		));
+	}
+	let keystore_signer = match &cli.run.eth_keystore {
+		Some(path) => {
+			let password = match &cli.run.eth_keystore_password_file {
+				Some(file) => std::fs::read_to_string(file)
+					.map_err(|err| {
+						ServiceError::Other(format!(
+							"Cannot read keystore password file {}: {:?}",
+							file.display(),
+							err
+						))
+					})?
+					.trim_end_matches(&['\r', '\n'][..])
+					.to_string(),
+				None => String::new(),
+			};
+			let signer =
+				EthKeystoreSigner::from_dir(path, &password).map_err(ServiceError::Other)?;
+			log::info!(
+				"Loaded {} account(s) from the Ethereum keystore {}",
+				signer.accounts().len(),
+				path.display()
+			);
+			Some(signer)
+		}
+		None => None,
+	};
+	let remote_signer = cli.run.eth_remote_signer.clone().map(|url| {
+		EthRemoteSigner::new(
+			url,
+			cli.run.eth_remote_signer_protocol,
+			Duration::from_secs(10),
+		)
+	});
+	let fork = match &cli.run.fork_url {
+		Some(url) if cfg!(feature = "manual-seal") => {
+			let fork = Arc::new(EthFork::new(
+				url.clone(),
+				cli.run.fork_block,
+				Duration::from_secs(30),
+				dev_state.clone(),
+			));
+			// Pins the remote block now rather than on the first request.
+			let (pinned_fork, url) = (fork.clone(), url.clone());
+			task_manager.spawn_handle().spawn("eth-fork", async move {
+				match pinned_fork.block_number().await {
+					Ok(number) => log::info!("Forked {} at block {}", url, number),
+					Err(err) => log::error!("Cannot fork {}: {}", url, err.message),
+				}
+			});
+			Some(fork)
+		}
+		Some(_) => {
+			return Err(ServiceError::Other(
+				"Forking a chain requires the manual seal".to_string(),
+			))
+		}
+		None => None,
+	};
 	let subscription_task_executor =
 		sc_rpc::SubscriptionTaskExecutor::new(task_manager.spawn_handle());
 
+	if let Some(network_id) = cli.run.eth_network_id {
+		let best = BlockId::Hash(client.info().best_hash);
+		match client.runtime_api().chain_id(&best) {
+			Ok(chain_id) if chain_id != network_id => log::warn!(
+				"The Ethereum network id {} differs from the chain id {}, tools expecting \
+				 `net_version` to match `eth_chainId` may reject the node.",
+				network_id,
+				chain_id
+			),
+			Ok(_) => {}
+			Err(err) => log::warn!(
+				"Cannot check the Ethereum network id against the chain id: {:?}",
+				err
+			),
+		}
+	}
+
+	let overrides = crate::rpc::overrides_handle(client.clone());
+	let fee_history_cache: FeeHistoryCache = Arc::new(Mutex::new(BTreeMap::new()));
+
+	let rpc_limits_config = RpcLimitsConfig {
+		max_batch_len: cli.run.eth_rpc_max_batch_len,
+		max_request_size: cli.run.eth_rpc_max_request_size * 1024 * 1024,
+		max_response_size: cli.run.eth_rpc_max_response_size * 1024 * 1024,
+	};
+	// The Substrate RPC servers take no middleware, they refuse the requests larger than the
+	// request size limit before parsing them, which also bounds their batches.
+	if config.rpc_max_payload.is_none() {
+		config.rpc_max_payload = Some(cli.run.eth_rpc_max_request_size);
+	}
 	let rpc_extensions_builder = {
+		let rpc_limits_config = rpc_limits_config.clone();
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 		let network = network.clone();
+		let system_rpc_tx = system_rpc_tx.clone();
+		let client_name = format!("{}/v{}", config.impl_name, config.impl_version);
 		let filter_pool = filter_pool.clone();
 		let frontier_backend = frontier_backend.clone();
+		let substrate_backend = backend.clone();
 		let max_past_logs = cli.run.max_past_logs;
+		let estimate_gas_error_ratio = cli.run.estimate_gas_error_ratio;
+		let allow_unprotected_txs = cli.run.rpc_allow_unprotected_txs;
+		let spawn_handle = task_manager.spawn_handle();
+		let ethapi = cli.run.ethapi.clone();
+		let overrides = overrides.clone();
+		let fee_history_cache = fee_history_cache.clone();
+		let dev_state = if cfg!(feature = "manual-seal") && cli.run.base.shared_params.dev {
+			Some(dev_state.clone())
+		} else {
+			None
+		};
+		let rate_limiter = if cli.run.rpc_rate_limit.is_some()
+			|| cli.run.rpc_rate_limit_per_connection.is_some()
+		{
+			Some(Arc::new(RateLimiter::new(RateLimitConfig {
+				global_rate: cli.run.rpc_rate_limit,
+				connection_rate: cli.run.rpc_rate_limit_per_connection,
+				method_costs: cli.run.rpc_method_cost.iter().cloned().collect(),
+			})))
+		} else {
+			None
+		};
+		let rpc_limits = Arc::new(RpcLimits::new(rpc_limits_config.clone()));
+		let slow_query_tracer = cli
+			.run
+			.rpc_slow_query_threshold
+			.map(|ms| Arc::new(SlowQueryTracer::new(Duration::from_millis(ms))));
+		let response_cache = cli.run.rpc_response_cache.map(|max_entries| {
+			Arc::new(ResponseCache::new::<Block, _>(
+				client.clone(),
+				ResponseCacheConfig {
+					max_entries,
+					method_ttls: cli
+						.run
+						.rpc_response_cache_ttl
+						.iter()
+						.map(|(method, ms)| (method.clone(), Duration::from_millis(*ms)))
+						.collect(),
+				},
+			))
+		});
+		let sender_limits = SenderLimits {
+			pending: cli.run.eth_pool_account_slots,
+			queued: cli.run.eth_pool_account_queue,
+			queued_transactions: Default::default(),
+		};
 
 		Box::new(move |deny_unsafe, _| {
 			let deps = crate::rpc::FullDeps {
@@ -387,11 +570,35 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 				deny_unsafe,
 				is_authority,
 				enable_dev_signer,
+				keystore_signer: keystore_signer.clone(),
+				remote_signer: remote_signer.clone(),
+				journal_local_transactions,
+				max_sync_lag: cli.run.max_sync_lag,
 				network: network.clone(),
+				system_rpc_tx: system_rpc_tx.clone(),
+				client_name: client_name.clone(),
 				filter_pool: filter_pool.clone(),
 				backend: frontier_backend.clone(),
+				substrate_backend: substrate_backend.clone(),
 				max_past_logs,
+				estimate_gas_error_ratio,
+				allow_unprotected_txs,
+				history_window: cli.run.eth_history_window,
+				archive_url: cli.run.archive_fallback_url.clone(),
+				coinbase: cli.run.eth_coinbase,
+				sender_limits: sender_limits.clone(),
+				network_id: cli.run.eth_network_id,
+				spawn_handle: spawn_handle.clone(),
+				ethapi: ethapi.clone(),
+				rpc_limits: rpc_limits.clone(),
+				rate_limiter: rate_limiter.clone(),
+				slow_query_tracer: slow_query_tracer.clone(),
+				response_cache: response_cache.clone(),
+				overrides: overrides.clone(),
+				fee_history_cache: fee_history_cache.clone(),
 				command_sink: Some(command_sink.clone()),
+				dev_state: dev_state.clone(),
+				fork: fork.clone(),
 			};
 
 			Ok(crate::rpc::create_full(
@@ -401,6 +608,43 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 		})
 	};
 
+	if let Some(port) = cli.run.graphql_port {
+		let io = rpc_extensions_builder(
+			sc_rpc::DenyUnsafe::Yes,
+			sc_rpc::SubscriptionTaskExecutor::new(task_manager.spawn_handle()),
+		)?;
+		task_manager.spawn_handle().spawn(
+			"frontier-graphql",
+			fc_graphql::run_server(
+				SocketAddr::from(([127, 0, 0, 1], port)),
+				fc_graphql::schema(Arc::new(io)),
+			),
+		);
+	}
+
+	if let Some(port) = cli.run.eth_rpc_port {
+		let io = rpc_extensions_builder(
+			sc_rpc::DenyUnsafe::Yes,
+			sc_rpc::SubscriptionTaskExecutor::new(task_manager.spawn_handle()),
+		)?;
+		// The Substrate RPC server takes no other middleware, so the batch length is only
+		// limited on this server.
+		let mut limited =
+			jsonrpc_core::MetaIoHandler::with_middleware(RpcLimits::new(rpc_limits_config.clone()));
+		limited.extend_with(
+			io.iter()
+				.map(|(name, procedure)| (name.clone(), procedure.clone())),
+		);
+		let server = jsonrpc_http_server::ServerBuilder::new(limited)
+			.max_request_body_size(rpc_limits_config.max_request_size)
+			.start_http(&SocketAddr::from(([127, 0, 0, 1], port)))
+			.map_err(|e| ServiceError::Other(format!("Failed to start Ethereum RPC: {}", e)))?;
+		task_manager
+			.spawn_handle()
+			.spawn_blocking("frontier-eth-rpc", async move { server.wait() });
+	}
+
+	let rpc_ipc = config.rpc_ipc.clone();
 	let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		network: network.clone(),
 		client: client.clone(),
@@ -416,6 +660,16 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	// The IPC server of `--ipc-path` serves the unsafe methods, the socket is restricted to the
+	// user running the node.
+	#[cfg(unix)]
+	if let Some(path) = &rpc_ipc {
+		use std::os::unix::fs::PermissionsExt;
+		std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).map_err(|e| {
+			ServiceError::Other(format!("Failed to restrict the IPC socket {}: {}", path, e))
+		})?;
+	}
+
 	task_manager.spawn_essential_handle().spawn(
 		"frontier-mapping-sync-worker",
 		MappingSyncWorker::new(
@@ -425,10 +679,28 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 			backend.clone(),
 			frontier_backend.clone(),
 			SyncStrategy::Normal,
+			match cli.run.tx_index_history {
+				0 => None,
+				blocks => Some(blocks),
+			},
 		)
 		.for_each(|()| futures::future::ready(())),
 	);
 
+	if frontier_backend.log_index().is_some() {
+		task_manager.spawn_essential_handle().spawn(
+			"frontier-log-indexer",
+			LogIndexerWorker::new(
+				client.import_notification_stream(),
+				Duration::new(6, 0),
+				client.clone(),
+				frontier_backend.clone(),
+				cli.run.eth_history_window,
+			)
+			.for_each(|()| futures::future::ready(())),
+		);
+	}
+
 	// Spawn Frontier EthFilterApi maintenance task.
 	if let Some(filter_pool) = filter_pool {
 		// Each filter is allowed to stay in the pool for 100 blocks.
@@ -439,6 +711,110 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 		);
 	}
 
+	if let Some(port) = cli.run.readiness_port {
+		let mut io = jsonrpc_core::IoHandler::default();
+		let frontier_api: FrontierApi<_, _, FullBackend> = FrontierApi::new(
+			client.clone(),
+			frontier_backend.clone(),
+			cli.run.max_sync_lag,
+			None,
+			Arc::new(EthBlockDataCache::new(1, 1)),
+			fee_history_cache.clone(),
+			None,
+			None,
+			overrides.clone(),
+			cli.run.max_past_logs,
+			cli.run.eth_history_window,
+		);
+		// The probe port may be made reachable from outside the node, so it serves
+		// `frontier_ready` alone. It answers 200 when it succeeds, 503 otherwise.
+		io.add_sync_method("frontier_ready", move |_| {
+			FrontierApiT::ready(&frontier_api).map(jsonrpc_core::Value::Bool)
+		});
+		let interface = if cli.run.readiness_external {
+			[0, 0, 0, 0]
+		} else {
+			[127, 0, 0, 1]
+		};
+		let server = jsonrpc_http_server::ServerBuilder::new(io)
+			.health_api(("/ready", "frontier_ready"))
+			.start_http(&std::net::SocketAddr::from((interface, port)))
+			.map_err(|e| ServiceError::Other(format!("Failed to start readiness probe: {}", e)))?;
+		task_manager
+			.spawn_handle()
+			.spawn_blocking("frontier-readiness-probe", async move { server.wait() });
+	}
+
+	if let Some(port) = cli.run.chain_head_ws_port {
+		let mut io = jsonrpc_core::MetaIoHandler::<sc_rpc::Metadata>::default();
+		let subscriptions = SubscriptionManager::<HexEncodedIdProvider>::with_id_provider(
+			HexEncodedIdProvider::default(),
+			Arc::new(sc_rpc::SubscriptionTaskExecutor::new(
+				task_manager.spawn_handle(),
+			)),
+		);
+		let frontier_pubsub: FrontierPubSubApi<_, _, FullBackend> = FrontierPubSubApi::new(
+			client.clone(),
+			frontier_backend.clone(),
+			cli.run.max_sync_lag,
+			subscriptions,
+			overrides.clone(),
+		)
+		.chain_head_only();
+		io.extend_with(FrontierPubSubApiServer::to_delegate(frontier_pubsub));
+		// Only the `chainHead` subscriptions of `frontier_subscribe` are served, on the local
+		// interface unless the WebSocket RPC is external.
+		let interface = if cli.run.base.ws_external || cli.run.base.unsafe_ws_external {
+			[0, 0, 0, 0]
+		} else {
+			[127, 0, 0, 1]
+		};
+		let server = jsonrpc_ws_server::ServerBuilder::with_meta_extractor(
+			io,
+			|context: &jsonrpc_ws_server::RequestContext| context.sender().into(),
+		)
+		.start(&std::net::SocketAddr::from((interface, port)))
+		.map_err(|e| ServiceError::Other(format!("Failed to start chain head server: {}", e)))?;
+		task_manager
+			.spawn_handle()
+			.spawn_blocking("frontier-chain-head", async move {
+				let _ = server.wait();
+			});
+	}
+
+	if journal_local_transactions {
+		task_manager.spawn_handle().spawn(
+			"frontier-local-transactions",
+			EthTask::local_transactions_task(
+				Arc::clone(&client),
+				transaction_pool.clone(),
+				Arc::clone(&frontier_backend),
+				overrides.clone(),
+				frontier_template_runtime::TransactionConverter,
+			),
+		);
+	}
+
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-fee-history",
+		EthTask::fee_history_task(
+			Arc::clone(&client),
+			overrides.clone(),
+			fee_history_cache,
+			cli.run.fee_history_limit,
+		),
+	);
+
+	if let Some(registry) = prometheus_registry.as_ref() {
+		match EvmMetrics::register(registry) {
+			Ok(metrics) => task_manager.spawn_handle().spawn(
+				"frontier-evm-metrics",
+				EthTask::evm_metrics_task(Arc::clone(&client), Arc::clone(&overrides), metrics),
+			),
+			Err(err) => log::warn!("Failed to register the EVM metrics: {:?}", err),
+		}
+	}
+
 	task_manager.spawn_essential_handle().spawn(
 		"frontier-schema-cache-task",
 		EthTask::ethereum_schema_cache_task(Arc::clone(&client), Arc::clone(&frontier_backend)),
@@ -459,58 +835,56 @@ pub fn new_full(mut config: Configuration, cli: &Cli) -> Result<TaskManager, Ser
 
 			let target_gas_price = cli.run.target_gas_price;
 
-			// Background authorship future
-			match sealing {
-				Sealing::Manual => {
-					let authorship_future =
-						manual_seal::run_manual_seal(manual_seal::ManualSealParams {
-							block_import,
-							env,
-							client,
-							pool: transaction_pool.clone(),
-							commands_stream,
-							select_chain,
-							consensus_data_provider: None,
-							create_inherent_data_providers: move |_, ()| async move {
-								let mock_timestamp = MockTimestampInherentDataProvider;
-
-								let dynamic_fee = pallet_dynamic_fee::InherentDataProvider(
-									U256::from(target_gas_price),
-								);
-
-								Ok((mock_timestamp, dynamic_fee))
-							},
-						});
-					// we spawn the future on a background thread managed by service.
-					task_manager
-						.spawn_essential_handle()
-						.spawn_blocking("manual-seal", authorship_future);
-				}
-				Sealing::Instant => {
-					let authorship_future =
-						manual_seal::run_instant_seal(manual_seal::InstantSealParams {
-							block_import,
-							env,
-							client: client.clone(),
-							pool: transaction_pool.clone(),
-							select_chain,
-							consensus_data_provider: None,
-							create_inherent_data_providers: move |_, ()| async move {
-								let mock_timestamp = MockTimestampInherentDataProvider;
-
-								let dynamic_fee = pallet_dynamic_fee::InherentDataProvider(
-									U256::from(target_gas_price),
-								);
-
-								Ok((mock_timestamp, dynamic_fee))
-							},
-						});
-					// we spawn the future on a background thread managed by service.
-					task_manager
-						.spawn_essential_handle()
-						.spawn_blocking("instant-seal", authorship_future);
-				}
+			// Background authorship future, instant seal also seals a block on each imported
+			// transaction.
+			let commands_stream: Box<
+				dyn futures::Stream<Item = EngineCommand<frontier_template_runtime::Hash>>
+					+ Send
+					+ Unpin,
+			> = match sealing {
+				Sealing::Manual => Box::new(commands_stream),
+				Sealing::Instant => Box::new(futures::stream::select(
+					commands_stream,
+					transaction_pool
+						.pool()
+						.validated_pool()
+						.import_notification_stream()
+						.map(|_| EngineCommand::SealNewBlock {
+							create_empty: false,
+							finalize: false,
+							parent_hash: None,
+							sender: None,
+						}),
+				)),
 			};
+
+			let authorship_future = manual_seal::run_manual_seal(manual_seal::ManualSealParams {
+				block_import,
+				env,
+				client,
+				pool: transaction_pool.clone(),
+				commands_stream,
+				select_chain,
+				consensus_data_provider: None,
+				create_inherent_data_providers: move |_, ()| {
+					let dev_state = dev_state.clone();
+					async move {
+						let mock_timestamp = MockTimestampInherentDataProvider(dev_state.clone());
+
+						let dynamic_fee =
+							pallet_dynamic_fee::InherentDataProvider(U256::from(target_gas_price));
+
+						let dev_commands =
+							pallet_evm_dev::InherentDataProvider(dev_state.take_commands());
+
+						Ok((mock_timestamp, dynamic_fee, dev_commands))
+					}
+				},
+			});
+			// we spawn the future on a background thread managed by service.
+			task_manager
+				.spawn_essential_handle()
+				.spawn_blocking("manual-seal", authorship_future);
 		}
 		log::info!("Manual Seal Ready");
 	}
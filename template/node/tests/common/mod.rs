@@ -0,0 +1,19 @@
+//! Starts the template node in the test process.
+
+use fc_test_harness::{FrontierNode, NodeConfig};
+use frontier_template_node::{cli::Cli, service};
+use sc_cli::SubstrateCli;
+use structopt::StructOpt;
+
+/// Starts the template node dev chain, which requires the `manual-seal` feature.
+pub async fn spawn_node(config: NodeConfig) -> FrontierNode {
+	FrontierNode::spawn(config, |args| {
+		let cli = Cli::from_iter_safe(args).map_err(|err| err.to_string())?;
+		let config = cli
+			.create_configuration(&cli.run.base, tokio::runtime::Handle::current())
+			.map_err(|err| err.to_string())?;
+		service::new_full(config, &cli).map_err(|err| err.to_string())
+	})
+	.await
+	.unwrap()
+}
@@ -0,0 +1,44 @@
+//! Runs the template node in the test process, with the `manual-seal` feature.
+#![cfg(feature = "manual-seal")]
+
+mod common;
+
+use common::spawn_node;
+use ethers::prelude::{Address, Bytes, Middleware, Signer, U256};
+use fc_test_harness::{assert_receipt_success, NodeConfig};
+
+/// Contract whose constructor returns the runtime code `PUSH1 0x2a PUSH1 0 MSTORE PUSH1 32
+/// PUSH1 0 RETURN`, answering 42 to any call.
+const ANSWER_CONTRACT: &str = "600a600c600039600a6000f3602a60005260206000f3";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer_and_deploy() {
+	let node = spawn_node(NodeConfig::default()).await;
+
+	let recipient = Address::from_low_u64_be(0x1234);
+	let receipt = node.transfer(recipient, U256::from(1000)).await.unwrap();
+	assert_receipt_success(&receipt);
+	assert_eq!(
+		node.provider().get_balance(recipient, None).await.unwrap(),
+		U256::from(1000)
+	);
+
+	let contract = node
+		.deploy(Bytes::from(
+			ethers::utils::hex::decode(ANSWER_CONTRACT).unwrap(),
+		))
+		.await
+		.unwrap();
+	let answer = node
+		.provider()
+		.call(
+			&ethers::prelude::TransactionRequest::new()
+				.from(node.dev_wallet().address())
+				.to(contract)
+				.into(),
+			None,
+		)
+		.await
+		.unwrap();
+	assert_eq!(U256::from_big_endian(&answer), U256::from(42));
+}
@@ -0,0 +1,59 @@
+//! Runs the template node in the test process, with the `manual-seal` feature. Skipped when
+//! the specification given by `EXECUTION_APIS_SPEC` is missing.
+#![cfg(feature = "manual-seal")]
+
+mod common;
+
+use common::spawn_node;
+use ethers::prelude::{Address, Signer, U256};
+use fc_rpc_conformance::{cases, Compliance, Fixture, Specification, SPEC_ENV};
+use fc_test_harness::{assert_receipt_success, NodeConfig};
+use serde_json::{json, Value};
+use std::{env, path::PathBuf};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn responses_match_the_specification() {
+	let spec = match env::var(SPEC_ENV) {
+		Ok(path) => Specification::load(&PathBuf::from(path)).unwrap(),
+		Err(_) => {
+			println!("{} is not set, skipping", SPEC_ENV);
+			return;
+		}
+	};
+	let node = spawn_node(NodeConfig::default()).await;
+
+	let recipient = Address::from_low_u64_be(0x1111);
+	let receipt = node.transfer(recipient, U256::from(0x200)).await.unwrap();
+	assert_receipt_success(&receipt);
+	let fixture = Fixture {
+		account: json!(node.dev_wallet().address()),
+		recipient: json!(recipient),
+		transaction_hash: json!(receipt.transaction_hash),
+		block_hash: json!(receipt.block_hash),
+	};
+
+	let mut compliance = Compliance::default();
+	let mut failures = Vec::new();
+	for case in cases(&fixture) {
+		let outcome = node
+			.provider()
+			.request::<_, Value>(case.method, &case.params)
+			.await
+			.map_err(|err| err.to_string())
+			.and_then(|result| {
+				spec.validate(case.method, &result)?;
+				case.check.map_or(Ok(()), |check| check(&result))
+			});
+		compliance.record(case.method, outcome.is_ok());
+		if let Err(err) = outcome {
+			failures.push(format!("{} {}: {}", case.method, case.params, err));
+		}
+	}
+
+	println!("{}", compliance);
+	assert!(
+		compliance.is_compliant(),
+		"non compliant responses:\n{}",
+		failures.join("\n")
+	);
+}
@@ -25,7 +25,7 @@ use sp_runtime::{
 		PostDispatchInfoOf, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity, TransactionValidityError},
-	ApplyExtrinsicResult, MultiSignature,
+	ApplyExtrinsicResult, FixedU128, MultiSignature,
 };
 use sp_std::{marker::PhantomData, prelude::*};
 #[cfg(feature = "std")]
@@ -45,7 +45,10 @@ pub use frame_support::{
 };
 pub use pallet_balances::Call as BalancesCall;
 use pallet_ethereum::{Call::transact, Transaction as EthereumTransaction};
-use pallet_evm::{Account as EVMAccount, EnsureAddressTruncated, HashedAddressMapping, Runner};
+use pallet_evm::{
+	Account as EVMAccount, AddressMapping, BenchmarkedPrecompile, EnsureAddressTruncated,
+	HashedAddressMapping, PrecompileSet, PrecompileWeightInfo, Runner,
+};
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::CurrencyAdapter;
 #[cfg(any(feature = "std", test))]
@@ -291,9 +294,23 @@ impl<F: FindAuthor<u32>> FindAuthor<H160> for FindAuthorTruncated<F> {
 	}
 }
 
+/// Weights of the `Sha3FIPS256` precompile, to measure with the `sha3_fips_256` benchmark of
+/// `pallet_evm`. The template maps gas to weight one to one, these keep the former `BASE` and
+/// `WORD` gas costs until regenerated.
+pub struct Sha3FIPS256Weights;
+impl PrecompileWeightInfo for Sha3FIPS256Weights {
+	fn base() -> Weight {
+		60
+	}
+	fn per_word() -> Weight {
+		12
+	}
+}
+
 parameter_types! {
 	pub const ChainId: u64 = 42;
 	pub BlockGasLimit: U256 = U256::from(u32::max_value());
+	pub const CountPrecompileCalls: bool = true;
 }
 
 impl pallet_evm::Config for Runtime {
@@ -313,50 +330,94 @@ impl pallet_evm::Config for Runtime {
 		pallet_evm_precompile_simple::Identity,
 		pallet_evm_precompile_modexp::Modexp,
 		pallet_evm_precompile_simple::ECRecoverPublicKey,
-		pallet_evm_precompile_sha3fips::Sha3FIPS256,
+		BenchmarkedPrecompile<pallet_evm_precompile_sha3fips::Sha3FIPS256, Sha3FIPS256Weights, ()>,
 		pallet_evm_precompile_sha3fips::Sha3FIPS512,
 	);
 	type ChainId = ChainId;
-	type BlockGasLimit = BlockGasLimit;
-	type OnChargeTransaction = ();
+	type BlockGasLimit = pallet_ethereum::EthereumBlockGasLimit<Self>;
+	type OnChargeTransaction = pallet_evm::sponsor::EVMSponsoredAdapter<()>;
 	type FindAuthor = FindAuthorTruncated<Aura>;
+	type CountPrecompileCalls = CountPrecompileCalls;
+}
+
+frame_support::parameter_types! {
+	pub PoolMinGasPrice: U256 = U256::zero();
+	pub const CompactReceipts: bool = false;
+	pub const BlockMetrics: bool = true;
+	pub const RecordInternalTransfers: bool = false;
+	pub const MaxInternalTransfers: u32 = 10_000;
+	pub const IndexTransactionsByHash: bool = false;
+	pub GasLimitBoundDivisor: Option<U256> = Some(U256::from(1024));
 }
 
 impl pallet_ethereum::Config for Runtime {
 	type Event = Event;
 	type StateRoot = pallet_ethereum::IntermediateStateRoot;
+	type HeaderFields = pallet_ethereum::EvmAuthorHeaderFields<Self>;
+	type PoolMinGasPrice = PoolMinGasPrice;
+	type CompactReceipts = CompactReceipts;
+	type GasLimitOrigin = frame_system::EnsureRoot<AccountId>;
+	type DefaultBlockGasLimit = BlockGasLimit;
+	type GasLimitBoundDivisor = GasLimitBoundDivisor;
+	type BlockMetrics = BlockMetrics;
+	type RecordInternalTransfers = RecordInternalTransfers;
+	type MaxInternalTransfers = MaxInternalTransfers;
+	type IndexTransactionsByHash = IndexTransactionsByHash;
+	type ExtraValidation = ();
 }
 
 frame_support::parameter_types! {
 	pub BoundDivision: U256 = U256::from(1024);
+	pub MinGasPriceLowerBound: U256 = U256::zero();
+	pub MinGasPriceUpperBound: U256 = U256::max_value();
 }
 
 impl pallet_dynamic_fee::Config for Runtime {
+	type Event = Event;
 	type MinGasPriceBoundDivisor = BoundDivision;
+	type MinGasPriceLowerBound = MinGasPriceLowerBound;
+	type MinGasPriceUpperBound = MinGasPriceUpperBound;
+	type BlockFullness = ();
 }
 
+#[cfg(feature = "manual-seal")]
+impl pallet_evm_dev::Config for Runtime {}
+
 impl pallet_randomness_collective_flip::Config for Runtime {}
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
-construct_runtime!(
-	pub enum Runtime where
-		Block = Block,
-		NodeBlock = opaque::Block,
-		UncheckedExtrinsic = UncheckedExtrinsic
-	{
-		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
-		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
-		Aura: pallet_aura::{Pallet, Config<T>},
-		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event},
-		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
-		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
-		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
-		Ethereum: pallet_ethereum::{Pallet, Call, Storage, Event, Config, Origin},
-		EVM: pallet_evm::{Pallet, Config, Call, Storage, Event<T>},
-		DynamicFee: pallet_dynamic_fee::{Pallet, Call, Storage, Config, Inherent},
-	}
-);
+//
+// The dev pallet applies unchecked state changes, it is only part of the runtime built for the
+// manual seal development node.
+macro_rules! runtime {
+	($($dev_pallets:tt)*) => {
+		construct_runtime!(
+			pub enum Runtime where
+				Block = Block,
+				NodeBlock = opaque::Block,
+				UncheckedExtrinsic = UncheckedExtrinsic
+			{
+				System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+				RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+				Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+				Aura: pallet_aura::{Pallet, Config<T>},
+				Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event},
+				Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+				TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
+				Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
+				Ethereum: pallet_ethereum::{Pallet, Call, Storage, Event, Config, Origin},
+				EVM: pallet_evm::{Pallet, Config, Call, Storage, Event<T>},
+				DynamicFee: pallet_dynamic_fee::{Pallet, Call, Storage, Config, Inherent, Event},
+				$($dev_pallets)*
+			}
+		);
+	};
+}
+
+#[cfg(feature = "manual-seal")]
+runtime!(EVMDev: pallet_evm_dev::{Pallet, Call, Storage, Config, Inherent},);
+#[cfg(not(feature = "manual-seal"))]
+runtime!();
 
 pub struct TransactionConverter;
 
@@ -570,9 +631,11 @@ impl_runtime_apis! {
 			data: Vec<u8>,
 			value: U256,
 			gas_limit: U256,
-			gas_price: Option<U256>,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
 			nonce: Option<U256>,
 			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
 		) -> Result<pallet_evm::CallInfo, sp_runtime::DispatchError> {
 			let config = if estimate {
 				let mut config = <Runtime as pallet_evm::Config>::config().clone();
@@ -581,17 +644,23 @@ impl_runtime_apis! {
 			} else {
 				None
 			};
+			let evm_config = config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config());
+			let access_list_gas =
+				EVM::access_list_gas(evm_config, &access_list.unwrap_or_default())?;
 
 			<Runtime as pallet_evm::Config>::Runner::call(
 				from,
 				to,
 				data,
 				value,
-				gas_limit.low_u64(),
-				gas_price,
+				gas_limit.low_u64().saturating_sub(access_list_gas),
+				EVM::effective_gas_price(max_fee_per_gas, max_priority_fee_per_gas),
 				nonce,
-				config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
-			).map_err(|err| err.into())
+				evm_config,
+			).map(|mut info| {
+				info.used_gas = info.used_gas.saturating_add(access_list_gas.into());
+				info
+			}).map_err(|err| err.into())
 		}
 
 		fn create(
@@ -599,9 +668,11 @@ impl_runtime_apis! {
 			data: Vec<u8>,
 			value: U256,
 			gas_limit: U256,
-			gas_price: Option<U256>,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
 			nonce: Option<U256>,
 			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
 		) -> Result<pallet_evm::CreateInfo, sp_runtime::DispatchError> {
 			let config = if estimate {
 				let mut config = <Runtime as pallet_evm::Config>::config().clone();
@@ -610,16 +681,22 @@ impl_runtime_apis! {
 			} else {
 				None
 			};
+			let evm_config = config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config());
+			let access_list_gas =
+				EVM::access_list_gas(evm_config, &access_list.unwrap_or_default())?;
 
 			<Runtime as pallet_evm::Config>::Runner::create(
 				from,
 				data,
 				value,
-				gas_limit.low_u64(),
-				gas_price,
+				gas_limit.low_u64().saturating_sub(access_list_gas),
+				EVM::effective_gas_price(max_fee_per_gas, max_priority_fee_per_gas),
 				nonce,
-				config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
-			).map_err(|err| err.into())
+				evm_config,
+			).map(|mut info| {
+				info.used_gas = info.used_gas.saturating_add(access_list_gas.into());
+				info
+			}).map_err(|err| err.into())
 		}
 
 		fn current_transaction_statuses() -> Option<Vec<TransactionStatus>> {
@@ -654,6 +731,144 @@ impl_runtime_apis! {
 				_ => None
 			}).collect::<Vec<EthereumTransaction>>()
 		}
+
+		fn pending_removals() -> Vec<(H160, u32)> {
+			EVM::pending_removals()
+		}
+
+		fn transaction_status_by_hash(hash: H256) -> Option<fp_rpc::IndexedTransactionStatus> {
+			Ethereum::transaction_status_by_hash(hash)
+		}
+
+		fn evm_fork() -> Vec<u8> {
+			EVM::evm_fork().as_bytes().to_vec()
+		}
+
+		fn chain_config() -> fp_rpc::ChainConfig {
+			let config = <Runtime as pallet_evm::Config>::config();
+			fp_rpc::ChainConfig {
+				chain_id: <Runtime as pallet_evm::Config>::ChainId::get(),
+				evm_fork: EVM::evm_fork().as_bytes().to_vec(),
+				evm_features: EVM::evm_features()
+					.into_iter()
+					.map(|feature| feature.as_bytes().to_vec())
+					.collect(),
+				create_contract_limit: config.create_contract_limit.map(|limit| limit as u64),
+				block_gas_limit: <Runtime as pallet_evm::Config>::BlockGasLimit::get(),
+				min_gas_price: <Runtime as pallet_evm::Config>::FeeCalculator::min_gas_price(),
+				min_gas_price_bounds: Some((
+					DynamicFee::bound_divisor(),
+					DynamicFee::lower_bound(),
+					DynamicFee::upper_bound(),
+				)),
+				precompiles: <Runtime as pallet_evm::Config>::Precompiles::addresses(),
+			}
+		}
+
+		fn pending_block(
+			xts: Vec<<Block as BlockT>::Extrinsic>,
+		) -> (
+			Option<pallet_ethereum::Block>,
+			Option<Vec<pallet_ethereum::Receipt>>,
+			Option<Vec<TransactionStatus>>
+		) {
+			use frame_support::traits::OnFinalize;
+
+			// The block is built on a discarded overlay of the current state, only the Ethereum
+			// block is finalized.
+			Executive::initialize_block(&Header {
+				parent_hash: Default::default(),
+				number: System::block_number() + 1,
+				state_root: Default::default(),
+				extrinsics_root: Default::default(),
+				digest: Default::default(),
+			});
+			for ext in xts.into_iter() {
+				let _ = Executive::apply_extrinsic(ext);
+			}
+			Ethereum::on_finalize(System::block_number());
+
+			(
+				Ethereum::current_block(),
+				Ethereum::current_receipts(),
+				Ethereum::current_transaction_statuses()
+			)
+		}
+
+		fn block_author(header: <Block as BlockT>::Header) -> H160 {
+			EVM::find_author_of(&header.digest)
+		}
+
+		fn precompile_calls() -> Vec<(H160, u32)> {
+			EVM::precompile_calls()
+		}
+
+		fn london_block() -> Option<U256> {
+			DynamicFee::london_block().map(U256::from)
+		}
+
+		fn fee_token_rate(_from: H160, _to: Option<H160>) -> Option<FixedU128> {
+			// The template charges the EVM fees in the native currency only.
+			None
+		}
+
+		fn fee_sponsors() -> Vec<(H160, U256, H160)> {
+			EVM::fee_sponsors()
+		}
+
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<pallet_evm::CallTrace, sp_runtime::DispatchError> {
+			EVM::trace_call(
+				from,
+				to,
+				data,
+				value,
+				gas_limit,
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+				nonce,
+				access_list.unwrap_or_default(),
+			)
+		}
+
+		fn internal_transfers() -> Vec<fp_rpc::InternalTransfer> {
+			Ethereum::internal_transfers()
+		}
+
+		fn accounts(addresses: Vec<H160>) -> Vec<fp_rpc::AccountSummary> {
+			addresses
+				.into_iter()
+				.map(|address| {
+					let account = EVM::account_basic(&address);
+					let code = EVM::account_code_metadata(address);
+					fp_rpc::AccountSummary {
+						balance: account.balance,
+						nonce: account.nonce,
+						code_hash: code.hash,
+						code_size: code.size,
+					}
+				})
+				.collect()
+		}
+
+		fn address_to_account(address: H160) -> Vec<u8> {
+			<Runtime as pallet_evm::Config>::AddressMapping::into_account_id(address).encode()
+		}
+
+		fn account_to_address(account: Vec<u8>) -> Option<H160> {
+			// The withdraw origin is `EnsureAddressTruncated`.
+			let account = AccountId::decode(&mut &account[..]).ok()?;
+			Some(H160::from_slice(&AsRef::<[u8; 32]>::as_ref(&account)[0..20]))
+		}
 	}
 
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
@@ -28,4 +28,30 @@ describeWithFrontier("Frontier RPC (Balance)", (context) => {
 		expect(await context.web3.eth.getBalance(GENESIS_ACCOUNT)).to.equal("340282366920938463463374607431768189443");
 		expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal("12");
 	});
+
+	step("balance history samples the balance at each block", async function () {
+		const history = await customRequest(context.web3, "frontier_getBalanceHistory", [
+			TEST_ACCOUNT,
+			"earliest",
+			"latest",
+			"0x1",
+		]);
+		expect(history.result).to.deep.equal([
+			{ blockNumber: "0x0", value: "0x0" },
+			{ blockNumber: "0x1", value: "0xc" },
+		]);
+	});
+
+	step("accounts are queried in a single call", async function () {
+		const accounts = await customRequest(context.web3, "frontier_getAccounts", [[TEST_ACCOUNT], "latest"]);
+		expect(accounts.result).to.deep.equal([
+			{
+				address: TEST_ACCOUNT,
+				balance: "0xc",
+				nonce: "0x0",
+				codeHash: "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+				codeSize: "0x0",
+			},
+		]);
+	});
 });
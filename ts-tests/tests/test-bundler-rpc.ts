@@ -0,0 +1,45 @@
+import { expect } from "chai";
+
+import { customRequest, describeWithFrontier } from "./util";
+
+describeWithFrontier("Frontier RPC (Bundler)", (context) => {
+	const GENESIS_ACCOUNT = "0x6be02d1d3665660d22ff9624b7be0551ee1ac91b";
+	const TEST_ACCOUNT = "0x1111111111111111111111111111111111111111";
+
+	it("eth_createAccessList leaves out the sender and the target", async function () {
+		const result = await customRequest(context.web3, "eth_createAccessList", [
+			{ from: GENESIS_ACCOUNT, to: TEST_ACCOUNT, value: "0x1" },
+			"latest",
+		]);
+		expect(result.error).to.be.undefined;
+		expect(result.result.accessList).to.deep.equal([]);
+		expect(result.result.gasUsed).to.equal("0x5208");
+	});
+
+	it("debug_traceCall returns the bundler collector trace", async function () {
+		const result = await customRequest(context.web3, "debug_traceCall", [
+			{ from: GENESIS_ACCOUNT, to: TEST_ACCOUNT, data: "0x" },
+			"latest",
+			{ tracer: "bundlerCollectorTracer" },
+		]);
+		expect(result.error).to.be.undefined;
+		expect(result.result).to.deep.equal({
+			callsFromEntryPoint: [],
+			keccak: [],
+			logs: [],
+			calls: [],
+			debug: [],
+		});
+	});
+
+	it("debug_traceCall rejects the other tracers", async function () {
+		const result = await customRequest(context.web3, "debug_traceCall", [
+			{ from: GENESIS_ACCOUNT, to: TEST_ACCOUNT, data: "0x" },
+			"latest",
+			{ tracer: "callTracer" },
+		]);
+		expect(result.error.message).to.equal(
+			"unsupported tracer callTracer, only bundlerCollectorTracer is supported"
+		);
+	});
+});
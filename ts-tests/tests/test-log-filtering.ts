@@ -87,4 +87,28 @@ describeWithFrontier("Frontier RPC (Log filtering)", (context) => {
 			expect(request.result.length).to.be.eq(0);
 		}
 	});
+
+	step("FrontierApi::getLogs - should page the logs with a cursor.", async function () {
+		await sendTransaction(context);
+		await createAndFinalizeBlock(context.web3);
+		await sendTransaction(context);
+		await createAndFinalizeBlock(context.web3);
+
+		const filter = { "fromBlock": "0x0", "toBlock": "latest" };
+		const all = await customRequest(context.web3, "eth_getLogs", [filter]);
+
+		let logs = [];
+		let cursor = null;
+		do {
+			const page = (await customRequest(context.web3, "frontier_getLogs", [filter, cursor, 1])).result;
+			expect(page.logs.length).to.be.at.most(1);
+			logs = logs.concat(page.logs);
+			cursor = page.next;
+		} while (cursor);
+
+		expect(logs.length).to.be.eq(all.result.length);
+		expect(logs.map((log) => log.transactionHash).sort()).to.deep.eq(
+			all.result.map((log) => log.transactionHash).sort()
+		);
+	});
 });
@@ -1,6 +1,6 @@
 import { expect } from "chai";
 
-import { describeWithFrontier } from "./util";
+import { customRequest, describeWithFrontier } from "./util";
 
 // All test for the RPC
 
@@ -23,4 +23,13 @@ describeWithFrontier("Frontier RPC (Constant)", (context) => {
 		// as we are running manual sealing consensus.
 		expect(await context.web3.eth.getCoinbase()).to.equal("0x0000000000000000000000000000000000000000");
 	});
+
+	it("should report the chain config", async function () {
+		const config = (await customRequest(context.web3, "eth_chainConfig", [])).result;
+		expect(config.chainId).to.equal("0x2a");
+		expect(config.evmFork).to.equal("istanbul");
+		expect(config.evmFeatures).to.include("chainId");
+		expect(config.precompiles).to.have.lengthOf(8);
+		expect(config.precompiles[0]).to.equal("0x0000000000000000000000000000000000000001");
+	});
 });
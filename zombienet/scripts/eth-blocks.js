@@ -0,0 +1,33 @@
+// Checks that the nodes given as argument return the same Ethereum blocks as the node
+// running the script, for every block up to its finalized block.
+const { connect, finalizedNumber } = require("./eth");
+
+async function run(nodeName, networkInfo, args) {
+	const node = await connect(networkInfo, nodeName);
+	const others = await Promise.all(args.map((name) => connect(networkInfo, name)));
+
+	const finalized = await finalizedNumber(node);
+	for (let number = 0; number <= finalized; number++) {
+		const tag = "0x" + number.toString(16);
+		const block = await node.send("eth_getBlockByNumber", [tag, false]);
+		if (!block) {
+			console.log(`Missing Ethereum block ${number} on ${nodeName}`);
+			return 0;
+		}
+		for (let i = 0; i < others.length; i++) {
+			const other = await others[i].send("eth_getBlockByNumber", [tag, false]);
+			if (!other || other.hash !== block.hash || other.stateRoot !== block.stateRoot) {
+				console.log(`Ethereum block ${number} differs between ${nodeName} and ${args[i]}`);
+				return 0;
+			}
+			const byHash = await others[i].send("eth_getBlockByHash", [block.hash, false]);
+			if (!byHash || byHash.number !== block.number) {
+				console.log(`Ethereum block ${block.hash} is not indexed by ${args[i]}`);
+				return 0;
+			}
+		}
+	}
+	return 1;
+}
+
+module.exports = { run };
@@ -0,0 +1,25 @@
+// Waits until the best block of the node running the script is the given number of blocks
+// ahead of its finalized block, and checks that the Ethereum RPC follows the best block
+// rather than the finalized one.
+const { connect, finalizedNumber, sleep } = require("./eth");
+
+async function run(nodeName, networkInfo, args) {
+	const node = await connect(networkInfo, nodeName);
+	const lag = parseInt(args[0], 10);
+
+	for (;;) {
+		const header = await node.api.rpc.chain.getHeader();
+		const best = header.number.toNumber();
+		const finalized = await finalizedNumber(node);
+		if (best - finalized >= lag) {
+			const latest = await node.send("eth_getBlockByNumber", ["latest", false]);
+			const number = await node.send("eth_blockNumber");
+			return latest && parseInt(latest.number, 16) >= best && parseInt(number, 16) >= best
+				? 1
+				: 0;
+		}
+		await sleep(1000);
+	}
+}
+
+module.exports = { run };
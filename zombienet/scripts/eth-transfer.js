@@ -0,0 +1,47 @@
+// Sends a transfer through the node running the script and waits until the node given as
+// argument returns the same receipt. The transaction is signed with web3 from the
+// functional tests dependencies (`cd ts-tests && npm install`).
+const Web3 = require("../../ts-tests/node_modules/web3");
+const { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY, connect, sleep } = require("./eth");
+
+async function run(nodeName, networkInfo, args) {
+	const node = await connect(networkInfo, nodeName);
+	const other = await connect(networkInfo, args[0]);
+
+	const chainId = await node.send("eth_chainId");
+	const nonce = await node.send("eth_getTransactionCount", [GENESIS_ACCOUNT, "pending"]);
+	const recipient = Web3.utils.randomHex(20);
+	const web3 = new Web3();
+	const tx = await web3.eth.accounts.signTransaction(
+		{
+			from: GENESIS_ACCOUNT,
+			to: recipient,
+			value: "0x200",
+			gasPrice: "0x01",
+			gas: "0x100000",
+			nonce,
+			chainId,
+		},
+		GENESIS_ACCOUNT_PRIVATE_KEY
+	);
+	const hash = await node.send("eth_sendRawTransaction", [tx.rawTransaction]);
+
+	for (;;) {
+		const [receipt, otherReceipt] = await Promise.all([
+			node.send("eth_getTransactionReceipt", [hash]),
+			other.send("eth_getTransactionReceipt", [hash]),
+		]);
+		if (receipt && otherReceipt) {
+			const balance = await other.send("eth_getBalance", [recipient, otherReceipt.blockNumber]);
+			const agree =
+				receipt.status === "0x1" &&
+				receipt.blockHash === otherReceipt.blockHash &&
+				receipt.transactionIndex === otherReceipt.transactionIndex &&
+				BigInt(balance) === 0x200n;
+			return agree ? 1 : 0;
+		}
+		await sleep(1000);
+	}
+}
+
+module.exports = { run };
@@ -0,0 +1,35 @@
+// Helpers shared by the zombienet scripts, which run in the zombienet process with its
+// `zombie` global.
+
+// Development account prefunded in the EVM genesis of the template chains.
+const GENESIS_ACCOUNT = "0x6be02d1d3665660d22ff9624b7be0551ee1ac91b";
+const GENESIS_ACCOUNT_PRIVATE_KEY = "0x99B3C12287537E38C90A9219D4CB074A89A16E9CDB20BF85728EBD97C343E342";
+
+// Connects to a node of the network, returning a function sending raw JSON-RPC requests.
+async function connect(networkInfo, nodeName) {
+	const { wsUri, userDefinedTypes } = networkInfo.nodesByName[nodeName];
+	const api = await zombie.connect(wsUri, userDefinedTypes);
+	return {
+		api,
+		send: (method, params = []) => api._rpcCore.provider.send(method, params),
+	};
+}
+
+// Number of the finalized block of a node.
+async function finalizedNumber(node) {
+	const hash = await node.api.rpc.chain.getFinalizedHead();
+	const header = await node.api.rpc.chain.getHeader(hash);
+	return header.number.toNumber();
+}
+
+function sleep(ms) {
+	return new Promise((resolve) => setTimeout(resolve, ms));
+}
+
+module.exports = {
+	GENESIS_ACCOUNT,
+	GENESIS_ACCOUNT_PRIVATE_KEY,
+	connect,
+	finalizedNumber,
+	sleep,
+};